@@ -0,0 +1,59 @@
+//! Benchmarks of the per-update overhead of `ItemUpdate`: building an update, cloning it, as done
+//! to store and deliver it, and looking up its values by name and by position.
+//!
+//! Run with `cargo bench --bench item_update`.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use lightstreamer_client::item_update::ItemUpdate;
+use std::hint::black_box;
+
+/// Fields of the stock-list demo, a realistic market-data schema.
+const FIELDS: [&str; 12] = [
+    "stock_name",
+    "last_price",
+    "time",
+    "pct_change",
+    "bid_quantity",
+    "bid",
+    "ask",
+    "ask_quantity",
+    "min",
+    "max",
+    "ref_price",
+    "open_price",
+];
+
+fn market_data_update() -> ItemUpdate {
+    ItemUpdate::new(
+        Some("item2"),
+        2,
+        FIELDS.map(|field| (field, Some("12.5"))),
+        false,
+    )
+}
+
+fn item_update(c: &mut Criterion) {
+    let mut group = c.benchmark_group("item_update");
+    group.bench_function("new", |b| b.iter(market_data_update));
+    let update = market_data_update();
+    group.bench_function("clone", |b| b.iter(|| black_box(&update).clone()));
+    group.bench_function("get_value", |b| {
+        b.iter(|| {
+            FIELDS
+                .iter()
+                .filter_map(|field| black_box(&update).get_value(field))
+                .count()
+        })
+    });
+    group.bench_function("get_value_by_position", |b| {
+        b.iter(|| {
+            (1..=FIELDS.len())
+                .filter_map(|pos| black_box(&update).get_value_by_position(pos))
+                .count()
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, item_update);
+criterion_main!(benches);
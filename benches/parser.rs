@@ -0,0 +1,86 @@
+//! Benchmarks of the hot path of the TLCP parser: splitting the frames received from the Server
+//! into notifications, parsing update notifications and decoding their field values.
+//!
+//! Run with `cargo bench --bench parser`.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use lightstreamer_client::item_update::FieldValue;
+use lightstreamer_client::protocol::{decode_update_values, parse_update, split_frame};
+use lightstreamer_client::util::clean_message;
+use std::hint::black_box;
+
+/// Number of update notifications in the benchmarked frame.
+const UPDATES: usize = 1000;
+
+/// Number of fields of the benchmarked updates, as in the stock-list demo.
+const FIELDS: usize = 12;
+
+/// Builds a frame of MERGE updates of a market-data schema, where most fields change with each
+/// update and some are sent as unchanged.
+fn market_data_frame() -> String {
+    (0..UPDATES)
+        .map(|i| {
+            format!(
+                "U,1,{},Anduct|{}.{:02}|10:3{}:0{}|||3.{}|{}|{}.50|{}.75|^2|#\r\n",
+                i % 30 + 1,
+                i,
+                i % 100,
+                i % 10,
+                i % 10,
+                i % 10,
+                i * 1000,
+                i,
+                i + 1,
+            )
+        })
+        .collect()
+}
+
+fn parser(c: &mut Criterion) {
+    let frame = market_data_frame();
+    let mut group = c.benchmark_group("parser");
+    group.throughput(Throughput::Elements(UPDATES as u64));
+
+    group.bench_function("clean_message", |b| {
+        b.iter(|| clean_message(black_box(&frame)))
+    });
+    group.bench_function("split_frame", |b| {
+        b.iter(|| split_frame(black_box(frame.clone())).count())
+    });
+    group.bench_function("parse_update", |b| {
+        let lines: Vec<&str> = frame.lines().collect();
+        b.iter(|| {
+            lines
+                .iter()
+                .map(|line| parse_update(black_box(line)).item_index)
+                .sum::<usize>()
+        })
+    });
+    group.bench_function("decode_update_values", |b| {
+        let lines: Vec<&str> = frame.lines().collect();
+        b.iter(|| {
+            for line in &lines {
+                let mut changes = vec![FieldValue::Unchanged; FIELDS];
+                decode_update_values(parse_update(black_box(line)).values, &[], &mut changes);
+                black_box(changes);
+            }
+        })
+    });
+    group.bench_function("frame_to_changes", |b| {
+        b.iter(|| {
+            for notification in split_frame(black_box(frame.clone())) {
+                let mut changes = vec![FieldValue::Unchanged; FIELDS];
+                decode_update_values(
+                    parse_update(notification.as_str()).values,
+                    &[],
+                    &mut changes,
+                );
+                black_box(changes);
+            }
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, parser);
+criterion_main!(benches);
@@ -8,9 +8,7 @@ use signal_hook::low_level::signal_name;
 use signal_hook::{consts::SIGINT, consts::SIGTERM, iterator::Signals};
 use std::error::Error;
 use std::sync::Arc;
-use tokio::sync::{Mutex, Notify};
-
-const MAX_CONNECTION_ATTEMPTS: u64 = 1;
+use tokio::sync::Notify;
 
 /// Sets up a signal hook for SIGINT and SIGTERM.
 ///
@@ -31,12 +29,12 @@ async fn setup_signal_hook(shutdown_signal: Arc<Notify>) {
     let signals = &[SIGINT, SIGTERM];
     let mut signals_iterator = Signals::new(signals).expect("Failed to create signal iterator");
 
-    // Create a new thread to handle signals sent to the process
-    tokio::spawn(async move {
-        for signal in signals_iterator.forever() {
+    // Create a new thread to handle signals sent to the process. A dedicated OS thread is used
+    // because the signal iterator blocks, and it must not starve the runtime worker threads.
+    std::thread::spawn(move || {
+        if let Some(signal) = signals_iterator.forever().next() {
             println!("Received signal: {}", signal_name(signal).unwrap());
-            let _ = shutdown_signal.notify_one();
-            break;
+            shutdown_signal.notify_one();
         }
     });
 }
@@ -63,8 +61,8 @@ impl SubscriptionListener for MySubscriptionListener {
         ];
         let mut output = String::new();
         for field in fields {
-            let value = update.get_value(field).unwrap_or(&not_available).clone();
-            let value_str = if update.changed_fields.contains_key(field) {
+            let value = update.get_value(field).unwrap_or(&not_available);
+            let value_str = if update.is_value_changed(field) {
                 value.yellow().to_string()
             } else {
                 value.to_string()
@@ -82,31 +80,23 @@ async fn main() -> Result<(), Box<dyn Error>> {
     //
     let mut my_subscription = Subscription::new(
         SubscriptionMode::Merge,
-        Some(vec![
-            "item1".to_string(),
-            "item2".to_string(),
-            "item3".to_string(),
-            "item4".to_string(),
-            "item5".to_string(),
-            "item6".to_string(),
-            "item7".to_string(),
-            "item8".to_string(),
-            "item9".to_string(),
-            "item10".to_string(),
+        Some([
+            "item1", "item2", "item3", "item4", "item5", "item6", "item7", "item8", "item9",
+            "item10",
         ]),
-        Some(vec![
-            "stock_name".to_string(),
-            "last_price".to_string(),
-            "time".to_string(),
-            "pct_change".to_string(),
-            "bid_quantity".to_string(),
-            "bid".to_string(),
-            "ask".to_string(),
-            "ask_quantity".to_string(),
-            "min".to_string(),
-            "max".to_string(),
-            "ref_price".to_string(),
-            "open_price".to_string(),
+        Some([
+            "stock_name",
+            "last_price",
+            "time",
+            "pct_change",
+            "bid_quantity",
+            "bid",
+            "ask",
+            "ask_quantity",
+            "min",
+            "max",
+            "ref_price",
+            "open_price",
         ]),
     )?;
 
@@ -114,24 +104,18 @@ async fn main() -> Result<(), Box<dyn Error>> {
     my_subscription.set_requested_snapshot(Some(Snapshot::Yes))?;
     my_subscription.add_listener(Box::new(MySubscriptionListener {}));
 
-    // Create a new Lightstreamer client instance and wrap it in an Arc<Mutex<>> so it can be shared across threads.
-    let client = Arc::new(Mutex::new(LightstreamerClient::new(
+    // Create a new Lightstreamer client instance. All the configuration happens before connecting;
+    // afterwards, the client only needs a shared reference, so it could also be put in an Arc and
+    // used from several tasks without any lock.
+    let mut client = LightstreamerClient::new(
         Some("http://push.lightstreamer.com/lightstreamer"),
         Some("DEMO"),
         None,
         None,
-    )?));
-
-    //
-    // Add the subscription to the client.
-    //
-    {
-        let mut client = client.lock().await;
-        client.subscribe(my_subscription);
-        client
-            .connection_options
-            .set_forced_transport(Some(Transport::WsStreaming));
-    }
+    )?;
+    client
+        .connection_options
+        .set_forced_transport(Some(Transport::WsStreaming));
 
     // Create a new Notify instance to send a shutdown signal to the signal handler thread.
     let shutdown_signal = Arc::new(tokio::sync::Notify::new());
@@ -139,39 +123,19 @@ async fn main() -> Result<(), Box<dyn Error>> {
     setup_signal_hook(Arc::clone(&shutdown_signal)).await;
 
     //
-    // Infinite loop that will indefinitely retry failed connections unless
-    // a SIGTERM or SIGINT signal is received.
+    // Add the subscription to the client and start the session in the background. connect()
+    // returns as soon as the session task has been started.
     //
-    let mut retry_interval_milis: u64 = 0;
-    let mut retry_counter: u64 = 0;
-    while retry_counter < MAX_CONNECTION_ATTEMPTS {
-        let mut client = client.lock().await;
-        match client.connect(Arc::clone(&shutdown_signal)).await {
-            Ok(_) => {
-                client.disconnect().await;
-                break;
-            }
-            Err(e) => {
-                println!("Failed to connect: {:?}", e);
-                tokio::time::sleep(std::time::Duration::from_millis(retry_interval_milis)).await;
-                retry_interval_milis = (retry_interval_milis + (200 * retry_counter)).min(5000);
-                retry_counter += 1;
-                println!(
-                    "Retrying connection in {} seconds...",
-                    format!("{:.2}", retry_interval_milis as f64 / 1000.0)
-                );
-            }
-        }
-    }
+    client.subscribe(my_subscription);
+    client.connect().await?;
 
-    if retry_counter == MAX_CONNECTION_ATTEMPTS {
-        println!(
-            "Failed to connect after {} retries. Exiting...",
-            retry_counter
-        );
-    } else {
-        println!("Exiting orderly from Lightstreamer client...");
-    }
+    //
+    // Wait until a SIGTERM or SIGINT signal is received and close the session.
+    //
+    shutdown_signal.notified().await;
+    client.disconnect().await;
+
+    println!("Exiting orderly from Lightstreamer client...");
 
     // Exit using std::process::exit() to avoid waiting for existing tokio tasks to complete.
     std::process::exit(0);
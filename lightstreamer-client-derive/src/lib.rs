@@ -0,0 +1,144 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Fields, GenericArgument, LitStr, PathArguments, Type,
+};
+
+/// Derives `lightstreamer_client::item_update::LightstreamerFields` for a struct with named
+/// fields, so that an `ItemUpdate` can be mapped into it with `from_item_update()`.
+///
+/// Every struct field is read from the Lightstreamer field with the same name, unless renamed
+/// with `#[lightstreamer(rename = "...")]`, and parsed through `FromStr`. Fields declared as
+/// `Option<T>` are set to `None` when the Lightstreamer field has no value (null or not received
+/// yet); any other field makes the mapping fail in that case.
+///
+/// # Examples
+///
+/// ```ignore
+/// use lightstreamer_client::LightstreamerFields;
+///
+/// #[derive(LightstreamerFields)]
+/// struct Quote {
+///     stock_name: String,
+///     #[lightstreamer(rename = "last_price")]
+///     price: f64,
+///     bid: Option<f64>,
+/// }
+///
+/// let quote = Quote::from_item_update(&update)?;
+/// ```
+#[proc_macro_derive(LightstreamerFields, attributes(lightstreamer))]
+pub fn derive_lightstreamer_fields(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    match expand(&input) {
+        Ok(tokens) => tokens.into(),
+        Err(err) => err.to_compile_error().into(),
+    }
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    input,
+                    "LightstreamerFields can only be derived for structs with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                input,
+                "LightstreamerFields can only be derived for structs",
+            ))
+        }
+    };
+
+    let mut initializers = Vec::with_capacity(fields.len());
+    for field in fields {
+        let ident = field.ident.as_ref().expect("named field");
+        let field_name = field_name(field)?.unwrap_or_else(|| ident.to_string());
+        let initializer = match option_inner_type(&field.ty) {
+            Some(inner_type) => quote! {
+                #ident: ::lightstreamer_client::item_update::parse_field::<#inner_type>(
+                    update,
+                    #field_name,
+                )?
+            },
+            None => {
+                let field_type = &field.ty;
+                quote! {
+                    #ident: ::lightstreamer_client::item_update::parse_field::<#field_type>(
+                        update,
+                        #field_name,
+                    )?
+                    .ok_or_else(|| {
+                        ::lightstreamer_client::error::IllegalArgumentException::new(
+                            &format!("Field '{}' has no value", #field_name),
+                        )
+                    })?
+                }
+            }
+        };
+        initializers.push(initializer);
+    }
+
+    let name = &input.ident;
+    let (impl_generics, type_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::lightstreamer_client::item_update::LightstreamerFields
+            for #name #type_generics #where_clause
+        {
+            fn from_item_update(
+                update: &::lightstreamer_client::item_update::ItemUpdate,
+            ) -> ::std::result::Result<Self, ::lightstreamer_client::error::IllegalArgumentException>
+            {
+                ::std::result::Result::Ok(Self {
+                    #(#initializers,)*
+                })
+            }
+        }
+    })
+}
+
+/// Returns the Lightstreamer field name set through `#[lightstreamer(rename = "...")]`, if any.
+fn field_name(field: &syn::Field) -> syn::Result<Option<String>> {
+    let mut name = None;
+    for attr in &field.attrs {
+        if !attr.path().is_ident("lightstreamer") {
+            continue;
+        }
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("rename") {
+                let value: LitStr = meta.value()?.parse()?;
+                name = Some(value.value());
+                Ok(())
+            } else {
+                Err(meta.error("unsupported lightstreamer attribute, expected `rename`"))
+            }
+        })?;
+    }
+    Ok(name)
+}
+
+/// Returns `T` if the given type is `Option<T>`.
+fn option_inner_type(ty: &Type) -> Option<&Type> {
+    let Type::Path(type_path) = ty else {
+        return None;
+    };
+    let segment = type_path.path.segments.last()?;
+    if segment.ident != "Option" {
+        return None;
+    }
+    match &segment.arguments {
+        PathArguments::AngleBracketed(arguments) if arguments.args.len() == 1 => {
+            match arguments.args.first()? {
+                GenericArgument::Type(inner_type) => Some(inner_type),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
@@ -0,0 +1,203 @@
+use lightstreamer_client::client_listener::ClientListener;
+use lightstreamer_client::client_message_listener::ClientMessageListener;
+use lightstreamer_client::item_update::ItemUpdate;
+use lightstreamer_client::ls_client::{LightstreamerClient, LogType, Transport};
+use lightstreamer_client::subscription::{Snapshot, Subscription, SubscriptionMode};
+use lightstreamer_client::subscription_listener::SubscriptionListener;
+
+use clap::{Parser, ValueEnum};
+use serde_json::json;
+use std::error::Error;
+
+/// Command line tool to connect to a Lightstreamer Server, subscribe to items and print the
+/// received updates as JSON lines. Useful to debug Data and Metadata Adapters without writing code.
+#[derive(Debug, Parser)]
+#[command(name = "ls-cli", version)]
+struct Args {
+    /// Address of the Lightstreamer Server.
+    #[arg(
+        short,
+        long,
+        default_value = "http://push.lightstreamer.com/lightstreamer"
+    )]
+    server: String,
+
+    /// Adapter Set to be used for the session.
+    #[arg(short, long, default_value = "DEMO")]
+    adapter_set: String,
+
+    /// User name used for authentication.
+    #[arg(short, long)]
+    user: Option<String>,
+
+    /// Password used for authentication.
+    #[arg(short, long)]
+    password: Option<String>,
+
+    /// Data Adapter supplying the items.
+    #[arg(short, long)]
+    data_adapter: Option<String>,
+
+    /// Subscription mode.
+    #[arg(short, long, value_enum, default_value_t = Mode::Merge)]
+    mode: Mode,
+
+    /// Items to subscribe to, comma separated.
+    #[arg(short, long, value_delimiter = ',')]
+    items: Vec<String>,
+
+    /// Fields to subscribe to, comma separated.
+    #[arg(short, long, value_delimiter = ',')]
+    fields: Vec<String>,
+
+    /// Request the snapshot of the items.
+    #[arg(long)]
+    snapshot: bool,
+
+    /// Message to send to the Metadata Adapter once connected. Can be repeated.
+    #[arg(long = "send", value_name = "MESSAGE")]
+    messages: Vec<String>,
+
+    /// Sequence of the messages to send.
+    #[arg(long)]
+    sequence: Option<String>,
+}
+
+/// Subscription modes selectable from the command line.
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum Mode {
+    Merge,
+    Distinct,
+    Raw,
+    Command,
+}
+
+impl From<Mode> for SubscriptionMode {
+    fn from(mode: Mode) -> Self {
+        match mode {
+            Mode::Merge => SubscriptionMode::Merge,
+            Mode::Distinct => SubscriptionMode::Distinct,
+            Mode::Raw => SubscriptionMode::Raw,
+            Mode::Command => SubscriptionMode::Command,
+        }
+    }
+}
+
+/// Prints status changes and server errors on standard error.
+#[derive(Debug)]
+struct StatusPrinter;
+
+impl ClientListener for StatusPrinter {
+    fn on_server_error(&self, code: i32, message: &str) {
+        eprintln!("Server error {}: {}", code, message);
+    }
+
+    fn on_status_change(&self, status: &str) {
+        eprintln!("Status: {}", status);
+    }
+}
+
+/// Prints every item update as a JSON line on standard output.
+struct UpdatePrinter;
+
+impl SubscriptionListener for UpdatePrinter {
+    fn on_item_update(&self, update: &ItemUpdate) {
+        match serde_json::to_string(update) {
+            Ok(line) => println!("{}", line),
+            Err(err) => eprintln!("Failed to serialize update: {}", err),
+        }
+    }
+
+    fn on_subscription(&mut self) {
+        eprintln!("Subscribed");
+    }
+
+    fn on_subscription_error(&mut self, code: i32, message: Option<&str>) {
+        eprintln!("Subscription error {}: {}", code, message.unwrap_or(""));
+    }
+}
+
+/// Prints the outcome of a sent message as a JSON line on standard output.
+#[derive(Debug)]
+struct MessagePrinter;
+
+impl ClientMessageListener for MessagePrinter {
+    fn on_abort(&self, msg: &str, sent_on_network: bool) {
+        println!(
+            "{}",
+            json!({ "message": msg, "outcome": "aborted", "sent_on_network": sent_on_network })
+        );
+    }
+
+    fn on_deny(&self, msg: &str, code: i32, error: &str) {
+        println!(
+            "{}",
+            json!({ "message": msg, "outcome": "denied", "code": code, "error": error })
+        );
+    }
+
+    fn on_discarded(&self, msg: &str) {
+        println!("{}", json!({ "message": msg, "outcome": "discarded" }));
+    }
+
+    fn on_error(&self, msg: &str) {
+        println!("{}", json!({ "message": msg, "outcome": "error" }));
+    }
+
+    fn on_processed(&self, msg: &str, response: Option<&str>) {
+        println!(
+            "{}",
+            json!({ "message": msg, "outcome": "processed", "response": response })
+        );
+    }
+}
+
+#[tokio::main]
+async fn main() -> Result<(), Box<dyn Error>> {
+    let args = Args::parse();
+
+    let mut client = LightstreamerClient::new(
+        Some(&args.server),
+        Some(&args.adapter_set),
+        args.user.as_deref(),
+        args.password.as_deref(),
+    )?;
+    // Standard output is reserved to JSON lines: route the client logs to tracing, which is
+    // silent unless a subscriber is installed.
+    client.set_logging_type(LogType::TracingLogs);
+    client
+        .connection_options
+        .set_forced_transport(Some(Transport::WsStreaming));
+    client.add_listener(Box::new(StatusPrinter));
+
+    if !args.items.is_empty() || !args.fields.is_empty() {
+        let mut subscription = Subscription::new(
+            args.mode.into(),
+            Some(args.items.clone()),
+            Some(args.fields.clone()),
+        )?;
+        subscription.set_data_adapter(args.data_adapter.clone())?;
+        if args.snapshot {
+            subscription.set_requested_snapshot(Some(Snapshot::Yes))?;
+        }
+        subscription.add_listener(Box::new(UpdatePrinter));
+        client.subscribe(subscription);
+    }
+
+    client.connect().await?;
+
+    for message in &args.messages {
+        client.send_message(
+            message,
+            args.sequence.as_deref(),
+            None,
+            Some(Box::new(MessagePrinter)),
+            true,
+        )?;
+    }
+
+    tokio::signal::ctrl_c().await?;
+    client.disconnect().await;
+
+    Ok(())
+}
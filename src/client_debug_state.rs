@@ -0,0 +1,104 @@
+use crate::ls_client::{ClientStatus, ConnectionType};
+use crate::subscription::SubscriptionMode;
+
+use std::fmt::{self, Display, Formatter};
+use std::time::Duration;
+
+/// Report on the internal state of a `LightstreamerClient`, obtained through
+/// `LightstreamerClient.debugState()`.
+///
+/// It's meant to be attached to bug reports or exposed by admin endpoints: its `Display`
+/// implementation renders it as a human readable multi-line text, while its fields can be
+/// inspected programmatically.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientDebugState {
+    /// The status of the client.
+    pub status: ClientStatus,
+    /// The configured server address.
+    pub server_address: Option<String>,
+    /// The ID of the current server session, if any.
+    pub session_id: Option<String>,
+    /// The transport of the active connection, if any.
+    pub transport: Option<ConnectionType>,
+    /// Number of messages queued through `LightstreamerClient.sendMessage()` and not sent yet.
+    pub queued_messages: usize,
+    /// Number of messages sent and still waiting for their outcome.
+    pub messages_awaiting_outcome: usize,
+    /// Number of subscription changes queued and not sent yet.
+    pub queued_subscription_changes: usize,
+    /// Number of requests sent on the current connection and not acknowledged yet.
+    pub unacknowledged_requests: usize,
+    /// State of each subscription of the client, in the order they were given to it.
+    pub subscriptions: Vec<SubscriptionDebugState>,
+}
+
+/// State of a single subscription within a `ClientDebugState` report.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubscriptionDebugState {
+    /// The subscription mode.
+    pub mode: SubscriptionMode,
+    /// The "Item List" or "Item Group" of the subscription.
+    pub items: String,
+    /// The "Field List" or "Field Schema" of the subscription.
+    pub fields: String,
+    /// Whether the subscription is active, i.e. given to the client through `subscribe()`.
+    pub active: bool,
+    /// The ID the subscription has in the current server session, if it was subscribed to.
+    pub subscription_id: Option<usize>,
+    /// Number of updates received for the subscription.
+    pub updates_received: u64,
+    /// Time elapsed since the last update received for the subscription, if any.
+    pub since_last_update: Option<Duration>,
+}
+
+impl Display for ClientDebugState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        writeln!(f, "status: {}", self.status)?;
+        writeln!(
+            f,
+            "server address: {}",
+            self.server_address.as_deref().unwrap_or("-")
+        )?;
+        writeln!(f, "session: {}", self.session_id.as_deref().unwrap_or("-"))?;
+        match &self.transport {
+            Some(transport) => writeln!(f, "transport: {}", transport)?,
+            None => writeln!(f, "transport: -")?,
+        }
+        writeln!(
+            f,
+            "messages: {} queued, {} awaiting outcome",
+            self.queued_messages, self.messages_awaiting_outcome
+        )?;
+        writeln!(
+            f,
+            "requests: {} queued subscription changes, {} unacknowledged",
+            self.queued_subscription_changes, self.unacknowledged_requests
+        )?;
+        writeln!(f, "subscriptions: {}", self.subscriptions.len())?;
+        for (index, subscription) in self.subscriptions.iter().enumerate() {
+            writeln!(f, "  #{} {}", index, subscription)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for SubscriptionDebugState {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} [{}] [{}] {}",
+            self.mode,
+            self.items,
+            self.fields,
+            if self.active { "active" } else { "inactive" }
+        )?;
+        if let Some(subscription_id) = self.subscription_id {
+            write!(f, ", subId {}", subscription_id)?;
+        }
+        write!(f, ", {} updates", self.updates_received)?;
+        if let Some(since_last_update) = self.since_last_update {
+            write!(f, ", last {} ms ago", since_last_update.as_millis())?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,288 @@
+use crate::client_message_listener::ClientMessageListener;
+use crate::client_metrics::{ClientMetrics, MetricsRecorder};
+use crate::error::{IllegalArgumentException, IllegalStateException};
+use crate::ls_client::{ClientStatus, LightstreamerClient, Transport};
+use crate::subscription::{Subscription, SubscriptionToken};
+
+use futures::channel::oneshot;
+use std::error::Error;
+use std::fmt::{self, Debug, Formatter};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::{UnboundedReceiver, UnboundedSender};
+
+/// Operation requested through a `ClientHandle`, carried out by the engine task.
+pub(crate) enum ClientCommand {
+    Connect(oneshot::Sender<Result<(), String>>),
+    Disconnect(oneshot::Sender<()>),
+    DisconnectAndWait(oneshot::Sender<()>),
+    Subscribe(Box<Subscription>, oneshot::Sender<SubscriptionToken>),
+    Unsubscribe(
+        SubscriptionToken,
+        oneshot::Sender<Result<(), IllegalArgumentException>>,
+    ),
+    SetKeepaliveInterval(u64),
+    SetForcedTransport(Option<Transport>),
+    SendMessage {
+        message: String,
+        sequence: Option<String>,
+        delay_timeout: Option<u64>,
+        listener: Option<Box<dyn ClientMessageListener>>,
+        enqueue_while_disconnected: bool,
+        reply: oneshot::Sender<Result<(), IllegalStateException>>,
+    },
+}
+
+/// Runs a `LightstreamerClient` on behalf of its handles: commands are carried out one at a time,
+/// in the order they were issued. When all the handles are dropped, the client is disconnected and
+/// the engine terminates.
+pub(crate) async fn run_engine(
+    mut client: LightstreamerClient,
+    mut commands: UnboundedReceiver<ClientCommand>,
+) {
+    while let Some(command) = commands.recv().await {
+        match command {
+            ClientCommand::Connect(reply) => {
+                let result = client.connect().await.map_err(|err| err.to_string());
+                let _ = reply.send(result);
+            }
+            ClientCommand::Disconnect(reply) => {
+                client.disconnect().await;
+                let _ = reply.send(());
+            }
+            ClientCommand::DisconnectAndWait(reply) => {
+                client.disconnect_and_wait().await;
+                let _ = reply.send(());
+            }
+            ClientCommand::Subscribe(subscription, reply) => {
+                let _ = reply.send(client.subscribe(*subscription));
+            }
+            ClientCommand::Unsubscribe(token, reply) => {
+                let _ = reply.send(client.unsubscribe(token));
+            }
+            ClientCommand::SetKeepaliveInterval(keepalive_interval) => {
+                // Any keepalive interval is accepted.
+                let _ = client
+                    .connection_options
+                    .set_keepalive_interval(keepalive_interval);
+            }
+            ClientCommand::SetForcedTransport(forced_transport) => {
+                client
+                    .connection_options
+                    .set_forced_transport(forced_transport);
+            }
+            ClientCommand::SendMessage {
+                message,
+                sequence,
+                delay_timeout,
+                listener,
+                enqueue_while_disconnected,
+                reply,
+            } => {
+                let result = client.send_message(
+                    &message,
+                    sequence.as_deref(),
+                    delay_timeout,
+                    listener,
+                    enqueue_while_disconnected,
+                );
+                let _ = reply.send(result);
+            }
+        }
+    }
+    client.disconnect().await;
+}
+
+/// Cheap, clonable handle to a `LightstreamerClient` running in a background engine task, as
+/// returned by `LightstreamerClient.spawn()`.
+///
+/// Any number of tasks can drive the same session through their own clones of the handle, without
+/// sharing the client behind a lock: operations are forwarded to the engine over a channel and
+/// carried out in the order they were issued, while the status and the metrics are read directly.
+///
+/// The engine disconnects the client and terminates when the last handle is dropped.
+#[derive(Clone)]
+pub struct ClientHandle {
+    commands: UnboundedSender<ClientCommand>,
+    status: Arc<Mutex<ClientStatus>>,
+    metrics: Arc<MetricsRecorder>,
+}
+
+impl ClientHandle {
+    pub(crate) fn new(
+        commands: UnboundedSender<ClientCommand>,
+        status: Arc<Mutex<ClientStatus>>,
+        metrics: Arc<MetricsRecorder>,
+    ) -> ClientHandle {
+        ClientHandle {
+            commands,
+            status,
+            metrics,
+        }
+    }
+
+    /// Operation method that requests to open a Session against the configured Lightstreamer
+    /// Server. See `LightstreamerClient.connect()` for details.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalStateException`: if no server address was configured, the client is already
+    ///   connected or connecting, or the engine has terminated.
+    pub async fn connect(&self) -> Result<(), Box<dyn Error>> {
+        let (reply, outcome) = oneshot::channel();
+        self.send(ClientCommand::Connect(reply))?;
+        match outcome.await {
+            Ok(result) => result.map_err(|err| err.into()),
+            Err(_) => Err(Box::new(Self::engine_terminated())),
+        }
+    }
+
+    /// Operation method that requests to close the Session opened against the configured
+    /// Lightstreamer Server, if any, and waits for it to be closed. See
+    /// `LightstreamerClient.disconnect()` for details.
+    ///
+    /// If the engine has terminated, the client is already disconnected and nothing is done.
+    pub async fn disconnect(&self) {
+        let (reply, done) = oneshot::channel();
+        if self.send(ClientCommand::Disconnect(reply)).is_ok() {
+            let _ = done.await;
+        }
+    }
+
+    /// Operation method that closes the Session like `disconnect()` and waits until the tasks of
+    /// the client have terminated, except for the engine task, which terminates when the last
+    /// handle is dropped. See `LightstreamerClient.disconnectAndWait()` for details.
+    ///
+    /// If the engine has terminated, the client is already disconnected and nothing is done.
+    pub async fn disconnect_and_wait(&self) {
+        let (reply, done) = oneshot::channel();
+        if self.send(ClientCommand::DisconnectAndWait(reply)).is_ok() {
+            let _ = done.await;
+        }
+    }
+
+    /// Operation method that adds a `Subscription` to the list of "active" Subscriptions. See
+    /// `LightstreamerClient.subscribe()` for details.
+    ///
+    /// # Returns
+    ///
+    /// The token identifying the `Subscription`, to be passed to `unsubscribe()`.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalStateException`: if the engine has terminated.
+    pub async fn subscribe(
+        &self,
+        subscription: Subscription,
+    ) -> Result<SubscriptionToken, IllegalStateException> {
+        let (reply, token) = oneshot::channel();
+        self.send(ClientCommand::Subscribe(Box::new(subscription), reply))?;
+        token.await.map_err(|_| Self::engine_terminated())
+    }
+
+    /// Operation method that removes a `Subscription` that is currently in the "active" state.
+    /// See `LightstreamerClient.unsubscribe()` for details.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if the token doesn't identify an "active" `Subscription` of
+    ///   the client, e.g. because it was unsubscribed from already.
+    /// * `IllegalStateException`: if the engine has terminated.
+    pub async fn unsubscribe(&self, token: SubscriptionToken) -> Result<(), Box<dyn Error>> {
+        let (reply, outcome) = oneshot::channel();
+        self.send(ClientCommand::Unsubscribe(token, reply))?;
+        match outcome.await {
+            Ok(result) => result.map_err(|err| err.into()),
+            Err(_) => Err(Box::new(Self::engine_terminated())),
+        }
+    }
+
+    /// Setter method that sets the interval between two keepalive packets to be sent by
+    /// Lightstreamer Server on a stream connection. If a session is active, its streaming
+    /// connection is rebound on the fly to request the new value. See
+    /// `ConnectionOptions.setKeepaliveInterval()` for details.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalStateException`: if the engine has terminated.
+    pub fn set_keepalive_interval(
+        &self,
+        keepalive_interval: u64,
+    ) -> Result<(), IllegalStateException> {
+        self.send(ClientCommand::SetKeepaliveInterval(keepalive_interval))
+    }
+
+    /// Setter method that forces the use of the given transport, or lets the client choose it
+    /// when `None`. If a session is active, it is rebound on the fly to a connection of the new
+    /// transport. See `ConnectionOptions.setForcedTransport()` for details.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalStateException`: if the engine has terminated.
+    pub fn set_forced_transport(
+        &self,
+        forced_transport: Option<Transport>,
+    ) -> Result<(), IllegalStateException> {
+        self.send(ClientCommand::SetForcedTransport(forced_transport))
+    }
+
+    /// Operation method that sends a message to the Server. See `LightstreamerClient.sendMessage()`
+    /// for details.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalStateException`: if the engine has terminated, in which case the listener, if any,
+    ///   is not notified, or if the status is "DISCONNECTED*" when the engine handles the message
+    ///   and `enqueue_while_disconnected` is `false`, in which case the message is aborted as by
+    ///   `LightstreamerClient.sendMessage()`.
+    pub async fn send_message(
+        &self,
+        message: &str,
+        sequence: Option<&str>,
+        delay_timeout: Option<u64>,
+        listener: Option<Box<dyn ClientMessageListener>>,
+        enqueue_while_disconnected: bool,
+    ) -> Result<(), IllegalStateException> {
+        let (reply, outcome) = oneshot::channel();
+        self.send(ClientCommand::SendMessage {
+            message: message.to_string(),
+            sequence: sequence.map(str::to_string),
+            delay_timeout,
+            listener,
+            enqueue_while_disconnected,
+            reply,
+        })?;
+        outcome
+            .await
+            .unwrap_or_else(|_| Err(Self::engine_terminated()))
+    }
+
+    /// Inquiry method that gets the current client status and transport (when applicable). See
+    /// `LightstreamerClient.getStatus()` for details.
+    pub fn get_status(&self) -> ClientStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Inquiry method that gets a snapshot of the health counters of the client. See
+    /// `LightstreamerClient.getMetrics()` for details.
+    pub fn get_metrics(&self) -> ClientMetrics {
+        self.metrics.snapshot(self.get_status())
+    }
+
+    fn send(&self, command: ClientCommand) -> Result<(), IllegalStateException> {
+        self.commands
+            .send(command)
+            .map_err(|_| Self::engine_terminated())
+    }
+
+    fn engine_terminated() -> IllegalStateException {
+        IllegalStateException::new("The client engine has terminated")
+    }
+}
+
+impl Debug for ClientHandle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ClientHandle")
+            .field("status", &self.get_status())
+            .finish_non_exhaustive()
+    }
+}
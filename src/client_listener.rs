@@ -1,4 +1,8 @@
+use crate::disconnect_info::DisconnectInfo;
+use crate::session_end_cause::SessionEndCause;
+
 use std::fmt::Debug;
+use std::ops::Deref;
 
 /// Interface to be implemented to listen to `LightstreamerClient` events comprehending notifications
 /// of connection activity and errors.
@@ -8,7 +12,52 @@ use std::fmt::Debug;
 /// has changed. On the other hand, all the notifications for a single `LightstreamerClient`,
 /// including notifications to `ClientListener`, `SubscriptionListener` and `ClientMessageListener`
 /// will be dispatched by the same thread.
+///
+/// A panic raised by an event handler is caught and logged by the library, so that it doesn't
+/// stop the dispatching of the following events, to this or other listeners.
 pub trait ClientListener: Debug + Send {
+    /// Inquiry method that tells whether the listener is no longer interested in any event, for
+    /// instance because the component owning it has been dropped. Expired listeners are removed
+    /// from the `LightstreamerClient` before the next event is dispatched, without being notified.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the listener can be removed. The default implementation returns `false`.
+    ///
+    /// See also `WeakClientListener`
+    fn is_expired(&self) -> bool {
+        false
+    }
+
+    /// Event handler that is called when the current connection is interrupted because of an
+    /// error on the client side, such as a message from the Server exceeding the configured size
+    /// limits. After this notification the connection is closed and the session is bound to a new
+    /// connection after the delay of the `RetryPolicy`, so no action is required.
+    ///
+    /// # Parameters
+    ///
+    /// * `message`: the description of the error.
+    ///
+    /// See also `ConnectionOptions.setMaxMessageSize()`
+    ///
+    /// See also `ConnectionOptions.setMaxFrameSize()`
+    fn on_connection_error(&self, _message: &str) {
+        // Default implementation does nothing.
+    }
+
+    /// Event handler that receives a notification each time a WebSocket connection is closed,
+    /// reporting who closed it, the close code and reason received from the peer, and whether
+    /// the close handshake was completed or the connection was aborted.
+    ///
+    /// # Parameters
+    ///
+    /// * `info`: the details about the closure of the connection.
+    ///
+    /// See also `LightstreamerClient.getLastDisconnectInfo()`
+    fn on_disconnect(&self, _info: &DisconnectInfo) {
+        // Default implementation does nothing.
+    }
+
     /// Event handler that receives a notification when the `ClientListener` instance is removed
     /// from a `LightstreamerClient` through `LightstreamerClient.removeListener()`. This is the
     /// last event to be fired on the listener.
@@ -64,8 +113,7 @@ pub trait ClientListener: Debug + Send {
     ///
     /// See also `LightstreamerClient.connectionOptions`
     fn on_property_change(&self, _property: &str) {
-        // Implementation for on_property_change
-        unimplemented!("Implement on_property_change method for ClientListener");
+        // Default implementation does nothing.
     }
 
     /// Event handler that is called when the Server notifies a refusal on the client attempt
@@ -103,8 +151,60 @@ pub trait ClientListener: Debug + Send {
     ///
     /// See also `ConnectionDetails.setAdapterSet()`
     fn on_server_error(&self, _code: i32, _message: &str) {
-        // Implementation for on_server_error
-        unimplemented!("Implement on_server_error method for ClientListener");
+        // Default implementation does nothing.
+    }
+
+    /// Event handler that receives a notification each time a keepalive (`PROBE`) message is
+    /// received from the Server on the active streaming connection. Keepalives are sent by the
+    /// Server when no other data is flowing, so that the connection liveness can be displayed with
+    /// a finer granularity than the one given by status changes.
+    ///
+    /// See also `ConnectionOptions.setKeepaliveInterval()`
+    ///
+    /// See also `onServerSync()`
+    fn on_server_keepalive(&self) {
+        // Default implementation does nothing.
+    }
+
+    /// Event handler that receives a notification each time a synchronization (`SYNC`) message is
+    /// received from the Server, reporting the time elapsed on the Server since the session
+    /// stream was started. Comparing it with the local time elapsed allows to detect delays in
+    /// the delivery of data.
+    ///
+    /// # Parameters
+    ///
+    /// * `seconds`: the seconds elapsed on the Server since the start of the session stream.
+    ///
+    /// See also `onServerKeepalive()`
+    fn on_server_sync(&self, _seconds: u64) {
+        // Default implementation does nothing.
+    }
+
+    /// Event handler that is called once when a Server session ends, i.e. when it is closed or
+    /// when it can't be recovered anymore. Unlike `onStatusChange()`, it is not called when a
+    /// connection is lost and the session is then recovered.
+    ///
+    /// # Parameters
+    ///
+    /// * `cause`: the reason why the session ended.
+    ///
+    /// See also `onSessionStart()`
+    fn on_session_end(&self, _cause: &SessionEndCause) {
+        // Default implementation does nothing.
+    }
+
+    /// Event handler that is called once when a new Server session is created, before any
+    /// notification related to the session. It is not called when a session is recovered or
+    /// bound to a new connection, so it is the place to reset any state that the application
+    /// keeps for each session.
+    ///
+    /// # Parameters
+    ///
+    /// * `session_id`: the ID of the new session.
+    ///
+    /// See also `onSessionEnd()`
+    fn on_session_start(&self, _session_id: &str) {
+        // Default implementation does nothing.
     }
 
     /// Event handler that receives a notification each time the `LightstreamerClient` status has changed.
@@ -182,7 +282,64 @@ pub trait ClientListener: Debug + Send {
     ///
     /// See also `LightstreamerClient.getStatus()`
     fn on_status_change(&self, _status: &str) {
-        // Implementation for on_status_change
-        unimplemented!("Implement on_status_change method for ClientListener");
+        // Default implementation does nothing.
+    }
+}
+
+/// Token identifying a listener added to a `LightstreamerClient`, as returned by
+/// `LightstreamerClient.addListener()`, to be passed to `LightstreamerClient.removeListener()`.
+///
+/// Tokens are never reused by the same client, so that a stale token can't refer to another
+/// listener.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ListenerToken(usize);
+
+/// The listeners added to a `LightstreamerClient`, in the order they were added, as returned by
+/// `LightstreamerClient.getListeners()`. They can be inspected as a slice.
+#[derive(Debug, Default)]
+pub struct ClientListeners {
+    listeners: Vec<Box<dyn ClientListener>>,
+    /// Key of each listener, in the same order. Keys are assigned in increasing order, so that
+    /// they stay sorted.
+    keys: Vec<usize>,
+    next_key: usize,
+}
+
+impl ClientListeners {
+    /// Adds a listener at the end of the list, returning the token that identifies it.
+    pub(crate) fn push(&mut self, listener: Box<dyn ClientListener>) -> ListenerToken {
+        let key = self.next_key;
+        self.next_key += 1;
+        self.listeners.push(listener);
+        self.keys.push(key);
+        ListenerToken(key)
+    }
+
+    /// Removes the listener identified by the token, if it is still in the list.
+    pub(crate) fn remove(&mut self, token: ListenerToken) -> Option<Box<dyn ClientListener>> {
+        let position = self.keys.binary_search(&token.0).ok()?;
+        self.keys.remove(position);
+        Some(self.listeners.remove(position))
+    }
+
+    /// Removes the listeners that report themselves as expired.
+    pub(crate) fn remove_expired(&mut self) {
+        let mut position = 0;
+        while position < self.listeners.len() {
+            if self.listeners[position].is_expired() {
+                self.listeners.remove(position);
+                self.keys.remove(position);
+            } else {
+                position += 1;
+            }
+        }
+    }
+}
+
+impl Deref for ClientListeners {
+    type Target = [Box<dyn ClientListener>];
+
+    fn deref(&self) -> &Self::Target {
+        &self.listeners
     }
 }
@@ -3,7 +3,10 @@
 /// thread than the one that generates them. All the notifications for a single `LightstreamerClient`,
 /// including notifications to `ClientListener`, `SubscriptionListener` and `ClientMessageListener`
 /// will be dispatched by the same thread. Only one event per message is fired on this listener.
-pub trait ClientMessageListener {
+///
+/// A panic raised by an event handler is caught and logged by the library, so that it doesn't
+/// stop the dispatching of the following events, to this or other listeners.
+pub trait ClientMessageListener: Send {
     /// Event handler that is called by Lightstreamer when any notifications of the processing
     /// outcome of the related message haven't been received yet and can no longer be received.
     /// Typically, this happens after the session has been closed. In this case, the client has
@@ -16,8 +19,7 @@ pub trait ClientMessageListener {
     ///   Even if the flag is `true`, it is not possible to infer whether the message actually
     ///   reached the Lightstreamer Server or not.
     fn on_abort(&self, _msg: &str, _sent_on_network: bool) {
-        // Implementation for on_abort
-        unimplemented!("Implement on_abort method for ClientMessageListener.");
+        // Default implementation does nothing.
     }
 
     /// Event handler that is called by Lightstreamer when the related message has been processed
@@ -31,8 +33,7 @@ pub trait ClientMessageListener {
     ///     on the specific Metadata Adapter implementation.
     /// * `error`: the description of the error sent by the Server.
     fn on_deny(&self, _msg: &str, _code: i32, _error: &str) {
-        // Implementation for on_deny
-        unimplemented!("Implement on_deny method for ClientMessageListener.");
+        // Default implementation does nothing.
     }
 
     /// Event handler that is called by Lightstreamer to notify that the related message has
@@ -43,8 +44,7 @@ pub trait ClientMessageListener {
     ///
     /// * `msg`: the message to which this notification is related.
     fn on_discarded(&self, _msg: &str) {
-        // Implementation for on_discarded
-        unimplemented!("Implement on_discarded method for ClientMessageListener.");
+        // Default implementation does nothing.
     }
 
     /// Event handler that is called by Lightstreamer when the related message has been processed
@@ -55,8 +55,7 @@ pub trait ClientMessageListener {
     ///
     /// * `msg`: the message to which this notification is related.
     fn on_error(&self, _msg: &str) {
-        // Implementation for on_error
-        unimplemented!("Implement on_error method for ClientMessageListener.");
+        // Default implementation does nothing.
     }
 
     /// Event handler that is called by Lightstreamer when the related message has been processed
@@ -68,7 +67,6 @@ pub trait ClientMessageListener {
     /// * `response`: the response from the Metadata Adapter. If not supplied (i.e. supplied as `None`),
     ///   an empty message is received here.
     fn on_processed(&self, _msg: &str, _response: Option<&str>) {
-        // Implementation for on_processed
-        unimplemented!("Implement on_processed method for ClientMessageListener.");
+        // Default implementation does nothing.
     }
 }
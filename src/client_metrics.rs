@@ -0,0 +1,190 @@
+use crate::ls_client::ClientStatus;
+use crate::runtime::Instant;
+
+use std::collections::VecDeque;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::Duration;
+
+/// Snapshot of the health counters of a `LightstreamerClient`, obtained through
+/// `LightstreamerClient.getMetrics()`.
+///
+/// Counters are cumulative over the whole life of the client, across sessions and reconnections.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ClientMetrics {
+    /// Number of item updates received from the Server.
+    pub updates_received: u64,
+    /// Number of bytes of the text frames received from the Server.
+    pub bytes_received: u64,
+    /// Number of bytes of the text frames sent to the Server.
+    pub bytes_sent: u64,
+    /// Number of messages sent to the Server through `LightstreamerClient.sendMessage()`.
+    pub messages_sent: u64,
+    /// Number of connection attempts made after the first one, following a failure or the loss
+    /// of a connection.
+    pub reconnections: u64,
+    /// The status of the client when the snapshot was taken.
+    pub status: ClientStatus,
+    /// Round-trip time measured on the last request acknowledged by the Server, if any.
+    pub last_rtt: Option<Duration>,
+    /// Rolling average of the round-trip times of the WebSocket pings, if any was answered. See
+    /// `ConnectionOptions.setPingInterval()`.
+    pub ping_rtt: Option<Duration>,
+    /// Number of listener calls found stalled by the dispatch watchdog. See
+    /// `ConnectionOptions.setDispatchWatchdogTimeout()`.
+    pub dispatch_stalls: u64,
+}
+
+/// Listener call that has been blocking the dispatch task of a `LightstreamerClient` for longer
+/// than the timeout of the dispatch watchdog, obtained through
+/// `LightstreamerClient.getDispatchStall()`.
+///
+/// While a listener call is stalled, no other notification is delivered, and the updates pile up
+/// in the backpressure buffers of the Subscriptions.
+///
+/// See also `ConnectionOptions.setDispatchWatchdogTimeout()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DispatchStall {
+    /// Name of the listener callback that didn't return yet, e.g. `onItemUpdates`.
+    pub callback: String,
+    /// Items of the Subscription whose listener is stalled, as its item group or its
+    /// space-separated item list, or `None` for the client and message listeners.
+    pub subscription: Option<String>,
+    /// Time elapsed since the callback was invoked.
+    pub stalled_for: Duration,
+    /// Whether the Subscription was isolated, so that its listeners are skipped from now on. See
+    /// `ConnectionOptions.setStalledSubscriptionIsolationEnabled()`.
+    pub isolated: bool,
+}
+
+/// Percentiles of the latency of the real-time updates of a Subscription, i.e. the time elapsed
+/// from the server timestamp they carry to their reception, obtained through
+/// `Subscription.getLatencyStats()`.
+///
+/// Percentiles are computed over the latest measurements only, so that they follow the current
+/// conditions of the network and of the Server.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyStats {
+    /// Number of updates whose latency was measured since the measurement was enabled.
+    pub samples: u64,
+    /// Median latency.
+    pub p50: Duration,
+    /// 90th percentile of the latency.
+    pub p90: Duration,
+    /// 99th percentile of the latency.
+    pub p99: Duration,
+    /// Highest latency.
+    pub max: Duration,
+}
+
+impl LatencyStats {
+    /// Computes the percentiles of the given latencies, if any.
+    pub(crate) fn from_latencies(
+        samples: u64,
+        latencies: &VecDeque<Duration>,
+    ) -> Option<LatencyStats> {
+        let mut sorted: Vec<Duration> = latencies.iter().copied().collect();
+        sorted.sort_unstable();
+        let max = *sorted.last()?;
+        // Nearest-rank percentile.
+        let percentile = |percent: usize| sorted[(sorted.len() * percent).div_ceil(100).max(1) - 1];
+        Some(LatencyStats {
+            samples,
+            p50: percentile(50),
+            p90: percentile(90),
+            p99: percentile(99),
+            max,
+        })
+    }
+}
+
+/// Snapshot of the counters of a Subscription, obtained through `Subscription.getStats()`, meant
+/// for dashboards and debugging.
+///
+/// Counters are cumulative over the whole life of the Subscription, across sessions and
+/// reconnections.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubscriptionStats {
+    /// Number of item updates received for the Subscription.
+    pub updates_received: u64,
+    /// Number of the received item updates that belong to an item snapshot.
+    pub snapshot_updates: u64,
+    /// Number of item updates the Server notified as lost, because of buffer overflows or
+    /// frequency limits. See also `SubscriptionListener.onItemLostUpdates()`.
+    pub lost_updates: u64,
+    /// Instant at which the last item update was received, if any.
+    pub last_update_at: Option<Instant>,
+    /// Maximum update frequency, in updates per second for each item, granted by the Server to
+    /// the Subscription in the current session, or `None` if not notified yet. An infinite
+    /// frequency means that the updates are not limited.
+    pub real_max_frequency: Option<f64>,
+}
+
+/// Counters updated by the session task and read by `LightstreamerClient.getMetrics()`.
+#[derive(Debug, Default)]
+pub(crate) struct MetricsRecorder {
+    updates_received: AtomicU64,
+    bytes_received: AtomicU64,
+    bytes_sent: AtomicU64,
+    messages_sent: AtomicU64,
+    reconnections: AtomicU64,
+    dispatch_stalls: AtomicU64,
+    last_rtt: Mutex<Option<Duration>>,
+    ping_rtt: Mutex<Option<Duration>>,
+}
+
+impl MetricsRecorder {
+    pub(crate) fn update_received(&self) {
+        self.updates_received.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn bytes_received(&self, bytes: usize) {
+        self.bytes_received
+            .fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn bytes_sent(&self, bytes: usize) {
+        self.bytes_sent.fetch_add(bytes as u64, Ordering::Relaxed);
+    }
+
+    pub(crate) fn message_sent(&self) {
+        self.messages_sent.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn reconnection(&self) {
+        self.reconnections.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn dispatch_stall(&self) {
+        self.dispatch_stalls.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn rtt(&self, rtt: Duration) {
+        *self.last_rtt.lock().unwrap() = Some(rtt);
+    }
+
+    /// Accounts for the round-trip time of a ping in the rolling average, which gives each new
+    /// measurement a weight of 1/8, as TCP does for its smoothed round-trip time.
+    pub(crate) fn ping_rtt(&self, rtt: Duration) {
+        let mut ping_rtt = self.ping_rtt.lock().unwrap();
+        *ping_rtt = Some(match *ping_rtt {
+            Some(average) => (average * 7 + rtt) / 8,
+            None => rtt,
+        });
+    }
+
+    /// Takes a snapshot of the counters, completed with the current status of the client.
+    pub(crate) fn snapshot(&self, status: ClientStatus) -> ClientMetrics {
+        ClientMetrics {
+            updates_received: self.updates_received.load(Ordering::Relaxed),
+            bytes_received: self.bytes_received.load(Ordering::Relaxed),
+            bytes_sent: self.bytes_sent.load(Ordering::Relaxed),
+            messages_sent: self.messages_sent.load(Ordering::Relaxed),
+            reconnections: self.reconnections.load(Ordering::Relaxed),
+            status,
+            last_rtt: *self.last_rtt.lock().unwrap(),
+            ping_rtt: *self.ping_rtt.lock().unwrap(),
+            dispatch_stalls: self.dispatch_stalls.load(Ordering::Relaxed),
+        }
+    }
+}
@@ -0,0 +1,230 @@
+use crate::runtime::{CurrentRuntime, Runtime};
+
+use std::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use cookie::time::format_description::well_known::Rfc2822;
+use cookie::time::OffsetDateTime;
+
+/// Monotonic instant measured by a `Clock`, which on wasm32 is backed by the browser's
+/// `performance.now()`.
+#[cfg(not(target_arch = "wasm32"))]
+pub use std::time::Instant;
+
+/// Monotonic instant measured by a `Clock`, which on wasm32 is backed by the browser's
+/// `performance.now()`.
+#[cfg(target_arch = "wasm32")]
+pub use web_time::Instant;
+
+/// Future returned by `Clock.sleep()`.
+pub type Sleep = Pin<Box<dyn Future<Output = ()> + Send>>;
+
+/// Interface to be implemented to supply the time source used by the timer-driven logic of a
+/// `LightstreamerClient`: retry delays, session recovery timeouts, connection deadlines and
+/// client-side conflation intervals are all measured and awaited through it.
+///
+/// An instance of a type implementing this trait can be supplied through
+/// `ConnectionOptions.setClock()`; this is mostly useful in tests, where a virtual clock makes it
+/// possible to advance time deterministically instead of waiting for real delays. When no clock is
+/// supplied, a `RuntimeClock` is used.
+///
+/// The clock is used by the session task, which runs separately from the code that configured
+/// it; this is why implementations must be `Send` and `Sync`.
+pub trait Clock: Debug + Send + Sync {
+    /// Gets the current instant.
+    fn now(&self) -> Instant;
+
+    /// Waits for the given duration, as measured by this clock.
+    fn sleep(&self, duration: Duration) -> Sleep;
+}
+
+/// Clock backed by the timers of the async runtime in use.
+///
+/// With the tokio runtime, both the current instant and the sleeps follow `tokio::time`, so the
+/// client honors `tokio::time::pause()` and `tokio::time::advance()` in tests.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct RuntimeClock;
+
+impl Clock for RuntimeClock {
+    fn now(&self) -> Instant {
+        #[cfg(feature = "runtime-tokio")]
+        return tokio::time::Instant::now().into_std();
+        #[cfg(not(feature = "runtime-tokio"))]
+        return Instant::now();
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        Box::pin(CurrentRuntime::sleep(duration))
+    }
+}
+
+/// Estimate of the offset between the clocks of the Server and of the client, obtained through
+/// `LightstreamerClient.getClockSkew()`. It can be used to align the timestamps set by the Server,
+/// or by the Data Adapters running alongside it, such as those carried by item updates, with the
+/// local time.
+///
+/// The offset is first measured on the handshake of each connection, by comparing the `Date`
+/// header of the Server response with the local time halfway through the handshake, and then
+/// kept aligned, while the stream is open, through the time elapsed on the Server reported by the
+/// `SYNC` notifications.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClockSkew {
+    /// Estimated difference between the Server clock and the client clock, in milliseconds:
+    /// positive when the Server clock is ahead of the client one.
+    pub offset_millis: i64,
+    /// Approximate bound of the error of the estimate, due to the resolution of the `Date` header
+    /// and to the network delays.
+    pub uncertainty: Duration,
+}
+
+impl ClockSkew {
+    /// Converts a time read from the Server clock, such as a timestamp carried by an update, into
+    /// the corresponding time of the client clock.
+    pub fn to_local_time(&self, server_time: SystemTime) -> SystemTime {
+        shift(server_time, -self.offset_millis)
+    }
+
+    /// Converts a time read from the client clock into the corresponding time of the Server
+    /// clock.
+    pub fn to_server_time(&self, local_time: SystemTime) -> SystemTime {
+        shift(local_time, self.offset_millis)
+    }
+}
+
+/// Estimator of the `ClockSkew` of a session, fed by the session task with the handshakes of its
+/// connections and with the `SYNC` notifications.
+#[derive(Debug)]
+pub(crate) struct ClockSkewEstimator {
+    /// System time corresponding to `origin`, used to convert the instants measured by the
+    /// session clock into system times.
+    origin_time: SystemTime,
+    origin: Instant,
+    /// Offset, in milliseconds, and uncertainty measured on the handshake of the last connection.
+    handshake_sample: Option<(i64, Duration)>,
+    /// Instant the current stream was confirmed by the Server.
+    stream_start: Option<Instant>,
+    /// Largest difference, in milliseconds, between the time elapsed on the Server and on the
+    /// client since the start of the current stream. The largest one is the least affected by
+    /// the delays of the notifications.
+    sync_correction: Option<i64>,
+}
+
+impl ClockSkewEstimator {
+    pub(crate) fn new(now: Instant) -> ClockSkewEstimator {
+        ClockSkewEstimator {
+            origin_time: system_time_now(),
+            origin: now,
+            handshake_sample: None,
+            stream_start: None,
+            sync_correction: None,
+        }
+    }
+
+    /// Takes a sample on the handshake of a new connection, started and completed at the given
+    /// instants, given the `Date` header of the Server response, if any.
+    pub(crate) fn handshake(
+        &mut self,
+        started_at: Instant,
+        completed_at: Instant,
+        date: Option<&str>,
+    ) {
+        self.stream_start = None;
+        self.sync_correction = None;
+        let Some(server_time) = date.and_then(|date| OffsetDateTime::parse(date, &Rfc2822).ok())
+        else {
+            self.handshake_sample = None;
+            return;
+        };
+        let round_trip = completed_at.saturating_duration_since(started_at);
+        let midpoint = self.system_time(started_at + round_trip / 2);
+        // The header has a resolution of one second: the middle of that second is assumed.
+        let server_millis = server_time.unix_timestamp() * 1000 + 500;
+        self.handshake_sample = Some((
+            server_millis - unix_millis(midpoint),
+            Duration::from_millis(500) + round_trip / 2,
+        ));
+    }
+
+    /// Starts measuring the time elapsed on the client since the current stream was confirmed.
+    pub(crate) fn stream_started(&mut self, now: Instant) {
+        self.stream_start = Some(now);
+        self.sync_correction = None;
+    }
+
+    /// Takes a sample on a `SYNC` notification reporting the seconds elapsed on the Server since
+    /// the start of the current stream.
+    pub(crate) fn sync(&mut self, seconds: u64, now: Instant) {
+        let Some(stream_start) = self.stream_start else {
+            return;
+        };
+        let elapsed = now.saturating_duration_since(stream_start).as_millis() as i64;
+        let correction = (seconds as i64).saturating_mul(1000) - elapsed;
+        self.sync_correction = Some(
+            self.sync_correction
+                .map_or(correction, |previous| previous.max(correction)),
+        );
+    }
+
+    /// Gets the time elapsed from a server timestamp, given in milliseconds since the Unix epoch,
+    /// to the given instant, with the timestamp aligned with the local clock if an estimate is
+    /// available. Timestamps in the future, due to the uncertainty of the estimate, give zero.
+    pub(crate) fn elapsed_since(&self, server_millis: i64, now: Instant) -> Duration {
+        let server_time = shift(UNIX_EPOCH, server_millis);
+        let local_time = match self.estimate() {
+            Some(clock_skew) => clock_skew.to_local_time(server_time),
+            None => server_time,
+        };
+        self.system_time(now)
+            .duration_since(local_time)
+            .unwrap_or_default()
+    }
+
+    /// Gets the current estimate, if the Server supplied its time on the last handshake.
+    pub(crate) fn estimate(&self) -> Option<ClockSkew> {
+        self.handshake_sample
+            .map(|(offset_millis, uncertainty)| ClockSkew {
+                offset_millis: offset_millis + self.sync_correction.unwrap_or(0),
+                uncertainty,
+            })
+    }
+
+    /// Converts an instant measured by the session clock into a system time.
+    pub(crate) fn system_time(&self, instant: Instant) -> SystemTime {
+        match instant.checked_duration_since(self.origin) {
+            Some(elapsed) => self.origin_time + elapsed,
+            None => self.origin_time - self.origin.saturating_duration_since(instant),
+        }
+    }
+}
+
+/// Gets the current system time, which on wasm32 is read from the browser's `Date.now()`.
+pub(crate) fn system_time_now() -> SystemTime {
+    #[cfg(not(target_arch = "wasm32"))]
+    return SystemTime::now();
+    #[cfg(target_arch = "wasm32")]
+    return UNIX_EPOCH
+        + web_time::SystemTime::now()
+            .duration_since(web_time::UNIX_EPOCH)
+            .unwrap_or_default();
+}
+
+/// Gets the milliseconds elapsed from the Unix epoch to the given time, negative if it precedes
+/// the epoch.
+fn unix_millis(time: SystemTime) -> i64 {
+    match time.duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_millis() as i64,
+        Err(err) => -(err.duration().as_millis() as i64),
+    }
+}
+
+/// Moves a time forward, or backward if negative, by the given milliseconds.
+fn shift(time: SystemTime, millis: i64) -> SystemTime {
+    let delta = Duration::from_millis(millis.unsigned_abs());
+    if millis >= 0 {
+        time + delta
+    } else {
+        time - delta
+    }
+}
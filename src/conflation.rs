@@ -0,0 +1,88 @@
+//! Client-side conflation of the updates of MERGE subscriptions.
+
+use crate::item_update::ItemUpdate;
+use crate::runtime::Instant;
+
+use ahash::AHashMap;
+use std::time::Duration;
+
+/// Coalesces the updates of the items of a subscription, so that each item is delivered at most
+/// once per interval.
+///
+/// An update arriving when the interval since the last delivery of its item has elapsed is
+/// delivered right away; otherwise it's held, merged with the updates of the same item held
+/// before, until the interval elapses.
+pub(crate) struct Conflator {
+    interval: Duration,
+    /// State of the items delivered so far, indexed by item position.
+    items: AHashMap<usize, ConflatedItem>,
+}
+
+struct ConflatedItem {
+    last_delivery: Instant,
+    /// Update held until the interval elapses, if any.
+    pending: Option<ItemUpdate>,
+}
+
+impl Conflator {
+    /// Creates a conflator delivering each item at most the given number of times per second.
+    pub(crate) fn new(max_frequency: f64) -> Self {
+        Conflator {
+            interval: Duration::from_secs_f64(1.0 / max_frequency),
+            items: AHashMap::new(),
+        }
+    }
+
+    /// Offers an update, returning it if it has to be delivered right away.
+    pub(crate) fn offer(&mut self, update: ItemUpdate, now: Instant) -> Option<ItemUpdate> {
+        match self.items.get_mut(&update.get_item_pos()) {
+            Some(item) if now.saturating_duration_since(item.last_delivery) < self.interval => {
+                let mut update = update;
+                if let Some(pending) = item.pending.take() {
+                    // The changes of the held update are not lost, unless overwritten.
+                    update.merge_changes(&pending);
+                }
+                item.pending = Some(update);
+                None
+            }
+            _ => {
+                self.items.insert(
+                    update.get_item_pos(),
+                    ConflatedItem {
+                        last_delivery: now,
+                        pending: None,
+                    },
+                );
+                Some(update)
+            }
+        }
+    }
+
+    /// Gets the instant at which the next held update is due, if any.
+    pub(crate) fn next_flush(&self) -> Option<Instant> {
+        self.items
+            .values()
+            .filter(|item| item.pending.is_some())
+            .map(|item| item.last_delivery + self.interval)
+            .min()
+    }
+
+    /// Takes the held updates that are due.
+    pub(crate) fn flush(&mut self, now: Instant) -> Vec<ItemUpdate> {
+        let interval = self.interval;
+        let mut updates: Vec<ItemUpdate> = self
+            .items
+            .values_mut()
+            .filter(|item| {
+                item.pending.is_some()
+                    && now.saturating_duration_since(item.last_delivery) >= interval
+            })
+            .filter_map(|item| {
+                item.last_delivery = now;
+                item.pending.take()
+            })
+            .collect();
+        updates.sort_by_key(ItemUpdate::get_item_pos);
+        updates
+    }
+}
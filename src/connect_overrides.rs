@@ -0,0 +1,249 @@
+use crate::error::IllegalArgumentException;
+use crate::ls_client::Transport;
+
+/// Settings that replace, for a single call to `LightstreamerClient.connectWith()`, the
+/// corresponding ones of `LightstreamerClient.connectionOptions`, which are left untouched.
+///
+/// This allows a client to open a session with a different transport or different timeouts, for
+/// instance a diagnostic connection forced on HTTP polling, and to go back to its long-term
+/// configuration with the next call to `LightstreamerClient.connect()`. The overrides apply to the
+/// whole session task started by the call, including its reconnections, while the settings that
+/// are not overridden are taken from the `ConnectionOptions` as usual. A change made to an
+/// overridden setting of the `ConnectionOptions` while the session is running is still applied
+/// to it, as it would be without overrides.
+///
+/// Every setting starts not overridden.
+///
+/// See also `LightstreamerClient.connectWith()`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ConnectOverrides {
+    pub(crate) forced_transport: Option<Option<Transport>>,
+    pub(crate) idle_timeout: Option<u64>,
+    pub(crate) keepalive_interval: Option<u64>,
+    pub(crate) polling_interval: Option<u64>,
+    pub(crate) reconnect_timeout: Option<u64>,
+    pub(crate) retry_delay: Option<u64>,
+    pub(crate) session_recovery_timeout: Option<u64>,
+    pub(crate) stalled_timeout: Option<u64>,
+}
+
+impl ConnectOverrides {
+    /// Creates a set of overrides where no setting is overridden.
+    pub fn new() -> ConnectOverrides {
+        ConnectOverrides::default()
+    }
+
+    /// Inquiry method that gets the forced transport overriding
+    /// `ConnectionOptions.getForcedTransport()`.
+    ///
+    /// # Returns
+    ///
+    /// The forced transport, which may itself be `None`, or `None` if not overridden.
+    ///
+    /// See also `setForcedTransport()`
+    pub fn get_forced_transport(&self) -> Option<Option<Transport>> {
+        self.forced_transport
+    }
+
+    /// Inquiry method that gets the idle timeout overriding `ConnectionOptions.getIdleTimeout()`.
+    ///
+    /// # Returns
+    ///
+    /// The idle timeout (in milliseconds), or `None` if not overridden.
+    ///
+    /// See also `setIdleTimeout()`
+    pub fn get_idle_timeout(&self) -> Option<u64> {
+        self.idle_timeout
+    }
+
+    /// Inquiry method that gets the keepalive interval overriding
+    /// `ConnectionOptions.getKeepaliveInterval()`.
+    ///
+    /// # Returns
+    ///
+    /// The keepalive interval (in milliseconds), or `None` if not overridden.
+    ///
+    /// See also `setKeepaliveInterval()`
+    pub fn get_keepalive_interval(&self) -> Option<u64> {
+        self.keepalive_interval
+    }
+
+    /// Inquiry method that gets the polling interval overriding
+    /// `ConnectionOptions.getPollingInterval()`.
+    ///
+    /// # Returns
+    ///
+    /// The polling interval (in milliseconds), or `None` if not overridden.
+    ///
+    /// See also `setPollingInterval()`
+    pub fn get_polling_interval(&self) -> Option<u64> {
+        self.polling_interval
+    }
+
+    /// Inquiry method that gets the reconnect timeout overriding
+    /// `ConnectionOptions.getReconnectTimeout()`.
+    ///
+    /// # Returns
+    ///
+    /// The reconnect timeout (in milliseconds), or `None` if not overridden.
+    ///
+    /// See also `setReconnectTimeout()`
+    pub fn get_reconnect_timeout(&self) -> Option<u64> {
+        self.reconnect_timeout
+    }
+
+    /// Inquiry method that gets the retry delay overriding `ConnectionOptions.getRetryDelay()`.
+    ///
+    /// # Returns
+    ///
+    /// The retry delay (in milliseconds), or `None` if not overridden.
+    ///
+    /// See also `setRetryDelay()`
+    pub fn get_retry_delay(&self) -> Option<u64> {
+        self.retry_delay
+    }
+
+    /// Inquiry method that gets the session recovery timeout overriding
+    /// `ConnectionOptions.getSessionRecoveryTimeout()`.
+    ///
+    /// # Returns
+    ///
+    /// The session recovery timeout (in milliseconds), or `None` if not overridden.
+    ///
+    /// See also `setSessionRecoveryTimeout()`
+    pub fn get_session_recovery_timeout(&self) -> Option<u64> {
+        self.session_recovery_timeout
+    }
+
+    /// Inquiry method that gets the stalled timeout overriding
+    /// `ConnectionOptions.getStalledTimeout()`.
+    ///
+    /// # Returns
+    ///
+    /// The stalled timeout (in milliseconds), or `None` if not overridden.
+    ///
+    /// See also `setStalledTimeout()`
+    pub fn get_stalled_timeout(&self) -> Option<u64> {
+        self.stalled_timeout
+    }
+
+    /// Setter method that overrides `ConnectionOptions.setForcedTransport()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `forced_transport`: the transport to be forced, or `None` to enable the Stream-Sense
+    ///   algorithm, as in `ConnectionOptions.setForcedTransport()`.
+    pub fn set_forced_transport(&mut self, forced_transport: Option<Transport>) {
+        self.forced_transport = Some(forced_transport);
+    }
+
+    /// Setter method that overrides `ConnectionOptions.setIdleTimeout()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `idle_timeout`: the time (in milliseconds) the Server is allowed to wait for data to
+    ///   send upon polling requests.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if a zero value is configured
+    pub fn set_idle_timeout(&mut self, idle_timeout: u64) -> Result<(), IllegalArgumentException> {
+        if idle_timeout == 0 {
+            return Err(IllegalArgumentException::new("Idle timeout cannot be zero"));
+        }
+        self.idle_timeout = Some(idle_timeout);
+        Ok(())
+    }
+
+    /// Setter method that overrides `ConnectionOptions.setKeepaliveInterval()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `keepalive_interval`: the keepalive interval time (in milliseconds), or 0 to let the
+    ///   Server decide it.
+    pub fn set_keepalive_interval(&mut self, keepalive_interval: u64) {
+        self.keepalive_interval = Some(keepalive_interval);
+    }
+
+    /// Setter method that overrides `ConnectionOptions.setPollingInterval()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `polling_interval`: the time (in milliseconds) between subsequent polling requests.
+    pub fn set_polling_interval(&mut self, polling_interval: u64) {
+        self.polling_interval = Some(polling_interval);
+    }
+
+    /// Setter method that overrides `ConnectionOptions.setReconnectTimeout()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `reconnect_timeout`: the maximum time (in milliseconds) allowed for attempts to recover
+    ///   the current session upon a stall of the streaming connection.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if a zero value is configured
+    pub fn set_reconnect_timeout(
+        &mut self,
+        reconnect_timeout: u64,
+    ) -> Result<(), IllegalArgumentException> {
+        if reconnect_timeout == 0 {
+            return Err(IllegalArgumentException::new(
+                "Reconnect timeout cannot be zero",
+            ));
+        }
+        self.reconnect_timeout = Some(reconnect_timeout);
+        Ok(())
+    }
+
+    /// Setter method that overrides `ConnectionOptions.setRetryDelay()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `retry_delay`: the time (in milliseconds) to wait before trying a new connection.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if a zero value is configured
+    pub fn set_retry_delay(&mut self, retry_delay: u64) -> Result<(), IllegalArgumentException> {
+        if retry_delay == 0 {
+            return Err(IllegalArgumentException::new("Retry delay cannot be zero"));
+        }
+        self.retry_delay = Some(retry_delay);
+        Ok(())
+    }
+
+    /// Setter method that overrides `ConnectionOptions.setSessionRecoveryTimeout()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `session_recovery_timeout`: the maximum time allowed for recovery attempts, expressed
+    ///   in milliseconds, including 0 to disable session recovery.
+    pub fn set_session_recovery_timeout(&mut self, session_recovery_timeout: u64) {
+        self.session_recovery_timeout = Some(session_recovery_timeout);
+    }
+
+    /// Setter method that overrides `ConnectionOptions.setStalledTimeout()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `stalled_timeout`: the idle time (in milliseconds) admitted in a Stream-Sense
+    ///   connection before the connection is considered stalled.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if a zero value is configured
+    pub fn set_stalled_timeout(
+        &mut self,
+        stalled_timeout: u64,
+    ) -> Result<(), IllegalArgumentException> {
+        if stalled_timeout == 0 {
+            return Err(IllegalArgumentException::new(
+                "Stalled timeout cannot be zero",
+            ));
+        }
+        self.stalled_timeout = Some(stalled_timeout);
+        Ok(())
+    }
+}
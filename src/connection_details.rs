@@ -1,8 +1,12 @@
 use crate::client_listener::ClientListener;
+use crate::credentials_provider::{CredentialsProvider, ReauthenticationHandler};
 use crate::error::IllegalArgumentException;
+use crate::secret::SecretString;
 
 use std::error::Error;
 use std::fmt::{self, Debug, Formatter};
+use std::sync::{Arc, Mutex};
+use url::Url;
 
 /// Used by `LightstreamerClient` to provide a basic connection properties data object.
 ///
@@ -10,7 +14,11 @@ use std::fmt::{self, Debug, Formatter};
 ///
 /// An instance of this class is attached to every `LightstreamerClient` as `LightstreamerClient.connectionDetails`
 ///
+/// The password is kept as a `SecretString`, so it is never printed by the `Debug` implementation
+/// and it is wiped from memory when replaced or dropped.
+///
 /// See also `LightstreamerClient`
+#[derive(Default)]
 pub struct ConnectionDetails {
     adapter_set: Option<String>,
     client_ip: Option<String>,
@@ -19,8 +27,11 @@ pub struct ConnectionDetails {
     server_socket_name: Option<String>,
     session_id: Option<String>,
     user: Option<String>,
-    password: Option<String>,
-    listeners: Vec<Box<dyn ClientListener>>,
+    password: Option<SecretString>,
+    credentials_provider: Option<Arc<dyn CredentialsProvider>>,
+    reauthentication_handler: Option<Arc<dyn ReauthenticationHandler>>,
+    /// Guarded only to make the details `Sync`, as listeners are merely required to be `Send`.
+    listeners: Mutex<Vec<Box<dyn ClientListener>>>,
 }
 
 impl ConnectionDetails {
@@ -31,7 +42,7 @@ impl ConnectionDetails {
     /// # Returns
     ///
     /// The name of the Adapter Set; returns `None` if no name has been configured, that means
-    /// that the "DEFAULT" Adapter Set is used.
+    /// that the default Adapter Set of the Server (usually "DEFAULT") is used.
     ///
     /// See also `setAdapterSet()`
     pub fn get_adapter_set(&self) -> Option<&String> {
@@ -63,6 +74,31 @@ impl ConnectionDetails {
         self.client_ip.as_ref()
     }
 
+    /// Inquiry method that gets the provider of the credentials to be used for the authentication
+    /// on Lightstreamer Server, if any.
+    ///
+    /// # Returns
+    ///
+    /// The credentials provider; returns `None` if no provider has been configured, that means
+    /// that the configured user and password are used.
+    ///
+    /// See also `setCredentialsProvider()`
+    pub fn get_credentials_provider(&self) -> Option<&Arc<dyn CredentialsProvider>> {
+        self.credentials_provider.as_ref()
+    }
+
+    /// Inquiry method that gets the handler invoked before a lost session is replaced by a new
+    /// one, if any.
+    ///
+    /// # Returns
+    ///
+    /// The reauthentication handler; returns `None` if no handler has been configured.
+    ///
+    /// See also `setReauthenticationHandler()`
+    pub fn get_reauthentication_handler(&self) -> Option<&Arc<dyn ReauthenticationHandler>> {
+        self.reauthentication_handler.as_ref()
+    }
+
     /// Retrieves a reference to the password, if set.
     ///
     /// This method is crucial for accessing sensitive information in a controlled manner. It returns
@@ -72,10 +108,10 @@ impl ConnectionDetails {
     /// password data, which could have security implications and also incur a performance cost.
     ///
     /// # Returns
-    /// An `Option` containing a reference to the password `String` if it exists, or `None` if the
-    /// password has not been set. This allows calling code to handle the presence or absence of a
-    /// password appropriately without risking exposure of the password itself.
-    pub fn get_password(&self) -> Option<&String> {
+    /// An `Option` containing a reference to the password if it exists, or `None` if the
+    /// password has not been set. The password is wrapped in a `SecretString`, whose value must be
+    /// explicitly read through `SecretString.exposeSecret()`, so that it can't be logged by mistake.
+    pub fn get_password(&self) -> Option<&SecretString> {
         self.password.as_ref()
     }
 
@@ -83,7 +119,8 @@ impl ConnectionDetails {
     ///
     /// # Returns
     ///
-    /// The configured address of Lightstreamer Server.
+    /// The configured address of Lightstreamer Server, in the normalized form described in
+    /// `setServerAddress()`.
     pub fn get_server_address(&self) -> Option<&String> {
         self.server_address.as_ref()
     }
@@ -181,7 +218,7 @@ impl ConnectionDetails {
         connection_details.set_server_address(server_address.map(|s| s.to_string()))?;
         connection_details.set_adapter_set(adapter_set.map(|s| s.to_string()));
         connection_details.set_user(user.map(|s| s.to_string()));
-        connection_details.set_password(password.map(|s| s.to_string()));
+        connection_details.set_password(password.map(SecretString::from));
 
         Ok(connection_details)
     }
@@ -193,7 +230,8 @@ impl ConnectionDetails {
     /// on the server side through an "adapters.xml" file; the name is configured through the "id"
     /// attribute in the `<adapters_conf>` element.
     ///
-    /// The default Adapter Set, configured as "DEFAULT" on the Server.
+    /// `None` (meaning that no Adapter Set is requested, so that the Server uses its default one,
+    /// usually configured as "DEFAULT").
     ///
     /// The Adapter Set name should be set on the `LightstreamerClient.connectionDetails` object
     /// before calling the `LightstreamerClient.connect()` method. However, the value can be changed
@@ -207,17 +245,82 @@ impl ConnectionDetails {
     ///
     /// # Parameters
     ///
-    /// * `adapter_set`: The name of the Adapter Set to be used. A `None` value is equivalent to
-    ///   the "DEFAULT" name.
+    /// * `adapter_set`: The name of the Adapter Set to be used. A `None` value means that no
+    ///   Adapter Set is requested to the Server, which then uses its default one; "DEFAULT" is
+    ///   only sent when explicitly given.
     pub fn set_adapter_set(&mut self, adapter_set: Option<String>) {
-        self.adapter_set = Some(adapter_set.unwrap_or("DEFAULT".to_string()));
+        self.adapter_set = adapter_set;
 
         // Notify listeners about the property change
-        for listener in &self.listeners {
+        for listener in self.listeners.lock().unwrap().iter() {
             listener.on_property_change("adapterSet");
         }
     }
 
+    /// Setter method that sets a provider of the credentials to be used for the authentication on
+    /// Lightstreamer Server. The provider is consulted each time a new session is created, so that,
+    /// for instance, a fresh short-lived token can be obtained instead of sending an expired one.
+    /// The credentials it supplies take the place of the ones set through `setUser()` and
+    /// `setPassword()`.
+    ///
+    /// The provider should be set on the `LightstreamerClient.connectionDetails` object before
+    /// calling the `LightstreamerClient.connect()` method; a change will be obeyed upon the next
+    /// call to `connect()`.
+    ///
+    /// A change to this setting will be notified through a call to `ClientListener.onPropertyChange()`
+    /// with argument "credentialsProvider" on any `ClientListener` listening to the related
+    /// `LightstreamerClient`.
+    ///
+    /// # Parameters
+    ///
+    /// * `credentials_provider`: The credentials provider to be used. Specify `None` to use the
+    ///   configured user and password.
+    ///
+    /// See also `CredentialsProvider`
+    pub fn set_credentials_provider(
+        &mut self,
+        credentials_provider: Option<Arc<dyn CredentialsProvider>>,
+    ) {
+        self.credentials_provider = credentials_provider;
+
+        // Notify listeners about the property change
+        for listener in self.listeners.lock().unwrap().iter() {
+            listener.on_property_change("credentialsProvider");
+        }
+    }
+
+    /// Setter method that sets a handler invoked just before a new session is created to replace
+    /// one that was lost and couldn't be recovered. The handler can perform an asynchronous login
+    /// flow and supply the credentials to be used from then on; it is not invoked for the session
+    /// created by `LightstreamerClient.connect()`, unless the Server rejects its credentials and
+    /// `ConnectionOptions.setMaxReloginAttempts()` allows logging in again.
+    ///
+    /// The handler should be set on the `LightstreamerClient.connectionDetails` object before
+    /// calling the `LightstreamerClient.connect()` method; a change will be obeyed upon the next
+    /// call to `connect()`.
+    ///
+    /// A change to this setting will be notified through a call to `ClientListener.onPropertyChange()`
+    /// with argument "reauthenticationHandler" on any `ClientListener` listening to the related
+    /// `LightstreamerClient`.
+    ///
+    /// # Parameters
+    ///
+    /// * `reauthentication_handler`: The handler to be invoked. Specify `None` to create new
+    ///   sessions with the current credentials.
+    ///
+    /// See also `ReauthenticationHandler`
+    pub fn set_reauthentication_handler(
+        &mut self,
+        reauthentication_handler: Option<Arc<dyn ReauthenticationHandler>>,
+    ) {
+        self.reauthentication_handler = reauthentication_handler;
+
+        // Notify listeners about the property change
+        for listener in self.listeners.lock().unwrap().iter() {
+            listener.on_property_change("reauthenticationHandler");
+        }
+    }
+
     /// Setter method that sets the password to be used for the authentication on Lightstreamer
     /// Server when initiating the session. The Metadata Adapter is responsible for checking the
     /// credentials (username and password).
@@ -241,14 +344,15 @@ impl ConnectionDetails {
     /// # Parameters
     ///
     /// * `password`: The password to be used for the authentication on Lightstreamer Server. The
-    ///   password can be `None`.
+    ///   password can be `None`. A `SecretString` can be obtained from a `String` or a `&str`
+    ///   through `into()`.
     ///
     /// See also `setUser()`
-    pub fn set_password(&mut self, password: Option<String>) {
+    pub fn set_password(&mut self, password: Option<SecretString>) {
         self.password = password;
 
         // Notify listeners about the property change
-        for listener in &self.listeners {
+        for listener in self.listeners.lock().unwrap().iter() {
             listener.on_property_change("password");
         }
     }
@@ -285,26 +389,27 @@ impl ConnectionDetails {
     /// - `http://[2001:0db8:85a3:0000:0000:8a2e:0370:7334]`
     /// - `http://[2001:0db8:85a3::8a2e:0370:7334]:8080`
     ///
+    /// The address is stored in normalized form: the scheme and the host are lowercased, a default
+    /// port is dropped and trailing slashes are removed from the path, so `http://Push.MyCompany.com:80/`
+    /// is stored as `http://push.mycompany.com`.
+    ///
     /// # Raises
     ///
-    /// * `IllegalArgumentException`: if the given address is not valid.
+    /// * `IllegalArgumentException`: if the given address is not a valid `http` or `https` URL
+    ///   with a host, or if it contains user information, a query or a fragment.
     pub fn set_server_address(
         &mut self,
         server_address: Option<String>,
     ) -> Result<(), IllegalArgumentException> {
-        // Validate the server address
-        if let Some(address) = &server_address {
-            if !address.starts_with("http://") && !address.starts_with("https://") {
-                return Err(IllegalArgumentException::new(
-                    "Invalid server address: must start with http:// or https://",
-                ));
-            }
-        }
+        let server_address = match server_address {
+            Some(address) => Some(normalize_server_address(&parse_server_address(&address)?)),
+            None => None,
+        };
 
         self.server_address = server_address;
 
         // Notify listeners about the property change
-        for listener in &self.listeners {
+        for listener in self.listeners.lock().unwrap().iter() {
             listener.on_property_change("serverAddress");
         }
 
@@ -336,7 +441,7 @@ impl ConnectionDetails {
         self.user = user;
 
         // Notify listeners about the property change
-        for listener in &self.listeners {
+        for listener in self.listeners.lock().unwrap().iter() {
             listener.on_property_change("user");
         }
     }
@@ -350,7 +455,7 @@ impl ConnectionDetails {
     /// * `listener`: An object that will receive the events as documented in the `ClientListener`
     ///   interface.
     pub fn add_listener(&mut self, listener: Box<dyn ClientListener>) {
-        self.listeners.push(listener);
+        self.listeners.get_mut().unwrap().push(listener);
     }
 
     /// Removes a listener from the `ConnectionDetails` instance so that it will not receive events
@@ -376,22 +481,62 @@ impl Debug for ConnectionDetails {
             .field("session_id", &self.session_id)
             .field("user", &self.user)
             .field("password", &self.password)
+            .field("credentials_provider", &self.credentials_provider)
+            .field("reauthentication_handler", &self.reauthentication_handler)
             .finish()
     }
 }
 
-impl Default for ConnectionDetails {
-    fn default() -> Self {
-        ConnectionDetails {
-            adapter_set: None,
-            client_ip: None,
-            server_address: None,
-            server_instance_address: None,
-            server_socket_name: None,
-            session_id: None,
-            user: None,
-            password: None,
-            listeners: Vec::new(),
-        }
+/// Parses and validates a server address: it must be an absolute `http` or `https` URL with a
+/// host, and it can't carry user information, a query or a fragment, which would be silently
+/// dropped when connecting.
+pub(crate) fn parse_server_address(address: &str) -> Result<Url, IllegalArgumentException> {
+    let url = Url::parse(address).map_err(|err| {
+        IllegalArgumentException::new(&format!("Invalid server address '{}': {}", address, err))
+    })?;
+    let invalid = |reason: &str| {
+        Err(IllegalArgumentException::new(&format!(
+            "Invalid server address '{}': {}",
+            address, reason
+        )))
+    };
+    if url.scheme() != "http" && url.scheme() != "https" {
+        return invalid("the scheme must be http or https");
+    }
+    if url.host().is_none() {
+        return invalid("missing host");
+    }
+    if !url.username().is_empty() || url.password().is_some() {
+        return invalid("user information is not allowed, use setUser() and setPassword()");
     }
+    if url.query().is_some() {
+        return invalid("a query is not allowed");
+    }
+    if url.fragment().is_some() {
+        return invalid("a fragment is not allowed");
+    }
+    Ok(url)
+}
+
+/// Serializes a parsed server address without the trailing slashes of its path.
+fn normalize_server_address(url: &Url) -> String {
+    url.as_str().trim_end_matches('/').to_string()
+}
+
+/// Derives the WebSocket endpoint of a server address, switching `http` to `ws` and `https` to
+/// `wss` and keeping host, port and path.
+pub(crate) fn websocket_url(server_address: &Url) -> Url {
+    let mut endpoint = server_address.clone();
+    let scheme = if server_address.scheme() == "https" {
+        "wss"
+    } else {
+        "ws"
+    };
+    // Switching between special schemes is always allowed, and http/ws and https/wss share
+    // their default ports.
+    endpoint
+        .set_scheme(scheme)
+        .expect("Failed to set the WebSocket scheme.");
+    endpoint.set_path(server_address.path().trim_end_matches('/'));
+    endpoint
 }
@@ -0,0 +1,28 @@
+use crate::ls_client::ConnectionType;
+
+use std::time::SystemTime;
+
+/// Description of the connection currently used by a `LightstreamerClient`, obtained through
+/// `LightstreamerClient.connectionInfo()`.
+///
+/// It's meant for operational tooling that has to report exactly how the client is connected:
+/// unlike the settings of `ConnectionDetails` and `ConnectionOptions`, which tell what was
+/// requested, it tells what is actually in use. It's a snapshot, which is not updated afterwards.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ConnectionInfo {
+    /// The transport actually in use, which may differ from the forced one, e.g. when the forced
+    /// transport is not supported by the library.
+    pub transport: ConnectionType,
+    /// The version of the TLCP protocol negotiated with the Server, e.g. "TLCP-2.4.0".
+    pub protocol_version: String,
+    /// The WebSocket address the connection was opened to.
+    pub server_address: String,
+    /// The name of the Server instance serving the session, as notified by the Server, if any.
+    pub server_instance: Option<String>,
+    /// The IP address of the client as seen by the Server, as notified by the Server, if any.
+    pub client_ip: Option<String>,
+    /// The ID of the session bound to the connection.
+    pub session_id: String,
+    /// The time the connection was opened, i.e. the time its WebSocket handshake was completed.
+    pub connected_at: SystemTime,
+}
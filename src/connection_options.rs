@@ -1,9 +1,165 @@
-use crate::error::IllegalArgumentException;
-use crate::ls_client::Transport;
+use crate::clock::Clock;
+use crate::connect_overrides::ConnectOverrides;
+#[cfg(feature = "runtime-tokio")]
+use crate::connector::Connector;
+use crate::dispatcher::WatchdogSettings;
+use crate::error::{IllegalArgumentException, InvalidOptionsException};
+use crate::event_history::DEFAULT_EVENT_HISTORY_SIZE;
+#[cfg(feature = "test-util")]
+use crate::fault_injection::FaultInjector;
+#[cfg(feature = "reqwest")]
+use crate::http_transport::{self, HttpExecutor};
+use crate::ls_client::{LogType, Transport};
 use crate::proxy::Proxy;
+use crate::recording::SessionRecorder;
+use crate::request_interceptor::RequestInterceptor;
+use crate::retry_policy::RetryPolicy;
+use crate::runtime::tungstenite::http::{HeaderMap, HeaderName, HeaderValue};
+use crate::runtime::tungstenite::protocol::WebSocketConfig;
+use crate::session::{OptionChange, OptionChanges, ServerSettings, StreamSettings};
 
 use std::collections::HashMap;
 use std::fmt::{self, Debug, Formatter};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+#[cfg(feature = "tower")]
+use tower::util::BoxCloneSyncService;
+#[cfg(feature = "tower")]
+use tower::{BoxError, Service, ServiceExt};
+
+/// Tower service issuing the requests of the HTTP connections of a `LightstreamerClient`, set
+/// through `ConnectionOptions.setHttpService()`.
+///
+/// Available with the `tower` feature. Any cloneable service taking a `reqwest::Request` to a
+/// `reqwest::Response` can be boxed into an `HttpService` through `HttpService::new()`, typically
+/// a `reqwest::Client` wrapped in the layers of the application, e.g. for retries, rate limits,
+/// authentication headers or logging, which then apply to the session creation and control
+/// requests alike.
+///
+/// # Example
+///
+/// ```
+/// use lightstreamer_client::connection_options::HttpService;
+/// use lightstreamer_client::ls_client::{LightstreamerClient, Transport};
+/// use tower::ServiceBuilder;
+///
+/// let service = ServiceBuilder::new()
+///     .map_request(|mut request: reqwest::Request| {
+///         let token = "Bearer token".parse().unwrap();
+///         request.headers_mut().insert("authorization", token);
+///         request
+///     })
+///     .service(reqwest::Client::new());
+/// let mut client =
+///     LightstreamerClient::new(Some("https://push.example.com"), None, None, None).unwrap();
+/// client
+///     .connection_options
+///     .set_forced_transport(Some(Transport::HttpStreaming));
+/// client
+///     .connection_options
+///     .set_http_service(Some(HttpService::new(service)));
+/// ```
+#[cfg(feature = "tower")]
+#[derive(Clone)]
+pub struct HttpService {
+    inner: BoxCloneSyncService<reqwest::Request, reqwest::Response, BoxError>,
+}
+
+#[cfg(feature = "tower")]
+impl HttpService {
+    /// Boxes the given service.
+    pub fn new<S>(service: S) -> HttpService
+    where
+        S: Service<reqwest::Request, Response = reqwest::Response> + Clone + Send + Sync + 'static,
+        S::Error: Into<BoxError>,
+        S::Future: Send + 'static,
+    {
+        HttpService {
+            inner: BoxCloneSyncService::new(service.map_err(Into::into)),
+        }
+    }
+
+    /// Issues a request, once the service is ready.
+    pub(crate) async fn call(
+        self,
+        request: reqwest::Request,
+    ) -> Result<reqwest::Response, BoxError> {
+        self.inner.oneshot(request).await
+    }
+}
+
+#[cfg(feature = "tower")]
+impl Debug for HttpService {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HttpService").finish_non_exhaustive()
+    }
+}
+
+/// What happens to the messages sent through `LightstreamerClient.sendMessage()` whose request was
+/// not acknowledged by the Server yet when the connection is lost.
+///
+/// Messages still waiting to be sent are kept for the next connection, while messages already
+/// acknowledged keep waiting for their outcome on a recovered session, unless the messages are
+/// aborted on disconnection.
+///
+/// See also `ConnectionOptions.setMessageRecoveryPolicy()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MessageRecoveryPolicy {
+    /// All the pending messages are aborted as soon as the connection is lost, notifying
+    /// `ClientMessageListener.onAbort()`.
+    AbortOnDisconnect,
+    /// Unacknowledged messages are sent again, with their original progressive number, once the
+    /// session is recovered, so that the Server can discard the ones it had already received. If
+    /// the session can't be recovered, they are aborted, as the Server may have processed them.
+    #[default]
+    ResendOnRecovery,
+    /// As `ResendOnRecovery`, but if the session can't be recovered, unacknowledged messages are
+    /// sent again on the new session as well, in their original order within each sequence. This
+    /// may cause a message to be processed twice.
+    ResendAlways,
+}
+
+/// How the buffers collecting the data received from the Server grow when a message doesn't fit
+/// in them.
+///
+/// The buffers are allocated with the size set through `ConnectionOptions.setReadBufferSize()`
+/// and reused across reads. The policy applies to the buffers of the HTTP connections, while the
+/// buffer of a WebSocket connection is managed by the WebSocket implementation, which reserves
+/// the configured size on each read.
+///
+/// See also `ConnectionOptions.setReadBufferGrowth()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ReadBufferGrowth {
+    /// The buffer at least doubles its capacity, so that long messages cause few reallocations,
+    /// and keeps it for the next reads.
+    #[default]
+    Doubling,
+    /// The buffer grows just enough to hold the data received, saving memory at the cost of
+    /// more reallocations, and keeps its capacity for the next reads.
+    Exact,
+    /// The buffer grows as with `Doubling`, but shrinks back to the configured size as soon as
+    /// the long message has been received, so that occasional long messages, e.g. large
+    /// snapshots, don't hold memory afterwards.
+    Shrinking,
+}
+
+/// Settings of the buffers collecting the data received from the Server, resolved from
+/// `ConnectionOptions` when `connect()` is called.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub(crate) struct ReadBufferSettings {
+    /// Initial capacity of the buffers, in bytes.
+    pub(crate) size: usize,
+    pub(crate) growth: ReadBufferGrowth,
+}
+
+/// Extra HTTP headers to be sent to the Server, resolved from `ConnectionOptions` when
+/// `connect()` is called.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct ExtraHeaders {
+    pub(crate) headers: HeaderMap,
+    /// Whether the headers are only sent with the requests that create a session.
+    pub(crate) session_creation_only: bool,
+}
 
 /// Used by LightstreamerClient to provide an extra connection properties data object.
 /// Data struct that contains the policy settings used to connect to a Lightstreamer Server.
@@ -11,23 +167,49 @@ use std::fmt::{self, Debug, Formatter};
 ///
 /// See also `LightstreamerClient`
 pub struct ConnectionOptions {
+    clock: Option<Arc<dyn Clock>>,
+    #[cfg(feature = "runtime-tokio")]
+    connector: Option<Connector>,
     content_length: Option<u64>,
+    dispatch_watchdog_timeout: Option<u64>,
+    event_history_size: usize,
+    #[cfg(feature = "test-util")]
+    fault_injector: Option<Arc<FaultInjector>>,
     first_retry_max_delay: u64,
     forced_transport: Option<Transport>,
+    #[cfg(feature = "reqwest")]
+    http_client: Option<reqwest::Client>,
+    #[cfg(feature = "tower")]
+    http_service: Option<HttpService>,
     http_extra_headers: Option<HashMap<String, String>>,
     http_extra_headers_on_session_creation_only: bool,
     idle_timeout: u64,
     keepalive_interval: u64,
+    max_frame_size: Option<usize>,
+    max_message_size: Option<usize>,
+    max_relogin_attempts: u32,
+    max_retry_delay: u64,
+    message_recovery_policy: MessageRecoveryPolicy,
+    option_changes: OptionChanges,
+    ping_interval: u64,
     polling_interval: u64,
+    pong_timeout: u64,
     proxy: Option<Proxy>,
+    read_buffer_growth: ReadBufferGrowth,
+    read_buffer_size: Option<usize>,
     real_max_bandwidth: Option<u64>,
     reconnect_timeout: u64,
+    request_interceptors: Vec<Arc<dyn RequestInterceptor>>,
     requested_max_bandwidth: Option<f64>,
     retry_delay: u64,
+    retry_policy: Option<Arc<dyn RetryPolicy>>,
     reverse_heartbeat_interval: u64,
     server_instance_address_ignored: bool,
+    server_settings: Arc<Mutex<ServerSettings>>,
+    session_recorder: Option<Arc<SessionRecorder>>,
     session_recovery_timeout: u64,
     slowing_enabled: bool,
+    stalled_subscription_isolation_enabled: bool,
     stalled_timeout: u64,
     send_sync: bool,
     _reduce_head: bool,
@@ -40,24 +222,50 @@ impl ConnectionOptions {
     /// Creates a new instance of `ConnectionOptions` with default values.
     pub fn new() -> Self {
         ConnectionOptions {
+            clock: None,
+            #[cfg(feature = "runtime-tokio")]
+            connector: None,
             content_length: None,
+            dispatch_watchdog_timeout: None,
+            event_history_size: DEFAULT_EVENT_HISTORY_SIZE,
+            #[cfg(feature = "test-util")]
+            fault_injector: None,
             first_retry_max_delay: 100,
             forced_transport: None,
+            #[cfg(feature = "reqwest")]
+            http_client: None,
+            #[cfg(feature = "tower")]
+            http_service: None,
             http_extra_headers: None,
             http_extra_headers_on_session_creation_only: false,
             idle_timeout: 19000,
             keepalive_interval: 0,
+            max_frame_size: None,
+            max_message_size: None,
+            max_relogin_attempts: 0,
+            max_retry_delay: 60000,
+            message_recovery_policy: MessageRecoveryPolicy::default(),
+            option_changes: OptionChanges::default(),
+            ping_interval: 0,
             polling_interval: 0,
+            pong_timeout: 5000,
             proxy: None,
+            read_buffer_growth: ReadBufferGrowth::default(),
+            read_buffer_size: None,
             real_max_bandwidth: None,
             reconnect_timeout: 3000,
+            request_interceptors: Vec::new(),
             requested_max_bandwidth: None,
             retry_delay: 4000,
+            retry_policy: None,
             reverse_heartbeat_interval: 0,
+            session_recorder: None,
             session_recovery_timeout: 15000,
             slowing_enabled: false,
+            stalled_subscription_isolation_enabled: false,
             stalled_timeout: 2000,
             server_instance_address_ignored: false,
+            server_settings: Arc::default(),
             send_sync: true,
             _reduce_head: false,
             supported_diffs: None,
@@ -66,6 +274,30 @@ impl ConnectionOptions {
         }
     }
 
+    /// Inquiry method that gets the clock used by the timers of the client.
+    ///
+    /// # Returns
+    ///
+    /// The custom clock or `None` if a `RuntimeClock` is in use.
+    ///
+    /// See also `setClock()`
+    pub fn get_clock(&self) -> Option<&Arc<dyn Clock>> {
+        self.clock.as_ref()
+    }
+
+    /// Inquiry method that gets the connector whose resources are shared with other clients, if
+    /// any.
+    ///
+    /// # Returns
+    ///
+    /// The connector or `None` if the client uses resources of its own.
+    ///
+    /// See also `setConnector()`
+    #[cfg(feature = "runtime-tokio")]
+    pub fn get_connector(&self) -> Option<&Connector> {
+        self.connector.as_ref()
+    }
+
     /// Inquiry method that gets the length expressed in bytes to be used by the Server for the
     /// response body on a HTTP stream connection.
     ///
@@ -78,6 +310,42 @@ impl ConnectionOptions {
         self.content_length
     }
 
+    /// Inquiry method that gets the time after which a listener call that didn't return is
+    /// reported as stalled by the dispatch watchdog.
+    ///
+    /// # Returns
+    ///
+    /// The timeout (in milliseconds) of the dispatch watchdog, or `None` if the watchdog is
+    /// disabled.
+    ///
+    /// See also `setDispatchWatchdogTimeout()`
+    pub fn get_dispatch_watchdog_timeout(&self) -> Option<u64> {
+        self.dispatch_watchdog_timeout
+    }
+
+    /// Inquiry method that gets the maximum number of recent events kept by the client.
+    ///
+    /// # Returns
+    ///
+    /// The maximum number of events kept, or 0 if the history is disabled.
+    ///
+    /// See also `setEventHistorySize()`
+    pub fn get_event_history_size(&self) -> usize {
+        self.event_history_size
+    }
+
+    /// Inquiry method that gets the injector of faults in the connections of the client, if any.
+    ///
+    /// # Returns
+    ///
+    /// The fault injector or `None` if the connections are not altered.
+    ///
+    /// See also `setFaultInjector()`
+    #[cfg(feature = "test-util")]
+    pub fn get_fault_injector(&self) -> Option<&Arc<FaultInjector>> {
+        self.fault_injector.as_ref()
+    }
+
     /// Inquiry method that gets the maximum time to wait before trying a new connection to the
     /// Server in case the previous one is unexpectedly closed while correctly working.
     ///
@@ -101,6 +369,32 @@ impl ConnectionOptions {
         self.forced_transport.as_ref()
     }
 
+    /// Inquiry method that gets the `reqwest` client the HTTP connections are opened with, if
+    /// any.
+    ///
+    /// # Returns
+    ///
+    /// The HTTP client or `None` if a client with the default configuration is used.
+    ///
+    /// See also `setHttpClient()`
+    #[cfg(feature = "reqwest")]
+    pub fn get_http_client(&self) -> Option<&reqwest::Client> {
+        self.http_client.as_ref()
+    }
+
+    /// Inquiry method that gets the tower service the requests of the HTTP connections are
+    /// issued through, if any.
+    ///
+    /// # Returns
+    ///
+    /// The HTTP service or `None` if the requests are issued through the HTTP client.
+    ///
+    /// See also `setHttpService()`
+    #[cfg(feature = "tower")]
+    pub fn get_http_service(&self) -> Option<&HttpService> {
+        self.http_service.as_ref()
+    }
+
     /// Inquiry method that gets the Map object containing the extra headers to be sent to the server.
     ///
     /// # Returns
@@ -142,7 +436,10 @@ impl ConnectionOptions {
     ///
     /// See also `setKeepaliveInterval()`
     pub fn get_keepalive_interval(&self) -> u64 {
-        self.keepalive_interval
+        match self.server_settings.lock().unwrap().keepalive_interval {
+            Some(keepalive_interval) => keepalive_interval.as_millis() as u64,
+            None => self.keepalive_interval,
+        }
     }
 
     /// Inquiry method that gets the polling interval used for polling connections.
@@ -161,6 +458,116 @@ impl ConnectionOptions {
         self.polling_interval
     }
 
+    /// Inquiry method that gets the maximum size of a single WebSocket frame received from the
+    /// Server.
+    ///
+    /// # Returns
+    ///
+    /// The maximum size, in bytes, or `None` if the default limit of the WebSocket implementation
+    /// (16 MiB) applies.
+    ///
+    /// See also `setMaxFrameSize()`
+    pub fn get_max_frame_size(&self) -> Option<usize> {
+        self.max_frame_size
+    }
+
+    /// Inquiry method that gets the maximum size of a whole WebSocket message received from the
+    /// Server, possibly split over several frames.
+    ///
+    /// # Returns
+    ///
+    /// The maximum size, in bytes, or `None` if the default limit of the WebSocket implementation
+    /// (64 MiB) applies.
+    ///
+    /// See also `setMaxMessageSize()`
+    pub fn get_max_message_size(&self) -> Option<usize> {
+        self.max_message_size
+    }
+
+    /// Inquiry method that gets the maximum number of consecutive times the credentials are
+    /// refreshed through the reauthentication handler after the Server rejects them.
+    ///
+    /// # Returns
+    ///
+    /// The maximum number of consecutive re-login attempts, or 0 if the rejection of the
+    /// credentials is always final.
+    ///
+    /// See also `setMaxReloginAttempts()`
+    pub fn get_max_relogin_attempts(&self) -> u32 {
+        self.max_relogin_attempts
+    }
+
+    /// Inquiry method that gets the upper bound of the delay between connection attempts that
+    /// keep failing.
+    ///
+    /// # Returns
+    ///
+    /// The maximum time (in milliseconds) to wait before trying a new connection.
+    ///
+    /// See also `setMaxRetryDelay()`
+    pub fn get_max_retry_delay(&self) -> u64 {
+        self.max_retry_delay
+    }
+
+    /// Inquiry method that gets the policy applied to the messages still unacknowledged when the
+    /// connection is lost.
+    ///
+    /// # Returns
+    ///
+    /// The policy applied to the unacknowledged messages.
+    ///
+    /// See also `setMessageRecoveryPolicy()`
+    pub fn get_message_recovery_policy(&self) -> MessageRecoveryPolicy {
+        self.message_recovery_policy
+    }
+
+    /// Inquiry method that gets the interval between two WebSocket pings sent by the client.
+    ///
+    /// # Returns
+    ///
+    /// The time, expressed in milliseconds, between two pings, or 0 if no pings are sent.
+    ///
+    /// See also `setPingInterval()`
+    pub fn get_ping_interval(&self) -> u64 {
+        self.ping_interval
+    }
+
+    /// Inquiry method that gets the time the client waits for the answer to a WebSocket ping
+    /// before considering the connection stalled.
+    ///
+    /// # Returns
+    ///
+    /// The time, expressed in milliseconds, allowed for each pong to arrive.
+    ///
+    /// See also `setPongTimeout()`
+    pub fn get_pong_timeout(&self) -> u64 {
+        self.pong_timeout
+    }
+
+    /// Inquiry method that gets the policy by which the buffers collecting the data received
+    /// from the Server grow.
+    ///
+    /// # Returns
+    ///
+    /// The growth policy.
+    ///
+    /// See also `setReadBufferGrowth()`
+    pub fn get_read_buffer_growth(&self) -> ReadBufferGrowth {
+        self.read_buffer_growth
+    }
+
+    /// Inquiry method that gets the size of the buffers collecting the data received from the
+    /// Server.
+    ///
+    /// # Returns
+    ///
+    /// The size, in bytes, or `None` if the default size (128 KiB) applies.
+    ///
+    /// See also `setReadBufferSize()`
+    pub fn get_read_buffer_size(&self) -> Option<usize> {
+        self.read_buffer_size
+    }
+
     /// Inquiry method that gets the maximum bandwidth that can be consumed for the data coming
     /// from Lightstreamer Server. This is the actual maximum bandwidth, in contrast with the requested
     /// maximum bandwidth, returned by `get_requested_max_bandwidth()`.
@@ -198,6 +605,17 @@ impl ConnectionOptions {
         self.reconnect_timeout
     }
 
+    /// Inquiry method that gets the chain of interceptors invoked on every outgoing request.
+    ///
+    /// # Returns
+    ///
+    /// The interceptors, in the order they are invoked.
+    ///
+    /// See also `addRequestInterceptor()`
+    pub fn get_request_interceptors(&self) -> &[Arc<dyn RequestInterceptor>] {
+        &self.request_interceptors
+    }
+
     /// Inquiry method that gets the maximum bandwidth that can be consumed for the data coming
     /// from Lightstreamer Server, as requested for this session. The maximum bandwidth limit really
     /// applied by the Server on the session is provided by `get_real_max_bandwidth()`
@@ -227,6 +645,28 @@ impl ConnectionOptions {
         self.retry_delay
     }
 
+    /// Inquiry method that gets the custom retry policy used to reconnect to the Server (if any).
+    ///
+    /// # Returns
+    ///
+    /// The custom retry policy or `None` if the default policy is in use.
+    ///
+    /// See also `setRetryPolicy()`
+    pub fn get_retry_policy(&self) -> Option<&Arc<dyn RetryPolicy>> {
+        self.retry_policy.as_ref()
+    }
+
+    /// Inquiry method that gets the recorder of the frames exchanged with the Server, if any.
+    ///
+    /// # Returns
+    ///
+    /// The session recorder or `None` if the frames are not recorded.
+    ///
+    /// See also `setSessionRecorder()`
+    pub fn get_session_recorder(&self) -> Option<&Arc<SessionRecorder>> {
+        self.session_recorder.as_ref()
+    }
+
     /// Inquiry method that gets the reverse-heartbeat interval expressed in milliseconds. A 0 value
     /// is possible, meaning that the mechanism is disabled.
     ///
@@ -309,6 +749,57 @@ impl ConnectionOptions {
         self.slowing_enabled
     }
 
+    /// Inquiry method that checks if the Subscriptions whose listeners stall are isolated by the
+    /// dispatch watchdog.
+    ///
+    /// # Returns
+    ///
+    /// Whether the Subscriptions whose listeners stall are isolated or not.
+    ///
+    /// See also `setStalledSubscriptionIsolationEnabled()`
+    pub fn is_stalled_subscription_isolation_enabled(&self) -> bool {
+        self.stalled_subscription_isolation_enabled
+    }
+
+    /// Setter method that sets a custom clock, used to measure and await all the delays and
+    /// timeouts of the client, such as the retry delays, the session recovery timeout and the
+    /// client-side conflation intervals. This allows tests to advance virtual time instead of
+    /// waiting for real delays.
+    ///
+    /// `None` (meaning that a `RuntimeClock` is used).
+    ///
+    /// This setting should be performed before calling the `LightstreamerClient.connect()` method;
+    /// a change will be obeyed upon the next call to `connect()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `clock`: The clock to be used. Specify `None` to use the clock of the async runtime.
+    ///
+    /// See also `Clock`
+    pub fn set_clock(&mut self, clock: Option<Arc<dyn Clock>>) {
+        self.clock = clock;
+    }
+
+    /// Setter method that sets a connector, whose runtime, TLS configuration, DNS cache and
+    /// cookie jar are shared by all the clients it is given to.
+    ///
+    /// `None` (meaning that the client spawns its tasks on the runtime `connect()` is called from,
+    /// opens every connection with the default TLS configuration and name resolution, and uses the
+    /// process-wide cookie jar).
+    ///
+    /// This setting should be performed before calling the `LightstreamerClient.connect()` method;
+    /// a change will be obeyed upon the next call to `connect()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `connector`: The connector to be used. Specify `None` to use the defaults.
+    ///
+    /// See also `Connector`
+    #[cfg(feature = "runtime-tokio")]
+    pub fn set_connector(&mut self, connector: Option<Connector>) {
+        self.connector = connector;
+    }
+
     /// Setter method that sets the length in bytes to be used by the Server for the response body
     /// on a stream connection (a minimum length, however, is ensured by the server). After the
     /// content length exhaustion, the connection will be closed and a new bind connection will
@@ -348,6 +839,78 @@ impl ConnectionOptions {
         Ok(())
     }
 
+    /// Setter method that enables the dispatch watchdog, which detects the listener calls that
+    /// don't return within the given time, e.g. because a listener is blocked forever.
+    ///
+    /// All the notifications of a client are delivered one at a time by the same task, so a
+    /// stalled listener silently holds back all the others, and the updates pile up in the
+    /// backpressure buffers. A stalled call can't be interrupted, but the watchdog makes it
+    /// observable: it is logged, accounted in `ClientMetrics.dispatch_stalls` and described by
+    /// `LightstreamerClient.getDispatchStall()` until it returns. Optionally, the Subscription of
+    /// the stalled listener can also be isolated, see `setStalledSubscriptionIsolationEnabled()`.
+    ///
+    /// `None` (meaning that the watchdog is disabled).
+    ///
+    /// This value can be set and changed at any time. The supplied value will be used on the next
+    /// call to `LightstreamerClient.connect()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `dispatch_watchdog_timeout`: the time (in milliseconds) after which a listener call is
+    ///   reported as stalled, or `None` to disable the watchdog.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if a zero value is configured
+    pub fn set_dispatch_watchdog_timeout(
+        &mut self,
+        dispatch_watchdog_timeout: Option<u64>,
+    ) -> Result<(), IllegalArgumentException> {
+        if dispatch_watchdog_timeout == Some(0) {
+            return Err(IllegalArgumentException::new(
+                "Dispatch watchdog timeout cannot be zero",
+            ));
+        }
+        self.dispatch_watchdog_timeout = dispatch_watchdog_timeout;
+        Ok(())
+    }
+
+    /// Setter method that sets the maximum number of recent events kept by the client, returned by
+    /// `LightstreamerClient.recentEvents()`. When the limit is reached, the oldest event is
+    /// discarded.
+    ///
+    /// `DEFAULT_EVENT_HISTORY_SIZE` (256).
+    ///
+    /// This value can be set and changed at any time. The supplied value will be used on the next
+    /// call to `LightstreamerClient.connect()`; reducing it discards the oldest events in excess.
+    ///
+    /// # Parameters
+    ///
+    /// * `event_history_size`: the maximum number of events kept, or 0 to disable the history.
+    pub fn set_event_history_size(&mut self, event_history_size: usize) {
+        self.event_history_size = event_history_size;
+    }
+
+    /// Setter method that sets an injector of faults in the connections of the client, such as
+    /// dropped connections and delayed, truncated or corrupted frames, so that tests can exercise
+    /// the recovery and parser resilience code paths.
+    ///
+    /// `None` (meaning that the connections are not altered).
+    ///
+    /// This setting should be performed before calling the `LightstreamerClient.connect()` method;
+    /// a change will be obeyed upon the next call to `connect()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `fault_injector`: The fault injector to be used. Specify `None` to leave the connections
+    ///   unaltered.
+    ///
+    /// See also `FaultInjector`
+    #[cfg(feature = "test-util")]
+    pub fn set_fault_injector(&mut self, fault_injector: Option<Arc<FaultInjector>>) {
+        self.fault_injector = fault_injector;
+    }
+
     /// Setter method that sets the maximum time to wait before trying a new connection to the Server
     /// in case the previous one is unexpectedly closed while correctly working. The new connection
     /// may be either the opening of a new session or an attempt to recovery the current session,
@@ -403,7 +966,10 @@ impl ConnectionOptions {
     /// None (full Stream-Sense enabled).
     ///
     /// This method can be called at any time. If called while the client is connecting or connected
-    /// it will instruct to switch connection type to match the given configuration.
+    /// it will instruct to switch connection type to match the given configuration: the current
+    /// session is rebound on a new connection of the requested type. Connections over HTTP are
+    /// only supported with the `reqwest` feature: without it, a request to switch to HTTP is
+    /// logged and the current connection is kept.
     ///
     /// A change to this setting will be notified through a call to `ClientListener.onPropertyChange()`
     /// with argument "forcedTransport" on any `ClientListener` listening to the related `LightstreamerClient`.
@@ -438,6 +1004,57 @@ impl ConnectionOptions {
     /// * `IllegalArgumentException`: if the given value is not in the list of the admitted ones.
     pub fn set_forced_transport(&mut self, forced_transport: Option<Transport>) {
         self.forced_transport = forced_transport;
+        self.option_changes
+            .push(OptionChange::ForcedTransport(forced_transport));
+    }
+
+    /// Setter method that sets the `reqwest` client the HTTP streaming and polling connections are
+    /// opened with, so that they share the connection pool, proxy and TLS configuration of the
+    /// rest of the application.
+    ///
+    /// The client only carries the requests over HTTP: the extra headers and the cookies of the
+    /// library are added to each of them, while the cookies set by the Server are only kept if
+    /// the client has a cookie store of its own.
+    ///
+    /// `None` (meaning that a client with the default configuration is used).
+    ///
+    /// This setting should be performed before calling the `LightstreamerClient.connect()` method;
+    /// a change will be obeyed upon the next call to `connect()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `http_client`: The HTTP client to be used. Specify `None` to use the default one.
+    ///
+    /// See also `setForcedTransport()`
+    #[cfg(feature = "reqwest")]
+    pub fn set_http_client(&mut self, http_client: Option<reqwest::Client>) {
+        self.http_client = http_client;
+    }
+
+    /// Setter method that sets the tower service the requests of the HTTP streaming and polling
+    /// connections are issued through, in place of the HTTP client, so that the layers of the
+    /// application, e.g. for retries, rate limits, authentication headers or logging, apply to
+    /// them.
+    ///
+    /// Every request, from the session creation ones to the control ones, goes through the
+    /// service, which has to return the response as soon as its headers are received: the body
+    /// of the session streams is only complete when the connection ends.
+    ///
+    /// `None` (meaning that the requests are issued through the HTTP client).
+    ///
+    /// This setting should be performed before calling the `LightstreamerClient.connect()` method;
+    /// a change will be obeyed upon the next call to `connect()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `http_service`: The HTTP service to be used. Specify `None` to use the HTTP client.
+    ///
+    /// See also `HttpService`
+    ///
+    /// See also `setHttpClient()`
+    #[cfg(feature = "tower")]
+    pub fn set_http_service(&mut self, http_service: Option<HttpService>) {
+        self.http_service = http_service;
     }
 
     /// Setter method that enables/disables the setting of extra HTTP headers to all the request
@@ -545,13 +1162,18 @@ impl ConnectionOptions {
     /// 0 (meaning that the Server will send keepalive packets based on its own configuration).
     ///
     /// The keepalive interval should be set before calling the `LightstreamerClient.connect()`
-    /// method. However, the value can be changed at any time: the supplied value will be used
-    /// for the next streaming connection (either a bind or a brand new session). Note that, after
-    /// a connection, the value may be changed to the one imposed by the Server.
+    /// method. However, the value can be changed at any time: if a session is active, its streaming
+    /// connection is rebound on the fly to request the new value, which will also be used for the
+    /// next streaming connections (either a bind or a brand new session). Note that, after a
+    /// connection, the value may be changed to the one imposed by the Server.
     ///
     /// A change to this setting will be notified through a call to `ClientListener.onPropertyChange()`
     /// with argument "keepaliveInterval" on any `ClientListener` listening to the related `LightstreamerClient`.
     ///
+    /// When not 0, the value should be greater than the stalled timeout and not lower than the
+    /// reconnect timeout; this is checked by `validate()` when `LightstreamerClient.connect()`
+    /// is called, so that the options can be set in any order.
+    ///
     /// # Parameters
     ///
     /// * `keepalive_interval`: the keepalive interval time (in milliseconds) to set, or 0.
@@ -567,19 +1189,11 @@ impl ConnectionOptions {
         &mut self,
         keepalive_interval: u64,
     ) -> Result<(), IllegalArgumentException> {
-        if keepalive_interval == 0 {
-            self.keepalive_interval = keepalive_interval;
-            return Ok(());
-        }
-
-        if keepalive_interval < self.stalled_timeout || keepalive_interval < self.reconnect_timeout
-        {
-            return Err(IllegalArgumentException::new(
-                "Keepalive interval should be greater than or equal to stalled timeout and reconnect timeout",
-            ));
-        }
-
         self.keepalive_interval = keepalive_interval;
+        // The value used by the Server is known again on the next connection.
+        self.server_settings.lock().unwrap().keepalive_interval = None;
+        self.option_changes
+            .push(OptionChange::KeepaliveInterval(millis(keepalive_interval)));
         Ok(())
     }
 
@@ -616,6 +1230,10 @@ impl ConnectionOptions {
     /// A change to this setting will be notified through a call to `ClientListener.onPropertyChange()`
     /// with argument "pollingInterval" on any `ClientListener` listening to the related `LightstreamerClient`.
     ///
+    /// When not 0, the value should not be lower than the idle timeout; this is checked by
+    /// `validate()` when `LightstreamerClient.connect()` is called, so that the options can be
+    /// set in any order.
+    ///
     /// # Parameters
     ///
     /// * `polling_interval`: The time (in milliseconds) between subsequent polling requests. Zero
@@ -629,18 +1247,195 @@ impl ConnectionOptions {
         &mut self,
         polling_interval: u64,
     ) -> Result<(), IllegalArgumentException> {
-        if polling_interval == 0 {
-            self.polling_interval = polling_interval;
-            return Ok(());
+        self.polling_interval = polling_interval;
+        Ok(())
+    }
+
+    /// Setter method that sets the maximum size of a single WebSocket frame received from the
+    /// Server. A larger frame is reported through `ClientListener.onConnectionError()`, then the
+    /// connection is closed and the session is bound to a new one after the delay of the retry
+    /// policy, which grows while the Server keeps sending oversized messages.
+    ///
+    /// None (meaning that the default limit of the WebSocket implementation, 16 MiB, applies).
+    ///
+    /// This value can be set and changed at any time. The supplied value will be used for the
+    /// next connection. It has no effect in browsers, which enforce their own limits.
+    ///
+    /// # Parameters
+    ///
+    /// * `max_frame_size`: the maximum size, in bytes, or `None` to apply the default limit.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if a zero value is configured
+    ///
+    /// See also `setMaxMessageSize()`
+    pub fn set_max_frame_size(
+        &mut self,
+        max_frame_size: Option<usize>,
+    ) -> Result<(), IllegalArgumentException> {
+        if max_frame_size == Some(0) {
+            return Err(IllegalArgumentException::new(
+                "Max frame size cannot be zero",
+            ));
         }
+        self.max_frame_size = max_frame_size;
+        Ok(())
+    }
 
-        if polling_interval < self.idle_timeout {
+    /// Setter method that sets the maximum size of a whole WebSocket message received from the
+    /// Server, possibly split over several frames. A larger message is reported through
+    /// `ClientListener.onConnectionError()`, then the connection is closed and the session is
+    /// bound to a new one after the delay of the retry policy, which grows while the Server keeps
+    /// sending oversized messages.
+    ///
+    /// None (meaning that the default limit of the WebSocket implementation, 64 MiB, applies).
+    ///
+    /// This value can be set and changed at any time. The supplied value will be used for the
+    /// next connection. It has no effect in browsers, which enforce their own limits.
+    ///
+    /// # Parameters
+    ///
+    /// * `max_message_size`: the maximum size, in bytes, or `None` to apply the default limit.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if a zero value is configured
+    ///
+    /// See also `setMaxFrameSize()`
+    pub fn set_max_message_size(
+        &mut self,
+        max_message_size: Option<usize>,
+    ) -> Result<(), IllegalArgumentException> {
+        if max_message_size == Some(0) {
             return Err(IllegalArgumentException::new(
-                "Polling interval should be greater than or equal to idle timeout",
+                "Max message size cannot be zero",
             ));
         }
+        self.max_message_size = max_message_size;
+        Ok(())
+    }
 
-        self.polling_interval = polling_interval;
+    /// Setter method that sets the maximum number of consecutive times the credentials are
+    /// refreshed after the Server rejects them. When a session creation is refused because of
+    /// wrong credentials (`CONERR` with cause code 1), the reauthentication handler set through
+    /// `ConnectionDetails.setReauthenticationHandler()` is invoked and the session creation is
+    /// retried with the credentials it supplies, as dictated by the configured `RetryPolicy`. Once
+    /// the attempts are exhausted, or if no handler is set, the refusal is notified through
+    /// `ClientListener.onServerError()` and the client stops connecting. The count starts over
+    /// whenever a session is created.
+    ///
+    /// 0 (the rejection of the credentials is always final).
+    ///
+    /// This value can be set and changed at any time. The supplied value will be used from the
+    /// next call to `LightstreamerClient.connect()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `max_relogin_attempts`: the maximum number of consecutive re-login attempts.
+    ///
+    /// See also `ReauthenticationHandler`
+    pub fn set_max_relogin_attempts(&mut self, max_relogin_attempts: u32) {
+        self.max_relogin_attempts = max_relogin_attempts;
+    }
+
+    /// Setter method that sets the upper bound of the delay between connection attempts that keep
+    /// failing. After the first failed attempts, which wait for the time set by `setRetryDelay()`,
+    /// the delay doubles at each further failure, with a random jitter, until this bound is
+    /// reached. This prevents a large number of clients from flooding a Server that is not
+    /// available.
+    ///
+    /// 60000 (1 minute).
+    ///
+    /// This value can be set and changed at any time. The supplied value will be used from the
+    /// next call to `LightstreamerClient.connect()`. It has no effect when a custom retry policy is
+    /// set through `setRetryPolicy()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `max_retry_delay`: the maximum time (in milliseconds) to wait before trying a new
+    ///   connection. A value lower than the retry delay disables the growth of the delay.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if a zero value is configured
+    ///
+    /// See also `setRetryDelay()`
+    pub fn set_max_retry_delay(
+        &mut self,
+        max_retry_delay: u64,
+    ) -> Result<(), IllegalArgumentException> {
+        if max_retry_delay == 0 {
+            return Err(IllegalArgumentException::new(
+                "Max retry delay cannot be zero",
+            ));
+        }
+        self.max_retry_delay = max_retry_delay;
+        Ok(())
+    }
+
+    /// Setter method that sets the policy applied to the messages sent through
+    /// `LightstreamerClient.sendMessage()` whose request was not acknowledged by the Server yet
+    /// when the connection is lost. Such messages can be sent again, with the same sequence and
+    /// progressive number, on the recovered session or even on a new one, or they can be aborted.
+    ///
+    /// `MessageRecoveryPolicy::ResendOnRecovery`.
+    ///
+    /// This value can be set and changed at any time. The supplied value will be used from the
+    /// next call to `LightstreamerClient.connect()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `message_recovery_policy`: the policy applied to the unacknowledged messages.
+    ///
+    /// See also `setSessionRecoveryTimeout()`
+    pub fn set_message_recovery_policy(&mut self, message_recovery_policy: MessageRecoveryPolicy) {
+        self.message_recovery_policy = message_recovery_policy;
+    }
+
+    /// Setter method that sets the interval between two WebSocket pings sent by the client on the
+    /// stream connection. The time elapsed until each pong is received is tracked in the
+    /// `pingRtt` of `LightstreamerClient.getMetrics()`, while a pong missing for longer than
+    /// `getPongTimeout()` is taken as a sign that the connection is stalled: the connection is
+    /// then closed and the session recovered as after any other connection issue.
+    ///
+    /// Unlike keepalives, pings probe the connection in both directions and can detect
+    /// half-open connections even while the Server has no data to send.
+    ///
+    /// 0 (meaning that no pings are sent).
+    ///
+    /// This value can be set and changed at any time. The supplied value will be used for the
+    /// next connection.
+    ///
+    /// # Parameters
+    ///
+    /// * `ping_interval`: the time (in milliseconds) between two pings, or 0.
+    ///
+    /// See also `setPongTimeout()`
+    pub fn set_ping_interval(&mut self, ping_interval: u64) {
+        self.ping_interval = ping_interval;
+    }
+
+    /// Setter method that sets the time the client waits for the answer to a WebSocket ping
+    /// before considering the connection stalled. See `setPingInterval()` for details.
+    ///
+    /// 5000 (5 seconds).
+    ///
+    /// This value can be set and changed at any time. The supplied value will be used for the
+    /// next connection.
+    ///
+    /// # Parameters
+    ///
+    /// * `pong_timeout`: the time (in milliseconds) allowed for each pong to arrive.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if a zero value is configured
+    pub fn set_pong_timeout(&mut self, pong_timeout: u64) -> Result<(), IllegalArgumentException> {
+        if pong_timeout == 0 {
+            return Err(IllegalArgumentException::new("Pong timeout cannot be zero"));
+        }
+        self.pong_timeout = pong_timeout;
         Ok(())
     }
 
@@ -662,6 +1457,55 @@ impl ConnectionOptions {
         self.proxy = proxy;
     }
 
+    /// Setter method that sets the policy by which the buffers collecting the data received from
+    /// the Server grow when a message doesn't fit in them.
+    ///
+    /// `ReadBufferGrowth::Doubling`.
+    ///
+    /// This value can be set and changed at any time. The supplied value will be used for the
+    /// next connection.
+    ///
+    /// # Parameters
+    ///
+    /// * `read_buffer_growth`: the growth policy.
+    ///
+    /// See also `setReadBufferSize()`
+    pub fn set_read_buffer_growth(&mut self, read_buffer_growth: ReadBufferGrowth) {
+        self.read_buffer_growth = read_buffer_growth;
+    }
+
+    /// Setter method that sets the size of the buffers collecting the data received from the
+    /// Server, which are reused across reads. High-throughput deployments can set a larger size
+    /// to read more data with each system call and reallocate the buffers less often, while
+    /// deployments with many clients can set a smaller size to save memory.
+    ///
+    /// None (meaning that the default size, 128 KiB, applies).
+    ///
+    /// This value can be set and changed at any time. The supplied value will be used for the
+    /// next connection. It has no effect in browsers, which manage their own buffers.
+    ///
+    /// # Parameters
+    ///
+    /// * `read_buffer_size`: the size, in bytes, or `None` to apply the default size.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if a zero value is configured
+    ///
+    /// See also `setReadBufferGrowth()`
+    pub fn set_read_buffer_size(
+        &mut self,
+        read_buffer_size: Option<usize>,
+    ) -> Result<(), IllegalArgumentException> {
+        if read_buffer_size == Some(0) {
+            return Err(IllegalArgumentException::new(
+                "Read buffer size cannot be zero",
+            ));
+        }
+        self.read_buffer_size = read_buffer_size;
+        Ok(())
+    }
+
     /// Setter method that sets the time the client, after entering "STALLED" status, is allowed
     /// to keep waiting for a keepalive packet or any data on a stream connection, before disconnecting
     /// and trying to reconnect to the Server. The new connection may be either the opening of
@@ -700,6 +1544,47 @@ impl ConnectionOptions {
         Ok(())
     }
 
+    /// Setter method that adds an interceptor at the end of the chain invoked on every outgoing
+    /// request, from the WebSocket handshakes to the session creation, control and message
+    /// requests, so that it can change their parameters and headers before they are sent.
+    ///
+    /// No interceptors (meaning that the requests are sent as built by the library).
+    ///
+    /// This setting should be performed before calling the `LightstreamerClient.connect()` method;
+    /// a change will be obeyed upon the next call to `connect()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `request_interceptor`: The interceptor to be invoked after the ones already added.
+    ///
+    /// See also `RequestInterceptor`
+    ///
+    /// See also `setRequestInterceptors()`
+    pub fn add_request_interceptor(&mut self, request_interceptor: Arc<dyn RequestInterceptor>) {
+        self.request_interceptors.push(request_interceptor);
+    }
+
+    /// Setter method that replaces the whole chain of interceptors invoked on every outgoing
+    /// request.
+    ///
+    /// No interceptors (meaning that the requests are sent as built by the library).
+    ///
+    /// This setting should be performed before calling the `LightstreamerClient.connect()` method;
+    /// a change will be obeyed upon the next call to `connect()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `request_interceptors`: The interceptors, in the order they are to be invoked. Specify
+    ///   an empty vector to remove all the interceptors.
+    ///
+    /// See also `addRequestInterceptor()`
+    pub fn set_request_interceptors(
+        &mut self,
+        request_interceptors: Vec<Arc<dyn RequestInterceptor>>,
+    ) {
+        self.request_interceptors = request_interceptors;
+    }
+
     /// Setter method that sets the maximum bandwidth expressed in kilobits/s that can be consumed
     /// for the data coming from Lightstreamer Server. A limit on bandwidth may already be posed
     /// by the Metadata Adapter, but the client can furtherly restrict this limit. The limit applies
@@ -747,6 +1632,8 @@ impl ConnectionOptions {
         }
 
         self.requested_max_bandwidth = max_bandwidth;
+        self.option_changes
+            .push(OptionChange::RequestedMaxBandwidth(max_bandwidth));
         Ok(())
     }
 
@@ -779,7 +1666,8 @@ impl ConnectionOptions {
     ///   scratch.
     ///
     /// This setting imposes only a minimum delay. In order to avoid network congestion, the library
-    /// may use a longer delay if the issue preventing the establishment of a session persists.
+    /// may use a longer delay if the issue preventing the establishment of a session persists, up
+    /// to the time set by `setMaxRetryDelay()`.
     ///
     /// 4000 (4 seconds).
     ///
@@ -797,6 +1685,8 @@ impl ConnectionOptions {
     /// * `IllegalArgumentException`: if a negative or zero value is configured
     ///
     /// See also `setFirstRetryMaxDelay()`
+    ///
+    /// See also `setMaxRetryDelay()`
     pub fn set_retry_delay(&mut self, retry_delay: u64) -> Result<(), IllegalArgumentException> {
         if retry_delay == 0 {
             return Err(IllegalArgumentException::new("Retry delay cannot be zero"));
@@ -806,6 +1696,43 @@ impl ConnectionOptions {
         Ok(())
     }
 
+    /// Setter method that sets a custom policy to decide how long to wait before each new
+    /// connection attempt and when to stop trying. This allows, for instance, exponential backoff
+    /// with a cap, a different jitter strategy or a limit on the number of attempts.
+    ///
+    /// `None` (meaning that a `DefaultRetryPolicy` based on `getRetryDelay()` and
+    /// `getFirstRetryMaxDelay()` is used). When a custom policy is set, those two settings are
+    /// ignored.
+    ///
+    /// This setting should be performed before calling the `LightstreamerClient.connect()` method;
+    /// a change will be obeyed upon the next call to `connect()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `retry_policy`: The retry policy to be used. Specify `None` to use the default policy.
+    ///
+    /// See also `RetryPolicy`
+    pub fn set_retry_policy(&mut self, retry_policy: Option<Arc<dyn RetryPolicy>>) {
+        self.retry_policy = retry_policy;
+    }
+
+    /// Setter method that sets a recorder of all the frames exchanged with the Server, so that a
+    /// session can be reproduced offline through `LightstreamerClient.replay()`.
+    ///
+    /// `None` (meaning that nothing is recorded).
+    ///
+    /// This setting should be performed before calling the `LightstreamerClient.connect()` method;
+    /// a change will be obeyed upon the next call to `connect()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `session_recorder`: The recorder to be used. Specify `None` to stop recording.
+    ///
+    /// See also `SessionRecorder`
+    pub fn set_session_recorder(&mut self, session_recorder: Option<Arc<SessionRecorder>>) {
+        self.session_recorder = session_recorder;
+    }
+
     /// Setter method that enables/disables the reverse-heartbeat mechanism by setting the heartbeat
     /// interval. If the given value (expressed in milliseconds) equals 0 then the reverse-heartbeat
     /// mechanism will be disabled; otherwise if the given value is greater than 0 the mechanism
@@ -844,6 +1771,10 @@ impl ConnectionOptions {
     /// with argument "reverseHeartbeatInterval" on any `ClientListener` listening to the related
     /// `LightstreamerClient`.
     ///
+    /// When not 0, the value should not be lower than the retry delay; this is checked by
+    /// `validate()` when `LightstreamerClient.connect()` is called, so that the options can be
+    /// set in any order.
+    ///
     /// # Parameters
     ///
     /// * `reverse_heartbeat_interval`: the interval, expressed in milliseconds, between subsequent
@@ -856,18 +1787,11 @@ impl ConnectionOptions {
         &mut self,
         reverse_heartbeat_interval: u64,
     ) -> Result<(), IllegalArgumentException> {
-        if reverse_heartbeat_interval == 0 {
-            self.reverse_heartbeat_interval = reverse_heartbeat_interval;
-            return Ok(());
-        }
-
-        if reverse_heartbeat_interval < self.retry_delay {
-            return Err(IllegalArgumentException::new(
-                "Reverse heartbeat interval should be greater than or equal to retry delay",
-            ));
-        }
-
         self.reverse_heartbeat_interval = reverse_heartbeat_interval;
+        self.option_changes
+            .push(OptionChange::ReverseHeartbeatInterval(millis(
+                reverse_heartbeat_interval,
+            )));
         Ok(())
     }
 
@@ -930,6 +1854,10 @@ impl ConnectionOptions {
     /// with argument "sessionRecoveryTimeout" on any `ClientListener` listening to the related
     /// `LightstreamerClient`.
     ///
+    /// When not 0, the value should not be lower than the retry delay; this is checked by
+    /// `validate()` when `LightstreamerClient.connect()` is called, so that the options can be
+    /// set in any order.
+    ///
     /// # Parameters
     ///
     /// * `session_recovery_timeout`: The maximum time allowed for recovery attempts, expressed
@@ -942,17 +1870,6 @@ impl ConnectionOptions {
         &mut self,
         session_recovery_timeout: u64,
     ) -> Result<(), IllegalArgumentException> {
-        if session_recovery_timeout == 0 {
-            self.session_recovery_timeout = session_recovery_timeout;
-            return Ok(());
-        }
-
-        if session_recovery_timeout < self.retry_delay {
-            return Err(IllegalArgumentException::new(
-                "Session recovery timeout should be greater than or equal to retry delay",
-            ));
-        }
-
         self.session_recovery_timeout = session_recovery_timeout;
         Ok(())
     }
@@ -986,6 +1903,34 @@ impl ConnectionOptions {
         self.slowing_enabled = slowing_enabled;
     }
 
+    /// Setter method that enables the isolation of the Subscriptions whose listeners are found
+    /// stalled by the dispatch watchdog.
+    ///
+    /// Once isolated, a Subscription stays active and subscribed to through the server, but its
+    /// listeners receive no further notifications: when the stalled call returns, the notifications
+    /// queued for it are discarded, so that the ones for the other listeners can catch up. The
+    /// isolation is lifted by unsubscribing from the Subscription and subscribing to it again.
+    ///
+    /// false.
+    ///
+    /// This value can be set and changed at any time. The supplied value will be used on the next
+    /// call to `LightstreamerClient.connect()`. It has no effect unless the watchdog is enabled.
+    ///
+    /// # Parameters
+    ///
+    /// * `stalled_subscription_isolation_enabled`: `true` or `false`, to enable or disable the
+    ///   isolation of the Subscriptions whose listeners stall.
+    ///
+    /// See also `setDispatchWatchdogTimeout()`
+    ///
+    /// See also `Subscription.isIsolated()`
+    pub fn set_stalled_subscription_isolation_enabled(
+        &mut self,
+        stalled_subscription_isolation_enabled: bool,
+    ) {
+        self.stalled_subscription_isolation_enabled = stalled_subscription_isolation_enabled;
+    }
+
     /// Setter method that sets the extra time the client is allowed to wait when an expected keepalive
     /// packet has not been received on a stream connection (and no actual data has arrived), before
     /// entering the "STALLED" status.
@@ -997,6 +1942,10 @@ impl ConnectionOptions {
     /// A change to this setting will be notified through a call to `ClientListener.onPropertyChange()`
     /// with argument "stalledTimeout" on any `ClientListener` listening to the related `LightstreamerClient`.
     ///
+    /// The value should be lower than the reconnect timeout and, unless it is 0, than the keepalive
+    /// interval; this is checked by `validate()` when `LightstreamerClient.connect()` is called,
+    /// so that the options can be set in any order.
+    ///
     /// # Parameters
     ///
     /// * `stalled_timeout`: The idle time (in milliseconds) allowed before entering the "STALLED"
@@ -1019,21 +1968,84 @@ impl ConnectionOptions {
             ));
         }
 
-        if stalled_timeout >= self.keepalive_interval {
-            return Err(IllegalArgumentException::new(
-                "Stalled timeout should be less than keepalive interval",
+        self.stalled_timeout = stalled_timeout;
+
+        Ok(())
+    }
+
+    /// Checks the constraints between the options, which are not enforced by the setters so that
+    /// the options can be set in any order. It is called by `LightstreamerClient.connect()`, but
+    /// it can also be called in advance to check a configuration.
+    ///
+    /// # Raises
+    ///
+    /// * `InvalidOptionsException`: listing all the violated constraints, if any.
+    pub fn validate(&self) -> Result<(), InvalidOptionsException> {
+        self.validate_with(&ConnectOverrides::default())
+    }
+
+    /// Checks the constraints between the options, as replaced by the given overrides.
+    pub(crate) fn validate_with(
+        &self,
+        overrides: &ConnectOverrides,
+    ) -> Result<(), InvalidOptionsException> {
+        let idle_timeout = overrides.idle_timeout.unwrap_or(self.idle_timeout);
+        let keepalive_interval = overrides
+            .keepalive_interval
+            .unwrap_or(self.keepalive_interval);
+        let polling_interval = overrides.polling_interval.unwrap_or(self.polling_interval);
+        let reconnect_timeout = overrides
+            .reconnect_timeout
+            .unwrap_or(self.reconnect_timeout);
+        let retry_delay = overrides.retry_delay.unwrap_or(self.retry_delay);
+        let session_recovery_timeout = overrides
+            .session_recovery_timeout
+            .unwrap_or(self.session_recovery_timeout);
+        let stalled_timeout = overrides.stalled_timeout.unwrap_or(self.stalled_timeout);
+        let mut violations = Vec::new();
+        if keepalive_interval != 0 {
+            if stalled_timeout >= keepalive_interval {
+                violations.push(format!(
+                    "Stalled timeout ({} ms) should be less than keepalive interval ({} ms)",
+                    stalled_timeout, keepalive_interval
+                ));
+            }
+            if keepalive_interval < reconnect_timeout {
+                violations.push(format!(
+                    "Keepalive interval ({} ms) should be greater than or equal to reconnect timeout ({} ms)",
+                    keepalive_interval, reconnect_timeout
+                ));
+            }
+        }
+        if stalled_timeout >= reconnect_timeout {
+            violations.push(format!(
+                "Stalled timeout ({} ms) should be less than reconnect timeout ({} ms)",
+                stalled_timeout, reconnect_timeout
             ));
         }
-
-        if stalled_timeout >= self.reconnect_timeout {
-            return Err(IllegalArgumentException::new(
-                "Stalled timeout should be less than reconnect timeout",
+        if polling_interval != 0 && polling_interval < idle_timeout {
+            violations.push(format!(
+                "Polling interval ({} ms) should be greater than or equal to idle timeout ({} ms)",
+                polling_interval, idle_timeout
             ));
         }
-
-        self.stalled_timeout = stalled_timeout;
-
-        Ok(())
+        if self.reverse_heartbeat_interval != 0 && self.reverse_heartbeat_interval < retry_delay {
+            violations.push(format!(
+                "Reverse heartbeat interval ({} ms) should be greater than or equal to retry delay ({} ms)",
+                self.reverse_heartbeat_interval, retry_delay
+            ));
+        }
+        if session_recovery_timeout != 0 && session_recovery_timeout < retry_delay {
+            violations.push(format!(
+                "Session recovery timeout ({} ms) should be greater than or equal to retry delay ({} ms)",
+                session_recovery_timeout, retry_delay
+            ));
+        }
+        if violations.is_empty() {
+            Ok(())
+        } else {
+            Err(InvalidOptionsException::new(violations))
+        }
     }
 
     /// Returns whether the client is configured for polling mode.
@@ -1119,12 +2131,139 @@ impl ConnectionOptions {
     }
 }
 
+impl ConnectionOptions {
+    /// Default size of the buffers collecting the data received from the Server, the same as the
+    /// one of the WebSocket implementation.
+    const DEFAULT_READ_BUFFER_SIZE: usize = 128 * 1024;
+
+    /// Gets the configuration of the WebSocket connections, with the configured size limits and
+    /// read buffer size.
+    pub(crate) fn websocket_config(&self) -> WebSocketConfig {
+        let mut config = WebSocketConfig::default();
+        if let Some(max_frame_size) = self.max_frame_size {
+            config = config.max_frame_size(Some(max_frame_size));
+        }
+        if let Some(max_message_size) = self.max_message_size {
+            config = config.max_message_size(Some(max_message_size));
+        }
+        config.read_buffer_size(self.read_buffer_settings().size)
+    }
+
+    /// Settings of the dispatch watchdog, if enabled.
+    pub(crate) fn watchdog_settings(&self, logging: LogType) -> Option<WatchdogSettings> {
+        Some(WatchdogSettings {
+            timeout: Duration::from_millis(self.dispatch_watchdog_timeout?),
+            isolation: self.stalled_subscription_isolation_enabled,
+            logging,
+        })
+    }
+
+    /// Gets the extra headers to be sent to the Server, together with the restriction on their
+    /// forwarding.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if the name or the value of a header is not valid
+    pub(crate) fn extra_headers(&self) -> Result<ExtraHeaders, IllegalArgumentException> {
+        let mut headers = HeaderMap::new();
+        for (name, value) in self.http_extra_headers.iter().flatten() {
+            let invalid = |err: &dyn std::error::Error| {
+                IllegalArgumentException::new(&format!("Invalid extra header '{}': {}", name, err))
+            };
+            headers.insert(
+                HeaderName::from_bytes(name.as_bytes()).map_err(|err| invalid(&err))?,
+                HeaderValue::from_str(value).map_err(|err| invalid(&err))?,
+            );
+        }
+        Ok(ExtraHeaders {
+            headers,
+            session_creation_only: self.http_extra_headers_on_session_creation_only,
+        })
+    }
+
+    /// Gets the settings of the buffers collecting the data received from the Server.
+    pub(crate) fn read_buffer_settings(&self) -> ReadBufferSettings {
+        ReadBufferSettings {
+            size: self
+                .read_buffer_size
+                .unwrap_or(Self::DEFAULT_READ_BUFFER_SIZE),
+            growth: self.read_buffer_growth,
+        }
+    }
+
+    /// Gets the executor of the requests of the HTTP connections, through the HTTP service, if
+    /// any, or else through the HTTP client.
+    #[cfg(feature = "reqwest")]
+    pub(crate) fn http_executor(&self) -> HttpExecutor {
+        #[cfg(feature = "tower")]
+        if let Some(http_service) = &self.http_service {
+            return http_transport::service_executor(http_service.clone());
+        }
+        http_transport::client_executor(self.http_client.clone())
+    }
+
+    /// Gets the settings of the streaming connection to be negotiated with the Server.
+    pub(crate) fn stream_settings(&self, overrides: &ConnectOverrides) -> StreamSettings {
+        let forced_transport = overrides.forced_transport.unwrap_or(self.forced_transport);
+        StreamSettings {
+            keepalive_interval: millis(
+                overrides
+                    .keepalive_interval
+                    .unwrap_or(self.keepalive_interval),
+            ),
+            reverse_heartbeat_interval: millis(self.reverse_heartbeat_interval),
+            requested_max_bandwidth: self.requested_max_bandwidth,
+            polling: matches!(
+                forced_transport,
+                Some(Transport::WsPolling | Transport::HttpPolling)
+            ),
+            http: matches!(
+                forced_transport,
+                Some(Transport::Http | Transport::HttpStreaming | Transport::HttpPolling)
+            ),
+            polling_interval: Duration::from_millis(
+                overrides.polling_interval.unwrap_or(self.polling_interval),
+            ),
+            idle_timeout: Duration::from_millis(
+                overrides.idle_timeout.unwrap_or(self.idle_timeout),
+            ),
+        }
+    }
+
+    /// Gets the queue of the changes to be applied to the current connection, shared with the
+    /// session task.
+    pub(crate) fn option_changes(&self) -> &OptionChanges {
+        &self.option_changes
+    }
+
+    /// Gets the settings notified by the Server, shared with the session task.
+    pub(crate) fn server_settings(&self) -> &Arc<Mutex<ServerSettings>> {
+        &self.server_settings
+    }
+}
+
+/// Converts an interval in milliseconds to a `Duration`, where 0 means no interval.
+fn millis(interval: u64) -> Option<Duration> {
+    (interval > 0).then(|| Duration::from_millis(interval))
+}
+
 impl Debug for ConnectionOptions {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        f.debug_struct("ConnectionOptions")
+        let mut debug = f.debug_struct("ConnectionOptions");
+        debug.field("clock", &self.clock);
+        #[cfg(feature = "runtime-tokio")]
+        debug.field("connector", &self.connector);
+        debug
             .field("content_length", &self.content_length)
+            .field("dispatch_watchdog_timeout", &self.dispatch_watchdog_timeout)
+            .field("event_history_size", &self.event_history_size)
             .field("first_retry_max_delay", &self.first_retry_max_delay)
-            .field("forced_transport", &self.forced_transport)
+            .field("forced_transport", &self.forced_transport);
+        #[cfg(feature = "reqwest")]
+        debug.field("http_client", &self.http_client);
+        #[cfg(feature = "tower")]
+        debug.field("http_service", &self.http_service);
+        debug
             .field("http_extra_headers", &self.http_extra_headers)
             .field(
                 "http_extra_headers_on_session_creation_only",
@@ -1132,12 +2271,23 @@ impl Debug for ConnectionOptions {
             )
             .field("idle_timeout", &self.idle_timeout)
             .field("keepalive_interval", &self.keepalive_interval)
+            .field("max_frame_size", &self.max_frame_size)
+            .field("max_message_size", &self.max_message_size)
+            .field("max_relogin_attempts", &self.max_relogin_attempts)
+            .field("max_retry_delay", &self.max_retry_delay)
+            .field("message_recovery_policy", &self.message_recovery_policy)
+            .field("ping_interval", &self.ping_interval)
             .field("polling_interval", &self.polling_interval)
+            .field("pong_timeout", &self.pong_timeout)
             .field("proxy", &self.proxy)
+            .field("read_buffer_growth", &self.read_buffer_growth)
+            .field("read_buffer_size", &self.read_buffer_size)
             .field("real_max_bandwidth", &self.real_max_bandwidth)
             .field("reconnect_timeout", &self.reconnect_timeout)
+            .field("request_interceptors", &self.request_interceptors)
             .field("requested_max_bandwidth", &self.requested_max_bandwidth)
             .field("retry_delay", &self.retry_delay)
+            .field("retry_policy", &self.retry_policy)
             .field(
                 "reverse_heartbeat_interval",
                 &self.reverse_heartbeat_interval,
@@ -1146,8 +2296,13 @@ impl Debug for ConnectionOptions {
                 "server_instance_address_ignored",
                 &self.server_instance_address_ignored,
             )
+            .field("session_recorder", &self.session_recorder)
             .field("session_recovery_timeout", &self.session_recovery_timeout)
             .field("slowing_enabled", &self.slowing_enabled)
+            .field(
+                "stalled_subscription_isolation_enabled",
+                &self.stalled_subscription_isolation_enabled,
+            )
             .field("stalled_timeout", &self.stalled_timeout)
             .finish()
     }
@@ -1156,25 +2311,51 @@ impl Debug for ConnectionOptions {
 impl Default for ConnectionOptions {
     fn default() -> Self {
         Self {
+            clock: None,
+            #[cfg(feature = "runtime-tokio")]
+            connector: None,
             content_length: None,
+            dispatch_watchdog_timeout: None,
+            event_history_size: DEFAULT_EVENT_HISTORY_SIZE,
+            #[cfg(feature = "test-util")]
+            fault_injector: None,
             first_retry_max_delay: 0,
             forced_transport: None,
+            #[cfg(feature = "reqwest")]
+            http_client: None,
+            #[cfg(feature = "tower")]
+            http_service: None,
             http_extra_headers: None,
             http_extra_headers_on_session_creation_only: false,
             idle_timeout: 19000,
             keepalive_interval: 0,
+            max_frame_size: None,
+            max_message_size: None,
+            max_relogin_attempts: 0,
+            max_retry_delay: 60000,
+            message_recovery_policy: MessageRecoveryPolicy::default(),
+            option_changes: OptionChanges::default(),
+            ping_interval: 0,
             polling_interval: 0,
+            pong_timeout: 5000,
             proxy: None,
+            read_buffer_growth: ReadBufferGrowth::default(),
+            read_buffer_size: None,
             real_max_bandwidth: None,
             reconnect_timeout: 3000,
             _reduce_head: false,
+            request_interceptors: Vec::new(),
             requested_max_bandwidth: None,
             retry_delay: 4000,
+            retry_policy: None,
             reverse_heartbeat_interval: 0,
             send_sync: false,
             server_instance_address_ignored: false,
+            server_settings: Arc::default(),
+            session_recorder: None,
             session_recovery_timeout: 15000,
             slowing_enabled: false,
+            stalled_subscription_isolation_enabled: false,
             stalled_timeout: 2000,
             polling: false,
             ttl_millis: None,
@@ -0,0 +1,274 @@
+//! Connection-level resources shared by several `LightstreamerClient` instances.
+//!
+//! Available with the `runtime-tokio` feature. Applications consuming several feeds, from
+//! different servers or adapter sets, typically create one client per feed; by giving all of them
+//! the same `Connector` through `ConnectionOptions.setConnector()`, they also share the runtime
+//! their tasks run on, the TLS configuration, the resolved server addresses and the cookies,
+//! instead of setting them up once per client.
+
+use crate::cookies::{parse_cookie_uri, CookieJar};
+use crate::error::IllegalArgumentException;
+use crate::runtime::tungstenite::error::{TlsError, UrlError};
+use crate::runtime::tungstenite::handshake::client::Response;
+use crate::runtime::tungstenite::http::Request;
+use crate::runtime::tungstenite::protocol::WebSocketConfig;
+use crate::runtime::tungstenite::Error as WsError;
+use crate::runtime::{CurrentRuntime, Runtime};
+
+use cookie::Cookie;
+use std::collections::HashMap;
+use std::fmt::{self, Debug, Formatter};
+use std::io;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::time::{Duration, Instant};
+use tokio::net::TcpStream;
+use tokio::runtime::{EnterGuard, Handle};
+
+pub use native_tls::TlsConnector;
+
+/// Addresses resolved for a host and port, with the instant they were resolved at.
+type ResolvedAddresses = (Instant, Vec<SocketAddr>);
+
+/// Handle to the connection-level resources shared by the `LightstreamerClient` instances it is
+/// given to through `ConnectionOptions.setConnector()`:
+///
+/// - the tokio runtime the session and dispatch tasks of the clients are spawned on, if one was
+///   supplied through `with_runtime()`; otherwise the tasks are spawned on the runtime
+///   `connect()` is called from;
+/// - the TLS configuration, either supplied through `with_tls_connector()` or created with the
+///   system defaults when the first secure connection is opened, and reused afterwards;
+/// - a cache of the addresses resolved for the server hosts, whose entries expire after the time
+///   set through `with_dns_cache_ttl()`;
+/// - a cookie jar, separate from the process-wide one used by the clients with no connector and
+///   managed through `addCookies()` and `getCookies()`.
+///
+/// A connector is cheap to clone, and all the clones share the same resources. It must be
+/// configured through the `with_*` methods before being cloned: they panic otherwise.
+///
+/// # Example
+///
+/// ```
+/// use lightstreamer_client::connector::Connector;
+/// use lightstreamer_client::ls_client::LightstreamerClient;
+/// use std::time::Duration;
+///
+/// let connector = Connector::new().with_dns_cache_ttl(Duration::from_secs(300));
+/// let server_address = Some("https://push.example.com");
+/// let mut quotes = LightstreamerClient::new(server_address, Some("QUOTES"), None, None).unwrap();
+/// let mut news = LightstreamerClient::new(server_address, Some("NEWS"), None, None).unwrap();
+/// quotes.connection_options.set_connector(Some(connector.clone()));
+/// news.connection_options.set_connector(Some(connector));
+/// ```
+#[derive(Clone)]
+pub struct Connector {
+    inner: Arc<ConnectorInner>,
+}
+
+struct ConnectorInner {
+    runtime: Option<Handle>,
+    tls_connector: OnceLock<TlsConnector>,
+    dns_cache_ttl: Duration,
+    dns_cache: Mutex<HashMap<(String, u16), ResolvedAddresses>>,
+    cookie_jar: CookieJar,
+}
+
+impl Connector {
+    /// Default time the resolved addresses are kept in the cache.
+    pub const DEFAULT_DNS_CACHE_TTL: Duration = Duration::from_secs(60);
+
+    /// Creates a connector with no runtime of its own, the default TLS configuration, a DNS cache
+    /// keeping entries for `DEFAULT_DNS_CACHE_TTL` and an empty cookie jar.
+    pub fn new() -> Connector {
+        Connector {
+            inner: Arc::new(ConnectorInner {
+                runtime: None,
+                tls_connector: OnceLock::new(),
+                dns_cache_ttl: Self::DEFAULT_DNS_CACHE_TTL,
+                dns_cache: Mutex::new(HashMap::new()),
+                cookie_jar: CookieJar::new(),
+            }),
+        }
+    }
+
+    /// Sets the runtime the tasks of the clients using this connector are spawned on, so that
+    /// `connect()` can also be called from other runtimes.
+    pub fn with_runtime(mut self, runtime: Handle) -> Self {
+        self.inner_mut().runtime = Some(runtime);
+        self
+    }
+
+    /// Sets the TLS configuration used for the secure connections of the clients using this
+    /// connector, for instance to trust additional root certificates or to present a client
+    /// certificate.
+    pub fn with_tls_connector(mut self, tls_connector: TlsConnector) -> Self {
+        self.inner_mut().tls_connector = OnceLock::from(tls_connector);
+        self
+    }
+
+    /// Sets how long the addresses resolved for a server host are reused before resolving it
+    /// again. A zero duration disables the cache.
+    pub fn with_dns_cache_ttl(mut self, ttl: Duration) -> Self {
+        self.inner_mut().dns_cache_ttl = ttl;
+        self
+    }
+
+    /// Gets the runtime the tasks of the clients using this connector are spawned on, if one was
+    /// set.
+    pub fn get_runtime(&self) -> Option<&Handle> {
+        self.inner.runtime.as_ref()
+    }
+
+    /// Gets how long the addresses resolved for a server host are reused.
+    pub fn get_dns_cache_ttl(&self) -> Duration {
+        self.inner.dns_cache_ttl
+    }
+
+    /// Adds cookies to the jar of this connector, as `LightstreamerClient.addCookies()` does with
+    /// the process-wide jar.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if `uri` is not a valid URI.
+    pub fn add_cookies<'c>(
+        &self,
+        uri: &str,
+        cookies: impl IntoIterator<Item = Cookie<'c>>,
+    ) -> Result<(), IllegalArgumentException> {
+        let url = parse_cookie_uri(uri)?;
+        self.inner.cookie_jar.add_cookies(&url, cookies);
+        Ok(())
+    }
+
+    /// Gets the cookies of the jar of this connector to be sent to the given URI, or all of them
+    /// if no URI is supplied, as `LightstreamerClient.getCookies()` does with the process-wide jar.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if `uri` is not a valid URI.
+    pub fn get_cookies(
+        &self,
+        uri: Option<&str>,
+    ) -> Result<Vec<Cookie<'static>>, IllegalArgumentException> {
+        let url = uri.map(parse_cookie_uri).transpose()?;
+        Ok(self.inner.cookie_jar.get_cookies(url.as_ref()))
+    }
+
+    /// Gets the cookie jar shared by the clients using this connector.
+    pub(crate) fn cookie_jar(&self) -> &CookieJar {
+        &self.inner.cookie_jar
+    }
+
+    /// Enters the runtime of this connector, if any, so that the tasks spawned until the guard is
+    /// dropped run on it.
+    pub(crate) fn enter(&self) -> Option<EnterGuard<'_>> {
+        self.inner.runtime.as_ref().map(Handle::enter)
+    }
+
+    /// Opens a WebSocket connection performing the handshake described by the given request, with
+    /// the given limits on the frames and messages received, through the shared DNS cache and TLS
+    /// configuration.
+    pub(crate) async fn connect_websocket(
+        &self,
+        request: Request<()>,
+        config: WebSocketConfig,
+    ) -> Result<(<CurrentRuntime as Runtime>::WebSocket, Response), WsError> {
+        let uri = request.uri();
+        let secure = uri.scheme_str() == Some("wss");
+        let host = uri.host().ok_or(WsError::Url(UrlError::NoHostName))?;
+        let host = host
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .to_string();
+        let port = uri.port_u16().unwrap_or(if secure { 443 } else { 80 });
+        let stream = self.connect_tcp(&host, port).await?;
+        let tls_connector = if secure {
+            Some(tokio_tungstenite::Connector::NativeTls(
+                self.tls_connector()?.clone(),
+            ))
+        } else {
+            None
+        };
+        tokio_tungstenite::client_async_tls_with_config(
+            request,
+            stream,
+            Some(config),
+            tls_connector,
+        )
+        .await
+    }
+
+    /// Gets the TLS configuration, creating the default one on first use.
+    fn tls_connector(&self) -> Result<&TlsConnector, WsError> {
+        if let Some(tls_connector) = self.inner.tls_connector.get() {
+            return Ok(tls_connector);
+        }
+        let tls_connector =
+            TlsConnector::new().map_err(|err| WsError::Tls(TlsError::Native(Box::new(err))))?;
+        Ok(self.inner.tls_connector.get_or_init(|| tls_connector))
+    }
+
+    /// Opens a TCP connection to the first reachable address of the given host.
+    async fn connect_tcp(&self, host: &str, port: u16) -> io::Result<TcpStream> {
+        let addresses = self.resolve(host, port).await?;
+        let mut last_error = None;
+        for address in &addresses {
+            match TcpStream::connect(address).await {
+                Ok(stream) => return Ok(stream),
+                Err(err) => last_error = Some(err),
+            }
+        }
+        // The addresses may be stale: resolve them again on the next attempt.
+        self.inner
+            .dns_cache
+            .lock()
+            .unwrap()
+            .remove(&(host.to_string(), port));
+        Err(last_error.unwrap_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("No address found for {}", host),
+            )
+        }))
+    }
+
+    /// Resolves the addresses of the given host, through the cache.
+    async fn resolve(&self, host: &str, port: u16) -> io::Result<Vec<SocketAddr>> {
+        let key = (host.to_string(), port);
+        let ttl = self.inner.dns_cache_ttl;
+        if let Some((resolved_at, addresses)) = self.inner.dns_cache.lock().unwrap().get(&key) {
+            if resolved_at.elapsed() < ttl {
+                return Ok(addresses.clone());
+            }
+        }
+        let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host, port)).await?.collect();
+        if !ttl.is_zero() {
+            self.inner
+                .dns_cache
+                .lock()
+                .unwrap()
+                .insert(key, (Instant::now(), addresses.clone()));
+        }
+        Ok(addresses)
+    }
+
+    /// Gets the resources to be configured. Only possible before the connector is cloned.
+    fn inner_mut(&mut self) -> &mut ConnectorInner {
+        Arc::get_mut(&mut self.inner).expect("Connector configured after being cloned")
+    }
+}
+
+impl Default for Connector {
+    fn default() -> Self {
+        Connector::new()
+    }
+}
+
+impl Debug for Connector {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Connector")
+            .field("runtime", &self.inner.runtime)
+            .field("dns_cache_ttl", &self.inner.dns_cache_ttl)
+            .finish_non_exhaustive()
+    }
+}
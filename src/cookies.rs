@@ -0,0 +1,214 @@
+//! Cookie jars used by the transports: a process-wide one shared by all the `LightstreamerClient`
+//! instances, and the ones of the `Connector`s, shared by the clients using each of them.
+//!
+//! Cookies are stored following a simplified version of the RFC 6265 rules: a cookie belongs to
+//! a domain (either exactly the host it was received from or, if it carries a `Domain` attribute,
+//! that domain and its subdomains) and to a path, and it's discarded once expired.
+
+use crate::error::IllegalArgumentException;
+use crate::runtime::tungstenite::http::{HeaderMap, HeaderValue, Request};
+
+use cookie::time::OffsetDateTime;
+use cookie::Cookie;
+use std::sync::Mutex;
+use url::Url;
+
+/// A cookie together with the attributes resolved when it was stored.
+struct StoredCookie {
+    cookie: Cookie<'static>,
+    /// Whether the cookie must be sent only to the exact host it was received from.
+    host_only: bool,
+    expires: Option<OffsetDateTime>,
+}
+
+impl StoredCookie {
+    fn is_expired(&self, now: OffsetDateTime) -> bool {
+        self.expires.is_some_and(|expires| expires <= now)
+    }
+
+    fn matches(&self, url: &Url) -> bool {
+        let Some(host) = url.host_str() else {
+            return false;
+        };
+        let domain = self.cookie.domain().unwrap_or_default();
+        let domain_matches = if self.host_only {
+            host.eq_ignore_ascii_case(domain)
+        } else {
+            domain_matches(host, domain)
+        };
+        let secure_matches =
+            !self.cookie.secure().unwrap_or(false) || matches!(url.scheme(), "https" | "wss");
+        domain_matches
+            && secure_matches
+            && path_matches(url.path(), self.cookie.path().unwrap_or("/"))
+    }
+}
+
+/// Process-wide jar, used by the clients with no `Connector`.
+static COOKIE_JAR: CookieJar = CookieJar::new();
+
+/// Gets the process-wide cookie jar.
+pub(crate) fn global_jar() -> &'static CookieJar {
+    &COOKIE_JAR
+}
+
+/// Adds the cookies received from the given URL to the process-wide jar.
+pub(crate) fn add_cookies<'c>(url: &Url, cookies: impl IntoIterator<Item = Cookie<'c>>) {
+    COOKIE_JAR.add_cookies(url, cookies);
+}
+
+/// Gets the cookies of the process-wide jar to be sent to the given URL, or all of them if no URL
+/// is supplied.
+pub(crate) fn get_cookies(url: Option<&Url>) -> Vec<Cookie<'static>> {
+    COOKIE_JAR.get_cookies(url)
+}
+
+/// Set of cookies, stored and retrieved by URL.
+#[derive(Default)]
+pub(crate) struct CookieJar {
+    cookies: Mutex<Vec<StoredCookie>>,
+}
+
+impl CookieJar {
+    pub(crate) const fn new() -> CookieJar {
+        CookieJar {
+            cookies: Mutex::new(Vec::new()),
+        }
+    }
+
+    /// Adds the cookies received from the given URL to the jar, replacing the ones with the same
+    /// name, domain and path. Cookies whose domain is not compatible with the URL are ignored,
+    /// while expired cookies remove the stored ones.
+    pub(crate) fn add_cookies<'c>(&self, url: &Url, cookies: impl IntoIterator<Item = Cookie<'c>>) {
+        let Some(host) = url.host_str() else {
+            return;
+        };
+        let now = OffsetDateTime::now_utc();
+        let mut jar = self.cookies.lock().unwrap();
+        for cookie in cookies {
+            let mut cookie = cookie.into_owned();
+            let (domain, host_only) = match cookie.domain() {
+                Some(domain) if !domain.is_empty() => {
+                    let domain = domain.trim_start_matches('.').to_ascii_lowercase();
+                    if !domain_matches(host, &domain) {
+                        continue;
+                    }
+                    (domain, false)
+                }
+                _ => (host.to_ascii_lowercase(), true),
+            };
+            if !cookie.path().is_some_and(|path| path.starts_with('/')) {
+                cookie.set_path(default_path(url.path()));
+            }
+            cookie.set_domain(domain);
+            // Max-Age takes precedence over Expires.
+            let expires = match cookie.max_age() {
+                Some(max_age) => Some(now.saturating_add(max_age)),
+                None => cookie.expires_datetime(),
+            };
+            let stored = StoredCookie {
+                cookie,
+                host_only,
+                expires,
+            };
+            jar.retain(|other| {
+                other.cookie.name() != stored.cookie.name()
+                    || other.cookie.domain() != stored.cookie.domain()
+                    || other.cookie.path() != stored.cookie.path()
+            });
+            if !stored.is_expired(now) {
+                jar.push(stored);
+            }
+        }
+    }
+
+    /// Gets the non-expired cookies to be sent to the given URL, or all the non-expired cookies if
+    /// no URL is supplied. Cookies with longer paths come first.
+    pub(crate) fn get_cookies(&self, url: Option<&Url>) -> Vec<Cookie<'static>> {
+        let now = OffsetDateTime::now_utc();
+        let mut jar = self.cookies.lock().unwrap();
+        jar.retain(|stored| !stored.is_expired(now));
+        let mut cookies: Vec<Cookie<'static>> = jar
+            .iter()
+            .filter(|stored| url.is_none_or(|url| stored.matches(url)))
+            .map(|stored| stored.cookie.clone())
+            .collect();
+        cookies.sort_by_key(|cookie| std::cmp::Reverse(cookie.path().map_or(0, str::len)));
+        cookies
+    }
+
+    /// Sets the `Cookie` header of a request to the cookies in the jar matching its URI, if any.
+    pub(crate) fn apply_cookies(&self, request: &mut Request<()>) {
+        let Some(url) = request_url(request) else {
+            return;
+        };
+        let cookies = self.get_cookies(Some(&url));
+        if cookies.is_empty() {
+            return;
+        }
+        let header = cookies
+            .iter()
+            .map(|cookie| cookie.stripped().to_string())
+            .collect::<Vec<_>>()
+            .join("; ");
+        if let Ok(value) = HeaderValue::from_str(&header) {
+            request.headers_mut().insert("cookie", value);
+        }
+    }
+
+    /// Stores the cookies set by the server through the `Set-Cookie` headers of the response to a
+    /// request.
+    pub(crate) fn store_response_cookies(&self, request: &Request<()>, headers: &HeaderMap) {
+        let Some(url) = request_url(request) else {
+            return;
+        };
+        let cookies = headers
+            .get_all("set-cookie")
+            .iter()
+            .filter_map(|value| value.to_str().ok())
+            .filter_map(|value| Cookie::parse(value.to_string()).ok());
+        self.add_cookies(&url, cookies);
+    }
+}
+
+/// Parses the URI given to the public cookie methods.
+pub(crate) fn parse_cookie_uri(uri: &str) -> Result<Url, IllegalArgumentException> {
+    Url::parse(uri).map_err(|err| {
+        IllegalArgumentException::new(&format!("Invalid cookie URI '{}': {}", uri, err))
+    })
+}
+
+/// Gets the URL a request is addressed to.
+fn request_url(request: &Request<()>) -> Option<Url> {
+    Url::parse(&request.uri().to_string()).ok()
+}
+
+/// Checks whether a host belongs to a cookie domain, i.e. it's the domain itself or one of its
+/// subdomains. IP addresses only match themselves.
+fn domain_matches(host: &str, domain: &str) -> bool {
+    if host.eq_ignore_ascii_case(domain) {
+        return true;
+    }
+    let is_ip_address = host.parse::<std::net::IpAddr>().is_ok() || host.starts_with('[');
+    !is_ip_address
+        && host.len() > domain.len()
+        && host.as_bytes()[host.len() - domain.len() - 1] == b'.'
+        && host[host.len() - domain.len()..].eq_ignore_ascii_case(domain)
+}
+
+/// Checks whether a request path is covered by a cookie path.
+fn path_matches(request_path: &str, cookie_path: &str) -> bool {
+    request_path == cookie_path
+        || (request_path.starts_with(cookie_path)
+            && (cookie_path.ends_with('/')
+                || request_path.as_bytes().get(cookie_path.len()) == Some(&b'/')))
+}
+
+/// Default cookie path for a request path: its "directory", up to but not including the last
+/// slash.
+fn default_path(request_path: &str) -> String {
+    match request_path.rfind('/') {
+        Some(0) | None => "/".to_string(),
+        Some(index) => request_path[..index].to_string(),
+    }
+}
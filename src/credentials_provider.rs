@@ -0,0 +1,105 @@
+use crate::secret::SecretString;
+
+use std::error::Error;
+use std::fmt::Debug;
+use std::future::Future;
+use std::pin::Pin;
+
+/// Credentials used to authenticate on Lightstreamer Server when a session is created, as
+/// supplied by a `CredentialsProvider`.
+///
+/// The password is kept as a `SecretString`, so it is never printed by the `Debug` implementation
+/// and it is wiped from memory when the credentials are dropped.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Credentials {
+    user: Option<String>,
+    password: Option<SecretString>,
+}
+
+impl Credentials {
+    /// Creates a new set of credentials.
+    ///
+    /// # Parameters
+    ///
+    /// * `user`: the username, or `None` to send no user information.
+    /// * `password`: the password, for instance a short-lived token, or `None` to send no
+    ///   password information.
+    pub fn new(user: Option<&str>, password: Option<&str>) -> Credentials {
+        Credentials {
+            user: user.map(str::to_string),
+            password: password.map(SecretString::from),
+        }
+    }
+
+    /// Returns the username, if any.
+    pub fn get_user(&self) -> Option<&str> {
+        self.user.as_deref()
+    }
+
+    /// Returns the password, if any.
+    pub fn get_password(&self) -> Option<&SecretString> {
+        self.password.as_ref()
+    }
+}
+
+/// Interface to be implemented to supply fresh credentials each time a `LightstreamerClient`
+/// creates a new session, instead of the fixed user and password of `ConnectionDetails`.
+///
+/// This suits deployments where the password is a short-lived token: the provider is consulted
+/// on the initial connection and whenever a new session has to be created because the previous
+/// one was lost and couldn't be recovered, so an expired token is never sent again. Session
+/// recoveries don't carry credentials, so they don't consult the provider.
+///
+/// An instance of a type implementing this trait can be supplied through
+/// `ConnectionDetails.setCredentialsProvider()`.
+///
+/// The provider is consulted by the session task, which runs separately from the code that
+/// configured it; this is why implementations must be `Send` and `Sync`.
+pub trait CredentialsProvider: Debug + Send + Sync {
+    /// Gets the credentials to be sent with the next session creation request.
+    ///
+    /// # Returns
+    ///
+    /// The credentials, or the error that prevented obtaining them: in that case the connection
+    /// attempt fails and is retried as dictated by the configured `RetryPolicy`.
+    fn get_credentials(&self) -> Result<Credentials, Box<dyn Error + Send + Sync>>;
+}
+
+/// Future returned by `ReauthenticationHandler.reauthenticate()`, resolving to the credentials to
+/// be used from then on, if they changed.
+pub type ReauthenticationFuture<'a> = Pin<
+    Box<dyn Future<Output = Result<Option<Credentials>, Box<dyn Error + Send + Sync>>> + Send + 'a>,
+>;
+
+/// Interface to be implemented to be notified, and given the chance to authenticate again, just
+/// before a `LightstreamerClient` creates a new session because the previous one was lost and
+/// couldn't be recovered.
+///
+/// Unlike `CredentialsProvider`, the handler is not involved in the session created by
+/// `LightstreamerClient.connect()`, but only in the ones that replace it, and it is asynchronous:
+/// this makes it the place for login flows that need a round trip to an external service, for
+/// instance a REST call that refreshes the tokens the Metadata Adapter expects as password.
+///
+/// The handler is also invoked when the Server rejects the credentials of a session creation,
+/// including the one of `LightstreamerClient.connect()`, as many consecutive times as allowed by
+/// `ConnectionOptions.setMaxReloginAttempts()`.
+///
+/// An instance of a type implementing this trait can be supplied through
+/// `ConnectionDetails.setReauthenticationHandler()`.
+///
+/// The handler is invoked by the session task, which runs separately from the code that
+/// configured it; this is why implementations must be `Send` and `Sync`.
+pub trait ReauthenticationHandler: Debug + Send + Sync {
+    /// Invoked just before a new session is created to replace a session that was lost, or to
+    /// retry a session creation whose credentials were rejected.
+    ///
+    /// # Returns
+    ///
+    /// A future resolving to the credentials to be used for this and the next session creations,
+    /// in place of the configured user and password, or to `None` to keep using the current ones.
+    /// If a `CredentialsProvider` is configured, it is still consulted afterwards and its
+    /// credentials take precedence. If the future resolves to an error, the connection attempt
+    /// fails and is retried as dictated by the configured `RetryPolicy`, invoking the handler
+    /// again.
+    fn reauthenticate(&self) -> ReauthenticationFuture<'_>;
+}
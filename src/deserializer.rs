@@ -0,0 +1,204 @@
+//! Deserialization of item updates into user types through `serde`.
+//!
+//! An `ItemUpdate` is seen as a map from field names to values, so that any type deriving
+//! `Deserialize` can be built from it with `ItemUpdate.deserialize()`:
+//!
+//! ```
+//! use lightstreamer_client::item_update::ItemUpdate;
+//! use serde::Deserialize;
+//!
+//! #[derive(Deserialize)]
+//! struct Tick {
+//!     stock_name: String,
+//!     last_price: f64,
+//!     bid: Option<f64>,
+//! }
+//!
+//! let update = ItemUpdate::new(
+//!     Some("item1"),
+//!     1,
+//!     [("stock_name", Some("ACME")), ("last_price", Some("12.5")), ("bid", None)],
+//!     false,
+//! );
+//! let tick: Tick = update.deserialize().unwrap();
+//! assert_eq!(tick.stock_name, "ACME");
+//! assert_eq!(tick.last_price, 12.5);
+//! assert_eq!(tick.bid, None);
+//! ```
+//!
+//! Values are strings on the wire, so they are parsed through `FromStr` into the numbers,
+//! booleans and characters requested by the target type, while enums are matched by the name of
+//! their unit variants. Fields with no value (null) can only be deserialized into `Option`, as
+//! `None`, and so can the fields missing from the update. Fields of the update that are not in
+//! the target type are ignored, unless it denies unknown fields.
+
+use crate::error::IllegalArgumentException;
+use crate::item_update::ItemUpdate;
+
+use serde::de::value::{MapDeserializer, StrDeserializer};
+use serde::de::{self, Deserializer, IntoDeserializer, Visitor};
+use serde::forward_to_deserialize_any;
+use std::fmt::Display;
+use std::str::FromStr;
+
+impl de::Error for IllegalArgumentException {
+    fn custom<T: Display>(msg: T) -> Self {
+        IllegalArgumentException::new(&msg.to_string())
+    }
+}
+
+/// Deserializer reading the current field values of an `ItemUpdate` as a map from field names to
+/// values. It is usually not needed directly, as `ItemUpdate.deserialize()` wraps it.
+pub struct ItemUpdateDeserializer<'a> {
+    update: &'a ItemUpdate,
+}
+
+impl<'a> ItemUpdateDeserializer<'a> {
+    /// Creates a deserializer over the given update.
+    pub fn new(update: &'a ItemUpdate) -> ItemUpdateDeserializer<'a> {
+        ItemUpdateDeserializer { update }
+    }
+}
+
+impl<'de> Deserializer<'de> for ItemUpdateDeserializer<'de> {
+    type Error = IllegalArgumentException;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        let fields = self
+            .update
+            .fields_iter()
+            .map(|(name, value)| (name, FieldDeserializer { name, value }));
+        let mut map = MapDeserializer::new(fields);
+        let value = visitor.visit_map(&mut map)?;
+        map.end()?;
+        Ok(value)
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string bytes byte_buf option
+        unit unit_struct newtype_struct seq tuple tuple_struct map struct enum identifier
+        ignored_any
+    }
+}
+
+/// Deserializer of the value of a single field, parsing it into the type requested by the visitor.
+struct FieldDeserializer<'de> {
+    name: &'de str,
+    value: Option<&'de str>,
+}
+
+impl<'de> FieldDeserializer<'de> {
+    /// Gets the value of the field, failing if it is null.
+    fn value(&self) -> Result<&'de str, IllegalArgumentException> {
+        self.value.ok_or_else(|| {
+            IllegalArgumentException::new(&format!("Field '{}' has no value", self.name))
+        })
+    }
+
+    /// Parses the value of the field, failing if it is null or can't be parsed.
+    fn parse<T>(&self) -> Result<T, IllegalArgumentException>
+    where
+        T: FromStr,
+        T::Err: Display,
+    {
+        self.value()?.parse().map_err(|err| {
+            IllegalArgumentException::new(&format!(
+                "Invalid value for field '{}': {}",
+                self.name, err
+            ))
+        })
+    }
+}
+
+impl<'de> IntoDeserializer<'de, IllegalArgumentException> for FieldDeserializer<'de> {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Implements the deserialization of the given types by parsing the value of the field.
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for FieldDeserializer<'de> {
+    type Error = IllegalArgumentException;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Some(value) => visitor.visit_borrowed_str(value),
+            None => visitor.visit_none(),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_str(self.value()?)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        visitor.visit_borrowed_bytes(self.value()?.as_bytes())
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Self::Error> {
+        match self.value {
+            Some(_) => visitor.visit_some(self),
+            None => visitor.visit_none(),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error> {
+        let variant: StrDeserializer<'de, IllegalArgumentException> =
+            self.value()?.into_deserializer();
+        visitor.visit_enum(variant)
+    }
+
+    forward_to_deserialize_any! {
+        unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
@@ -0,0 +1,93 @@
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+/// WebSocket close code of a normal closure (RFC 6455).
+pub const CLOSE_NORMAL: u16 = 1000;
+
+/// Side that started the closure of a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DisconnectInitiator {
+    /// The client closed the connection, e.g. through `LightstreamerClient.disconnect()`.
+    Client,
+    /// The Server closed the connection, or the connection was lost.
+    Server,
+}
+
+/// Details about how the last WebSocket connection of a `LightstreamerClient` was closed.
+///
+/// A close is *clean* when the WebSocket close handshake was completed: the peer sent a close
+/// frame (with an optional close code and reason) in reply to ours or on its own initiative. A
+/// connection that ended without a close frame, because of a network failure, a read error or an
+/// unresponsive peer, is *aborted*.
+///
+/// After a connection is lost, the `DisconnectInfo` is also the error handed to the
+/// `RetryPolicy`, which can downcast it to tell clean closes from aborted ones.
+///
+/// See also `LightstreamerClient.getLastDisconnectInfo()`
+///
+/// See also `ClientListener.onDisconnect()`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DisconnectInfo {
+    /// Side that started the closure.
+    pub initiator: DisconnectInitiator,
+    /// Close code of the close frame received from the peer, or `None` if the frame carried no
+    /// code or no frame was received at all.
+    pub code: Option<u16>,
+    /// Reason of the close frame received from the peer, or a description of the failure for
+    /// aborted connections. It may be empty.
+    pub reason: String,
+    /// Whether the close handshake was completed.
+    pub clean: bool,
+}
+
+impl DisconnectInfo {
+    /// Creates the `DisconnectInfo` of a connection whose close handshake was completed.
+    pub(crate) fn clean(initiator: DisconnectInitiator, code: Option<u16>, reason: &str) -> Self {
+        DisconnectInfo {
+            initiator,
+            code,
+            reason: reason.to_string(),
+            clean: true,
+        }
+    }
+
+    /// Creates the `DisconnectInfo` of a connection that ended without a close handshake.
+    pub(crate) fn aborted(initiator: DisconnectInitiator, reason: &str) -> Self {
+        DisconnectInfo {
+            initiator,
+            code: None,
+            reason: reason.to_string(),
+            clean: false,
+        }
+    }
+
+    /// Inquiry method that checks whether the Server closed the connection cleanly on its own
+    /// initiative, e.g. because it is shutting down or it deliberately dropped the client.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the Server started and completed the close handshake.
+    pub fn is_clean_server_close(&self) -> bool {
+        self.clean && self.initiator == DisconnectInitiator::Server
+    }
+}
+
+impl Display for DisconnectInfo {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match (self.clean, self.initiator) {
+            (true, DisconnectInitiator::Client) => write!(f, "Connection closed by the client")?,
+            (true, DisconnectInitiator::Server) => write!(f, "Connection closed by the server")?,
+            (false, DisconnectInitiator::Client) => write!(f, "Connection aborted by the client")?,
+            (false, DisconnectInitiator::Server) => write!(f, "Connection aborted")?,
+        }
+        if let Some(code) = self.code {
+            write!(f, " with code {}", code)?;
+        }
+        if !self.reason.is_empty() {
+            write!(f, ": {}", self.reason)?;
+        }
+        Ok(())
+    }
+}
+
+impl Error for DisconnectInfo {}
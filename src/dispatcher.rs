@@ -0,0 +1,839 @@
+use crate::client_listener::{ClientListener, ClientListeners};
+use crate::client_metrics::{DispatchStall, MetricsRecorder};
+use crate::item_update::ItemUpdate;
+use crate::logger::LogCategory;
+use crate::ls_client::LogType;
+use crate::runtime::{spawn_task, CurrentRuntime, Instant, Runtime, TaskHandle};
+use crate::subscription::{find_subscription_mut, BackpressurePolicy, Subscription};
+use crate::subscription_listener::SubscriptionListener;
+use crate::util::call_listener;
+
+use crossbeam_queue::{ArrayQueue, SegQueue};
+use futures_util::future::{self, Either};
+use std::collections::{BTreeMap, VecDeque};
+use std::pin::pin;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, Weak};
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::sync::Notify;
+use tracing::Level;
+
+/// Listener event waiting to be dispatched.
+type Event = Box<dyn FnOnce() + Send>;
+
+/// Queue of the listener events of a `LightstreamerClient`, consumed by a single dispatch task.
+///
+/// All the notifications for a client, to `ClientListener`, `SubscriptionListener` and
+/// `ClientMessageListener` alike, are queued here by the session task (or by the client itself)
+/// and invoked one at a time, in order, by the dispatch task. This way a slow listener never
+/// blocks the network read loop, and listeners never run concurrently with each other.
+///
+/// The dispatch task is started by `start()`, which must be called within the async runtime;
+/// events queued before are kept until then. The task terminates when all the clones of the
+/// dispatcher are dropped, or when it's stopped by `join()`. If it's dropped before, e.g. with the
+/// runtime it was running on, the queue is handed back, so that the next `start()` can resume the
+/// dispatching on another runtime.
+#[derive(Clone)]
+pub(crate) struct EventDispatcher {
+    sender: UnboundedSender<Event>,
+    /// The receiving side of the queue, until it's moved to the dispatch task.
+    receiver: Arc<Mutex<Option<UnboundedReceiver<Event>>>>,
+    /// Handle to the running dispatch task, if any.
+    task: Arc<Mutex<Option<TaskHandle>>>,
+    /// Flag set by the last event run by the dispatch task before it's stopped.
+    stopping: Arc<AtomicBool>,
+    /// Handles to the workers of the dispatch pools started through this dispatcher.
+    workers: Arc<Mutex<Vec<TaskHandle>>>,
+    /// Client listeners shared with the client.
+    listeners: Arc<Mutex<ClientListeners>>,
+    /// Subscriptions shared with the client.
+    subscriptions: Arc<Mutex<Vec<Subscription>>>,
+    /// Watchdog of the listener calls run by the dispatch task.
+    watchdog: Arc<Watchdog>,
+}
+
+/// Receiving side of the event queue owned by the dispatch task, which puts it back into the
+/// dispatcher when the task terminates or is dropped.
+struct ReturnedReceiver {
+    receiver: Option<UnboundedReceiver<Event>>,
+    slot: Arc<Mutex<Option<UnboundedReceiver<Event>>>>,
+}
+
+impl ReturnedReceiver {
+    async fn recv(&mut self) -> Option<Event> {
+        self.receiver.as_mut()?.recv().await
+    }
+}
+
+impl Drop for ReturnedReceiver {
+    fn drop(&mut self) {
+        if let Ok(mut slot) = self.slot.lock() {
+            *slot = self.receiver.take();
+        }
+    }
+}
+
+impl EventDispatcher {
+    pub(crate) fn new(
+        listeners: Arc<Mutex<ClientListeners>>,
+        subscriptions: Arc<Mutex<Vec<Subscription>>>,
+        metrics: Arc<MetricsRecorder>,
+    ) -> Self {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        EventDispatcher {
+            sender,
+            receiver: Arc::new(Mutex::new(Some(receiver))),
+            task: Arc::new(Mutex::new(None)),
+            stopping: Arc::new(AtomicBool::new(false)),
+            workers: Arc::new(Mutex::new(Vec::new())),
+            listeners,
+            subscriptions,
+            watchdog: Arc::new(Watchdog::new(metrics)),
+        }
+    }
+
+    /// Starts the dispatch task, unless already running, and the watchdog of its listener calls
+    /// with the given settings, if any, replacing the running one.
+    pub(crate) fn start(&self, watchdog: Option<WatchdogSettings>) {
+        if let Some(handle) = Watchdog::start(&self.watchdog, watchdog) {
+            self.workers.lock().unwrap().push(handle);
+        }
+        let Some(receiver) = self.receiver.lock().unwrap().take() else {
+            return;
+        };
+        let mut receiver = ReturnedReceiver {
+            receiver: Some(receiver),
+            slot: Arc::clone(&self.receiver),
+        };
+        let stopping = Arc::clone(&self.stopping);
+        *self.task.lock().unwrap() = Some(spawn_task(async move {
+            while let Some(event) = receiver.recv().await {
+                event();
+                if stopping.swap(false, Ordering::SeqCst) {
+                    break;
+                }
+            }
+        }));
+    }
+
+    /// Stops the dispatch task once the events queued so far have been run, and waits for it, for
+    /// the watchdog and for the workers of the dispatch pools to terminate. The pools must have
+    /// been dropped, so that their workers terminate after draining their queues.
+    ///
+    /// Events queued afterwards are kept until the next `start()`.
+    pub(crate) async fn join(&self) {
+        let task = self.task.lock().unwrap().take();
+        if let Some(task) = task {
+            // The stop request is queued like any event, so that the previous ones are run first.
+            let stopping = Arc::clone(&self.stopping);
+            self.dispatch(move || stopping.store(true, Ordering::SeqCst));
+            let _ = task.join().await;
+        }
+        self.watchdog.stop();
+        let workers: Vec<TaskHandle> = self.workers.lock().unwrap().drain(..).collect();
+        for worker in workers {
+            let _ = worker.join().await;
+        }
+    }
+
+    /// Describes the listener call blocking the dispatch task, if the watchdog reported it as
+    /// stalled.
+    pub(crate) fn stall(&self) -> Option<DispatchStall> {
+        self.watchdog.stall()
+    }
+
+    /// Queues an event to be run by the dispatch task.
+    pub(crate) fn dispatch(&self, event: impl FnOnce() + Send + 'static) {
+        // Sending fails only if the dispatch task is gone, when nobody can listen anymore.
+        let _ = self.sender.send(Box::new(event));
+    }
+
+    /// Queues the notification of an event to all the client listeners.
+    pub(crate) fn notify_client_listeners(
+        &self,
+        logging: LogType,
+        callback: &'static str,
+        notify: impl Fn(&dyn ClientListener) + Send + 'static,
+    ) {
+        let listeners = Arc::clone(&self.listeners);
+        let watchdog = Arc::clone(&self.watchdog);
+        self.dispatch(move || {
+            let mut listeners = listeners.lock().unwrap();
+            listeners.remove_expired();
+            for listener in listeners.iter() {
+                watchdog.watch(LogCategory::Connections, callback, None, || {
+                    call_listener(logging, LogCategory::Connections, callback, || {
+                        notify(listener.as_ref())
+                    })
+                });
+            }
+        });
+    }
+
+    /// Queues the notification of the removal of a client listener, which is dropped afterwards.
+    pub(crate) fn notify_removed_client_listener(
+        &self,
+        logging: LogType,
+        listener: Box<dyn ClientListener>,
+    ) {
+        let watchdog = Arc::clone(&self.watchdog);
+        self.dispatch(move || {
+            watchdog.watch(LogCategory::Connections, "onListenEnd", None, || {
+                call_listener(logging, LogCategory::Connections, "onListenEnd", || {
+                    listener.on_listen_end()
+                })
+            });
+        });
+    }
+
+    /// Queues the notification of an event to a `ClientMessageListener`.
+    pub(crate) fn notify_message_listener(
+        &self,
+        logging: LogType,
+        callback: &'static str,
+        notify: impl FnOnce() + Send + 'static,
+    ) {
+        let watchdog = Arc::clone(&self.watchdog);
+        self.dispatch(move || {
+            watchdog.watch(LogCategory::Messages, callback, None, || {
+                call_listener(logging, LogCategory::Messages, callback, notify)
+            });
+        });
+    }
+
+    /// Queues the notification of an event to all the listeners of the subscription with the given
+    /// key in the client list.
+    pub(crate) fn notify_subscription_listeners(
+        &self,
+        key: usize,
+        logging: LogType,
+        callback: &'static str,
+        notify: impl FnMut(&mut dyn SubscriptionListener) + Send + 'static,
+    ) {
+        let subscriptions = Arc::clone(&self.subscriptions);
+        let watchdog = Arc::clone(&self.watchdog);
+        self.dispatch(move || {
+            let mut subscriptions = subscriptions.lock().unwrap();
+            let Some(subscription) = find_subscription_mut(&mut subscriptions, key) else {
+                return;
+            };
+            notify_listeners(subscription, &watchdog, logging, callback, notify);
+        });
+    }
+
+    /// Queues the `onUnsubscription` notification of a subscription removed from the client while
+    /// subscribed to, dropping the subscription once notified.
+    pub(crate) fn notify_removed_subscription(
+        &self,
+        logging: LogType,
+        mut subscription: Subscription,
+    ) {
+        let watchdog = Arc::clone(&self.watchdog);
+        self.dispatch(move || {
+            notify_listeners(
+                &mut subscription,
+                &watchdog,
+                logging,
+                "onUnsubscription",
+                |listener| listener.on_unsubscription(),
+            );
+        });
+    }
+
+    /// Queues the notification of a batch of item updates to all the listeners of the
+    /// subscription with the given key in the client list, followed by the given pooled
+    /// listeners, if any.
+    pub(crate) fn notify_item_updates(
+        &self,
+        key: usize,
+        logging: LogType,
+        pooled_listeners: Option<PooledListeners>,
+        updates: Vec<ItemUpdate>,
+    ) {
+        let subscriptions = Arc::clone(&self.subscriptions);
+        let watchdog = Arc::clone(&self.watchdog);
+        self.dispatch(move || {
+            deliver_updates(
+                &subscriptions,
+                &watchdog,
+                key,
+                logging,
+                pooled_listeners.as_ref(),
+                &updates,
+            )
+        });
+    }
+
+    /// Queues the delivery of the next update of a backpressure queue to the listeners of the
+    /// subscription with the given key in the client list, preceded by the notification of the
+    /// updates discarded so far. The delivery is queued again as long as the queue is not empty, so
+    /// that the dispatch queue holds at most one pending delivery per backpressure queue.
+    pub(crate) fn notify_queued_update(
+        &self,
+        key: usize,
+        logging: LogType,
+        pooled_listeners: Option<PooledListeners>,
+        queue: Arc<UpdateQueue>,
+    ) {
+        let dispatcher = self.clone();
+        self.dispatch(move || {
+            let (lost_updates, update, more) = queue.pop();
+            let mut subscriptions = dispatcher.subscriptions.lock().unwrap();
+            if let Some(subscription) = find_subscription_mut(&mut subscriptions, key) {
+                let watched = subscription.get_watched().cloned();
+                let isolated = watched
+                    .as_ref()
+                    .is_some_and(|watched| watched.is_isolated());
+                for (item_pos, (item_name, lost_updates)) in lost_updates {
+                    if isolated {
+                        break;
+                    }
+                    for listener in subscription.get_listeners_mut() {
+                        dispatcher.watchdog.watch(
+                            LogCategory::Subscriptions,
+                            "onItemLostUpdates",
+                            watched.as_ref(),
+                            || {
+                                call_listener(
+                                    logging,
+                                    LogCategory::Subscriptions,
+                                    "onItemLostUpdates",
+                                    || {
+                                        listener.on_item_lost_updates(
+                                            item_name.as_deref(),
+                                            item_pos,
+                                            lost_updates,
+                                        )
+                                    },
+                                )
+                            },
+                        );
+                    }
+                }
+            }
+            drop(subscriptions);
+            if let Some(update) = update {
+                deliver_updates(
+                    &dispatcher.subscriptions,
+                    &dispatcher.watchdog,
+                    key,
+                    logging,
+                    pooled_listeners.as_ref(),
+                    std::slice::from_ref(&update),
+                );
+            }
+            if more {
+                dispatcher.notify_queued_update(key, logging, pooled_listeners, queue);
+            }
+        });
+    }
+}
+
+/// Settings of the dispatch watchdog, taken from the `ConnectionOptions` on `connect()`.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct WatchdogSettings {
+    /// Time after which a listener call that didn't return is reported as stalled.
+    pub(crate) timeout: Duration,
+    /// Whether the subscription of a stalled listener is isolated.
+    pub(crate) isolation: bool,
+    pub(crate) logging: LogType,
+}
+
+/// State of an active subscription shared with the dispatch watchdog, which can't lock the
+/// subscriptions of the client while a stalled listener call holds them.
+#[derive(Debug)]
+pub(crate) struct WatchedSubscription {
+    /// Description of the subscription reported in a `DispatchStall`.
+    label: String,
+    /// Whether the listeners of the subscription are skipped, after one of them stalled.
+    isolated: AtomicBool,
+}
+
+impl WatchedSubscription {
+    pub(crate) fn new(label: String) -> Self {
+        WatchedSubscription {
+            label,
+            isolated: AtomicBool::new(false),
+        }
+    }
+
+    pub(crate) fn is_isolated(&self) -> bool {
+        self.isolated.load(Ordering::Relaxed)
+    }
+}
+
+/// Listener call being run by the dispatch task.
+struct RunningCall {
+    category: LogCategory,
+    callback: &'static str,
+    started_at: Instant,
+    subscription: Option<Arc<WatchedSubscription>>,
+    /// Whether the call was already reported as stalled.
+    stalled: bool,
+}
+
+/// Watchdog of the listener calls run by the dispatch task, which reports the calls that don't
+/// return within the configured timeout, e.g. because a listener is blocked forever.
+///
+/// A stalled call can't be interrupted: it is logged and accounted in the metrics of the client,
+/// and, if so configured, its subscription is isolated, so that its listeners are skipped once the
+/// call returns and the notifications queued for the other ones can catch up.
+struct Watchdog {
+    settings: Mutex<Option<WatchdogSettings>>,
+    /// Whether the listener calls are tracked, i.e. whether a watchdog task is running.
+    enabled: AtomicBool,
+    running: Mutex<Option<RunningCall>>,
+    /// Incremented to stop the current watchdog task, if any.
+    generation: AtomicU64,
+    /// Signal waking the watchdog task up when it's stopped.
+    stopped: Arc<Notify>,
+    metrics: Arc<MetricsRecorder>,
+}
+
+impl Watchdog {
+    fn new(metrics: Arc<MetricsRecorder>) -> Self {
+        Watchdog {
+            settings: Mutex::new(None),
+            enabled: AtomicBool::new(false),
+            running: Mutex::new(None),
+            generation: AtomicU64::new(0),
+            stopped: Arc::new(Notify::new()),
+            metrics,
+        }
+    }
+
+    /// Stops the current watchdog task, if any, and starts a new one with the given settings, if
+    /// any, returning its handle.
+    fn start(watchdog: &Arc<Watchdog>, settings: Option<WatchdogSettings>) -> Option<TaskHandle> {
+        watchdog.stop();
+        *watchdog.settings.lock().unwrap() = settings;
+        let settings = settings?;
+        watchdog.enabled.store(true, Ordering::SeqCst);
+        let generation = watchdog.generation.load(Ordering::SeqCst);
+        let stopped = Arc::clone(&watchdog.stopped);
+        // Checking 4 times per timeout reports a stall at most a quarter of the timeout late.
+        let period = (settings.timeout / 4).max(Duration::from_millis(1));
+        Some(spawn_task(Watchdog::run(
+            Arc::downgrade(watchdog),
+            stopped,
+            generation,
+            period,
+        )))
+    }
+
+    async fn run(
+        watchdog: Weak<Watchdog>,
+        stopped: Arc<Notify>,
+        generation: u64,
+        period: Duration,
+    ) {
+        loop {
+            // Registered before checking, so that a stop in between is not missed.
+            let mut stop = pin!(stopped.notified());
+            stop.as_mut().enable();
+            {
+                let Some(watchdog) = watchdog.upgrade() else {
+                    return;
+                };
+                if watchdog.generation.load(Ordering::SeqCst) != generation {
+                    return;
+                }
+                watchdog.check();
+            }
+            let sleep = pin!(CurrentRuntime::sleep(period));
+            if let Either::Right(_) = future::select(sleep, stop).await {
+                return;
+            }
+        }
+    }
+
+    /// Stops the current watchdog task, if any.
+    fn stop(&self) {
+        self.enabled.store(false, Ordering::SeqCst);
+        self.generation.fetch_add(1, Ordering::SeqCst);
+        self.stopped.notify_waiters();
+    }
+
+    /// Runs a listener call, tracking it while the watchdog is enabled.
+    fn watch(
+        &self,
+        category: LogCategory,
+        callback: &'static str,
+        subscription: Option<&Arc<WatchedSubscription>>,
+        call: impl FnOnce(),
+    ) {
+        if !self.enabled.load(Ordering::Relaxed) {
+            call();
+            return;
+        }
+        *self.running.lock().unwrap() = Some(RunningCall {
+            category,
+            callback,
+            started_at: Instant::now(),
+            subscription: subscription.cloned(),
+            stalled: false,
+        });
+        call();
+        let finished = self.running.lock().unwrap().take();
+        if let (Some(call), Some(settings)) = (finished, *self.settings.lock().unwrap()) {
+            if call.stalled {
+                settings.logging.log(
+                    call.category,
+                    Level::INFO,
+                    &format!(
+                        "Listener returned from {} after {:?}",
+                        call.callback,
+                        call.started_at.elapsed()
+                    ),
+                );
+            }
+        }
+    }
+
+    /// Reports the running listener call, if it has been running for longer than the timeout.
+    fn check(&self) {
+        let Some(settings) = *self.settings.lock().unwrap() else {
+            return;
+        };
+        let mut running = self.running.lock().unwrap();
+        let Some(call) = running.as_mut() else {
+            return;
+        };
+        if call.stalled || call.started_at.elapsed() < settings.timeout {
+            return;
+        }
+        call.stalled = true;
+        self.metrics.dispatch_stall();
+        let mut message = format!("Listener stalled in {}", call.callback);
+        if let Some(subscription) = &call.subscription {
+            message.push_str(&format!(" of subscription to '{}'", subscription.label));
+            if settings.isolation {
+                subscription.isolated.store(true, Ordering::Relaxed);
+                message.push_str(", which is now isolated");
+            }
+        }
+        settings.logging.log(
+            call.category,
+            Level::WARN,
+            &format!("{} for {:?}", message, call.started_at.elapsed()),
+        );
+    }
+
+    /// Describes the running listener call, if it was reported as stalled.
+    fn stall(&self) -> Option<DispatchStall> {
+        let running = self.running.lock().unwrap();
+        let call = running.as_ref().filter(|call| call.stalled)?;
+        Some(DispatchStall {
+            callback: call.callback.to_string(),
+            subscription: call
+                .subscription
+                .as_ref()
+                .map(|subscription| subscription.label.clone()),
+            stalled_for: call.started_at.elapsed(),
+            isolated: call
+                .subscription
+                .as_ref()
+                .is_some_and(|subscription| subscription.is_isolated()),
+        })
+    }
+}
+
+/// Invokes a callback on the listeners of a subscription, unless it was isolated by the watchdog.
+fn notify_listeners(
+    subscription: &mut Subscription,
+    watchdog: &Watchdog,
+    logging: LogType,
+    callback: &'static str,
+    mut notify: impl FnMut(&mut dyn SubscriptionListener),
+) {
+    let watched = subscription.get_watched().cloned();
+    if watched
+        .as_ref()
+        .is_some_and(|watched| watched.is_isolated())
+    {
+        return;
+    }
+    subscription.remove_expired_listeners();
+    for listener in subscription.get_listeners_mut() {
+        watchdog.watch(
+            LogCategory::Subscriptions,
+            callback,
+            watched.as_ref(),
+            || {
+                call_listener(logging, LogCategory::Subscriptions, callback, || {
+                    notify(listener.as_mut())
+                })
+            },
+        );
+    }
+}
+
+/// Invokes `onItemUpdates()` on the listeners of the subscription with the given key in the
+/// client list and on the given pooled listeners, if any, unless the subscription was isolated by
+/// the watchdog.
+fn deliver_updates(
+    subscriptions: &Mutex<Vec<Subscription>>,
+    watchdog: &Watchdog,
+    key: usize,
+    logging: LogType,
+    pooled_listeners: Option<&PooledListeners>,
+    updates: &[ItemUpdate],
+) {
+    let mut subscriptions = subscriptions.lock().unwrap();
+    let Some(subscription) = find_subscription_mut(&mut subscriptions, key) else {
+        return;
+    };
+    let watched = subscription.get_watched().cloned();
+    if watched
+        .as_ref()
+        .is_some_and(|watched| watched.is_isolated())
+    {
+        return;
+    }
+    subscription.remove_expired_listeners();
+    for listener in subscription.get_listeners() {
+        watchdog.watch(
+            LogCategory::Subscriptions,
+            "onItemUpdates",
+            watched.as_ref(),
+            || {
+                call_listener(logging, LogCategory::Subscriptions, "onItemUpdates", || {
+                    listener.on_item_updates(updates)
+                })
+            },
+        );
+    }
+    drop(subscriptions);
+    if let Some(pooled_listeners) = pooled_listeners {
+        watchdog.watch(
+            LogCategory::Subscriptions,
+            "onItemUpdates",
+            watched.as_ref(),
+            || notify_pooled_listeners(pooled_listeners, logging, updates),
+        );
+    }
+}
+
+/// Buffer of the item updates of a subscription waiting to be dispatched, managed according to
+/// its `BackpressurePolicy`.
+///
+/// The updates are pushed by the session task and popped by the dispatch task. Apart from
+/// `BackpressurePolicy::ConflateLatest`, which needs to find the update queued for an item, the
+/// handoff goes through a lock-free queue, so that the network reader never waits for a listener
+/// holding a lock under bursty loads. The updates discarded by the policy are accounted per item,
+/// to be notified to the listeners as lost updates before the next delivery.
+pub(crate) struct UpdateQueue {
+    policy: BackpressurePolicy,
+    updates: UpdateBuffer,
+    /// Whether a delivery of the queue is pending in the dispatch queue.
+    scheduled: AtomicBool,
+    /// Updates discarded since the last delivery, only locked when some are.
+    lost_updates: Mutex<LostUpdates>,
+    /// Whether `lost_updates` is not empty, so that deliveries don't need to lock it otherwise.
+    has_lost_updates: AtomicBool,
+    /// Signal used by the dispatch task to notify that updates have been popped.
+    space: Notify,
+}
+
+/// Number of updates discarded for each item, with the item name, indexed by item position.
+type LostUpdates = BTreeMap<usize, (Option<String>, u32)>;
+
+/// Storage of the updates of an `UpdateQueue`, depending on its policy.
+enum UpdateBuffer {
+    /// Lock-free queue without limits, whose size is checked by `BackpressurePolicy::Block`.
+    Unbounded(SegQueue<ItemUpdate>),
+    /// Lock-free queue with the capacity of a dropping policy.
+    Bounded(ArrayQueue<ItemUpdate>),
+    /// Queue searched by item when an update is pushed.
+    Conflated(Mutex<VecDeque<ItemUpdate>>),
+}
+
+impl UpdateBuffer {
+    fn pop(&self) -> Option<ItemUpdate> {
+        match self {
+            UpdateBuffer::Unbounded(updates) => updates.pop(),
+            UpdateBuffer::Bounded(updates) => updates.pop(),
+            UpdateBuffer::Conflated(updates) => updates.lock().unwrap().pop_front(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        match self {
+            UpdateBuffer::Unbounded(updates) => updates.len(),
+            UpdateBuffer::Bounded(updates) => updates.len(),
+            UpdateBuffer::Conflated(updates) => updates.lock().unwrap().len(),
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl UpdateQueue {
+    pub(crate) fn new(policy: BackpressurePolicy) -> Self {
+        let updates = match policy {
+            BackpressurePolicy::Unbounded | BackpressurePolicy::Block(_) => {
+                UpdateBuffer::Unbounded(SegQueue::new())
+            }
+            BackpressurePolicy::DropOldest(capacity) | BackpressurePolicy::DropNewest(capacity) => {
+                UpdateBuffer::Bounded(ArrayQueue::new(capacity.max(1)))
+            }
+            BackpressurePolicy::ConflateLatest => {
+                UpdateBuffer::Conflated(Mutex::new(VecDeque::new()))
+            }
+        };
+        UpdateQueue {
+            policy,
+            updates,
+            scheduled: AtomicBool::new(false),
+            lost_updates: Mutex::new(LostUpdates::new()),
+            has_lost_updates: AtomicBool::new(false),
+            space: Notify::new(),
+        }
+    }
+
+    /// Queues an update, applying the policy. Returns `true` if a delivery of the queue has to be
+    /// scheduled, as none is pending.
+    pub(crate) fn push(&self, update: ItemUpdate) -> bool {
+        match (&self.updates, self.policy) {
+            (UpdateBuffer::Unbounded(updates), _) => updates.push(update),
+            (UpdateBuffer::Bounded(updates), BackpressurePolicy::DropOldest(_)) => {
+                if let Some(oldest) = updates.force_push(update) {
+                    self.count_lost(&oldest);
+                }
+            }
+            (UpdateBuffer::Bounded(updates), _) => {
+                if let Err(update) = updates.push(update) {
+                    self.count_lost(&update);
+                }
+            }
+            (UpdateBuffer::Conflated(updates), _) => {
+                let mut updates = updates.lock().unwrap();
+                let item_pos = update.get_item_pos();
+                match updates
+                    .iter()
+                    .position(|queued| queued.get_item_pos() == item_pos)
+                {
+                    Some(position) => {
+                        // The replaced update is lost, but its changes are carried over.
+                        self.count_lost(&update);
+                        let queued = &mut updates[position];
+                        let mut update = update;
+                        update.merge_changes(queued);
+                        *queued = update;
+                    }
+                    None => updates.push_back(update),
+                }
+            }
+        }
+        !self.updates.is_empty() && !self.scheduled.swap(true, Ordering::SeqCst)
+    }
+
+    fn count_lost(&self, update: &ItemUpdate) {
+        let mut lost_updates = self.lost_updates.lock().unwrap();
+        let (_, lost) = lost_updates
+            .entry(update.get_item_pos())
+            .or_insert_with(|| (update.get_item_name().map(str::to_string), 0));
+        *lost = lost.saturating_add(1);
+        self.has_lost_updates.store(true, Ordering::SeqCst);
+    }
+
+    /// Checks whether the queue of a `BackpressurePolicy::Block` policy is full.
+    pub(crate) fn is_full(&self) -> bool {
+        match self.policy {
+            BackpressurePolicy::Block(capacity) => self.updates.len() >= capacity,
+            _ => false,
+        }
+    }
+
+    /// Waits until the queue is no longer full.
+    pub(crate) async fn wait_for_space(&self) {
+        loop {
+            // Registered before checking, so that a pop in between is not missed.
+            let popped = self.space.notified();
+            if !self.is_full() {
+                return;
+            }
+            popped.await;
+        }
+    }
+
+    /// Takes the lost updates accounted so far and the next update to be delivered, if any, and
+    /// tells whether more updates are queued.
+    fn pop(&self) -> (LostUpdates, Option<ItemUpdate>, bool) {
+        let lost_updates = if self.has_lost_updates.swap(false, Ordering::SeqCst) {
+            std::mem::take(&mut *self.lost_updates.lock().unwrap())
+        } else {
+            LostUpdates::new()
+        };
+        let update = self.updates.pop();
+        let mut more = !self.updates.is_empty();
+        if !more {
+            // An update pushed after the check, but before the flag is cleared, would not be
+            // scheduled by `push()`: the queue is checked again once the flag is cleared.
+            self.scheduled.store(false, Ordering::SeqCst);
+            more = !self.updates.is_empty() && !self.scheduled.swap(true, Ordering::SeqCst);
+        }
+        self.space.notify_waiters();
+        (lost_updates, update, more)
+    }
+}
+
+/// Listeners added to a subscription through `Subscription.addPooledListener()`.
+pub(crate) type PooledListeners = Arc<Mutex<Vec<Arc<dyn SubscriptionListener + Sync>>>>;
+
+/// Invokes `onItemUpdates()` on the given pooled listeners.
+fn notify_pooled_listeners(listeners: &PooledListeners, logging: LogType, updates: &[ItemUpdate]) {
+    // The lock is not held during the calls, so that other workers can proceed in parallel.
+    let listeners = listeners.lock().unwrap().clone();
+    for listener in listeners {
+        call_listener(logging, LogCategory::Subscriptions, "onItemUpdates", || {
+            listener.on_item_updates(updates)
+        });
+    }
+}
+
+/// Pool of worker tasks dispatching the item updates of a subscription using
+/// `DispatchMode::Pooled` to its pooled listeners.
+///
+/// Each item is bound to a worker by its position, so that the updates of the same item are
+/// dispatched in order while the ones of different items are dispatched in parallel. The workers
+/// terminate, after draining their queues, when the pool is dropped.
+pub(crate) struct DispatchPool {
+    workers: Vec<UnboundedSender<ItemUpdate>>,
+}
+
+impl DispatchPool {
+    /// Starts a pool of the given number of workers, which must be positive. The workers are
+    /// tracked by the given dispatcher, so that `EventDispatcher::join()` waits for them.
+    pub(crate) fn start(
+        workers: usize,
+        listeners: PooledListeners,
+        logging: LogType,
+        dispatcher: &EventDispatcher,
+    ) -> Self {
+        let mut handles = dispatcher.workers.lock().unwrap();
+        handles.retain_mut(|handle| !handle.is_finished());
+        let workers = (0..workers)
+            .map(|_| {
+                let (sender, mut receiver) = mpsc::unbounded_channel::<ItemUpdate>();
+                let listeners = Arc::clone(&listeners);
+                handles.push(spawn_task(async move {
+                    while let Some(update) = receiver.recv().await {
+                        notify_pooled_listeners(&listeners, logging, std::slice::from_ref(&update));
+                    }
+                }));
+                sender
+            })
+            .collect();
+        DispatchPool { workers }
+    }
+
+    /// Queues an update to the worker its item is bound to.
+    pub(crate) fn dispatch(&self, update: ItemUpdate) {
+        let worker = &self.workers[update.get_item_pos() % self.workers.len()];
+        let _ = worker.send(update);
+    }
+}
@@ -1,6 +1,5 @@
 use std::error::Error;
 use std::fmt;
-use tracing::error;
 
 #[derive(Debug)]
 pub struct IllegalArgumentException(String);
@@ -47,3 +46,58 @@ impl Error for IllegalStateException {
         &self.details
     }
 }
+
+#[derive(Debug)]
+pub struct TimeoutException {
+    details: String,
+}
+
+impl TimeoutException {
+    pub fn new(msg: &str) -> TimeoutException {
+        TimeoutException {
+            details: msg.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for TimeoutException {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.details)
+    }
+}
+
+impl Error for TimeoutException {
+    fn description(&self) -> &str {
+        &self.details
+    }
+}
+
+/// Error listing all the constraints between the `ConnectionOptions` that are violated, raised by
+/// `ConnectionOptions.validate()`.
+#[derive(Debug)]
+pub struct InvalidOptionsException {
+    violations: Vec<String>,
+}
+
+impl InvalidOptionsException {
+    pub fn new(violations: Vec<String>) -> InvalidOptionsException {
+        InvalidOptionsException { violations }
+    }
+
+    /// Gets the description of each violated constraint.
+    pub fn get_violations(&self) -> &[String] {
+        &self.violations
+    }
+}
+
+impl fmt::Display for InvalidOptionsException {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Invalid connection options: {}",
+            self.violations.join("; ")
+        )
+    }
+}
+
+impl Error for InvalidOptionsException {}
@@ -0,0 +1,234 @@
+//! Bounded history of the recent significant events of a `LightstreamerClient`, obtained through
+//! `LightstreamerClient.recentEvents()`, so that the behavior of a client can be inspected after
+//! something went wrong, even when its logs were not enabled.
+//!
+//! The history records the status changes and the diagnostics of the client at the INFO level and
+//! above, such as errors, retries and session lifecycle events. The frames exchanged with the
+//! Server are not recorded one by one, which would evict everything else in a few moments under
+//! load, but summarized at most once per second.
+
+use crate::clock::system_time_now;
+use crate::runtime::Instant;
+
+use std::collections::VecDeque;
+use std::fmt::{self, Display, Formatter};
+use std::sync::Mutex;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tracing::Level;
+
+/// Default number of events kept by the history of a client.
+pub const DEFAULT_EVENT_HISTORY_SIZE: usize = 256;
+
+/// Interval over which the frames exchanged with the Server are summarized in a single event.
+const FRAME_SUMMARY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Kind of a `ClientEvent`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ClientEventKind {
+    /// The status of the client changed, as notified through `ClientListener.onStatusChange()`.
+    StatusChange,
+    /// Summary of the frames exchanged with the Server over an interval.
+    Frames,
+    /// Diagnostic of the normal activity of the client, e.g. a retry or a session recovery.
+    Info,
+    /// Diagnostic of an unexpected but recoverable condition.
+    Warning,
+    /// Diagnostic of an error.
+    Error,
+}
+
+impl Display for ClientEventKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientEventKind::StatusChange => write!(f, "STATUS"),
+            ClientEventKind::Frames => write!(f, "FRAMES"),
+            ClientEventKind::Info => write!(f, "INFO"),
+            ClientEventKind::Warning => write!(f, "WARN"),
+            ClientEventKind::Error => write!(f, "ERROR"),
+        }
+    }
+}
+
+/// Significant event in the recent history of a `LightstreamerClient`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClientEvent {
+    /// Time of the event, or, for a summary of frames, of the first frame summarized.
+    pub time: SystemTime,
+    /// Kind of the event.
+    pub kind: ClientEventKind,
+    /// Description of the event.
+    pub description: String,
+}
+
+impl Display for ClientEvent {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let millis = self
+            .time
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_millis();
+        write!(
+            f,
+            "{}.{:03} {} {}",
+            millis / 1000,
+            millis % 1000,
+            self.kind,
+            self.description
+        )
+    }
+}
+
+/// Frames exchanged since the last summary.
+struct FrameSummary {
+    started_at: Instant,
+    time: SystemTime,
+    frames_received: u64,
+    bytes_received: u64,
+    frames_sent: u64,
+    bytes_sent: u64,
+}
+
+impl FrameSummary {
+    fn to_event(&self) -> ClientEvent {
+        ClientEvent {
+            time: self.time,
+            kind: ClientEventKind::Frames,
+            description: format!(
+                "Received {} frames ({} bytes), sent {} frames ({} bytes) in {} ms",
+                self.frames_received,
+                self.bytes_received,
+                self.frames_sent,
+                self.bytes_sent,
+                self.started_at.elapsed().as_millis()
+            ),
+        }
+    }
+}
+
+#[derive(Default)]
+struct HistoryState {
+    capacity: usize,
+    events: VecDeque<ClientEvent>,
+    frames: Option<FrameSummary>,
+}
+
+impl HistoryState {
+    fn push(&mut self, event: ClientEvent) {
+        if self.events.len() >= self.capacity {
+            self.events.pop_front();
+        }
+        self.events.push_back(event);
+    }
+
+    fn flush_frames(&mut self) {
+        if let Some(frames) = self.frames.take() {
+            self.push(frames.to_event());
+        }
+    }
+}
+
+/// Ring buffer of the recent events of a client, shared by the client and its session task.
+#[derive(Default)]
+pub(crate) struct EventHistory {
+    state: Mutex<HistoryState>,
+}
+
+impl EventHistory {
+    pub(crate) fn new(capacity: usize) -> Self {
+        let history = EventHistory::default();
+        history.set_capacity(capacity);
+        history
+    }
+
+    /// Changes the number of events kept, discarding the oldest ones in excess. A capacity of
+    /// zero disables the history.
+    pub(crate) fn set_capacity(&self, capacity: usize) {
+        let mut state = self.state.lock().unwrap();
+        state.capacity = capacity;
+        let excess = state.events.len().saturating_sub(capacity);
+        state.events.drain(..excess);
+        if capacity == 0 {
+            state.frames = None;
+        }
+    }
+
+    /// Records an event, after the summary of the frames exchanged so far, if any.
+    pub(crate) fn record(&self, kind: ClientEventKind, description: &str) {
+        let mut state = self.state.lock().unwrap();
+        if state.capacity == 0 {
+            return;
+        }
+        state.flush_frames();
+        state.push(ClientEvent {
+            time: system_time_now(),
+            kind,
+            description: description.to_string(),
+        });
+    }
+
+    /// Records a diagnostic at the INFO level or above.
+    pub(crate) fn record_log(&self, level: Level, message: &str) {
+        let kind = match level {
+            Level::ERROR => ClientEventKind::Error,
+            Level::WARN => ClientEventKind::Warning,
+            Level::INFO => ClientEventKind::Info,
+            _ => return,
+        };
+        self.record(kind, message);
+    }
+
+    /// Accounts for a frame received from the Server in the current summary.
+    pub(crate) fn frame_received(&self, bytes: usize) {
+        self.frame(|frames| {
+            frames.frames_received += 1;
+            frames.bytes_received += bytes as u64;
+        });
+    }
+
+    /// Accounts for a frame sent to the Server in the current summary.
+    pub(crate) fn frame_sent(&self, bytes: usize) {
+        self.frame(|frames| {
+            frames.frames_sent += 1;
+            frames.bytes_sent += bytes as u64;
+        });
+    }
+
+    fn frame(&self, account: impl FnOnce(&mut FrameSummary)) {
+        let mut state = self.state.lock().unwrap();
+        if state.capacity == 0 {
+            return;
+        }
+        if state
+            .frames
+            .as_ref()
+            .is_some_and(|frames| frames.started_at.elapsed() >= FRAME_SUMMARY_INTERVAL)
+        {
+            state.flush_frames();
+        }
+        let frames = state.frames.get_or_insert_with(|| FrameSummary {
+            started_at: Instant::now(),
+            time: system_time_now(),
+            frames_received: 0,
+            bytes_received: 0,
+            frames_sent: 0,
+            bytes_sent: 0,
+        });
+        account(frames);
+    }
+
+    /// Gets the events recorded, oldest first, including the summary of the frames exchanged
+    /// since the last one.
+    pub(crate) fn events(&self) -> Vec<ClientEvent> {
+        let state = self.state.lock().unwrap();
+        let pending = state.frames.as_ref().map(FrameSummary::to_event);
+        // The pending summary takes the place of the oldest event, as it will when flushed.
+        let skipped = (state.events.len() + pending.iter().len()).saturating_sub(state.capacity);
+        state
+            .events
+            .iter()
+            .skip(skipped)
+            .cloned()
+            .chain(pending)
+            .collect()
+    }
+}
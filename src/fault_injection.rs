@@ -0,0 +1,209 @@
+//! Faults injected at the transport boundary of a `LightstreamerClient`, to exercise its recovery
+//! and parser resilience code paths automatically.
+//!
+//! Available with the `test-util` feature. A `FaultInjector` installed through
+//! `ConnectionOptions.setFaultInjector()` sits between the WebSocket connection and the session
+//! task, and alters the frames received from the Server as requested by the test.
+
+use crate::clock::{Clock, Sleep};
+use crate::runtime::tungstenite::{Error as WsError, Message};
+use crate::runtime::BoxedSocket;
+
+use futures_util::{Sink, Stream};
+use std::collections::VecDeque;
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Waker};
+use std::time::Duration;
+
+/// Alteration of a single frame received from the Server.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum FrameFault {
+    /// Keep only the given number of bytes.
+    Truncate(usize),
+    /// Overwrite the byte at the given position.
+    Corrupt { position: usize, byte: u8 },
+}
+
+#[derive(Debug, Default)]
+struct FaultState {
+    /// Whether the current connection has to be dropped.
+    drop_connection: bool,
+    /// Delay applied to every received frame.
+    frame_delay: Option<Duration>,
+    /// Whether the pongs received are discarded.
+    drop_pongs: bool,
+    /// Alterations of the next received frames, in order.
+    frame_faults: VecDeque<FrameFault>,
+    /// Waker of the connection waiting for the next frame.
+    waker: Option<Waker>,
+}
+
+/// Handle used by tests to inject faults in the connections of a `LightstreamerClient`.
+///
+/// Faults are applied to whatever connection is open when they take effect, so that a single
+/// injector can be kept for the whole life of a client, across reconnections.
+///
+/// See also `ConnectionOptions.setFaultInjector()`
+#[derive(Debug, Default)]
+pub struct FaultInjector {
+    state: Mutex<FaultState>,
+}
+
+impl FaultInjector {
+    /// Creates an injector that doesn't alter the connections until faults are requested.
+    pub fn new() -> FaultInjector {
+        FaultInjector::default()
+    }
+
+    /// Drops the current connection, as if it was reset by the network: the client sees a read
+    /// error and reacts as configured through its retry and recovery settings.
+    pub fn drop_connection(&self) {
+        let mut state = self.state.lock().unwrap();
+        state.drop_connection = true;
+        if let Some(waker) = state.waker.take() {
+            waker.wake();
+        }
+    }
+
+    /// Delays every frame received from now on by the given duration, as measured by the clock
+    /// of the client. Specify `None` to stop delaying frames.
+    pub fn delay_frames(&self, delay: Option<Duration>) {
+        self.state.lock().unwrap().frame_delay = delay;
+    }
+
+    /// Discards the pongs received from now on, as an unresponsive connection would do, while the
+    /// other frames go through. Specify `false` to let pongs through again.
+    pub fn drop_pongs(&self, drop_pongs: bool) {
+        self.state.lock().unwrap().drop_pongs = drop_pongs;
+    }
+
+    /// Truncates the next frame received to the given number of bytes.
+    pub fn truncate_next_frame(&self, length: usize) {
+        self.push_frame_fault(FrameFault::Truncate(length));
+    }
+
+    /// Overwrites the byte at the given position of the next frame received. If the frame is no
+    /// longer valid UTF-8, it is reported as a WebSocket error, as a real connection would do.
+    pub fn corrupt_next_frame(&self, position: usize, byte: u8) {
+        self.push_frame_fault(FrameFault::Corrupt { position, byte });
+    }
+
+    fn push_frame_fault(&self, fault: FrameFault) {
+        self.state.lock().unwrap().frame_faults.push_back(fault);
+    }
+
+    /// Takes the pending request to drop the connection, registering the waker to be notified of
+    /// new ones.
+    fn take_drop_request(&self, waker: &Waker) -> bool {
+        let mut state = self.state.lock().unwrap();
+        state.waker = Some(waker.clone());
+        std::mem::take(&mut state.drop_connection)
+    }
+}
+
+/// Connection applying the faults requested through a `FaultInjector` to the frames received on
+/// another connection. Whatever is sent goes through unchanged.
+pub(crate) struct FaultySocket {
+    inner: BoxedSocket,
+    injector: Arc<FaultInjector>,
+    clock: Arc<dyn Clock>,
+    /// Frame received and waiting for its delay to elapse.
+    delayed: Option<(Message, Sleep)>,
+}
+
+impl FaultySocket {
+    pub(crate) fn new(
+        inner: BoxedSocket,
+        injector: Arc<FaultInjector>,
+        clock: Arc<dyn Clock>,
+    ) -> FaultySocket {
+        FaultySocket {
+            inner,
+            injector,
+            clock,
+            delayed: None,
+        }
+    }
+
+    /// Applies the next frame fault, if any, to a received frame.
+    fn alter(&self, message: Message) -> Result<Message, WsError> {
+        let Message::Text(text) = message else {
+            return Ok(message);
+        };
+        let Some(fault) = self.injector.state.lock().unwrap().frame_faults.pop_front() else {
+            return Ok(Message::Text(text));
+        };
+        let mut bytes = text.as_bytes().to_vec();
+        match fault {
+            FrameFault::Truncate(length) => bytes.truncate(length),
+            FrameFault::Corrupt { position, byte } => {
+                if let Some(target) = bytes.get_mut(position) {
+                    *target = byte;
+                }
+            }
+        }
+        let text = String::from_utf8(bytes).map_err(|err| WsError::from(err.utf8_error()))?;
+        Ok(Message::Text(text.into()))
+    }
+}
+
+impl Stream for FaultySocket {
+    type Item = Result<Message, WsError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.injector.take_drop_request(cx.waker()) {
+            return Poll::Ready(Some(Err(WsError::Io(io::Error::new(
+                io::ErrorKind::ConnectionReset,
+                "Connection dropped by the fault injector",
+            )))));
+        }
+        if let Some((_, sleep)) = &mut self.delayed {
+            if sleep.as_mut().poll(cx).is_pending() {
+                return Poll::Pending;
+            }
+            let (message, _) = self.delayed.take().unwrap();
+            return Poll::Ready(Some(self.alter(message)));
+        }
+        match Pin::new(&mut self.inner).poll_next(cx) {
+            Poll::Ready(Some(Ok(Message::Pong(_))))
+                if self.injector.state.lock().unwrap().drop_pongs =>
+            {
+                self.poll_next(cx)
+            }
+            Poll::Ready(Some(Ok(message))) => {
+                let frame_delay = self.injector.state.lock().unwrap().frame_delay;
+                match frame_delay {
+                    Some(delay) => {
+                        let sleep = self.clock.sleep(delay);
+                        self.delayed = Some((message, sleep));
+                        self.poll_next(cx)
+                    }
+                    None => Poll::Ready(Some(self.alter(message))),
+                }
+            }
+            other => other,
+        }
+    }
+}
+
+impl Sink<Message> for FaultySocket {
+    type Error = WsError;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        Pin::new(&mut self.inner).poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, message: Message) -> Result<(), WsError> {
+        Pin::new(&mut self.inner).start_send(message)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+}
@@ -0,0 +1,424 @@
+//! HTTP streaming and polling transports, built on a `reqwest` client.
+//!
+//! Available with the `reqwest` feature. The session task speaks TLCP over a `Socket` whatever
+//! the transport: an `HttpSocket` turns the requests sent by the task into the requests of the
+//! HTTP binding of TLCP, issued through the `reqwest::Client` configured with
+//! `ConnectionOptions.setHttpClient()`, or through the tower service configured with
+//! `ConnectionOptions.setHttpService()`, and forwards the lines of their responses as the frames
+//! the task would receive over a WebSocket connection.
+
+use crate::connection_options::{ReadBufferGrowth, ReadBufferSettings};
+use crate::ls_client::LightstreamerClient;
+use crate::protocol::encode_params;
+use crate::request_interceptor::{self, InterceptedRequest, RequestInterceptors, RequestKind};
+use crate::runtime::tungstenite::error::UrlError;
+use crate::runtime::tungstenite::http::{HeaderMap as WsHeaderMap, Request};
+use crate::runtime::tungstenite::{Error as WsError, Message};
+
+use futures_util::future::BoxFuture;
+use futures_util::{Sink, Stream};
+use reqwest::header::{HeaderMap, HeaderName, HeaderValue};
+use reqwest::{Method, Url};
+use std::error::Error;
+use std::io;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, OnceLock};
+use std::task::{Context, Poll};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio::task::JoinHandle;
+
+/// Headers of the WebSocket handshake that are not copied to the HTTP requests.
+const HANDSHAKE_HEADERS: &[&str] = &[
+    "connection",
+    "host",
+    "sec-websocket-key",
+    "sec-websocket-protocol",
+    "sec-websocket-version",
+    "upgrade",
+];
+
+/// Converts the headers of a WebSocket handshake into the ones of the HTTP client, which may
+/// depend on a different version of the `http` crate. Headers that can't be converted are dropped.
+fn convert_headers(headers: &WsHeaderMap) -> impl Iterator<Item = (HeaderName, HeaderValue)> + '_ {
+    headers.iter().filter_map(|(name, value)| {
+        Some((
+            HeaderName::from_bytes(name.as_str().as_bytes()).ok()?,
+            HeaderValue::from_bytes(value.as_bytes()).ok()?,
+        ))
+    })
+}
+
+/// Requests whose response is the stream of the notifications of the session.
+const SESSION_REQUESTS: &[&str] = &["create_session", "bind_session", "recover_session"];
+
+/// Frame received by the session task, or the error that ended the connection.
+type Frame = Result<Message, WsError>;
+
+/// Error returned when issuing a request.
+type HttpError = Box<dyn Error + Send + Sync>;
+
+/// Function issuing the requests of the HTTP connections, either through a `reqwest::Client` or
+/// through a tower service.
+pub(crate) type HttpExecutor = Arc<
+    dyn Fn(reqwest::Request) -> BoxFuture<'static, Result<reqwest::Response, HttpError>>
+        + Send
+        + Sync,
+>;
+
+/// Gets the executor issuing the requests through the given client, or else through a client
+/// with the default configuration, created upon the first request.
+pub(crate) fn client_executor(client: Option<reqwest::Client>) -> HttpExecutor {
+    let client = client.map_or_else(OnceLock::new, OnceLock::from);
+    Arc::new(move |request| {
+        let response = client.get_or_init(reqwest::Client::new).execute(request);
+        Box::pin(async move { Ok(response.await?) })
+    })
+}
+
+/// Gets the executor issuing the requests through the given tower service, waiting for it to be
+/// ready before each request.
+#[cfg(feature = "tower")]
+pub(crate) fn service_executor(service: crate::connection_options::HttpService) -> HttpExecutor {
+    Arc::new(move |request| Box::pin(service.clone().call(request)))
+}
+
+/// Connection to the server over HTTP, driven by the session task like a WebSocket connection:
+///
+/// - `wsok` is answered locally, as there is no WebSocket handshake to confirm;
+/// - `create_session`, `bind_session` and `recover_session` open a new stream of notifications,
+///   which replaces the previous one, if any;
+/// - `control`, `msg` and `heartbeat` are sent on requests of their own, bound to the session
+///   notified by the last `CONOK`, and their responses are forwarded like the notifications;
+/// - pings are answered locally, and a close frame ends the connection.
+///
+/// Cookies set by the server are only kept if the `reqwest::Client` has a cookie store of its
+/// own.
+pub(crate) struct HttpSocket {
+    executor: HttpExecutor,
+    /// Address of the TLCP endpoint, ending with "/lightstreamer/".
+    endpoint: Url,
+    /// Headers of the WebSocket handshake added to every request, e.g. the cookies.
+    headers: HeaderMap,
+    /// Headers added to the requests that create a session only.
+    session_creation_headers: HeaderMap,
+    /// Chain of the interceptors of the requests.
+    interceptors: RequestInterceptors,
+    /// Settings of the buffers of the streams of notifications.
+    read_buffer: ReadBufferSettings,
+    /// ID of the session, as notified by the last `CONOK` received.
+    session_id: Arc<Mutex<Option<String>>>,
+    sender: UnboundedSender<Frame>,
+    receiver: UnboundedReceiver<Frame>,
+    /// Task reading the stream of notifications of the session.
+    stream_task: Option<JoinHandle<()>>,
+    /// Whether a close frame has been sent.
+    closing: bool,
+    /// Whether the close frame has been delivered back to the session task.
+    closed: bool,
+}
+
+impl HttpSocket {
+    /// Creates a connection to the server the given WebSocket handshake is addressed to. No
+    /// request is issued until the session task sends one.
+    pub(crate) fn new(
+        executor: HttpExecutor,
+        ws_request: &Request<()>,
+        session_creation_headers: &WsHeaderMap,
+        interceptors: RequestInterceptors,
+        read_buffer: ReadBufferSettings,
+    ) -> Result<HttpSocket, WsError> {
+        let mut endpoint = Url::parse(&ws_request.uri().to_string())
+            .map_err(|err| WsError::Io(io::Error::new(io::ErrorKind::InvalidInput, err)))?;
+        let scheme = match endpoint.scheme() {
+            "ws" => "http",
+            "wss" => "https",
+            _ => return Err(WsError::Url(UrlError::UnsupportedUrlScheme)),
+        };
+        // Switching between special schemes is always allowed.
+        endpoint
+            .set_scheme(scheme)
+            .expect("Failed to set the HTTP scheme.");
+        let path = format!("{}/lightstreamer/", endpoint.path().trim_end_matches('/'));
+        endpoint.set_path(&path);
+        let headers = convert_headers(ws_request.headers())
+            .filter(|(name, _)| !HANDSHAKE_HEADERS.contains(&name.as_str()))
+            .collect();
+        let session_creation_headers = convert_headers(session_creation_headers).collect();
+        let (sender, receiver) = mpsc::unbounded_channel();
+        Ok(HttpSocket {
+            executor,
+            endpoint,
+            headers,
+            session_creation_headers,
+            interceptors,
+            read_buffer,
+            session_id: Arc::new(Mutex::new(None)),
+            sender,
+            receiver,
+            stream_task: None,
+            closing: false,
+            closed: false,
+        })
+    }
+
+    /// Issues the request carried by a text frame, forwarding its response.
+    fn send_request(&mut self, text: &str) -> Result<(), WsError> {
+        let (name, params) = text.split_once("\r\n").unwrap_or((text, ""));
+        if name == "wsok" {
+            self.deliver(Message::Text("WSOK\r\n".into()));
+            return Ok(());
+        }
+        let mut url = self
+            .endpoint
+            .join(&format!("{}.txt", name))
+            .map_err(|err| WsError::Io(io::Error::new(io::ErrorKind::InvalidInput, err)))?;
+        url.query_pairs_mut()
+            .append_pair("LS_protocol", LightstreamerClient::TLCP_VERSION);
+        let is_session_request = SESSION_REQUESTS.contains(&name);
+        if !is_session_request {
+            if let Some(session_id) = self.session_id.lock().unwrap().as_deref() {
+                url.query_pairs_mut().append_pair("LS_session", session_id);
+            }
+        }
+        let mut request = reqwest::Request::new(Method::POST, url);
+        let mut headers = self.headers.clone();
+        if name == "create_session" {
+            headers.extend(self.session_creation_headers.clone());
+        }
+        let (params, headers) = self.intercept(name, params, headers);
+        *request.headers_mut() = headers;
+        request.headers_mut().insert(
+            reqwest::header::CONTENT_TYPE,
+            HeaderValue::from_static("application/x-www-form-urlencoded"),
+        );
+        *request.body_mut() = Some(params.into());
+        // The responses to the other requests are short: their buffers start empty.
+        let buffer = LineBuffer::new(if is_session_request {
+            self.read_buffer
+        } else {
+            ReadBufferSettings {
+                size: 0,
+                ..self.read_buffer
+            }
+        });
+        let response = forward_response(
+            (self.executor)(request),
+            buffer,
+            self.sender.clone(),
+            Arc::clone(&self.session_id),
+            is_session_request,
+        );
+        if is_session_request {
+            if let Some(stream_task) = self.stream_task.replace(tokio::spawn(response)) {
+                stream_task.abort();
+            }
+        } else {
+            tokio::spawn(response);
+        }
+        Ok(())
+    }
+
+    /// Runs a request with the given headers through the chain of interceptors, if any, returning
+    /// its parameters and headers. Requests whose parameters can't be decoded are left unchanged.
+    fn intercept(&self, name: &str, params: &str, headers: HeaderMap) -> (String, HeaderMap) {
+        let decoded = RequestKind::from_name(name)
+            .filter(|_| !self.interceptors.is_empty())
+            .and_then(|kind| Some((kind, serde_urlencoded::from_str(params.trim()).ok()?)));
+        let Some((kind, decoded)) = decoded else {
+            return (params.to_string(), headers);
+        };
+        let headers = headers
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect();
+        let mut request = InterceptedRequest::new(kind, decoded, headers);
+        request_interceptor::intercept(&self.interceptors, &mut request);
+        let headers = request
+            .get_headers()
+            .iter()
+            .filter_map(|(name, value)| {
+                Some((
+                    HeaderName::from_bytes(name.as_bytes()).ok()?,
+                    HeaderValue::from_str(value).ok()?,
+                ))
+            })
+            .collect();
+        (encode_params(request.into_params()), headers)
+    }
+
+    /// Queues a frame for the session task.
+    fn deliver(&self, message: Message) {
+        // The receiver is owned by the socket itself.
+        let _ = self.sender.send(Ok(message));
+    }
+
+    /// Stops reading the stream of notifications, if any.
+    fn abort_stream(&mut self) {
+        if let Some(stream_task) = self.stream_task.take() {
+            stream_task.abort();
+        }
+    }
+}
+
+impl Drop for HttpSocket {
+    fn drop(&mut self) {
+        self.abort_stream();
+    }
+}
+
+/// Buffer collecting the bytes of a response until they form complete lines, reused across
+/// reads.
+struct LineBuffer {
+    bytes: Vec<u8>,
+    /// Number of bytes already searched for the end of a line.
+    searched: usize,
+    settings: ReadBufferSettings,
+}
+
+impl LineBuffer {
+    fn new(settings: ReadBufferSettings) -> LineBuffer {
+        LineBuffer {
+            bytes: Vec::with_capacity(settings.size),
+            searched: 0,
+            settings,
+        }
+    }
+
+    /// Appends the bytes received, growing the buffer as dictated by the growth policy.
+    fn extend(&mut self, chunk: &[u8]) {
+        match self.settings.growth {
+            ReadBufferGrowth::Exact => self.bytes.reserve_exact(chunk.len()),
+            ReadBufferGrowth::Doubling | ReadBufferGrowth::Shrinking => {
+                self.bytes.reserve(chunk.len())
+            }
+        }
+        self.bytes.extend_from_slice(chunk);
+    }
+
+    /// Takes the complete lines received so far, if any, keeping the rest for the next reads.
+    fn take_lines(&mut self) -> Option<Result<String, io::Error>> {
+        // The end of a line may be split between two reads.
+        let from = self.searched.saturating_sub(1);
+        let end = self.bytes[from..]
+            .windows(2)
+            .rposition(|bytes| bytes == b"\r\n")
+            .map(|end| from + end + 2);
+        let Some(end) = end else {
+            self.searched = self.bytes.len();
+            return None;
+        };
+        let lines = String::from_utf8(self.bytes[..end].to_vec())
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err));
+        self.bytes.drain(..end);
+        self.searched = self.bytes.len();
+        if self.settings.growth == ReadBufferGrowth::Shrinking {
+            self.bytes.shrink_to(self.settings.size);
+        }
+        Some(lines)
+    }
+}
+
+/// Issues a request and forwards the lines of its response, as soon as they are complete, as text
+/// frames. The session ID notified by a `CONOK` is recorded before the line is forwarded.
+///
+/// A stream of notifications is expected to end with a `LOOP` or an `END`: otherwise the
+/// connection is reported as lost.
+async fn forward_response(
+    response: BoxFuture<'static, Result<reqwest::Response, HttpError>>,
+    mut buffer: LineBuffer,
+    sender: UnboundedSender<Frame>,
+    session_id: Arc<Mutex<Option<String>>>,
+    is_session_request: bool,
+) {
+    let result = async {
+        let mut response = response.await?.error_for_status()?;
+        let mut last_notification = String::new();
+        while let Some(chunk) = response.chunk().await? {
+            buffer.extend(&chunk);
+            let Some(frame) = buffer.take_lines() else {
+                continue;
+            };
+            let frame = frame?;
+            for line in frame.lines().filter(|line| !line.is_empty()) {
+                let mut fields = line.split(',');
+                last_notification = fields.next().unwrap_or_default().to_string();
+                if last_notification == "CONOK" {
+                    *session_id.lock().unwrap() = fields.next().map(str::to_string);
+                }
+            }
+            if sender.send(Ok(Message::Text(frame.into()))).is_err() {
+                return Ok(());
+            }
+        }
+        if is_session_request && !matches!(last_notification.as_str(), "LOOP" | "END") {
+            return Err(Box::new(io::Error::new(
+                io::ErrorKind::UnexpectedEof,
+                "Stream closed by the server",
+            )) as HttpError);
+        }
+        Ok(())
+    }
+    .await;
+    if let Err(err) = result {
+        let _ = sender.send(Err(WsError::Io(io::Error::other(err))));
+    }
+}
+
+impl Stream for HttpSocket {
+    type Item = Result<Message, WsError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if self.closed {
+            return Poll::Ready(None);
+        }
+        let frame = self.receiver.poll_recv(cx);
+        if let Poll::Ready(Some(Ok(Message::Close(_)))) = &frame {
+            self.closed = true;
+        }
+        frame
+    }
+}
+
+impl Sink<Message> for HttpSocket {
+    type Error = WsError;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        if self.closing {
+            return Poll::Ready(Err(WsError::AlreadyClosed));
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, message: Message) -> Result<(), WsError> {
+        if self.closing {
+            return Err(WsError::AlreadyClosed);
+        }
+        match message {
+            Message::Text(text) => self.send_request(text.as_str()),
+            Message::Ping(payload) => {
+                self.deliver(Message::Pong(payload));
+                Ok(())
+            }
+            Message::Close(frame) => {
+                self.closing = true;
+                self.abort_stream();
+                self.deliver(Message::Close(frame));
+                Ok(())
+            }
+            message => Err(WsError::Io(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                format!("Unsupported frame over HTTP: {:?}", message),
+            ))),
+        }
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        self.closing = true;
+        self.abort_stream();
+        Poll::Ready(Ok(()))
+    }
+}
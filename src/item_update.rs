@@ -1,6 +1,155 @@
-use std::collections::HashMap;
+#[cfg(feature = "serde")]
+use crate::deserializer::ItemUpdateDeserializer;
+use crate::error::{IllegalArgumentException, IllegalStateException};
 
-use serde::Serialize;
+use std::collections::{BTreeSet, HashMap};
+use std::fmt::Display;
+use std::str::FromStr;
+use std::sync::Arc;
+
+use ahash::AHashMap;
+#[cfg(feature = "serde")]
+use serde::ser::SerializeStruct;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize, Serializer};
+use smallvec::SmallVec;
+
+/// Number of fields whose per-update state is kept inline, without heap allocations. Most
+/// schemas, such as market data ones, have fewer fields than this.
+const INLINE_FIELDS: usize = 32;
+
+/// Per-field state indexed by field position, kept inline for typical field counts.
+pub(crate) type FieldValues<T> = SmallVec<[T; INLINE_FIELDS]>;
+
+/// Names of the fields of a Subscription, in the order of its "Field List", together with their
+/// 1-based positions. It is built once per Subscription and shared by all its updates, so that
+/// updates only carry their values, indexed by field position.
+///
+/// Fields of a "Field Schema" are only known by position, so their positions are used as names
+/// and the name-based accessors of `ItemUpdate` are not available.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct FieldNames {
+    names: Vec<Arc<str>>,
+    positions: AHashMap<Arc<str>, usize>,
+    schema: bool,
+}
+
+impl FieldNames {
+    /// Creates the field names of a Subscription from its "Field List". In case of duplicated
+    /// names, the first position is meant.
+    ///
+    /// # Parameters
+    /// - `names` – The names of the fields, in the order of the "Field List".
+    pub fn new<I, S>(names: I) -> FieldNames
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<Arc<str>>,
+    {
+        let names: Vec<Arc<str>> = names.into_iter().map(Into::into).collect();
+        let positions = names
+            .iter()
+            .enumerate()
+            .rev()
+            .map(|(index, name)| (Arc::clone(name), index + 1))
+            .collect();
+        FieldNames {
+            names,
+            positions,
+            schema: false,
+        }
+    }
+
+    /// Creates the field names of a Subscription from the number of fields of its "Field Schema",
+    /// which are only known by position.
+    ///
+    /// # Parameters
+    /// - `field_count` – The number of fields of the "Field Schema".
+    pub fn from_schema(field_count: usize) -> FieldNames {
+        FieldNames {
+            schema: true,
+            ..FieldNames::new((1..=field_count).map(|pos| pos.to_string()))
+        }
+    }
+
+    /// Checks whether the fields come from a "Field Schema", so that they have no names.
+    pub fn is_schema(&self) -> bool {
+        self.schema
+    }
+
+    /// Returns the number of fields.
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    /// Checks whether there are no fields.
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    /// Gets the name of the field at the given 1-based position, if any.
+    pub fn get_name(&self, pos: usize) -> Option<&str> {
+        pos.checked_sub(1)
+            .and_then(|index| self.names.get(index))
+            .map(AsRef::as_ref)
+    }
+
+    /// Gets the 1-based position of the field with the given name, if any.
+    pub fn get_position(&self, name: &str) -> Option<usize> {
+        self.positions.get(name).copied()
+    }
+
+    /// Returns an iterator over the field names, in the order of the "Field List".
+    pub fn iter(&self) -> impl Iterator<Item = &str> + '_ {
+        self.names.iter().map(AsRef::as_ref)
+    }
+}
+
+/// Value of a field, as carried by an update or as held by an `ItemUpdate`, telling apart the
+/// cases that `ItemUpdate.get_value()` reports as None.
+///
+/// See also `ItemUpdate.getFieldValue()`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum FieldValue<S = String> {
+    /// The update carries no value for the field, which keeps its previous value. As the value
+    /// held for a field, it means that no value has been received for the field yet.
+    #[default]
+    Unchanged,
+    /// The field is null, as a None value has been received from the Server. On COMMAND
+    /// Subscriptions, a DELETE event carries null values for all the fields but the key and the
+    /// command ones.
+    Null,
+    /// The value of the field, possibly empty.
+    Value(S),
+}
+
+impl<S: AsRef<str>> FieldValue<S> {
+    /// Borrows the value of the field, if any.
+    pub fn as_deref(&self) -> FieldValue<&str> {
+        match self {
+            FieldValue::Unchanged => FieldValue::Unchanged,
+            FieldValue::Null => FieldValue::Null,
+            FieldValue::Value(value) => FieldValue::Value(value.as_ref()),
+        }
+    }
+
+    /// Gets the value of the field, or None if it is null or unchanged.
+    pub fn value(&self) -> Option<&str> {
+        match self {
+            FieldValue::Value(value) => Some(value.as_ref()),
+            _ => None,
+        }
+    }
+
+    /// Checks whether the field is null.
+    pub fn is_null(&self) -> bool {
+        matches!(self, FieldValue::Null)
+    }
+
+    /// Checks whether the field is unchanged.
+    pub fn is_unchanged(&self) -> bool {
+        matches!(self, FieldValue::Unchanged)
+    }
+}
 
 /// Contains all the information related to an update of the field values for an item.
 /// It reports all the new values of the fields.
@@ -23,16 +172,131 @@ use serde::Serialize;
 ///   at the highest position of the first-level field list + 1. If a field schema had been specified for
 ///   either first-level or second-level Subscriptions, then client-side knowledge of the first-level schema
 ///   length would be required.
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone)]
 pub struct ItemUpdate {
     pub item_name: Option<String>,
     pub item_pos: usize,
-    pub fields: HashMap<String, Option<String>>,
-    pub changed_fields: HashMap<String, String>,
     pub is_snapshot: bool,
+    /// Whether the snapshot was sent again after the Subscription was re-established on a new
+    /// session.
+    pub(crate) is_refresh: bool,
+    /// Sequence number of the update among the ones stored by its Subscription, starting from 1,
+    /// or 0 if the update didn't come from a Subscription.
+    pub(crate) sequence: u64,
+    /// Names of the fields, shared with all the updates of the Subscription.
+    field_names: Arc<FieldNames>,
+    /// Values of all the fields, indexed by 0-based field position. A field is
+    /// `FieldValue::Unchanged` until its first value is received.
+    values: Vec<FieldValue>,
+    /// Whether each field changed with this update, indexed by 0-based field position.
+    changed: FieldValues<bool>,
 }
 
 impl ItemUpdate {
+    /// Creates an update carrying the given field values, where every field with a value is
+    /// marked as changed. Useful to test listeners without a connection to a Server.
+    ///
+    /// # Parameters
+    /// - `item_name` – The name of the item, or None for an item of an "Item Group".
+    /// - `item_pos` – The 1-based position of the item.
+    /// - `fields` – The names and values of the fields, in the order of the "Field List".
+    /// - `is_snapshot` – Whether the update belongs to the item snapshot.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lightstreamer_client::item_update::ItemUpdate;
+    ///
+    /// let update = ItemUpdate::new(
+    ///     Some("item1"),
+    ///     1,
+    ///     [("last_price", Some("12.5")), ("bid", None)],
+    ///     false,
+    /// );
+    /// assert_eq!(update.get_value("2"), None);
+    /// assert_eq!(update.get_value_by_position(1), Some("12.5"));
+    /// assert!(update.is_value_changed("last_price"));
+    /// ```
+    pub fn new<'a>(
+        item_name: Option<&str>,
+        item_pos: usize,
+        fields: impl IntoIterator<Item = (&'a str, Option<&'a str>)>,
+        is_snapshot: bool,
+    ) -> ItemUpdate {
+        let (names, values): (Vec<&str>, Vec<FieldValue>) = fields
+            .into_iter()
+            .map(|(name, value)| match value {
+                Some(value) => (name, FieldValue::Value(value.to_string())),
+                None => (name, FieldValue::Unchanged),
+            })
+            .unzip();
+        ItemUpdate::from_changes(
+            item_name.map(str::to_string),
+            item_pos,
+            Arc::new(FieldNames::new(names)),
+            values,
+            is_snapshot,
+        )
+    }
+
+    /// Creates the first update of an item, where the changed fields are the ones not unchanged.
+    pub(crate) fn from_changes(
+        item_name: Option<String>,
+        item_pos: usize,
+        field_names: Arc<FieldNames>,
+        mut values: Vec<FieldValue>,
+        is_snapshot: bool,
+    ) -> ItemUpdate {
+        values.resize(field_names.len(), FieldValue::Unchanged);
+        let changed = values.iter().map(|value| !value.is_unchanged()).collect();
+        ItemUpdate {
+            item_name,
+            item_pos,
+            is_snapshot,
+            is_refresh: false,
+            sequence: 0,
+            field_names,
+            values,
+            changed,
+        }
+    }
+
+    /// Applies the values of a subsequent update of the item. When `all_changed` is set, as for
+    /// RAW updates, every field with a value, even a null one, is marked as changed.
+    pub(crate) fn apply_changes(&mut self, changes: FieldValues<FieldValue>, all_changed: bool) {
+        self.changed.fill(false);
+        for (index, change) in changes.into_iter().enumerate().take(self.values.len()) {
+            if !change.is_unchanged() {
+                self.values[index] = change;
+                self.changed[index] = true;
+            }
+        }
+        if all_changed {
+            for (changed, value) in self.changed.iter_mut().zip(&self.values) {
+                *changed = !value.is_unchanged();
+            }
+        }
+    }
+
+    /// Gets the values held for all the fields, indexed by 0-based field position, which the
+    /// JSON Patches of the next update of the item apply to.
+    pub(crate) fn held_values(&self) -> &[FieldValue] {
+        &self.values
+    }
+
+    /// Marks as changed also the fields changed with an older update of the same item, which is
+    /// being replaced by this one without being delivered.
+    pub(crate) fn merge_changes(&mut self, older: &ItemUpdate) {
+        for (changed, older_changed) in self.changed.iter_mut().zip(&older.changed) {
+            *changed |= older_changed;
+        }
+    }
+
+    /// Gets the names of the fields of the update, shared with all the updates of the Subscription.
+    pub fn get_field_names(&self) -> &Arc<FieldNames> {
+        &self.field_names
+    }
+
     /// Returns a map containing the values for each field changed with the last server update.
     /// The related field name is used as key for the values in the map. Note that if the Subscription
     /// mode of the involved Subscription is COMMAND, then changed fields are meant as relative to the
@@ -42,12 +306,17 @@ impl ItemUpdate {
     /// will not be iterated.
     ///
     /// # Raises
-    /// - `IllegalStateException` – if the Subscription was initialized using a field schema.
+    /// - `IllegalStateException` – if the Subscription was initialized using a field schema; use
+    ///   `getChangedFieldsByPosition()` instead.
     ///
     /// # Returns
     /// A map containing the values for each field changed with the last server update.
-    pub fn get_changed_fields(&self) -> HashMap<String, String> {
-        self.changed_fields.clone()
+    pub fn get_changed_fields(&self) -> Result<HashMap<String, String>, IllegalStateException> {
+        self.check_field_names()?;
+        Ok(self
+            .changed_fields_iter()
+            .filter_map(|(name, value)| value.map(|value| (name.to_string(), value.to_string())))
+            .collect())
     }
 
     /// Returns a map containing the values for each field changed with the last server update.
@@ -61,9 +330,14 @@ impl ItemUpdate {
     /// # Returns
     /// A map containing the values for each field changed with the last server update.
     pub fn get_changed_fields_by_position(&self) -> HashMap<usize, String> {
-        self.changed_fields
+        self.values
             .iter()
-            .map(|(name, value)| (self.get_field_position(name), value.clone()))
+            .zip(&self.changed)
+            .enumerate()
+            .filter(|(_, (_, changed))| **changed)
+            .filter_map(|(index, (value, _))| {
+                value.value().map(|value| (index + 1, value.to_string()))
+            })
             .collect()
     }
 
@@ -71,12 +345,66 @@ impl ItemUpdate {
     /// The related field name is used as key for the values in the map.
     ///
     /// # Raises
-    /// - `IllegalStateException` – if the Subscription was initialized using a field schema.
+    /// - `IllegalStateException` – if the Subscription was initialized using a field schema; use
+    ///   `getFieldsByPosition()` instead.
     ///
     /// # Returns
     /// A map containing the values for each field in the Subscription.
-    pub fn get_fields(&self) -> HashMap<String, Option<String>> {
-        self.fields.clone()
+    pub fn get_fields(&self) -> Result<HashMap<String, Option<String>>, IllegalStateException> {
+        self.check_field_names()?;
+        Ok(self
+            .fields_iter()
+            .map(|(name, value)| (name.to_string(), value.map(str::to_string)))
+            .collect())
+    }
+
+    /// Returns an iterator over the values of each field changed with the last server update, as
+    /// pairs of field name and value. Unlike `ItemUpdate.get_changed_fields()`, the values are
+    /// borrowed from the update, so no allocation takes place; this suits listeners handling
+    /// high-frequency updates.
+    ///
+    /// If the Subscription was initialized using a field schema, the 1-based field positions are
+    /// used as names.
+    ///
+    /// See also `getChangedFields()`
+    ///
+    /// # Returns
+    /// An iterator over the fields changed with the last server update, in field list order.
+    pub fn changed_fields_iter(&self) -> impl Iterator<Item = (&str, Option<&str>)> + '_ {
+        self.fields_iter()
+            .zip(&self.changed)
+            .filter(|(_, changed)| **changed)
+            .map(|(field, _)| field)
+    }
+
+    /// Returns an iterator over the values of each field in the Subscription, as pairs of field
+    /// name and value. Unlike `ItemUpdate.get_fields()`, the values are borrowed from the update,
+    /// so no allocation takes place; this suits listeners handling high-frequency updates. The
+    /// fields follow the order of the field list, which gives logging and fixed-width displays a
+    /// stable column order.
+    ///
+    /// If the Subscription was initialized using a field schema, the 1-based field positions are
+    /// used as names.
+    ///
+    /// See also `getFields()`
+    ///
+    /// # Returns
+    /// An iterator over the fields in the Subscription, in field list order.
+    pub fn fields_iter(&self) -> impl Iterator<Item = (&str, Option<&str>)> + '_ {
+        self.field_names
+            .iter()
+            .zip(&self.values)
+            .map(|(name, value)| (name, value.value()))
+    }
+
+    /// Returns an iterator over the values of each field in the Subscription, in the order of the
+    /// field list. This is a former alias of `ItemUpdate.fields_iter()`, which iterates in the same
+    /// order.
+    ///
+    /// See also `fieldsIter()`
+    #[deprecated(note = "use fields_iter")]
+    pub fn iter_fields_ordered(&self) -> impl Iterator<Item = (&str, Option<&str>)> + '_ {
+        self.fields_iter()
     }
 
     /// Returns a map containing the values for each field in the Subscription.
@@ -85,9 +413,10 @@ impl ItemUpdate {
     /// # Returns
     /// A map containing the values for each field in the Subscription.
     pub fn get_fields_by_position(&self) -> HashMap<usize, Option<String>> {
-        self.fields
+        self.values
             .iter()
-            .map(|(name, value)| (self.get_field_position(name), value.clone()))
+            .enumerate()
+            .map(|(index, value)| (index + 1, value.value().map(str::to_string)))
             .collect()
     }
 
@@ -127,17 +456,305 @@ impl ItemUpdate {
     /// - the item is subscribed to with the COMMAND mode and a DELETE command is received (only the fields
     ///   used to carry key and command information are valued).
     pub fn get_value(&self, field_name_or_pos: &str) -> Option<&str> {
-        match field_name_or_pos.parse::<usize>() {
-            Ok(pos) => self
-                .fields
-                .iter()
-                .find(|(name, _)| self.get_field_position(name) == pos)
-                .and_then(|(_, value)| value.as_deref()),
-            Err(_) => self
-                .fields
-                .get(field_name_or_pos)
-                .and_then(|v| v.as_deref()),
-        }
+        self.get_field_index(field_name_or_pos)
+            .and_then(|index| self.values[index].value())
+    }
+
+    /// Inquiry method that gets the value for the field at a specified position, as received from
+    /// the Server with the current or previous update. Unlike `ItemUpdate.get_value()`, no name
+    /// lookup nor parsing takes place.
+    ///
+    /// See also `getValue()`
+    ///
+    /// # Parameters
+    /// - `field_pos` – The 1-based position of the field within the "Field List" or "Field Schema".
+    ///
+    /// # Returns
+    /// The value of the specified field, or None in the same cases where `ItemUpdate.get_value()`
+    /// returns None, or if there is no field at that position.
+    pub fn get_value_by_position(&self, field_pos: usize) -> Option<&str> {
+        field_pos
+            .checked_sub(1)
+            .and_then(|index| self.values.get(index))
+            .and_then(FieldValue::value)
+    }
+
+    /// Inquiry method that gets what the current update carries for a specified field, telling apart
+    /// the cases where `ItemUpdate.get_value()` returns None:
+    ///
+    /// - `FieldValue::Unchanged` if the field was not changed by the update; its value, if any, was
+    ///   received with a previous update and is available through `ItemUpdate.get_value()`;
+    /// - `FieldValue::Null` if the update set the field to null, as for all the fields but the key
+    ///   and the command ones on COMMAND DELETE events;
+    /// - `FieldValue::Value` with the new value of the field otherwise.
+    ///
+    /// To tell a null field from a field with no value yet, regardless of the current update, see
+    /// `ItemUpdate.is_null()`.
+    ///
+    /// # Parameters
+    /// - `field_name_or_pos` – The field name or the 1-based position of the field within the "Field List" or "Field Schema".
+    ///
+    /// # Returns
+    /// What the current update carries for the specified field, or None if the field is not part
+    /// of the Subscription.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lightstreamer_client::item_update::{FieldValue, ItemUpdate};
+    ///
+    /// let update = ItemUpdate::new(Some("item1"), 1, [("last_price", Some("12.5")), ("bid", None)], false);
+    /// assert_eq!(update.get_field_value("last_price"), Some(FieldValue::Value("12.5")));
+    /// assert_eq!(update.get_field_value("bid"), Some(FieldValue::Unchanged));
+    /// assert_eq!(update.get_field_value("ask"), None);
+    /// ```
+    pub fn get_field_value(&self, field_name_or_pos: &str) -> Option<FieldValue<&str>> {
+        self.get_field_index(field_name_or_pos)
+            .map(|index| self.get_field_change(index))
+    }
+
+    /// Inquiry method that gets what the current update carries for the field at a specified
+    /// position. Unlike `ItemUpdate.get_field_value()`, no name lookup nor parsing takes place,
+    /// so this suits Subscriptions initialized using a "Field Schema".
+    ///
+    /// See also `getFieldValue()`
+    ///
+    /// # Parameters
+    /// - `field_pos` – The 1-based position of the field within the "Field List" or "Field Schema".
+    ///
+    /// # Returns
+    /// What the current update carries for the specified field, or None if there is no field at
+    /// that position.
+    pub fn get_field_value_by_position(&self, field_pos: usize) -> Option<FieldValue<&str>> {
+        self.get_position_index(field_pos)
+            .map(|index| self.get_field_change(index))
+    }
+
+    /// Inquiry method that asks whether the value of a specified field is null, as a None value was
+    /// received from the Server with the current or previous update. This tells a null field apart
+    /// from a field for which no value has been received yet, as `ItemUpdate.get_value()` returns
+    /// None for both.
+    ///
+    /// # Parameters
+    /// - `field_name_or_pos` – The field name or the 1-based position of the field within the "Field List" or "Field Schema".
+    ///
+    /// # Returns
+    /// `true` if the field is null; `false` if it has a value, if no value has been received for it
+    /// yet or if it is not part of the Subscription.
+    pub fn is_null(&self, field_name_or_pos: &str) -> bool {
+        self.get_field_index(field_name_or_pos)
+            .is_some_and(|index| self.values[index].is_null())
+    }
+
+    /// Inquiry method that gets the value for a specified field, as received from the Server with the
+    /// current or previous update, parsed into the requested type. This spares listeners the parsing
+    /// boilerplate for numeric fields such as prices and quantities.
+    ///
+    /// # Parameters
+    /// - `field_name_or_pos` – The field name or the 1-based position of the field within the "Field List" or "Field Schema".
+    ///
+    /// # Returns
+    /// The parsed value of the specified field, or None in the same cases where `ItemUpdate.get_value()` returns None.
+    ///
+    /// # Errors
+    /// The parsing error of the target type, if the value of the field can't be parsed into it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lightstreamer_client::item_update::ItemUpdate;
+    ///
+    /// let update = ItemUpdate::new(Some("item1"), 1, [("last_price", Some("12.5"))], false);
+    /// assert_eq!(update.get_value_as::<f64>("last_price"), Ok(Some(12.5)));
+    /// assert_eq!(update.get_value_as::<f64>("bid"), Ok(None));
+    /// assert!(update.get_value_as::<u32>("last_price").is_err());
+    /// ```
+    pub fn get_value_as<T: FromStr>(&self, field_name_or_pos: &str) -> Result<Option<T>, T::Err> {
+        self.get_value(field_name_or_pos)
+            .map(str::parse::<T>)
+            .transpose()
+    }
+
+    /// Builds a user type from the current field values of the update, seen as a map from field
+    /// names to values, through its `serde::Deserialize` implementation. Values are parsed into the
+    /// numbers, booleans and characters requested by the target type, and null or missing fields
+    /// are deserialized as `None` into `Option` fields.
+    ///
+    /// # Returns
+    /// The deserialized value.
+    ///
+    /// # Raises
+    /// - `IllegalArgumentException` – if a required field has no value, a value can't be parsed or
+    ///   the target type rejects the fields of the update.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lightstreamer_client::item_update::ItemUpdate;
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct Quote {
+    ///     last_price: f64,
+    ///     bid: Option<f64>,
+    /// }
+    ///
+    /// let update = ItemUpdate::new(Some("item1"), 1, [("last_price", Some("12.5")), ("bid", None)], false);
+    /// let quote: Quote = update.deserialize().unwrap();
+    /// assert_eq!((quote.last_price, quote.bid), (12.5, None));
+    /// ```
+    #[cfg(feature = "serde")]
+    pub fn deserialize<'de, T: Deserialize<'de>>(&'de self) -> Result<T, IllegalArgumentException> {
+        T::deserialize(ItemUpdateDeserializer::new(self))
+    }
+
+    /// Inquiry method that gets the value for a specified field, as received from the Server with the
+    /// current or previous update, parsed as a JSON document. Useful for fields carrying JSON values.
+    ///
+    /// # Parameters
+    /// - `field_name_or_pos` – The field name or the 1-based position of the field within the "Field List" or "Field Schema".
+    ///
+    /// # Returns
+    /// The parsed JSON value of the specified field, or None in the same cases where `ItemUpdate.get_value()` returns None.
+    ///
+    /// # Errors
+    /// The parsing error, if the value of the field is not a valid JSON document.
+    #[cfg(feature = "json")]
+    pub fn get_value_as_json(
+        &self,
+        field_name_or_pos: &str,
+    ) -> Result<Option<serde_json::Value>, serde_json::Error> {
+        self.get_value(field_name_or_pos)
+            .map(serde_json::from_str)
+            .transpose()
+    }
+
+    /// Converts the whole update into a JSON object carrying the item name and position, the values
+    /// of all the fields, the changed fields and the snapshot flag, ready to be forwarded to
+    /// downstream pipelines.
+    ///
+    /// # Returns
+    /// The JSON representation of the update.
+    #[cfg(feature = "json")]
+    pub fn to_json(&self) -> serde_json::Value {
+        // Serializing string maps and scalars can't fail.
+        serde_json::to_value(self).expect("ItemUpdate is always serializable to JSON")
+    }
+
+    /// Inquiry method that gets the value for a specified field, as received from the Server with the
+    /// current or previous update, parsed as an exact decimal number. Unlike `f64`, this preserves
+    /// prices and quantities exactly as sent by the Data Adapter, with no floating-point rounding.
+    /// Values in scientific notation, such as "1.5e3", are accepted as well.
+    ///
+    /// # Parameters
+    /// - `field_name_or_pos` – The field name or the 1-based position of the field within the "Field List" or "Field Schema".
+    ///
+    /// # Returns
+    /// The decimal value of the specified field, or None in the same cases where `ItemUpdate.get_value()` returns None.
+    ///
+    /// # Raises
+    /// - `IllegalArgumentException` – if the value of the field is not a valid decimal number.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lightstreamer_client::item_update::ItemUpdate;
+    ///
+    /// let update = ItemUpdate::new(Some("item1"), 1, [("last_price", Some("0.1"))], false);
+    /// let price = update.get_value_as_decimal("last_price").unwrap().unwrap();
+    /// assert_eq!((price + price + price).to_string(), "0.3");
+    /// ```
+    #[cfg(feature = "decimal")]
+    pub fn get_value_as_decimal(
+        &self,
+        field_name_or_pos: &str,
+    ) -> Result<Option<rust_decimal::Decimal>, IllegalArgumentException> {
+        self.get_value(field_name_or_pos)
+            .map(|value| {
+                let value = value.trim();
+                rust_decimal::Decimal::from_str_exact(value)
+                    .or_else(|_| rust_decimal::Decimal::from_scientific(value))
+            })
+            .transpose()
+            .map_err(|err| {
+                IllegalArgumentException::new(&format!(
+                    "Invalid decimal for field '{}': {}",
+                    field_name_or_pos, err
+                ))
+            })
+    }
+
+    /// Inquiry method that gets the value for a specified field, as received from the Server with the
+    /// current or previous update, parsed as a timestamp in the given format.
+    ///
+    /// # Parameters
+    /// - `field_name_or_pos` – The field name or the 1-based position of the field within the "Field List" or "Field Schema".
+    /// - `format` – The format used by the Data Adapter to encode the timestamp.
+    ///
+    /// # Returns
+    /// The timestamp of the specified field, or None in the same cases where `ItemUpdate.get_value()` returns None.
+    ///
+    /// # Raises
+    /// - `IllegalArgumentException` – if the value of the field doesn't match the given format.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lightstreamer_client::item_update::{ItemUpdate, TimestampFormat};
+    ///
+    /// let update = ItemUpdate::new(Some("item1"), 1, [("timestamp", Some("1718000000000"))], false);
+    /// let timestamp = update
+    ///     .get_value_as_datetime("timestamp", &TimestampFormat::UnixMillis)
+    ///     .unwrap()
+    ///     .unwrap();
+    /// assert_eq!(timestamp.to_rfc3339(), "2024-06-10T06:13:20+00:00");
+    /// ```
+    #[cfg(feature = "chrono")]
+    pub fn get_value_as_datetime(
+        &self,
+        field_name_or_pos: &str,
+        format: &TimestampFormat,
+    ) -> Result<Option<chrono::DateTime<chrono::Utc>>, IllegalArgumentException> {
+        self.get_value(field_name_or_pos)
+            .map(|value| format.parse_datetime(value))
+            .transpose()
+            .map_err(|err| {
+                IllegalArgumentException::new(&format!(
+                    "Invalid timestamp for field '{}': {}",
+                    field_name_or_pos, err
+                ))
+            })
+    }
+
+    /// Inquiry method that gets the value for a specified field, as received from the Server with the
+    /// current or previous update, parsed as a time of day in the given `strftime`-like format. This
+    /// suits fields that carry no date, like the "time" field of the stock-list demo, which uses the
+    /// "%H:%M:%S" format.
+    ///
+    /// # Parameters
+    /// - `field_name_or_pos` – The field name or the 1-based position of the field within the "Field List" or "Field Schema".
+    /// - `format` – The format of the time of day, e.g. "%H:%M:%S".
+    ///
+    /// # Returns
+    /// The time of day of the specified field, or None in the same cases where `ItemUpdate.get_value()` returns None.
+    ///
+    /// # Raises
+    /// - `IllegalArgumentException` – if the value of the field doesn't match the given format.
+    #[cfg(feature = "chrono")]
+    pub fn get_value_as_time(
+        &self,
+        field_name_or_pos: &str,
+        format: &str,
+    ) -> Result<Option<chrono::NaiveTime>, IllegalArgumentException> {
+        self.get_value(field_name_or_pos)
+            .map(|value| chrono::NaiveTime::parse_from_str(value, format))
+            .transpose()
+            .map_err(|err| {
+                IllegalArgumentException::new(&format!(
+                    "Invalid time for field '{}': {}",
+                    field_name_or_pos, err
+                ))
+            })
     }
 
     /// Inquiry method that gets the difference between the new value and the previous one as a JSON Patch structure,
@@ -192,6 +809,24 @@ impl ItemUpdate {
         self.is_snapshot
     }
 
+    /// Inquiry method that asks whether the current update belongs to a snapshot sent again because
+    /// the Subscription had to be re-established on a new session, after the session it was
+    /// subscribed to in was lost and could not be recovered. Updates may have been missed in the
+    /// meantime, so the state built from the updates of the previous session should be discarded
+    /// and rebuilt from the refreshed snapshot.
+    ///
+    /// The updates received on a recovered session are never duplicated nor missing, hence they
+    /// don't carry this flag.
+    ///
+    /// # Returns
+    /// `true` if the current update event belongs to a refreshed item snapshot; `false` otherwise.
+    ///
+    /// # See also
+    /// `ItemUpdate.isSnapshot()`
+    pub fn is_snapshot_refresh(&self) -> bool {
+        self.is_refresh
+    }
+
     /// Inquiry method that asks whether the value for a field has changed after the reception of the last update from the Server
     /// for an item. If the Subscription mode is COMMAND then the change is meant as relative to the same key.
     ///
@@ -216,26 +851,205 @@ impl ItemUpdate {
     /// # Raises
     /// - `IllegalArgumentException` – if the specified field is not part of the Subscription.
     pub fn is_value_changed(&self, field_name_or_pos: &str) -> bool {
-        match field_name_or_pos.parse::<usize>() {
-            Ok(pos) => self
-                .changed_fields
-                .iter()
-                .any(|(name, _)| self.get_field_position(name) == pos),
-            Err(_) => self.changed_fields.contains_key(field_name_or_pos),
+        self.get_field_index(field_name_or_pos)
+            .is_some_and(|index| self.changed[index])
+    }
+
+    /// Inquiry method that asks whether the value for the field at a specified position has
+    /// changed after the reception of the last update from the Server for an item, with the same
+    /// meaning as `ItemUpdate.is_value_changed()`.
+    ///
+    /// See also `isValueChanged()`
+    ///
+    /// # Parameters
+    /// - `field_pos` – The 1-based position of the field within the "Field List" or "Field Schema".
+    ///
+    /// # Returns
+    /// `true` if the value of the field has changed; `false` otherwise, or if there is no field at
+    /// that position.
+    pub fn is_value_changed_by_position(&self, field_pos: usize) -> bool {
+        self.get_position_index(field_pos)
+            .is_some_and(|index| self.changed[index])
+    }
+
+    /// Inquiry method that gets the 1-based positions of the fields changed with the last server
+    /// update, with the same meaning as `ItemUpdate.is_value_changed()`. Unlike
+    /// `ItemUpdate.get_changed_fields_by_position()`, the positions of the fields changed to a
+    /// null value are included.
+    ///
+    /// # Returns
+    /// The positions of the changed fields within the "Field List" or "Field Schema", in ascending
+    /// order.
+    pub fn get_changed_positions(&self) -> BTreeSet<usize> {
+        self.changed
+            .iter()
+            .enumerate()
+            .filter(|(_, changed)| **changed)
+            .map(|(index, _)| index + 1)
+            .collect()
+    }
+
+    /// Helper method to get what the current update carries for the field with the given 0-based
+    /// index.
+    fn get_field_change(&self, index: usize) -> FieldValue<&str> {
+        match self.changed[index] {
+            true => self.values[index].as_deref(),
+            false => FieldValue::Unchanged,
+        }
+    }
+
+    /// Helper method to get the 0-based index of the field at the given 1-based position, if any.
+    fn get_position_index(&self, field_pos: usize) -> Option<usize> {
+        field_pos
+            .checked_sub(1)
+            .filter(|&index| index < self.values.len())
+    }
+
+    /// Helper method that fails if the fields of the update have no names, as they come from a
+    /// field schema.
+    fn check_field_names(&self) -> Result<(), IllegalStateException> {
+        if self.field_names.is_schema() {
+            return Err(IllegalStateException::new(
+                "The Subscription was initialized using a field schema: fields are only available by position",
+            ));
         }
+        Ok(())
     }
 
-    /// Helper method to get the 1-based position of a field within the field list or field schema.
+    /// Helper method to get the 0-based index of a field within the field list or field schema.
     ///
     /// # Parameters
-    /// - `field_name` – The name of the field.
+    /// - `field_name_or_pos` – The field name or the 1-based position of the field.
     ///
     /// # Returns
-    /// The 1-based position of the field within the field list or field schema.
-    fn get_field_position(&self, _field_name: &str) -> usize {
-        // Implementation pending
-        // This method should return the 1-based position of the field based on the field list or field schema
-        // If the field is not found, it should raise an IllegalArgumentException
-        unimplemented!()
+    /// The 0-based index of the field, or None if the field is unknown.
+    fn get_field_index(&self, field_name_or_pos: &str) -> Option<usize> {
+        match field_name_or_pos.parse::<usize>() {
+            Ok(pos) => self.get_position_index(pos),
+            Err(_) => self
+                .field_names
+                .get_position(field_name_or_pos)
+                .map(|pos| pos - 1),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for ItemUpdate {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut state = serializer.serialize_struct("ItemUpdate", 6)?;
+        state.serialize_field("item_name", &self.item_name)?;
+        state.serialize_field("item_pos", &self.item_pos)?;
+        state.serialize_field("fields", &SerializedFields(self, false))?;
+        state.serialize_field("changed_fields", &SerializedFields(self, true))?;
+        state.serialize_field("is_snapshot", &self.is_snapshot)?;
+        state.serialize_field("is_snapshot_refresh", &self.is_refresh)?;
+        state.end()
+    }
+}
+
+/// Serializes the fields of an update as a map, either all of them or only the changed ones.
+#[cfg(feature = "serde")]
+struct SerializedFields<'a>(&'a ItemUpdate, bool);
+
+#[cfg(feature = "serde")]
+impl Serialize for SerializedFields<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let SerializedFields(update, changed_only) = *self;
+        if changed_only {
+            serializer.collect_map(
+                update
+                    .changed_fields_iter()
+                    .filter_map(|(name, value)| value.map(|value| (name, value))),
+            )
+        } else {
+            serializer.collect_map(update.fields_iter())
+        }
+    }
+}
+
+/// Formats used by Data Adapters to encode timestamps in field values, used by
+/// `ItemUpdate.get_value_as_datetime()`.
+#[cfg(feature = "chrono")]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TimestampFormat {
+    /// Seconds since the Unix epoch, e.g. "1718000000".
+    UnixSeconds,
+    /// Milliseconds since the Unix epoch, e.g. "1718000000000".
+    UnixMillis,
+    /// RFC 3339 date and time with offset, e.g. "2024-06-10T06:13:20Z".
+    Rfc3339,
+    /// Custom `strftime`-like format. If it carries no offset, the timestamp is taken as UTC.
+    Custom(String),
+}
+
+#[cfg(feature = "chrono")]
+impl TimestampFormat {
+    /// Parses a field value according to this format.
+    fn parse_datetime(&self, value: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+        use chrono::{DateTime, NaiveDateTime, Utc};
+
+        match self {
+            TimestampFormat::UnixSeconds => value
+                .trim()
+                .parse::<i64>()
+                .map_err(|err| err.to_string())
+                .and_then(|seconds| {
+                    DateTime::from_timestamp(seconds, 0)
+                        .ok_or_else(|| "timestamp out of range".to_string())
+                }),
+            TimestampFormat::UnixMillis => value
+                .trim()
+                .parse::<i64>()
+                .map_err(|err| err.to_string())
+                .and_then(|millis| {
+                    DateTime::from_timestamp_millis(millis)
+                        .ok_or_else(|| "timestamp out of range".to_string())
+                }),
+            TimestampFormat::Rfc3339 => DateTime::parse_from_rfc3339(value)
+                .map(|datetime| datetime.with_timezone(&Utc))
+                .map_err(|err| err.to_string()),
+            TimestampFormat::Custom(format) => DateTime::parse_from_str(value, format)
+                .map(|datetime| datetime.with_timezone(&Utc))
+                .or_else(|_| {
+                    NaiveDateTime::parse_from_str(value, format).map(|datetime| datetime.and_utc())
+                })
+                .map_err(|err| err.to_string()),
+        }
     }
 }
+
+/// Interface implemented by user types that can be built from the fields of an `ItemUpdate`, so
+/// that listeners can work with typed domain objects instead of string maps.
+///
+/// It can be derived with `#[derive(LightstreamerFields)]` when the `derive` feature is enabled:
+/// each struct field is read from the Lightstreamer field with the same name (or the one set with
+/// `#[lightstreamer(rename = "...")]`) and parsed through `FromStr`, while `Option` fields are set
+/// to `None` when the Lightstreamer field has no value.
+pub trait LightstreamerFields: Sized {
+    /// Builds a new instance from the current field values of the given update.
+    ///
+    /// # Raises
+    /// - `IllegalArgumentException` – if a required field has no value or a value can't be parsed.
+    fn from_item_update(update: &ItemUpdate) -> Result<Self, IllegalArgumentException>;
+}
+
+/// Gets the value of a field of an update parsed into the requested type, converting parsing
+/// errors into `IllegalArgumentException`. Used by the code generated by
+/// `#[derive(LightstreamerFields)]`.
+#[doc(hidden)]
+pub fn parse_field<T>(
+    update: &ItemUpdate,
+    field_name: &str,
+) -> Result<Option<T>, IllegalArgumentException>
+where
+    T: FromStr,
+    T::Err: Display,
+{
+    update.get_value_as::<T>(field_name).map_err(|err| {
+        IllegalArgumentException::new(&format!(
+            "Invalid value for field '{}': {}",
+            field_name, err
+        ))
+    })
+}
@@ -1,11 +1,92 @@
-pub mod client_listener;
-pub mod client_message_listener;
-pub mod connection_details;
-pub mod connection_options;
-pub mod error;
-pub mod item_update;
-pub mod ls_client;
-pub mod proxy;
-pub mod subscription;
-pub mod subscription_listener;
-pub mod util;
+#[cfg(not(any(
+    feature = "runtime-tokio",
+    feature = "runtime-async-std",
+    feature = "runtime-smol",
+    all(target_arch = "wasm32", feature = "runtime-wasm")
+)))]
+compile_error!(
+    "one of the features \"runtime-tokio\", \"runtime-async-std\", \"runtime-smol\" or \"runtime-wasm\" (wasm32 only) must be enabled"
+);
+
+/// Declares the given items only when a runtime is enabled, as they all depend on the WebSocket
+/// implementation it brings, so that without one only the error above is reported.
+macro_rules! with_runtime {
+    ($($item:item)*) => {
+        $(
+            #[cfg(any(
+                feature = "runtime-tokio",
+                feature = "runtime-async-std",
+                feature = "runtime-smol",
+                all(target_arch = "wasm32", feature = "runtime-wasm")
+            ))]
+            $item
+        )*
+    };
+}
+
+with_runtime! {
+    pub mod client_debug_state;
+    pub mod client_handle;
+    pub mod client_listener;
+    pub mod client_message_listener;
+    pub mod client_metrics;
+    pub mod clock;
+    mod conflation;
+    pub mod connect_overrides;
+    pub mod connection_details;
+    pub mod connection_info;
+    pub mod connection_options;
+    #[cfg(feature = "runtime-tokio")]
+    pub mod connector;
+    mod cookies;
+    pub mod credentials_provider;
+    #[cfg(feature = "serde")]
+    pub mod deserializer;
+    pub mod disconnect_info;
+    mod dispatcher;
+    pub mod error;
+    pub mod event_history;
+    #[cfg(feature = "test-util")]
+    pub mod fault_injection;
+    #[cfg(feature = "reqwest")]
+    mod http_transport;
+    pub mod item_update;
+    pub mod logger;
+    pub mod ls_client;
+    pub mod monitor;
+    pub mod prelude;
+    #[doc(hidden)]
+    pub mod protocol;
+    pub mod proxy;
+    pub mod recording;
+    pub mod request_interceptor;
+    pub mod retry_policy;
+    mod runtime;
+    pub mod secret;
+    mod session;
+    pub mod session_end_cause;
+    pub mod session_pool;
+    pub mod subscription;
+    pub mod subscription_listener;
+    pub mod util;
+    pub mod weak_listener;
+
+    pub use client_listener::ClientListener;
+    pub use client_message_listener::ClientMessageListener;
+    pub use connection_options::ConnectionOptions;
+    pub use error::{
+        IllegalArgumentException, IllegalStateException, InvalidOptionsException, TimeoutException,
+    };
+    pub use item_update::{ItemUpdate, LightstreamerFields};
+    pub use ls_client::{ClientStatus, LightstreamerClient, LogType, Transport};
+    pub use session_pool::{PoolStatus, SessionPool};
+    pub use subscription::{Snapshot, Subscription, SubscriptionMode};
+    pub use subscription_listener::SubscriptionListener;
+
+    #[cfg(feature = "derive")]
+    pub use lightstreamer_client_derive::LightstreamerFields;
+    /// Former name of the `ls_client` module, kept so that the imports written against it keep
+    /// compiling. New code should use `ls_client` or the re-exports above.
+    #[doc(hidden)]
+    pub use ls_client as lightstreamer_client;
+}
@@ -0,0 +1,164 @@
+//! Pluggable logging of the library diagnostics, organized in categories.
+//!
+//! A `LoggerProvider` installed through `LightstreamerClient::set_logger_provider()` is asked
+//! once for a `Logger` per `LogCategory`, and all the diagnostics of that category are then routed
+//! to it. This allows to forward them to any logging stack, with a different level per category.
+
+use std::fmt::{self, Display, Formatter};
+use std::sync::{Arc, RwLock};
+use tracing::Level;
+
+/// The categories the library diagnostics are organized in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LogCategory {
+    /// Connection lifecycle: socket activity, session creation, recovery, retries and closure.
+    Connections,
+    /// Requests sent to the Server and notifications received from it.
+    Protocol,
+    /// Subscription requests, their outcome and the related updates.
+    Subscriptions,
+    /// Messages sent to the Server and their outcome.
+    Messages,
+}
+
+impl LogCategory {
+    /// All the categories, in declaration order.
+    pub const ALL: [LogCategory; 4] = [
+        LogCategory::Connections,
+        LogCategory::Protocol,
+        LogCategory::Subscriptions,
+        LogCategory::Messages,
+    ];
+
+    /// Gets the name of the category, as used by the official Lightstreamer clients.
+    pub fn name(&self) -> &'static str {
+        match self {
+            LogCategory::Connections => "lightstreamer.connections",
+            LogCategory::Protocol => "lightstreamer.protocol",
+            LogCategory::Subscriptions => "lightstreamer.subscriptions",
+            LogCategory::Messages => "lightstreamer.messages",
+        }
+    }
+
+    fn index(&self) -> usize {
+        *self as usize
+    }
+}
+
+impl Display for LogCategory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Interface to be implemented to receive the log of a category of the library.
+pub trait Logger: Send + Sync {
+    /// Receives a log message.
+    ///
+    /// # Parameters
+    ///
+    /// * `level`: the level of the message.
+    /// * `message`: the message to be logged.
+    fn log(&self, level: Level, message: &str);
+
+    /// Checks whether messages of the given level are wanted. Messages of disabled levels are not
+    /// passed to `log()`.
+    ///
+    /// By default all the levels are enabled.
+    fn is_enabled(&self, level: Level) -> bool {
+        let _ = level;
+        true
+    }
+}
+
+/// Interface to be implemented to provide the library with a `Logger` for each category.
+///
+/// See also `LightstreamerClient.setLoggerProvider()`
+pub trait LoggerProvider: Send + Sync {
+    /// Gets the `Logger` for a category. It's invoked once per category when the provider is
+    /// installed.
+    ///
+    /// # Parameters
+    ///
+    /// * `category`: the category the returned logger will receive the log of.
+    fn get_logger(&self, category: LogCategory) -> Arc<dyn Logger>;
+}
+
+/// `LoggerProvider` writing to the standard error, with a threshold level that can be tuned per
+/// category.
+///
+/// ```
+/// use lightstreamer_client::logger::{ConsoleLoggerProvider, LogCategory};
+/// use lightstreamer_client::ls_client::LightstreamerClient;
+/// use std::sync::Arc;
+/// use tracing::Level;
+///
+/// let provider = ConsoleLoggerProvider::new(Level::WARN)
+///     .with_category_level(LogCategory::Subscriptions, Level::DEBUG);
+/// LightstreamerClient::set_logger_provider(Some(Arc::new(provider)));
+/// ```
+#[derive(Debug, Clone)]
+pub struct ConsoleLoggerProvider {
+    levels: [Level; 4],
+}
+
+impl ConsoleLoggerProvider {
+    /// Creates a provider logging the messages of all the categories up to the given level.
+    pub fn new(level: Level) -> Self {
+        ConsoleLoggerProvider { levels: [level; 4] }
+    }
+
+    /// Sets the threshold level of a single category.
+    pub fn with_category_level(mut self, category: LogCategory, level: Level) -> Self {
+        self.levels[category.index()] = level;
+        self
+    }
+}
+
+impl LoggerProvider for ConsoleLoggerProvider {
+    fn get_logger(&self, category: LogCategory) -> Arc<dyn Logger> {
+        Arc::new(ConsoleLogger {
+            category,
+            level: self.levels[category.index()],
+        })
+    }
+}
+
+/// Logger of a category created by `ConsoleLoggerProvider`.
+struct ConsoleLogger {
+    category: LogCategory,
+    level: Level,
+}
+
+impl Logger for ConsoleLogger {
+    fn log(&self, level: Level, message: &str) {
+        eprintln!("{:>5} {} - {}", level, self.category, message);
+    }
+
+    fn is_enabled(&self, level: Level) -> bool {
+        level <= self.level
+    }
+}
+
+/// The loggers obtained from the installed provider, indexed by category.
+static LOGGERS: RwLock<Option<[Arc<dyn Logger>; 4]>> = RwLock::new(None);
+
+/// Installs a logger provider, or removes the current one if `None` is given.
+pub(crate) fn set_logger_provider(provider: Option<Arc<dyn LoggerProvider>>) {
+    let loggers =
+        provider.map(|provider| LogCategory::ALL.map(|category| provider.get_logger(category)));
+    *LOGGERS.write().unwrap() = loggers;
+}
+
+/// Routes a message to the logger of its category. Returns `false` if no provider is installed.
+pub(crate) fn log(category: LogCategory, level: Level, message: &str) -> bool {
+    let loggers = LOGGERS.read().unwrap();
+    let Some(loggers) = loggers.as_ref() else {
+        return false;
+    };
+    let logger = &loggers[category.index()];
+    if logger.is_enabled(level) {
+        logger.log(level, message);
+    }
+    true
+}
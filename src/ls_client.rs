@@ -1,30 +1,46 @@
-use crate::client_listener::ClientListener;
+use crate::client_debug_state::ClientDebugState;
+use crate::client_handle::{run_engine, ClientHandle};
+use crate::client_listener::{ClientListener, ClientListeners, ListenerToken};
 use crate::client_message_listener::ClientMessageListener;
-use crate::connection_details::ConnectionDetails;
+use crate::client_metrics::{ClientMetrics, DispatchStall, MetricsRecorder};
+use crate::clock::{Clock, ClockSkew, RuntimeClock};
+use crate::connect_overrides::ConnectOverrides;
+use crate::connection_details::{self, ConnectionDetails};
+use crate::connection_info::ConnectionInfo;
 use crate::connection_options::ConnectionOptions;
-use crate::error::IllegalStateException;
-use crate::item_update::ItemUpdate;
-use crate::subscription::{Snapshot, Subscription, SubscriptionMode};
-use crate::util::*;
+#[cfg(feature = "runtime-tokio")]
+use crate::connector::Connector;
+use crate::cookies;
+use crate::disconnect_info::DisconnectInfo;
+use crate::dispatcher::EventDispatcher;
+use crate::error::{IllegalArgumentException, IllegalStateException};
+use crate::event_history::{ClientEvent, EventHistory, DEFAULT_EVENT_HISTORY_SIZE};
+use crate::logger::{self, LogCategory, LoggerProvider};
+use crate::recording::{Replay, SessionRecording};
+use crate::retry_policy::DefaultRetryPolicy;
+use crate::runtime::tungstenite::http::{HeaderName, HeaderValue, Request};
+use crate::runtime::{spawn_task, CurrentRuntime, Runtime, TaskHandle};
+use crate::session::{
+    set_status, PendingMessage, RetrySettings, Session, SessionInfo, SubscriptionChange,
+    SubscriptionChanges,
+};
+use crate::subscription::{
+    find_subscription_mut, subscription_position, Subscription, SubscriptionToken,
+};
+use crate::weak_listener::WeakClientListener;
 
 use cookie::Cookie;
-use futures_util::{SinkExt, StreamExt};
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::error::Error;
-use std::fmt::{self, Debug, Formatter};
-use std::sync::Arc;
-use tokio::sync::Notify;
-use tokio_tungstenite::{
-    connect_async,
-    tungstenite::{
-        http::{HeaderName, HeaderValue, Request},
-        Message,
-    },
-};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::time::Duration;
+use tokio::sync::{mpsc, Notify};
 use tracing::{debug, error, info, instrument, trace, warn, Level};
-use url::Url;
 
 /// Represents the current status of the `LightstreamerClient`.
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ClientStatus {
     Connecting,
     Connected(ConnectionType),
@@ -32,6 +48,21 @@ pub enum ClientStatus {
     Disconnected(DisconnectionType),
 }
 
+impl Display for ClientStatus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ClientStatus::Connecting => write!(f, "CONNECTING"),
+            ClientStatus::Connected(connection_type) => write!(f, "CONNECTED:{}", connection_type),
+            ClientStatus::Stalled => write!(f, "STALLED"),
+            ClientStatus::Disconnected(DisconnectionType::None) => write!(f, "DISCONNECTED"),
+            ClientStatus::Disconnected(disconnection_type) => {
+                write!(f, "DISCONNECTED:{}", disconnection_type)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum ConnectionType {
     HttpPolling,
     HttpStreaming,
@@ -40,16 +71,108 @@ pub enum ConnectionType {
     WsStreaming,
 }
 
+impl Display for ConnectionType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            ConnectionType::HttpPolling => write!(f, "HTTP-POLLING"),
+            ConnectionType::HttpStreaming => write!(f, "HTTP-STREAMING"),
+            ConnectionType::StreamSensing => write!(f, "STREAM-SENSING"),
+            ConnectionType::WsPolling => write!(f, "WS-POLLING"),
+            ConnectionType::WsStreaming => write!(f, "WS-STREAMING"),
+        }
+    }
+}
+
+/// Qualifies a `ClientStatus::Disconnected` status. `None` means that no connection is active and
+/// none will be attempted until a new `connect()` call is issued.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 pub enum DisconnectionType {
     WillRetry,
     TryingRecovery,
+    #[default]
+    None,
 }
 
+impl Display for DisconnectionType {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            DisconnectionType::WillRetry => write!(f, "WILL-RETRY"),
+            DisconnectionType::TryingRecovery => write!(f, "TRYING-RECOVERY"),
+            DisconnectionType::None => Ok(()),
+        }
+    }
+}
+
+/// The logging system used by a `LightstreamerClient` instance when no `LoggerProvider` is
+/// installed.
+///
+/// The default is `LogCrateLogs` when the `log` feature is enabled and `TracingLogs` otherwise,
+/// so that nothing is written to the console unless requested.
+#[derive(Debug, Clone, Copy)]
 pub enum LogType {
+    /// Events of the `tracing` crate.
     TracingLogs,
+    /// Records of the `log` crate, with the log category as target.
+    #[cfg(feature = "log")]
+    LogCrateLogs,
+    /// Plain lines written to the standard error.
     StdLogs,
 }
 
+impl Default for LogType {
+    fn default() -> Self {
+        #[cfg(feature = "log")]
+        return LogType::LogCrateLogs;
+        #[cfg(not(feature = "log"))]
+        return LogType::TracingLogs;
+    }
+}
+
+impl LogType {
+    /// Emits a log message through the installed `LoggerProvider`, if any, or otherwise through
+    /// the logging system selected by this `LogType`.
+    ///
+    /// `loglevel` is ignored in the `StdLogs` case, where everything is written to stderr.
+    pub(crate) fn log(&self, category: LogCategory, loglevel: Level, log: &str) {
+        if logger::log(category, loglevel, log) {
+            return;
+        }
+        match self {
+            LogType::StdLogs => {
+                eprintln!("{}", log);
+            }
+            #[cfg(feature = "log")]
+            LogType::LogCrateLogs => {
+                let level = match loglevel {
+                    Level::ERROR => log::Level::Error,
+                    Level::WARN => log::Level::Warn,
+                    Level::INFO => log::Level::Info,
+                    Level::DEBUG => log::Level::Debug,
+                    Level::TRACE => log::Level::Trace,
+                };
+                log::log!(target: category.name(), level, "{}", log);
+            }
+            LogType::TracingLogs => match loglevel {
+                Level::INFO => {
+                    info!(log);
+                }
+                Level::WARN => {
+                    warn!(log);
+                }
+                Level::ERROR => {
+                    error!(log);
+                }
+                Level::TRACE => {
+                    trace!(log);
+                }
+                Level::DEBUG => {
+                    debug!(log);
+                }
+            },
+        }
+    }
+}
+
 /// Facade class for the management of the communication to Lightstreamer Server. Used to provide
 /// configuration settings, event handlers, operations for the control of the connection lifecycle,
 /// Subscription handling and to send messages.
@@ -64,14 +187,24 @@ pub enum LogType {
 ///
 /// You can listen to the events generated by a session by registering an event listener, such as
 /// `ClientListener` or `SubscriptionListener`. These listeners allow you to handle various events,
-/// such as session creation, connection status, subscription updates, and server messages. However,
-/// you should be aware that the event notifications are dispatched by a single thread, the so-called
-/// event thread. This means that if the operations of a listener are slow or blocking, they will
-/// delay the processing of the other listeners and affect the performance of your application.
-/// Therefore, you should delegate any slow or blocking operations to a dedicated thread, and keep
-/// the listener methods as fast and simple as possible. Note that even if you create multiple
-/// instances of `LightstreamerClient`, they will all use a single event thread, that is shared
-/// among them.
+/// such as session creation, connection status, subscription updates, and server messages.
+///
+/// Each `LightstreamerClient` runs its sessions in a task of its own, started by `connect()` on
+/// the async runtime, and notifies the events through a dispatch task of its own, which calls the
+/// listeners one at a time, in the order the events occurred. This means that if the operations of
+/// a listener are slow or blocking, they will delay the notifications to the other listeners of the
+/// same client, though not those of other clients. Therefore, you should delegate any slow or
+/// blocking operations to a dedicated task or thread, and keep the listener methods as fast and
+/// simple as possible. See also `ConnectionOptions.setDispatchWatchdogTimeout()` to detect the
+/// listeners blocking the dispatch task, and `Subscription.setDispatchMode()` to deliver the
+/// updates of a subscription through a pool of workers instead.
+///
+/// Configuration, through `connectionDetails`, `connectionOptions` and the other setters taking
+/// `&mut self`, is meant to happen before connecting. The operations used afterwards, such as
+/// `connect()`, `subscribe()`, `sendMessage()`, `getStatus()` and `disconnect()`, only need a
+/// shared reference and don't block each other, so a configured client can be shared among tasks
+/// through a plain `Arc`, with no `Mutex` around it. Alternatively, `spawn()` moves the client
+/// into a background engine driven through clonable `ClientHandle`s.
 ///
 /// # Parameters
 ///
@@ -102,14 +235,35 @@ pub struct LightstreamerClient {
     /// can be overwritten by values received from a Lightstreamer Server.
     pub connection_options: ConnectionOptions,
     /// A list of listeners that will receive events from the `LightstreamerClient` instance.
-    listeners: Vec<Box<dyn ClientListener>>,
+    /// Shared with the session task, which dispatches status changes and server errors.
+    listeners: Arc<Mutex<ClientListeners>>,
     /// A list containing all the `Subscription` instances that are currently "active" on this
-    /// `LightstreamerClient`.
-    subscriptions: Vec<Subscription>,
-    /// The current status of the client.
-    status: ClientStatus,
+    /// `LightstreamerClient`. Shared with the session task, which dispatches item updates.
+    subscriptions: Arc<Mutex<Vec<Subscription>>>,
+    /// Key to be assigned to the next `Subscription` given to the client, so that keys are never
+    /// reused and the list stays sorted by key.
+    next_subscription_key: AtomicUsize,
+    /// The current status of the client, updated by the session task.
+    status: Arc<Mutex<ClientStatus>>,
     /// Logging Type to be used
     logging: LogType,
+    /// The background task running the current session, if any. Guarded so that `connect()` and
+    /// `disconnect()` can be called through a shared reference.
+    session_task: Mutex<Option<SessionTask>>,
+    /// Messages submitted through `send_message()` and waiting to be sent by the session task.
+    messages: Arc<Mutex<VecDeque<PendingMessage>>>,
+    /// Signal used to notify the session task that new messages have been queued.
+    message_signal: Arc<Notify>,
+    /// Changes to the subscriptions waiting to be forwarded to the server by the session task.
+    subscription_changes: SubscriptionChanges,
+    /// Queue of the events for the listeners, consumed by a single dispatch task.
+    dispatcher: EventDispatcher,
+    /// Health counters, updated by the session task.
+    metrics: Arc<MetricsRecorder>,
+    /// State published by the session task for `debug_state()`.
+    session_info: Arc<Mutex<SessionInfo>>,
+    /// Recent significant events, returned by `recent_events()`.
+    history: Arc<EventHistory>,
 }
 
 impl Debug for LightstreamerClient {
@@ -155,13 +309,22 @@ impl LightstreamerClient {
     ///
     /// # Parameters
     ///
-    /// * `uri`: the URI from which the supplied cookies were received. It cannot be `None`.
-    /// * `cookies`: an instance of `http.cookies.SimpleCookie`.
+    /// * `uri`: the URI from which the supplied cookies were received.
+    /// * `cookies`: the cookies to be added. Cookies without a `Domain` attribute are bound to the
+    ///   host of `uri` and cookies without a `Path` attribute to its path.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if `uri` is not a valid URI.
     ///
     /// See also `getCookies()`
-    pub fn add_cookies(_uri: &str, _cookies: &Cookie) {
-        // Implementation for add_cookies
-        unimplemented!("Implement mechanism to add cookies to LightstreamerClient");
+    pub fn add_cookies<'c>(
+        uri: &str,
+        cookies: impl IntoIterator<Item = Cookie<'c>>,
+    ) -> Result<(), IllegalArgumentException> {
+        let url = cookies::parse_cookie_uri(uri)?;
+        cookies::add_cookies(&url, cookies);
+        Ok(())
     }
 
     /// Adds a listener that will receive events from the `LightstreamerClient` instance.
@@ -175,9 +338,91 @@ impl LightstreamerClient {
     /// * `listener`: An object that will receive the events as documented in the `ClientListener`
     ///   interface.
     ///
+    /// # Returns
+    ///
+    /// The token identifying the listener, to be passed to `removeListener()`.
+    ///
     /// See also `removeListener()`
-    pub fn add_listener(&mut self, listener: Box<dyn ClientListener>) {
-        self.listeners.push(listener);
+    pub fn add_listener(&self, listener: Box<dyn ClientListener>) -> ListenerToken {
+        self.listeners.lock().unwrap().push(listener)
+    }
+
+    /// Adds a listener held through a weak reference, so that it receives events from the
+    /// `LightstreamerClient` instance only as long as other owners keep it alive. Once the listener
+    /// is dropped, it is removed from the client before the next event is dispatched, without
+    /// receiving `ClientListener.onListenEnd()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `listener`: An object that will receive the events as documented in the `ClientListener`
+    ///   interface, owned by the component interested in them.
+    ///
+    /// # Returns
+    ///
+    /// The token identifying the listener, to be passed to `removeListener()` while it is alive.
+    ///
+    /// See also `addListener()`
+    ///
+    /// See also `WeakClientListener`
+    pub fn add_weak_listener<L: ClientListener + Sync + 'static>(
+        &self,
+        listener: &Arc<L>,
+    ) -> ListenerToken {
+        self.add_listener(Box::new(WeakClientListener::new(listener)))
+    }
+
+    /// Adds a listener that calls the given closure each time the status of the client changes,
+    /// with the new status, sparing the definition of a `ClientListener` for simple handlers.
+    /// Other events are ignored.
+    ///
+    /// # Parameters
+    ///
+    /// * `handler`: the closure called as `ClientListener.onStatusChange()`.
+    ///
+    /// # Returns
+    ///
+    /// The token identifying the listener, to be passed to `removeListener()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use lightstreamer_client::ls_client::LightstreamerClient;
+    /// let client = LightstreamerClient::new(None, None, None, None).unwrap();
+    /// let token = client.on_status_change(|status| println!("Status: {}", status));
+    /// client.on_server_error(|code, message| eprintln!("Refused ({}): {}", code, message));
+    /// // The closure is no longer called once its listener is removed.
+    /// client.remove_listener(token).unwrap();
+    /// ```
+    ///
+    /// See also `addListener()`
+    ///
+    /// See also `onServerError()`
+    pub fn on_status_change<F>(&self, handler: F) -> ListenerToken
+    where
+        F: FnMut(&str) + Send + 'static,
+    {
+        self.add_listener(Box::new(StatusChangeHandler(Mutex::new(handler))))
+    }
+
+    /// Adds a listener that calls the given closure when the Server refuses or closes the
+    /// session, with the error code and the description of the error. Other events are ignored.
+    ///
+    /// # Parameters
+    ///
+    /// * `handler`: the closure called as `ClientListener.onServerError()`.
+    ///
+    /// # Returns
+    ///
+    /// The token identifying the listener, to be passed to `removeListener()`.
+    ///
+    /// See also `addListener()`
+    ///
+    /// See also `onStatusChange()`
+    pub fn on_server_error<F>(&self, handler: F) -> ListenerToken
+    where
+        F: FnMut(i32, &str) + Send + 'static,
+    {
+        self.add_listener(Box::new(ServerErrorHandler(Mutex::new(handler))))
     }
 
     /// Operation method that requests to open a Session against the configured Lightstreamer Server.
@@ -193,16 +438,32 @@ impl LightstreamerClient {
     /// Note that as "polling connection" we mean a loop of polling requests, each of which requires
     /// opening a synchronous (i.e. not streaming) connection to Lightstreamer Server.
     ///
-    /// Note that the request to connect is accomplished by the client in a separate thread; this
+    /// Note that the request to connect is accomplished by the client in a separate task; this
     /// means that an invocation to `getStatus()` right after `connect()` might not reflect the change
-    /// yet.
+    /// yet. The method returns as soon as the session task has been started; the outcome of the
+    /// connection attempt is notified through `ClientListener.onStatusChange()` and, in case of a
+    /// refusal from the server, `ClientListener.onServerError()`.
+    ///
+    /// Once started, the session task takes care of connection failures by itself: broken
+    /// connections are retried according to `ConnectionOptions.setRetryPolicy()` (by default,
+    /// `ConnectionOptions.setFirstRetryMaxDelay()` and `ConnectionOptions.setRetryDelay()`), and the
+    /// current session is recovered when possible within `ConnectionOptions.setSessionRecoveryTimeout()`.
+    /// Only a refusal or a closure from the server, or the retry policy giving up, stops the attempts.
     ///
-    /// When the request to connect is finally being executed, if the current status of the client
-    /// is not `DISCONNECTED`, then nothing will be done.
+    /// A client can only be connected while its status is `DISCONNECTED`, i.e. while no session
+    /// task is running, not even one waiting to retry: otherwise an `IllegalStateException` is
+    /// returned and nothing is done.
+    ///
+    /// A client can be connected again after `disconnect()`, any number of times, even from a
+    /// different async runtime: each call opens a new Session, in which all the Subscriptions of
+    /// the client are subscribed to again, notifying `SubscriptionListener.onSubscription()`.
     ///
     /// # Raises
     ///
-    /// * `IllegalStateException`: if no server address was configured.
+    /// * `IllegalStateException`: if no server address was configured or the client is already
+    ///   connected or connecting.
+    /// * `InvalidOptionsException`: if the `ConnectionOptions` violate any constraint, see
+    ///   `ConnectionOptions.validate()`.
     ///
     /// See also `getStatus()`
     ///
@@ -212,56 +473,339 @@ impl LightstreamerClient {
     ///
     /// See also `ConnectionDetails.setServerAddress()`
     #[instrument]
-    pub async fn connect(&mut self, shutdown_signal: Arc<Notify>) -> Result<(), Box<dyn Error>> {
+    pub async fn connect(&self) -> Result<(), Box<dyn Error>> {
+        self.start_session(&ConnectOverrides::default(), None, None)
+    }
+
+    /// Operation method that requests to open a Session against the configured Lightstreamer Server,
+    /// bounding the duration of every single connection attempt.
+    ///
+    /// It behaves like `connect()`, but each connection attempt, including DNS resolution, TCP
+    /// connection, TLS and WebSocket handshakes and the confirmation of the session by the Server,
+    /// must complete within the given deadline. An attempt exceeding it is abandoned and handled as
+    /// a failed attempt, so the retry policy decides whether and when to try again. The deadline is
+    /// independent of the long-term retry settings in `ConnectionOptions`.
+    ///
+    /// # Parameters
+    ///
+    /// * `deadline`: the maximum duration of a single connection attempt.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if a zero deadline is configured.
+    /// * `IllegalStateException`: if no server address was configured or the client is already
+    ///   connected or connecting.
+    /// * `InvalidOptionsException`: if the `ConnectionOptions` violate any constraint, see
+    ///   `ConnectionOptions.validate()`.
+    ///
+    /// See also `connect()`
+    #[instrument]
+    pub async fn connect_with_deadline(&self, deadline: Duration) -> Result<(), Box<dyn Error>> {
+        if deadline.is_zero() {
+            return Err(Box::new(IllegalArgumentException::new(
+                "Connect deadline cannot be zero",
+            )));
+        }
+        self.start_session(&ConnectOverrides::default(), Some(deadline), None)
+    }
+
+    /// Operation method that requests to open a Session against the configured Lightstreamer Server,
+    /// with some of the `ConnectionOptions` replaced by the given overrides.
+    ///
+    /// It behaves like `connect()`, but the session task started by this call, including its
+    /// reconnections, uses the overridden settings in place of the ones of `connectionOptions`,
+    /// which are left untouched: a later call to `connect()` uses the long-term configuration
+    /// again. This allows, for instance, a diagnostic connection forced on HTTP polling or with
+    /// shorter timeouts. The constraints checked by `ConnectionOptions.validate()` apply to the
+    /// overridden settings.
+    ///
+    /// # Parameters
+    ///
+    /// * `overrides`: the settings replacing the ones of `connectionOptions`.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalStateException`: if no server address was configured or the client is already
+    ///   connected or connecting.
+    /// * `InvalidOptionsException`: if the overridden `ConnectionOptions` violate any constraint,
+    ///   see `ConnectionOptions.validate()`.
+    ///
+    /// See also `connect()`
+    ///
+    /// See also `ConnectOverrides`
+    #[instrument]
+    pub async fn connect_with(&self, overrides: ConnectOverrides) -> Result<(), Box<dyn Error>> {
+        self.start_session(&overrides, None, None)
+    }
+
+    /// Operation method that replays a session recorded through
+    /// `ConnectionOptions.setSessionRecorder()` instead of connecting to the Server.
+    ///
+    /// It behaves like `connect()`, but each connection the client would open delivers, in order
+    /// and as fast as they can be processed, the frames received on the corresponding recorded
+    /// connection, while the requests of the client are discarded. The frames go through the same
+    /// parser and dispatcher as live ones, so the listeners observe what they observed when the
+    /// session was recorded, provided that the same subscriptions are made in the same order.
+    /// The last recorded connection stays open until `disconnect()` is called.
+    ///
+    /// # Parameters
+    ///
+    /// * `recording`: the recorded session to be replayed.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalStateException`: if no server address was configured or the client is already
+    ///   connected or connecting.
+    /// * `InvalidOptionsException`: if the `ConnectionOptions` violate any constraint, see
+    ///   `ConnectionOptions.validate()`.
+    ///
+    /// See also `connect()`
+    #[instrument(skip(recording))]
+    pub async fn replay(&self, recording: SessionRecording) -> Result<(), Box<dyn Error>> {
+        self.start_session(
+            &ConnectOverrides::default(),
+            None,
+            Some(Arc::new(Replay::new(recording))),
+        )
+    }
+
+    /// Moves this `LightstreamerClient` into a background engine task and returns a handle to drive
+    /// it.
+    ///
+    /// The client should be fully configured before being spawned: from then on it is only
+    /// reachable through the returned `ClientHandle`, which can be cloned cheaply and shared among
+    /// any number of tasks. Their requests are forwarded to the engine over a channel and carried
+    /// out in the order they were issued. When the last handle is dropped, the client is
+    /// disconnected and the engine terminates.
+    ///
+    /// Must be called within the async runtime.
+    ///
+    /// # Returns
+    ///
+    /// The handle to the client running in the engine task.
+    ///
+    /// See also `ClientHandle`
+    pub fn spawn(self) -> ClientHandle {
+        // The engine runs on the runtime of the connector, if it has one, like the session.
+        #[cfg(feature = "runtime-tokio")]
+        let connector = self.connection_options.get_connector().cloned();
+        #[cfg(feature = "runtime-tokio")]
+        let _runtime = connector.as_ref().and_then(Connector::enter);
+
+        let (commands, receiver) = mpsc::unbounded_channel();
+        let handle = ClientHandle::new(
+            commands,
+            Arc::clone(&self.status),
+            Arc::clone(&self.metrics),
+        );
+        CurrentRuntime::spawn(run_engine(self, receiver));
+        handle
+    }
+
+    /// Starts the task running the session, failing if one is already running. The given overrides
+    /// replace the corresponding connection options. When a recorded session is given, it is
+    /// replayed instead of connecting to the server.
+    fn start_session(
+        &self,
+        overrides: &ConnectOverrides,
+        connect_deadline: Option<Duration>,
+        replay: Option<Arc<Replay>>,
+    ) -> Result<(), Box<dyn Error>> {
         // Check if the server address is configured.
         if self.server_address.is_none() {
             return Err(Box::new(IllegalStateException::new(
                 "No server address was configured.",
             )));
         }
+        self.connection_options.validate_with(overrides)?;
         //
-        // Only WebSocket streaming transport is currently supported.
+        // Only WebSocket streaming and polling transports are currently supported, and the HTTP
+        // ones with the `reqwest` feature. A replay uses no transport.
         //
-        let forced_transport = self.connection_options.get_forced_transport();
-        if forced_transport.is_none()
-            || *forced_transport.unwrap() /* unwrap() is safe here */ != Transport::WsStreaming
-        {
+        let forced_transport = overrides
+            .forced_transport
+            .unwrap_or(self.connection_options.get_forced_transport().copied());
+        let supported = matches!(
+            forced_transport,
+            Some(Transport::WsStreaming | Transport::WsPolling)
+        ) || cfg!(feature = "reqwest")
+            && matches!(
+                forced_transport,
+                Some(Transport::HttpStreaming | Transport::HttpPolling)
+            );
+        if replay.is_none() && !supported {
             return Err(Box::new(IllegalStateException::new(
-                "Only WebSocket streaming transport is currently supported.",
+                if cfg!(feature = "reqwest") {
+                    "Only streaming and polling transports are currently supported."
+                } else {
+                    "Only WebSocket streaming and polling transports are currently supported."
+                },
             )));
         }
         //
-        // Convert the HTTP URL to a WebSocket URL.
+        // Refuse to start a second session task. The lock is held until the new task is stored,
+        // so that concurrent calls can't start two sessions.
         //
-        let http_url = self.connection_details.get_server_address().unwrap(); // unwrap() is safe here.
-        let mut url = Url::parse(&http_url)
-            .expect("Failed to parse server address URL from connection details.");
-        match url.scheme() {
-            "http" => url
-                .set_scheme("ws")
-                .expect("Failed to set scheme to ws for WebSocket URL."),
-            "https" => url
-                .set_scheme("wss")
-                .expect("Failed to set scheme to wss for WebSocket URL."),
-            invalid_scheme => {
-                return Err(Box::new(IllegalStateException::new(&format!(
-                    "Unsupported scheme '{}' found when converting HTTP URL to WebSocket URL.",
-                    invalid_scheme
-                ))));
-            }
+        let mut session_task = self.session_task.lock().unwrap();
+        if session_task
+            .as_mut()
+            .is_some_and(|session_task| !session_task.handle.is_finished())
+        {
+            return Err(Box::new(IllegalStateException::new(
+                "The client is already connected or connecting: call disconnect() first.",
+            )));
         }
-        let ws_url = url.as_str();
+
+        let ws_request = self.build_ws_request()?;
+        let extra_headers = self.connection_options.extra_headers()?;
+        let create_session_params = self.build_create_session_params()?;
+        // The option changes made so far are part of the settings resolved below.
+        self.connection_options.option_changes().clear();
+        let retry_policy = match self.connection_options.get_retry_policy() {
+            Some(retry_policy) => Arc::clone(retry_policy),
+            None => Arc::new(
+                DefaultRetryPolicy::new(
+                    Duration::from_millis(
+                        overrides
+                            .retry_delay
+                            .unwrap_or(self.connection_options.get_retry_delay()),
+                    ),
+                    Duration::from_millis(self.connection_options.get_first_retry_max_delay()),
+                )
+                .with_max_retry_delay(Duration::from_millis(
+                    self.connection_options.get_max_retry_delay(),
+                )),
+            ),
+        };
+
+        let clock: Arc<dyn Clock> = match self.connection_options.get_clock() {
+            Some(clock) => Arc::clone(clock),
+            None => Arc::new(RuntimeClock),
+        };
+
+        // The tasks of the client run on the runtime of its connector, if it has one.
+        #[cfg(feature = "runtime-tokio")]
+        let _runtime = self
+            .connection_options
+            .get_connector()
+            .and_then(Connector::enter);
+
+        // A fresh signal for every session, so that a stale notification can't stop a new one.
+        let shutdown_signal = Arc::new(Notify::new());
+        self.history
+            .set_capacity(self.connection_options.get_event_history_size());
+        self.dispatcher
+            .start(self.connection_options.watchdog_settings(self.logging));
+        set_status(
+            &self.status,
+            &self.dispatcher,
+            &self.history,
+            self.logging,
+            ClientStatus::Connecting,
+        );
+
+        #[allow(unused_mut)]
+        let mut session = Session::new(
+            ws_request,
+            self.connection_options.websocket_config(),
+            create_session_params,
+            Arc::clone(&self.subscriptions),
+            self.dispatcher.clone(),
+            Arc::clone(&self.status),
+            self.logging,
+            Arc::clone(&shutdown_signal),
+            RetrySettings {
+                retry_policy,
+                session_recovery_timeout: Duration::from_millis(
+                    overrides
+                        .session_recovery_timeout
+                        .unwrap_or(self.connection_options.get_session_recovery_timeout()),
+                ),
+                connect_deadline,
+                ping_interval: match self.connection_options.get_ping_interval() {
+                    0 => None,
+                    ping_interval => Some(Duration::from_millis(ping_interval)),
+                },
+                pong_timeout: Duration::from_millis(self.connection_options.get_pong_timeout()),
+                stalled_timeout: Duration::from_millis(
+                    overrides
+                        .stalled_timeout
+                        .unwrap_or(self.connection_options.get_stalled_timeout()),
+                ),
+                reconnect_timeout: Duration::from_millis(
+                    overrides
+                        .reconnect_timeout
+                        .unwrap_or(self.connection_options.get_reconnect_timeout()),
+                ),
+                message_recovery_policy: self.connection_options.get_message_recovery_policy(),
+                max_relogin_attempts: self.connection_options.get_max_relogin_attempts(),
+            },
+            clock,
+            Arc::clone(&self.messages),
+            Arc::clone(&self.message_signal),
+            self.subscription_changes.clone(),
+            self.connection_options.stream_settings(overrides),
+            self.connection_options.option_changes().clone(),
+            Arc::clone(self.connection_options.server_settings()),
+            Arc::clone(&self.metrics),
+            Arc::clone(&self.session_info),
+            self.connection_details.get_credentials_provider().cloned(),
+            self.connection_details
+                .get_reauthentication_handler()
+                .cloned(),
+            replay,
+            self.connection_options.get_session_recorder().cloned(),
+        );
+        #[cfg(feature = "test-util")]
+        session.set_fault_injector(self.connection_options.get_fault_injector().cloned());
+        #[cfg(feature = "runtime-tokio")]
+        session.set_connector(self.connection_options.get_connector().cloned());
+        session.set_request_interceptors(self.connection_options.get_request_interceptors().into());
+        session.set_event_history(Arc::clone(&self.history));
+        session.set_extra_headers(extra_headers);
+        #[cfg(feature = "reqwest")]
+        session.set_http_executor(
+            self.connection_options.http_executor(),
+            self.connection_options.read_buffer_settings(),
+        );
+        *session_task = Some(SessionTask {
+            handle: spawn_task(session.run()),
+            shutdown_signal,
+        });
+
+        Ok(())
+    }
+
+    /// Builds the WebSocket upgrade request for the configured server address, converting the
+    /// HTTP URL to a WebSocket URL.
+    fn build_ws_request(&self) -> Result<Request<()>, Box<dyn Error>> {
+        let http_url = match self.connection_details.get_server_address() {
+            Some(http_url) => http_url,
+            None => {
+                return Err(Box::new(IllegalStateException::new(
+                    "No server address was configured.",
+                )))
+            }
+        };
+        let url =
+            connection_details::websocket_url(&connection_details::parse_server_address(http_url)?);
+        // The Host header carries the port too when it is not the default one of the scheme;
+        // IPv6 literals are kept in brackets.
+        let host = match url.port() {
+            Some(port) => format!("{}:{}", url.host_str().unwrap_or("localhost"), port),
+            None => url.host_str().unwrap_or("localhost").to_string(),
+        };
 
         // Build the WebSocket request with the necessary headers.
         let request = Request::builder()
-            .uri(ws_url)
+            .uri(url.as_str())
             .header(
                 HeaderName::from_static("connection"),
                 HeaderValue::from_static("Upgrade"),
             )
             .header(
                 HeaderName::from_static("host"),
-                HeaderValue::from_str(url.host_str().unwrap_or("localhost")).map_err(|err| {
+                HeaderValue::from_str(&host).map_err(|err| {
                     IllegalStateException::new(&format!(
                         "Invalid header value for header with name 'host': {}",
                         err
@@ -286,477 +830,30 @@ impl LightstreamerClient {
             )
             .body(())?;
 
-        // Connect to the Lightstreamer server using WebSocket.
-        let ws_stream = match connect_async(request).await {
-            Ok((ws_stream, response)) => {
-                if let Some(server_header) = response.headers().get("server") {
-                    self.make_log(
-                        Level::INFO,
-                        &format!(
-                            "Connected to Lightstreamer server: {}",
-                            server_header.to_str().unwrap_or("")
-                        ),
-                    );
-                } else {
-                    self.make_log(Level::INFO, "Connected to Lightstreamer server");
-                }
-                ws_stream
-            }
-            Err(err) => {
-                return Err(Box::new(std::io::Error::new(
-                    std::io::ErrorKind::ConnectionRefused,
-                    format!(
-                        "Failed to connect to Lightstreamer server with WebSocket: {}",
-                        err
-                    ),
-                )));
-            }
-        };
-
-        // Split the WebSocket stream into a write and a read stream.
-        let (mut write_stream, mut read_stream) = ws_stream.split();
-
-        //
-        // Initiate communication with the server by sending a 'wsok' message.
-        //
-        write_stream.send(Message::Text("wsok".into())).await?;
+        Ok(request)
+    }
 
-        //
-        // Start reading and processing messages from the server.
-        //
-        let mut request_id: usize = 0;
-        let mut _session_id: Option<String> = None;
-        let mut subscription_id: usize = 0;
-        let mut subscription_item_updates: HashMap<usize, HashMap<usize, ItemUpdate>> =
-            HashMap::new();
-        loop {
-            tokio::select! {
-                message = read_stream.next() => {
-                    match message {
-                        Some(Ok(Message::Text(text))) => {
-                            // Messages could include multiple submessages separated by /r/n.
-                            // Split the message into submessages and process each one separately.
-                            let submessages: Vec<&str> = text.split("\r\n")
-                                .filter(|&line| !line.trim().is_empty()) // Filter out empty lines.
-                                .collect();
-                            for submessage in submessages {
-                                let clean_text = clean_message(&submessage);
-                                let submessage_fields: Vec<&str> = clean_text.split(",").collect();
-                                match *submessage_fields.first().unwrap_or(&"") {
-                                    //
-                                    // Errors from server.
-                                    //
-                                    "conerr" | "reqerr" => {
-                                        self.make_log( Level::ERROR, &format!("Received connection error from Lightstreamer server: {}", clean_text) );
-                                        break;
-                                    },
-                                    //
-                                    // Session created successfully.
-                                    //
-                                    "conok" => {
-                                        if let Some(session_id) = submessage_fields.get(1).as_deref() {
-                                            self.make_log( Level::DEBUG, &format!("Session creation confirmed by server: {}", clean_text) );
-                                            self.make_log( Level::DEBUG, &format!("Session created with ID: {:?}", session_id) );
-                                            //
-                                            // Subscribe to the desired items.
-                                            //
-                                            while let Some(subscription) = self.subscriptions.get(subscription_id) {
-                                                //
-                                                // Gather all the necessary subscription parameters.
-                                                //
-                                                request_id += 1;
-                                                let ls_req_id = request_id.to_string();
-                                                subscription_id += 1;
-                                                let ls_sub_id = subscription_id.to_string();
-                                                let ls_mode = subscription.get_mode().to_string();
-                                                let ls_group = match subscription.get_item_group() {
-                                                    Some(item_group) => item_group.to_string(),
-                                                    None => match subscription.get_items() {
-                                                        Some(items) => {
-                                                            items.join(" ")
-                                                        },
-                                                        None => {
-                                                            return Err(Box::new(std::io::Error::new(
-                                                                std::io::ErrorKind::InvalidData,
-                                                                "No item group or items found in subscription.",
-                                                            )));
-                                                        },
-                                                    },
-                                                };
-                                                let ls_schema = match subscription.get_field_schema() {
-                                                    Some(field_schema) => field_schema.to_string(),
-                                                    None => match subscription.get_fields() {
-                                                        Some(fields) => {
-                                                            fields.join(" ")
-                                                        },
-                                                        None => {
-                                                            return Err(Box::new(std::io::Error::new(
-                                                                std::io::ErrorKind::InvalidData,
-                                                                "No field schema or fields found in subscription.",
-                                                            )));
-                                                        },
-                                                    },
-                                                };
-                                                let ls_data_adapter = match subscription.get_data_adapter() {
-                                                    Some(data_adapter) => data_adapter.to_string(),
-                                                    None => "".to_string(),
-                                                };
-                                                let ls_snapshot = subscription.get_requested_snapshot().unwrap_or_default().to_string();
-                                                //
-                                                // Prepare the subscription request.
-                                                //
-                                                let mut params: Vec<(&str, &str)> = vec![
-                                                    ("LS_data_adapter", &ls_data_adapter),
-                                                    ("LS_reqId", &ls_req_id),
-                                                    ("LS_op", "add"),
-                                                    ("LS_subId", &ls_sub_id),
-                                                    ("LS_mode", &ls_mode),
-                                                    ("LS_group", &ls_group),
-                                                    ("LS_schema", &ls_schema),
-                                                    ("LS_ack", "false"),
-                                                ];
-                                                // Remove the data adapter parameter if not specified.
-                                                if ls_data_adapter == "" {
-                                                    params.remove(0);
-                                                }
-                                                if ls_snapshot != "" {
-                                                    params.push(("LS_snapshot", &ls_snapshot));
-                                                }
-                                                let encoded_params = serde_urlencoded::to_string(&params)?;
-                                                write_stream
-                                                    .send(Message::Text(format!("control\r\n{}", encoded_params)))
-                                                    .await?;
-                                                info!("Sent subscription request: '{}'", encoded_params);
-                                            }
-                                        } else {
-                                            return Err(Box::new(std::io::Error::new(
-                                                std::io::ErrorKind::InvalidData,
-                                                "Session ID not found in 'conok' message from server",
-                                            )));
-                                        }
-                                    },
-                                    //
-                                    // Notifications from server.
-                                    //
-                                    "conf" | "cons" | "clientip" | "servname" | "prog" | "sync" => {
-                                        self.make_log( Level::INFO, &format!("Received notification from server: {}", clean_text) );
-                                        // Don't do anything with these notifications for now.
-                                    },
-                                    "probe" => {
-                                        self.make_log( Level::DEBUG, &format!("Received probe message from server: {}", clean_text ) );
-                                    },
-                                    "reqok" => {
-                                        self.make_log( Level::DEBUG, &format!("Received reqok message from server: '{}'", clean_text ) );
-                                    },
-                                    //
-                                    // Subscription confirmation from server.
-                                    //
-                                    "subok" => {
-                                        self.make_log( Level::INFO, &format!("Subscription confirmed by server: '{}'", clean_text) );
-                                    },
-                                    //
-                                    // Data updates from server.
-                                    //
-                                    "u" => {
-                                        // Parse arguments from the received message.
-                                        let arguments = clean_text.split(",").collect::<Vec<&str>>();
-                                        //
-                                        // Extract the subscription from the first argument.
-                                        //
-                                        let subscription_index = arguments.get(1).unwrap_or(&"").parse::<usize>().unwrap_or(0);
-                                        let subscription = match self.get_subscriptions().get(subscription_index-1) {
-                                            Some(subscription) => subscription,
-                                            None => {
-                                                self.make_log( Level::WARN, &format!("Subscription not found for index: {}", subscription_index) );
-                                                continue;
-
-                                            }
-                                        };
-                                        //
-                                        // Extract the item from the second argument.
-                                        //
-                                        let item_index = arguments.get(2).unwrap_or(&"").parse::<usize>().unwrap_or(0);
-                                        let item = match subscription.get_items() {
-                                            Some(items) => items.get(item_index-1),
-                                            None => {
-                                                self.make_log( Level::WARN, &format!("No items found in subscription: {:?}", subscription) );
-                                                continue;
-                                            }
-                                        };
-                                        //
-                                        // Determine if the update is a snapshot or real-time update based on the subscription parameters.
-                                        //
-                                        let is_snapshot = match subscription.get_requested_snapshot() {
-                                            Some(ls_snapshot) => {
-                                                match ls_snapshot {
-                                                    Snapshot::No => false,
-                                                    Snapshot::Yes => {
-                                                        match subscription.get_mode() {
-                                                            SubscriptionMode::Merge => {
-                                                                if arguments.len() == 4 && arguments[3] == "$" {
-                                                                    // EOS notification received
-                                                                    true
-                                                                } else {
-                                                                    // If item doesn't exist in item_updates yet, the first update
-                                                                    // is always a snapshot.
-                                                                    if let Some(item_updates) = subscription_item_updates.get(&(subscription_index)) {
-                                                                        if let Some(_) = item_updates.get(&(item_index)) {
-                                                                            // Item update already exists in item_updates, so it's not a snapshot.
-                                                                            false
-                                                                        } else {
-                                                                            // Item update doesn't exist in item_updates, so the first update is always a snapshot.
-                                                                            true
-                                                                        }
-                                                                    } else {
-                                                                        // Item updates not found for subscription, so the first update is always a snapshot.
-                                                                        true
-                                                                    }
-                                                                }
-                                                            },
-                                                            SubscriptionMode::Distinct | SubscriptionMode::Command => {
-                                                                if !subscription.is_subscribed() {
-                                                                    true
-                                                                } else {
-                                                                    false
-                                                                }
-                                                            },
-                                                            _ => false,
-                                                        }
-                                                    },
-                                                    _ => false,
-                                                }
-                                            },
-                                            None => false,
-                                        };
-
-                                        // Extract the field values from the third argument.
-                                        let field_values: Vec<&str> = arguments.get(3).unwrap_or(&"").split('|').collect();
-
-                                        //
-                                        // Get fields from subscription and create a HashMap of field names and values.
-                                        //
-                                        let subscription_fields = subscription.get_fields();
-                                        let mut field_map: HashMap<String, Option<String>> = subscription_fields
-                                            .map(|fields| fields.iter().map(|field_name| (field_name.to_string(), None)).collect())
-                                            .unwrap_or_default();
-
-                                        let mut field_index = 0;
-                                        for value in field_values {
-                                            match value {
-                                                "" => {
-                                                    // An empty value means the field is unchanged compared to the previous update of the same field.
-                                                    if let Some(field_name) = subscription_fields.and_then(|fields| fields.get(field_index)) {
-                                                        field_map.insert(field_name.to_string(), None);
-                                                    }
-                                                    field_index += 1;
-                                                }
-                                                "#" | "$" => {
-                                                    // A value corresponding to a hash sign "#" or dollar sign "$" means the field is null or empty.
-                                                    if let Some(field_name) = subscription_fields.and_then(|fields| fields.get(field_index)) {
-                                                        field_map.insert(field_name.to_string(), Some("".to_string()));
-                                                    }
-                                                    field_index += 1;
-                                                }
-                                                value if value.starts_with('^') => {
-                                                    let command = value.chars().nth(1).unwrap_or(' ');
-                                                    match command {
-                                                        '0'..='9' => {
-                                                            let count = value[1..].parse().unwrap_or(0);
-                                                            for i in 0..count {
-                                                                if let Some(field_name) = subscription_fields.and_then(|fields| fields.get(field_index + i)) {
-                                                                    field_map.insert(field_name.to_string(), None);
-                                                                }
-                                                            }
-                                                            field_index += count;
-                                                        }
-                                                        'P' | 'T' => {
-                                                            let diff_value = serde_urlencoded::from_str(&value[2..]).unwrap_or_else(|_| value[2..].to_string());
-                                                            if let Some(field_name) = subscription_fields.and_then(|fields| fields.get(field_index)) {
-                                                                if let Some(prev_value) = field_map.get(field_name).and_then(|v| v.as_ref()) {
-                                                                    let new_value = match command {
-                                                                        'P' => {
-                                                                            // Apply JSON Patch
-                                                                            let patch: serde_json::Value = serde_json::from_str(&diff_value).unwrap_or(serde_json::Value::Null);
-                                                                            let mut prev_json: serde_json::Value = serde_json::from_str(prev_value).unwrap_or(serde_json::Value::Null);
-                                                                            let patch_operations: Vec<json_patch::PatchOperation> = serde_json::from_value(patch).unwrap_or_default();
-                                                                            let _ = json_patch::patch(&mut prev_json, &patch_operations);
-                                                                            prev_json.to_string()
-                                                                        }
-                                                                        'T' => {
-                                                                            // Apply TLCP-diff
-                                                                            //tlcp_diff::apply_diff(prev_value, &diff_value).unwrap_or_else(|_| prev_value.to_string())
-                                                                            unimplemented!("Implement TLCP-diff");
-                                                                        }
-                                                                        _ => unreachable!(),
-                                                                    };
-                                                                    field_map.insert(field_name.to_string(), Some(new_value.to_string()));
-                                                                }
-                                                            }
-                                                            field_index += 1;
-                                                        }
-                                                        _ => {
-                                                            let decoded_value = serde_urlencoded::from_str(value).unwrap_or_else(|_| value.to_string());
-                                                            if let Some(field_name) = subscription_fields.and_then(|fields| fields.get(field_index)) {
-                                                                field_map.insert(field_name.to_string(), Some(decoded_value));
-                                                            }
-                                                            field_index += 1;
-                                                        }
-                                                    }
-                                                }
-                                                _ => {
-                                                    let decoded_value = serde_urlencoded::from_str(value).unwrap_or_else(|_| value.to_string());
-                                                    if let Some(field_name) = subscription_fields.and_then(|fields| fields.get(field_index)) {
-                                                        field_map.insert(field_name.to_string(), Some(decoded_value));
-                                                    }
-                                                    field_index += 1;
-                                                }
-                                            }
-                                        }
-
-                                        // Store only item_update's changed fields.
-                                        let changed_fields: HashMap<String, String> = field_map.iter()
-                                            .filter_map(|(k, v)| {
-                                                if let Some(v) = v {
-                                                    Some((k.clone(), v.clone()))
-                                                } else {
-                                                    None
-                                                }
-                                            })
-                                            .collect();
-
-                                        //
-                                        // Take the proper item_update from item_updates and update it with changed fields.
-                                        // If the item_update doesn't exist yet, create a new one.
-                                        //
-                                        let current_item_update: ItemUpdate;
-                                        match subscription_item_updates.get_mut(&(subscription_index)) {
-                                            Some(item_updates) => match item_updates.get_mut(&(item_index)) {
-                                                Some(item_update) => {
-                                                    //
-                                                    // Iterate changed_fields and update existing item_update.fields assigning the new values.
-                                                    //
-                                                    for (field_name, new_value) in &changed_fields {
-                                                        if item_update.fields.contains_key(field_name) {
-                                                            item_update.fields.insert((*field_name).clone(), Some(new_value.clone()));
-                                                        }
-                                                    }
-                                                    item_update.changed_fields = changed_fields;
-                                                    item_update.is_snapshot = is_snapshot;
-                                                    current_item_update = item_update.clone();
-                                                },
-                                                None => {
-                                                    // Create a new item_update and add it to item_updates.
-                                                    let item_update = ItemUpdate {
-                                                        item_name: item.cloned(),
-                                                        item_pos: item_index,
-                                                        fields: field_map,
-                                                        changed_fields: changed_fields,
-                                                        is_snapshot: is_snapshot,
-                                                    };
-                                                    current_item_update = item_update.clone();
-                                                    item_updates.insert(item_index, item_update);
-                                                }
-                                            },
-                                            None => {
-                                                // Create a new item_update and add it to item_updates.
-                                                let item_update = ItemUpdate {
-                                                    item_name: item.cloned(),
-                                                    item_pos: item_index,
-                                                    fields: field_map,
-                                                    changed_fields: changed_fields,
-                                                    is_snapshot: is_snapshot,
-                                                };
-                                                current_item_update = item_update.clone();
-                                                let mut item_updates = HashMap::new();
-                                                item_updates.insert(item_index, item_update);
-                                                subscription_item_updates.insert(subscription_index, item_updates);
-                                            }
-                                        };
-
-                                        // Get mutable subscription listeners directly.
-                                        let subscription_listeners = subscription.get_listeners();
-
-                                        // Iterate subscription listeners and call on_item_update for each listener.
-                                        for listener in subscription_listeners {
-                                            listener.on_item_update(&current_item_update);
-                                        }
-                                    }
-                                    //
-                                    // Connection confirmation from server.
-                                    //
-                                    "wsok" => {
-                                        self.make_log( Level::INFO, &format!("Connection confirmed by server: '{}'", clean_text) );
-                                        //
-                                        // Request session creation.
-                                        //
-                                        let ls_adapter_set = match self.connection_details.get_adapter_set() {
-                                            Some(adapter_set) => adapter_set,
-                                            None => {
-                                                return Err(Box::new(IllegalStateException::new(
-                                                    "No adapter set found in connection details.",
-                                                )));
-                                            },
-                                        };
-                                        let ls_send_sync = self.connection_options.get_send_sync().to_string();
-                                        let mut params: Vec<(&str, &str)> = vec![
-                                            ("LS_adapter_set", &ls_adapter_set),
-                                            ("LS_cid", "mgQkwtwdysogQz2BJ4Ji kOj2Bg"),
-                                            ("LS_send_sync", &ls_send_sync),
-                                        ];
-                                        if let Some(user) = &self.connection_details.get_user() {
-                                            params.push(("LS_user", user));
-                                        }
-                                        if let Some(password) = &self.connection_details.get_password() {
-                                            params.push(("LS_password", password));
-                                        }
-                                        params.push(("LS_protocol", Self::TLCP_VERSION));
-                                        let encoded_params = serde_urlencoded::to_string(&params)?;
-                                        write_stream
-                                            .send(Message::Text(format!("create_session\r\n{}\n", encoded_params)))
-                                            .await?;
-                                        self.make_log( Level::DEBUG, &format!("Sent create session request: '{}'", encoded_params) );
-                                    },
-                                    unexpected_message => {
-                                        return Err(Box::new(std::io::Error::new(
-                                            std::io::ErrorKind::InvalidData,
-                                            format!(
-                                                "Unexpected message received from server: '{:?}'",
-                                                unexpected_message
-                                            ),
-                                        )));
-                                    },
-                                }
-                            }
-                        },
-                        Some(Ok(non_text_message)) => {
-                            return Err(Box::new(std::io::Error::new(
-                                std::io::ErrorKind::InvalidData,
-                                format!(
-                                    "Unexpected non-text message from server: {:?}",
-                                    non_text_message
-                                ),
-                            )));
-                        },
-                        Some(Err(err)) => {
-                            return Err(Box::new(std::io::Error::new(
-                                std::io::ErrorKind::InvalidData,
-                                format!("Error reading message from server: {}", err),
-                            )));
-                        },
-                        None => {
-                            self.make_log( Level::DEBUG, "No more messages from server" );
-                            break;
-                        },
-                    }
-                },
-                _ = shutdown_signal.notified() => {
-                    self.make_log( Level::INFO, &format!("Received shutdown signal") );
-                    break;
-                },
-            }
+    /// Resolves the parameters of the `create_session` request from the current connection
+    /// details and options.
+    fn build_create_session_params(&self) -> Result<Vec<(&'static str, String)>, Box<dyn Error>> {
+        let mut params = Vec::new();
+        // Without an adapter set, the server uses its default one.
+        if let Some(adapter_set) = self.connection_details.get_adapter_set() {
+            params.push(("LS_adapter_set", adapter_set.clone()));
+        }
+        params.push(("LS_cid", "mgQkwtwdysogQz2BJ4Ji kOj2Bg".to_string()));
+        params.push((
+            "LS_send_sync",
+            self.connection_options.get_send_sync().to_string(),
+        ));
+        if let Some(user) = self.connection_details.get_user() {
+            params.push(("LS_user", user.clone()));
+        }
+        if let Some(password) = self.connection_details.get_password() {
+            params.push(("LS_password", password.expose_secret().to_string()));
         }
 
-        Ok(())
+        Ok(params)
     }
 
     /// Operation method that requests to close the Session opened against the configured Lightstreamer
@@ -765,20 +862,55 @@ impl LightstreamerClient {
     /// When `disconnect()` is called, the "Stream-Sense" mechanism is stopped.
     ///
     /// Note that active `Subscription` instances, associated with this `LightstreamerClient` instance,
-    /// are preserved to be re-subscribed to on future Sessions.
+    /// are preserved to be re-subscribed to on future Sessions: they are notified through
+    /// `SubscriptionListener.onUnsubscription()` and subscribed to again by the next `connect()`.
     ///
-    /// Note that the request to disconnect is accomplished by the client in a separate thread; this
-    /// means that an invocation to `getStatus()` right after `disconnect()` might not reflect the
-    /// change yet.
+    /// The returned future completes only once the session has been destroyed on the Server,
+    /// the socket has been closed and the session task has terminated, so no background work is
+    /// left when it resolves and `getStatus()` reports "DISCONNECTED".
     ///
-    /// When the request to disconnect is finally being executed, if the status of the client is
-    /// "DISCONNECTED", then nothing will be done.
+    /// If the status of the client is already "DISCONNECTED", then nothing will be done.
     ///
     /// See also `connect()`
     #[instrument]
-    pub async fn disconnect(&mut self) {
-        // Implementation for disconnect
-        self.make_log( Level::INFO, "Disconnecting from Lightstreamer server" );
+    pub async fn disconnect(&self) {
+        let session_task = self.session_task.lock().unwrap().take();
+        if let Some(session_task) = session_task {
+            self.make_log(Level::INFO, "Disconnecting from Lightstreamer server");
+            session_task.shutdown_signal.notify_one();
+            if let Err(err) = session_task.handle.join().await {
+                self.make_log(
+                    Level::ERROR,
+                    &format!("Session task terminated abnormally: {}", err),
+                );
+                // The task couldn't reset the status, e.g. because its runtime was shut down.
+                set_status(
+                    &self.status,
+                    &self.dispatcher,
+                    &self.history,
+                    self.logging,
+                    ClientStatus::Disconnected(DisconnectionType::None),
+                );
+            }
+        }
+    }
+
+    /// Operation method that closes the Session like `disconnect()`, then waits until every task
+    /// started by the library for this client has terminated: besides the session task, which
+    /// owns the connection and its timers, the dispatch task, once all the notifications queued so
+    /// far have been delivered to the listeners, and the workers serving pooled listeners.
+    ///
+    /// This is useful for a clean teardown of tests and for process shutdown, when nothing should
+    /// be left running in the background, nor any listener be invoked, once the future resolves.
+    ///
+    /// The client can still be used afterwards: the dispatching of the notifications resumes with
+    /// the next call to `connect()`.
+    ///
+    /// See also `disconnect()`
+    #[instrument]
+    pub async fn disconnect_and_wait(&self) {
+        self.disconnect().await;
+        self.dispatcher.join().await;
     }
 
     /// Static inquiry method that can be used to share cookies between connections to the Server
@@ -797,9 +929,15 @@ impl LightstreamerClient {
     ///
     /// A list with the various cookies that can be sent in a HTTP request for the specified URI.
     /// If a `None` URI was supplied, all available non-expired cookies will be returned.
-    pub fn get_cookies(_uri: Option<&str>) -> Cookie {
-        // Implementation for get_cookies
-        unimplemented!()
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if `uri` is not a valid URI.
+    pub fn get_cookies(
+        uri: Option<&str>,
+    ) -> Result<Vec<Cookie<'static>>, IllegalArgumentException> {
+        let url = uri.map(cookies::parse_cookie_uri).transpose()?;
+        Ok(cookies::get_cookies(url.as_ref()))
     }
 
     /// Returns a list containing the `ClientListener` instances that were added to this client.
@@ -809,8 +947,8 @@ impl LightstreamerClient {
     /// A list containing the listeners that were added to this client.
     ///
     /// See also `addListener()`
-    pub fn get_listeners(&self) -> &Vec<Box<dyn ClientListener>> {
-        &self.listeners
+    pub fn get_listeners(&self) -> MutexGuard<'_, ClientListeners> {
+        self.listeners.lock().unwrap()
     }
 
     /// Inquiry method that gets the current client status and transport (when applicable).
@@ -835,8 +973,147 @@ impl LightstreamerClient {
     /// - `"DISCONNECTED"`: no connection is currently active.
     ///
     /// See also `ClientListener.onStatusChange()`
-    pub fn get_status(&self) -> &ClientStatus {
-        &self.status
+    pub fn get_status(&self) -> ClientStatus {
+        self.status.lock().unwrap().clone()
+    }
+
+    /// Inquiry method that gets a snapshot of the health counters of this `LightstreamerClient`:
+    /// updates received, bytes exchanged, messages sent, reconnections, current status and last
+    /// round-trip time. Counters are cumulative over the whole life of the client.
+    ///
+    /// # Returns
+    ///
+    /// A `ClientMetrics` snapshot, which is not updated afterwards.
+    ///
+    /// See also `getStatus()`
+    pub fn get_metrics(&self) -> ClientMetrics {
+        self.metrics.snapshot(self.get_status())
+    }
+
+    /// Inquiry method that gets the current estimate of the offset between the clocks of the
+    /// Server and of the client, which can be used to align the server timestamps carried by the
+    /// updates with the local time. The estimate is measured on the handshake of each connection
+    /// and kept aligned through the `SYNC` notifications of the stream.
+    ///
+    /// # Returns
+    ///
+    /// The current `ClockSkew` estimate, or `None` if no connection was established yet or the
+    /// Server didn't supply its time through the `Date` header of the handshake response.
+    ///
+    /// See also `ClientListener.onServerSync()`
+    pub fn get_clock_skew(&self) -> Option<ClockSkew> {
+        self.session_info.lock().unwrap().clock_skew
+    }
+
+    /// Inquiry method that describes the listener call currently blocking the delivery of the
+    /// notifications of this `LightstreamerClient`, if the dispatch watchdog found it stalled.
+    /// Unlike a notification, which can't be delivered while a listener is blocked, this can be
+    /// polled from any task, e.g. by a health check.
+    ///
+    /// # Returns
+    ///
+    /// The `DispatchStall` describing the stalled call, or `None` if no call is stalled or the
+    /// watchdog is disabled.
+    ///
+    /// See also `ConnectionOptions.setDispatchWatchdogTimeout()`
+    pub fn get_dispatch_stall(&self) -> Option<DispatchStall> {
+        self.dispatcher.stall()
+    }
+
+    /// Inquiry method that gets the details about the closure of the last WebSocket connection:
+    /// who closed it, the close code and reason received from the peer, and whether the close
+    /// handshake was completed or the connection was aborted.
+    ///
+    /// # Returns
+    ///
+    /// The `DisconnectInfo` of the last connection closed, or `None` if no connection was closed
+    /// yet.
+    ///
+    /// See also `ClientListener.onDisconnect()`
+    pub fn get_last_disconnect_info(&self) -> Option<DisconnectInfo> {
+        self.session_info.lock().unwrap().last_disconnect.clone()
+    }
+
+    /// Inquiry method that describes the connection currently used by this `LightstreamerClient`:
+    /// the transport actually in use, the negotiated protocol version, the address of the Server
+    /// and the instance serving the session, the session ID and the time the connection was
+    /// opened.
+    ///
+    /// # Returns
+    ///
+    /// A `ConnectionInfo` snapshot, or `None` if no connection is currently bound to a session,
+    /// i.e. if the status is not "CONNECTED:*" or "STALLED".
+    ///
+    /// See also `getStatus()`
+    pub fn connection_info(&self) -> Option<ConnectionInfo> {
+        self.session_info.lock().unwrap().connection_info.clone()
+    }
+
+    /// Inquiry method that gets the recent significant events of this `LightstreamerClient`: its
+    /// status changes, its diagnostics at the INFO level and above, such as errors, retries and
+    /// session lifecycle events, and periodic summaries of the frames exchanged with the Server.
+    ///
+    /// The events are recorded whether or not logging is enabled, up to the number configured
+    /// through `ConnectionOptions.setEventHistorySize()`, so that the last minutes of activity of
+    /// the client can be inspected when something goes wrong in production.
+    ///
+    /// # Returns
+    ///
+    /// The recent events, oldest first, which are not updated afterwards.
+    ///
+    /// See also `debugState()`
+    pub fn recent_events(&self) -> Vec<ClientEvent> {
+        self.history.events()
+    }
+
+    /// Inquiry method that builds a report on the internal state of this `LightstreamerClient`:
+    /// status, session, transport, pending requests and the state of each subscription, with the
+    /// number of updates received and the time elapsed since the last one.
+    ///
+    /// The report can be printed through its `Display` implementation to be attached to bug
+    /// reports, or inspected to drive admin endpoints.
+    ///
+    /// # Returns
+    ///
+    /// A `ClientDebugState` report, which is not updated afterwards.
+    ///
+    /// See also `getMetrics()`
+    pub fn debug_state(&self) -> ClientDebugState {
+        let status = self.get_status();
+        let transport = match &status {
+            ClientStatus::Connected(connection_type) => Some(connection_type.clone()),
+            _ => None,
+        };
+        let info = self.session_info.lock().unwrap();
+        // Subscription IDs of the subscriptions in the current server session, by key.
+        let subscription_ids: HashMap<usize, usize> = info
+            .active_subscriptions
+            .iter()
+            .map(|(&subscription_id, &key)| (key, subscription_id))
+            .collect();
+        let subscriptions = self
+            .subscriptions
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|subscription| {
+                let subscription_id = subscription
+                    .client_key()
+                    .and_then(|key| subscription_ids.get(&key));
+                subscription.debug_state(subscription_id.copied())
+            })
+            .collect();
+        ClientDebugState {
+            status,
+            server_address: self.connection_details.get_server_address().cloned(),
+            session_id: info.session_id.clone(),
+            transport,
+            queued_messages: self.messages.lock().unwrap().len(),
+            messages_awaiting_outcome: info.messages_awaiting_outcome,
+            queued_subscription_changes: self.subscription_changes.len(),
+            unacknowledged_requests: info.unacknowledged_requests,
+            subscriptions,
+        }
     }
 
     /// Inquiry method that returns a list containing all the `Subscription` instances that are
@@ -850,8 +1127,56 @@ impl LightstreamerClient {
     /// The list can be empty.
     ///
     /// See also `subscribe()`
-    pub fn get_subscriptions(&self) -> &Vec<Subscription> {
-        &self.subscriptions
+    pub fn get_subscriptions(&self) -> MutexGuard<'_, Vec<Subscription>> {
+        self.subscriptions.lock().unwrap()
+    }
+
+    /// Operation method that gives access to an "active" `Subscription` through the token returned
+    /// by `subscribe()`, so that its settings and listeners can be changed and its state inspected
+    /// while it is owned by this `LightstreamerClient`.
+    ///
+    /// The list of the "active" Subscriptions is locked while `action` runs, so `action` must not
+    /// call the subscription methods of this `LightstreamerClient`.
+    ///
+    /// # Parameters
+    ///
+    /// * `token`: The token returned by `subscribe()` for the "active" `Subscription`.
+    /// * `action`: The closure called with the `Subscription`.
+    ///
+    /// # Returns
+    ///
+    /// The value returned by `action`.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if the token doesn't identify an "active" `Subscription` of
+    ///   this `LightstreamerClient`, e.g. because it was unsubscribed from already.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use lightstreamer_client::ls_client::LightstreamerClient;
+    /// # use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+    /// let client = LightstreamerClient::new(None, None, None, None).unwrap();
+    /// let subscription =
+    ///     Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["last_price"]).unwrap();
+    /// let token = client.subscribe(subscription);
+    /// client
+    ///     .with_subscription(token, |subscription| subscription.set_requested_max_frequency(Some(1.0)))
+    ///     .unwrap()
+    ///     .unwrap();
+    /// ```
+    ///
+    /// See also `subscribe()`
+    pub fn with_subscription<R>(
+        &self,
+        token: SubscriptionToken,
+        action: impl FnOnce(&mut Subscription) -> R,
+    ) -> Result<R, IllegalArgumentException> {
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        let subscription = find_subscription_mut(&mut subscriptions, token.0)
+            .ok_or_else(|| IllegalArgumentException::new("No active subscription for the token"))?;
+        Ok(action(subscription))
     }
 
     /// Creates a new instance of `LightstreamerClient`.
@@ -878,16 +1203,19 @@ impl LightstreamerClient {
     ///
     /// # Example
     /// ```
+    /// use lightstreamer_client::ls_client::LightstreamerClient;
+    ///
     /// // Example usage of `new` to create a LightstreamerClient with specified server address and
     /// // adapter set.
     /// let server_address = Some("http://myserver.com");
     /// let adapter_set = Some("MY_ADAPTER_SET");
-    /// let ls_client = LightstreamerClient::new(server_address, adapter_set);
+    /// let ls_client = LightstreamerClient::new(server_address, adapter_set, None, None);
     ///
     /// assert!(ls_client.is_ok());
     /// if let Ok(client) = ls_client {
-    ///     assert_eq!(client.server_address.unwrap(), "http://myserver.com".to_string());
-    ///     assert_eq!(client.adapter_set.unwrap(), "MY_ADAPTER_SET".to_string());
+    ///     let details = &client.connection_details;
+    ///     assert_eq!(details.get_server_address().unwrap(), "http://myserver.com");
+    ///     assert_eq!(details.get_adapter_set().unwrap(), "MY_ADAPTER_SET");
     /// }
     /// ```
     pub fn new(
@@ -900,31 +1228,64 @@ impl LightstreamerClient {
             ConnectionDetails::new(server_address, adapter_set, username, password)?;
         let connection_options = ConnectionOptions::default();
 
+        let listeners = Arc::new(Mutex::new(ClientListeners::default()));
+        let subscriptions = Arc::new(Mutex::new(Vec::new()));
+        let metrics = Arc::new(MetricsRecorder::default());
+        let dispatcher = EventDispatcher::new(
+            Arc::clone(&listeners),
+            Arc::clone(&subscriptions),
+            Arc::clone(&metrics),
+        );
         Ok(LightstreamerClient {
             server_address: server_address.map(|s| s.to_string()),
             adapter_set: adapter_set.map(|s| s.to_string()),
             connection_details,
             connection_options,
-            listeners: Vec::new(),
-            subscriptions: Vec::new(),
-            status: ClientStatus::Disconnected(DisconnectionType::WillRetry),
-            logging: LogType::StdLogs,
+            listeners,
+            subscriptions,
+            next_subscription_key: AtomicUsize::new(0),
+            status: Arc::new(Mutex::new(ClientStatus::Disconnected(
+                DisconnectionType::None,
+            ))),
+            logging: LogType::default(),
+            session_task: Mutex::new(None),
+            messages: Arc::new(Mutex::new(VecDeque::new())),
+            message_signal: Arc::new(Notify::new()),
+            subscription_changes: SubscriptionChanges::default(),
+            metrics,
+            session_info: Arc::new(Mutex::new(SessionInfo::default())),
+            history: Arc::new(EventHistory::new(DEFAULT_EVENT_HISTORY_SIZE)),
+            dispatcher,
         })
     }
 
     /// Removes a listener from the `LightstreamerClient` instance so that it will not receive
     /// events anymore.
     ///
-    /// A listener can be removed at any time.
+    /// A listener can be removed at any time. Once removed, it receives
+    /// `ClientListener.onListenEnd()` as its last event. A listener that was already removed
+    /// because it expired (see `ClientListener.isExpired()`) can't be removed again.
     ///
     /// # Parameters
     ///
-    /// * `listener`: The listener to be removed.
+    /// * `token`: The token returned by `addListener()` for the listener to be removed.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if the token doesn't identify a listener of this client,
+    ///   for instance because it was already removed.
     ///
     /// See also `addListener()`
-    pub fn remove_listener(&mut self, _listener: Box<dyn ClientListener>) {
-        unimplemented!("Implement mechanism to remove listener from LightstreamerClient");
-        //self.listeners.remove(&listener);
+    pub fn remove_listener(&self, token: ListenerToken) -> Result<(), IllegalArgumentException> {
+        let listener = self
+            .listeners
+            .lock()
+            .unwrap()
+            .remove(token)
+            .ok_or_else(|| IllegalArgumentException::new("No listener for the token"))?;
+        self.dispatcher
+            .notify_removed_client_listener(self.logging, listener);
+        Ok(())
     }
 
     /// Operation method that sends a message to the Server. The message is interpreted and handled
@@ -975,9 +1336,13 @@ impl LightstreamerClient {
     /// status is "DISCONNECTED*", the message will be abandoned and the `ClientMessageListener.onAbort()`
     /// event will be fired.
     ///
-    /// Note that, in any case, as soon as the status switches again to "DISCONNECTED*", any message
-    /// still pending is aborted, including messages that were queued with the `enqueueWhileDisconnected`
-    /// flag set to `true`.
+    /// Note that, when the connection is lost, the messages still pending are handled according to
+    /// `ConnectionOptions.setMessageRecoveryPolicy()`: by default, the messages not yet acknowledged
+    /// by the Server are sent again, with the same progressive number, once the session is
+    /// recovered, and aborted if a new session has to be created; messages not sent yet, including
+    /// messages that were queued with the `enqueueWhileDisconnected` flag set to `true`, wait for
+    /// the next session. Any message still pending is aborted when the client is disconnected
+    /// through `disconnect()` or gives up reconnecting.
     ///
     /// Also note that forwarding of the message to the server is made in a separate thread, hence,
     /// if a message is sent while the connection is active, it could be aborted because of a subsequent
@@ -1003,72 +1368,78 @@ impl LightstreamerClient {
     ///   status when the provided message is handled, then the message is not aborted right away but
     ///   is queued waiting for a new session. Note that the message can still be aborted later when
     ///   a new session is established.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalStateException`: if the status is "DISCONNECTED*" and `enqueue_while_disconnected`
+    ///   is `false`; the message is abandoned and `ClientMessageListener.onAbort()` is fired anyway.
     pub fn send_message(
-        &mut self,
+        &self,
         message: &str,
         sequence: Option<&str>,
-        _delay_timeout: Option<u64>,
+        delay_timeout: Option<u64>,
         listener: Option<Box<dyn ClientMessageListener>>,
         enqueue_while_disconnected: bool,
-    ) {
-        let _sequence = sequence.unwrap_or_else(|| "UNORDERED_MESSAGES");
-
-        // Handle the message based on the current connection status
-        match &self.status {
-            ClientStatus::Connected(_connection_type) => {
-                // Send the message to the server in a separate thread
-                // ...
-            }
-            ClientStatus::Disconnected(_disconnection_type) => {
-                if enqueue_while_disconnected {
-                    // Enqueue the message to be sent when a connection is available
-                    // ...
-                } else {
-                    // Abort the message and notify the listener
-                    if let Some(listener) = listener {
-                        listener.on_abort(message, false);
-                    }
-                }
-            }
-            _ => {
-                // Enqueue the message to be sent when a connection is available
-                // ...
+    ) -> Result<(), IllegalStateException> {
+        let sequence = sequence.unwrap_or("UNORDERED_MESSAGES");
+
+        // Abort the message right away if there is no connection and it can't be queued.
+        if let ClientStatus::Disconnected(_) = self.get_status() {
+            if !enqueue_while_disconnected {
+                let pending_message = PendingMessage {
+                    message: message.to_string(),
+                    sequence: sequence.to_string(),
+                    delay_timeout,
+                    listener,
+                    prog: None,
+                };
+                pending_message.abort(&self.dispatcher, self.logging, false);
+                return Err(IllegalStateException::new(
+                    "The client is disconnected and the message was not to be enqueued.",
+                ));
             }
         }
-        unimplemented!("Complete mechanism to send message to LightstreamerClient.");
+        // Queue the message: the session task sends it as soon as a session is available.
+        self.messages.lock().unwrap().push_back(PendingMessage {
+            message: message.to_string(),
+            sequence: sequence.to_string(),
+            delay_timeout,
+            listener,
+            prog: None,
+        });
+        self.message_signal.notify_one();
+        Ok(())
     }
 
     /// Static method that permits to configure the logging system used by the library. The logging
     /// system must respect the `LoggerProvider` interface. A custom class can be used to wrap any
     /// third-party logging system.
     ///
-    /// If no logging system is specified, all the generated log is discarded.
-    ///
-    /// The following categories are available to be consumed:
+    /// The provider is asked for a `Logger` for each of the following categories as soon as it's
+    /// installed:
     ///
-    /// - `lightstreamer.stream`: logs socket activity on Lightstreamer Server connections; at INFO
-    ///   level, socket operations are logged; at DEBUG level, read/write data exchange is logged.
+    /// - `lightstreamer.connections`: logs the connection lifecycle; at INFO level, connections,
+    ///   session creation and recovery, retries and closures are logged; at DEBUG level, their
+    ///   details are logged.
     /// - `lightstreamer.protocol`: logs requests to Lightstreamer Server and Server answers; at INFO
-    ///   level, requests are logged; at DEBUG level, request details and events from the Server are logged.
-    /// - `lightstreamer.session`: logs Server Session lifecycle events; at INFO level, lifecycle events
-    ///   are logged; at DEBUG level, lifecycle event details are logged.
-    /// - `lightstreamer.subscriptions`: logs subscription requests received by the clients and the related
-    ///   updates; at WARN level, alert events from the Server are logged; at INFO level, subscriptions
-    ///   and unsubscriptions are logged; at DEBUG level, requests batching and update details are logged.
-    /// - `lightstreamer.actions`: logs settings / API calls.
+    ///   level, notifications are logged; at DEBUG level, request details and probes are logged.
+    /// - `lightstreamer.subscriptions`: logs subscription requests and the related events; at INFO
+    ///   level, subscriptions and unsubscriptions are logged; at DEBUG level, snapshot and lost
+    ///   updates events are logged.
+    /// - `lightstreamer.messages`: logs the messages sent to the Server and their outcome.
+    ///
+    /// If no logging system is specified, the log is emitted according to the `LogType` of each
+    /// `LightstreamerClient` instance.
     ///
     /// # Parameters
     ///
     /// * `provider`: A `LoggerProvider` instance that will be used to generate log messages by the
-    ///   library classes.
-    pub fn set_logger_provider() {
-        unimplemented!("Implement mechanism to set logger provider for LightstreamerClient.");
-    }
-    /*
-    pub fn set_logger_provider(provider: LoggerProvider) {
-        // Implementation for set_logger_provider
+    ///   library classes, or `None` to remove the current one.
+    ///
+    /// See also `setLoggingType()`
+    pub fn set_logger_provider(provider: Option<Arc<dyn LoggerProvider>>) {
+        logger::set_logger_provider(provider);
     }
-    */
 
     /// Provides a mean to control the way TLS certificates are evaluated, with the possibility to
     /// accept untrusted ones.
@@ -1110,7 +1481,9 @@ impl LightstreamerClient {
     /// immediately enters the "active" state.
     ///
     /// Once "active", a `Subscription` instance cannot be provided again to a `LightstreamerClient`
-    /// unless it is first removed from the "active" state through a call to `unsubscribe()`.
+    /// unless it is first removed from the "active" state through a call to `unsubscribe()`. Since
+    /// the `Subscription` is moved into the client, this rule is enforced at compile time: an
+    /// active `Subscription` can't be given to a second client either.
     ///
     /// Also note that forwarding of the subscription to the server is made in a separate thread.
     ///
@@ -1122,10 +1495,106 @@ impl LightstreamerClient {
     /// * `subscription`: A `Subscription` object, carrying all the information needed to process real-time
     ///   values.
     ///
+    /// # Returns
+    ///
+    /// The token identifying the `Subscription`, to be passed to `unsubscribe()`.
+    ///
     /// See also `unsubscribe()`
-    pub fn subscribe(&mut self, subscription: Subscription) {
-        self.subscriptions.push(subscription);
-        // Implementation for subscribe
+    pub fn subscribe(&self, mut subscription: Subscription) -> SubscriptionToken {
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        // Assigned under the lock, so that the list stays sorted by key.
+        let key = self.next_subscription_key.fetch_add(1, Ordering::Relaxed);
+        subscription.activate(key, self.subscription_changes.clone());
+        subscriptions.push(subscription);
+        self.subscription_changes.push(SubscriptionChange::Add(key));
+        SubscriptionToken(key)
+    }
+
+    /// Operation method that replaces the "Item List" of a `Subscription` in the "active" state,
+    /// preserving its listeners and settings.
+    ///
+    /// If a session is running, the `Subscription` is unsubscribed from and subscribed to again
+    /// with the new "Item List" at once; the values received for the old items are discarded.
+    ///
+    /// # Parameters
+    ///
+    /// * `token`: The token returned by `subscribe()` for the "active" `Subscription`.
+    /// * `items`: the new "Item List", as any collection of strings.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if the token doesn't identify an "active" `Subscription` of
+    ///   this `LightstreamerClient`, e.g. because it was unsubscribed from already, or if any of
+    ///   the item names is not valid.
+    ///
+    /// See also `Subscription.setItems()`
+    pub fn update_items<I>(
+        &self,
+        token: SubscriptionToken,
+        items: I,
+    ) -> Result<(), IllegalArgumentException>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
+        let items = items
+            .into_iter()
+            .map(|item| item.as_ref().to_string())
+            .collect();
+        self.update_subscription(token, |subscription| subscription.replace_items(items))
+    }
+
+    /// Operation method that replaces the "Field List" of a `Subscription` in the "active" state,
+    /// preserving its listeners and settings.
+    ///
+    /// If a session is running, the `Subscription` is unsubscribed from and subscribed to again
+    /// with the new "Field List" at once; the values received for the old fields are discarded.
+    ///
+    /// # Parameters
+    ///
+    /// * `token`: The token returned by `subscribe()` for the "active" `Subscription`.
+    /// * `fields`: the new "Field List", as any collection of strings.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if the token doesn't identify an "active" `Subscription` of
+    ///   this `LightstreamerClient`, e.g. because it was unsubscribed from already, or if any of
+    ///   the field names is not valid.
+    ///
+    /// See also `Subscription.setFields()`
+    pub fn update_fields<F>(
+        &self,
+        token: SubscriptionToken,
+        fields: F,
+    ) -> Result<(), IllegalArgumentException>
+    where
+        F: IntoIterator,
+        F::Item: AsRef<str>,
+    {
+        let fields = fields
+            .into_iter()
+            .map(|field| field.as_ref().to_string())
+            .collect();
+        self.update_subscription(token, |subscription| subscription.replace_fields(fields))
+    }
+
+    /// Applies a change to the `Subscription` identified by the token and requests it to be
+    /// subscribed to again.
+    fn update_subscription(
+        &self,
+        token: SubscriptionToken,
+        change: impl FnOnce(&mut Subscription) -> Result<(), String>,
+    ) -> Result<(), IllegalArgumentException> {
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        let position = subscription_position(&subscriptions, token.0)
+            .ok_or_else(|| IllegalArgumentException::new("No active subscription for the token"))?;
+        let subscription = &mut subscriptions[position];
+        change(subscription).map_err(|err| IllegalArgumentException::new(&err))?;
+        if let Some(key) = subscription.client_key() {
+            self.subscription_changes
+                .push(SubscriptionChange::Resubscribe(key));
+        }
+        Ok(())
     }
 
     /// Operation method that removes a `Subscription` that is currently in the "active" state.
@@ -1134,36 +1603,52 @@ impl LightstreamerClient {
     /// items is requested to Lightstreamer Server.
     ///
     /// Subscription can be unsubscribed from at any time. Once done the `Subscription` immediately
-    /// exits the "active" state.
+    /// exits the "active" state and is removed from the list returned by `get_subscriptions()`.
     ///
     /// Note that forwarding of the unsubscription to the server is made in a separate thread.
     ///
-    /// The unsubscription will be notified through a `SubscriptionListener.onUnsubscription()` event.
+    /// If the `Subscription` was subscribed to, the unsubscription will be notified through a
+    /// `SubscriptionListener.onUnsubscription()` event, after which the `Subscription` is dropped.
     ///
     /// # Parameters
     ///
-    /// * `subscription`: An "active" `Subscription` object that was activated by this `LightstreamerClient`
-    ///   instance.
-    pub fn unsubscribe(&mut self, _subscription: Subscription) {
-        unimplemented!("Implement mechanism to unsubscribe from LightstreamerClient.");
-    }
-    /*
-    pub fn unsubscribe(&mut self, subscription: &Subscription) {
-        if let Some(index) = self.subscriptions.iter().position(|s| s == subscription) {
-            self.subscriptions.remove(index);
-            // Implementation for unsubscribe
+    /// * `token`: The token returned by `subscribe()` for the "active" `Subscription`.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if the token doesn't identify an "active" `Subscription` of
+    ///   this `LightstreamerClient`, e.g. because it was unsubscribed from already.
+    ///
+    /// See also `subscribe()`
+    pub fn unsubscribe(&self, token: SubscriptionToken) -> Result<(), IllegalArgumentException> {
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        let position = subscription_position(&subscriptions, token.0)
+            .ok_or_else(|| IllegalArgumentException::new("No active subscription for the token"))?;
+        let mut subscription = subscriptions.remove(position);
+        // Queued under the lock, so that the session can't subscribe to it again in between.
+        self.subscription_changes
+            .push(SubscriptionChange::Remove(token.0));
+        drop(subscriptions);
+        let subscribed = subscription.is_subscribed();
+        subscription.deactivate();
+        if subscribed {
+            self.dispatcher
+                .notify_removed_subscription(self.logging, subscription);
         }
+        Ok(())
     }
-    */
 
     /// Method setting enum for the logging of this instance.
     ///
-    /// Default logging type is StdLogs, corresponding to `stdout`
+    /// Default logging type is `LogCrateLogs` with the `log` feature enabled, `TracingLogs`
+    /// otherwise.
     ///
     /// `LightstreamerClient` has methods for logging that are compatible with the `Tracing` crate.
     /// Enabling logging for the `Tracing` crate requires implementation of a tracing subscriber
     /// and its configuration and formatting.
     ///
+    /// The logging type is not used while a `LoggerProvider` is installed.
+    ///
     /// # Parameters
     ///
     /// * `logging`: An enum declaring the logging type of this `LightstreamerClient` instance.
@@ -1178,32 +1663,49 @@ impl LightstreamerClient {
     /// # Parameters
     ///
     /// * `loglevel` Enum determining use of stdout or Tracing subscriber.
-    pub fn make_log(&mut self, loglevel: Level, log: &str) {
-        match self.logging {
-            LogType::StdLogs => {
-                println!("{}", log);
-            }
-            LogType::TracingLogs => match loglevel {
-                Level::INFO => {
-                    info!(log);
-                }
-                Level::WARN => {
-                    warn!(log);
-                }
-                Level::ERROR => {
-                    error!(log);
-                }
-                Level::TRACE => {
-                    trace!(log);
-                }
-                Level::DEBUG => {
-                    debug!(log);
-                }
-            },
-        }
+    pub fn make_log(&self, loglevel: Level, log: &str) {
+        self.logging.log(LogCategory::Connections, loglevel, log);
+        self.history.record_log(loglevel, log);
+    }
+}
+
+/// Listener used by `LightstreamerClient.on_status_change()` to call a closure with each status.
+struct StatusChangeHandler<F>(Mutex<F>);
+
+impl<F> Debug for StatusChangeHandler<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("StatusChangeHandler")
+    }
+}
+
+impl<F: FnMut(&str) + Send> ClientListener for StatusChangeHandler<F> {
+    fn on_status_change(&self, status: &str) {
+        // A panic of the closure is caught by the library, so the closure can be called again.
+        (self.0.lock().unwrap_or_else(PoisonError::into_inner))(status);
+    }
+}
+
+/// Listener used by `LightstreamerClient.on_server_error()` to call a closure with each error.
+struct ServerErrorHandler<F>(Mutex<F>);
+
+impl<F> Debug for ServerErrorHandler<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("ServerErrorHandler")
     }
 }
 
+impl<F: FnMut(i32, &str) + Send> ClientListener for ServerErrorHandler<F> {
+    fn on_server_error(&self, code: i32, message: &str) {
+        (self.0.lock().unwrap_or_else(PoisonError::into_inner))(code, message);
+    }
+}
+
+/// Background task running a session, with the signal requesting it to terminate.
+struct SessionTask {
+    handle: TaskHandle,
+    shutdown_signal: Arc<Notify>,
+}
+
 /// The transport type to be used by the client.
 /// - WS: the Stream-Sense algorithm is enabled as in the `None` case but the client will
 ///   only use WebSocket based connections. If a connection over WebSocket is not possible
@@ -1223,7 +1725,7 @@ impl LightstreamerClient {
 /// - HTTP-POLLING: the Stream-Sense algorithm is disabled and the client will only connect
 ///   on Polling over HTTP. If Polling over HTTP is not possible because of the environment
 ///   the client will not connect at all.
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Transport {
     Ws,
     Http,
@@ -0,0 +1,153 @@
+//! Helpers to watch the health of a Lightstreamer Server through its internal MONITOR Data
+//! Adapter, which is available in every Adapter Set and publishes the server statistics as the
+//! fields of the `monitor_statistics` item.
+//!
+//! The functions of this module build correctly-shaped Subscriptions to that item, while
+//! `SessionStatistics` and `ThroughputStatistics` read the corresponding fields of its updates:
+//!
+//! ```
+//! use lightstreamer_client::item_update::ItemUpdate;
+//! use lightstreamer_client::monitor::{self, ThroughputStatistics};
+//! use lightstreamer_client::subscription_listener::SubscriptionListener;
+//! use lightstreamer_client::LightstreamerFields;
+//!
+//! struct ThroughputListener;
+//!
+//! impl SubscriptionListener for ThroughputListener {
+//!     fn on_item_update(&self, update: &ItemUpdate) {
+//!         if let Ok(stats) = ThroughputStatistics::from_item_update(update) {
+//!             println!("outbound: {:?} updates/s", stats.outbound_throughput);
+//!         }
+//!     }
+//! }
+//!
+//! let mut subscription = monitor::throughput_subscription();
+//! subscription.add_listener(Box::new(ThroughputListener));
+//! ```
+
+use crate::error::IllegalArgumentException;
+use crate::item_update::{parse_field, ItemUpdate, LightstreamerFields};
+use crate::subscription::{Snapshot, Subscription, SubscriptionMode};
+
+use std::error::Error;
+
+/// Name of the internal Data Adapter publishing the server statistics.
+pub const MONITOR_DATA_ADAPTER: &str = "MONITOR";
+
+/// Name of the item of the MONITOR Data Adapter carrying the server statistics.
+pub const STATISTICS_ITEM: &str = "monitor_statistics";
+
+/// Number of sessions currently open.
+pub const CURR_SESSIONS: &str = "CURR_SESSIONS";
+/// Maximum number of sessions open at the same time since the server started.
+pub const MAX_SESSIONS: &str = "MAX_SESSIONS";
+/// Number of items currently subscribed to by the sessions.
+pub const CURR_ITEMS: &str = "CURR_ITEMS";
+/// Events received from the Data Adapters per second.
+pub const INBOUND_THROUGHPUT: &str = "INBOUND_THROUGHPUT";
+/// Updates sent to the clients per second.
+pub const OUTBOUND_THROUGHPUT: &str = "OUTBOUND_THROUGHPUT";
+/// Maximum number of updates sent to the clients per second since the server started.
+pub const MAX_OUTBOUND_THROUGHPUT: &str = "MAX_OUTBOUND_THROUGHPUT";
+/// Bandwidth used towards the clients, in kilobits per second.
+pub const OUT_BANDWIDTH: &str = "OUT_BANDWIDTH";
+/// Maximum bandwidth used towards the clients since the server started, in kilobits per second.
+pub const MAX_OUT_BANDWIDTH: &str = "MAX_OUT_BANDWIDTH";
+
+/// Fields of the `monitor_statistics` item about the sessions, read by `SessionStatistics`.
+pub const SESSION_FIELDS: [&str; 3] = [CURR_SESSIONS, MAX_SESSIONS, CURR_ITEMS];
+
+/// Fields of the `monitor_statistics` item about the throughput, read by `ThroughputStatistics`.
+pub const THROUGHPUT_FIELDS: [&str; 5] = [
+    INBOUND_THROUGHPUT,
+    OUTBOUND_THROUGHPUT,
+    MAX_OUTBOUND_THROUGHPUT,
+    OUT_BANDWIDTH,
+    MAX_OUT_BANDWIDTH,
+];
+
+/// Creates a MERGE Subscription to the given fields of the `monitor_statistics` item of the
+/// MONITOR Data Adapter, requesting the snapshot so that the current values are received as soon
+/// as the Subscription is active.
+///
+/// # Parameters
+///
+/// * `fields`: names of the statistics to subscribe to.
+///
+/// # Errors
+///
+/// Returns an error if the Subscription can't be created, as documented for `Subscription::new()`.
+pub fn statistics_subscription<F>(fields: F) -> Result<Subscription, Box<dyn Error>>
+where
+    F: IntoIterator,
+    F::Item: AsRef<str>,
+{
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, STATISTICS_ITEM, fields)?;
+    subscription.set_data_adapter(Some(MONITOR_DATA_ADAPTER.to_string()))?;
+    subscription.set_requested_snapshot(Some(Snapshot::Yes))?;
+    Ok(subscription)
+}
+
+/// Creates a Subscription to the `SESSION_FIELDS` statistics, whose updates can be read through
+/// `SessionStatistics`. See also `statistics_subscription()`.
+pub fn sessions_subscription() -> Subscription {
+    statistics_subscription(SESSION_FIELDS).expect("valid MONITOR subscription")
+}
+
+/// Creates a Subscription to the `THROUGHPUT_FIELDS` statistics, whose updates can be read through
+/// `ThroughputStatistics`. See also `statistics_subscription()`.
+pub fn throughput_subscription() -> Subscription {
+    statistics_subscription(THROUGHPUT_FIELDS).expect("valid MONITOR subscription")
+}
+
+/// Session statistics of the server, read from an update of a `sessions_subscription()`. Fields
+/// with no value yet are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionStatistics {
+    /// Number of sessions currently open.
+    pub current_sessions: Option<u64>,
+    /// Maximum number of sessions open at the same time since the server started.
+    pub max_sessions: Option<u64>,
+    /// Number of items currently subscribed to by the sessions.
+    pub current_items: Option<u64>,
+}
+
+impl LightstreamerFields for SessionStatistics {
+    fn from_item_update(update: &ItemUpdate) -> Result<Self, IllegalArgumentException> {
+        Ok(SessionStatistics {
+            current_sessions: parse_field(update, CURR_SESSIONS)?,
+            max_sessions: parse_field(update, MAX_SESSIONS)?,
+            current_items: parse_field(update, CURR_ITEMS)?,
+        })
+    }
+}
+
+/// Throughput statistics of the server, read from an update of a `throughput_subscription()`.
+/// Fields with no value yet are `None`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ThroughputStatistics {
+    /// Events received from the Data Adapters per second.
+    pub inbound_throughput: Option<f64>,
+    /// Updates sent to the clients per second.
+    pub outbound_throughput: Option<f64>,
+    /// Maximum number of updates sent to the clients per second since the server started.
+    pub max_outbound_throughput: Option<f64>,
+    /// Bandwidth used towards the clients, in kilobits per second.
+    pub out_bandwidth: Option<f64>,
+    /// Maximum bandwidth used towards the clients since the server started, in kilobits per
+    /// second.
+    pub max_out_bandwidth: Option<f64>,
+}
+
+impl LightstreamerFields for ThroughputStatistics {
+    fn from_item_update(update: &ItemUpdate) -> Result<Self, IllegalArgumentException> {
+        Ok(ThroughputStatistics {
+            inbound_throughput: parse_field(update, INBOUND_THROUGHPUT)?,
+            outbound_throughput: parse_field(update, OUTBOUND_THROUGHPUT)?,
+            max_outbound_throughput: parse_field(update, MAX_OUTBOUND_THROUGHPUT)?,
+            out_bandwidth: parse_field(update, OUT_BANDWIDTH)?,
+            max_out_bandwidth: parse_field(update, MAX_OUT_BANDWIDTH)?,
+        })
+    }
+}
@@ -0,0 +1,27 @@
+//! The types needed by most applications, so that a single `use` line brings them into scope:
+//!
+//! ```
+//! use lightstreamer_client::prelude::*;
+//!
+//! let client = LightstreamerClient::new(
+//!     Some("http://push.lightstreamer.com/lightstreamer"),
+//!     Some("DEMO"),
+//!     None,
+//!     None,
+//! )
+//! .unwrap();
+//! let subscription = Subscription::new(
+//!     SubscriptionMode::Merge,
+//!     Some(["item1", "item2"]),
+//!     Some(["field1", "field2"]),
+//! )
+//! .unwrap();
+//! # let _ = (client, subscription);
+//! ```
+
+pub use crate::client_listener::ClientListener;
+pub use crate::client_message_listener::ClientMessageListener;
+pub use crate::item_update::ItemUpdate;
+pub use crate::ls_client::{ClientStatus, LightstreamerClient, Transport};
+pub use crate::subscription::{Snapshot, Subscription, SubscriptionMode};
+pub use crate::subscription_listener::SubscriptionListener;
@@ -0,0 +1,212 @@
+//! Zero-copy splitting of the TLCP frames received from the Server, and encoding of the
+//! parameters of the requests sent to it.
+//!
+//! A frame received on the WebSocket connection is kept in a single reference-counted `Bytes`
+//! buffer, and each notification it carries is a slice of that buffer: no copies are made while
+//! splitting a frame into notifications and a notification into its arguments.
+//!
+//! The module is public only so that the parser can be benchmarked; it is not part of the
+//! supported API.
+
+use crate::item_update::FieldValue;
+
+use bytes::{Buf, Bytes};
+use percent_encoding::{percent_decode_str, utf8_percent_encode, AsciiSet, NON_ALPHANUMERIC};
+use std::borrow::Cow;
+
+/// Characters left as they are in the names and values of request parameters: the unreserved
+/// characters of RFC 3986. Everything else, spaces included, is percent-encoded as UTF-8.
+const UNRESERVED: &AsciiSet = &NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'.')
+    .remove(b'_')
+    .remove(b'~');
+
+/// Encodes the parameters of a TLCP request as `name=value` pairs separated by `&`.
+///
+/// Names and values are percent-encoded, so that user names, passwords and message payloads can
+/// contain separators such as `&`, `=` and `|`, percent signs, plus signs and non-ASCII
+/// characters. Spaces are encoded as `%20` rather than `+`, which the Server would take literally.
+pub fn encode_params<K: AsRef<str>, V: AsRef<str>>(
+    params: impl IntoIterator<Item = (K, V)>,
+) -> String {
+    let mut encoded = String::new();
+    for (name, value) in params {
+        if !encoded.is_empty() {
+            encoded.push('&');
+        }
+        encoded.extend(utf8_percent_encode(name.as_ref(), UNRESERVED));
+        encoded.push('=');
+        encoded.extend(utf8_percent_encode(value.as_ref(), UNRESERVED));
+    }
+    encoded
+}
+
+/// A TLCP notification, i.e. a line of a frame received from the Server, without its terminator.
+#[derive(Debug, Clone)]
+pub struct Notification {
+    line: Bytes,
+}
+
+impl Notification {
+    /// Gets the text of the notification.
+    pub fn as_str(&self) -> &str {
+        // Frames are valid UTF-8 and are only split at ASCII characters.
+        std::str::from_utf8(&self.line).unwrap_or_default()
+    }
+
+    /// Gets the name of the notification, e.g. `U` or `CONOK`.
+    pub fn name(&self) -> &str {
+        let text = self.as_str();
+        text.split_once(',').map_or(text, |(name, _)| name)
+    }
+}
+
+/// Splits a frame into its notifications, skipping blank lines.
+pub fn split_frame(frame: impl Into<Bytes>) -> impl Iterator<Item = Notification> {
+    let mut rest: Bytes = frame.into();
+    std::iter::from_fn(move || loop {
+        if rest.is_empty() {
+            return None;
+        }
+        let mut line = match rest.iter().position(|&byte| byte == b'\n') {
+            Some(end) => {
+                let line = rest.split_to(end);
+                rest.advance(1);
+                line
+            }
+            None => std::mem::take(&mut rest),
+        };
+        if line.ends_with(b"\r") {
+            line.truncate(line.len() - 1);
+        }
+        if !line.trim_ascii().is_empty() {
+            return Some(Notification { line });
+        }
+    })
+}
+
+/// The arguments of an update notification, i.e. `U,<subscription>,<item>,<values>`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UpdateArguments<'a> {
+    /// The id of the subscription, or 0 if missing or invalid.
+    pub subscription_id: usize,
+    /// The 1-based position of the item, or 0 if missing or invalid.
+    pub item_index: usize,
+    /// The field values, still encoded and separated by `|`.
+    pub values: &'a str,
+}
+
+/// Parses the arguments of an update notification, borrowing them from the notification text.
+pub fn parse_update(line: &str) -> UpdateArguments<'_> {
+    let mut arguments = line.splitn(4, ',').skip(1);
+    let mut next_number = || {
+        arguments
+            .next()
+            .and_then(|argument| argument.parse().ok())
+            .unwrap_or(0)
+    };
+    let subscription_id = next_number();
+    let item_index = next_number();
+    UpdateArguments {
+        subscription_id,
+        item_index,
+        values: arguments.next().unwrap_or_default(),
+    }
+}
+
+/// Decodes the field values of an update notification into `changes`, indexed by field position.
+/// Fields not carried by the update are left untouched, so they stay `FieldValue::Unchanged`.
+/// Values sent as JSON Patches are applied to the values held for the item before the update,
+/// given in `previous`, indexed by field position.
+///
+/// Returns the number of values skipped because they were sent in the TLCP-diff format, which is
+/// not supported, or as JSON Patches that can't be applied to the previous value; their fields
+/// are left untouched as well.
+pub fn decode_update_values(
+    values: &str,
+    previous: &[FieldValue],
+    changes: &mut [FieldValue],
+) -> usize {
+    let mut field_index = 0;
+    let mut skipped = 0;
+    for value in values.split('|') {
+        match value {
+            // An empty value means the field is unchanged compared to the previous update of the same field.
+            "" => field_index += 1,
+            // A hash sign "#" means the field is null.
+            "#" => {
+                if let Some(change) = changes.get_mut(field_index) {
+                    *change = FieldValue::Null;
+                }
+                field_index += 1;
+            }
+            // A dollar sign "$" means the field is empty.
+            "$" => {
+                if let Some(change) = changes.get_mut(field_index) {
+                    *change = FieldValue::Value(String::new());
+                }
+                field_index += 1;
+            }
+            value if value.starts_with('^') => {
+                let command = value.chars().nth(1).unwrap_or(' ');
+                match command {
+                    // A caret followed by a number means that many fields are unchanged.
+                    '0'..='9' => field_index += value[1..].parse::<usize>().unwrap_or(0),
+                    // A JSON Patch to be applied to the previous value of the field.
+                    'P' => {
+                        let patched = match previous.get(field_index) {
+                            Some(FieldValue::Value(previous)) => {
+                                apply_json_patch(previous, &decode_value(&value[2..]))
+                            }
+                            _ => None,
+                        };
+                        match (patched, changes.get_mut(field_index)) {
+                            (Some(patched), Some(change)) => *change = FieldValue::Value(patched),
+                            _ => skipped += 1,
+                        }
+                        field_index += 1;
+                    }
+                    // TLCP-diff is not supported, so the field keeps its previous value.
+                    'T' => {
+                        skipped += 1;
+                        field_index += 1;
+                    }
+                    _ => {
+                        if let Some(change) = changes.get_mut(field_index) {
+                            *change = FieldValue::Value(decode_value(value));
+                        }
+                        field_index += 1;
+                    }
+                }
+            }
+            _ => {
+                if let Some(change) = changes.get_mut(field_index) {
+                    *change = FieldValue::Value(decode_value(value));
+                }
+                field_index += 1;
+            }
+        }
+    }
+    skipped
+}
+
+/// Applies a JSON Patch to a JSON value, returning the patched value, or None if either is not
+/// valid or the patch can't be applied.
+fn apply_json_patch(value: &str, patch: &str) -> Option<String> {
+    let mut value: serde_json::Value = serde_json::from_str(value).ok()?;
+    let patch: json_patch::Patch = serde_json::from_str(patch).ok()?;
+    json_patch::patch(&mut value, &patch).ok()?;
+    Some(value.to_string())
+}
+
+/// Decodes a percent-encoded field value, sparing the decoder the values with nothing to decode,
+/// which are the vast majority. Plus signs are literal, as the Server never encodes spaces as `+`.
+fn decode_value(value: &str) -> String {
+    if !value.contains('%') {
+        return value.to_string();
+    }
+    percent_decode_str(value)
+        .decode_utf8()
+        .map_or_else(|_| value.to_string(), Cow::into_owned)
+}
@@ -1,3 +1,5 @@
+use crate::secret::SecretString;
+
 /// Simple class representing a Proxy configuration.
 ///
 /// An instance of this class can be used through `ConnectionOptions.setProxy()` to instruct
@@ -10,13 +12,16 @@
 /// * `port`: the proxy port
 /// * `user`: the user name to be used to validate against the proxy. Optional.
 /// * `password`: the password to be used to validate against the proxy. Optional.
+///
+/// The password is kept as a `SecretString`, so it is never printed by the `Debug` implementation
+/// and it is wiped from memory when the proxy configuration is dropped.
 #[derive(Debug)]
 pub struct Proxy {
     proxy_type: ProxyType,
     host: String,
     port: u16,
     user: Option<String>,
-    password: Option<String>,
+    password: Option<SecretString>,
 }
 
 impl Proxy {
@@ -34,7 +39,7 @@ impl Proxy {
         host: String,
         port: u16,
         user: Option<String>,
-        password: Option<String>,
+        password: Option<SecretString>,
     ) -> Self {
         Proxy {
             proxy_type,
@@ -66,7 +71,7 @@ impl Proxy {
     }
 
     /// Returns the proxy password.
-    pub fn get_password(&self) -> Option<&String> {
+    pub fn get_password(&self) -> Option<&SecretString> {
         self.password.as_ref()
     }
 }
@@ -0,0 +1,271 @@
+//! Recording of the TLCP frames exchanged with the Server and replay of the recorded sessions.
+//!
+//! A `SessionRecorder`, installed through `ConnectionOptions.setSessionRecorder()`, writes every
+//! frame sent and received by a client to a file, one JSON object per line. The file can be loaded
+//! into a `SessionRecording` and fed back to a client through `LightstreamerClient.replay()`: the
+//! recorded frames go through the same parser and dispatcher as live ones, so that the behavior
+//! seen on a production feed can be reproduced offline and turned into a regression test.
+
+use crate::runtime::tungstenite::{Error as WsError, Message};
+use crate::runtime::Instant;
+
+use futures_util::{Sink, Stream};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::collections::VecDeque;
+use std::fmt::{self, Debug, Formatter};
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
+use std::path::Path;
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll, Waker};
+
+/// Direction of a recorded frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
+pub enum FrameDirection {
+    /// A new connection was opened; the text is the address of the Server.
+    Connected,
+    /// A request sent by the client. Passwords are redacted.
+    Sent,
+    /// A frame received from the Server.
+    Received,
+}
+
+/// A frame recorded by a `SessionRecorder`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RecordedFrame {
+    /// Milliseconds elapsed from the creation of the recorder.
+    pub elapsed_ms: u64,
+    /// Whether the frame was sent or received.
+    pub direction: FrameDirection,
+    /// The text of the frame.
+    pub text: String,
+}
+
+impl FrameDirection {
+    fn as_str(self) -> &'static str {
+        match self {
+            FrameDirection::Connected => "connected",
+            FrameDirection::Sent => "sent",
+            FrameDirection::Received => "received",
+        }
+    }
+
+    fn parse(direction: &str) -> Option<FrameDirection> {
+        match direction {
+            "connected" => Some(FrameDirection::Connected),
+            "sent" => Some(FrameDirection::Sent),
+            "received" => Some(FrameDirection::Received),
+            _ => None,
+        }
+    }
+}
+
+impl RecordedFrame {
+    /// Converts the frame into the JSON object written on its line of a recording. The format is
+    /// built by hand, rather than derived, so that recordings don't depend on the `serde` feature.
+    fn to_json(&self) -> Value {
+        json!({
+            "elapsed_ms": self.elapsed_ms,
+            "direction": self.direction.as_str(),
+            "text": self.text,
+        })
+    }
+
+    /// Reads a frame from a line of a recording.
+    fn from_json(line: &str) -> io::Result<RecordedFrame> {
+        let value: Value = serde_json::from_str(line)?;
+        let invalid = || io::Error::new(io::ErrorKind::InvalidData, "Invalid recorded frame");
+        Ok(RecordedFrame {
+            elapsed_ms: value["elapsed_ms"].as_u64().ok_or_else(invalid)?,
+            direction: value["direction"]
+                .as_str()
+                .and_then(FrameDirection::parse)
+                .ok_or_else(invalid)?,
+            text: value["text"].as_str().ok_or_else(invalid)?.to_string(),
+        })
+    }
+}
+
+/// Writes the frames exchanged by a `LightstreamerClient` with the Server, one `RecordedFrame`
+/// serialized as JSON per line.
+///
+/// Every frame is flushed as soon as it is written, so that the recording is complete even if the
+/// application is abruptly terminated. Errors while writing are logged and don't affect the
+/// session.
+///
+/// See also `ConnectionOptions.setSessionRecorder()`
+pub struct SessionRecorder {
+    started_at: Instant,
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl SessionRecorder {
+    /// Creates a recorder writing to the given writer.
+    pub fn new(writer: impl Write + Send + 'static) -> SessionRecorder {
+        SessionRecorder {
+            started_at: Instant::now(),
+            writer: Mutex::new(Box::new(writer)),
+        }
+    }
+
+    /// Creates a recorder writing to the file at the given path, which is created or truncated.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<SessionRecorder> {
+        Ok(SessionRecorder::new(BufWriter::new(File::create(path)?)))
+    }
+
+    /// Writes a frame.
+    pub(crate) fn record(&self, direction: FrameDirection, text: &str) -> io::Result<()> {
+        let frame = RecordedFrame {
+            elapsed_ms: self.started_at.elapsed().as_millis() as u64,
+            direction,
+            text: text.to_string(),
+        };
+        let mut writer = self.writer.lock().unwrap();
+        serde_json::to_writer(&mut *writer, &frame.to_json())?;
+        writer.write_all(b"\n")?;
+        writer.flush()
+    }
+}
+
+impl Debug for SessionRecorder {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionRecorder").finish_non_exhaustive()
+    }
+}
+
+/// The frames of a session recorded by a `SessionRecorder`, to be replayed through
+/// `LightstreamerClient.replay()`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SessionRecording {
+    frames: Vec<RecordedFrame>,
+}
+
+impl SessionRecording {
+    /// Creates a recording made of the given frames.
+    pub fn new(frames: Vec<RecordedFrame>) -> SessionRecording {
+        SessionRecording { frames }
+    }
+
+    /// Loads a recording from the file at the given path.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<SessionRecording> {
+        SessionRecording::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Loads a recording from the given reader, skipping blank lines.
+    pub fn from_reader(reader: impl BufRead) -> io::Result<SessionRecording> {
+        let mut frames = Vec::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            frames.push(RecordedFrame::from_json(&line)?);
+        }
+        Ok(SessionRecording { frames })
+    }
+
+    /// Gets the recorded frames, in the order they were exchanged.
+    pub fn frames(&self) -> &[RecordedFrame] {
+        &self.frames
+    }
+}
+
+/// Source of the connections of a session replaying a recording: each connection opened delivers
+/// the frames received on the corresponding recorded connection.
+#[derive(Debug)]
+pub(crate) struct Replay {
+    connections: Mutex<VecDeque<Vec<String>>>,
+}
+
+impl Replay {
+    pub(crate) fn new(recording: SessionRecording) -> Replay {
+        let mut connections: VecDeque<Vec<String>> = VecDeque::new();
+        for frame in recording.frames {
+            match frame.direction {
+                FrameDirection::Connected => connections.push_back(Vec::new()),
+                FrameDirection::Received => match connections.back_mut() {
+                    Some(frames) => frames.push(frame.text),
+                    None => connections.push_back(vec![frame.text]),
+                },
+                FrameDirection::Sent => {}
+            }
+        }
+        Replay {
+            connections: Mutex::new(connections),
+        }
+    }
+
+    /// Opens the next recorded connection. Fails when all of them have been replayed.
+    pub(crate) fn connect(&self) -> Result<ReplaySocket, WsError> {
+        let mut connections = self.connections.lock().unwrap();
+        let frames = connections.pop_front().ok_or_else(|| {
+            WsError::Io(io::Error::new(
+                io::ErrorKind::NotConnected,
+                "No more recorded connections to replay",
+            ))
+        })?;
+        Ok(ReplaySocket {
+            frames: frames.into(),
+            // The connection recorded last was still open when the recording stopped.
+            open_ended: connections.is_empty(),
+            closed: false,
+            waker: None,
+        })
+    }
+}
+
+/// Connection delivering recorded frames in order and discarding whatever is sent on it.
+///
+/// Once the frames are over, the connection is closed, unless it is the last recorded one: in
+/// that case it stays open until the client closes it.
+pub(crate) struct ReplaySocket {
+    frames: VecDeque<String>,
+    open_ended: bool,
+    closed: bool,
+    waker: Option<Waker>,
+}
+
+impl Stream for ReplaySocket {
+    type Item = Result<Message, WsError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if let Some(frame) = self.frames.pop_front() {
+            return Poll::Ready(Some(Ok(Message::Text(frame.into()))));
+        }
+        if self.closed || !self.open_ended {
+            return Poll::Ready(None);
+        }
+        self.waker = Some(cx.waker().clone());
+        Poll::Pending
+    }
+}
+
+impl Sink<Message> for ReplaySocket {
+    type Error = WsError;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, _message: Message) -> Result<(), WsError> {
+        Ok(())
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        self.closed = true;
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+        Poll::Ready(Ok(()))
+    }
+}
@@ -0,0 +1,307 @@
+use crate::protocol::encode_params;
+use crate::runtime::tungstenite::http::header::{HeaderName, HeaderValue};
+use crate::runtime::tungstenite::http::HeaderMap;
+use crate::runtime::tungstenite::{Error as WsError, Message};
+use crate::runtime::BoxedSocket;
+
+use futures_util::{Sink, Stream};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+/// Kind of an outgoing request intercepted by a `RequestInterceptor`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum RequestKind {
+    /// The opening of a WebSocket connection, which carries headers but no parameters.
+    Handshake,
+    /// A `create_session` request.
+    CreateSession,
+    /// A `bind_session` request.
+    BindSession,
+    /// A `recover_session` request.
+    RecoverSession,
+    /// A `control` request, e.g. a subscription or a change of the connection settings.
+    Control,
+    /// A `msg` request, carrying a message sent through `LightstreamerClient.sendMessage()`.
+    Message,
+    /// A `heartbeat` request, i.e. a reverse heartbeat.
+    Heartbeat,
+}
+
+impl RequestKind {
+    /// Gets the kind of the TLCP request with the given name, if it can be intercepted.
+    pub(crate) fn from_name(name: &str) -> Option<RequestKind> {
+        match name {
+            "create_session" => Some(RequestKind::CreateSession),
+            "bind_session" => Some(RequestKind::BindSession),
+            "recover_session" => Some(RequestKind::RecoverSession),
+            "control" => Some(RequestKind::Control),
+            "msg" => Some(RequestKind::Message),
+            "heartbeat" => Some(RequestKind::Heartbeat),
+            _ => None,
+        }
+    }
+}
+
+impl Display for RequestKind {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            RequestKind::Handshake => write!(f, "handshake"),
+            RequestKind::CreateSession => write!(f, "create_session"),
+            RequestKind::BindSession => write!(f, "bind_session"),
+            RequestKind::RecoverSession => write!(f, "recover_session"),
+            RequestKind::Control => write!(f, "control"),
+            RequestKind::Message => write!(f, "msg"),
+            RequestKind::Heartbeat => write!(f, "heartbeat"),
+        }
+    }
+}
+
+/// Outgoing request of a `LightstreamerClient`, as seen by a `RequestInterceptor`, which can
+/// change its parameters and headers before it is sent.
+///
+/// Headers are only sent with the requests that open a connection: over WebSocket, they are the
+/// ones of the `RequestKind::Handshake` request, and the headers of the requests sent on the
+/// connection afterwards are ignored; over HTTP, every request carries its own headers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InterceptedRequest {
+    kind: RequestKind,
+    params: Vec<(String, String)>,
+    headers: Vec<(String, String)>,
+}
+
+impl InterceptedRequest {
+    pub(crate) fn new(
+        kind: RequestKind,
+        params: Vec<(String, String)>,
+        headers: Vec<(String, String)>,
+    ) -> InterceptedRequest {
+        InterceptedRequest {
+            kind,
+            params,
+            headers,
+        }
+    }
+
+    /// Consumes the request, returning its parameters.
+    pub(crate) fn into_params(self) -> Vec<(String, String)> {
+        self.params
+    }
+
+    /// Returns the kind of the request.
+    pub fn get_kind(&self) -> RequestKind {
+        self.kind
+    }
+
+    /// Returns the parameters of the request, in the order they are sent.
+    pub fn get_params(&self) -> &[(String, String)] {
+        &self.params
+    }
+
+    /// Returns the value of the first parameter with the given name, if any.
+    pub fn get_param(&self, name: &str) -> Option<&str> {
+        self.params
+            .iter()
+            .find(|(param_name, _)| param_name == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets the value of the parameter with the given name, replacing its current value, if any,
+    /// or else appending it to the parameters.
+    pub fn set_param(&mut self, name: &str, value: &str) {
+        set_entry(&mut self.params, name, value, |a, b| a == b);
+    }
+
+    /// Removes the parameter with the given name, if any.
+    pub fn remove_param(&mut self, name: &str) {
+        self.params.retain(|(param_name, _)| param_name != name);
+    }
+
+    /// Returns the headers of the request.
+    pub fn get_headers(&self) -> &[(String, String)] {
+        &self.headers
+    }
+
+    /// Returns the value of the header with the given name, matched case-insensitively, if any.
+    pub fn get_header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header_name, _)| header_name.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Sets the value of the header with the given name, matched case-insensitively, replacing
+    /// its current value, if any, or else adding it to the headers.
+    pub fn set_header(&mut self, name: &str, value: &str) {
+        set_entry(&mut self.headers, name, value, str::eq_ignore_ascii_case);
+    }
+
+    /// Removes the header with the given name, matched case-insensitively, if any.
+    pub fn remove_header(&mut self, name: &str) {
+        self.headers
+            .retain(|(header_name, _)| !header_name.eq_ignore_ascii_case(name));
+    }
+}
+
+/// Sets the value of the first entry matching the given name, removing the other ones, or appends
+/// a new entry.
+fn set_entry(
+    entries: &mut Vec<(String, String)>,
+    name: &str,
+    value: &str,
+    matches: impl Fn(&str, &str) -> bool,
+) {
+    let mut found = false;
+    entries.retain_mut(|(entry_name, entry_value)| {
+        if !matches(entry_name, name) {
+            return true;
+        }
+        if found {
+            return false;
+        }
+        found = true;
+        *entry_value = value.to_string();
+        true
+    });
+    if !found {
+        entries.push((name.to_string(), value.to_string()));
+    }
+}
+
+/// Interface to be implemented to change the outgoing requests of a `LightstreamerClient` before
+/// they are sent, e.g. to sign them or to add the tracking parameters or the headers required by
+/// a gateway.
+///
+/// Instances of types implementing this trait can be supplied through
+/// `ConnectionOptions.addRequestInterceptor()`: they form a chain, invoked in the order they
+/// were added on every request, so that each interceptor sees the changes of the previous ones.
+/// An interceptor computing a signature should then be added last.
+///
+/// The parameters are the ones of the TLCP protocol: the Server refuses the requests whose
+/// parameters are changed inconsistently, as it would refuse any malformed request.
+///
+/// The interceptors are invoked by the session task, which runs separately from the code that
+/// configured them; this is why implementations must be `Send` and `Sync`. They should not block,
+/// as the requests wait for them.
+pub trait RequestInterceptor: Debug + Send + Sync {
+    /// Invoked before a request is sent.
+    ///
+    /// # Parameters
+    ///
+    /// * `request`: the request, whose parameters and headers can be changed.
+    fn intercept(&self, request: &mut InterceptedRequest);
+}
+
+/// Chain of the interceptors configured for a client.
+pub(crate) type RequestInterceptors = Arc<[Arc<dyn RequestInterceptor>]>;
+
+/// Runs a request through the chain of interceptors.
+pub(crate) fn intercept(
+    interceptors: &[Arc<dyn RequestInterceptor>],
+    request: &mut InterceptedRequest,
+) {
+    for interceptor in interceptors {
+        interceptor.intercept(request);
+    }
+}
+
+/// Runs the requests carried by a text frame through the chain of interceptors, returning the
+/// frame to be sent instead. Frames that carry no request, like `wsok`, are returned unchanged,
+/// and so are the requests whose parameters can't be decoded.
+pub(crate) fn intercept_frame(interceptors: &[Arc<dyn RequestInterceptor>], text: &str) -> String {
+    let Some((name, lines)) = text.split_once("\r\n") else {
+        return text.to_string();
+    };
+    let Some(kind) = RequestKind::from_name(name) else {
+        return text.to_string();
+    };
+    let mut frame = name.to_string();
+    for line in lines.lines().filter(|line| !line.trim().is_empty()) {
+        frame.push_str("\r\n");
+        let Ok(params) = serde_urlencoded::from_str(line.trim()) else {
+            frame.push_str(line);
+            continue;
+        };
+        let mut request = InterceptedRequest::new(kind, params, Vec::new());
+        intercept(interceptors, &mut request);
+        frame.push_str(&encode_params(request.into_params()));
+    }
+    frame
+}
+
+/// Runs the headers of a WebSocket handshake through the chain of interceptors. The headers set
+/// with an invalid name or value are dropped.
+pub(crate) fn intercept_handshake(
+    interceptors: &[Arc<dyn RequestInterceptor>],
+    headers: &mut HeaderMap,
+) {
+    let mut request = InterceptedRequest::new(
+        RequestKind::Handshake,
+        Vec::new(),
+        headers
+            .iter()
+            .filter_map(|(name, value)| Some((name.to_string(), value.to_str().ok()?.to_string())))
+            .collect(),
+    );
+    intercept(interceptors, &mut request);
+    headers.clear();
+    for (name, value) in request.headers {
+        if let (Ok(name), Ok(value)) = (
+            HeaderName::from_bytes(name.as_bytes()),
+            HeaderValue::from_str(&value),
+        ) {
+            headers.append(name, value);
+        }
+    }
+}
+
+/// WebSocket connection running the requests sent through it through the chain of interceptors.
+/// Whatever is received goes through unchanged.
+pub(crate) struct InterceptingSocket {
+    inner: BoxedSocket,
+    interceptors: RequestInterceptors,
+}
+
+impl InterceptingSocket {
+    pub(crate) fn new(inner: BoxedSocket, interceptors: RequestInterceptors) -> InterceptingSocket {
+        InterceptingSocket {
+            inner,
+            interceptors,
+        }
+    }
+}
+
+impl Stream for InterceptingSocket {
+    type Item = Result<Message, WsError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        Pin::new(&mut self.inner).poll_next(cx)
+    }
+}
+
+impl Sink<Message> for InterceptingSocket {
+    type Error = WsError;
+
+    fn poll_ready(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        Pin::new(&mut self.inner).poll_ready(cx)
+    }
+
+    fn start_send(mut self: Pin<&mut Self>, message: Message) -> Result<(), WsError> {
+        let message = match message {
+            Message::Text(text) => {
+                Message::Text(intercept_frame(&self.interceptors, text.as_str()).into())
+            }
+            message => message,
+        };
+        Pin::new(&mut self.inner).start_send(message)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_close(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        Pin::new(&mut self.inner).poll_close(cx)
+    }
+}
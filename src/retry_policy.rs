@@ -0,0 +1,114 @@
+use crate::disconnect_info::DisconnectInfo;
+use crate::util::random_delay;
+
+use std::error::Error;
+use std::fmt::Debug;
+use std::time::Duration;
+
+/// Interface to be implemented to control how a `LightstreamerClient` reconnects to the Server
+/// after a connection is lost or a connection attempt fails.
+///
+/// An instance of a type implementing this trait can be supplied through
+/// `ConnectionOptions.setRetryPolicy()`. When no policy is supplied, a `DefaultRetryPolicy` built
+/// from `ConnectionOptions.getRetryDelay()` and `ConnectionOptions.getFirstRetryMaxDelay()` is
+/// used.
+///
+/// The policy is consulted by the session task, which runs separately from the code that
+/// configured it; this is why implementations must be `Send` and `Sync`.
+pub trait RetryPolicy: Debug + Send + Sync {
+    /// Computes the time to wait before the next connection attempt.
+    ///
+    /// # Parameters
+    ///
+    /// * `attempt`: the number of consecutive connection attempts that failed so far. A value of
+    ///   0 means that a working connection has just been closed and no attempt has failed yet.
+    /// * `error`: the error that caused the last connection to be closed or the last attempt
+    ///   to fail. When the WebSocket connection was closed or lost, it is a `DisconnectInfo`,
+    ///   which can be obtained through `downcast_ref()` to tell clean closes from aborted ones.
+    ///
+    /// # Returns
+    ///
+    /// The time to wait before trying again, or `None` to give up: in that case the client stops
+    /// retrying and its status becomes "DISCONNECTED".
+    fn next_delay(
+        &self,
+        attempt: u32,
+        error: &(dyn Error + Send + Sync + 'static),
+    ) -> Option<Duration>;
+}
+
+/// Retry policy that implements the standard Lightstreamer behavior: the first attempt after
+/// a working connection has been lost waits for a random time up to the first retry max delay,
+/// while the next attempt waits for the retry delay. When the Server closed the connection
+/// cleanly, e.g. because it is shutting down, even the first attempt waits for the retry delay,
+/// so as not to come back too early. It never gives up.
+///
+/// If the attempts keep failing, the delay doubles at each further attempt, up to the max retry
+/// delay, so as not to flood an unavailable Server. A random jitter of up to half the delay is
+/// subtracted from these growing delays, so that many clients disconnected at once don't come
+/// back at the same time; they never get shorter than the retry delay, though.
+///
+/// See also `ConnectionOptions.setRetryDelay()`
+///
+/// See also `ConnectionOptions.setFirstRetryMaxDelay()`
+///
+/// See also `ConnectionOptions.setMaxRetryDelay()`
+#[derive(Debug, Clone)]
+pub struct DefaultRetryPolicy {
+    retry_delay: Duration,
+    first_retry_max_delay: Duration,
+    max_retry_delay: Duration,
+}
+
+impl DefaultRetryPolicy {
+    /// Default upper bound of the growing delays.
+    pub const DEFAULT_MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+    /// Creates a new instance of `DefaultRetryPolicy`, whose delays grow up to
+    /// `DEFAULT_MAX_RETRY_DELAY`.
+    ///
+    /// # Parameters
+    ///
+    /// * `retry_delay`: the time to wait before retrying after a failed connection attempt.
+    /// * `first_retry_max_delay`: the maximum time to wait before the first attempt after
+    ///   a working connection has been closed.
+    pub fn new(retry_delay: Duration, first_retry_max_delay: Duration) -> Self {
+        DefaultRetryPolicy {
+            retry_delay,
+            first_retry_max_delay,
+            max_retry_delay: Self::DEFAULT_MAX_RETRY_DELAY,
+        }
+    }
+
+    /// Sets the upper bound of the delays growing while the attempts keep failing. A bound lower
+    /// than the retry delay disables the growth.
+    pub fn with_max_retry_delay(mut self, max_retry_delay: Duration) -> Self {
+        self.max_retry_delay = max_retry_delay;
+        self
+    }
+}
+
+impl RetryPolicy for DefaultRetryPolicy {
+    fn next_delay(
+        &self,
+        attempt: u32,
+        error: &(dyn Error + Send + Sync + 'static),
+    ) -> Option<Duration> {
+        let clean_server_close = error
+            .downcast_ref::<DisconnectInfo>()
+            .is_some_and(DisconnectInfo::is_clean_server_close);
+        if attempt == 0 && !clean_server_close {
+            return Some(random_delay(self.first_retry_max_delay));
+        }
+        if attempt <= 1 {
+            return Some(self.retry_delay);
+        }
+        let factor = 1u32.checked_shl(attempt - 1).unwrap_or(u32::MAX);
+        let delay = self
+            .retry_delay
+            .saturating_mul(factor)
+            .min(self.max_retry_delay);
+        let jittered = delay - random_delay(delay / 2);
+        Some(jittered.max(self.retry_delay))
+    }
+}
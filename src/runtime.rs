@@ -0,0 +1,290 @@
+use futures::channel::oneshot;
+use futures_util::{Sink, Stream};
+use std::future::Future;
+use std::time::Duration;
+
+#[cfg(all(
+    target_arch = "wasm32",
+    feature = "runtime-wasm",
+    not(feature = "runtime-tokio"),
+    not(feature = "runtime-async-std"),
+    not(feature = "runtime-smol")
+))]
+mod wasm;
+
+#[cfg(feature = "runtime-tokio")]
+pub(crate) use tokio_tungstenite::tungstenite;
+
+#[cfg(all(
+    not(feature = "runtime-tokio"),
+    any(feature = "runtime-async-std", feature = "runtime-smol")
+))]
+pub(crate) use async_tungstenite::tungstenite;
+
+#[cfg(all(
+    target_arch = "wasm32",
+    feature = "runtime-wasm",
+    not(feature = "runtime-tokio"),
+    not(feature = "runtime-async-std"),
+    not(feature = "runtime-smol")
+))]
+#[allow(clippy::single_component_path_imports)]
+// Makes `crate::runtime::tungstenite` available.
+pub(crate) use tungstenite;
+
+/// Monotonic clock, which on wasm32 is backed by the browser's `performance.now()`.
+#[cfg(not(target_arch = "wasm32"))]
+pub(crate) use std::time::Instant;
+
+/// Monotonic clock, which on wasm32 is backed by the browser's `performance.now()`.
+#[cfg(target_arch = "wasm32")]
+pub(crate) use web_time::Instant;
+
+use tungstenite::handshake::client::Response;
+use tungstenite::http::Request;
+use tungstenite::protocol::WebSocketConfig;
+use tungstenite::{Error as WsError, Message};
+
+/// Runtime-dependent pieces needed by the client: spawning tasks, waiting and opening
+/// WebSocket connections.
+///
+/// An implementation exists for every supported async runtime, and the one in use is selected
+/// at compile time through the `runtime-tokio` (default), `runtime-async-std`, `runtime-smol` and
+/// `runtime-wasm` (browsers, wasm32 only) cargo features. When several of them are enabled, tokio
+/// takes precedence over async-std, async-std over smol and smol over wasm.
+pub(crate) trait Runtime {
+    /// WebSocket stream returned by `connect_websocket()`.
+    type WebSocket: Stream<Item = Result<Message, WsError>>
+        + Sink<Message, Error = WsError>
+        + Send
+        + Unpin
+        + 'static;
+
+    /// Spawns a future on the runtime, detached from the caller.
+    fn spawn<F>(future: F)
+    where
+        F: Future<Output = ()> + Send + 'static;
+
+    /// Waits for the given duration.
+    fn sleep(duration: Duration) -> impl Future<Output = ()> + Send;
+
+    /// Opens a WebSocket connection performing the handshake described by the given request, with
+    /// the given limits on the frames and messages received.
+    fn connect_websocket(
+        request: Request<()>,
+        config: WebSocketConfig,
+    ) -> impl Future<Output = Result<(Self::WebSocket, Response), WsError>> + Send;
+}
+
+/// WebSocket connection driven by the session task, either opened through
+/// `Runtime::connect_websocket()` or replaying a recorded session.
+pub(crate) trait Socket:
+    Stream<Item = Result<Message, WsError>> + Sink<Message, Error = WsError> + Send + Unpin
+{
+}
+
+impl<S> Socket for S where
+    S: Stream<Item = Result<Message, WsError>> + Sink<Message, Error = WsError> + Send + Unpin
+{
+}
+
+/// Type-erased `Socket`, so that the session task handles every kind of connection alike.
+pub(crate) type BoxedSocket = Box<dyn Socket>;
+
+// Only the implementation of the selected runtime is compiled.
+
+/// Runtime implementation based on tokio.
+#[cfg(feature = "runtime-tokio")]
+pub(crate) struct TokioRuntime;
+
+#[cfg(feature = "runtime-tokio")]
+impl Runtime for TokioRuntime {
+    type WebSocket = tokio_tungstenite::WebSocketStream<
+        tokio_tungstenite::MaybeTlsStream<tokio::net::TcpStream>,
+    >;
+
+    fn spawn<F>(future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        tokio::spawn(future);
+    }
+
+    fn sleep(duration: Duration) -> impl Future<Output = ()> + Send {
+        tokio::time::sleep(duration)
+    }
+
+    fn connect_websocket(
+        request: Request<()>,
+        config: WebSocketConfig,
+    ) -> impl Future<Output = Result<(Self::WebSocket, Response), WsError>> + Send {
+        tokio_tungstenite::connect_async_with_config(request, Some(config), false)
+    }
+}
+
+/// Runtime implementation based on async-std.
+#[cfg(all(not(feature = "runtime-tokio"), feature = "runtime-async-std"))]
+pub(crate) struct AsyncStdRuntime;
+
+// async-std is no longer maintained upstream, so its bindings are deprecated; they are still
+// offered here for applications already built on it.
+#[cfg(all(not(feature = "runtime-tokio"), feature = "runtime-async-std"))]
+#[allow(deprecated)]
+impl Runtime for AsyncStdRuntime {
+    type WebSocket =
+        async_tungstenite::WebSocketStream<async_tungstenite::async_std::ConnectStream>;
+
+    fn spawn<F>(future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        async_std::task::spawn(future);
+    }
+
+    fn sleep(duration: Duration) -> impl Future<Output = ()> + Send {
+        async_std::task::sleep(duration)
+    }
+
+    fn connect_websocket(
+        request: Request<()>,
+        config: WebSocketConfig,
+    ) -> impl Future<Output = Result<(Self::WebSocket, Response), WsError>> + Send {
+        async_tungstenite::async_std::connect_async_with_config(request, Some(config))
+    }
+}
+
+/// Runtime implementation based on smol.
+#[cfg(all(
+    not(feature = "runtime-tokio"),
+    not(feature = "runtime-async-std"),
+    feature = "runtime-smol"
+))]
+pub(crate) struct SmolRuntime;
+
+#[cfg(all(
+    not(feature = "runtime-tokio"),
+    not(feature = "runtime-async-std"),
+    feature = "runtime-smol"
+))]
+impl Runtime for SmolRuntime {
+    type WebSocket = async_tungstenite::WebSocketStream<async_tungstenite::smol::ConnectStream>;
+
+    fn spawn<F>(future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        smol::spawn(future).detach();
+    }
+
+    async fn sleep(duration: Duration) {
+        smol::Timer::after(duration).await;
+    }
+
+    fn connect_websocket(
+        request: Request<()>,
+        config: WebSocketConfig,
+    ) -> impl Future<Output = Result<(Self::WebSocket, Response), WsError>> + Send {
+        async_tungstenite::smol::connect_async_with_config(request, Some(config))
+    }
+}
+
+/// Runtime implementation for browsers, based on the WebSocket API and the timers of the
+/// JavaScript host.
+#[cfg(all(
+    target_arch = "wasm32",
+    feature = "runtime-wasm",
+    not(feature = "runtime-tokio"),
+    not(feature = "runtime-async-std"),
+    not(feature = "runtime-smol")
+))]
+pub(crate) struct WasmRuntime;
+
+#[cfg(all(
+    target_arch = "wasm32",
+    feature = "runtime-wasm",
+    not(feature = "runtime-tokio"),
+    not(feature = "runtime-async-std"),
+    not(feature = "runtime-smol")
+))]
+impl Runtime for WasmRuntime {
+    type WebSocket = wasm::WasmWebSocket;
+
+    fn spawn<F>(future: F)
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        wasm_bindgen_futures::spawn_local(future);
+    }
+
+    fn sleep(duration: Duration) -> impl Future<Output = ()> + Send {
+        // Timers of the JavaScript host are not `Send`, but wasm32 runs on a single thread.
+        send_wrapper::SendWrapper::new(gloo_timers::future::sleep(duration))
+    }
+
+    fn connect_websocket(
+        request: Request<()>,
+        _config: WebSocketConfig,
+    ) -> impl Future<Output = Result<(Self::WebSocket, Response), WsError>> + Send {
+        // The browser enforces its own limits on the messages received.
+        send_wrapper::SendWrapper::new(wasm::connect(request))
+    }
+}
+
+/// Runtime selected through cargo features.
+#[cfg(feature = "runtime-tokio")]
+pub(crate) type CurrentRuntime = TokioRuntime;
+
+/// Runtime selected through cargo features.
+#[cfg(all(not(feature = "runtime-tokio"), feature = "runtime-async-std"))]
+pub(crate) type CurrentRuntime = AsyncStdRuntime;
+
+/// Runtime selected through cargo features.
+#[cfg(all(
+    not(feature = "runtime-tokio"),
+    not(feature = "runtime-async-std"),
+    feature = "runtime-smol"
+))]
+pub(crate) type CurrentRuntime = SmolRuntime;
+
+/// Runtime selected through cargo features.
+#[cfg(all(
+    target_arch = "wasm32",
+    feature = "runtime-wasm",
+    not(feature = "runtime-tokio"),
+    not(feature = "runtime-async-std"),
+    not(feature = "runtime-smol")
+))]
+pub(crate) type CurrentRuntime = WasmRuntime;
+
+/// Handle to a task spawned through `spawn_task()`, which can be used to check whether the task
+/// has finished and to wait for its termination, regardless of the runtime in use.
+#[derive(Debug)]
+pub(crate) struct TaskHandle {
+    done: oneshot::Receiver<()>,
+}
+
+impl TaskHandle {
+    /// Returns `true` if the task has already terminated.
+    pub(crate) fn is_finished(&mut self) -> bool {
+        !matches!(self.done.try_recv(), Ok(None))
+    }
+
+    /// Waits for the task to terminate. Returns an error if the task was dropped before
+    /// completing, e.g. because it panicked.
+    pub(crate) async fn join(self) -> Result<(), oneshot::Canceled> {
+        self.done.await
+    }
+}
+
+/// Spawns a future on the current runtime and returns a handle to it.
+pub(crate) fn spawn_task<F>(future: F) -> TaskHandle
+where
+    F: Future<Output = ()> + Send + 'static,
+{
+    let (done_sender, done) = oneshot::channel();
+    CurrentRuntime::spawn(async move {
+        future.await;
+        let _ = done_sender.send(());
+    });
+    TaskHandle { done }
+}
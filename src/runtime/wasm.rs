@@ -0,0 +1,184 @@
+use super::tungstenite::handshake::client::Response;
+use super::tungstenite::http::Request;
+use super::tungstenite::protocol::CloseFrame;
+use super::tungstenite::{Error as WsError, Message};
+
+use futures::channel::{mpsc, oneshot};
+use futures_util::{Sink, Stream, StreamExt};
+use send_wrapper::SendWrapper;
+use std::cell::RefCell;
+use std::pin::Pin;
+use std::rc::Rc;
+use std::task::{Context, Poll};
+use wasm_bindgen::closure::Closure;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{BinaryType, CloseEvent, Event, MessageEvent, WebSocket};
+
+/// Callbacks registered on the browser WebSocket, which must live as long as the socket.
+#[allow(dead_code)]
+struct Callbacks {
+    on_open: Closure<dyn FnMut(Event)>,
+    on_message: Closure<dyn FnMut(MessageEvent)>,
+    on_close: Closure<dyn FnMut(CloseEvent)>,
+    on_error: Closure<dyn FnMut(Event)>,
+}
+
+/// WebSocket connection backed by the browser WebSocket API, exposed as a stream and a sink
+/// of tungstenite messages like the WebSocket streams of the native runtimes.
+///
+/// Browser objects can't be shared among threads, but wasm32 targets run on a single thread:
+/// they are wrapped in `SendWrapper` to satisfy the `Send` bounds of the runtime abstraction.
+pub(crate) struct WasmWebSocket {
+    socket: SendWrapper<WebSocket>,
+    messages: mpsc::UnboundedReceiver<Result<Message, WsError>>,
+    // Never read, only kept alive until the socket is dropped.
+    _callbacks: SendWrapper<Callbacks>,
+}
+
+impl Drop for WasmWebSocket {
+    fn drop(&mut self) {
+        self.socket.set_onopen(None);
+        self.socket.set_onmessage(None);
+        self.socket.set_onclose(None);
+        self.socket.set_onerror(None);
+        let _ = self.socket.close();
+    }
+}
+
+impl Stream for WasmWebSocket {
+    type Item = Result<Message, WsError>;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        self.messages.poll_next_unpin(cx)
+    }
+}
+
+impl Sink<Message> for WasmWebSocket {
+    type Error = WsError;
+
+    fn poll_ready(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        if self.socket.ready_state() == WebSocket::CLOSED {
+            Poll::Ready(Err(WsError::AlreadyClosed))
+        } else {
+            Poll::Ready(Ok(()))
+        }
+    }
+
+    fn start_send(self: Pin<&mut Self>, message: Message) -> Result<(), WsError> {
+        match message {
+            Message::Text(text) => self.socket.send_with_str(&text),
+            Message::Binary(data) => self.socket.send_with_u8_array(&data),
+            Message::Close(Some(frame)) => self
+                .socket
+                .close_with_code_and_reason(frame.code.into(), &frame.reason),
+            Message::Close(None) => self.socket.close(),
+            // Pings and pongs are handled by the browser.
+            Message::Ping(_) | Message::Pong(_) | Message::Frame(_) => Ok(()),
+        }
+        .map_err(js_error)
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), WsError>> {
+        Poll::Ready(self.socket.close().map_err(js_error))
+    }
+}
+
+/// Opens a browser WebSocket connection to the URI of the given request, using the subprotocol
+/// found in its `Sec-WebSocket-Protocol` header. Other headers are ignored, since the browser
+/// doesn't allow to set them.
+pub(crate) async fn connect(request: Request<()>) -> Result<(WasmWebSocket, Response), WsError> {
+    let url = request.uri().to_string();
+    let protocol = request
+        .headers()
+        .get("sec-websocket-protocol")
+        .and_then(|protocol| protocol.to_str().ok());
+    let socket = match protocol {
+        Some(protocol) => WebSocket::new_with_str(&url, protocol),
+        None => WebSocket::new(&url),
+    }
+    .map_err(js_error)?;
+    socket.set_binary_type(BinaryType::Arraybuffer);
+
+    let (message_sender, messages) = mpsc::unbounded();
+    let (open_sender, open) = oneshot::channel();
+    // Notified once, either when the socket is open or when it fails before being open.
+    let open_sender = Rc::new(RefCell::new(Some(open_sender)));
+
+    let on_open = {
+        let open_sender = Rc::clone(&open_sender);
+        Closure::<dyn FnMut(Event)>::new(move |_: Event| {
+            if let Some(open_sender) = open_sender.borrow_mut().take() {
+                let _ = open_sender.send(Ok(()));
+            }
+        })
+    };
+    let on_message = {
+        let message_sender = message_sender.clone();
+        Closure::<dyn FnMut(MessageEvent)>::new(move |event: MessageEvent| {
+            let data = event.data();
+            let message = match data.as_string() {
+                Some(text) => Message::Text(text.into()),
+                None => Message::Binary(js_sys::Uint8Array::new(&data).to_vec().into()),
+            };
+            let _ = message_sender.unbounded_send(Ok(message));
+        })
+    };
+    let on_close = {
+        let message_sender = message_sender.clone();
+        let open_sender = Rc::clone(&open_sender);
+        Closure::<dyn FnMut(CloseEvent)>::new(move |event: CloseEvent| {
+            if let Some(open_sender) = open_sender.borrow_mut().take() {
+                let _ = open_sender.send(Err(WsError::ConnectionClosed));
+            }
+            let frame = CloseFrame {
+                code: event.code().into(),
+                reason: event.reason().into(),
+            };
+            let _ = message_sender.unbounded_send(Ok(Message::Close(Some(frame))));
+            message_sender.close_channel();
+        })
+    };
+    let on_error = {
+        let open_sender = Rc::clone(&open_sender);
+        Closure::<dyn FnMut(Event)>::new(move |_: Event| {
+            let error = || WsError::Io(std::io::Error::other("WebSocket error"));
+            match open_sender.borrow_mut().take() {
+                Some(open_sender) => {
+                    let _ = open_sender.send(Err(error()));
+                }
+                None => {
+                    let _ = message_sender.unbounded_send(Err(error()));
+                }
+            }
+        })
+    };
+    socket.set_onopen(Some(on_open.as_ref().unchecked_ref()));
+    socket.set_onmessage(Some(on_message.as_ref().unchecked_ref()));
+    socket.set_onclose(Some(on_close.as_ref().unchecked_ref()));
+    socket.set_onerror(Some(on_error.as_ref().unchecked_ref()));
+
+    let web_socket = WasmWebSocket {
+        socket: SendWrapper::new(socket),
+        messages,
+        _callbacks: SendWrapper::new(Callbacks {
+            on_open,
+            on_message,
+            on_close,
+            on_error,
+        }),
+    };
+    match open.await {
+        Ok(Ok(())) => Ok((web_socket, Response::new(None))),
+        Ok(Err(err)) => Err(err),
+        Err(_) => Err(WsError::ConnectionClosed),
+    }
+}
+
+/// Converts an exception thrown by the browser into a WebSocket error.
+fn js_error(err: JsValue) -> WsError {
+    WsError::Io(std::io::Error::other(format!("{:?}", err)))
+}
@@ -0,0 +1,67 @@
+use std::fmt::{self, Debug, Formatter};
+use zeroize::Zeroize;
+
+/// A password or token supplied to the library, such as the password of
+/// `ConnectionDetails.setPassword()` or of a `Proxy`.
+///
+/// The value can only be read through `expose_secret()`, so that every place where it is used is
+/// explicit. It has no `Display` or `Serialize` implementation, its `Debug` implementation never
+/// prints it and it is wiped from memory when dropped.
+///
+/// When the `secrecy` feature is enabled, it can be converted from and into a
+/// `secrecy::SecretString`.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub struct SecretString(String);
+
+impl SecretString {
+    /// Creates a new secret from the given value.
+    pub fn new(secret: String) -> SecretString {
+        SecretString(secret)
+    }
+
+    /// Gets the value of the secret. The returned value should be used right away, and never
+    /// logged or stored elsewhere.
+    pub fn expose_secret(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<String> for SecretString {
+    fn from(secret: String) -> SecretString {
+        SecretString::new(secret)
+    }
+}
+
+impl From<&str> for SecretString {
+    fn from(secret: &str) -> SecretString {
+        SecretString::new(secret.to_string())
+    }
+}
+
+#[cfg(feature = "secrecy")]
+impl From<secrecy::SecretString> for SecretString {
+    fn from(secret: secrecy::SecretString) -> SecretString {
+        use secrecy::ExposeSecret;
+
+        SecretString::from(secret.expose_secret())
+    }
+}
+
+#[cfg(feature = "secrecy")]
+impl From<SecretString> for secrecy::SecretString {
+    fn from(secret: SecretString) -> secrecy::SecretString {
+        secrecy::SecretString::from(secret.expose_secret())
+    }
+}
+
+impl Debug for SecretString {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("SecretString(***)")
+    }
+}
+
+impl Drop for SecretString {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
@@ -0,0 +1,3095 @@
+use crate::client_message_listener::ClientMessageListener;
+use crate::client_metrics::MetricsRecorder;
+use crate::clock::{Clock, ClockSkew, ClockSkewEstimator, Instant};
+use crate::conflation::Conflator;
+use crate::connection_info::ConnectionInfo;
+#[cfg(feature = "reqwest")]
+use crate::connection_options::ReadBufferSettings;
+use crate::connection_options::{ExtraHeaders, MessageRecoveryPolicy};
+#[cfg(feature = "runtime-tokio")]
+use crate::connector::Connector;
+use crate::cookies::{self, CookieJar};
+use crate::credentials_provider::{Credentials, CredentialsProvider, ReauthenticationHandler};
+use crate::disconnect_info::{DisconnectInfo, DisconnectInitiator, CLOSE_NORMAL};
+use crate::dispatcher::{DispatchPool, EventDispatcher, PooledListeners, UpdateQueue};
+use crate::error::IllegalStateException;
+use crate::event_history::{ClientEventKind, EventHistory};
+#[cfg(feature = "test-util")]
+use crate::fault_injection::{FaultInjector, FaultySocket};
+#[cfg(feature = "reqwest")]
+use crate::http_transport::{HttpExecutor, HttpSocket};
+use crate::item_update::{FieldNames, FieldValue, FieldValues, ItemUpdate};
+use crate::logger::LogCategory;
+use crate::ls_client::{ClientStatus, ConnectionType, DisconnectionType, LogType, Transport};
+use crate::protocol::{self, encode_params};
+use crate::recording::{FrameDirection, Replay, SessionRecorder};
+use crate::request_interceptor::{self, InterceptingSocket, RequestInterceptors};
+use crate::retry_policy::RetryPolicy;
+use crate::runtime::tungstenite::handshake::client::Response;
+use crate::runtime::tungstenite::protocol::frame::coding::CloseCode;
+use crate::runtime::tungstenite::protocol::{CloseFrame, WebSocketConfig};
+use crate::runtime::tungstenite::{http::Request, Error as WsError, Message};
+use crate::runtime::{BoxedSocket, CurrentRuntime, Runtime};
+use crate::session_end_cause::SessionEndCause;
+use crate::subscription::{
+    find_subscription, find_subscription_mut, BackpressurePolicy, DispatchMode, Snapshot,
+    Subscription, SubscriptionMode,
+};
+
+use ahash::AHashMap;
+use futures_util::sink::Send as SendFuture;
+use futures_util::{Sink, SinkExt, StreamExt};
+use smallvec::smallvec;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::error::Error;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, SystemTime};
+use tokio::sync::Notify;
+use tracing::Level;
+use zeroize::Zeroize;
+
+/// Boxed error type returned by the session task. It must be `Send` so that the task can be
+/// spawned on the runtime.
+pub(crate) type SessionError = Box<dyn Error + Send + Sync>;
+
+/// Future opening a connection, returned by `Session::connect_socket()`. It doesn't borrow the
+/// session, so that it can be raced against the session's own signals.
+type ConnectFuture = Pin<Box<dyn Future<Output = Result<(BoxedSocket, Response), WsError>> + Send>>;
+
+/// TLCP notifications that count as "data notifications", i.e. the ones taken into account by
+/// the `LS_recovery_from` parameter of a `recover_session` request.
+const DATA_NOTIFICATIONS: &[&str] = &[
+    "U", "CS", "EOS", "OV", "CONF", "SUBOK", "SUBCMD", "UNSUB", "MPNREG", "MPNOK", "MPNDEL",
+    "MPNZERO", "MPNCONF", "MSGDONE", "MSGFAIL",
+];
+
+/// Maximum time to wait for the server to close the WebSocket connection on disconnection.
+const CLOSE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Reconnection settings resolved from `ConnectionOptions` when `connect()` is called.
+#[derive(Debug, Clone)]
+pub(crate) struct RetrySettings {
+    /// Policy deciding the delay before each new connection attempt and when to give up.
+    pub(crate) retry_policy: Arc<dyn RetryPolicy>,
+    /// Maximum time allowed to recover a session before falling back to a brand new session.
+    /// A zero value disables session recovery.
+    pub(crate) session_recovery_timeout: Duration,
+    /// Maximum time allowed to each connection attempt, from the DNS resolution to the
+    /// confirmation of the session by the server. `None` means no limit.
+    pub(crate) connect_deadline: Option<Duration>,
+    /// Interval between the WebSocket pings sent on each connection. `None` means no pings.
+    pub(crate) ping_interval: Option<Duration>,
+    /// Time allowed for each pong to arrive before the connection is considered stalled.
+    pub(crate) pong_timeout: Duration,
+    /// Time allowed to a streaming connection, beyond the keepalive interval, to receive any
+    /// message before it is considered stalled.
+    pub(crate) stalled_timeout: Duration,
+    /// Time allowed to a stalled streaming connection to receive any message before it is closed
+    /// and the session recovered.
+    pub(crate) reconnect_timeout: Duration,
+    /// What to do with the messages still unacknowledged when a connection is lost.
+    pub(crate) message_recovery_policy: MessageRecoveryPolicy,
+    /// Maximum number of consecutive times the credentials are refreshed through the
+    /// reauthentication handler after the server rejects them.
+    pub(crate) max_relogin_attempts: u32,
+}
+
+/// Outcome of a single connection handled by `Session::run_connection()`.
+enum ConnectionOutcome {
+    /// The client requested the session to terminate.
+    Shutdown,
+    /// The server refused or closed the session; no further attempt has to be made.
+    Terminated,
+    /// The connection was closed while the session could still be alive on the server, together
+    /// with the details of the closure, if the WebSocket connection itself was closed.
+    Closed(Option<DisconnectInfo>),
+    /// The current session has to be bound to a new connection right away, as requested by the
+    /// server through `LOOP` or by option changes that the server only accepts on a new
+    /// connection.
+    Rebind,
+    /// The connection was closed because of a message from the server exceeding the configured
+    /// size limits; the current session has to be bound to a new connection after the delay of
+    /// the `RetryPolicy`, together with the details of the closure.
+    Oversized(DisconnectInfo),
+}
+
+/// Internal task that owns the network connection of a `LightstreamerClient` and drives its
+/// Lightstreamer sessions over WebSocket streaming: it sends the session creation request, submits
+/// the active subscriptions once the session is confirmed and dispatches the received updates to
+/// the subscription listeners.
+///
+/// When the connection is lost, the task transparently tries to recover the current session
+/// (within `ConnectionOptions.setSessionRecoveryTimeout()`) or to create a new one, waiting
+/// between attempts as dictated by the configured `RetryPolicy`. Besides an explicit
+/// `disconnect()`, only a refusal or a closure from the server, or the retry policy giving up,
+/// terminates the task.
+///
+/// The session shares the subscription list, the client listeners and the client status with the
+/// `LightstreamerClient` that spawned it, so that both sides always see the same state.
+pub(crate) struct Session {
+    /// The WebSocket upgrade request used to open the connection.
+    ws_request: Request<()>,
+    /// Limits on the frames and messages received on each connection.
+    websocket_config: WebSocketConfig,
+    /// Recorded session replayed in place of the connections to the server, if any.
+    replay: Option<Arc<Replay>>,
+    /// Recorder of the frames exchanged with the server, if any.
+    recorder: Option<Arc<SessionRecorder>>,
+    /// Injector of faults in the connections, if any.
+    #[cfg(feature = "test-util")]
+    fault_injector: Option<Arc<FaultInjector>>,
+    /// Connector whose resources are shared with other clients, if any.
+    #[cfg(feature = "runtime-tokio")]
+    connector: Option<Connector>,
+    /// Chain of the interceptors of the outgoing requests.
+    request_interceptors: RequestInterceptors,
+    /// Extra headers sent with the WebSocket handshakes and the HTTP requests.
+    extra_headers: ExtraHeaders,
+    /// Recent events of the client, shared with it.
+    history: Arc<EventHistory>,
+    /// Executor of the requests of the HTTP connections, if they are enabled.
+    #[cfg(feature = "reqwest")]
+    http_executor: Option<HttpExecutor>,
+    /// Settings of the buffers of the HTTP connections.
+    #[cfg(feature = "reqwest")]
+    read_buffer: ReadBufferSettings,
+    /// Parameters of the `create_session` request, already resolved from the connection details
+    /// and options at the time `connect()` was called.
+    create_session_params: Vec<(&'static str, String)>,
+    /// Provider consulted for fresh credentials on each session creation, replacing the user
+    /// and password of `create_session_params`.
+    credentials_provider: Option<Arc<dyn CredentialsProvider>>,
+    /// Handler invoked before each session creation that replaces a lost session.
+    reauthentication_handler: Option<Arc<dyn ReauthenticationHandler>>,
+    /// Credentials supplied by the reauthentication handler, replacing the user and password of
+    /// `create_session_params`.
+    credentials: Option<Credentials>,
+    /// Whether a session has already been created by this task, so that the next creations
+    /// replace a lost session.
+    session_created: bool,
+    /// Number of consecutive times the credentials were refreshed after being rejected by the
+    /// server, since the last session creation.
+    relogin_attempts: u32,
+    /// Whether the credentials were rejected by the server, so that the reauthentication handler
+    /// has to be invoked before the next session creation.
+    relogin_pending: bool,
+    /// Whether the next connection has to bind the current session through `bind_session`, rather
+    /// than recover it, since the previous connection was left on purpose.
+    rebind_pending: bool,
+    /// Subscriptions shared with the client.
+    subscriptions: Arc<Mutex<Vec<Subscription>>>,
+    /// Queue of the listener events, shared with the client.
+    dispatcher: EventDispatcher,
+    /// Client status shared with the client.
+    status: Arc<Mutex<ClientStatus>>,
+    /// Logging type inherited from the client.
+    logging: LogType,
+    /// Signal used by the client to request the session to terminate.
+    shutdown_signal: Arc<Notify>,
+    /// Reconnection settings.
+    retry_settings: RetrySettings,
+    /// Time source of all the timers of the session.
+    clock: Arc<dyn Clock>,
+    /// Estimator of the offset between the clocks of the server and of the client.
+    clock_skew: ClockSkewEstimator,
+    /// ID of the current server session, if one has been established and not abandoned yet.
+    session_id: Option<String>,
+    /// Number of data notifications received in the current server session.
+    data_notifications: u64,
+    /// Number of the next data notifications to be skipped, since they were already received
+    /// before the session was recovered.
+    skipped_notifications: u64,
+    /// Progressive number of the control requests sent in the current server session.
+    request_id: usize,
+    /// Requests sent on the current connection and waiting to be acknowledged by the server, with
+    /// the instant they were issued at, indexed by request ID.
+    pending_requests: HashMap<usize, Instant>,
+    /// Health counters shared with the client.
+    metrics: Arc<MetricsRecorder>,
+    /// State of the session published for `LightstreamerClient.debugState()`, shared with the
+    /// client.
+    info: Arc<Mutex<SessionInfo>>,
+    /// Latest state of each item of each subscription, indexed by subscription ID and item position.
+    item_updates: AHashMap<usize, AHashMap<usize, ItemUpdate>>,
+    /// Worker pools of the subscriptions using `DispatchMode::Pooled`, indexed by subscription ID.
+    dispatch_pools: AHashMap<usize, DispatchPool>,
+    /// Backpressure queues of the subscriptions with a bounded `BackpressurePolicy`, indexed by
+    /// subscription ID.
+    update_queues: AHashMap<usize, Arc<UpdateQueue>>,
+    /// Conflators of the subscriptions with client-side conflation, indexed by subscription ID.
+    conflators: AHashMap<usize, Conflator>,
+    /// Updates received in the current frame and waiting to be dispatched together, with the
+    /// key of their subscription in the client list and its ordered pooled listeners.
+    update_batches: Vec<(usize, Option<PooledListeners>, Vec<ItemUpdate>)>,
+    /// Backpressure queue found full after the last update, which must drain before reading on.
+    full_update_queue: Option<Arc<UpdateQueue>>,
+    /// Number of fields of each subscription, as confirmed by the server, indexed by subscription
+    /// ID.
+    field_counts: AHashMap<usize, usize>,
+    /// 1-based positions of the key and command fields of each COMMAND subscription, as
+    /// confirmed by the server, indexed by subscription ID.
+    command_positions: AHashMap<usize, (usize, usize)>,
+    /// Interned names of the fields of each subscription, indexed by subscription ID.
+    field_names: AHashMap<usize, Arc<FieldNames>>,
+    /// Items whose snapshot has been fully received, indexed by subscription ID and item position.
+    ended_snapshots: HashSet<(usize, usize)>,
+    /// Last subscription ID used in the current server session.
+    subscription_id: usize,
+    /// Key in the client list of each subscription active in the current server session, indexed
+    /// by subscription ID.
+    active_subscriptions: HashMap<usize, usize>,
+    /// Subscription ID of each subscription request waiting to be confirmed by the server,
+    /// indexed by request ID, so that a refusal can be notified to the subscription.
+    subscription_requests: HashMap<usize, usize>,
+    /// Subscription changes requested by the client and waiting to be sent, shared with the client.
+    subscription_changes: SubscriptionChanges,
+    /// Current settings of the streaming connection.
+    stream_settings: StreamSettings,
+    /// Connection option changes made by the client and waiting to be applied, shared with the
+    /// client options.
+    option_changes: OptionChanges,
+    /// Settings notified by the server, shared with the client options.
+    server_settings: Arc<Mutex<ServerSettings>>,
+    /// Interval between the keepalive packets of the server on the current session, on which
+    /// the detection of stalled connections is based. `None` disables the detection.
+    keepalive_interval: Option<Duration>,
+    /// TLCP version negotiated through the handshake of the current connection and the time the
+    /// handshake was completed.
+    handshake: Option<(String, SystemTime)>,
+    /// Name of the server instance serving the current session, as notified by `SERVNAME`.
+    server_instance: Option<String>,
+    /// IP address of the client as seen by the server, as notified by `CLIENTIP`.
+    client_ip: Option<String>,
+    /// Description of the current connection, once bound to a session.
+    connection_info: Option<ConnectionInfo>,
+    /// Messages queued by the client and waiting to be sent, shared with the client.
+    messages: Arc<Mutex<VecDeque<PendingMessage>>>,
+    /// Signal used by the client to notify that new messages have been queued.
+    message_signal: Arc<Notify>,
+    /// Progressive number of the last message sent in each sequence in the current server session.
+    message_progs: HashMap<String, u64>,
+    /// Messages sent to the server and waiting for their outcome, indexed by sequence and
+    /// progressive number.
+    sent_messages: HashMap<(String, u64), PendingMessage>,
+    /// Messages sent to the server on the current connection whose request was not acknowledged
+    /// yet, indexed by request ID.
+    unacknowledged_messages: BTreeMap<usize, PendingMessage>,
+}
+
+/// Change to the subscription list requested by the client, to be forwarded to the server if a
+/// session is running.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum SubscriptionChange {
+    /// A subscription has been added to the client list with the given key.
+    Add(usize),
+    /// The items or fields of the subscription with the given key in the client list have
+    /// changed, so that it has to be subscribed to again.
+    Resubscribe(usize),
+    /// The requested max frequency of the subscription with the given key in the client list has
+    /// changed, so that it has to be reconfigured on the fly.
+    Reconfigure(usize),
+    /// The subscription with the given key has been removed from the client list, so that it has
+    /// to be deleted.
+    Remove(usize),
+}
+
+impl SubscriptionChange {
+    /// Key in the client list of the subscription the change refers to.
+    fn key(self) -> usize {
+        match self {
+            SubscriptionChange::Add(key)
+            | SubscriptionChange::Resubscribe(key)
+            | SubscriptionChange::Reconfigure(key)
+            | SubscriptionChange::Remove(key) => key,
+        }
+    }
+}
+
+/// Queue of the subscription changes requested by the client, shared by the client, its active
+/// subscriptions and the session task.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct SubscriptionChanges {
+    queue: Arc<Mutex<VecDeque<SubscriptionChange>>>,
+    signal: Arc<Notify>,
+}
+
+impl SubscriptionChanges {
+    /// Queues a change and wakes up the session task.
+    pub(crate) fn push(&self, change: SubscriptionChange) {
+        self.queue.lock().unwrap().push_back(change);
+        self.signal.notify_one();
+    }
+
+    /// Takes all the queued changes.
+    fn take(&self) -> Vec<SubscriptionChange> {
+        self.queue.lock().unwrap().drain(..).collect()
+    }
+
+    /// Gets the number of queued changes.
+    pub(crate) fn len(&self) -> usize {
+        self.queue.lock().unwrap().len()
+    }
+
+    /// Discards all the queued changes.
+    fn clear(&self) {
+        self.queue.lock().unwrap().clear();
+    }
+
+    /// Waits for new changes to be queued.
+    async fn notified(&self) {
+        self.signal.notified().await
+    }
+}
+
+/// Settings of the streaming connection negotiated with the server, resolved from
+/// `ConnectionOptions` when `connect()` is called and kept up to date with the later changes.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub(crate) struct StreamSettings {
+    /// Interval between the keepalive packets requested to the server. `None` lets the server
+    /// decide.
+    pub(crate) keepalive_interval: Option<Duration>,
+    /// Interval between the reverse heartbeats sent to the server. `None` disables them.
+    pub(crate) reverse_heartbeat_interval: Option<Duration>,
+    /// Maximum bandwidth (in kbps) requested to the server. `None` means unlimited.
+    pub(crate) requested_max_bandwidth: Option<f64>,
+    /// Whether the session is bound in polling mode rather than in streaming mode.
+    pub(crate) polling: bool,
+    /// Whether the connections are opened over HTTP rather than over WebSocket.
+    pub(crate) http: bool,
+    /// Time between the end of a poll and the start of the next one, in polling mode.
+    pub(crate) polling_interval: Duration,
+    /// Time the server is allowed to wait for data on each poll, in polling mode.
+    pub(crate) idle_timeout: Duration,
+}
+
+/// Settings notified by the server for the current session, shared by the session task and the
+/// client options.
+#[derive(Debug, Default)]
+pub(crate) struct ServerSettings {
+    /// Interval between the keepalive packets sent by the server on streaming connections, as
+    /// notified by the last `CONOK`.
+    pub(crate) keepalive_interval: Option<Duration>,
+}
+
+/// Change to the connection options made by the client, to be applied on the fly to the current
+/// connection if a session is running.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum OptionChange {
+    /// The keepalive interval has changed, so that the connection has to be rebound.
+    KeepaliveInterval(Option<Duration>),
+    /// The reverse heartbeat interval has changed.
+    ReverseHeartbeatInterval(Option<Duration>),
+    /// The requested max bandwidth has changed, so that the connection has to be constrained.
+    RequestedMaxBandwidth(Option<f64>),
+    /// The forced transport has changed, so that the connection may have to be rebound on the
+    /// new transport.
+    ForcedTransport(Option<Transport>),
+}
+
+/// Queue of the connection option changes made by the client, shared by its `ConnectionOptions`
+/// and the session task.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct OptionChanges {
+    queue: Arc<Mutex<VecDeque<OptionChange>>>,
+    signal: Arc<Notify>,
+}
+
+impl OptionChanges {
+    /// Queues a change and wakes up the session task.
+    pub(crate) fn push(&self, change: OptionChange) {
+        self.queue.lock().unwrap().push_back(change);
+        self.signal.notify_one();
+    }
+
+    /// Takes all the queued changes.
+    fn take(&self) -> Vec<OptionChange> {
+        self.queue.lock().unwrap().drain(..).collect()
+    }
+
+    /// Discards all the queued changes.
+    pub(crate) fn clear(&self) {
+        self.queue.lock().unwrap().clear();
+    }
+
+    /// Waits for new changes to be queued.
+    async fn notified(&self) {
+        self.signal.notified().await
+    }
+}
+
+/// State of the session task published for `LightstreamerClient.debugState()`.
+#[derive(Debug, Default)]
+pub(crate) struct SessionInfo {
+    /// ID of the current server session, if any.
+    pub(crate) session_id: Option<String>,
+    /// Key in the client list of each subscription active in the current server session, indexed
+    /// by subscription ID.
+    pub(crate) active_subscriptions: HashMap<usize, usize>,
+    /// Number of requests sent on the current connection and not acknowledged yet.
+    pub(crate) unacknowledged_requests: usize,
+    /// Number of messages sent and waiting for their outcome.
+    pub(crate) messages_awaiting_outcome: usize,
+    /// Current estimate of the offset between the clocks of the server and of the client.
+    pub(crate) clock_skew: Option<ClockSkew>,
+    /// Details about the closure of the last connection, if any.
+    pub(crate) last_disconnect: Option<DisconnectInfo>,
+    /// Description of the current connection, if bound to a session.
+    pub(crate) connection_info: Option<ConnectionInfo>,
+}
+
+/// Message submitted through `LightstreamerClient.sendMessage()`, waiting to be sent to the server
+/// or for its outcome.
+pub(crate) struct PendingMessage {
+    /// The text of the message.
+    pub(crate) message: String,
+    /// The sequence the message belongs to.
+    pub(crate) sequence: String,
+    /// Timeout (in milliseconds) the server waits for missing messages of the sequence.
+    pub(crate) delay_timeout: Option<u64>,
+    /// Listener notified of the processing outcome.
+    pub(crate) listener: Option<Box<dyn ClientMessageListener>>,
+    /// Progressive number assigned to the message in its sequence when it was first sent, kept
+    /// when the message is queued again to be resent after a connection loss.
+    pub(crate) prog: Option<u64>,
+}
+
+impl PendingMessage {
+    /// Queues the notification to the listener that the outcome of the message can no longer be
+    /// received.
+    pub(crate) fn abort(
+        self,
+        dispatcher: &EventDispatcher,
+        logging: LogType,
+        sent_on_network: bool,
+    ) {
+        if let Some(listener) = self.listener {
+            dispatcher.notify_message_listener(logging, "onAbort", move || {
+                listener.on_abort(&self.message, sent_on_network)
+            });
+        }
+    }
+}
+
+/// Schedule of the WebSocket pings sent on a connection, and the ping waiting for its pong.
+struct Pinger {
+    interval: Duration,
+    pong_timeout: Duration,
+    next_ping_at: Instant,
+    /// Payload and sending instant of the ping waiting for its pong.
+    outstanding: Option<(u64, Instant)>,
+    sent: u64,
+}
+
+impl Pinger {
+    fn new(interval: Duration, pong_timeout: Duration, now: Instant) -> Pinger {
+        Pinger {
+            interval,
+            pong_timeout,
+            next_ping_at: now + interval,
+            outstanding: None,
+            sent: 0,
+        }
+    }
+
+    /// Gets the instant the next ping is due, or the pong of the outstanding one is overdue.
+    fn next_deadline(&self) -> Instant {
+        match self.outstanding {
+            Some((_, sent_at)) => (sent_at + self.pong_timeout).min(self.next_ping_at),
+            None => self.next_ping_at,
+        }
+    }
+
+    fn is_pong_overdue(&self, now: Instant) -> bool {
+        self.outstanding
+            .is_some_and(|(_, sent_at)| now >= sent_at + self.pong_timeout)
+    }
+
+    /// Gets the payload of the ping to be sent, if one is due. No new ping is sent while the
+    /// previous one is waiting for its pong.
+    fn ping(&mut self, now: Instant) -> Option<Vec<u8>> {
+        if now < self.next_ping_at {
+            return None;
+        }
+        self.next_ping_at = now + self.interval;
+        if self.outstanding.is_some() {
+            return None;
+        }
+        self.sent += 1;
+        self.outstanding = Some((self.sent, now));
+        Some(self.sent.to_be_bytes().to_vec())
+    }
+
+    /// Gets the round-trip time of the outstanding ping, if the given pong answers it.
+    fn pong(&mut self, payload: &[u8], now: Instant) -> Option<Duration> {
+        let (sent, sent_at) = self.outstanding?;
+        if payload != sent.to_be_bytes() {
+            return None;
+        }
+        self.outstanding = None;
+        Some(now.saturating_duration_since(sent_at))
+    }
+}
+
+impl Session {
+    /// Creates a new session ready to be run on a separate task.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn new(
+        ws_request: Request<()>,
+        websocket_config: WebSocketConfig,
+        create_session_params: Vec<(&'static str, String)>,
+        subscriptions: Arc<Mutex<Vec<Subscription>>>,
+        dispatcher: EventDispatcher,
+        status: Arc<Mutex<ClientStatus>>,
+        logging: LogType,
+        shutdown_signal: Arc<Notify>,
+        retry_settings: RetrySettings,
+        clock: Arc<dyn Clock>,
+        messages: Arc<Mutex<VecDeque<PendingMessage>>>,
+        message_signal: Arc<Notify>,
+        subscription_changes: SubscriptionChanges,
+        stream_settings: StreamSettings,
+        option_changes: OptionChanges,
+        server_settings: Arc<Mutex<ServerSettings>>,
+        metrics: Arc<MetricsRecorder>,
+        info: Arc<Mutex<SessionInfo>>,
+        credentials_provider: Option<Arc<dyn CredentialsProvider>>,
+        reauthentication_handler: Option<Arc<dyn ReauthenticationHandler>>,
+        replay: Option<Arc<Replay>>,
+        recorder: Option<Arc<SessionRecorder>>,
+    ) -> Session {
+        Session {
+            ws_request,
+            websocket_config,
+            replay,
+            recorder,
+            #[cfg(feature = "test-util")]
+            fault_injector: None,
+            #[cfg(feature = "runtime-tokio")]
+            connector: None,
+            request_interceptors: Arc::new([]),
+            extra_headers: ExtraHeaders::default(),
+            history: Arc::default(),
+            #[cfg(feature = "reqwest")]
+            http_executor: None,
+            #[cfg(feature = "reqwest")]
+            read_buffer: ReadBufferSettings::default(),
+            create_session_params,
+            credentials_provider,
+            reauthentication_handler,
+            credentials: None,
+            session_created: false,
+            relogin_attempts: 0,
+            relogin_pending: false,
+            rebind_pending: false,
+            subscriptions,
+            dispatcher,
+            status,
+            logging,
+            shutdown_signal,
+            retry_settings,
+            clock_skew: ClockSkewEstimator::new(clock.now()),
+            clock,
+            session_id: None,
+            data_notifications: 0,
+            skipped_notifications: 0,
+            request_id: 0,
+            pending_requests: HashMap::new(),
+            metrics,
+            info,
+            item_updates: AHashMap::new(),
+            dispatch_pools: AHashMap::new(),
+            update_queues: AHashMap::new(),
+            conflators: AHashMap::new(),
+            update_batches: Vec::new(),
+            full_update_queue: None,
+            field_counts: AHashMap::new(),
+            command_positions: AHashMap::new(),
+            field_names: AHashMap::new(),
+            ended_snapshots: HashSet::new(),
+            subscription_id: 0,
+            active_subscriptions: HashMap::new(),
+            subscription_requests: HashMap::new(),
+            subscription_changes,
+            keepalive_interval: stream_settings.keepalive_interval,
+            handshake: None,
+            server_instance: None,
+            client_ip: None,
+            connection_info: None,
+            stream_settings,
+            option_changes,
+            server_settings,
+            messages,
+            message_signal,
+            message_progs: HashMap::new(),
+            sent_messages: HashMap::new(),
+            unacknowledged_messages: BTreeMap::new(),
+        }
+    }
+
+    /// Sets the injector of faults in the connections of the session.
+    #[cfg(feature = "test-util")]
+    pub(crate) fn set_fault_injector(&mut self, fault_injector: Option<Arc<FaultInjector>>) {
+        self.fault_injector = fault_injector;
+    }
+
+    /// Sets the connector whose resources are used to open the connections of the session.
+    #[cfg(feature = "runtime-tokio")]
+    pub(crate) fn set_connector(&mut self, connector: Option<Connector>) {
+        self.connector = connector;
+    }
+
+    /// Gets the cookie jar of the connector, if any, or the process-wide one.
+    fn cookie_jar(&self) -> &CookieJar {
+        #[cfg(feature = "runtime-tokio")]
+        if let Some(connector) = &self.connector {
+            return connector.cookie_jar();
+        }
+        cookies::global_jar()
+    }
+
+    /// Sets the history of the recent events of the client.
+    pub(crate) fn set_event_history(&mut self, history: Arc<EventHistory>) {
+        self.history = history;
+    }
+
+    /// Sets the chain of the interceptors of the outgoing requests.
+    pub(crate) fn set_request_interceptors(&mut self, request_interceptors: RequestInterceptors) {
+        self.request_interceptors = request_interceptors;
+    }
+
+    /// Sets the extra headers sent with the WebSocket handshakes and the HTTP requests.
+    pub(crate) fn set_extra_headers(&mut self, extra_headers: ExtraHeaders) {
+        self.extra_headers = extra_headers;
+    }
+
+    /// Sets the executor of the requests of the HTTP connections of the session, and the
+    /// settings of their buffers.
+    #[cfg(feature = "reqwest")]
+    pub(crate) fn set_http_executor(
+        &mut self,
+        http_executor: HttpExecutor,
+        read_buffer: ReadBufferSettings,
+    ) {
+        self.http_executor = Some(http_executor);
+        self.read_buffer = read_buffer;
+    }
+
+    /// Opens a WebSocket connection to the server, through the connector if any, or, when
+    /// replaying a recorded session, the next recorded connection. HTTP connections are opened
+    /// with the HTTP client instead, with no handshake. Unless replaying, the extra headers are
+    /// added and the requests go through the interceptors, if any.
+    fn connect_socket(&self, mut ws_request: Request<()>) -> ConnectFuture {
+        if let Some(replay) = &self.replay {
+            let socket = replay.connect();
+            return Box::pin(async move {
+                let socket: BoxedSocket = Box::new(socket?);
+                Ok((socket, Response::default()))
+            });
+        }
+        let extra_headers = &self.extra_headers;
+        if !extra_headers.session_creation_only {
+            ws_request
+                .headers_mut()
+                .extend(extra_headers.headers.clone());
+        }
+        let interceptors = Arc::clone(&self.request_interceptors);
+        #[cfg(feature = "reqwest")]
+        if let Some(http_executor) = self
+            .http_executor
+            .clone()
+            .filter(|_| self.stream_settings.http)
+        {
+            // Over HTTP, the headers restricted to session creation go with that request only.
+            let session_creation_headers = match extra_headers.session_creation_only {
+                true => extra_headers.headers.clone(),
+                false => Default::default(),
+            };
+            let socket = HttpSocket::new(
+                http_executor,
+                &ws_request,
+                &session_creation_headers,
+                interceptors,
+                self.read_buffer,
+            );
+            return Box::pin(async move {
+                let socket: BoxedSocket = Box::new(socket?);
+                Ok((socket, Response::default()))
+            });
+        }
+        // Over WebSocket, the headers restricted to session creation go with the handshakes of the
+        // connections that create a session, rather than recover one.
+        if extra_headers.session_creation_only && self.session_id.is_none() {
+            ws_request
+                .headers_mut()
+                .extend(extra_headers.headers.clone());
+        }
+        if !interceptors.is_empty() {
+            request_interceptor::intercept_handshake(&interceptors, ws_request.headers_mut());
+        }
+        // The interceptors are left out of the socket when there are none.
+        let intercept = move |socket: BoxedSocket| -> BoxedSocket {
+            if interceptors.is_empty() {
+                socket
+            } else {
+                Box::new(InterceptingSocket::new(socket, interceptors))
+            }
+        };
+        let websocket_config = self.websocket_config;
+        #[cfg(feature = "runtime-tokio")]
+        if let Some(connector) = self.connector.clone() {
+            return Box::pin(async move {
+                let (ws_stream, response) = connector
+                    .connect_websocket(ws_request, websocket_config)
+                    .await?;
+                Ok((intercept(Box::new(ws_stream)), response))
+            });
+        }
+        Box::pin(async move {
+            let (ws_stream, response) =
+                CurrentRuntime::connect_websocket(ws_request, websocket_config).await?;
+            Ok((intercept(Box::new(ws_stream)), response))
+        })
+    }
+
+    /// Runs the session, reconnecting as needed, until a shutdown is requested by the client or
+    /// the server refuses or closes the session. The client status is set to `DISCONNECTED`
+    /// before returning.
+    pub(crate) async fn run(mut self) {
+        // Number of consecutive connection attempts that failed since the last working connection.
+        let mut failed_attempts: u32 = 0;
+        // Instant at which the last working connection was lost.
+        let mut disconnected_at: Option<Instant> = None;
+        // Number of consecutive connections closed because of an oversized message, as the server
+        // is likely to send it again on the next one.
+        let mut oversized_messages: u32 = 0;
+        // Cause of the end of the session, if it is still in place when the task terminates.
+        let mut end_cause = SessionEndCause::Disconnected;
+        loop {
+            let outcome = self.run_connection().await;
+            if !matches!(outcome, Ok((ConnectionOutcome::Oversized(_), _))) {
+                oversized_messages = 0;
+            }
+            let (error, connected): (SessionError, bool) = match outcome {
+                Ok((ConnectionOutcome::Shutdown, _)) | Ok((ConnectionOutcome::Terminated, _)) => {
+                    break
+                }
+                Ok((ConnectionOutcome::Oversized(disconnect_info), _)) => {
+                    // The session is still in place, but it is bound to a new connection only
+                    // after the delay of the retry policy: the first oversized message counts as
+                    // the loss of a working connection and each further one in a row as a failed
+                    // attempt, so as not to loop on a message the server keeps sending.
+                    let Some(delay) = self
+                        .retry_settings
+                        .retry_policy
+                        .next_delay(oversized_messages, &disconnect_info)
+                    else {
+                        self.make_log(
+                            LogCategory::Connections,
+                            Level::ERROR,
+                            &format!(
+                                "Giving up rebinding after {} oversized messages",
+                                oversized_messages + 1
+                            ),
+                        );
+                        end_cause = SessionEndCause::RetriesExhausted;
+                        break;
+                    };
+                    oversized_messages += 1;
+                    self.make_log(
+                        LogCategory::Connections,
+                        Level::INFO,
+                        &format!("Rebinding session in {} ms", delay.as_millis()),
+                    );
+                    tokio::select! {
+                        _ = self.clock.sleep(delay) => {},
+                        _ = self.shutdown_signal.notified() => {
+                            self.make_log( LogCategory::Connections, Level::INFO, "Received shutdown signal" );
+                            break;
+                        },
+                    }
+                    failed_attempts = 0;
+                    disconnected_at = Some(self.clock.now());
+                    self.rebind_pending = true;
+                    continue;
+                }
+                Ok((ConnectionOutcome::Rebind, _)) => {
+                    // The session is still in place: it is bound to a new connection at once,
+                    // keeping its status and its pending messages.
+                    failed_attempts = 0;
+                    disconnected_at = Some(self.clock.now());
+                    self.rebind_pending = true;
+                    continue;
+                }
+                Ok((ConnectionOutcome::Closed(Some(disconnect_info)), connected)) => {
+                    (Box::new(disconnect_info), connected)
+                }
+                Ok((ConnectionOutcome::Closed(None), connected)) => {
+                    self.make_log(
+                        LogCategory::Connections,
+                        Level::INFO,
+                        "Connection to Lightstreamer server closed",
+                    );
+                    let error = std::io::Error::new(
+                        std::io::ErrorKind::ConnectionAborted,
+                        "Connection to Lightstreamer server closed",
+                    );
+                    (Box::new(error), connected)
+                }
+                Err((err, connected)) => {
+                    self.make_log(
+                        LogCategory::Connections,
+                        Level::WARN,
+                        &format!("Connection attempt failed: {}", err),
+                    );
+                    (err, connected)
+                }
+            };
+            self.requeue_messages();
+            if connected {
+                failed_attempts = 0;
+                disconnected_at = Some(self.clock.now());
+            } else {
+                failed_attempts += 1;
+            }
+            let delay = match self
+                .retry_settings
+                .retry_policy
+                .next_delay(failed_attempts, error.as_ref())
+            {
+                Some(delay) => delay,
+                None => {
+                    self.make_log(
+                        LogCategory::Connections,
+                        Level::ERROR,
+                        &format!(
+                            "Giving up reconnecting after {} failed attempts: {}",
+                            failed_attempts, error
+                        ),
+                    );
+                    end_cause = SessionEndCause::RetriesExhausted;
+                    break;
+                }
+            };
+            //
+            // Decide whether the current session can still be recovered or a new one is needed.
+            //
+            let recovery_timeout = self.retry_settings.session_recovery_timeout;
+            let can_recover = self.session_id.is_some()
+                && !recovery_timeout.is_zero()
+                && disconnected_at.is_some_and(|instant| {
+                    self.clock.now().saturating_duration_since(instant) < recovery_timeout
+                });
+            if can_recover {
+                set_status(
+                    &self.status,
+                    &self.dispatcher,
+                    &self.history,
+                    self.logging,
+                    ClientStatus::Disconnected(DisconnectionType::TryingRecovery),
+                );
+            } else {
+                if self.session_id.take().is_some() {
+                    self.make_log(
+                        LogCategory::Connections,
+                        Level::INFO,
+                        "Session can't be recovered, a new one will be created",
+                    );
+                    self.session_ended(SessionEndCause::Lost);
+                }
+                set_status(
+                    &self.status,
+                    &self.dispatcher,
+                    &self.history,
+                    self.logging,
+                    ClientStatus::Disconnected(DisconnectionType::WillRetry),
+                );
+            }
+            self.make_log(
+                LogCategory::Connections,
+                Level::INFO,
+                &format!("Retrying connection in {} ms", delay.as_millis()),
+            );
+            self.publish_info();
+            tokio::select! {
+                _ = self.clock.sleep(delay) => {},
+                _ = self.shutdown_signal.notified() => {
+                    self.make_log( LogCategory::Connections, Level::INFO, "Received shutdown signal" );
+                    break;
+                },
+            }
+            if !can_recover {
+                set_status(
+                    &self.status,
+                    &self.dispatcher,
+                    &self.history,
+                    self.logging,
+                    ClientStatus::Connecting,
+                );
+            }
+            self.metrics.reconnection();
+        }
+        self.abort_messages();
+        if self.session_id.take().is_some() {
+            self.session_ended(end_cause);
+        }
+        self.unsubscribe_all();
+        self.active_subscriptions.clear();
+        self.subscription_requests.clear();
+        self.pending_requests.clear();
+        self.publish_info();
+        set_status(
+            &self.status,
+            &self.dispatcher,
+            &self.history,
+            self.logging,
+            ClientStatus::Disconnected(DisconnectionType::None),
+        );
+    }
+
+    /// Opens a WebSocket connection and either creates a new session or, if a session is already
+    /// in place, recovers it. Then processes the messages from the server until the connection
+    /// is closed.
+    ///
+    /// Together with the outcome, returns whether a session was successfully established or
+    /// recovered on this connection.
+    async fn run_connection(&mut self) -> Result<(ConnectionOutcome, bool), (SessionError, bool)> {
+        let mut connected = false;
+        let outcome = self.process_connection(&mut connected).await;
+        self.connection_info = None;
+        match outcome {
+            Ok(outcome) => Ok((outcome, connected)),
+            Err(err) => Err((err, connected)),
+        }
+    }
+
+    async fn process_connection(
+        &mut self,
+        connected: &mut bool,
+    ) -> Result<ConnectionOutcome, SessionError> {
+        // Whether this connection binds the current session rather than recovering it.
+        let rebinding = std::mem::take(&mut self.rebind_pending) && self.session_id.is_some();
+        // Timer bounding the connection attempt until the session is confirmed by the server.
+        let connect_deadline = self.retry_settings.connect_deadline;
+        let deadline_sleep =
+            connect_deadline.map(|connect_deadline| self.clock.sleep(connect_deadline));
+        let deadline_timer = async {
+            match deadline_sleep {
+                Some(deadline_sleep) => deadline_sleep.await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::pin!(deadline_timer);
+
+        // Connect to the Lightstreamer server using WebSocket, sending the cookies currently
+        // stored for it.
+        let mut ws_request = self.ws_request.clone();
+        self.cookie_jar().apply_cookies(&mut ws_request);
+        let connect_started_at = self.clock.now();
+        let connection = tokio::select! {
+            connection = self.connect_socket(ws_request) => connection,
+            _ = &mut deadline_timer => {
+                return Err(connect_deadline_error(connect_deadline));
+            },
+            _ = self.shutdown_signal.notified() => {
+                self.make_log( LogCategory::Connections, Level::INFO, "Received shutdown signal" );
+                return Ok(ConnectionOutcome::Shutdown);
+            },
+        };
+        let ws_stream = match connection {
+            Ok((ws_stream, response)) => {
+                self.record(
+                    FrameDirection::Connected,
+                    &self.ws_request.uri().to_string(),
+                );
+                self.cookie_jar()
+                    .store_response_cookies(&self.ws_request, response.headers());
+                let date = response.headers().get("date");
+                let connected_at = self.clock.now();
+                self.clock_skew.handshake(
+                    connect_started_at,
+                    connected_at,
+                    date.and_then(|date| date.to_str().ok()),
+                );
+                // The subprotocol is missing from the recorded handshakes.
+                let protocol_version = response
+                    .headers()
+                    .get("sec-websocket-protocol")
+                    .and_then(|protocol| protocol.to_str().ok())
+                    .and_then(|protocol| protocol.strip_suffix(".lightstreamer.com"))
+                    .unwrap_or(crate::ls_client::LightstreamerClient::TLCP_VERSION);
+                self.handshake = Some((
+                    protocol_version.to_string(),
+                    self.clock_skew.system_time(connected_at),
+                ));
+                if let Some(server_header) = response.headers().get("server") {
+                    self.make_log(
+                        LogCategory::Connections,
+                        Level::INFO,
+                        &format!(
+                            "Connected to Lightstreamer server: {}",
+                            server_header.to_str().unwrap_or("")
+                        ),
+                    );
+                } else {
+                    self.make_log(
+                        LogCategory::Connections,
+                        Level::INFO,
+                        "Connected to Lightstreamer server",
+                    );
+                }
+                #[cfg(feature = "test-util")]
+                let ws_stream: BoxedSocket = match &self.fault_injector {
+                    Some(injector) => Box::new(FaultySocket::new(
+                        ws_stream,
+                        Arc::clone(injector),
+                        Arc::clone(&self.clock),
+                    )),
+                    None => ws_stream,
+                };
+                ws_stream
+            }
+            Err(err) => {
+                return Err(Box::new(std::io::Error::new(
+                    std::io::ErrorKind::ConnectionRefused,
+                    format!(
+                        "Failed to connect to Lightstreamer server with WebSocket: {}",
+                        err
+                    ),
+                )));
+            }
+        };
+
+        // Split the WebSocket stream into a write and a read stream.
+        let (mut write_stream, mut read_stream) = StreamExt::split(ws_stream);
+
+        //
+        // Initiate communication with the server by sending a 'wsok' message.
+        //
+        self.pending_requests.clear();
+        self.skipped_notifications = 0;
+        let wsok_sent_at = self.clock.now();
+        self.send_text(&mut write_stream, "wsok".to_string())
+            .await?;
+        let mut pings = self
+            .retry_settings
+            .ping_interval
+            .map(|interval| Pinger::new(interval, self.retry_settings.pong_timeout, wsok_sent_at));
+
+        // Instant of the last reverse heartbeat, or of the confirmation of the session.
+        let mut last_heartbeat_at = wsok_sent_at;
+        // Instant the next poll is due at, in polling mode.
+        let mut next_poll_at: Option<Instant> = None;
+        // Instant the last message was received from the server, and whether the connection has
+        // been reported as stalled since.
+        let mut last_received_at = wsok_sent_at;
+        let mut stalled = false;
+
+        //
+        // Start reading and processing messages from the server.
+        //
+        'messages: loop {
+            self.publish_info();
+            let next_flush = self
+                .conflators
+                .values()
+                .filter_map(Conflator::next_flush)
+                .min();
+            let flush_delay = next_flush.map_or(Duration::ZERO, |at| {
+                at.saturating_duration_since(self.clock.now())
+            });
+            let ping_delay = pings.as_ref().map_or(Duration::ZERO, |pings| {
+                pings
+                    .next_deadline()
+                    .saturating_duration_since(self.clock.now())
+            });
+            let heartbeat_interval = self.stream_settings.reverse_heartbeat_interval;
+            let heartbeat_delay = heartbeat_interval.map_or(Duration::ZERO, |interval| {
+                (last_heartbeat_at + interval).saturating_duration_since(self.clock.now())
+            });
+            let poll_delay = next_poll_at.map_or(Duration::ZERO, |at| {
+                at.saturating_duration_since(self.clock.now())
+            });
+            // Silence allowed before the connection is considered stalled or, once stalled, lost.
+            let silence_limit = self
+                .keepalive_interval
+                .filter(|_| *connected && !self.stream_settings.polling)
+                .map(|keepalive_interval| {
+                    let limit = keepalive_interval + self.retry_settings.stalled_timeout;
+                    if stalled {
+                        limit + self.retry_settings.reconnect_timeout
+                    } else {
+                        limit
+                    }
+                });
+            let silence_delay = silence_limit.map_or(Duration::ZERO, |limit| {
+                (last_received_at + limit).saturating_duration_since(self.clock.now())
+            });
+            tokio::select! {
+                message = read_stream.next() => {
+                    if let Some(Ok(_)) = message {
+                        last_received_at = self.clock.now();
+                        if stalled {
+                            stalled = false;
+                            self.make_log( LogCategory::Connections, Level::INFO, "Data received from server again: the connection is no longer stalled" );
+                            set_status(&self.status, &self.dispatcher, &self.history, self.logging, ClientStatus::Connected(self.connection_type()));
+                        }
+                    }
+                    match message {
+                        Some(Ok(Message::Text(text))) => {
+                            self.metrics.bytes_received(text.len());
+                            self.history.frame_received(text.len());
+                            self.record(FrameDirection::Received, &text);
+                            // Messages could include multiple submessages separated by /r/n.
+                            // Split the message into submessages and process each one separately.
+                            for line in protocol::split_frame(text) {
+                                let submessage = line.as_str();
+                                let notification = line.name();
+                                if DATA_NOTIFICATIONS.contains(&notification) {
+                                    self.data_notifications += 1;
+                                    if self.skipped_notifications > 0 {
+                                        self.skipped_notifications -= 1;
+                                        self.make_log( LogCategory::Protocol, Level::DEBUG, &format!("Skipped notification already received: {}", submessage) );
+                                        continue;
+                                    }
+                                }
+                                // Consecutive updates are dispatched together, in order with the other events.
+                                if notification != "U" {
+                                    self.flush_update_batches();
+                                }
+                                match notification {
+                                    //
+                                    // Errors from server.
+                                    //
+                                    "CONERR" => {
+                                        self.make_log( LogCategory::Connections, Level::ERROR, &format!("Received connection error from Lightstreamer server: {}", submessage) );
+                                        if self.session_id.take().is_some() {
+                                            // The session could not be recovered: a new one will be created.
+                                            self.session_ended(server_closed(submessage));
+                                            return Ok(ConnectionOutcome::Closed(None));
+                                        }
+                                        if self.relogin_due(submessage) {
+                                            return Ok(ConnectionOutcome::Closed(None));
+                                        }
+                                        self.notify_server_error(submessage);
+                                        return Ok(ConnectionOutcome::Terminated);
+                                    },
+                                    "END" => {
+                                        self.make_log( LogCategory::Connections, Level::ERROR, &format!("Session closed by Lightstreamer server: {}", submessage) );
+                                        if self.session_id.take().is_some() {
+                                            self.session_ended(server_closed(submessage));
+                                        }
+                                        self.notify_server_error(submessage);
+                                        return Ok(ConnectionOutcome::Terminated);
+                                    },
+                                    "REQERR" => {
+                                        self.make_log( LogCategory::Protocol, Level::ERROR, &format!("Received request error from Lightstreamer server: {}", submessage) );
+                                        self.acknowledge_request(submessage);
+                                        self.process_subscription_error(submessage);
+                                    },
+                                    //
+                                    // Session created or recovered successfully.
+                                    //
+                                    "CONOK" => {
+                                        let session_id = match submessage.split(',').nth(1) {
+                                            Some(session_id) => session_id.trim().to_string(),
+                                            None => {
+                                                return Err(Box::new(std::io::Error::new(
+                                                    std::io::ErrorKind::InvalidData,
+                                                    "Session ID not found in 'conok' message from server",
+                                                )));
+                                            }
+                                        };
+                                        *connected = true;
+                                        if self.session_id.is_none() {
+                                            self.server_instance = None;
+                                            self.client_ip = None;
+                                        }
+                                        let (protocol_version, connected_at) = self.handshake.clone().unwrap_or_else(|| {
+                                            (crate::ls_client::LightstreamerClient::TLCP_VERSION.to_string(), self.clock_skew.system_time(self.clock.now()))
+                                        });
+                                        self.connection_info = Some(ConnectionInfo {
+                                            transport: self.connection_type(),
+                                            protocol_version,
+                                            server_address: self.ws_request.uri().to_string(),
+                                            server_instance: self.server_instance.clone(),
+                                            client_ip: self.client_ip.clone(),
+                                            session_id: session_id.clone(),
+                                            connected_at,
+                                        });
+                                        self.process_keepalive(submessage);
+                                        self.clock_skew.stream_started(self.clock.now());
+                                        set_status(
+                                            &self.status,
+                                            &self.dispatcher,
+                                            &self.history, self.logging,
+                                            ClientStatus::Connected(self.connection_type()),
+                                        );
+                                        if rebinding {
+                                            self.make_log( LogCategory::Connections, Level::INFO, &format!("Session rebound with ID: {:?}", session_id) );
+                                        } else if self.session_id.is_some() {
+                                            self.make_log( LogCategory::Connections, Level::INFO, &format!("Session recovered with ID: {:?}", session_id) );
+                                        } else {
+                                            self.make_log( LogCategory::Connections, Level::DEBUG, &format!("Session creation confirmed by server: {}", submessage) );
+                                            self.make_log( LogCategory::Connections, Level::DEBUG, &format!("Session created with ID: {:?}", session_id) );
+                                            self.session_id = Some(session_id.clone());
+                                            self.dispatcher.notify_client_listeners(self.logging, "onSessionStart", move |listener| listener.on_session_start(&session_id));
+                                            self.session_created = true;
+                                            self.relogin_attempts = 0;
+                                            self.data_notifications = 0;
+                                            self.request_id = 0;
+                                            self.pending_requests.clear();
+                                            self.item_updates.clear();
+                                            self.dispatch_pools.clear();
+                                            self.update_queues.clear();
+                                            self.conflators.clear();
+                                            self.field_counts.clear();
+                                            self.command_positions.clear();
+                                            self.field_names.clear();
+                                            self.ended_snapshots.clear();
+                                            self.subscription_id = 0;
+                                            // The snapshots of the subscriptions of a previous session replace the state
+                                            // built on it.
+                                            for subscription in self.subscriptions.lock().unwrap().iter_mut() {
+                                                let refresh = subscription.is_subscribed();
+                                                subscription.clear_values();
+                                                subscription.set_snapshot_refresh(refresh);
+                                            }
+                                            // The subscriptions of a previous session are gone with it.
+                                            self.unsubscribe_all();
+                                            self.active_subscriptions.clear();
+                                            self.subscription_requests.clear();
+                                            // All the subscriptions are sent below, with their current settings.
+                                            self.subscription_changes.clear();
+                                            self.message_progs.clear();
+                                            self.abort_previous_session_messages();
+                                            //
+                                            // Subscribe to the desired items.
+                                            //
+                                            let requests = match self.subscription_requests() {
+                                                Ok(requests) => requests,
+                                                Err(err) => {
+                                                    self.make_log( LogCategory::Subscriptions, Level::ERROR, &format!("Invalid subscription: {}", err) );
+                                                    return Ok(ConnectionOutcome::Terminated);
+                                                }
+                                            };
+                                            for encoded_params in requests {
+                                                self.send_text(&mut write_stream, format!("control\r\n{}", encoded_params)).await?;
+                                                self.make_log( LogCategory::Subscriptions, Level::INFO, &format!("Sent subscription request: '{}'", encoded_params) );
+                                            }
+                                        }
+                                        //
+                                        // Send the subscription changes and the messages queued while waiting for the session.
+                                        //
+                                        for encoded_params in self.subscription_change_requests()? {
+                                            self.send_text(&mut write_stream, format!("control\r\n{}", encoded_params)).await?;
+                                            self.make_log( LogCategory::Subscriptions, Level::INFO, &format!("Sent subscription request: '{}'", encoded_params) );
+                                        }
+                                        for encoded_params in self.message_requests()? {
+                                            self.send_text(&mut write_stream, format!("msg\r\n{}", encoded_params)).await?;
+                                            self.metrics.message_sent();
+                                            self.make_log( LogCategory::Messages, Level::DEBUG, &format!("Sent message request: '{}'", encoded_params) );
+                                        }
+                                        last_heartbeat_at = self.clock.now();
+                                        if self.apply_option_changes(&mut write_stream).await? {
+                                            return Ok(ConnectionOutcome::Rebind);
+                                        }
+                                    },
+                                    //
+                                    // Rebind requested by the server, or end of a poll.
+                                    //
+                                    "LOOP" => {
+                                        if self.stream_settings.polling {
+                                            // The poll is over: the next one is bound on the same connection.
+                                            self.make_log( LogCategory::Connections, Level::DEBUG, &format!("Poll completed: {}", submessage) );
+                                            next_poll_at = Some(self.clock.now() + self.stream_settings.polling_interval);
+                                        } else {
+                                            self.make_log( LogCategory::Connections, Level::INFO, &format!("Rebind requested by server: {}", submessage) );
+                                            return Ok(ConnectionOutcome::Rebind);
+                                        }
+                                    },
+                                    //
+                                    // Notifications from server.
+                                    //
+                                    //
+                                    // Maximum update frequency granted to a subscription.
+                                    //
+                                    "CONF" => {
+                                        self.make_log( LogCategory::Subscriptions, Level::DEBUG, &format!("Received subscription frequency from server: '{}'", submessage) );
+                                        self.process_real_max_frequency(submessage);
+                                    },
+                                    //
+                                    // Progressive of the data notifications resent on a recovered session.
+                                    //
+                                    "PROG" => {
+                                        self.make_log( LogCategory::Protocol, Level::DEBUG, &format!("Received notification progressive from server: {}", submessage) );
+                                        if !self.process_prog(submessage) {
+                                            // Some notifications are lost: the state must be rebuilt on a new session.
+                                            self.session_id = None;
+                                            self.session_ended(SessionEndCause::Lost);
+                                            return Ok(ConnectionOutcome::Closed(None));
+                                        }
+                                    },
+                                    //
+                                    // Details about the server instance and the client address.
+                                    //
+                                    "SERVNAME" | "CLIENTIP" => {
+                                        self.make_log( LogCategory::Protocol, Level::INFO, &format!("Received notification from server: {}", submessage) );
+                                        self.process_connection_details(submessage);
+                                    },
+                                    "CONS" => {
+                                        self.make_log( LogCategory::Protocol, Level::INFO, &format!("Received notification from server: {}", submessage) );
+                                        // Don't do anything with these notifications for now.
+                                    },
+                                    "SYNC" => {
+                                        self.make_log( LogCategory::Protocol, Level::DEBUG, &format!("Received sync message from server: {}", submessage) );
+                                        self.process_sync(submessage);
+                                    },
+                                    "PROBE" => {
+                                        self.make_log( LogCategory::Protocol, Level::DEBUG, &format!("Received probe message from server: {}", submessage ) );
+                                        self.dispatcher.notify_client_listeners(self.logging, "onServerKeepalive", |listener| listener.on_server_keepalive());
+                                    },
+                                    "REQOK" => {
+                                        self.make_log( LogCategory::Protocol, Level::DEBUG, &format!("Received reqok message from server: '{}'", submessage ) );
+                                        self.acknowledge_request(submessage);
+                                    },
+                                    //
+                                    // Subscription confirmation from server.
+                                    //
+                                    "SUBOK" | "SUBCMD" => {
+                                        self.make_log( LogCategory::Subscriptions, Level::INFO, &format!("Subscription confirmed by server: '{}'", submessage) );
+                                        self.process_subscription_confirmation(submessage);
+                                    },
+                                    //
+                                    // End of the snapshot of an item.
+                                    //
+                                    "EOS" => {
+                                        self.make_log( LogCategory::Subscriptions, Level::DEBUG, &format!("Received end of snapshot from server: '{}'", submessage) );
+                                        self.process_end_of_snapshot(submessage);
+                                    },
+                                    "UNSUB" => {
+                                        self.make_log( LogCategory::Subscriptions, Level::INFO, &format!("Unsubscription confirmed by server: '{}'", submessage) );
+                                    },
+                                    //
+                                    // Outcome of messages sent to the server.
+                                    //
+                                    "MSGDONE" | "MSGFAIL" => {
+                                        self.make_log( LogCategory::Messages, Level::DEBUG, &format!("Received message outcome from server: '{}'", submessage) );
+                                        self.process_message_outcome(submessage);
+                                    },
+                                    //
+                                    // Data updates from server.
+                                    //
+                                    "U" => {
+                                        self.metrics.update_received();
+                                        self.process_update(submessage);
+                                    }
+                                    //
+                                    // Updates dropped by the server for an item.
+                                    //
+                                    "OV" => {
+                                        self.make_log( LogCategory::Subscriptions, Level::DEBUG, &format!("Received lost updates notification from server: '{}'", submessage) );
+                                        self.process_lost_updates(submessage);
+                                    }
+                                    //
+                                    // Connection confirmation from server.
+                                    //
+                                    "WSOK" => {
+                                        self.make_log( LogCategory::Connections, Level::INFO, &format!("Connection confirmed by server: '{}'", submessage) );
+                                        self.metrics.rtt(self.clock.now().saturating_duration_since(wsok_sent_at));
+                                        // The changes made so far are carried by the request parameters.
+                                        self.take_option_changes();
+                                        let stream_params = self.stream_params();
+                                        let stream_params = stream_params.iter().map(|(name, value)| (*name, value.as_str()));
+                                        let (request_name, mut encoded_params) = match &self.session_id {
+                                            //
+                                            // Bind the session to this connection.
+                                            //
+                                            Some(session_id) if rebinding => {
+                                                let mut params = vec![("LS_session", session_id.as_str())];
+                                                params.extend(stream_params);
+                                                ("bind_session", encode_params(params))
+                                            },
+                                            //
+                                            // Request session recovery.
+                                            //
+                                            Some(session_id) => {
+                                                let recovery_from = self.data_notifications.to_string();
+                                                let mut params = vec![
+                                                    ("LS_session", session_id.as_str()),
+                                                    ("LS_recovery_from", recovery_from.as_str()),
+                                                ];
+                                                params.extend(stream_params);
+                                                ("recover_session", encode_params(params))
+                                            },
+                                            //
+                                            // Request session creation.
+                                            //
+                                            None => {
+                                                if let Some(handler) = self.reauthentication_handler.as_ref().filter(|_| self.session_created || self.relogin_pending) {
+                                                    self.make_log( LogCategory::Connections, Level::DEBUG, "Invoking the reauthentication handler before creating a new session" );
+                                                    let handler = Arc::clone(handler);
+                                                    let credentials = tokio::select! {
+                                                        credentials = handler.reauthenticate() => credentials?,
+                                                        _ = self.shutdown_signal.notified() => {
+                                                            self.make_log( LogCategory::Connections, Level::INFO, "Received shutdown signal" );
+                                                            break 'messages;
+                                                        },
+                                                    };
+                                                    if credentials.is_some() {
+                                                        self.credentials = credentials;
+                                                    }
+                                                    self.relogin_pending = false;
+                                                }
+                                                let credentials = match &self.credentials_provider {
+                                                    Some(provider) => Some(provider.get_credentials()?),
+                                                    None => self.credentials.clone(),
+                                                };
+                                                let mut params: Vec<(&str, &str)> = self
+                                                    .create_session_params
+                                                    .iter()
+                                                    .filter(|(name, _)| credentials.is_none() || !matches!(*name, "LS_user" | "LS_password"))
+                                                    .map(|(name, value)| (*name, value.as_str()))
+                                                    .collect();
+                                                if let Some(credentials) = &credentials {
+                                                    if let Some(user) = credentials.get_user() {
+                                                        params.push(("LS_user", user));
+                                                    }
+                                                    if let Some(password) = credentials.get_password() {
+                                                        params.push(("LS_password", password.expose_secret()));
+                                                    }
+                                                }
+                                                params.extend(stream_params);
+                                                params.push(("LS_protocol", crate::ls_client::LightstreamerClient::TLCP_VERSION));
+                                                ("create_session", encode_params(params))
+                                            },
+                                        };
+                                        self.send_text(&mut write_stream, format!("{}\r\n{}\n", request_name, encoded_params)).await?;
+                                        self.make_log( LogCategory::Protocol, Level::DEBUG, &format!("Sent {} request: '{}'", request_name, redact_password(&encoded_params)) );
+                                        encoded_params.zeroize();
+                                    },
+                                    unexpected_message => {
+                                        self.make_log( LogCategory::Protocol, Level::WARN, &format!("Unexpected message received from server: '{:?}'", unexpected_message) );
+                                    },
+                                }
+                            }
+                            self.flush_update_batches();
+                            // Stop reading until the listeners drain a full blocking buffer.
+                            if let Some(queue) = self.full_update_queue.take() {
+                                self.make_log( LogCategory::Subscriptions, Level::DEBUG, "Backpressure buffer full: waiting for the listeners" );
+                                let shutdown = tokio::select! {
+                                    _ = queue.wait_for_space() => false,
+                                    _ = self.shutdown_signal.notified() => true,
+                                };
+                                if shutdown {
+                                    self.make_log( LogCategory::Connections, Level::INFO, "Received shutdown signal" );
+                                    break;
+                                }
+                            }
+                        },
+                        Some(Ok(Message::Ping(_))) => {
+                            // Pings are answered automatically by the WebSocket implementation.
+                        },
+                        Some(Ok(Message::Pong(payload))) => {
+                            if let Some(rtt) = pings.as_mut().and_then(|pings| pings.pong(&payload, self.clock.now())) {
+                                self.metrics.ping_rtt(rtt);
+                            }
+                        },
+                        Some(Ok(Message::Close(frame))) => {
+                            // Complete the close handshake by sending the reply queued by the
+                            // WebSocket implementation.
+                            if let Err(err) = write_stream.close().await {
+                                self.make_log( LogCategory::Connections, Level::DEBUG, &format!("Failed to reply to the close frame: {}", err) );
+                            }
+                            let disconnect_info = match frame {
+                                Some(frame) => DisconnectInfo::clean(DisconnectInitiator::Server, Some(frame.code.into()), frame.reason.as_str()),
+                                None => DisconnectInfo::clean(DisconnectInitiator::Server, None, ""),
+                            };
+                            self.disconnected(disconnect_info.clone());
+                            return Ok(ConnectionOutcome::Closed(Some(disconnect_info)));
+                        },
+                        Some(Ok(non_text_message)) => {
+                            return Err(Box::new(std::io::Error::new(
+                                std::io::ErrorKind::InvalidData,
+                                format!(
+                                    "Unexpected non-text message from server: {:?}",
+                                    non_text_message
+                                ),
+                            )));
+                        },
+                        Some(Err(WsError::Capacity(err))) => {
+                            let message = format!("Message from server too large: {}", err);
+                            self.make_log( LogCategory::Connections, Level::ERROR, &message );
+                            let frame = CloseFrame { code: CloseCode::Size, reason: "Message too large".into() };
+                            if let Err(err) = write_stream.send(Message::Close(Some(frame))).await {
+                                self.make_log( LogCategory::Connections, Level::DEBUG, &format!("Failed to send close frame: {}", err) );
+                            }
+                            let disconnect_info = DisconnectInfo::aborted(DisconnectInitiator::Client, &message);
+                            self.disconnected(disconnect_info.clone());
+                            self.dispatcher.notify_client_listeners(self.logging, "onConnectionError", move |listener| listener.on_connection_error(&message));
+                            // The connection is healthy, only the message is too large for the
+                            // configured limits: the session is bound to a new connection rather
+                            // than recovered as after a failure.
+                            return Ok(ConnectionOutcome::Oversized(disconnect_info));
+                        },
+                        Some(Err(err)) => {
+                            self.disconnected(DisconnectInfo::aborted(DisconnectInitiator::Server, &err.to_string()));
+                            return Err(Box::new(std::io::Error::new(
+                                std::io::ErrorKind::InvalidData,
+                                format!("Error reading message from server: {}", err),
+                            )));
+                        },
+                        None => {
+                            self.make_log( LogCategory::Connections, Level::DEBUG, "No more messages from server" );
+                            let disconnect_info = DisconnectInfo::aborted(DisconnectInitiator::Server, "Connection closed without a close frame");
+                            self.disconnected(disconnect_info.clone());
+                            return Ok(ConnectionOutcome::Closed(Some(disconnect_info)));
+                        },
+                    }
+                },
+                _ = self.subscription_changes.notified(), if *connected => {
+                    for encoded_params in self.subscription_change_requests()? {
+                        self.send_text(&mut write_stream, format!("control\r\n{}", encoded_params)).await?;
+                        self.make_log( LogCategory::Subscriptions, Level::INFO, &format!("Sent subscription request: '{}'", encoded_params) );
+                    }
+                },
+                _ = self.option_changes.notified(), if *connected => {
+                    if self.apply_option_changes(&mut write_stream).await? {
+                        return Ok(ConnectionOutcome::Rebind);
+                    }
+                },
+                _ = self.message_signal.notified(), if *connected => {
+                    for encoded_params in self.message_requests()? {
+                        self.send_text(&mut write_stream, format!("msg\r\n{}", encoded_params)).await?;
+                        self.metrics.message_sent();
+                        self.make_log( LogCategory::Messages, Level::DEBUG, &format!("Sent message request: '{}'", encoded_params) );
+                    }
+                },
+                _ = self.clock.sleep(flush_delay), if next_flush.is_some() => {
+                    self.flush_conflated_updates();
+                },
+                _ = self.clock.sleep(ping_delay), if pings.is_some() => {
+                    let Some(pings) = pings.as_mut() else { continue };
+                    let now = self.clock.now();
+                    if pings.is_pong_overdue(now) {
+                        self.make_log( LogCategory::Connections, Level::WARN, "No pong received from server: the connection is stalled" );
+                        set_status(&self.status, &self.dispatcher, &self.history, self.logging, ClientStatus::Stalled);
+                        self.disconnected(DisconnectInfo::aborted(DisconnectInitiator::Client, "No pong received from server"));
+                        return Err(Box::new(std::io::Error::new(
+                            std::io::ErrorKind::TimedOut,
+                            format!("No pong received within {:?}", self.retry_settings.pong_timeout),
+                        )));
+                    }
+                    if let Some(payload) = pings.ping(now) {
+                        write_stream.send(Message::Ping(payload.into())).await?;
+                        self.make_log( LogCategory::Protocol, Level::TRACE, "Sent ping" );
+                    }
+                },
+                _ = self.clock.sleep(heartbeat_delay), if *connected && heartbeat_interval.is_some() => {
+                    let Some(session_id) = &self.session_id else { continue };
+                    let encoded_params = encode_params([("LS_session", session_id)]);
+                    self.send_text(&mut write_stream, format!("heartbeat\r\n{}", encoded_params)).await?;
+                    self.make_log( LogCategory::Protocol, Level::TRACE, "Sent reverse heartbeat" );
+                    last_heartbeat_at = self.clock.now();
+                },
+                _ = self.clock.sleep(silence_delay), if silence_limit.is_some() => {
+                    if stalled {
+                        self.make_log( LogCategory::Connections, Level::WARN, "No data received from server: the connection is lost" );
+                        self.disconnected(DisconnectInfo::aborted(DisconnectInitiator::Client, "No data received from server"));
+                        return Err(Box::new(std::io::Error::new(
+                            std::io::ErrorKind::TimedOut,
+                            format!("No data received within {:?}", silence_limit.unwrap_or_default()),
+                        )));
+                    }
+                    self.make_log( LogCategory::Connections, Level::WARN, "No data received from server: the connection is stalled" );
+                    stalled = true;
+                    set_status(&self.status, &self.dispatcher, &self.history, self.logging, ClientStatus::Stalled);
+                },
+                _ = self.clock.sleep(poll_delay), if next_poll_at.is_some() => {
+                    next_poll_at = None;
+                    let Some(session_id) = &self.session_id else { continue };
+                    let stream_params = self.stream_params();
+                    let mut params = vec![("LS_session", session_id.as_str())];
+                    params.extend(stream_params.iter().map(|(name, value)| (*name, value.as_str())));
+                    let encoded_params = encode_params(params);
+                    self.send_text(&mut write_stream, format!("bind_session\r\n{}", encoded_params)).await?;
+                    self.make_log( LogCategory::Connections, Level::DEBUG, &format!("Sent bind_session request: '{}'", encoded_params) );
+                },
+                _ = &mut deadline_timer, if !*connected => {
+                    return Err(connect_deadline_error(connect_deadline));
+                },
+                _ = self.shutdown_signal.notified() => {
+                    self.make_log( LogCategory::Connections, Level::INFO, "Received shutdown signal" );
+                    break;
+                },
+            }
+        }
+
+        //
+        // Destroy the session and close the socket before returning.
+        //
+        if let Some(session_id) = self.session_id.take() {
+            self.request_id += 1;
+            let params = [
+                ("LS_reqId", self.request_id.to_string()),
+                ("LS_op", "destroy".to_string()),
+                ("LS_close_socket", "true".to_string()),
+            ];
+            let encoded_params = encode_params(params);
+            match self
+                .send_text(&mut write_stream, format!("control\r\n{}", encoded_params))
+                .await
+            {
+                Ok(()) => self.make_log(
+                    LogCategory::Connections,
+                    Level::DEBUG,
+                    &format!("Sent destroy request for session {:?}", session_id),
+                ),
+                Err(err) => self.make_log(
+                    LogCategory::Connections,
+                    Level::WARN,
+                    &format!("Failed to send destroy request: {}", err),
+                ),
+            }
+            self.session_ended(SessionEndCause::Disconnected);
+        }
+        let frame = CloseFrame {
+            code: CloseCode::from(CLOSE_NORMAL),
+            reason: "".into(),
+        };
+        if let Err(err) = write_stream.send(Message::Close(Some(frame))).await {
+            self.make_log(
+                LogCategory::Connections,
+                Level::WARN,
+                &format!("Failed to close connection: {}", err),
+            );
+        }
+        // Wait for the server to acknowledge the closure, without hanging on unresponsive servers.
+        let closed = async {
+            while let Some(Ok(message)) = read_stream.next().await {
+                if let Message::Close(frame) = message {
+                    return Some(frame);
+                }
+            }
+            None
+        };
+        let reply = tokio::select! {
+            reply = closed => reply,
+            _ = self.clock.sleep(CLOSE_TIMEOUT) => None,
+        };
+        let disconnect_info = match reply {
+            Some(Some(frame)) => DisconnectInfo::clean(
+                DisconnectInitiator::Client,
+                Some(frame.code.into()),
+                frame.reason.as_str(),
+            ),
+            Some(None) => DisconnectInfo::clean(DisconnectInitiator::Client, None, ""),
+            None => {
+                self.make_log(
+                    LogCategory::Connections,
+                    Level::WARN,
+                    "Timed out waiting for the server to close the connection",
+                );
+                DisconnectInfo::aborted(
+                    DisconnectInitiator::Client,
+                    "No close frame received from server",
+                )
+            }
+        };
+        self.disconnected(disconnect_info);
+
+        Ok(ConnectionOutcome::Shutdown)
+    }
+
+    /// Builds the encoded `control` requests needed to subscribe all the subscriptions currently
+    /// registered on the client.
+    fn subscription_requests(&mut self) -> Result<Vec<String>, SessionError> {
+        let subscriptions = Arc::clone(&self.subscriptions);
+        let subscriptions = subscriptions.lock().unwrap();
+        let mut requests = Vec::with_capacity(subscriptions.len());
+        for subscription in subscriptions.iter() {
+            if let Some(key) = subscription.client_key() {
+                requests.push(self.add_request(key, subscription)?);
+            }
+        }
+        Ok(requests)
+    }
+
+    /// Builds the encoded `control` requests for the subscription changes queued by the client.
+    /// Subscriptions that can't be subscribed to are reported and skipped.
+    fn subscription_change_requests(&mut self) -> Result<Vec<String>, SessionError> {
+        let changes = self.subscription_changes.take();
+        let subscriptions = Arc::clone(&self.subscriptions);
+        let mut subscriptions = subscriptions.lock().unwrap();
+        let mut requests = Vec::with_capacity(changes.len());
+        for change in changes {
+            let key = change.key();
+            let active_id = self
+                .active_subscriptions
+                .iter()
+                .find(|(_, &active_key)| active_key == key)
+                .map(|(&subscription_id, _)| subscription_id);
+            // The subscription is gone from the client list: only its server state is left.
+            if let SubscriptionChange::Remove(_) = change {
+                if let Some(subscription_id) = active_id {
+                    requests.push(self.delete_request(subscription_id)?);
+                }
+                continue;
+            }
+            let Some(subscription) = find_subscription_mut(&mut subscriptions, key) else {
+                continue;
+            };
+            match (change, active_id) {
+                // Already subscribed to, e.g. with the requests sent on session creation.
+                (SubscriptionChange::Add(_), Some(_)) => continue,
+                (SubscriptionChange::Reconfigure(_), Some(subscription_id)) => {
+                    requests.push(self.reconf_request(subscription_id, subscription)?);
+                    continue;
+                }
+                // Not subscribed to yet: the add request carries the current settings.
+                (SubscriptionChange::Reconfigure(_), None) => continue,
+                (SubscriptionChange::Resubscribe(_), Some(subscription_id)) => {
+                    requests.push(self.delete_request(subscription_id)?);
+                    self.set_subscribed(key, subscription, false);
+                    subscription.clear_values();
+                }
+                _ => {}
+            }
+            match self.add_request(key, subscription) {
+                Ok(request) => requests.push(request),
+                Err(err) => {
+                    self.make_log(
+                        LogCategory::Subscriptions,
+                        Level::ERROR,
+                        &format!("Invalid subscription: {}", err),
+                    );
+                }
+            }
+        }
+        Ok(requests)
+    }
+
+    /// Applies the connection option changes queued by the client to the stream settings,
+    /// returning the ones that actually changed a setting. Transports that can't be used are
+    /// reported and ignored.
+    fn take_option_changes(&mut self) -> Vec<OptionChange> {
+        let mut changes = Vec::new();
+        for change in self.option_changes.take() {
+            let settings = &mut self.stream_settings;
+            let changed = match change {
+                OptionChange::KeepaliveInterval(interval) => {
+                    std::mem::replace(&mut settings.keepalive_interval, interval) != interval
+                }
+                OptionChange::ReverseHeartbeatInterval(interval) => {
+                    std::mem::replace(&mut settings.reverse_heartbeat_interval, interval)
+                        != interval
+                }
+                OptionChange::RequestedMaxBandwidth(bandwidth) => {
+                    std::mem::replace(&mut settings.requested_max_bandwidth, bandwidth) != bandwidth
+                }
+                OptionChange::ForcedTransport(transport) => match is_polling_transport(transport) {
+                    Some(polling) => {
+                        let http = is_http_transport(transport);
+                        let changed = settings.polling != polling || settings.http != http;
+                        settings.polling = polling;
+                        settings.http = http;
+                        changed
+                    }
+                    None => {
+                        self.make_log(
+                            LogCategory::Connections,
+                            Level::WARN,
+                            &format!(
+                                "Cannot switch the session to {:?}: HTTP transports require the `reqwest` feature",
+                                transport
+                            ),
+                        );
+                        false
+                    }
+                },
+            };
+            if changed {
+                changes.push(change);
+            }
+        }
+        changes
+    }
+
+    /// Builds the encoded `control` requests applying to the current connection the option
+    /// changes queued by the client. Returns whether the connection has to be rebound as well, for
+    /// the changes that the server only accepts on a new connection.
+    fn option_change_requests(&mut self) -> Result<(Vec<String>, bool), SessionError> {
+        let mut requests = Vec::new();
+        let mut rebind = false;
+        for change in self.take_option_changes() {
+            match change {
+                // Keepalives are only sent on streaming connections.
+                OptionChange::KeepaliveInterval(_) if !self.stream_settings.polling => {
+                    self.make_log(
+                        LogCategory::Connections,
+                        Level::INFO,
+                        "Keepalive interval changed: rebinding the session",
+                    );
+                    rebind = true;
+                }
+                OptionChange::ForcedTransport(_) => {
+                    self.make_log(
+                        LogCategory::Connections,
+                        Level::INFO,
+                        &format!(
+                            "Transport changed: rebinding the session in {} mode",
+                            self.connection_type()
+                        ),
+                    );
+                    rebind = true;
+                }
+                OptionChange::RequestedMaxBandwidth(bandwidth) => {
+                    let request_id = self.next_acknowledged_request_id();
+                    let params = [
+                        ("LS_reqId", request_id.to_string()),
+                        ("LS_op", "constrain".to_string()),
+                        ("LS_requested_max_bandwidth", max_bandwidth_param(bandwidth)),
+                    ];
+                    requests.push(encode_params(params));
+                }
+                // The timer of the reverse heartbeats follows the settings.
+                _ => {}
+            }
+        }
+        Ok((requests, rebind))
+    }
+
+    /// Records the keepalive interval notified by a `CONOK` on a streaming connection, falling
+    /// back to the requested one when the server doesn't specify it.
+    fn process_keepalive(&mut self, submessage: &str) {
+        if self.stream_settings.polling {
+            return;
+        }
+        let notified = submessage
+            .split(',')
+            .nth(3)
+            .and_then(|keepalive| keepalive.trim().parse::<u64>().ok())
+            .filter(|&keepalive| keepalive > 0)
+            .map(Duration::from_millis);
+        self.keepalive_interval = notified.or(self.stream_settings.keepalive_interval);
+        self.server_settings.lock().unwrap().keepalive_interval = notified;
+    }
+
+    /// Gets the type of the connections of the session, as set by the stream settings.
+    fn connection_type(&self) -> ConnectionType {
+        match (self.stream_settings.http, self.stream_settings.polling) {
+            (false, false) => ConnectionType::WsStreaming,
+            (false, true) => ConnectionType::WsPolling,
+            (true, false) => ConnectionType::HttpStreaming,
+            (true, true) => ConnectionType::HttpPolling,
+        }
+    }
+
+    /// Applies to the current connection the option changes queued by the client, sending the
+    /// needed `control` requests. Returns whether the connection has to be rebound.
+    async fn apply_option_changes<S>(&mut self, write_stream: &mut S) -> Result<bool, SessionError>
+    where
+        S: Sink<Message, Error = WsError> + Unpin,
+    {
+        let (requests, rebind) = self.option_change_requests()?;
+        for encoded_params in requests {
+            self.send_text(write_stream, format!("control\r\n{}", encoded_params))
+                .await?;
+            self.make_log(
+                LogCategory::Connections,
+                Level::INFO,
+                &format!("Sent constrain request: '{}'", encoded_params),
+            );
+        }
+        Ok(rebind)
+    }
+
+    /// Gets the parameters of the `create_session`, `recover_session` and `bind_session` requests
+    /// carrying the current stream settings.
+    fn stream_params(&self) -> Vec<(&'static str, String)> {
+        let settings = &self.stream_settings;
+        let mut params = Vec::new();
+        if settings.polling {
+            params.push(("LS_polling", "true".to_string()));
+            params.push((
+                "LS_polling_millis",
+                settings.polling_interval.as_millis().to_string(),
+            ));
+            params.push((
+                "LS_idle_millis",
+                settings.idle_timeout.as_millis().to_string(),
+            ));
+        } else if let Some(keepalive_interval) = settings.keepalive_interval {
+            params.push((
+                "LS_keepalive_millis",
+                keepalive_interval.as_millis().to_string(),
+            ));
+        }
+        if let Some(reverse_heartbeat_interval) = settings.reverse_heartbeat_interval {
+            params.push((
+                "LS_inactivity_millis",
+                reverse_heartbeat_interval.as_millis().to_string(),
+            ));
+        }
+        if let Some(bandwidth) = settings.requested_max_bandwidth {
+            params.push((
+                "LS_requested_max_bandwidth",
+                max_bandwidth_param(Some(bandwidth)),
+            ));
+        }
+        params
+    }
+
+    /// Builds the encoded `add` control request for the subscription with the given key in the
+    /// client list, registering it as active under a new subscription ID, so that updates can be
+    /// routed back to it.
+    fn add_request(
+        &mut self,
+        key: usize,
+        subscription: &Subscription,
+    ) -> Result<String, SessionError> {
+        //
+        // Gather all the necessary subscription parameters.
+        //
+        let ls_group = match subscription.get_item_group() {
+            Some(item_group) => item_group.to_string(),
+            None => match subscription.get_items() {
+                Some(items) => items.join(" "),
+                None => {
+                    return Err(Box::new(IllegalStateException::new(
+                        "No item group or items found in subscription.",
+                    )));
+                }
+            },
+        };
+        let ls_schema = match subscription.get_field_schema() {
+            Some(field_schema) => field_schema.to_string(),
+            None => match subscription.get_fields() {
+                Some(fields) => fields.join(" "),
+                None => {
+                    return Err(Box::new(IllegalStateException::new(
+                        "No field schema or fields found in subscription.",
+                    )));
+                }
+            },
+        };
+        self.request_id += 1;
+        self.subscription_id += 1;
+        let ls_req_id = self.request_id.to_string();
+        let ls_sub_id = self.subscription_id.to_string();
+        let ls_mode = subscription.get_mode().to_string();
+        let ls_data_adapter = match subscription.get_data_adapter() {
+            Some(data_adapter) => data_adapter.to_string(),
+            None => "".to_string(),
+        };
+        // With no preference, the server decides whether to send the snapshot.
+        let ls_snapshot = match subscription.get_requested_snapshot() {
+            Some(Snapshot::None) | None => String::new(),
+            Some(snapshot) => snapshot.to_string(),
+        };
+        let ls_requested_buffer_size = subscription
+            .get_requested_buffer_size()
+            .map(ToString::to_string);
+        let ls_requested_max_frequency = subscription
+            .get_requested_max_frequency()
+            .map(|freq| max_frequency_param(*freq));
+        //
+        // Prepare the subscription request.
+        //
+        let mut params: Vec<(&str, &str)> = vec![
+            ("LS_data_adapter", &ls_data_adapter),
+            ("LS_reqId", &ls_req_id),
+            ("LS_op", "add"),
+            ("LS_subId", &ls_sub_id),
+            ("LS_mode", &ls_mode),
+            ("LS_group", &ls_group),
+            ("LS_schema", &ls_schema),
+            ("LS_ack", "false"),
+        ];
+        // Remove the data adapter parameter if not specified.
+        if ls_data_adapter.is_empty() {
+            params.remove(0);
+        }
+        if !ls_snapshot.is_empty() {
+            params.push(("LS_snapshot", &ls_snapshot));
+        }
+        if let Some(ls_requested_buffer_size) = &ls_requested_buffer_size {
+            params.push(("LS_requested_buffer_size", ls_requested_buffer_size));
+        }
+        if let Some(ls_requested_max_frequency) = &ls_requested_max_frequency {
+            params.push(("LS_requested_max_frequency", ls_requested_max_frequency));
+        }
+        let request = encode_params(params);
+        self.active_subscriptions.insert(self.subscription_id, key);
+        self.subscription_requests
+            .insert(self.request_id, self.subscription_id);
+        Ok(request)
+    }
+
+    /// Builds the encoded `reconf` control request that changes on the fly the max frequency of
+    /// the subscription with the given ID.
+    fn reconf_request(
+        &mut self,
+        subscription_id: usize,
+        subscription: &Subscription,
+    ) -> Result<String, SessionError> {
+        let request_id = self.next_acknowledged_request_id();
+        let ls_requested_max_frequency = subscription.get_requested_max_frequency().map_or_else(
+            || "unlimited".to_string(),
+            |freq| max_frequency_param(*freq),
+        );
+        let params = [
+            ("LS_reqId", request_id.to_string()),
+            ("LS_op", "reconf".to_string()),
+            ("LS_subId", subscription_id.to_string()),
+            ("LS_requested_max_frequency", ls_requested_max_frequency),
+        ];
+        Ok(encode_params(params))
+    }
+
+    /// Builds the encoded `delete` control request for the subscription with the given ID,
+    /// forgetting its state. Updates still received for it are ignored.
+    fn delete_request(&mut self, subscription_id: usize) -> Result<String, SessionError> {
+        let request_id = self.next_acknowledged_request_id();
+        self.forget_subscription(subscription_id);
+        let params = [
+            ("LS_reqId", request_id.to_string()),
+            ("LS_op", "delete".to_string()),
+            ("LS_subId", subscription_id.to_string()),
+        ];
+        Ok(encode_params(params))
+    }
+
+    /// Forgets the state of the subscription with the given ID, so that the updates still
+    /// received for it are ignored.
+    fn forget_subscription(&mut self, subscription_id: usize) {
+        self.active_subscriptions.remove(&subscription_id);
+        self.subscription_requests
+            .retain(|_, requested_id| *requested_id != subscription_id);
+        self.item_updates.remove(&subscription_id);
+        self.dispatch_pools.remove(&subscription_id);
+        self.update_queues.remove(&subscription_id);
+        self.conflators.remove(&subscription_id);
+        self.field_counts.remove(&subscription_id);
+        self.command_positions.remove(&subscription_id);
+        self.field_names.remove(&subscription_id);
+        self.ended_snapshots
+            .retain(|&(ended_id, _)| ended_id != subscription_id);
+    }
+
+    /// Builds the encoded `msg` requests for the messages queued by the client, moving them to the
+    /// messages waiting for their acknowledgement. Every message gets the next progressive number
+    /// of its sequence, unless it is being resent and already has one.
+    fn message_requests(&mut self) -> Result<Vec<String>, SessionError> {
+        let queued_messages: Vec<PendingMessage> =
+            self.messages.lock().unwrap().drain(..).collect();
+        let mut requests = Vec::with_capacity(queued_messages.len());
+        for mut pending_message in queued_messages {
+            let prog = match pending_message.prog {
+                Some(prog) => prog,
+                None => {
+                    let prog = self
+                        .message_progs
+                        .entry(pending_message.sequence.clone())
+                        .or_insert(0);
+                    *prog += 1;
+                    *prog
+                }
+            };
+            pending_message.prog = Some(prog);
+            let request_id = self.next_acknowledged_request_id();
+            let mut params = vec![
+                ("LS_reqId", request_id.to_string()),
+                ("LS_message", pending_message.message.clone()),
+                ("LS_sequence", pending_message.sequence.clone()),
+                ("LS_msg_prog", prog.to_string()),
+                ("LS_outcome", pending_message.listener.is_some().to_string()),
+            ];
+            if let Some(delay_timeout) = pending_message.delay_timeout {
+                params.push(("LS_max_wait", delay_timeout.to_string()));
+            }
+            requests.push(encode_params(params));
+            self.unacknowledged_messages
+                .insert(request_id, pending_message);
+        }
+        Ok(requests)
+    }
+
+    /// Processes a `MSGDONE` or `MSGFAIL` notification, dispatching the outcome to the listener of
+    /// the related message.
+    ///
+    /// The raw submessage is used, since the sequence name and the response are case sensitive.
+    fn process_message_outcome(&mut self, submessage: &str) {
+        let arguments: Vec<&str> = submessage.trim().splitn(5, ',').collect();
+        let sequence = arguments.get(1).unwrap_or(&"").to_string();
+        let prog = arguments.get(2).unwrap_or(&"").parse::<u64>().unwrap_or(0);
+        // The outcome may overtake the acknowledgement of the request.
+        let unacknowledged = self
+            .unacknowledged_messages
+            .iter()
+            .find(|(_, pending_message)| {
+                pending_message.sequence == sequence && pending_message.prog == Some(prog)
+            })
+            .map(|(&request_id, _)| request_id);
+        let pending_message = match self.sent_messages.remove(&(sequence, prog)).or_else(|| {
+            unacknowledged.and_then(|request_id| self.unacknowledged_messages.remove(&request_id))
+        }) {
+            Some(pending_message) => pending_message,
+            None => {
+                self.make_log(
+                    LogCategory::Messages,
+                    Level::DEBUG,
+                    &format!("No listener waiting for message outcome: {}", submessage),
+                );
+                return;
+            }
+        };
+        let Some(listener) = pending_message.listener else {
+            return;
+        };
+        let message = pending_message.message;
+        let logging = self.logging;
+        if arguments[0].eq_ignore_ascii_case("msgdone") {
+            // The response is optional and may contain commas.
+            let response = submessage
+                .trim()
+                .splitn(4, ',')
+                .nth(3)
+                .unwrap_or("")
+                .to_string();
+            self.dispatcher
+                .notify_message_listener(logging, "onProcessed", move || {
+                    listener.on_processed(&message, Some(&response))
+                });
+            return;
+        }
+        let code = arguments.get(3).unwrap_or(&"").parse::<i32>().unwrap_or(0);
+        let error = arguments.get(4).unwrap_or(&"").to_string();
+        match code {
+            // Message discarded by the server (e.g. timed out or overtaken in its sequence).
+            38 | 39 => self
+                .dispatcher
+                .notify_message_listener(logging, "onDiscarded", move || {
+                    listener.on_discarded(&message)
+                }),
+            // Message refused by the Metadata Adapter.
+            code if code <= 0 => {
+                self.dispatcher
+                    .notify_message_listener(logging, "onDeny", move || {
+                        listener.on_deny(&message, code, &error)
+                    })
+            }
+            _ => self
+                .dispatcher
+                .notify_message_listener(logging, "onError", move || listener.on_error(&message)),
+        }
+    }
+
+    /// Aborts all the messages still queued or waiting for an outcome, notifying their listeners
+    /// through `ClientMessageListener.onAbort()`.
+    fn abort_messages(&mut self) {
+        for (_, pending_message) in self.sent_messages.drain() {
+            pending_message.abort(&self.dispatcher, self.logging, true);
+        }
+        for (_, pending_message) in std::mem::take(&mut self.unacknowledged_messages) {
+            pending_message.abort(&self.dispatcher, self.logging, true);
+        }
+        let queued_messages: Vec<PendingMessage> =
+            self.messages.lock().unwrap().drain(..).collect();
+        for pending_message in queued_messages {
+            let sent_on_network = pending_message.prog.is_some();
+            pending_message.abort(&self.dispatcher, self.logging, sent_on_network);
+        }
+    }
+
+    /// Handles the messages left pending by a lost connection according to the message recovery
+    /// policy: either they are all aborted, or the unacknowledged ones are queued again, ahead of
+    /// the messages not sent yet, to be resent on the next connection.
+    fn requeue_messages(&mut self) {
+        if self.retry_settings.message_recovery_policy == MessageRecoveryPolicy::AbortOnDisconnect {
+            self.abort_messages();
+            return;
+        }
+        let mut queued_messages = self.messages.lock().unwrap();
+        for (_, pending_message) in std::mem::take(&mut self.unacknowledged_messages)
+            .into_iter()
+            .rev()
+        {
+            queued_messages.push_front(pending_message);
+        }
+    }
+
+    /// Aborts the messages whose outcome was bound to a session that could not be recovered. The
+    /// messages queued again to be resent are aborted as well, unless the message recovery policy
+    /// allows resending them on the new session, where they get new progressive numbers.
+    fn abort_previous_session_messages(&mut self) {
+        for (_, pending_message) in self.sent_messages.drain() {
+            pending_message.abort(&self.dispatcher, self.logging, true);
+        }
+        let resend =
+            self.retry_settings.message_recovery_policy == MessageRecoveryPolicy::ResendAlways;
+        let queued_messages: Vec<PendingMessage> =
+            self.messages.lock().unwrap().drain(..).collect();
+        let mut kept_messages = VecDeque::with_capacity(queued_messages.len());
+        for mut pending_message in queued_messages {
+            if pending_message.prog.is_none() {
+                kept_messages.push_back(pending_message);
+            } else if resend {
+                pending_message.prog = None;
+                kept_messages.push_back(pending_message);
+            } else {
+                pending_message.abort(&self.dispatcher, self.logging, true);
+            }
+        }
+        // Messages queued by the client in the meantime go after the kept ones.
+        let mut messages = self.messages.lock().unwrap();
+        kept_messages.extend(messages.drain(..));
+        *messages = kept_messages;
+    }
+
+    /// Processes a `U` (update) notification, merging the received values into the current state
+    /// of the involved item and dispatching the resulting `ItemUpdate` to the subscription listeners.
+    fn process_update(&mut self, submessage: &str) {
+        // Parse arguments from the received message.
+        let update = protocol::parse_update(submessage);
+        let subscription_id = update.subscription_id;
+        //
+        // Extract the subscription from the first argument.
+        //
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        let Some(subscription) = self
+            .active_subscriptions
+            .get(&subscription_id)
+            .and_then(|&key| find_subscription_mut(&mut subscriptions, key))
+        else {
+            // Updates can still arrive for a subscription being deleted.
+            self.make_log(
+                LogCategory::Subscriptions,
+                Level::DEBUG,
+                &format!("Subscription not found for ID: {}", subscription_id),
+            );
+            return;
+        };
+        //
+        // Extract the item from the second argument.
+        //
+        let item_index = update.item_index;
+        // Items of an item group are only known by position.
+        let item = subscription
+            .get_items()
+            .and_then(|items| items.get(item_index.wrapping_sub(1)));
+        //
+        // Determine if the update is a snapshot or real-time update based on the subscription parameters.
+        //
+        let is_snapshot = match subscription.get_requested_snapshot() {
+            Some(Snapshot::Yes | Snapshot::Number(_)) => match subscription.get_mode() {
+                SubscriptionMode::Merge => {
+                    if update.values == "$" {
+                        // EOS notification received
+                        true
+                    } else {
+                        // If item doesn't exist in item_updates yet, the first update
+                        // is always a snapshot.
+                        !self
+                            .item_updates
+                            .get(&subscription_id)
+                            .is_some_and(|item_updates| item_updates.contains_key(&item_index))
+                    }
+                }
+                // The snapshot lasts until the end-of-snapshot notification for the item.
+                SubscriptionMode::Distinct | SubscriptionMode::Command => !self
+                    .ended_snapshots
+                    .contains(&(subscription_id, item_index)),
+                _ => false,
+            },
+            _ => false,
+        };
+
+        //
+        // Get the field names of the subscription. Fields of a field schema are only known by
+        // position, so their positions are used as names. The names are interned once per
+        // subscription and shared by all its updates, which only carry their values.
+        //
+        let subscription_fields: Arc<FieldNames> = match self.field_names.get(&subscription_id) {
+            Some(field_names) => Arc::clone(field_names),
+            None => {
+                let field_names = Arc::new(match subscription.get_fields() {
+                    Some(fields) => FieldNames::new(fields.iter().map(String::as_str)),
+                    None => {
+                        let field_count = self
+                            .field_counts
+                            .get(&subscription_id)
+                            .copied()
+                            .unwrap_or_else(|| {
+                                update
+                                    .values
+                                    .split('|')
+                                    .map(|value| match value.strip_prefix('^') {
+                                        Some(count) => count.parse().unwrap_or(1),
+                                        None => 1,
+                                    })
+                                    .sum()
+                            });
+                        FieldNames::from_schema(field_count)
+                    }
+                });
+                // Without a confirmed field count, the names are only valid for this update.
+                if subscription.get_fields().is_some()
+                    || self.field_counts.contains_key(&subscription_id)
+                {
+                    self.field_names
+                        .insert(subscription_id, Arc::clone(&field_names));
+                }
+                field_names
+            }
+        };
+        // The new values of the fields, indexed by position.
+        let mut changes: FieldValues<FieldValue> =
+            smallvec![FieldValue::Unchanged; subscription_fields.len()];
+
+        let previous = self
+            .item_updates
+            .get(&subscription_id)
+            .and_then(|item_updates| item_updates.get(&item_index))
+            .map_or(&[][..], ItemUpdate::held_values);
+        let skipped = protocol::decode_update_values(update.values, previous, &mut changes);
+        if skipped > 0 {
+            self.make_log(
+                LogCategory::Subscriptions,
+                Level::WARN,
+                &format!(
+                    "Skipped {} field values in the unsupported TLCP-diff format or with a JSON Patch that can't be applied: '{}'",
+                    skipped, submessage
+                ),
+            );
+        }
+        if *subscription.get_mode() == SubscriptionMode::Command {
+            let positions = self.command_positions.get(&subscription_id).copied();
+            null_deleted_fields(positions, &subscription_fields, &mut changes);
+        }
+
+        //
+        // Take the proper item_update from item_updates and update it with changed fields.
+        // If the item_update doesn't exist yet, create a new one.
+        //
+        let item_updates = self.item_updates.entry(subscription_id).or_default();
+        let mut current_item_update: ItemUpdate = match item_updates.get_mut(&item_index) {
+            Some(item_update) => {
+                // RAW updates are independent events rather than changes to a state, so
+                // each one carries all its values, including the ones sent as unchanged.
+                item_update
+                    .apply_changes(changes, *subscription.get_mode() == SubscriptionMode::Raw);
+                item_update.is_snapshot = is_snapshot;
+                item_update.is_refresh = is_snapshot && subscription.is_snapshot_refresh();
+                item_update.clone()
+            }
+            None => {
+                // Create a new item_update and add it to item_updates.
+                let mut item_update = ItemUpdate::from_changes(
+                    item.cloned(),
+                    item_index,
+                    subscription_fields,
+                    changes.into_vec(),
+                    is_snapshot,
+                );
+                item_update.is_refresh = is_snapshot && subscription.is_snapshot_refresh();
+                item_updates.insert(item_index, item_update.clone());
+                item_update
+            }
+        };
+
+        current_item_update.sequence = subscription.store_update(&current_item_update);
+        if let Some(latency) = self.update_latency(subscription, &current_item_update) {
+            subscription.record_latency(latency);
+        }
+        let conflation_frequency = subscription.get_conflation_frequency();
+        drop(subscriptions);
+
+        let update = match conflation_frequency {
+            Some(frequency) => self
+                .conflators
+                .entry(subscription_id)
+                .or_insert_with(|| Conflator::new(frequency))
+                .offer(current_item_update, self.clock.now()),
+            None => Some(current_item_update),
+        };
+        if let Some(update) = update {
+            self.dispatch_update(subscription_id, update);
+        }
+    }
+
+    /// Measures the latency of a real-time update from the server timestamp it carries in the
+    /// latency field of its subscription, if configured and changed by the update.
+    fn update_latency(&self, subscription: &Subscription, update: &ItemUpdate) -> Option<Duration> {
+        let field = subscription.get_latency_field()?;
+        if update.is_snapshot() || !update.is_value_changed(field) {
+            return None;
+        }
+        let server_millis = update.get_value(field)?.trim().parse::<i64>().ok()?;
+        Some(
+            self.clock_skew
+                .elapsed_since(server_millis, self.clock.now()),
+        )
+    }
+
+    /// Queues the batches of updates collected so far for their listeners.
+    fn flush_update_batches(&mut self) {
+        for (key, pooled_listeners, updates) in self.update_batches.drain(..) {
+            self.dispatcher
+                .notify_item_updates(key, self.logging, pooled_listeners, updates);
+        }
+    }
+
+    /// Delivers the updates held by the conflators of the subscriptions whose interval elapsed.
+    fn flush_conflated_updates(&mut self) {
+        let now = self.clock.now();
+        let updates: Vec<(usize, ItemUpdate)> = self
+            .conflators
+            .iter_mut()
+            .flat_map(|(&subscription_id, conflator)| {
+                conflator
+                    .flush(now)
+                    .into_iter()
+                    .map(move |update| (subscription_id, update))
+            })
+            .collect();
+        for (subscription_id, update) in updates {
+            self.dispatch_update(subscription_id, update);
+        }
+        self.flush_update_batches();
+    }
+
+    /// Queues an update for the listeners of the subscription with the given ID, according to its
+    /// dispatch mode and backpressure policy.
+    fn dispatch_update(&mut self, subscription_id: usize, current_item_update: ItemUpdate) {
+        let subscriptions = Arc::clone(&self.subscriptions);
+        let subscriptions = subscriptions.lock().unwrap();
+        let Some((key, subscription)) = self
+            .active_subscriptions
+            .get(&subscription_id)
+            .and_then(|&key| Some((key, find_subscription(&subscriptions, key)?)))
+        else {
+            return;
+        };
+        // The listeners of an isolated subscription are skipped anyway.
+        if subscription.is_isolated() {
+            return;
+        }
+
+        // Queue the update for the pooled listeners served by a worker pool, if any.
+        let pooled_listeners = subscription.get_pooled_listeners();
+        let has_pooled_listeners = !pooled_listeners.lock().unwrap().is_empty();
+        let dispatch_mode = subscription.get_dispatch_mode();
+        if let (DispatchMode::Pooled(workers), true) = (dispatch_mode, has_pooled_listeners) {
+            self.dispatch_pools
+                .entry(subscription_id)
+                .or_insert_with(|| {
+                    DispatchPool::start(
+                        workers,
+                        Arc::clone(&pooled_listeners),
+                        self.logging,
+                        &self.dispatcher,
+                    )
+                })
+                .dispatch(current_item_update.clone());
+        }
+        let pooled_listeners = (dispatch_mode == DispatchMode::Ordered && has_pooled_listeners)
+            .then_some(pooled_listeners);
+
+        // Queue the update for the other listeners, applying the backpressure policy.
+        match subscription.get_backpressure_policy() {
+            BackpressurePolicy::Unbounded => {
+                match self
+                    .update_batches
+                    .iter_mut()
+                    .find(|(batch_key, _, _)| *batch_key == key)
+                {
+                    Some((_, _, updates)) => updates.push(current_item_update),
+                    None => {
+                        self.update_batches
+                            .push((key, pooled_listeners, vec![current_item_update]))
+                    }
+                }
+            }
+            policy => {
+                let queue = self
+                    .update_queues
+                    .entry(subscription_id)
+                    .or_insert_with(|| Arc::new(UpdateQueue::new(policy)));
+                if queue.push(current_item_update) {
+                    self.dispatcher.notify_queued_update(
+                        key,
+                        self.logging,
+                        pooled_listeners,
+                        Arc::clone(queue),
+                    );
+                }
+                if queue.is_full() {
+                    self.full_update_queue = Some(Arc::clone(queue));
+                }
+            }
+        }
+    }
+
+    /// Notifies the subscription listeners about the updates dropped by the server for an item,
+    /// reported by an `OV` notification.
+    fn process_lost_updates(&mut self, submessage: &str) {
+        let mut arguments = submessage
+            .split(',')
+            .skip(1)
+            .map(|arg| arg.parse::<usize>().ok());
+        let (Some(Some(subscription_id)), Some(Some(item_index)), Some(Some(lost_updates))) =
+            (arguments.next(), arguments.next(), arguments.next())
+        else {
+            self.make_log(
+                LogCategory::Subscriptions,
+                Level::WARN,
+                &format!("Invalid lost updates notification: '{}'", submessage),
+            );
+            return;
+        };
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        let Some((key, subscription)) = self
+            .active_subscriptions
+            .get(&subscription_id)
+            .and_then(|&key| Some((key, find_subscription_mut(&mut subscriptions, key)?)))
+        else {
+            return;
+        };
+        subscription.record_lost_updates(lost_updates as u64);
+        let item_name = subscription
+            .get_items()
+            .and_then(|items| items.get(item_index.wrapping_sub(1)))
+            .cloned();
+        let lost_updates = u32::try_from(lost_updates).unwrap_or(u32::MAX);
+        self.dispatcher.notify_subscription_listeners(
+            key,
+            self.logging,
+            "onItemLostUpdates",
+            move |listener| {
+                listener.on_item_lost_updates(item_name.as_deref(), item_index, lost_updates)
+            },
+        );
+    }
+
+    /// Records the maximum update frequency granted to a subscription by a `CONF` notification.
+    fn process_real_max_frequency(&mut self, submessage: &str) {
+        let mut arguments = submessage.split(',').skip(1);
+        let subscription_id = arguments.next().and_then(|arg| arg.parse::<usize>().ok());
+        let frequency = match arguments.next() {
+            Some("unlimited") => Some(f64::INFINITY),
+            Some(frequency) => frequency.parse::<f64>().ok(),
+            None => None,
+        };
+        let (Some(subscription_id), Some(frequency)) = (subscription_id, frequency) else {
+            self.make_log(
+                LogCategory::Subscriptions,
+                Level::WARN,
+                &format!(
+                    "Invalid subscription frequency notification: '{}'",
+                    submessage
+                ),
+            );
+            return;
+        };
+        let Some(&key) = self.active_subscriptions.get(&subscription_id) else {
+            return;
+        };
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        if let Some(subscription) = find_subscription_mut(&mut subscriptions, key) {
+            subscription.set_real_max_frequency(Some(frequency));
+        }
+    }
+
+    /// Records the end of the snapshot of an item notified by an `EOS` notification, so that the
+    /// following updates of the item are no longer flagged as snapshot.
+    fn process_end_of_snapshot(&mut self, submessage: &str) {
+        let mut arguments = submessage
+            .split(',')
+            .skip(1)
+            .map(|arg| arg.parse::<usize>().ok());
+        if let (Some(Some(subscription_id)), Some(Some(item_index))) =
+            (arguments.next(), arguments.next())
+        {
+            self.ended_snapshots.insert((subscription_id, item_index));
+        }
+    }
+
+    /// Records the number of fields of a subscription confirmed by a `SUBOK` or `SUBCMD`
+    /// notification, which is needed to decode the updates of subscriptions using a field schema,
+    /// together with the positions of the key and command fields carried by `SUBCMD`.
+    fn process_subscription_confirmation(&mut self, submessage: &str) {
+        let arguments = submessage.split(',').collect::<Vec<&str>>();
+        let argument = |index: usize| {
+            arguments
+                .get(index)
+                .and_then(|arg| arg.parse::<usize>().ok())
+        };
+        let subscription_id = argument(1);
+        if let (Some(subscription_id), Some(field_count)) = (subscription_id, argument(3)) {
+            self.field_counts.insert(subscription_id, field_count);
+        }
+        if let Some(subscription_id) = subscription_id {
+            self.subscription_requests
+                .retain(|_, requested_id| *requested_id != subscription_id);
+        }
+        let command_positions = argument(4).zip(argument(5));
+        if let (Some(subscription_id), Some(positions)) = (subscription_id, command_positions) {
+            self.command_positions.insert(subscription_id, positions);
+        }
+        let Some(&key) = subscription_id.and_then(|id| self.active_subscriptions.get(&id)) else {
+            return;
+        };
+        let mut subscriptions = self.subscriptions.lock().unwrap();
+        if let Some(subscription) = find_subscription_mut(&mut subscriptions, key) {
+            if let Some((key_pos, command_pos)) = command_positions {
+                subscription.set_command_positions(key_pos, command_pos);
+            }
+            self.set_subscribed(key, subscription, true);
+        }
+    }
+
+    /// Processes a `REQERR` notification: if the refused request is a subscription request, the
+    /// subscription is forgotten and the error is notified to its listeners. The subscription
+    /// stays in the client list, so that it can be subscribed to again.
+    fn process_subscription_error(&mut self, submessage: &str) {
+        let mut arguments = submessage.trim().splitn(4, ',').skip(1);
+        let request_id = arguments.next().and_then(|id| id.parse::<usize>().ok());
+        let Some(subscription_id) =
+            request_id.and_then(|id| self.subscription_requests.remove(&id))
+        else {
+            return;
+        };
+        let Some(&key) = self.active_subscriptions.get(&subscription_id) else {
+            return;
+        };
+        self.forget_subscription(subscription_id);
+        let code = arguments
+            .next()
+            .and_then(|code| code.parse::<i32>().ok())
+            .unwrap_or(0);
+        let message = arguments.next().map(str::to_string);
+        self.make_log(
+            LogCategory::Subscriptions,
+            Level::WARN,
+            &format!("Subscription refused by server: '{}'", submessage),
+        );
+        self.dispatcher.notify_subscription_listeners(
+            key,
+            self.logging,
+            "onSubscriptionError",
+            move |listener| listener.on_subscription_error(code, message.as_deref()),
+        );
+    }
+
+    /// Marks the subscription with the given key in the client list as subscribed to through the
+    /// server or not, notifying its listeners when the flag changes.
+    fn set_subscribed(&self, key: usize, subscription: &mut Subscription, subscribed: bool) {
+        if subscription.is_subscribed() == subscribed {
+            return;
+        }
+        subscription.set_subscribed(subscribed);
+        if subscribed {
+            self.dispatcher.notify_subscription_listeners(
+                key,
+                self.logging,
+                "onSubscription",
+                |listener| listener.on_subscription(),
+            );
+        } else {
+            self.dispatcher.notify_subscription_listeners(
+                key,
+                self.logging,
+                "onUnsubscription",
+                |listener| listener.on_unsubscription(),
+            );
+        }
+    }
+
+    /// Marks all the subscriptions of the client as no longer subscribed to, since the session
+    /// they were subscribed to in is gone.
+    fn unsubscribe_all(&self) {
+        let subscriptions = Arc::clone(&self.subscriptions);
+        for subscription in subscriptions.lock().unwrap().iter_mut() {
+            if let Some(key) = subscription.client_key() {
+                self.set_subscribed(key, subscription, false);
+            }
+        }
+    }
+
+    /// Processes a `SERVNAME` or `CLIENTIP` notification, recording the name of the server instance
+    /// or the address of the client for the description of the current connection.
+    ///
+    /// The raw submessage is used, since the server name is case sensitive.
+    fn process_connection_details(&mut self, submessage: &str) {
+        let Some((notification, value)) = submessage.trim().split_once(',') else {
+            return;
+        };
+        let value = Some(value.to_string());
+        if notification.eq_ignore_ascii_case("servname") {
+            self.server_instance.clone_from(&value);
+            if let Some(connection_info) = &mut self.connection_info {
+                connection_info.server_instance = value;
+            }
+        } else {
+            self.client_ip.clone_from(&value);
+            if let Some(connection_info) = &mut self.connection_info {
+                connection_info.client_ip = value;
+            }
+        }
+    }
+
+    /// Processes a `PROG` notification, which tells the number of data notifications sent by the
+    /// server in the session before the ones that follow, so that the notifications already
+    /// received before the session was recovered are skipped.
+    ///
+    /// Returns `false` if the server can't resend some notifications that were never received.
+    fn process_prog(&mut self, submessage: &str) -> bool {
+        let Some(prog) = submessage
+            .split(',')
+            .nth(1)
+            .and_then(|prog| prog.trim().parse::<u64>().ok())
+        else {
+            self.make_log(
+                LogCategory::Protocol,
+                Level::WARN,
+                &format!("Invalid progressive notification: '{}'", submessage),
+            );
+            return true;
+        };
+        if prog > self.data_notifications {
+            self.make_log(
+                LogCategory::Connections,
+                Level::WARN,
+                &format!(
+                    "Session can't be recovered: {} notifications were lost",
+                    prog - self.data_notifications
+                ),
+            );
+            return false;
+        }
+        self.skipped_notifications = self.data_notifications - prog;
+        self.data_notifications = prog;
+        true
+    }
+
+    /// Processes a `SYNC` notification received from the server, which refines the estimate of
+    /// the clock skew and is notified to the client listeners.
+    fn process_sync(&mut self, submessage: &str) {
+        let Some(seconds) = submessage
+            .split(',')
+            .nth(1)
+            .and_then(|seconds| seconds.parse::<u64>().ok())
+        else {
+            self.make_log(
+                LogCategory::Protocol,
+                Level::WARN,
+                &format!("Invalid sync notification: '{}'", submessage),
+            );
+            return;
+        };
+        self.clock_skew.sync(seconds, self.clock.now());
+        self.dispatcher
+            .notify_client_listeners(self.logging, "onServerSync", move |listener| {
+                listener.on_server_sync(seconds)
+            });
+    }
+
+    /// Decides whether the session creation refused through the given `CONERR` notification has
+    /// to be retried after refreshing the credentials, which is the case when the server rejected
+    /// them and the re-login attempts are not exhausted yet.
+    fn relogin_due(&mut self, submessage: &str) -> bool {
+        let (code, _) = parse_server_error(submessage);
+        if code != 1
+            || self.reauthentication_handler.is_none()
+            || self.relogin_attempts >= self.retry_settings.max_relogin_attempts
+        {
+            return false;
+        }
+        self.relogin_attempts += 1;
+        self.relogin_pending = true;
+        self.make_log(
+            LogCategory::Connections,
+            Level::WARN,
+            &format!(
+                "Credentials rejected by server: logging in again (attempt {} of {})",
+                self.relogin_attempts, self.retry_settings.max_relogin_attempts
+            ),
+        );
+        true
+    }
+
+    /// Notifies the client listeners about a `CONERR` notification received from the server.
+    fn notify_server_error(&self, submessage: &str) {
+        let (code, message) = parse_server_error(submessage);
+        set_status(
+            &self.status,
+            &self.dispatcher,
+            &self.history,
+            self.logging,
+            ClientStatus::Disconnected(DisconnectionType::None),
+        );
+        self.dispatcher
+            .notify_client_listeners(self.logging, "onServerError", move |listener| {
+                listener.on_server_error(code, &message)
+            });
+    }
+
+    /// Notifies the client listeners that the current server session has ended.
+    fn session_ended(&self, cause: SessionEndCause) {
+        self.make_log(LogCategory::Connections, Level::INFO, &cause.to_string());
+        self.dispatcher
+            .notify_client_listeners(self.logging, "onSessionEnd", move |listener| {
+                listener.on_session_end(&cause)
+            });
+    }
+
+    /// Publishes the details about the closure of the current connection and notifies them to the
+    /// client listeners.
+    fn disconnected(&self, disconnect_info: DisconnectInfo) {
+        self.make_log(
+            LogCategory::Connections,
+            Level::INFO,
+            &disconnect_info.to_string(),
+        );
+        self.info.lock().unwrap().last_disconnect = Some(disconnect_info.clone());
+        self.dispatcher
+            .notify_client_listeners(self.logging, "onDisconnect", move |listener| {
+                listener.on_disconnect(&disconnect_info)
+            });
+    }
+
+    /// Publishes the current state of the session for `LightstreamerClient.debugState()`.
+    fn publish_info(&self) {
+        let mut info = self.info.lock().unwrap();
+        info.session_id.clone_from(&self.session_id);
+        info.active_subscriptions
+            .clone_from(&self.active_subscriptions);
+        info.unacknowledged_requests = self.pending_requests.len();
+        info.messages_awaiting_outcome =
+            self.sent_messages.len() + self.unacknowledged_messages.len();
+        info.clock_skew = self.clock_skew.estimate();
+        info.connection_info.clone_from(&self.connection_info);
+    }
+
+    /// Sends a text frame to the server, accounting for it in the client metrics. The returned
+    /// future doesn't borrow the session.
+    fn send_text<'a, S>(&self, write_stream: &'a mut S, text: String) -> SendFuture<'a, S, Message>
+    where
+        S: Sink<Message> + Unpin,
+    {
+        self.metrics.bytes_sent(text.len());
+        self.history.frame_sent(text.len());
+        if self.recorder.is_some() {
+            self.record(FrameDirection::Sent, &redact_password(&text));
+        }
+        write_stream.send(Message::Text(text.into()))
+    }
+
+    /// Writes a frame to the session recorder, if any.
+    fn record(&self, direction: FrameDirection, text: &str) {
+        if let Some(recorder) = &self.recorder {
+            if let Err(err) = recorder.record(direction, text) {
+                self.make_log(
+                    LogCategory::Protocol,
+                    Level::WARN,
+                    &format!("Failed to record frame: {}", err),
+                );
+            }
+        }
+    }
+
+    /// Gets the ID for a new request acknowledged by the server through `REQOK` or `REQERR`,
+    /// recording when it was issued to measure the round-trip time.
+    fn next_acknowledged_request_id(&mut self) -> usize {
+        self.request_id += 1;
+        self.pending_requests
+            .insert(self.request_id, self.clock.now());
+        self.request_id
+    }
+
+    /// Processes a `REQOK` or `REQERR` notification, recording the round-trip time of the
+    /// acknowledged request. An acknowledged message keeps waiting for its outcome only if it has
+    /// a listener.
+    fn acknowledge_request(&mut self, submessage: &str) {
+        let request_id = submessage
+            .split(',')
+            .nth(1)
+            .and_then(|request_id| request_id.parse::<usize>().ok());
+        if let Some(issued_at) = request_id.and_then(|id| self.pending_requests.remove(&id)) {
+            self.metrics
+                .rtt(self.clock.now().saturating_duration_since(issued_at));
+        }
+        if let Some(pending_message) =
+            request_id.and_then(|id| self.unacknowledged_messages.remove(&id))
+        {
+            if let Some(prog) = pending_message.prog {
+                if pending_message.listener.is_some() {
+                    self.sent_messages
+                        .insert((pending_message.sequence.clone(), prog), pending_message);
+                }
+            }
+        }
+    }
+
+    fn make_log(&self, category: LogCategory, loglevel: Level, log: &str) {
+        self.logging.log(category, loglevel, log);
+        self.history.record_log(loglevel, log);
+    }
+}
+
+impl Drop for Session {
+    fn drop(&mut self) {
+        for (name, value) in self.create_session_params.iter_mut() {
+            if *name == "LS_password" {
+                value.zeroize();
+            }
+        }
+    }
+}
+
+/// Replaces the value of the `LS_password` parameter of an encoded request, so that the request
+/// can be logged or recorded without leaking the password.
+fn redact_password(request: &str) -> String {
+    const PARAM: &str = "LS_password=";
+    let mut redacted = String::with_capacity(request.len());
+    let mut rest = request;
+    while let Some(start) = rest.find(PARAM) {
+        let value_start = start + PARAM.len();
+        redacted.push_str(&rest[..value_start]);
+        redacted.push_str("***");
+        let value_end = rest[value_start..]
+            .find(['&', '\r', '\n'])
+            .map_or(rest.len(), |end| value_start + end);
+        rest = &rest[value_end..];
+    }
+    redacted.push_str(rest);
+    redacted
+}
+
+/// Gets the cause code and message of a `CONERR` or `END` notification.
+fn parse_server_error(submessage: &str) -> (i32, String) {
+    let mut arguments = submessage.trim().splitn(3, ',').skip(1);
+    let code = arguments
+        .next()
+        .and_then(|code| code.parse::<i32>().ok())
+        .unwrap_or(0);
+    let message = arguments.next().unwrap_or_default().to_string();
+    (code, message)
+}
+
+/// Gets the cause of the end of a session closed by the server through a `CONERR` or `END`
+/// notification.
+fn server_closed(submessage: &str) -> SessionEndCause {
+    let (code, message) = parse_server_error(submessage);
+    SessionEndCause::ServerClosed { code, message }
+}
+
+/// Formats a requested max frequency as a `LS_requested_max_frequency` value, where an infinite
+/// frequency means "unlimited".
+fn max_frequency_param(freq: f64) -> String {
+    if freq.is_infinite() {
+        "unlimited".to_string()
+    } else {
+        freq.to_string()
+    }
+}
+
+/// Tells whether the given forced transport binds the session in polling mode, or `None` if it
+/// can't be used.
+fn is_polling_transport(transport: Option<Transport>) -> Option<bool> {
+    match transport {
+        None | Some(Transport::Ws | Transport::WsStreaming) => Some(false),
+        Some(Transport::WsPolling) => Some(true),
+        Some(Transport::Http | Transport::HttpStreaming) if cfg!(feature = "reqwest") => {
+            Some(false)
+        }
+        Some(Transport::HttpPolling) if cfg!(feature = "reqwest") => Some(true),
+        Some(Transport::Http | Transport::HttpStreaming | Transport::HttpPolling) => None,
+    }
+}
+
+/// Tells whether the given forced transport opens the connections over HTTP.
+fn is_http_transport(transport: Option<Transport>) -> bool {
+    matches!(
+        transport,
+        Some(Transport::Http | Transport::HttpStreaming | Transport::HttpPolling)
+    )
+}
+
+/// Formats the requested max bandwidth (in kbps) as a TLCP parameter value.
+fn max_bandwidth_param(bandwidth: Option<f64>) -> String {
+    bandwidth.map_or_else(
+        || "unlimited".to_string(),
+        |bandwidth| bandwidth.to_string(),
+    )
+}
+
+/// Sets to null all the fields of a COMMAND update carrying a DELETE command, but the key and
+/// the command ones, as DELETE events carry no values for them. The positions of the key and
+/// command fields are the ones confirmed by the server, if any, or else the ones of the fields
+/// named "key" and "command".
+fn null_deleted_fields(
+    positions: Option<(usize, usize)>,
+    field_names: &FieldNames,
+    changes: &mut [FieldValue],
+) {
+    let (key_pos, command_pos) = match positions {
+        Some((key_pos, command_pos)) => (Some(key_pos), Some(command_pos)),
+        None => (
+            field_names.get_position("key"),
+            field_names.get_position("command"),
+        ),
+    };
+    let Some(command_index) = command_pos.and_then(|pos| pos.checked_sub(1)) else {
+        return;
+    };
+    let is_delete = changes
+        .get(command_index)
+        .and_then(FieldValue::value)
+        .is_some_and(|command| command.eq_ignore_ascii_case("DELETE"));
+    if !is_delete {
+        return;
+    }
+    let key_index = key_pos.and_then(|pos| pos.checked_sub(1));
+    for (index, change) in changes.iter_mut().enumerate() {
+        if index != command_index && Some(index) != key_index {
+            *change = FieldValue::Null;
+        }
+    }
+}
+
+/// Builds the error reported when a connection attempt exceeds its deadline.
+fn connect_deadline_error(connect_deadline: Option<Duration>) -> SessionError {
+    Box::new(std::io::Error::new(
+        std::io::ErrorKind::TimedOut,
+        format!(
+            "Connection attempt timed out after {} ms",
+            connect_deadline.unwrap_or_default().as_millis()
+        ),
+    ))
+}
+
+/// Updates the shared client status and queues the notification to the client listeners through
+/// `ClientListener.onStatusChange()`, but only if the status actually changed.
+pub(crate) fn set_status(
+    status: &Mutex<ClientStatus>,
+    dispatcher: &EventDispatcher,
+    history: &EventHistory,
+    logging: LogType,
+    new_status: ClientStatus,
+) {
+    {
+        let mut status = status.lock().unwrap();
+        if *status == new_status {
+            return;
+        }
+        *status = new_status.clone();
+    }
+    let status_text = new_status.to_string();
+    history.record(ClientEventKind::StatusChange, &status_text);
+    dispatcher.notify_client_listeners(logging, "onStatusChange", move |listener| {
+        listener.on_status_change(&status_text)
+    });
+}
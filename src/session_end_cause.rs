@@ -0,0 +1,52 @@
+use std::fmt::{self, Display, Formatter};
+
+/// Reason why a Server session of a `LightstreamerClient` ended, notified through
+/// `ClientListener.onSessionEnd()`.
+///
+/// A session survives the loss of its connections as long as it is recovered, so it ends only
+/// when it is closed or when it can't be recovered anymore. In the latter case, unless the client
+/// gives up reconnecting, a new session is created, which is notified through
+/// `ClientListener.onSessionStart()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SessionEndCause {
+    /// The client closed the session, through `LightstreamerClient.disconnect()` or because of an
+    /// unrecoverable error on the client side.
+    Disconnected,
+    /// The Server closed the session, or refused to recover it, with the given cause code and
+    /// message. See `ClientListener.onServerError()` for the meaning of the codes.
+    ServerClosed {
+        /// The cause code sent by the Server.
+        code: i32,
+        /// The description of the cause sent by the Server. It may be empty.
+        message: String,
+    },
+    /// The connection was lost and the session couldn't be recovered, because the session
+    /// recovery timeout expired or the Server couldn't resend the missed notifications. A new
+    /// session is going to be created.
+    Lost,
+    /// The connection was lost and the client gave up reconnecting, as decided by the
+    /// `RetryPolicy`.
+    RetriesExhausted,
+}
+
+impl Display for SessionEndCause {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionEndCause::Disconnected => write!(f, "Session closed by the client"),
+            SessionEndCause::ServerClosed { code, message } if message.is_empty() => {
+                write!(f, "Session closed by the server with code {}", code)
+            }
+            SessionEndCause::ServerClosed { code, message } => {
+                write!(
+                    f,
+                    "Session closed by the server with code {}: {}",
+                    code, message
+                )
+            }
+            SessionEndCause::Lost => write!(f, "Session lost"),
+            SessionEndCause::RetriesExhausted => {
+                write!(f, "Session lost after giving up reconnecting")
+            }
+        }
+    }
+}
@@ -0,0 +1,343 @@
+use crate::error::{IllegalArgumentException, IllegalStateException};
+use crate::ls_client::{ClientStatus, LightstreamerClient};
+use crate::subscription::Subscription;
+
+use futures::future::join_all;
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Debug, Formatter};
+
+/// Container of several `LightstreamerClient` instances, each one with its own session, for the
+/// applications consuming feeds from different Adapter Sets or different Lightstreamer Servers.
+///
+/// Every client is registered under a name. Subscriptions are routed to a client by the name of
+/// their Data Adapter, as configured through `routeDataAdapter()`, or to the default client if
+/// their Data Adapter has no route. The clients can still be reached one by one through
+/// `getClient()`, for instance to add listeners or to change their options.
+///
+/// See also `LightstreamerClient`
+#[derive(Default)]
+pub struct SessionPool {
+    /// The registered clients, in registration order.
+    clients: Vec<(String, LightstreamerClient)>,
+    /// The names of the clients serving each Data Adapter.
+    routes: HashMap<String, String>,
+    /// The name of the client serving the subscriptions with no route, if any.
+    default_client: Option<String>,
+}
+
+impl Debug for SessionPool {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SessionPool")
+            .field("clients", &self.get_client_names())
+            .field("routes", &self.routes)
+            .field("default_client", &self.default_client)
+            .finish()
+    }
+}
+
+impl SessionPool {
+    /// Creates an empty pool.
+    pub fn new() -> SessionPool {
+        SessionPool::default()
+    }
+
+    /// Setter method that registers a client under the given name. The first client registered
+    /// becomes the default one, see `setDefaultClient()`.
+    ///
+    /// # Parameters
+    ///
+    /// * `name`: the name of the client, used to route subscriptions to it.
+    /// * `client`: the client, which should not be connected yet.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if a client is already registered under the same name.
+    pub fn add_client(
+        &mut self,
+        name: &str,
+        client: LightstreamerClient,
+    ) -> Result<(), IllegalArgumentException> {
+        if self.position(name).is_some() {
+            return Err(IllegalArgumentException::new(&format!(
+                "Client '{}' is already registered",
+                name
+            )));
+        }
+        if self.default_client.is_none() {
+            self.default_client = Some(name.to_string());
+        }
+        self.clients.push((name.to_string(), client));
+        Ok(())
+    }
+
+    /// Inquiry method that gets a registered client.
+    ///
+    /// # Parameters
+    ///
+    /// * `name`: the name the client was registered under.
+    ///
+    /// # Returns
+    ///
+    /// The client, or `None` if no client is registered under the given name.
+    pub fn get_client(&self, name: &str) -> Option<&LightstreamerClient> {
+        self.position(name).map(|index| &self.clients[index].1)
+    }
+
+    /// Inquiry method that gets a registered client, to change its options or details.
+    ///
+    /// # Parameters
+    ///
+    /// * `name`: the name the client was registered under.
+    ///
+    /// # Returns
+    ///
+    /// The client, or `None` if no client is registered under the given name.
+    pub fn get_client_mut(&mut self, name: &str) -> Option<&mut LightstreamerClient> {
+        self.position(name).map(|index| &mut self.clients[index].1)
+    }
+
+    /// Inquiry method that gets the names of the registered clients.
+    ///
+    /// # Returns
+    ///
+    /// The names of the clients, in registration order.
+    pub fn get_client_names(&self) -> Vec<&str> {
+        self.clients.iter().map(|(name, _)| name.as_str()).collect()
+    }
+
+    /// Inquiry method that gets the name of the client serving the subscriptions whose Data
+    /// Adapter has no route.
+    ///
+    /// # Returns
+    ///
+    /// The name of the default client, or `None` if there is none.
+    ///
+    /// See also `setDefaultClient()`
+    pub fn get_default_client(&self) -> Option<&str> {
+        self.default_client.as_deref()
+    }
+
+    /// Setter method that sets the client serving the subscriptions whose Data Adapter has no
+    /// route.
+    ///
+    /// The default value is the first client registered.
+    ///
+    /// # Parameters
+    ///
+    /// * `name`: the name of the client, or `None` to reject the subscriptions with no route.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if no client is registered under the given name.
+    ///
+    /// See also `getDefaultClient()`
+    pub fn set_default_client(
+        &mut self,
+        name: Option<&str>,
+    ) -> Result<(), IllegalArgumentException> {
+        if let Some(name) = name {
+            self.check_registered(name)?;
+        }
+        self.default_client = name.map(str::to_string);
+        Ok(())
+    }
+
+    /// Setter method that routes the subscriptions to the given Data Adapter to a client,
+    /// replacing any previous route for the same Data Adapter.
+    ///
+    /// # Parameters
+    ///
+    /// * `data_adapter`: the name of the Data Adapter, as set through
+    ///   `Subscription.setDataAdapter()`.
+    /// * `client_name`: the name of the client serving it.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if no client is registered under the given name.
+    pub fn route_data_adapter(
+        &mut self,
+        data_adapter: &str,
+        client_name: &str,
+    ) -> Result<(), IllegalArgumentException> {
+        self.check_registered(client_name)?;
+        self.routes
+            .insert(data_adapter.to_string(), client_name.to_string());
+        Ok(())
+    }
+
+    /// Inquiry method that gets the name of the client a `Subscription` would be routed to.
+    ///
+    /// # Parameters
+    ///
+    /// * `subscription`: the `Subscription` to be routed.
+    ///
+    /// # Returns
+    ///
+    /// The name of the client serving the Data Adapter of the `Subscription`, or the name of the
+    /// default client if the Data Adapter has no route, or `None` if there is no default client.
+    pub fn route(&self, subscription: &Subscription) -> Option<&str> {
+        subscription
+            .get_data_adapter()
+            .and_then(|data_adapter| self.routes.get(data_adapter))
+            .map(String::as_str)
+            .or(self.default_client.as_deref())
+    }
+
+    /// Operation method that subscribes a `Subscription` through the client it is routed to. See
+    /// `route()` and `LightstreamerClient.subscribe()` for details.
+    ///
+    /// # Parameters
+    ///
+    /// * `subscription`: A `Subscription` object, carrying all the information needed to process
+    ///   real-time values.
+    ///
+    /// # Returns
+    ///
+    /// The name of the client the `Subscription` was routed to.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalArgumentException`: if the Data Adapter of the `Subscription` has no route and
+    ///   there is no default client.
+    pub fn subscribe(&self, subscription: Subscription) -> Result<&str, IllegalArgumentException> {
+        let name = self.route(&subscription).ok_or_else(|| {
+            IllegalArgumentException::new(&format!(
+                "No client serves data adapter {:?}",
+                subscription.get_data_adapter()
+            ))
+        })?;
+        let client = self
+            .get_client(name)
+            .expect("routes only name registered clients");
+        client.subscribe(subscription);
+        Ok(name)
+    }
+
+    /// Operation method that connects all the registered clients at the same time. See
+    /// `LightstreamerClient.connect()` for details.
+    ///
+    /// The clients that can be connected are connected even if others fail.
+    ///
+    /// # Raises
+    ///
+    /// * `IllegalStateException`: if any client could not be connected, with the names of the
+    ///   clients and the reasons.
+    pub async fn connect_all(&self) -> Result<(), Box<dyn Error>> {
+        let results = join_all(self.clients.iter().map(|(_, client)| client.connect())).await;
+        let failures: Vec<String> = self
+            .clients
+            .iter()
+            .zip(results)
+            .filter_map(|((name, _), result)| {
+                result
+                    .err()
+                    .map(|err| format!("client '{}': {}", name, err))
+            })
+            .collect();
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(Box::new(IllegalStateException::new(&format!(
+                "Failed to connect {}",
+                failures.join("; ")
+            ))))
+        }
+    }
+
+    /// Operation method that disconnects all the registered clients at the same time. See
+    /// `LightstreamerClient.disconnect()` for details.
+    pub async fn disconnect_all(&self) {
+        join_all(self.clients.iter().map(|(_, client)| client.disconnect())).await;
+    }
+
+    /// Inquiry method that gets the status of all the registered clients.
+    ///
+    /// # Returns
+    ///
+    /// The aggregate status of the pool.
+    pub fn get_status(&self) -> PoolStatus {
+        PoolStatus {
+            clients: self
+                .clients
+                .iter()
+                .map(|(name, client)| (name.clone(), client.get_status()))
+                .collect(),
+        }
+    }
+
+    /// Gets the position of the client registered under the given name, if any.
+    fn position(&self, name: &str) -> Option<usize> {
+        self.clients
+            .iter()
+            .position(|(client_name, _)| client_name == name)
+    }
+
+    /// Fails if no client is registered under the given name.
+    fn check_registered(&self, name: &str) -> Result<(), IllegalArgumentException> {
+        match self.position(name) {
+            Some(_) => Ok(()),
+            None => Err(IllegalArgumentException::new(&format!(
+                "No client is registered as '{}'",
+                name
+            ))),
+        }
+    }
+}
+
+/// Status of the clients of a `SessionPool`, obtained through `SessionPool.getStatus()`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PoolStatus {
+    /// The name and the status of every client, in registration order.
+    pub clients: Vec<(String, ClientStatus)>,
+}
+
+impl PoolStatus {
+    /// Inquiry method that gets the status of a single client.
+    ///
+    /// # Parameters
+    ///
+    /// * `name`: the name the client was registered under.
+    ///
+    /// # Returns
+    ///
+    /// The status of the client, or `None` if no client is registered under the given name.
+    pub fn get(&self, name: &str) -> Option<&ClientStatus> {
+        self.clients
+            .iter()
+            .find(|(client_name, _)| client_name == name)
+            .map(|(_, status)| status)
+    }
+
+    /// Inquiry method that counts the clients with a session in place.
+    ///
+    /// # Returns
+    ///
+    /// The number of clients whose status is `ClientStatus::Connected`.
+    pub fn connected_count(&self) -> usize {
+        self.clients
+            .iter()
+            .filter(|(_, status)| matches!(status, ClientStatus::Connected(_)))
+            .count()
+    }
+
+    /// Inquiry method that checks whether all the clients have a session in place.
+    ///
+    /// # Returns
+    ///
+    /// `true` if every client is connected, including when the pool is empty.
+    pub fn is_all_connected(&self) -> bool {
+        self.connected_count() == self.clients.len()
+    }
+
+    /// Inquiry method that checks whether no client has a session in place or is opening one.
+    ///
+    /// # Returns
+    ///
+    /// `true` if every client is disconnected, including when the pool is empty.
+    pub fn is_all_disconnected(&self) -> bool {
+        self.clients
+            .iter()
+            .all(|(_, status)| matches!(status, ClientStatus::Disconnected(_)))
+    }
+}
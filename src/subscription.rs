@@ -1,7 +1,24 @@
+use crate::client_debug_state::SubscriptionDebugState;
+use crate::client_metrics::{LatencyStats, SubscriptionStats};
+use crate::dispatcher::WatchedSubscription;
+use crate::error::{IllegalArgumentException, TimeoutException};
+use crate::item_update::{FieldNames, FieldValue, ItemUpdate};
+use crate::runtime::{CurrentRuntime, Instant, Runtime};
+use crate::session::{SubscriptionChange, SubscriptionChanges};
 use crate::subscription_listener::SubscriptionListener;
-use std::collections::HashMap;
+use crate::weak_listener::WeakSubscriptionListener;
+use ahash::AHashMap;
+use std::collections::{BTreeMap, VecDeque};
 use std::error::Error;
-use std::fmt::{self, Debug, Formatter};
+use std::fmt::{self, Debug, Display, Formatter};
+use std::future::Future;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::time::Duration;
+
+use futures::channel::oneshot;
+
+/// Number of latest measurements the latency percentiles of a Subscription are computed on.
+const LATENCY_WINDOW: usize = 1000;
 
 /// Enum representing the snapshot delivery preferences to be requested to Lightstreamer Server for the items in the Subscription.
 #[derive(Debug, Default)]
@@ -19,19 +36,35 @@ impl Default for &Snapshot {
     }
 }
 
-impl ToString for Snapshot {
-    fn to_string(&self) -> String {
+impl Display for Snapshot {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
-            Snapshot::Yes => "true".to_string(),
-            Snapshot::No => "false".to_string(),
-            Snapshot::Number(n) => n.to_string(),
-            Snapshot::None => "none".to_string(),
+            Snapshot::Yes => write!(f, "true"),
+            Snapshot::No => write!(f, "false"),
+            Snapshot::Number(n) => write!(f, "{}", n),
+            Snapshot::None => write!(f, "none"),
+        }
+    }
+}
+
+/// Enum representing the length to be requested to Lightstreamer Server for the internal queuing buffers for the items in the Subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BufferSize {
+    Size(usize),
+    Unlimited,
+}
+
+impl Display for BufferSize {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            BufferSize::Size(size) => write!(f, "{}", size),
+            BufferSize::Unlimited => write!(f, "unlimited"),
         }
     }
 }
 
 /// Enum representing the subscription mode.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum SubscriptionMode {
     Merge,
     Distinct,
@@ -39,17 +72,95 @@ pub enum SubscriptionMode {
     Command,
 }
 
-impl ToString for SubscriptionMode {
-    fn to_string(&self) -> String {
+impl Display for SubscriptionMode {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
-            SubscriptionMode::Merge => "MERGE".to_string(),
-            SubscriptionMode::Distinct => "DISTINCT".to_string(),
-            SubscriptionMode::Raw => "RAW".to_string(),
-            SubscriptionMode::Command => "COMMAND".to_string(),
+            SubscriptionMode::Merge => write!(f, "MERGE"),
+            SubscriptionMode::Distinct => write!(f, "DISTINCT"),
+            SubscriptionMode::Raw => write!(f, "RAW"),
+            SubscriptionMode::Command => write!(f, "COMMAND"),
         }
     }
 }
 
+/// Policy applied to the item updates of a Subscription when its listeners can't keep up with
+/// them, set through `Subscription.setBackpressurePolicy()`.
+///
+/// The policy applies to the updates delivered by the dispatch task of the client, that is to
+/// the listeners added through `Subscription.addListener()` and, with `DispatchMode::Ordered`,
+/// to the pooled ones. The updates discarded by the client are notified to the listeners through
+/// `SubscriptionListener.onItemLostUpdates()`, before the next update delivered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum BackpressurePolicy {
+    /// The updates are queued without limits.
+    #[default]
+    Unbounded,
+    /// Up to the given number of updates are queued. When the buffer is full the client stops
+    /// reading from the connection until the listeners catch up, so that the Server applies its
+    /// own policies, such as buffering or conflation, to all the subscriptions of the session.
+    Block(usize),
+    /// Up to the given number of updates are queued. When the buffer is full the oldest update
+    /// is discarded to make room for the new one.
+    DropOldest(usize),
+    /// Up to the given number of updates are queued. When the buffer is full the new update is
+    /// discarded.
+    DropNewest(usize),
+    /// At most one update per item is queued: a new update replaces the one queued for the same
+    /// item, if any, carrying also its changed fields. The replaced update is accounted as lost.
+    ConflateLatest,
+}
+
+/// Policy used to dispatch the item updates of a Subscription to its pooled listeners, added
+/// through `Subscription.addPooledListener()`.
+///
+/// The listeners added through `Subscription.addListener()` are not affected, as they always
+/// receive all the events in order from the dispatch task of the client.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DispatchMode {
+    /// The updates are dispatched to the pooled listeners by the dispatch task of the client,
+    /// in order with all the other events.
+    #[default]
+    Ordered,
+    /// The updates are dispatched to the pooled listeners by a pool of the given number of worker
+    /// tasks, reserved to the Subscription. The updates of each item are always handled by the same
+    /// worker, so that they are received in order, while the updates of different items can be
+    /// processed in parallel.
+    Pooled(usize),
+}
+
+/// Consolidated state of a Subscription at a given time, as returned by
+/// `Subscription.get_snapshot()`. It allows rendering the full table on demand, rather than
+/// replaying every update received.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SubscriptionSnapshot {
+    /// The names of the fields in the "Field List", in order, if one was specified.
+    pub fields: Vec<String>,
+    /// The latest values of each item, keyed by the 1-based item position and then by the
+    /// 1-based field position. Empty for COMMAND Subscriptions.
+    pub items: BTreeMap<usize, BTreeMap<usize, String>>,
+    /// The latest values of each key of a COMMAND Subscription, keyed by the 1-based item
+    /// position, then by key and then by the 1-based field position. Keys removed through
+    /// a DELETE command are not included.
+    pub keys: BTreeMap<usize, BTreeMap<String, BTreeMap<usize, String>>>,
+}
+
+/// Token identifying a Subscription given to a `LightstreamerClient`, as returned by
+/// `LightstreamerClient.subscribe()`, to be passed to `LightstreamerClient.unsubscribe()`.
+///
+/// Tokens are never reused by the same client, so that a stale token can't refer to another
+/// Subscription.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionToken(pub(crate) usize);
+
+/// Token identifying a listener added to a Subscription, as returned by
+/// `Subscription.addListener()` and the closure adapters, to be passed to
+/// `Subscription.removeListenerByToken()`.
+///
+/// Tokens are never reused by the same Subscription, so that a stale token can't refer to another
+/// listener.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct SubscriptionListenerToken(usize);
+
 /// Struct representing a Subscription to be submitted to a Lightstreamer Server.
 /// It contains subscription details and the listeners needed to process the real-time data.
 pub struct Subscription {
@@ -72,7 +183,7 @@ pub struct Subscription {
     /// The "Field Schema" to be subscribed to through Lightstreamer Server for the second-level items in a COMMAND Subscription.
     command_second_level_field_schema: Option<String>,
     /// The length to be requested to Lightstreamer Server for the internal queuing buffers for the items in the Subscription.
-    requested_buffer_size: Option<usize>,
+    requested_buffer_size: Option<BufferSize>,
     /// The maximum update frequency to be requested to Lightstreamer Server for all the items in the Subscription.
     requested_max_frequency: Option<f64>,
     /// The snapshot delivery preferences to be requested to Lightstreamer Server for the items in the Subscription.
@@ -81,14 +192,61 @@ pub struct Subscription {
     selector: Option<String>,
     /// A list of SubscriptionListener instances that will receive events from this Subscription.
     listeners: Vec<Box<dyn SubscriptionListener>>,
+    /// Key of each listener, in the same order. Keys are assigned in increasing order, so that
+    /// they stay sorted.
+    listener_keys: Vec<usize>,
+    next_listener_key: usize,
+    /// Listeners receiving the item updates according to the dispatch mode, shared with the
+    /// workers dispatching them.
+    pooled_listeners: Arc<Mutex<Vec<Arc<dyn SubscriptionListener + Sync>>>>,
+    /// The policy used to dispatch the item updates to the pooled listeners.
+    dispatch_mode: DispatchMode,
+    /// The policy applied to the item updates when the listeners can't keep up with them.
+    backpressure_policy: BackpressurePolicy,
+    /// The maximum frequency at which the updates of each item are delivered to the listeners.
+    conflation_frequency: Option<f64>,
     /// A HashMap storing the latest values received for each item/field pair.
-    values: HashMap<(usize, usize), String>,
-    /// A HashMap storing the latest values received for each key/field pair in a COMMAND Subscription.
-    command_values: HashMap<String, HashMap<usize, String>>,
+    values: AHashMap<(usize, usize), String>,
+    /// A HashMap storing the latest values received for each item/key/field combination in a COMMAND Subscription.
+    command_values: AHashMap<(usize, String), AHashMap<usize, String>>,
+    /// The 1-based positions of the "key" and "command" fields of a COMMAND Subscription, as
+    /// notified by the server.
+    command_positions: Option<(usize, usize)>,
+    /// The field names of the last update received, used to rebuild the current state of the
+    /// items for the listeners added through `add_listener_with_replay()`.
+    field_names: Option<Arc<FieldNames>>,
+    /// The maximum number of updates kept in the history of each item of a DISTINCT Subscription.
+    history_length: usize,
+    /// A HashMap storing the latest updates received for each item of a DISTINCT Subscription, oldest first.
+    history: AHashMap<usize, VecDeque<ItemUpdate>>,
+    /// The field carrying the server timestamp of the updates, used to measure their latency.
+    latency_field: Option<String>,
+    /// Latencies of the latest real-time updates carrying a server timestamp, oldest first.
+    latencies: VecDeque<Duration>,
+    /// Number of updates whose latency was measured.
+    latency_samples: u64,
+    /// Number of updates received for the Subscription.
+    updates_received: u64,
+    /// Instant at which the last update for the Subscription was received.
+    last_update_at: Option<Instant>,
+    /// Number of updates received for the Subscription that belong to an item snapshot.
+    snapshot_updates: u64,
+    /// Number of updates notified as lost by the server for the Subscription.
+    lost_updates: u64,
+    /// Maximum update frequency granted by the server in the current session, if notified.
+    real_max_frequency: Option<f64>,
     /// A flag indicating whether the Subscription is currently active or not.
     is_active: bool,
+    /// Key of the Subscription in the list of the client it was given to, and the queue used to
+    /// forward changes of the active Subscription to the server.
+    client_link: Option<(usize, SubscriptionChanges)>,
     /// A flag indicating whether the Subscription is currently subscribed to through the server or not.
     is_subscribed: bool,
+    /// A flag indicating whether the snapshot is requested again on a new session, replacing the
+    /// state received on a previous session.
+    snapshot_refresh: bool,
+    /// State of the active Subscription shared with the dispatch watchdog.
+    watched: Option<Arc<WatchedSubscription>>,
 }
 
 impl Subscription {
@@ -99,16 +257,41 @@ impl Subscription {
     /// - `items`: An array of items to be subscribed to through Lightstreamer server. It is also possible to specify the "Item List" or "Item Group" later.
     /// - `fields`: An array of fields for the items to be subscribed to through Lightstreamer Server. It is also possible to specify the "Field List" or "Field Schema" later.
     ///
+    /// Items and fields can be supplied as any collection of strings, such as `Vec<String>`,
+    /// arrays or slices of `&str`.
+    ///
     /// # Errors
     /// Returns an error if no items or fields are provided.
-    pub fn new(
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+    ///
+    /// let subscription = Subscription::new(
+    ///     SubscriptionMode::Merge,
+    ///     Some(["item1", "item2"]),
+    ///     Some(["stock_name", "last_price"]),
+    /// )
+    /// .unwrap();
+    /// assert_eq!(subscription.get_items().unwrap(), &["item1", "item2"]);
+    /// ```
+    pub fn new<I, F>(
         mode: SubscriptionMode,
-        items: Option<Vec<String>>,
-        fields: Option<Vec<String>>,
-    ) -> Result<Subscription, Box<dyn Error>> {
+        items: Option<I>,
+        fields: Option<F>,
+    ) -> Result<Subscription, Box<dyn Error>>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+        F: IntoIterator,
+        F::Item: AsRef<str>,
+    {
         if items.is_none() || fields.is_none() {
             return Err("Items and fields must be provided".to_string().into());
         }
+        let items = items.map(to_strings);
+        let fields = fields.map(to_strings);
 
         Ok(Subscription {
             mode,
@@ -125,13 +308,67 @@ impl Subscription {
             requested_snapshot: None,
             selector: None,
             listeners: Vec::new(),
-            values: HashMap::new(),
-            command_values: HashMap::new(),
+            listener_keys: Vec::new(),
+            next_listener_key: 0,
+            pooled_listeners: Arc::new(Mutex::new(Vec::new())),
+            dispatch_mode: DispatchMode::Ordered,
+            backpressure_policy: BackpressurePolicy::Unbounded,
+            conflation_frequency: None,
+            values: AHashMap::new(),
+            command_values: AHashMap::new(),
+            command_positions: None,
+            field_names: None,
+            history_length: 0,
+            history: AHashMap::new(),
+            latency_field: None,
+            latencies: VecDeque::new(),
+            latency_samples: 0,
+            updates_received: 0,
+            last_update_at: None,
+            snapshot_updates: 0,
+            lost_updates: 0,
+            real_max_frequency: None,
             is_active: false,
+            client_link: None,
             is_subscribed: false,
+            snapshot_refresh: false,
+            watched: None,
         })
     }
 
+    /// Constructor for creating a new Subscription instance for a single item, which is the most
+    /// common case. The resulting Subscription behaves as one created with an "Item List" of one
+    /// element, so `get_items()` returns a list containing only the given item.
+    ///
+    /// # Parameters
+    /// - `mode`: The subscription mode for the item, required by Lightstreamer Server.
+    /// - `item`: The item to be subscribed to through Lightstreamer server.
+    /// - `fields`: An array of fields for the item to be subscribed to through Lightstreamer Server.
+    ///
+    /// # Errors
+    /// Returns an error if the Subscription can't be created, as documented for `new()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+    ///
+    /// let subscription =
+    ///     Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["last_price"]).unwrap();
+    /// assert_eq!(subscription.get_items().unwrap(), &["item1"]);
+    /// ```
+    pub fn new_single_item<F>(
+        mode: SubscriptionMode,
+        item: &str,
+        fields: F,
+    ) -> Result<Subscription, Box<dyn Error>>
+    where
+        F: IntoIterator,
+        F::Item: AsRef<str>,
+    {
+        Subscription::new(mode, Some([item]), Some(fields))
+    }
+
     /// Adds a listener that will receive events from the Subscription instance.
     ///
     /// The same listener can be added to several different Subscription instances.
@@ -142,10 +379,245 @@ impl Subscription {
     /// # Parameters
     /// - `listener`: An object that will receive the events as documented in the SubscriptionListener interface.
     ///
+    /// # Returns
+    /// The token identifying the listener, to be passed to `removeListenerByToken()`.
+    ///
     /// # See also
-    /// `removeListener()`
-    pub fn add_listener(&mut self, listener: Box<dyn SubscriptionListener>) {
+    /// `removeListener()`, `removeListenerByToken()`
+    pub fn add_listener(
+        &mut self,
+        listener: Box<dyn SubscriptionListener>,
+    ) -> SubscriptionListenerToken {
+        let key = self.next_listener_key;
+        self.next_listener_key += 1;
         self.listeners.push(listener);
+        self.listener_keys.push(key);
+        SubscriptionListenerToken(key)
+    }
+
+    /// Adds a listener held through a weak reference, so that it receives events from the
+    /// Subscription instance only as long as other owners keep it alive. Once the listener is
+    /// dropped, it is removed from the Subscription before the next event is dispatched, without
+    /// receiving `on_listen_end()`.
+    ///
+    /// # Parameters
+    /// - `listener`: An object that will receive the events as documented in the SubscriptionListener
+    ///   interface, owned by the component interested in them. It is shared behind a `Mutex`, as
+    ///   some of the events require mutable access to it.
+    ///
+    /// # Returns
+    /// The token identifying the listener, to be passed to `removeListenerByToken()` while it is
+    /// alive.
+    ///
+    /// # See also
+    /// `addListener()`, `WeakSubscriptionListener`
+    pub fn add_weak_listener<L: SubscriptionListener + 'static>(
+        &mut self,
+        listener: &Arc<Mutex<L>>,
+    ) -> SubscriptionListenerToken {
+        self.add_listener(Box::new(WeakSubscriptionListener::new(listener)))
+    }
+
+    /// Adds a listener that calls the given closure with each update pertaining to an item of the
+    /// Subscription, sparing the definition of a `SubscriptionListener` for simple handlers.
+    /// Other events are ignored.
+    ///
+    /// # Parameters
+    /// - `handler`: The closure called as `SubscriptionListener.on_item_update()`.
+    ///
+    /// # Returns
+    /// The token identifying the listener, to be passed to `removeListenerByToken()`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// # use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+    /// let mut subscription =
+    ///     Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["last_price"]).unwrap();
+    /// let token = subscription.on_update(|update| {
+    ///     println!("Last price: {:?}", update.get_value("last_price"));
+    /// });
+    /// // The closure is no longer called once its listener is removed.
+    /// subscription.remove_listener_by_token(token).unwrap();
+    /// ```
+    ///
+    /// # See also
+    /// `addListener()`, `on_error()`
+    pub fn on_update<F>(&mut self, handler: F) -> SubscriptionListenerToken
+    where
+        F: FnMut(&ItemUpdate) + Send + 'static,
+    {
+        self.add_listener(Box::new(UpdateHandler(Mutex::new(handler))))
+    }
+
+    /// Adds a listener that calls the given closure when the Server refuses the Subscription,
+    /// with the error code and the description of the error, if any. Other events are ignored.
+    ///
+    /// # Parameters
+    /// - `handler`: The closure called as `SubscriptionListener.on_subscription_error()`.
+    ///
+    /// # Returns
+    /// The token identifying the listener, to be passed to `removeListenerByToken()`.
+    ///
+    /// # See also
+    /// `addListener()`, `on_update()`
+    pub fn on_error<F>(&mut self, handler: F) -> SubscriptionListenerToken
+    where
+        F: FnMut(i32, Option<&str>) + Send + 'static,
+    {
+        self.add_listener(Box::new(ErrorHandler(handler)))
+    }
+
+    /// Adds a listener that will receive events from the Subscription instance, replaying to it
+    /// the current state of the items first, so that a listener added to an already subscribed
+    /// Subscription doesn't start from a blank state.
+    ///
+    /// The current state is replayed through `SubscriptionListener.onItemUpdate()` calls, made
+    /// before this method returns and flagged as snapshot: one for each item with values, carrying
+    /// the latest value of each field, or, on COMMAND Subscriptions, one "ADD" event for each key
+    /// currently present. Null fields are replayed as having no value. Updates received before
+    /// the call but not yet delivered to the listeners are already included in the replayed state,
+    /// so they are not delivered to the new listener again.
+    ///
+    /// # Lifecycle
+    /// A listener can be added at any time; while the Subscription is active, it is reached
+    /// through `LightstreamerClient.withSubscription()`.
+    ///
+    /// # Parameters
+    /// - `listener`: An object that will receive the events as documented in the SubscriptionListener interface.
+    ///
+    /// # Returns
+    /// The token identifying the listener, to be passed to `removeListenerByToken()`.
+    ///
+    /// # See also
+    /// `addListener()`
+    pub fn add_listener_with_replay(
+        &mut self,
+        listener: Box<dyn SubscriptionListener>,
+    ) -> SubscriptionListenerToken {
+        for update in self.current_state() {
+            listener.on_item_update(&update);
+        }
+        if self.updates_received == 0 {
+            return self.add_listener(listener);
+        }
+        self.add_listener(Box::new(ReplayedListener {
+            listener,
+            replayed_through: self.updates_received,
+        }))
+    }
+
+    /// Rebuilds the current state of the items as snapshot updates, in item and key order.
+    fn current_state(&self) -> Vec<ItemUpdate> {
+        let Some(field_names) = &self.field_names else {
+            return Vec::new();
+        };
+        let set_value = |changes: &mut Vec<FieldValue>, field_pos: usize, value: &String| {
+            if let Some(change) = field_pos
+                .checked_sub(1)
+                .and_then(|index| changes.get_mut(index))
+            {
+                *change = FieldValue::Value(value.clone());
+            }
+        };
+        let mut items: BTreeMap<usize, Vec<FieldValue>> = BTreeMap::new();
+        for (&(item_pos, field_pos), value) in &self.values {
+            let changes = items
+                .entry(item_pos)
+                .or_insert_with(|| vec![FieldValue::Unchanged; field_names.len()]);
+            set_value(changes, field_pos, value);
+        }
+        let mut states: Vec<(usize, Vec<FieldValue>)> = items.into_iter().collect();
+        let command_index = self
+            .command_positions
+            .map(|(_, command_pos)| command_pos)
+            .or_else(|| field_names.get_position("command"))
+            .and_then(|pos| pos.checked_sub(1));
+        let rows: BTreeMap<_, _> = self.command_values.iter().collect();
+        for ((item_pos, _), values) in rows {
+            let mut changes = vec![FieldValue::Unchanged; field_names.len()];
+            for (&field_pos, value) in values {
+                set_value(&mut changes, field_pos, value);
+            }
+            if let Some(change) = command_index.and_then(|index| changes.get_mut(index)) {
+                *change = FieldValue::Value("ADD".to_string());
+            }
+            states.push((*item_pos, changes));
+        }
+        states
+            .into_iter()
+            .map(|(item_pos, changes)| {
+                let item_name = self
+                    .items
+                    .as_ref()
+                    .and_then(|items| items.get(item_pos.wrapping_sub(1)))
+                    .cloned();
+                ItemUpdate::from_changes(
+                    item_name,
+                    item_pos,
+                    Arc::clone(field_names),
+                    changes,
+                    true,
+                )
+            })
+            .collect()
+    }
+
+    /// Returns a future that resolves to the first update received for any item of the
+    /// Subscription, which greatly simplifies request/response-style usage and smoke tests.
+    ///
+    /// The method must be called before the Subscription is given to
+    /// `LightstreamerClient.subscribe()`, while the returned future is awaited afterwards; it
+    /// relies on a listener added to the Subscription.
+    ///
+    /// # Parameters
+    /// - `timeout`: The maximum time to wait for the update, counted from when the future is
+    ///   first polled.
+    ///
+    /// # Errors
+    /// The future returns a `TimeoutException` if no update is received within the timeout.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// # use lightstreamer_client::ls_client::LightstreamerClient;
+    /// # use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+    /// # use std::time::Duration;
+    /// # async fn example(client: &mut LightstreamerClient) -> Result<(), Box<dyn std::error::Error>> {
+    /// let mut subscription =
+    ///     Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["last_price"])?;
+    /// let first_update = subscription.wait_first_update(Duration::from_secs(10));
+    /// client.subscribe(subscription);
+    /// client.connect().await?;
+    /// let update = first_update.await?;
+    /// println!("Last price: {:?}", update.get_value("last_price"));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn wait_first_update(
+        &mut self,
+        timeout: Duration,
+    ) -> impl Future<Output = Result<ItemUpdate, TimeoutException>> + Send + 'static {
+        let (sender, receiver) = oneshot::channel();
+        self.add_listener(Box::new(FirstUpdateListener {
+            sender: Mutex::new(Some(sender)),
+        }));
+        async move {
+            let first_update = async {
+                match receiver.await {
+                    Ok(update) => update,
+                    // The Subscription was dropped, so no update can arrive anymore.
+                    Err(_) => std::future::pending().await,
+                }
+            };
+            tokio::select! {
+                update = first_update => Ok(update),
+                _ = CurrentRuntime::sleep(timeout) => Err(TimeoutException::new(&format!(
+                    "No update received within {:?}",
+                    timeout
+                ))),
+            }
+        }
     }
 
     /// Removes a listener from the Subscription instance so that it will not receive events anymore.
@@ -162,11 +634,45 @@ impl Subscription {
     where
         T: SubscriptionListener,
     {
-        self.listeners.retain(|l| {
+        let listener_ref = listener as &dyn SubscriptionListener;
+        let position = self.listeners.iter().position(|l| {
             let l_ref = l.as_ref() as &dyn SubscriptionListener;
-            let listener_ref = listener as &dyn SubscriptionListener;
-            !(std::ptr::addr_of!(*l_ref) == std::ptr::addr_of!(*listener_ref))
+            std::ptr::addr_of!(*l_ref) == std::ptr::addr_of!(*listener_ref)
         });
+        if let Some(position) = position {
+            self.listeners.remove(position);
+            self.listener_keys.remove(position);
+        }
+    }
+
+    /// Removes the listener identified by the token from the Subscription instance so that it will
+    /// not receive events anymore. This is the only way to remove the listeners wrapping a closure,
+    /// added through `on_update()` and `on_error()`.
+    ///
+    /// # Lifecycle
+    /// A listener can be removed at any time; while the Subscription is active, it is reached
+    /// through `LightstreamerClient.withSubscription()`.
+    ///
+    /// # Parameters
+    /// - `token`: The token returned when the listener to be removed was added.
+    ///
+    /// # Raises
+    /// - `IllegalArgumentException`: if the token doesn't identify a listener of this
+    ///   Subscription, for instance because it was already removed.
+    ///
+    /// # See also
+    /// `addListener()`, `removeListener()`
+    pub fn remove_listener_by_token(
+        &mut self,
+        token: SubscriptionListenerToken,
+    ) -> Result<(), IllegalArgumentException> {
+        let position = self
+            .listener_keys
+            .binary_search(&token.0)
+            .map_err(|_| IllegalArgumentException::new("No listener for the token"))?;
+        self.listeners.remove(position);
+        self.listener_keys.remove(position);
+        Ok(())
     }
 
     /// Returns a list containing the SubscriptionListener instances that were added to this client.
@@ -180,6 +686,182 @@ impl Subscription {
         &self.listeners
     }
 
+    /// Returns the listeners of the Subscription, so that the events requiring mutable access
+    /// can be dispatched to them.
+    pub(crate) fn get_listeners_mut(&mut self) -> &mut [Box<dyn SubscriptionListener>] {
+        &mut self.listeners
+    }
+
+    /// Removes the listeners that are no longer interested in any event, see
+    /// `SubscriptionListener.isExpired()`.
+    pub(crate) fn remove_expired_listeners(&mut self) {
+        let mut position = 0;
+        while position < self.listeners.len() {
+            if self.listeners[position].is_expired() {
+                self.listeners.remove(position);
+                self.listener_keys.remove(position);
+            } else {
+                position += 1;
+            }
+        }
+    }
+
+    /// Adds a listener that will receive the item updates of the Subscription, dispatched according
+    /// to the mode set through `setDispatchMode()`. Only `SubscriptionListener.onItemUpdate()` is
+    /// invoked on pooled listeners; with `DispatchMode::Pooled` it can be invoked concurrently for
+    /// different items, hence the listener must be `Sync`.
+    ///
+    /// # Lifecycle
+    /// A listener can be added at any time. Updates already dispatched are not delivered to it.
+    ///
+    /// # Parameters
+    /// - `listener`: An object that will receive the item updates of the Subscription.
+    ///
+    /// # See also
+    /// `setDispatchMode()`
+    pub fn add_pooled_listener(&mut self, listener: Arc<dyn SubscriptionListener + Sync>) {
+        self.pooled_listeners.lock().unwrap().push(listener);
+    }
+
+    /// Removes a listener added through `addPooledListener()`, if present.
+    ///
+    /// # Parameters
+    /// - `listener`: The listener to be removed.
+    pub fn remove_pooled_listener(&mut self, listener: &Arc<dyn SubscriptionListener + Sync>) {
+        self.pooled_listeners
+            .lock()
+            .unwrap()
+            .retain(|other| !Arc::ptr_eq(other, listener));
+    }
+
+    /// Returns the listeners added through `addPooledListener()`, shared with the workers
+    /// dispatching the item updates to them.
+    pub(crate) fn get_pooled_listeners(
+        &self,
+    ) -> Arc<Mutex<Vec<Arc<dyn SubscriptionListener + Sync>>>> {
+        Arc::clone(&self.pooled_listeners)
+    }
+
+    /// Setter method that sets the policy used to dispatch the item updates to the listeners added
+    /// through `addPooledListener()`.
+    ///
+    /// # Default
+    /// `DispatchMode::Ordered`.
+    ///
+    /// # Lifecycle
+    /// This method can only be called while the Subscription instance is in its "inactive" state.
+    ///
+    /// # Errors
+    /// - Returns an error if the Subscription is currently "active".
+    /// - Returns an error if a pool of zero workers is requested.
+    ///
+    /// # Parameters
+    /// - `mode`: The dispatch mode of the item updates.
+    pub fn set_dispatch_mode(&mut self, mode: DispatchMode) -> Result<(), String> {
+        if self.is_active {
+            return Err("Subscription is active".to_string());
+        }
+        if mode == DispatchMode::Pooled(0) {
+            return Err("The dispatch pool must have at least one worker".to_string());
+        }
+        self.dispatch_mode = mode;
+        Ok(())
+    }
+
+    /// Inquiry method that can be used to read the dispatch mode set through `setDispatchMode()`.
+    ///
+    /// # Lifecycle
+    /// This method can be called at any time.
+    pub fn get_dispatch_mode(&self) -> DispatchMode {
+        self.dispatch_mode
+    }
+
+    /// Setter method that sets the policy applied to the item updates of the Subscription when its
+    /// listeners can't keep up with them.
+    ///
+    /// # Default
+    /// `BackpressurePolicy::Unbounded`.
+    ///
+    /// # Lifecycle
+    /// This method can only be called while the Subscription instance is in its "inactive" state.
+    ///
+    /// # Errors
+    /// - Returns an error if the Subscription is currently "active".
+    /// - Returns an error if a buffer of zero updates is requested.
+    ///
+    /// # Parameters
+    /// - `policy`: The backpressure policy.
+    pub fn set_backpressure_policy(&mut self, policy: BackpressurePolicy) -> Result<(), String> {
+        if self.is_active {
+            return Err("Subscription is active".to_string());
+        }
+        if let BackpressurePolicy::Block(0)
+        | BackpressurePolicy::DropOldest(0)
+        | BackpressurePolicy::DropNewest(0) = policy
+        {
+            return Err("Backpressure buffer size must be a positive number".to_string());
+        }
+        self.backpressure_policy = policy;
+        Ok(())
+    }
+
+    /// Inquiry method that can be used to read the backpressure policy set through
+    /// `setBackpressurePolicy()`.
+    ///
+    /// # Lifecycle
+    /// This method can be called at any time.
+    pub fn get_backpressure_policy(&self) -> BackpressurePolicy {
+        self.backpressure_policy
+    }
+
+    /// Setter method that enables client-side conflation of the updates, which can only be used
+    /// if the Subscription mode is MERGE. The updates received for the same item within the
+    /// interval corresponding to the given frequency are coalesced into a single delivery, carrying
+    /// the latest values and all the fields changed in the meantime.
+    ///
+    /// Unlike `setRequestedMaxFrequency()`, this doesn't change the frequency of the updates sent
+    /// by the Server, but reduces the load on listeners, like the ones refreshing a slow UI.
+    ///
+    /// # Default
+    /// `None`, meaning that each update is delivered as soon as it's received.
+    ///
+    /// # Lifecycle
+    /// This method can only be called while the Subscription instance is in its "inactive" state.
+    ///
+    /// # Errors
+    /// - Returns an error if the Subscription is currently "active".
+    /// - Returns an error if the Subscription mode is not MERGE.
+    /// - Returns an error if the specified value is not `None` nor a valid positive number.
+    ///
+    /// # Parameters
+    /// - `freq`: The maximum number of deliveries per second for each item, or `None` to disable
+    ///   conflation.
+    ///
+    /// # See also
+    /// `Subscription.setRequestedMaxFrequency()`
+    pub fn set_conflation_frequency(&mut self, freq: Option<f64>) -> Result<(), String> {
+        if self.is_active {
+            return Err("Subscription is active".to_string());
+        }
+        if self.mode != SubscriptionMode::Merge {
+            return Err("Subscription mode is not Merge".to_string());
+        }
+        if freq.is_some_and(|freq| !freq.is_finite() || freq <= 0.0) {
+            return Err("Invalid conflation frequency".to_string());
+        }
+        self.conflation_frequency = freq;
+        Ok(())
+    }
+
+    /// Inquiry method that can be used to read the conflation frequency set through
+    /// `setConflationFrequency()`.
+    ///
+    /// # Lifecycle
+    /// This method can be called at any time.
+    pub fn get_conflation_frequency(&self) -> Option<f64> {
+        self.conflation_frequency
+    }
+
     /// Inquiry method that can be used to read the mode specified for this Subscription.
     ///
     /// # Lifecycle
@@ -208,6 +890,7 @@ impl Subscription {
             return Err("Subscription is active. This method can only be called while the Subscription instance is in its 'inactive' state.".to_string());
         }
         self.item_group = Some(group);
+        self.items = None;
         Ok(())
     }
 
@@ -234,17 +917,28 @@ impl Subscription {
     /// - Returns an error if any of the item names in the "Item List" contains a space, is a number, or is empty/None.
     ///
     /// # Parameters
-    /// - `items`: An array of items to be subscribed to through the server.
-    pub fn set_items(&mut self, items: Vec<String>) -> Result<(), String> {
+    /// - `items`: An array of items to be subscribed to through the server, as any collection of strings.
+    pub fn set_items<I>(&mut self, items: I) -> Result<(), String>
+    where
+        I: IntoIterator,
+        I::Item: AsRef<str>,
+    {
         if self.is_active {
             return Err("Subscription is active".to_string());
         }
+        self.replace_items(to_strings(items))
+    }
+
+    /// Replaces the "Item List" regardless of the "active" state, as done by
+    /// `LightstreamerClient.update_items()`.
+    pub(crate) fn replace_items(&mut self, items: Vec<String>) -> Result<(), String> {
         for item in &items {
             if item.contains(" ") || item.parse::<usize>().is_ok() || item.is_empty() {
                 return Err("Invalid item name".to_string());
             }
         }
         self.items = Some(items);
+        self.item_group = None;
         Ok(())
     }
 
@@ -277,6 +971,7 @@ impl Subscription {
             return Err("Subscription is active".to_string());
         }
         self.field_schema = Some(schema);
+        self.fields = None;
         Ok(())
     }
 
@@ -304,16 +999,27 @@ impl Subscription {
     ///
     /// # Parameters
     /// - `fields`: An array of fields to be subscribed to through the server.
-    pub fn set_fields(&mut self, fields: Vec<String>) -> Result<(), String> {
+    pub fn set_fields<F>(&mut self, fields: F) -> Result<(), String>
+    where
+        F: IntoIterator,
+        F::Item: AsRef<str>,
+    {
         if self.is_active {
             return Err("Subscription is active".to_string());
         }
+        self.replace_fields(to_strings(fields))
+    }
+
+    /// Replaces the "Field List" regardless of the "active" state, as done by
+    /// `LightstreamerClient.update_fields()`.
+    pub(crate) fn replace_fields(&mut self, fields: Vec<String>) -> Result<(), String> {
         for field in &fields {
             if field.contains(" ") || field.is_empty() {
                 return Err("Invalid field name".to_string());
             }
         }
         self.fields = Some(fields);
+        self.field_schema = None;
         Ok(())
     }
 
@@ -559,14 +1265,17 @@ impl Subscription {
     /// - Returns an error if the specified value is not `None` nor "unlimited" nor a valid positive integer number.
     ///
     /// # Parameters
-    /// - `size`: An integer number, representing the length of the internal queuing buffers to be used in the Server. If the string "unlimited" is supplied, then no buffer size limit is requested (the check is case insensitive). It is also possible to supply a `None` value to stick to the Server default (which currently depends on the subscription mode).
+    /// - `size`: `BufferSize::Size`, representing the length of the internal queuing buffers to be used in the Server. If `BufferSize::Unlimited` is supplied, then no buffer size limit is requested. It is also possible to supply a `None` value to stick to the Server default (which currently depends on the subscription mode).
     ///
     /// # See also
     /// `Subscription.setRequestedMaxFrequency()`
-    pub fn set_requested_buffer_size(&mut self, size: Option<usize>) -> Result<(), String> {
+    pub fn set_requested_buffer_size(&mut self, size: Option<BufferSize>) -> Result<(), String> {
         if self.is_active {
             return Err("Subscription is active".to_string());
         }
+        if size == Some(BufferSize::Size(0)) {
+            return Err("Buffer size must be a positive number".to_string());
+        }
         self.requested_buffer_size = size;
         Ok(())
     }
@@ -577,8 +1286,8 @@ impl Subscription {
     /// This method can be called at any time.
     ///
     /// # Returns
-    /// An integer number, representing the buffer size to be requested to the server, or the string "unlimited", or `None`.
-    pub fn get_requested_buffer_size(&self) -> Option<&usize> {
+    /// `BufferSize::Size`, representing the buffer size to be requested to the server, or `BufferSize::Unlimited`, or `None`.
+    pub fn get_requested_buffer_size(&self) -> Option<&BufferSize> {
         self.requested_buffer_size.as_ref()
     }
 
@@ -605,20 +1314,25 @@ impl Subscription {
     /// - Returns an error if the Subscription is currently "active" and the current value of this property is "unfiltered".
     /// - Returns an error if the Subscription is currently "active" and the given parameter is `None` or "unfiltered".
     /// - Returns an error if the specified value is not `None` nor one of the special "unlimited" and "unfiltered" values nor a valid positive number.
+    /// - Returns an error if the specified value is not `None` and the Subscription mode is RAW.
     ///
     /// # Parameters
     /// - `freq`: A decimal number, representing the maximum update frequency (expressed in updates per second) for each item in the Subscription; for instance, with a setting of 0.5, for each single item, no more than one update every 2 seconds will be received. If the string "unlimited" is supplied, then no frequency limit is requested. It is also possible to supply the string "unfiltered", to ask for unfiltered dispatching, if it is allowed for the items, or a `None` value to stick to the Server default (which currently corresponds to "unlimited"). The check for the string constants is case insensitive.
+    ///   In this implementation, "unlimited" is expressed as `f64::INFINITY`.
     pub fn set_requested_max_frequency(&mut self, freq: Option<f64>) -> Result<(), String> {
-        if self.is_active && self.requested_max_frequency.is_none() {
-            return Err("Subscription is active and current value is unfiltered".to_string());
+        if freq.is_some_and(|freq| freq.is_nan() || freq <= 0.0) {
+            return Err("Invalid max frequency".to_string());
         }
-        if self.is_active && freq.is_none() {
-            return Err("Cannot set unfiltered while active".to_string());
+        if freq.is_some() && self.mode == SubscriptionMode::Raw {
+            return Err("Cannot request max frequency for Raw mode".to_string());
         }
         if self.is_active && freq.is_none() {
             return Err("Cannot set None while active".to_string());
         }
         self.requested_max_frequency = freq;
+        if let Some((key, changes)) = &self.client_link {
+            changes.push(SubscriptionChange::Reconfigure(*key));
+        }
         Ok(())
     }
 
@@ -650,7 +1364,7 @@ impl Subscription {
     ///
     /// # Parameters
     /// - `snapshot`: "yes"/"no" to request/not request snapshot delivery (the check is case insensitive). If the Subscription mode is DISTINCT, instead of "yes", it is also possible to supply an integer number, to specify the requested length of the snapshot (though the length of the received snapshot may be less than
-    /// requested, because of insufficient data or server side limits); passing "yes" means that the snapshot length should be determined only by the Server. `None` is also a valid value; if specified, no snapshot preference will be sent to the server that will decide itself whether or not to send any snapshot.
+    ///   requested, because of insufficient data or server side limits); passing "yes" means that the snapshot length should be determined only by the Server. `None` is also a valid value; if specified, no snapshot preference will be sent to the server that will decide itself whether or not to send any snapshot.
     ///
     /// # See also
     /// `ItemUpdate.isSnapshot()`
@@ -659,15 +1373,16 @@ impl Subscription {
             return Err("Subscription is active".to_string());
         }
         match snapshot {
-            Some(Snapshot::None) => {
-                if self.mode == SubscriptionMode::Raw {
-                    return Err("Cannot request snapshot for Raw mode".to_string());
-                }
+            Some(Snapshot::Yes | Snapshot::No | Snapshot::Number(_))
+                if self.mode == SubscriptionMode::Raw =>
+            {
+                return Err("Cannot request snapshot for Raw mode".to_string());
             }
-            Some(Snapshot::Number(_)) => {
-                if self.mode != SubscriptionMode::Distinct {
-                    return Err("Cannot specify snapshot length for non-Distinct mode".to_string());
-                }
+            Some(Snapshot::Number(_)) if self.mode != SubscriptionMode::Distinct => {
+                return Err("Cannot specify snapshot length for non-Distinct mode".to_string());
+            }
+            Some(Snapshot::Number(0)) => {
+                return Err("Snapshot length must be a positive number".to_string());
             }
             _ => {}
         }
@@ -766,12 +1481,344 @@ impl Subscription {
         key: &str,
         field_pos: usize,
     ) -> Option<&String> {
-        let key = format!("{}_{}", item_pos, key);
         self.command_values
-            .get(&key)
+            .get(&(item_pos, key.to_string()))
             .and_then(|fields| fields.get(&field_pos))
     }
 
+    /// Returns the current consolidated state of the Subscription: the latest values of each item
+    /// or, for COMMAND Subscriptions, the latest values of each key of each item.
+    ///
+    /// Note that values can only be tracked for Subscriptions initialized using a "Field List",
+    /// and that internal data is cleared when a new session is created.
+    ///
+    /// # Lifecycle
+    /// This method can be called at any time; before any update is received, the returned snapshot
+    /// contains no values.
+    ///
+    /// # Returns
+    /// A copy of the current state of the Subscription.
+    ///
+    /// See also `getValue()`
+    ///
+    /// See also `getCommandValue()`
+    pub fn get_snapshot(&self) -> SubscriptionSnapshot {
+        let mut snapshot = SubscriptionSnapshot {
+            fields: self.fields.clone().unwrap_or_default(),
+            ..Default::default()
+        };
+        for (&(item_pos, field_pos), value) in &self.values {
+            snapshot
+                .items
+                .entry(item_pos)
+                .or_default()
+                .insert(field_pos, value.clone());
+        }
+        for ((item_pos, key), values) in &self.command_values {
+            snapshot.keys.entry(*item_pos).or_default().insert(
+                key.clone(),
+                values
+                    .iter()
+                    .map(|(&field_pos, value)| (field_pos, value.clone()))
+                    .collect(),
+            );
+        }
+        snapshot
+    }
+
+    /// Switches the Subscription to the "active" state, as done by `LightstreamerClient.subscribe()`,
+    /// linking it to the client it was given to under the given key.
+    pub(crate) fn activate(&mut self, key: usize, changes: SubscriptionChanges) {
+        self.is_active = true;
+        self.client_link = Some((key, changes));
+        let label = match (&self.item_group, &self.items) {
+            (Some(item_group), _) => item_group.clone(),
+            (None, Some(items)) => items.join(" "),
+            (None, None) => String::new(),
+        };
+        self.watched = Some(Arc::new(WatchedSubscription::new(label)));
+    }
+
+    /// Switches the Subscription back to the "inactive" state, as done by
+    /// `LightstreamerClient.unsubscribe()`, unlinking it from its client.
+    pub(crate) fn deactivate(&mut self) {
+        self.is_active = false;
+        self.is_subscribed = false;
+        self.client_link = None;
+    }
+
+    /// Gets the key of the active Subscription in the list of its client.
+    pub(crate) fn client_key(&self) -> Option<usize> {
+        self.client_link.as_ref().map(|(key, _)| *key)
+    }
+
+    /// Gets the state of the active Subscription shared with the dispatch watchdog.
+    pub(crate) fn get_watched(&self) -> Option<&Arc<WatchedSubscription>> {
+        self.watched.as_ref()
+    }
+
+    /// Setter method that sets how many of the latest updates of each item are kept in the history
+    /// of a DISTINCT Subscription, so that chat or news consumers can fetch the recent events
+    /// through `get_history()` without storing them themselves. When the limit is reached, the
+    /// oldest update of the item is discarded.
+    ///
+    /// # Default
+    /// 0, meaning that no history is kept.
+    ///
+    /// # Lifecycle
+    /// This method can be called at any time. Reducing the length discards the oldest updates
+    /// in excess.
+    ///
+    /// # Errors
+    /// Returns an error if the Subscription mode is not DISTINCT and the length is not 0.
+    ///
+    /// # Parameters
+    /// - `length`: The maximum number of updates kept for each item.
+    pub fn set_history_length(&mut self, length: usize) -> Result<(), String> {
+        if length > 0 && self.mode != SubscriptionMode::Distinct {
+            return Err("History is only available for Distinct mode".to_string());
+        }
+        self.history_length = length;
+        for updates in self.history.values_mut() {
+            let excess = updates.len().saturating_sub(length);
+            updates.drain(..excess);
+        }
+        Ok(())
+    }
+
+    /// Inquiry method that can be used to read the history length, configured through `setHistoryLength()`.
+    ///
+    /// # Returns
+    /// The maximum number of updates kept for each item.
+    pub fn get_history_length(&self) -> usize {
+        self.history_length
+    }
+
+    /// Returns the latest updates received for the specified item, oldest first, as kept according
+    /// to `setHistoryLength()`.
+    ///
+    /// Note that internal data is cleared when a new session is created.
+    ///
+    /// # Parameters
+    /// - `item_pos`: The 1-based position of the item in the "Item List" or "Item Group".
+    ///
+    /// # Returns
+    /// An iterator over the updates kept for the item, which is empty if none was received yet.
+    pub fn get_history(&self, item_pos: usize) -> impl Iterator<Item = &ItemUpdate> + '_ {
+        self.history.get(&item_pos).into_iter().flatten()
+    }
+
+    /// Setter method that enables the measurement of the latency of the real-time updates, i.e.
+    /// the time elapsed from the server timestamp carried by the given field to their reception,
+    /// so that market-data consumers can monitor the end-to-end delay through
+    /// `getLatencyStats()`.
+    ///
+    /// The field must carry the milliseconds since the Unix epoch, as read from the clock of the
+    /// Server or of the Data Adapter; timestamps are aligned with the local clock through the
+    /// estimate of `LightstreamerClient.getClockSkew()`, when available. Only the updates that
+    /// change the field are measured, while snapshot updates are ignored.
+    ///
+    /// # Default
+    /// `None`, meaning that the latency is not measured.
+    ///
+    /// # Lifecycle
+    /// This method can be called at any time. The measurements taken so far are discarded.
+    ///
+    /// # Parameters
+    /// - `field`: The name or the 1-based position of the field carrying the server timestamp, or
+    ///   `None` to disable the measurement.
+    pub fn set_latency_field(&mut self, field: Option<String>) {
+        self.latency_field = field;
+        self.latencies.clear();
+        self.latency_samples = 0;
+    }
+
+    /// Inquiry method that can be used to read the field whose server timestamp is used to
+    /// measure the latency of the updates, configured through `setLatencyField()`.
+    ///
+    /// # Returns
+    /// The name or position of the field, or `None` if the latency is not measured.
+    pub fn get_latency_field(&self) -> Option<&String> {
+        self.latency_field.as_ref()
+    }
+
+    /// Inquiry method that gets the percentiles of the latency of the latest real-time updates,
+    /// measured as configured through `setLatencyField()`.
+    ///
+    /// # Returns
+    /// A `LatencyStats` snapshot, computed over the latest measurements, or `None` if no latency
+    /// was measured yet.
+    pub fn get_latency_stats(&self) -> Option<LatencyStats> {
+        LatencyStats::from_latencies(self.latency_samples, &self.latencies)
+    }
+
+    /// Inquiry method that gets the counters of this Subscription: the updates received, the
+    /// snapshot and lost ones, the instant of the last update and the maximum update frequency
+    /// granted by the Server.
+    ///
+    /// # Lifecycle
+    /// This method can be called at any time.
+    ///
+    /// # Returns
+    /// A `SubscriptionStats` snapshot of the counters.
+    pub fn get_stats(&self) -> SubscriptionStats {
+        SubscriptionStats {
+            updates_received: self.updates_received,
+            snapshot_updates: self.snapshot_updates,
+            lost_updates: self.lost_updates,
+            last_update_at: self.last_update_at,
+            real_max_frequency: self.real_max_frequency,
+        }
+    }
+
+    /// Records the updates notified as lost by the server.
+    pub(crate) fn record_lost_updates(&mut self, lost_updates: u64) {
+        self.lost_updates += lost_updates;
+    }
+
+    /// Sets the maximum update frequency granted by the server, where an infinite frequency
+    /// means unlimited.
+    pub(crate) fn set_real_max_frequency(&mut self, frequency: Option<f64>) {
+        self.real_max_frequency = frequency;
+    }
+
+    /// Records the latency measured on a real-time update, keeping only the latest measurements.
+    pub(crate) fn record_latency(&mut self, latency: Duration) {
+        if self.latencies.len() == LATENCY_WINDOW {
+            self.latencies.pop_front();
+        }
+        self.latencies.push_back(latency);
+        self.latency_samples += 1;
+    }
+
+    /// Stores the field values carried by an update, so that they are available through
+    /// `get_value()`, `get_command_value()`, `get_snapshot()` and `get_history()`. Returns the
+    /// sequence number of the update, so that the listeners added through
+    /// `add_listener_with_replay()` can tell the updates already included in their replay.
+    pub(crate) fn store_update(&mut self, update: &ItemUpdate) -> u64 {
+        self.updates_received += 1;
+        let sequence = self.updates_received;
+        self.last_update_at = Some(Instant::now());
+        self.field_names = Some(Arc::clone(update.get_field_names()));
+        if update.is_snapshot() {
+            self.snapshot_updates += 1;
+        }
+        let item_pos = update.get_item_pos();
+        if self.history_length > 0 {
+            let updates = self.history.entry(item_pos).or_default();
+            if updates.len() == self.history_length {
+                updates.pop_front();
+            }
+            updates.push_back(update.clone());
+        }
+        // Null fields are removed, so that they are reported as None like the fields with no
+        // value yet.
+        let values = (1..=update.get_field_names().len()).filter_map(|field_pos| {
+            match update.get_value_by_position(field_pos) {
+                Some(value) => Some((field_pos, Some(value.to_string()))),
+                None => update
+                    .is_null(&field_pos.to_string())
+                    .then_some((field_pos, None)),
+            }
+        });
+        if self.mode != SubscriptionMode::Command {
+            for (field_pos, value) in values {
+                match value {
+                    Some(value) => self.values.insert((item_pos, field_pos), value),
+                    None => self.values.remove(&(item_pos, field_pos)),
+                };
+            }
+            return sequence;
+        }
+        let (key_pos, command_pos) = match self.command_positions {
+            Some(positions) => positions,
+            None => {
+                let field_names = update.get_field_names();
+                match (
+                    field_names.get_position("key"),
+                    field_names.get_position("command"),
+                ) {
+                    (Some(key_pos), Some(command_pos)) => (key_pos, command_pos),
+                    _ => return sequence,
+                }
+            }
+        };
+        let Some(key) = update.get_value_by_position(key_pos) else {
+            return sequence;
+        };
+        let row = (item_pos, key.to_string());
+        if update
+            .get_value_by_position(command_pos)
+            .is_some_and(|command| command.eq_ignore_ascii_case("DELETE"))
+        {
+            self.command_values.remove(&row);
+        } else {
+            let row_values = self.command_values.entry(row).or_default();
+            for (field_pos, value) in values {
+                match value {
+                    Some(value) => row_values.insert(field_pos, value),
+                    None => row_values.remove(&field_pos),
+                };
+            }
+        }
+        sequence
+    }
+
+    /// Builds the state of this Subscription reported by `LightstreamerClient.debugState()`.
+    pub(crate) fn debug_state(&self, subscription_id: Option<usize>) -> SubscriptionDebugState {
+        SubscriptionDebugState {
+            mode: self.mode,
+            items: match (&self.items, &self.item_group) {
+                (Some(items), _) => items.join(" "),
+                (None, Some(item_group)) => item_group.clone(),
+                (None, None) => String::new(),
+            },
+            fields: match (&self.fields, &self.field_schema) {
+                (Some(fields), _) => fields.join(" "),
+                (None, Some(field_schema)) => field_schema.clone(),
+                (None, None) => String::new(),
+            },
+            active: self.is_active,
+            subscription_id,
+            updates_received: self.updates_received,
+            since_last_update: self.last_update_at.map(|instant| instant.elapsed()),
+        }
+    }
+
+    /// Clears the values stored through `store_update()`.
+    pub(crate) fn clear_values(&mut self) {
+        self.values.clear();
+        self.command_values.clear();
+        self.history.clear();
+        self.snapshot_refresh = false;
+    }
+
+    /// Sets whether the snapshot of the Subscription is requested again on a new session, after
+    /// the session it was subscribed to in was lost.
+    pub(crate) fn set_snapshot_refresh(&mut self, snapshot_refresh: bool) {
+        self.snapshot_refresh = snapshot_refresh;
+    }
+
+    /// Tells whether the snapshot of the Subscription is requested again on a new session. See
+    /// `ItemUpdate.isSnapshotRefresh()`.
+    pub(crate) fn is_snapshot_refresh(&self) -> bool {
+        self.snapshot_refresh
+    }
+
+    /// Sets whether the Subscription is currently subscribed to through the server.
+    pub(crate) fn set_subscribed(&mut self, subscribed: bool) {
+        self.is_subscribed = subscribed;
+        if !subscribed {
+            self.real_max_frequency = None;
+        }
+    }
+
+    /// Sets the 1-based positions of the "key" and "command" fields notified by the server for a
+    /// COMMAND Subscription.
+    pub(crate) fn set_command_positions(&mut self, key_pos: usize, command_pos: usize) {
+        self.command_positions = Some((key_pos, command_pos));
+    }
+
     /// Inquiry method that checks if the Subscription is currently "active" or not. Most of the Subscription properties cannot be modified if a Subscription is "active".
     ///
     /// The status of a Subscription is changed to "active" through the `LightstreamerClient.subscribe()` method and back to "inactive" through the `LightstreamerClient.unsubscribe()` one.
@@ -804,6 +1851,24 @@ impl Subscription {
         self.is_subscribed
     }
 
+    /// Inquiry method that checks if the Subscription was isolated by the dispatch watchdog,
+    /// because one of its listeners stalled. The listeners of an isolated Subscription receive no
+    /// further notifications, while it stays active and subscribed to through the server.
+    ///
+    /// # Lifecycle
+    /// This method can be called at any time. Subscribing to the Subscription again, after
+    /// unsubscribing from it, lifts the isolation.
+    ///
+    /// # Returns
+    /// `true`/`false` if the Subscription is isolated or not.
+    ///
+    /// See also `ConnectionOptions.setStalledSubscriptionIsolationEnabled()`
+    pub fn is_isolated(&self) -> bool {
+        self.watched
+            .as_ref()
+            .is_some_and(|watched| watched.is_isolated())
+    }
+
     /// Returns the position of the "key" field in a COMMAND Subscription.
     ///
     /// This method can only be used if the Subscription mode is COMMAND and the Subscription was initialized using a "Field Schema".
@@ -816,15 +1881,13 @@ impl Subscription {
     /// - Returns an error if a "Field List" was specified.
     ///
     /// # Returns
-    /// The 1-based position of the "key" field within the "Field Schema".
+    /// The 1-based position of the "key" field within the "Field Schema", as notified by the Server.
     pub fn get_key_position(&self) -> Option<usize> {
         if self.mode != SubscriptionMode::Command || !self.is_subscribed {
             return None;
         }
-        if let Some(ref schema) = self.field_schema {
-            return schema.split(',').position(|field| field.trim() == "key");
-        }
-        None
+        self.field_schema.as_ref()?;
+        self.command_positions.map(|(key_pos, _)| key_pos)
     }
 
     /// Returns the position of the "command" field in a COMMAND Subscription.
@@ -838,17 +1901,12 @@ impl Subscription {
     /// - Returns an error if the Subscription mode is not COMMAND or if the `SubscriptionListener.onSubscription()` event for this Subscription was not yet fired.
     ///
     /// # Returns
-    /// The 1-based position of the "command" field within the "Field Schema".
+    /// The 1-based position of the "command" field within the "Field Schema", as notified by the Server.
     pub fn get_command_position(&self) -> Option<usize> {
         if self.mode != SubscriptionMode::Command || !self.is_subscribed {
             return None;
         }
-        if let Some(ref schema) = self.field_schema {
-            return schema
-                .split(',')
-                .position(|field| field.trim() == "command");
-        }
-        None
+        self.command_positions.map(|(_, command_pos)| command_pos)
     }
 
     /*
@@ -891,6 +1949,174 @@ impl Subscription {
     */
 }
 
+/// Listener used by `Subscription.wait_first_update()` to hand over the first update received.
+struct FirstUpdateListener {
+    sender: Mutex<Option<oneshot::Sender<ItemUpdate>>>,
+}
+
+impl SubscriptionListener for FirstUpdateListener {
+    fn on_item_update(&self, update: &ItemUpdate) {
+        if let Some(sender) = self.sender.lock().unwrap().take() {
+            let _ = sender.send(update.clone());
+        }
+    }
+}
+
+/// Listener used by `Subscription.on_update()` to call a closure with each update.
+struct UpdateHandler<F>(Mutex<F>);
+
+impl<F: FnMut(&ItemUpdate) + Send> SubscriptionListener for UpdateHandler<F> {
+    fn on_item_update(&self, update: &ItemUpdate) {
+        // A panic of the closure is caught by the library, so the closure can be called again.
+        (self.0.lock().unwrap_or_else(PoisonError::into_inner))(update);
+    }
+}
+
+/// Listener used by `Subscription.on_error()` to call a closure with each subscription error.
+struct ErrorHandler<F>(F);
+
+impl<F: FnMut(i32, Option<&str>) + Send> SubscriptionListener for ErrorHandler<F> {
+    fn on_item_update(&self, _update: &ItemUpdate) {}
+
+    fn on_subscription_error(&mut self, code: i32, message: Option<&str>) {
+        (self.0)(code, message);
+    }
+}
+
+/// Listener used by `Subscription.add_listener_with_replay()` to skip the updates that were
+/// stored before the replay, which are still to be delivered but are part of the replayed state.
+struct ReplayedListener {
+    listener: Box<dyn SubscriptionListener>,
+    replayed_through: u64,
+}
+
+impl ReplayedListener {
+    fn is_replayed(&self, update: &ItemUpdate) -> bool {
+        update.sequence != 0 && update.sequence <= self.replayed_through
+    }
+}
+
+impl SubscriptionListener for ReplayedListener {
+    fn is_expired(&self) -> bool {
+        self.listener.is_expired()
+    }
+
+    fn on_clear_snapshot(&mut self, item_name: Option<&str>, item_pos: usize) {
+        self.listener.on_clear_snapshot(item_name, item_pos);
+    }
+
+    fn on_command_second_level_item_lost_updates(&mut self, lost_updates: u32, key: &str) {
+        self.listener
+            .on_command_second_level_item_lost_updates(lost_updates, key);
+    }
+
+    fn on_command_second_level_subscription_error(
+        &mut self,
+        code: i32,
+        message: Option<&str>,
+        key: &str,
+    ) {
+        self.listener
+            .on_command_second_level_subscription_error(code, message, key);
+    }
+
+    fn on_end_of_snapshot(&mut self, item_name: Option<&str>, item_pos: usize) {
+        self.listener.on_end_of_snapshot(item_name, item_pos);
+    }
+
+    fn on_item_lost_updates(
+        &mut self,
+        item_name: Option<&str>,
+        item_pos: usize,
+        lost_updates: u32,
+    ) {
+        self.listener
+            .on_item_lost_updates(item_name, item_pos, lost_updates);
+    }
+
+    fn on_item_update(&self, update: &ItemUpdate) {
+        if !self.is_replayed(update) {
+            self.listener.on_item_update(update);
+        }
+    }
+
+    fn on_item_updates(&self, updates: &[ItemUpdate]) {
+        if !updates.iter().any(|update| self.is_replayed(update)) {
+            self.listener.on_item_updates(updates);
+            return;
+        }
+        let fresh: Vec<ItemUpdate> = updates
+            .iter()
+            .filter(|update| !self.is_replayed(update))
+            .cloned()
+            .collect();
+        if !fresh.is_empty() {
+            self.listener.on_item_updates(&fresh);
+        }
+    }
+
+    fn on_listen_end(&mut self) {
+        self.listener.on_listen_end();
+    }
+
+    fn on_listen_start(&mut self) {
+        self.listener.on_listen_start();
+    }
+
+    fn on_real_max_frequency(&mut self, frequency: Option<f64>) {
+        self.listener.on_real_max_frequency(frequency);
+    }
+
+    fn on_subscription(&mut self) {
+        self.listener.on_subscription();
+    }
+
+    fn on_subscription_error(&mut self, code: i32, message: Option<&str>) {
+        self.listener.on_subscription_error(code, message);
+    }
+
+    fn on_unsubscription(&mut self) {
+        self.listener.on_unsubscription();
+    }
+}
+
+/// Gets the position of the Subscription with the given key in the list of a client, which is
+/// sorted by key as the keys are assigned in increasing order.
+pub(crate) fn subscription_position(subscriptions: &[Subscription], key: usize) -> Option<usize> {
+    subscriptions
+        .binary_search_by_key(&Some(key), Subscription::client_key)
+        .ok()
+}
+
+/// Finds the Subscription with the given key in the list of a client.
+pub(crate) fn find_subscription(
+    subscriptions: &[Subscription],
+    key: usize,
+) -> Option<&Subscription> {
+    subscriptions.get(subscription_position(subscriptions, key)?)
+}
+
+/// Finds the Subscription with the given key in the list of a client, for modification.
+pub(crate) fn find_subscription_mut(
+    subscriptions: &mut [Subscription],
+    key: usize,
+) -> Option<&mut Subscription> {
+    let position = subscription_position(subscriptions, key)?;
+    subscriptions.get_mut(position)
+}
+
+/// Collects a collection of string-like values into owned strings.
+fn to_strings<I>(values: I) -> Vec<String>
+where
+    I: IntoIterator,
+    I::Item: AsRef<str>,
+{
+    values
+        .into_iter()
+        .map(|value| value.as_ref().to_string())
+        .collect()
+}
+
 impl Debug for Subscription {
     fn fmt(&self, f: &mut Formatter) -> fmt::Result {
         f.debug_struct("Subscription")
@@ -916,8 +2142,13 @@ impl Debug for Subscription {
             .field("requested_max_frequency", &self.requested_max_frequency)
             .field("requested_snapshot", &self.requested_snapshot)
             .field("selector", &self.selector)
+            .field("history_length", &self.history_length)
+            .field("dispatch_mode", &self.dispatch_mode)
+            .field("backpressure_policy", &self.backpressure_policy)
+            .field("conflation_frequency", &self.conflation_frequency)
             .field("is_active", &self.is_active)
             .field("is_subscribed", &self.is_subscribed)
+            .field("is_isolated", &self.is_isolated())
             .finish()
     }
 }
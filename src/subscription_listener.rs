@@ -8,7 +8,23 @@ use crate::item_update::ItemUpdate;
 /// has changed. On the other hand, all the notifications for a single LightstreamerClient,
 /// including notifications to ClientListener, SubscriptionListener and ClientMessageListener
 /// will be dispatched by the same thread.
+///
+/// A panic raised by an event handler is caught and logged by the library, so that it doesn't
+/// stop the dispatching of the following events, to this or other listeners.
 pub trait SubscriptionListener: Send {
+    /// Inquiry method that tells whether the listener is no longer interested in any event, for
+    /// instance because the component owning it has been dropped. Expired listeners are removed
+    /// from the `Subscription` before the next event is dispatched, without being notified.
+    ///
+    /// # Returns
+    ///
+    /// `true` if the listener can be removed. The default implementation returns `false`.
+    ///
+    /// See also `WeakSubscriptionListener`
+    fn is_expired(&self) -> bool {
+        false
+    }
+
     /// Event handler that is called by Lightstreamer each time a request to clear the snapshot
     /// pertaining to an item in the Subscription has been received from the Server.
     /// More precisely, this kind of request can occur in two cases:
@@ -34,7 +50,6 @@ pub trait SubscriptionListener: Send {
     /// - `item_pos`: 1-based position of the item within the "Item List" or "Item Group".
     fn on_clear_snapshot(&mut self, _item_name: Option<&str>, _item_pos: usize) {
         // Default implementation does nothing.
-        unimplemented!("Implement on_clear_snapshot method for SubscriptionListener.");
     }
 
     /// Event handler that is called by Lightstreamer to notify that, due to internal resource
@@ -57,9 +72,6 @@ pub trait SubscriptionListener: Send {
     /// - `Subscription::set_command_second_level_field_schema()`
     fn on_command_second_level_item_lost_updates(&mut self, _lost_updates: u32, _key: &str) {
         // Default implementation does nothing.
-        unimplemented!(
-            "Implement on_command_second_level_item_lost_updates method for SubscriptionListener."
-        );
     }
 
     /// Event handler that is called when the Server notifies an error on a second-level subscription.
@@ -98,9 +110,6 @@ pub trait SubscriptionListener: Send {
         _key: &str,
     ) {
         // Default implementation does nothing.
-        unimplemented!(
-            "Implement on_command_second_level_subscription_error method for SubscriptionListener."
-        );
     }
 
     /// Event handler that is called by Lightstreamer to notify that all snapshot events for an item
@@ -128,7 +137,6 @@ pub trait SubscriptionListener: Send {
     /// - `ItemUpdate::is_snapshot()`
     fn on_end_of_snapshot(&mut self, _item_name: Option<&str>, _item_pos: usize) {
         // Default implementation does nothing.
-        unimplemented!("Implement on_end_of_snapshot method for SubscriptionListener.");
     }
 
     /// Event handler that is called by Lightstreamer to notify that, due to internal resource
@@ -142,6 +150,9 @@ pub trait SubscriptionListener: Send {
     ///
     /// By implementing this method it is possible to perform recovery actions.
     ///
+    /// The method is also invoked for the updates discarded by the client itself, according to
+    /// the `BackpressurePolicy` of the Subscription.
+    ///
     /// # Parameters
     ///
     /// - `item_name`: name of the involved item. If the Subscription was initialized using an
@@ -152,6 +163,7 @@ pub trait SubscriptionListener: Send {
     /// # See also
     ///
     /// - `Subscription::set_requested_max_frequency()`
+    /// - `Subscription::set_backpressure_policy()`
     fn on_item_lost_updates(
         &mut self,
         _item_name: Option<&str>,
@@ -159,7 +171,6 @@ pub trait SubscriptionListener: Send {
         _lost_updates: u32,
     ) {
         // Default implementation does nothing.
-        unimplemented!("Implement on_item_lost_updates method for SubscriptionListener.");
     }
 
     /// Event handler that is called by Lightstreamer each time an update pertaining to an item
@@ -172,7 +183,25 @@ pub trait SubscriptionListener: Send {
     ///   iterate through all or new values.
     fn on_item_update(&self, _update: &ItemUpdate) {
         // Default implementation does nothing.
-        unimplemented!("Implement on_item_update method for SubscriptionListener.");
+    }
+
+    /// Event handler that is called by Lightstreamer with the updates pertaining to the items in
+    /// the Subscription that were received together from the Server, i.e. read from the same
+    /// network frame. It lets high-throughput consumers process them in bulk, amortizing the cost
+    /// of locking or of writes to a database.
+    ///
+    /// The default implementation calls `on_item_update()` for each update, so only one of the
+    /// two methods needs to be implemented. The updates whose delivery is deferred by a
+    /// `BackpressurePolicy`, by client-side conflation or by a `DispatchMode::Pooled` pool are
+    /// delivered in batches of one.
+    ///
+    /// # Parameters
+    ///
+    /// - `updates`: the updates, in the order they were received.
+    fn on_item_updates(&self, updates: &[ItemUpdate]) {
+        for update in updates {
+            self.on_item_update(update);
+        }
     }
 
     /// Event handler that receives a notification when the `SubscriptionListener` instance is
@@ -211,7 +240,6 @@ pub trait SubscriptionListener: Send {
     ///   rare cases, when the frequency can no longer be determined.
     fn on_real_max_frequency(&mut self, _frequency: Option<f64>) {
         // Default implementation does nothing.
-        unimplemented!("Implement on_real_max_frequency method for SubscriptionListener.");
     }
 
     /// Event handler that is called by Lightstreamer to notify that a Subscription has been successfully
@@ -267,7 +295,6 @@ pub trait SubscriptionListener: Send {
     /// - `ConnectionDetails::set_adapter_set()`
     fn on_subscription_error(&mut self, _code: i32, _message: Option<&str>) {
         // Default implementation does nothing.
-        unimplemented!("Implement on_subscription_error method for SubscriptionListener.");
     }
 
     /// Event handler that is called by Lightstreamer to notify that a Subscription has been successfully
@@ -1,4 +1,59 @@
+use crate::logger::LogCategory;
+use crate::ls_client::LogType;
+
+use std::panic::{self, AssertUnwindSafe};
+use tracing::Level;
+
 /// Clean the message from newlines and carriage returns and convert it to lowercase.
 pub fn clean_message(text: &str) -> String {
-    text.replace("\n", "").replace("\r", "").to_lowercase()
+    let mut cleaned = String::with_capacity(text.len());
+    for c in text.chars().filter(|&c| c != '\n' && c != '\r') {
+        if c.is_ascii() {
+            cleaned.push(c.to_ascii_lowercase());
+        } else {
+            cleaned.extend(c.to_lowercase());
+        }
+    }
+    cleaned
+}
+
+/// Returns a random duration between zero and the given maximum, used to spread out
+/// reconnection attempts.
+pub(crate) fn random_delay(max: std::time::Duration) -> std::time::Duration {
+    use std::collections::hash_map::RandomState;
+    use std::hash::{BuildHasher, Hasher};
+
+    let max_millis = max.as_millis() as u64;
+    if max_millis == 0 {
+        return std::time::Duration::ZERO;
+    }
+    // Every RandomState is seeded with fresh random keys, which is enough randomness for jitter.
+    let random = RandomState::new().build_hasher().finish();
+    std::time::Duration::from_millis(random % (max_millis + 1))
+}
+
+/// Invokes a listener callback, catching the panic it may raise so that a faulty listener can't
+/// kill the task dispatching the events: the panic is logged and the dispatching goes on with the
+/// next listener. Panics can only be caught when unwinding is enabled.
+///
+/// Listeners are not assumed to be unwind safe: a listener that panicked may be left in an
+/// inconsistent state, but the library state is not affected.
+pub(crate) fn call_listener(
+    logging: LogType,
+    category: LogCategory,
+    callback: &str,
+    call: impl FnOnce(),
+) {
+    if let Err(panic) = panic::catch_unwind(AssertUnwindSafe(call)) {
+        let reason = panic
+            .downcast_ref::<&str>()
+            .copied()
+            .or_else(|| panic.downcast_ref::<String>().map(String::as_str))
+            .unwrap_or("unknown reason");
+        logging.log(
+            category,
+            Level::ERROR,
+            &format!("Listener panicked in {}: {}", callback, reason),
+        );
+    }
 }
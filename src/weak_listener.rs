@@ -0,0 +1,252 @@
+//! Listeners held through weak references, which are removed automatically once the component
+//! owning them is dropped.
+//!
+//! A listener added to a `LightstreamerClient` or a `Subscription` is owned by it and receives
+//! events for as long as the client or the subscription lives. When the listener belongs to a
+//! shorter-lived component, such as a view or a per-request handler, it can instead be kept in an
+//! `Arc` by the component and added through a weak reference: the events are forwarded to it while
+//! the component holds the `Arc`, and the listener is removed from the client or the subscription
+//! before the first event following its drop.
+//!
+//! ```
+//! use lightstreamer_client::client_listener::ClientListener;
+//! use lightstreamer_client::ls_client::LightstreamerClient;
+//! use std::sync::Arc;
+//!
+//! #[derive(Debug)]
+//! struct StatusView;
+//!
+//! impl ClientListener for StatusView {
+//!     fn on_status_change(&self, status: &str) {
+//!         println!("Status: {}", status);
+//!     }
+//! }
+//!
+//! let client = LightstreamerClient::new(None, None, None, None).unwrap();
+//! let view = Arc::new(StatusView);
+//! client.add_weak_listener(&view);
+//! // Dropping the view unregisters its listener.
+//! drop(view);
+//! ```
+
+use crate::client_listener::ClientListener;
+use crate::disconnect_info::DisconnectInfo;
+use crate::item_update::ItemUpdate;
+use crate::session_end_cause::SessionEndCause;
+use crate::subscription_listener::SubscriptionListener;
+
+use std::fmt::{self, Debug, Formatter};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, Weak};
+
+/// `ClientListener` forwarding the events to a listener held through a weak reference, and
+/// expiring as soon as the listener is dropped. See `LightstreamerClient.addWeakListener()`.
+pub struct WeakClientListener<L: ?Sized> {
+    listener: Weak<L>,
+}
+
+impl<L: ClientListener + Sync + ?Sized> WeakClientListener<L> {
+    /// Creates a listener forwarding the events to the given one for as long as it has other
+    /// owners.
+    pub fn new(listener: &Arc<L>) -> WeakClientListener<L> {
+        WeakClientListener {
+            listener: Arc::downgrade(listener),
+        }
+    }
+}
+
+impl<L: ?Sized> Debug for WeakClientListener<L> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WeakClientListener")
+            .field("expired", &(self.listener.strong_count() == 0))
+            .finish()
+    }
+}
+
+impl<L: ClientListener + Sync + ?Sized> ClientListener for WeakClientListener<L> {
+    fn is_expired(&self) -> bool {
+        self.listener
+            .upgrade()
+            .is_none_or(|listener| listener.is_expired())
+    }
+
+    fn on_connection_error(&self, message: &str) {
+        if let Some(listener) = self.listener.upgrade() {
+            listener.on_connection_error(message);
+        }
+    }
+
+    fn on_disconnect(&self, info: &DisconnectInfo) {
+        if let Some(listener) = self.listener.upgrade() {
+            listener.on_disconnect(info);
+        }
+    }
+
+    fn on_listen_end(&self) {
+        if let Some(listener) = self.listener.upgrade() {
+            listener.on_listen_end();
+        }
+    }
+
+    fn on_listen_start(&self) {
+        if let Some(listener) = self.listener.upgrade() {
+            listener.on_listen_start();
+        }
+    }
+
+    fn on_property_change(&self, property: &str) {
+        if let Some(listener) = self.listener.upgrade() {
+            listener.on_property_change(property);
+        }
+    }
+
+    fn on_server_error(&self, code: i32, message: &str) {
+        if let Some(listener) = self.listener.upgrade() {
+            listener.on_server_error(code, message);
+        }
+    }
+
+    fn on_server_keepalive(&self) {
+        if let Some(listener) = self.listener.upgrade() {
+            listener.on_server_keepalive();
+        }
+    }
+
+    fn on_server_sync(&self, seconds: u64) {
+        if let Some(listener) = self.listener.upgrade() {
+            listener.on_server_sync(seconds);
+        }
+    }
+
+    fn on_session_end(&self, cause: &SessionEndCause) {
+        if let Some(listener) = self.listener.upgrade() {
+            listener.on_session_end(cause);
+        }
+    }
+
+    fn on_session_start(&self, session_id: &str) {
+        if let Some(listener) = self.listener.upgrade() {
+            listener.on_session_start(session_id);
+        }
+    }
+
+    fn on_status_change(&self, status: &str) {
+        if let Some(listener) = self.listener.upgrade() {
+            listener.on_status_change(status);
+        }
+    }
+}
+
+/// `SubscriptionListener` forwarding the events to a listener held through a weak reference, and
+/// expiring as soon as the listener is dropped. See `Subscription.addWeakListener()`.
+///
+/// The listener is shared behind a `Mutex`, as some of the events require mutable access to it.
+pub struct WeakSubscriptionListener<L: ?Sized> {
+    listener: Weak<Mutex<L>>,
+}
+
+impl<L: SubscriptionListener + ?Sized> WeakSubscriptionListener<L> {
+    /// Creates a listener forwarding the events to the given one for as long as it has other
+    /// owners.
+    pub fn new(listener: &Arc<Mutex<L>>) -> WeakSubscriptionListener<L> {
+        WeakSubscriptionListener {
+            listener: Arc::downgrade(listener),
+        }
+    }
+
+    /// Calls the given function on the listener, if it has not been dropped.
+    fn with_listener(&self, call: impl FnOnce(&mut L)) {
+        if let Some(listener) = self.listener.upgrade() {
+            call(&mut lock(&listener));
+        }
+    }
+}
+
+/// Locks a listener, ignoring the poisoning left by a panic of a previous event handler, which
+/// the library catches.
+fn lock<L: ?Sized>(listener: &Mutex<L>) -> MutexGuard<'_, L> {
+    listener.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+impl<L: ?Sized> Debug for WeakSubscriptionListener<L> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("WeakSubscriptionListener")
+            .field("expired", &(self.listener.strong_count() == 0))
+            .finish()
+    }
+}
+
+impl<L: SubscriptionListener + ?Sized> SubscriptionListener for WeakSubscriptionListener<L> {
+    fn is_expired(&self) -> bool {
+        self.listener
+            .upgrade()
+            .is_none_or(|listener| lock(&listener).is_expired())
+    }
+
+    fn on_clear_snapshot(&mut self, item_name: Option<&str>, item_pos: usize) {
+        self.with_listener(|listener| listener.on_clear_snapshot(item_name, item_pos));
+    }
+
+    fn on_command_second_level_item_lost_updates(&mut self, lost_updates: u32, key: &str) {
+        self.with_listener(|listener| {
+            listener.on_command_second_level_item_lost_updates(lost_updates, key)
+        });
+    }
+
+    fn on_command_second_level_subscription_error(
+        &mut self,
+        code: i32,
+        message: Option<&str>,
+        key: &str,
+    ) {
+        self.with_listener(|listener| {
+            listener.on_command_second_level_subscription_error(code, message, key)
+        });
+    }
+
+    fn on_end_of_snapshot(&mut self, item_name: Option<&str>, item_pos: usize) {
+        self.with_listener(|listener| listener.on_end_of_snapshot(item_name, item_pos));
+    }
+
+    fn on_item_lost_updates(
+        &mut self,
+        item_name: Option<&str>,
+        item_pos: usize,
+        lost_updates: u32,
+    ) {
+        self.with_listener(|listener| {
+            listener.on_item_lost_updates(item_name, item_pos, lost_updates)
+        });
+    }
+
+    fn on_item_update(&self, update: &ItemUpdate) {
+        self.with_listener(|listener| listener.on_item_update(update));
+    }
+
+    fn on_item_updates(&self, updates: &[ItemUpdate]) {
+        self.with_listener(|listener| listener.on_item_updates(updates));
+    }
+
+    fn on_listen_end(&mut self) {
+        self.with_listener(|listener| listener.on_listen_end());
+    }
+
+    fn on_listen_start(&mut self) {
+        self.with_listener(|listener| listener.on_listen_start());
+    }
+
+    fn on_real_max_frequency(&mut self, frequency: Option<f64>) {
+        self.with_listener(|listener| listener.on_real_max_frequency(frequency));
+    }
+
+    fn on_subscription(&mut self) {
+        self.with_listener(|listener| listener.on_subscription());
+    }
+
+    fn on_subscription_error(&mut self, code: i32, message: Option<&str>) {
+        self.with_listener(|listener| listener.on_subscription_error(code, message));
+    }
+
+    fn on_unsubscription(&mut self) {
+        self.with_listener(|listener| listener.on_unsubscription());
+    }
+}
@@ -0,0 +1,50 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{request_param, MockServer};
+use lightstreamer_client::ls_client::{LightstreamerClient, Transport};
+
+/// Creates a client of the mock server with the given adapter set.
+fn client(server: &MockServer, adapter_set: Option<&str>) -> LightstreamerClient {
+    let mut client =
+        LightstreamerClient::new(Some(&server.address), adapter_set, None, None).unwrap();
+    client
+        .connection_options
+        .set_forced_transport(Some(Transport::WsStreaming));
+    client
+}
+
+#[tokio::test]
+async fn unset_adapter_set_is_not_sent() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let mut client = client(&server, None);
+    assert_eq!(client.connection_details.get_adapter_set(), None);
+    client.connection_details.set_adapter_set(None);
+    assert_eq!(client.connection_details.get_adapter_set(), None);
+    client.connect().await.unwrap();
+
+    let request = server.next_request("create_session").await;
+    assert_eq!(request_param(&request, "LS_adapter_set"), None);
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn explicit_default_adapter_set_is_sent() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let client = client(&server, Some("DEFAULT"));
+    assert_eq!(
+        client
+            .connection_details
+            .get_adapter_set()
+            .map(String::as_str),
+        Some("DEFAULT")
+    );
+    client.connect().await.unwrap();
+
+    let request = server.next_request("create_session").await;
+    assert_eq!(request_param(&request, "LS_adapter_set"), Some("DEFAULT"));
+
+    client.disconnect().await;
+}
@@ -0,0 +1,198 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::item_update::ItemUpdate;
+use lightstreamer_client::ls_client::LightstreamerClient;
+use lightstreamer_client::subscription::{BackpressurePolicy, Subscription, SubscriptionMode};
+use lightstreamer_client::subscription_listener::SubscriptionListener;
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Update(u32),
+    Lost(u32),
+}
+
+/// Listener blocked on its first update until released, to let the updates pile up.
+struct SlowListener {
+    events: UnboundedSender<Event>,
+    gate: Mutex<Option<Receiver<()>>>,
+}
+
+impl SubscriptionListener for SlowListener {
+    fn on_item_update(&self, update: &ItemUpdate) {
+        if let Some(gate) = self.gate.lock().unwrap().take() {
+            // The runtime worker hands over its queued tasks while blocked.
+            let _ = tokio::task::block_in_place(|| gate.recv());
+        }
+        let price = update.get_value("price").unwrap_or_default();
+        let _ = self.events.send(Event::Update(price.parse().unwrap()));
+    }
+
+    fn on_item_lost_updates(&mut self, _item_name: Option<&str>, item_pos: usize, lost: u32) {
+        assert_eq!(item_pos, 1);
+        let _ = self.events.send(Event::Lost(lost));
+    }
+}
+
+/// Receives 10 updates of a subscription with the given policy, releasing the listener only
+/// after all of them have been read, and returns the events notified to the listener.
+async fn receive_with_policy(policy: BackpressurePolicy) -> Vec<Event> {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            let mut notifications = vec!["SUBOK,1,1,1".to_string()];
+            notifications.extend((1..=10).map(|price| format!("U,1,1,{}", price)));
+            notifications
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let (sender, mut events) = mpsc::unbounded_channel();
+    let (release, gate): (Sender<()>, _) = std::sync::mpsc::channel();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price"]).unwrap();
+    subscription.set_backpressure_policy(policy).unwrap();
+    subscription.add_listener(Box::new(SlowListener {
+        events: sender,
+        gate: Mutex::new(Some(gate)),
+    }));
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    wait_for_updates(&client, 10).await;
+    release.send(()).unwrap();
+    let received = collect_events(&mut events).await;
+    client.disconnect().await;
+    received
+}
+
+async fn wait_for_updates(client: &LightstreamerClient, updates: u64) {
+    tokio::time::timeout(TIMEOUT, async {
+        while client.get_metrics().updates_received < updates {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("updates not received");
+}
+
+/// Collects the events until all the 10 updates are accounted for.
+async fn collect_events(events: &mut UnboundedReceiver<Event>) -> Vec<Event> {
+    let mut received = Vec::new();
+    let mut accounted = 0;
+    while accounted < 10 {
+        let event = tokio::time::timeout(TIMEOUT, events.recv())
+            .await
+            .expect("no event received")
+            .expect("listener dropped");
+        accounted += match event {
+            Event::Update(_) => 1,
+            Event::Lost(lost) => lost,
+        };
+        received.push(event);
+    }
+    received
+}
+
+fn delivered(events: &[Event]) -> Vec<u32> {
+    events
+        .iter()
+        .filter_map(|event| match event {
+            Event::Update(price) => Some(*price),
+            Event::Lost(_) => None,
+        })
+        .collect()
+}
+
+// The listener may be blocked on the first update before or after the others are queued, so
+// the assertions only rely on the capacity of the buffer.
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn drop_newest_keeps_the_first_updates() {
+    let events = receive_with_policy(BackpressurePolicy::DropNewest(2)).await;
+    let delivered = delivered(&events);
+    assert!(delivered.len() <= 3, "{:?}", events);
+    assert_eq!(delivered, (1..=delivered.len() as u32).collect::<Vec<_>>());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn drop_oldest_keeps_the_last_updates() {
+    let events = receive_with_policy(BackpressurePolicy::DropOldest(2)).await;
+    let delivered = delivered(&events);
+    assert!(delivered.len() <= 3, "{:?}", events);
+    assert!(delivered.ends_with(&[9, 10]), "{:?}", events);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn conflation_keeps_the_latest_update_per_item() {
+    let events = receive_with_policy(BackpressurePolicy::ConflateLatest).await;
+    let delivered = delivered(&events);
+    assert!(delivered.len() <= 2, "{:?}", events);
+    assert_eq!(delivered.last(), Some(&10));
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn blocking_buffer_delivers_all_the_updates() {
+    let events = receive_with_policy(BackpressurePolicy::Block(2)).await;
+    assert_eq!(events, (1..=10).map(Event::Update).collect::<Vec<_>>());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn bursts_are_delivered_in_order() {
+    const UPDATES: u32 = 2000;
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            let mut notifications = vec!["SUBOK,1,1,1".to_string()];
+            notifications.extend((1..=UPDATES).map(|price| format!("U,1,1,{}", price)));
+            notifications
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let (sender, mut events) = mpsc::unbounded_channel();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price"]).unwrap();
+    subscription
+        .set_backpressure_policy(BackpressurePolicy::Block(4))
+        .unwrap();
+    subscription.add_listener(Box::new(SlowListener {
+        events: sender,
+        gate: Mutex::new(None),
+    }));
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    for price in 1..=UPDATES {
+        let event = tokio::time::timeout(TIMEOUT, events.recv())
+            .await
+            .expect("no event received");
+        assert_eq!(event, Some(Event::Update(price)));
+    }
+    client.disconnect().await;
+}
+
+#[test]
+fn empty_buffers_are_rejected() {
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price"]).unwrap();
+    for policy in [
+        BackpressurePolicy::Block(0),
+        BackpressurePolicy::DropOldest(0),
+        BackpressurePolicy::DropNewest(0),
+    ] {
+        assert!(subscription.set_backpressure_policy(policy).is_err());
+    }
+    assert_eq!(
+        subscription.get_backpressure_policy(),
+        BackpressurePolicy::Unbounded
+    );
+}
@@ -0,0 +1,76 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::item_update::ItemUpdate;
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use lightstreamer_client::subscription_listener::SubscriptionListener;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Batch(Vec<String>),
+    Lost(u32),
+}
+
+struct BatchListener(UnboundedSender<Event>);
+
+impl SubscriptionListener for BatchListener {
+    fn on_item_updates(&self, updates: &[ItemUpdate]) {
+        let prices = updates
+            .iter()
+            .map(|update| update.get_value("price").unwrap_or_default().to_string())
+            .collect();
+        let _ = self.0.send(Event::Batch(prices));
+    }
+
+    fn on_item_lost_updates(&mut self, _item_name: Option<&str>, _item_pos: usize, lost: u32) {
+        let _ = self.0.send(Event::Lost(lost));
+    }
+}
+
+async fn next_event(events: &mut UnboundedReceiver<Event>) -> Event {
+    tokio::time::timeout(TIMEOUT, events.recv())
+        .await
+        .expect("no event received")
+        .expect("listener dropped")
+}
+
+fn batch(prices: &[&str]) -> Event {
+    Event::Batch(prices.iter().map(|price| price.to_string()).collect())
+}
+
+#[tokio::test]
+async fn updates_of_a_frame_are_delivered_together() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            [
+                "SUBOK,1,1,1",
+                "U,1,1,1",
+                "U,1,1,2",
+                "U,1,1,3",
+                "OV,1,1,4",
+                "U,1,1,8",
+            ]
+            .map(str::to_string)
+            .to_vec()
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let (sender, mut events) = mpsc::unbounded_channel();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price"]).unwrap();
+    subscription.add_listener(Box::new(BatchListener(sender)));
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    // The lost updates notification keeps its place among the updates.
+    assert_eq!(next_event(&mut events).await, batch(&["1", "2", "3"]));
+    assert_eq!(next_event(&mut events).await, Event::Lost(4));
+    assert_eq!(next_event(&mut events).await, batch(&["8"]));
+    client.disconnect().await;
+}
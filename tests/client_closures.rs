@@ -0,0 +1,77 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use tokio::sync::mpsc;
+
+#[tokio::test]
+async fn status_closure_receives_the_status_changes() {
+    let server = MockServer::start(|_| Vec::new()).await;
+    let client = server.client();
+    let (sender, mut statuses) = mpsc::unbounded_channel();
+    client.on_status_change(move |status| {
+        let _ = sender.send(status.to_string());
+    });
+    client.on_server_error(|code, message| panic!("unexpected error {} {}", code, message));
+    client.connect().await.unwrap();
+
+    for expected in ["CONNECTING", "CONNECTED:WS-STREAMING"] {
+        let status = tokio::time::timeout(TIMEOUT, statuses.recv())
+            .await
+            .unwrap();
+        assert_eq!(status.as_deref(), Some(expected));
+    }
+    client.disconnect().await;
+    let status = tokio::time::timeout(TIMEOUT, statuses.recv())
+        .await
+        .unwrap();
+    assert_eq!(status.as_deref(), Some("DISCONNECTED"));
+}
+
+#[tokio::test]
+async fn error_closure_receives_the_refusal_of_the_session() {
+    let server = MockServer::start(|request| {
+        if request.starts_with("create_session") {
+            vec!["CONERR,2,Requested Adapter Set not available".to_string()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let (sender, mut errors) = mpsc::unbounded_channel();
+    let mut count = 0;
+    client.on_server_error(move |code, message| {
+        count += 1;
+        let _ = sender.send((count, code, message.to_string()));
+    });
+    client.connect().await.unwrap();
+
+    let error = tokio::time::timeout(TIMEOUT, errors.recv()).await.unwrap();
+    assert_eq!(
+        error,
+        Some((1, 2, "Requested Adapter Set not available".to_string()))
+    );
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn closures_are_removed_through_the_returned_token() {
+    let server = MockServer::start(|_| Vec::new()).await;
+    let client = server.client();
+    let (sender, mut statuses) = mpsc::unbounded_channel();
+    let token = client.on_status_change(move |status| {
+        let _ = sender.send(status.to_string());
+    });
+    client.remove_listener(token).unwrap();
+    assert!(client.remove_listener(token).is_err());
+    client.connect().await.unwrap();
+    client.disconnect().await;
+
+    // The closure is dropped along with its listener, closing the channel without any status.
+    let status = tokio::time::timeout(TIMEOUT, statuses.recv())
+        .await
+        .unwrap();
+    assert_eq!(status, None);
+}
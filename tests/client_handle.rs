@@ -0,0 +1,218 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{request_param, MockServer, TIMEOUT};
+use lightstreamer_client::ls_client::{ClientStatus, ConnectionType, DisconnectionType, Transport};
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use std::sync::Arc;
+
+#[tokio::test]
+async fn handles_drive_the_same_session_from_several_tasks() {
+    let mut server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec!["SUBOK,1,1,1".to_string()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let handle = server.client().spawn();
+    handle.connect().await.unwrap();
+    server.next_request("create_session").await;
+
+    let subscriber = handle.clone();
+    tokio::spawn(async move {
+        let subscription =
+            Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price"]).unwrap();
+        subscriber.subscribe(subscription).await.unwrap();
+    })
+    .await
+    .unwrap();
+    let request = server.next_request("control").await;
+    assert_eq!(request_param(&request, "LS_group"), Some("item"));
+
+    let sender = handle.clone();
+    tokio::spawn(async move {
+        sender
+            .send_message("hello", None, None, None, false)
+            .await
+            .unwrap();
+    })
+    .await
+    .unwrap();
+    let request = server.next_request("msg").await;
+    assert_eq!(request_param(&request, "LS_message"), Some("hello"));
+
+    assert_eq!(
+        handle.get_status(),
+        ClientStatus::Connected(ConnectionType::WsStreaming)
+    );
+    assert_eq!(handle.get_metrics().messages_sent, 1);
+
+    handle.disconnect().await;
+    assert_eq!(
+        handle.get_status(),
+        ClientStatus::Disconnected(DisconnectionType::None)
+    );
+}
+
+#[tokio::test]
+async fn subscriptions_are_removed_through_the_returned_token() {
+    let mut server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec!["SUBOK,1,1,1".to_string()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let handle = server.client().spawn();
+    handle.connect().await.unwrap();
+    server.next_request("create_session").await;
+
+    let subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price"]).unwrap();
+    let token = handle.subscribe(subscription).await.unwrap();
+    server.next_request("control").await;
+    handle.unsubscribe(token).await.unwrap();
+    let request = server.next_request("control").await;
+    assert_eq!(request_param(&request, "LS_op"), Some("delete"));
+    assert_eq!(request_param(&request, "LS_subId"), Some("1"));
+    assert!(handle.unsubscribe(token).await.is_err());
+
+    handle.disconnect().await;
+}
+
+#[tokio::test]
+async fn messages_are_checked_after_the_commands_issued_before_them() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let handle = server.client().spawn();
+    // The message is handled once the session is connecting, although the handle still reads
+    // the client as disconnected when it is issued.
+    let (connected, sent) = tokio::join!(
+        handle.connect(),
+        handle.send_message("hello", None, None, None, false),
+    );
+    connected.unwrap();
+    sent.unwrap();
+    server.next_request("create_session").await;
+    let request = server.next_request("msg").await;
+    assert_eq!(request_param(&request, "LS_message"), Some("hello"));
+
+    let ((), sent) = tokio::join!(
+        handle.disconnect(),
+        handle.send_message("bye", None, None, None, false),
+    );
+    assert!(sent.is_err());
+}
+
+#[tokio::test]
+async fn keepalive_changes_rebind_the_session_at_once() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let handle = server.client().spawn();
+    handle.connect().await.unwrap();
+    server.next_request("create_session").await;
+
+    handle.set_keepalive_interval(10000).unwrap();
+    let request = server.next_request("bind_session").await;
+    assert_eq!(request_param(&request, "LS_session"), Some("S1"));
+    assert_eq!(
+        request_param(&request, "LS_keepalive_millis"),
+        Some("10000")
+    );
+    assert_eq!(handle.get_metrics().reconnections, 0);
+
+    handle.disconnect().await;
+}
+
+#[tokio::test]
+async fn transport_changes_rebind_the_session_at_once() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let handle = server.client().spawn();
+    handle.connect().await.unwrap();
+    server.next_request("create_session").await;
+
+    handle
+        .set_forced_transport(Some(Transport::WsPolling))
+        .unwrap();
+    let request = server.next_request("bind_session").await;
+    assert_eq!(request_param(&request, "LS_session"), Some("S1"));
+    assert_eq!(request_param(&request, "LS_polling"), Some("true"));
+    assert_eq!(handle.get_metrics().reconnections, 0);
+
+    handle.disconnect().await;
+}
+
+#[tokio::test]
+async fn engine_disconnects_when_the_last_handle_is_dropped() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let handle = server.client().spawn();
+    let other_handle = handle.clone();
+    handle.connect().await.unwrap();
+    server.next_request("create_session").await;
+
+    drop(handle);
+    drop(other_handle);
+    loop {
+        let request = server.next_request("control").await;
+        if request_param(&request, "LS_op") == Some("destroy") {
+            break;
+        }
+    }
+}
+
+#[tokio::test]
+async fn connect_errors_are_reported_to_the_caller() {
+    let client =
+        lightstreamer_client::ls_client::LightstreamerClient::new(None, None, None, None).unwrap();
+    let handle = client.spawn();
+    let err = handle.connect().await.unwrap_err();
+    assert_eq!(err.to_string(), "No server address was configured.");
+    // The engine keeps running after a failed request.
+    assert!(tokio::time::timeout(TIMEOUT, handle.disconnect())
+        .await
+        .is_ok());
+}
+
+#[tokio::test]
+async fn configured_client_is_shared_without_a_lock() {
+    let mut server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec!["SUBOK,1,1,1".to_string()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = Arc::new(server.client());
+    let connector = Arc::clone(&client);
+    tokio::spawn(async move { connector.connect().await.unwrap() })
+        .await
+        .unwrap();
+    server.next_request("create_session").await;
+
+    let subscriber = Arc::clone(&client);
+    let sender = Arc::clone(&client);
+    let (subscribed, sent) = tokio::join!(
+        tokio::spawn(async move {
+            let subscription =
+                Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price"]).unwrap();
+            subscriber.subscribe(subscription);
+        }),
+        tokio::spawn(async move { sender.send_message("hello", None, None, None, false) }),
+    );
+    subscribed.unwrap();
+    sent.unwrap().unwrap();
+    // The requests may reach the server in any order.
+    let mut requests = [server.next_request("").await, server.next_request("").await];
+    requests.sort();
+    assert!(requests[0].starts_with("control"));
+    assert!(requests[1].starts_with("msg"));
+
+    client.disconnect().await;
+    assert_eq!(
+        client.get_status(),
+        ClientStatus::Disconnected(DisconnectionType::None)
+    );
+}
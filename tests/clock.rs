@@ -0,0 +1,88 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{drop_connection, MockServer};
+use lightstreamer_client::clock::{Clock, Instant, Sleep};
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::watch;
+
+/// Virtual clock whose time only moves forward when the test advances it.
+#[derive(Debug)]
+struct ManualClock {
+    start: Instant,
+    elapsed: watch::Sender<Duration>,
+}
+
+impl ManualClock {
+    fn new() -> ManualClock {
+        ManualClock {
+            start: Instant::now(),
+            elapsed: watch::Sender::new(Duration::ZERO),
+        }
+    }
+
+    fn advance(&self, duration: Duration) {
+        self.elapsed.send_modify(|elapsed| *elapsed += duration);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        self.start + *self.elapsed.borrow()
+    }
+
+    fn sleep(&self, duration: Duration) -> Sleep {
+        let deadline = *self.elapsed.borrow() + duration;
+        let mut elapsed = self.elapsed.subscribe();
+        Box::pin(async move {
+            let _ = elapsed.wait_for(|elapsed| *elapsed >= deadline).await;
+        })
+    }
+}
+
+#[tokio::test]
+async fn retry_delay_elapses_on_the_configured_clock() {
+    // The first connection is dropped, and the session can't be recovered.
+    let looped = AtomicBool::new(false);
+    let mut server = MockServer::start(move |request| {
+        if request.contains("LS_op=add") && !looped.swap(true, Ordering::SeqCst) {
+            vec![drop_connection()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let clock = Arc::new(ManualClock::new());
+    let mut client = server.client();
+    client.connection_options.set_clock(Some(clock.clone()));
+    client
+        .connection_options
+        .set_session_recovery_timeout(0)
+        .unwrap();
+    client
+        .connection_options
+        .set_first_retry_max_delay(3_600_000)
+        .unwrap();
+    let subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price"]).unwrap();
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+    server.next_request("create_session").await;
+    server.next_request("control").await;
+
+    // No new session is created until an hour of virtual time has passed.
+    let early = tokio::time::timeout(
+        Duration::from_millis(200),
+        server.next_request("create_session"),
+    )
+    .await;
+    assert!(early.is_err());
+    clock.advance(Duration::from_secs(3600));
+    server.next_request("create_session").await;
+
+    client.disconnect().await;
+}
@@ -0,0 +1,129 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::client_listener::ClientListener;
+use lightstreamer_client::clock::ClockSkew;
+use lightstreamer_client::ls_client::{ClientStatus, LightstreamerClient};
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+/// Date sent by the mock server in the handshake responses.
+const SERVER_DATE: &str = "Sun, 06 Nov 1994 08:49:37 GMT";
+/// `SERVER_DATE` as seconds since the Unix epoch.
+const SERVER_SECONDS: u64 = 784_111_777;
+
+/// Listener forwarding the `SYNC` notifications to the test.
+#[derive(Debug)]
+struct SyncForwarder(UnboundedSender<u64>);
+
+impl ClientListener for SyncForwarder {
+    fn on_server_sync(&self, seconds: u64) {
+        let _ = self.0.send(seconds);
+    }
+}
+
+/// Offset expected for a server whose clock reads `SERVER_DATE` now, in the middle of its second.
+fn expected_offset_millis() -> i64 {
+    let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+    (SERVER_SECONDS * 1000 + 500) as i64 - now.as_millis() as i64
+}
+
+async fn wait_for_clock_skew(client: &LightstreamerClient) -> ClockSkew {
+    tokio::time::timeout(TIMEOUT, async {
+        loop {
+            if let Some(clock_skew) = client.get_clock_skew() {
+                return clock_skew;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("no clock skew estimated")
+}
+
+#[tokio::test]
+async fn date_header_of_the_handshake_sets_the_offset() {
+    let server =
+        MockServer::start_with_headers(vec![("date", SERVER_DATE.to_string())], |_| Vec::new())
+            .await;
+    let client = server.client();
+    assert_eq!(client.get_clock_skew(), None);
+    client.connect().await.unwrap();
+
+    let clock_skew = wait_for_clock_skew(&client).await;
+    assert!((clock_skew.offset_millis - expected_offset_millis()).abs() < 2000);
+    assert!(clock_skew.uncertainty >= Duration::from_millis(500));
+    let local_time = clock_skew.to_local_time(UNIX_EPOCH + Duration::from_secs(SERVER_SECONDS));
+    let error = SystemTime::now()
+        .duration_since(local_time)
+        .unwrap_or_else(|err| err.duration());
+    assert!(error < Duration::from_secs(2));
+    assert_eq!(
+        clock_skew.to_server_time(local_time),
+        UNIX_EPOCH + Duration::from_secs(SERVER_SECONDS)
+    );
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn sync_notifications_refine_the_offset() {
+    // The server reports that 30 seconds have elapsed since the start of the stream, while the
+    // client measured almost none: the server clock is running ahead.
+    let server =
+        MockServer::start_with_headers(vec![("date", SERVER_DATE.to_string())], |request| {
+            if request.contains("LS_op=add") {
+                vec!["SYNC,30".to_string()]
+            } else {
+                Vec::new()
+            }
+        })
+        .await;
+    let client = server.client();
+    let (sender, mut syncs) = mpsc::unbounded_channel();
+    client.add_listener(Box::new(SyncForwarder(sender)));
+    let subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["field1"]).unwrap();
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let seconds = tokio::time::timeout(TIMEOUT, syncs.recv())
+        .await
+        .expect("no sync notified")
+        .unwrap();
+    assert_eq!(seconds, 30);
+    let expected = expected_offset_millis() + 30_000;
+    tokio::time::timeout(TIMEOUT, async {
+        while client
+            .get_clock_skew()
+            .is_none_or(|clock_skew| (clock_skew.offset_millis - expected).abs() >= 2000)
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("offset not refined");
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn no_estimate_without_the_server_time() {
+    let server = MockServer::start(|_| Vec::new()).await;
+    let client = server.client();
+    client.connect().await.unwrap();
+    tokio::time::timeout(TIMEOUT, async {
+        while !matches!(client.get_status(), ClientStatus::Connected(_)) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("client not connected");
+
+    assert_eq!(client.get_clock_skew(), None);
+
+    client.disconnect().await;
+}
@@ -0,0 +1,250 @@
+//! Mock Lightstreamer server speaking TLCP over WebSocket, shared by the integration tests.
+
+#![allow(dead_code)]
+
+use futures_util::{SinkExt, StreamExt};
+use lightstreamer_client::item_update::ItemUpdate;
+use lightstreamer_client::ls_client::{LightstreamerClient, Transport};
+use lightstreamer_client::subscription_listener::SubscriptionListener;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::net::TcpListener;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
+use tokio_tungstenite::tungstenite::protocol::frame::coding::CloseCode;
+use tokio_tungstenite::tungstenite::protocol::CloseFrame;
+use tokio_tungstenite::tungstenite::Message;
+
+/// Maximum time the tests wait for an expected event.
+pub const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Maximum keepalive interval (in milliseconds) notified by the mock server in `CONOK`.
+pub const MAX_KEEPALIVE: u64 = 5000;
+
+/// Function producing the notifications sent back for a request received by the mock server.
+type Responder = dyn Fn(&str) -> Vec<String> + Send + Sync;
+
+/// Prefix of the pseudo-notifications that make the mock server close the connection instead of
+/// sending a text frame. See `close_connection()` and `drop_connection()`.
+const CONNECTION_COMMAND: &str = "#connection:";
+
+/// Pseudo-notification making the mock server close the connection with the given close frame,
+/// after sending the preceding notifications.
+pub fn close_connection(code: u16, reason: &str) -> String {
+    format!("{}close,{},{}", CONNECTION_COMMAND, code, reason)
+}
+
+/// Pseudo-notification making the mock server drop the connection without any close frame, after
+/// sending the preceding notifications.
+pub fn drop_connection() -> String {
+    format!("{}drop", CONNECTION_COMMAND)
+}
+
+/// Mock server accepting any number of connections. Session creation, binding, recovery and
+/// destruction are answered automatically, while every other request is answered through the
+/// responder given to `MockServer::start()`. The notifications the responder gives for session
+/// creation, binding and recovery follow the automatic `CONOK`, unless they start with a
+/// `CONERR`, which refuses the request in its place. Each WebSocket handshake is reported as a
+/// `handshake` request listing its headers, one per line.
+pub struct MockServer {
+    /// Address to be used as server address by the clients.
+    pub address: String,
+    requests: UnboundedReceiver<String>,
+}
+
+impl MockServer {
+    /// Starts a mock server on a free local port.
+    pub async fn start(responder: impl Fn(&str) -> Vec<String> + Send + Sync + 'static) -> Self {
+        Self::start_with_headers(Vec::new(), responder).await
+    }
+
+    /// Starts a mock server on a free local port, adding the given headers to the responses to
+    /// the WebSocket handshakes.
+    pub async fn start_with_headers(
+        headers: Vec<(&'static str, String)>,
+        responder: impl Fn(&str) -> Vec<String> + Send + Sync + 'static,
+    ) -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}/", listener.local_addr().unwrap());
+        let responder: Arc<Responder> = Arc::new(responder);
+        let (request_sender, requests) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            while let Ok((stream, _)) = listener.accept().await {
+                let responder = Arc::clone(&responder);
+                let headers = headers.clone();
+                let request_sender = request_sender.clone();
+                tokio::spawn(async move {
+                    // The client requires the TLCP subprotocol to be accepted. The error type
+                    // is imposed by tungstenite.
+                    #[allow(clippy::result_large_err)]
+                    let callback = |request: &Request, mut response: Response| {
+                        let mut handshake = "handshake".to_string();
+                        for (name, value) in request.headers() {
+                            handshake.push_str(&format!(
+                                "\r\n{}: {}",
+                                name,
+                                value.to_str().unwrap_or_default()
+                            ));
+                        }
+                        let _ = request_sender.send(handshake);
+                        if let Some(protocol) = request.headers().get("sec-websocket-protocol") {
+                            response
+                                .headers_mut()
+                                .insert("sec-websocket-protocol", protocol.clone());
+                        }
+                        for (name, value) in &headers {
+                            response.headers_mut().insert(*name, value.parse().unwrap());
+                        }
+                        Ok(response)
+                    };
+                    let Ok(mut ws) = tokio_tungstenite::accept_hdr_async(stream, callback).await
+                    else {
+                        return;
+                    };
+                    while let Some(Ok(message)) = ws.next().await {
+                        // Pings are answered automatically by tungstenite on the next read.
+                        let Message::Text(text) = message else {
+                            continue;
+                        };
+                        let request = text.to_string();
+                        let _ = request_sender.send(request.clone());
+                        let notifications = if request.starts_with("wsok") {
+                            vec!["WSOK".to_string()]
+                        } else if request.starts_with("create_session")
+                            || request.starts_with("recover_session")
+                            || request.starts_with("bind_session")
+                        {
+                            // Keepalive intervals are capped at 5 seconds, as by the server
+                            // configuration.
+                            let keepalive = request_param(&request, "LS_keepalive_millis")
+                                .and_then(|keepalive| keepalive.parse::<u64>().ok())
+                                .map_or(MAX_KEEPALIVE, |keepalive| keepalive.min(MAX_KEEPALIVE));
+                            let responses = responder(&request);
+                            if responses
+                                .first()
+                                .is_some_and(|first| first.starts_with("CONERR"))
+                            {
+                                responses
+                            } else {
+                                let mut notifications =
+                                    vec![format!("CONOK,S1,50000,{},*", keepalive)];
+                                notifications.extend(responses);
+                                notifications
+                            }
+                        } else if request.contains("LS_op=destroy") {
+                            vec!["END,31,destroyed".to_string()]
+                        } else {
+                            responder(&request)
+                        };
+                        let command = notifications
+                            .iter()
+                            .position(|notification| notification.starts_with(CONNECTION_COMMAND));
+                        let (notifications, command) = match command {
+                            Some(index) => (&notifications[..index], Some(&notifications[index])),
+                            None => (&notifications[..], None),
+                        };
+                        if !notifications.is_empty() {
+                            let frame = notifications.join("\r\n") + "\r\n";
+                            if ws.send(Message::Text(frame.into())).await.is_err() {
+                                return;
+                            }
+                        }
+                        let Some(command) = command else {
+                            continue;
+                        };
+                        let command = &command[CONNECTION_COMMAND.len()..];
+                        let Some(close) = command.strip_prefix("close,") else {
+                            // Dropping the socket aborts the connection.
+                            return;
+                        };
+                        let (code, reason) = close.split_once(',').unwrap();
+                        let frame = CloseFrame {
+                            code: CloseCode::from(code.parse::<u16>().unwrap()),
+                            reason: reason.to_string().into(),
+                        };
+                        let _ = ws.close(Some(frame)).await;
+                        // Keep reading to receive the reply completing the close handshake.
+                    }
+                });
+            }
+        });
+        MockServer { address, requests }
+    }
+
+    /// Waits for the next request received by the server that starts with the given prefix,
+    /// skipping the other ones.
+    pub async fn next_request(&mut self, prefix: &str) -> String {
+        tokio::time::timeout(TIMEOUT, async {
+            loop {
+                let request = self.requests.recv().await.expect("mock server stopped");
+                if request.starts_with(prefix) {
+                    return request;
+                }
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("no '{}' request received", prefix))
+    }
+
+    /// Creates a client configured to connect to this server.
+    pub fn client(&self) -> LightstreamerClient {
+        let mut client =
+            LightstreamerClient::new(Some(&self.address), Some("DEMO"), None, None).unwrap();
+        client
+            .connection_options
+            .set_forced_transport(Some(Transport::WsStreaming));
+        client
+    }
+}
+
+/// Gets the value of a parameter of an encoded request.
+pub fn request_param<'a>(request: &'a str, name: &str) -> Option<&'a str> {
+    request
+        .split(['\n', '&'])
+        .filter_map(|param| param.split_once('='))
+        .find(|(param_name, _)| param_name.trim() == name)
+        .map(|(_, value)| value.trim())
+}
+
+/// Starts a mock server confirming every subscription with the given notification and sending
+/// the given updates after it.
+pub async fn subscription_server(
+    subok: &'static str,
+    updates: &'static [&'static str],
+) -> MockServer {
+    MockServer::start(move |request| {
+        if request.contains("LS_op=add") {
+            let mut notifications = vec![subok.to_string()];
+            notifications.extend(updates.iter().map(|update| update.to_string()));
+            notifications
+        } else {
+            Vec::new()
+        }
+    })
+    .await
+}
+
+/// Listener forwarding the received updates to the test.
+pub struct UpdateForwarder(pub UnboundedSender<ItemUpdate>);
+
+impl SubscriptionListener for UpdateForwarder {
+    fn on_item_update(&self, update: &ItemUpdate) {
+        let _ = self.0.send(update.clone());
+    }
+}
+
+/// Waits for the next update forwarded by an `UpdateForwarder`.
+pub async fn next_update(updates: &mut UnboundedReceiver<ItemUpdate>) -> ItemUpdate {
+    next_update_within(updates, TIMEOUT).await
+}
+
+/// Waits for the next update forwarded by an `UpdateForwarder`, for at most the given time.
+pub async fn next_update_within(
+    updates: &mut UnboundedReceiver<ItemUpdate>,
+    timeout: Duration,
+) -> ItemUpdate {
+    tokio::time::timeout(timeout, updates.recv())
+        .await
+        .expect("no update received")
+        .expect("listener dropped")
+}
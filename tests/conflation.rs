@@ -0,0 +1,79 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::item_update::ItemUpdate;
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use lightstreamer_client::subscription_listener::SubscriptionListener;
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+struct UpdateListener(UnboundedSender<(Instant, HashMap<String, String>)>);
+
+impl SubscriptionListener for UpdateListener {
+    fn on_item_update(&self, update: &ItemUpdate) {
+        let _ = self
+            .0
+            .send((Instant::now(), update.get_changed_fields().unwrap()));
+    }
+}
+
+#[tokio::test]
+async fn bursts_are_coalesced_into_a_single_delivery() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            let mut notifications = vec!["SUBOK,1,1,2".to_string(), "U,1,1,1|a".to_string()];
+            notifications.extend((2..=10).map(|price| match price {
+                5 => "U,1,1,5|b".to_string(),
+                price => format!("U,1,1,{}|", price),
+            }));
+            notifications
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let (sender, mut updates) = mpsc::unbounded_channel();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price", "name"]).unwrap();
+    subscription.set_conflation_frequency(Some(5.0)).unwrap();
+    subscription.add_listener(Box::new(UpdateListener(sender)));
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let (first_at, first) = tokio::time::timeout(TIMEOUT, updates.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(first["price"], "1");
+    assert_eq!(first["name"], "a");
+    let (second_at, second) = tokio::time::timeout(TIMEOUT, updates.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(second_at - first_at >= Duration::from_millis(150));
+    assert_eq!(second["price"], "10");
+    assert_eq!(second["name"], "b");
+    assert!(
+        tokio::time::timeout(Duration::from_millis(400), updates.recv())
+            .await
+            .is_err(),
+        "unexpected further update"
+    );
+    client.disconnect().await;
+}
+
+#[test]
+fn conflation_requires_merge_mode() {
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Distinct, "item", ["price"]).unwrap();
+    assert!(subscription.set_conflation_frequency(Some(1.0)).is_err());
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price"]).unwrap();
+    assert!(subscription.set_conflation_frequency(Some(0.0)).is_err());
+    assert!(subscription.set_conflation_frequency(Some(2.0)).is_ok());
+    assert_eq!(subscription.get_conflation_frequency(), Some(2.0));
+}
@@ -0,0 +1,128 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::item_update::ItemUpdate;
+use lightstreamer_client::ls_client::{ClientStatus, DisconnectionType, LightstreamerClient};
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode, SubscriptionToken};
+use lightstreamer_client::subscription_listener::SubscriptionListener;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// Listener forwarding the lifecycle events and the updates of a subscription to the test.
+struct EventForwarder(UnboundedSender<String>);
+
+impl SubscriptionListener for EventForwarder {
+    fn on_item_update(&self, update: &ItemUpdate) {
+        let value = update.get_value("field1").unwrap_or_default().to_string();
+        let _ = self.0.send(format!("update {}", value));
+    }
+
+    fn on_subscription(&mut self) {
+        let _ = self.0.send("subscription".to_string());
+    }
+
+    fn on_unsubscription(&mut self) {
+        let _ = self.0.send("unsubscription".to_string());
+    }
+}
+
+async fn start_server() -> MockServer {
+    MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec!["SUBOK,1,1,1".to_string(), "U,1,1,a".to_string()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await
+}
+
+/// Creates a client for the server with a subscription to "item1", whose events are forwarded.
+/// Returns the client, the token of the subscription and the channel receiving its events.
+fn subscribed_client(
+    server: &MockServer,
+) -> (
+    LightstreamerClient,
+    SubscriptionToken,
+    UnboundedReceiver<String>,
+) {
+    let client = server.client();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["field1"]).unwrap();
+    let (sender, events) = mpsc::unbounded_channel();
+    subscription.add_listener(Box::new(EventForwarder(sender)));
+    let token = client.subscribe(subscription);
+    (client, token, events)
+}
+
+async fn next_event(events: &mut UnboundedReceiver<String>) -> String {
+    tokio::time::timeout(TIMEOUT, events.recv())
+        .await
+        .expect("no event received")
+        .expect("listener dropped")
+}
+
+#[tokio::test]
+async fn subscriptions_are_reattached_on_every_connect() {
+    let mut server = start_server().await;
+    let (client, token, mut events) = subscribed_client(&server);
+    let subscribed = || {
+        client
+            .with_subscription(token, |subscription| {
+                (subscription.is_subscribed(), subscription.is_active())
+            })
+            .unwrap()
+    };
+
+    for _ in 0..3 {
+        client.connect().await.unwrap();
+        server.next_request("create_session").await;
+        let request = server.next_request("control").await;
+        assert!(request.contains("LS_op=add"));
+        assert_eq!(next_event(&mut events).await, "subscription");
+        assert_eq!(next_event(&mut events).await, "update a");
+        assert_eq!(subscribed(), (true, true));
+
+        client.disconnect().await;
+        assert_eq!(
+            client.get_status(),
+            ClientStatus::Disconnected(DisconnectionType::None)
+        );
+        assert_eq!(subscribed(), (false, true));
+        assert_eq!(next_event(&mut events).await, "unsubscription");
+    }
+    assert_eq!(client.get_metrics().reconnections, 0);
+}
+
+#[test]
+fn client_can_be_connected_again_on_another_runtime() {
+    let server_runtime = tokio::runtime::Runtime::new().unwrap();
+    let mut server = server_runtime.block_on(start_server());
+    let (client, _, mut events) = subscribed_client(&server);
+
+    // The first runtime is shut down while the client is connected.
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(async {
+        client.connect().await.unwrap();
+        assert_eq!(next_event(&mut events).await, "subscription");
+        assert_eq!(next_event(&mut events).await, "update a");
+    });
+    drop(runtime);
+
+    let runtime = tokio::runtime::Runtime::new().unwrap();
+    runtime.block_on(async {
+        client.disconnect().await;
+        assert_eq!(
+            client.get_status(),
+            ClientStatus::Disconnected(DisconnectionType::None)
+        );
+        client.connect().await.unwrap();
+        server.next_request("create_session").await;
+        server.next_request("create_session").await;
+        assert_eq!(next_event(&mut events).await, "unsubscription");
+        assert_eq!(next_event(&mut events).await, "subscription");
+        assert_eq!(next_event(&mut events).await, "update a");
+        client.disconnect().await;
+    });
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{request_param, MockServer};
+use lightstreamer_client::connect_overrides::ConnectOverrides;
+use lightstreamer_client::error::InvalidOptionsException;
+use lightstreamer_client::ls_client::Transport;
+
+#[test]
+fn overrides_start_not_set_and_are_validated() {
+    let mut overrides = ConnectOverrides::new();
+    assert_eq!(overrides.get_forced_transport(), None);
+    assert_eq!(overrides.get_keepalive_interval(), None);
+    assert!(overrides.set_retry_delay(0).is_err());
+    assert!(overrides.set_idle_timeout(0).is_err());
+    overrides.set_forced_transport(None);
+    assert_eq!(overrides.get_forced_transport(), Some(None));
+}
+
+#[tokio::test]
+async fn overrides_apply_to_a_single_connect() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let client = server.client();
+
+    let mut overrides = ConnectOverrides::new();
+    overrides.set_forced_transport(Some(Transport::WsPolling));
+    overrides.set_polling_interval(30000);
+    client.connect_with(overrides).await.unwrap();
+    let request = server.next_request("create_session").await;
+    assert_eq!(request_param(&request, "LS_polling"), Some("true"));
+    assert_eq!(request_param(&request, "LS_polling_millis"), Some("30000"));
+    client.disconnect().await;
+
+    assert_eq!(
+        client.connection_options.get_forced_transport(),
+        Some(&Transport::WsStreaming)
+    );
+    client.connect().await.unwrap();
+    let request = server.next_request("create_session").await;
+    assert_eq!(request_param(&request, "LS_polling"), None);
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn overridden_options_must_satisfy_the_constraints() {
+    let server = MockServer::start(|_| Vec::new()).await;
+    let client = server.client();
+
+    let mut overrides = ConnectOverrides::new();
+    overrides.set_keepalive_interval(1000);
+    let error = client.connect_with(overrides).await.unwrap_err();
+    let error = error.downcast_ref::<InvalidOptionsException>().unwrap();
+    assert_eq!(
+        error.get_violations()[0],
+        "Stalled timeout (2000 ms) should be less than keepalive interval (1000 ms)"
+    );
+    assert!(client.connection_options.validate().is_ok());
+}
@@ -0,0 +1,66 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::connection_info::ConnectionInfo;
+use lightstreamer_client::ls_client::{ConnectionType, LightstreamerClient};
+use std::time::{Duration, SystemTime};
+
+/// Waits until the client is bound to a session whose server instance is known.
+async fn wait_connection_info(client: &LightstreamerClient) -> ConnectionInfo {
+    tokio::time::timeout(TIMEOUT, async {
+        loop {
+            if let Some(info) = client
+                .connection_info()
+                .filter(|info| info.client_ip.is_some())
+            {
+                return info;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("connection info not available")
+}
+
+#[tokio::test]
+async fn current_connection_is_described() {
+    let server = MockServer::start(|request| {
+        if request.starts_with("create_session") {
+            vec![
+                "SERVNAME,Lightstreamer HTTP Server".to_string(),
+                "CLIENTIP,127.0.0.1".to_string(),
+            ]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    assert_eq!(client.connection_info(), None);
+    let started_at = SystemTime::now();
+    client.connect().await.unwrap();
+
+    let info = wait_connection_info(&client).await;
+    assert_eq!(info.transport, ConnectionType::WsStreaming);
+    assert_eq!(info.protocol_version, "TLCP-2.4.0");
+    let ws_address = server.address.replacen("http://", "ws://", 1);
+    assert!(
+        info.server_address
+            .starts_with(ws_address.trim_end_matches('/')),
+        "unexpected address: {}",
+        info.server_address
+    );
+    assert_eq!(
+        info.server_instance.as_deref(),
+        Some("Lightstreamer HTTP Server")
+    );
+    assert_eq!(info.client_ip.as_deref(), Some("127.0.0.1"));
+    assert_eq!(info.session_id, "S1");
+    assert!(info.connected_at >= started_at - Duration::from_secs(1));
+    assert!(info.connected_at <= SystemTime::now() + Duration::from_secs(1));
+
+    client.disconnect().await;
+    assert_eq!(client.connection_info(), None);
+}
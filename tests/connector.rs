@@ -0,0 +1,97 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{request_param, MockServer, TIMEOUT};
+use cookie::Cookie;
+use lightstreamer_client::client_listener::ClientListener;
+use lightstreamer_client::connector::Connector;
+use lightstreamer_client::ls_client::{LightstreamerClient, Transport};
+use std::time::Duration;
+use tokio::runtime::{Handle, Id};
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+/// Creates a client for the given adapter set of the mock server, using the given connector.
+fn client(server: &MockServer, adapter_set: &str, connector: &Connector) -> LightstreamerClient {
+    // Resolved through the DNS cache of the connector.
+    let address = server.address.replace("127.0.0.1", "localhost");
+    let mut client =
+        LightstreamerClient::new(Some(&address), Some(adapter_set), None, None).unwrap();
+    client
+        .connection_options
+        .set_forced_transport(Some(Transport::WsStreaming));
+    client
+        .connection_options
+        .set_connector(Some(connector.clone()));
+    client
+}
+
+#[tokio::test]
+async fn clients_of_different_adapter_sets_share_a_connector() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let connector = Connector::new().with_dns_cache_ttl(Duration::from_secs(300));
+    let quotes = client(&server, "QUOTES", &connector);
+    let news = client(&server, "NEWS", &connector);
+
+    quotes.connect().await.unwrap();
+    let request = server.next_request("create_session").await;
+    assert_eq!(request_param(&request, "LS_adapter_set"), Some("QUOTES"));
+    news.connect().await.unwrap();
+    let request = server.next_request("create_session").await;
+    assert_eq!(request_param(&request, "LS_adapter_set"), Some("NEWS"));
+
+    quotes.disconnect().await;
+    news.disconnect().await;
+}
+
+#[tokio::test]
+async fn connector_has_its_own_cookie_jar() {
+    let connector = Connector::new();
+    connector
+        .add_cookies("http://push.example.com/", [Cookie::new("session", "42")])
+        .unwrap();
+
+    let cookies = connector
+        .get_cookies(Some("http://push.example.com/lightstreamer"))
+        .unwrap();
+    assert_eq!(cookies.len(), 1);
+    assert_eq!(cookies[0].value(), "42");
+    assert!(LightstreamerClient::get_cookies(None).unwrap().is_empty());
+    assert_eq!(connector.clone().get_cookies(None).unwrap().len(), 1);
+    assert!(connector.get_cookies(Some("not a uri")).is_err());
+}
+
+#[derive(Debug)]
+struct RuntimeProbe(UnboundedSender<Id>);
+
+impl ClientListener for RuntimeProbe {
+    fn on_status_change(&self, _status: &str) {
+        let _ = self.0.send(Handle::current().id());
+    }
+}
+
+#[tokio::test]
+async fn tasks_run_on_the_runtime_of_the_connector() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .enable_all()
+        .build()
+        .unwrap();
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let connector = Connector::new().with_runtime(runtime.handle().clone());
+    let client = client(&server, "DEMO", &connector);
+    let (sender, mut runtime_ids) = mpsc::unbounded_channel();
+    client.add_listener(Box::new(RuntimeProbe(sender)));
+
+    client.connect().await.unwrap();
+    server.next_request("create_session").await;
+    let runtime_id = tokio::time::timeout(TIMEOUT, runtime_ids.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(runtime_id, runtime.handle().id());
+    assert_ne!(runtime_id, Handle::current().id());
+
+    client.disconnect().await;
+    runtime.shutdown_background();
+}
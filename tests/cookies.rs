@@ -0,0 +1,69 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::MockServer;
+use cookie::Cookie;
+use lightstreamer_client::ls_client::LightstreamerClient;
+
+/// Gets the values of the cookies with the given name in the process-wide cookie jar, for the
+/// given URI.
+fn cookie_values(uri: &str, name: &str) -> Vec<String> {
+    LightstreamerClient::get_cookies(Some(uri))
+        .unwrap()
+        .into_iter()
+        .filter(|cookie| cookie.name() == name)
+        .map(|cookie| cookie.value().to_string())
+        .collect()
+}
+
+#[tokio::test]
+async fn added_cookies_are_sent_to_the_server() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    LightstreamerClient::add_cookies(&server.address, [Cookie::new("added", "42")]).unwrap();
+    let client = server.client();
+    client.connect().await.unwrap();
+
+    let handshake = server.next_request("handshake").await;
+    let cookie_header = handshake
+        .lines()
+        .find_map(|header| header.strip_prefix("cookie: "))
+        .expect("no cookie header");
+    assert!(
+        cookie_header.split("; ").any(|cookie| cookie == "added=42"),
+        "{}",
+        cookie_header
+    );
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn cookies_set_by_the_server_are_stored() {
+    let mut server = MockServer::start_with_headers(
+        vec![("set-cookie", "received=abc; Path=/".to_string())],
+        |_| Vec::new(),
+    )
+    .await;
+    let client = server.client();
+    client.connect().await.unwrap();
+    server.next_request("create_session").await;
+
+    assert_eq!(cookie_values(&server.address, "received"), ["abc"]);
+    // Cookies are only given for the URIs matching their domain.
+    assert!(cookie_values("http://push.example.com/", "received").is_empty());
+    client.disconnect().await;
+}
+
+#[test]
+fn cookies_are_replaced_and_filtered_by_path() {
+    let uri = "http://cookies.example.com/app/";
+    LightstreamerClient::add_cookies(uri, [Cookie::new("replaced", "1")]).unwrap();
+    LightstreamerClient::add_cookies(uri, [Cookie::new("replaced", "2")]).unwrap();
+    assert_eq!(cookie_values(uri, "replaced"), ["2"]);
+    assert_eq!(
+        cookie_values("http://cookies.example.com/app/stream", "replaced"),
+        ["2"]
+    );
+    assert!(cookie_values("http://cookies.example.com/other/", "replaced").is_empty());
+    assert!(LightstreamerClient::add_cookies("not a uri", [Cookie::new("a", "b")]).is_err());
+}
@@ -0,0 +1,121 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{drop_connection, request_param, MockServer};
+use lightstreamer_client::connection_details::ConnectionDetails;
+use lightstreamer_client::credentials_provider::{Credentials, CredentialsProvider};
+use lightstreamer_client::proxy::{Proxy, ProxyType};
+use lightstreamer_client::secret::SecretString;
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use std::error::Error;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// Provider issuing a new token each time it is consulted.
+#[derive(Debug, Default)]
+struct TokenProvider(AtomicUsize);
+
+impl CredentialsProvider for TokenProvider {
+    fn get_credentials(&self) -> Result<Credentials, Box<dyn Error + Send + Sync>> {
+        let token = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+        Ok(Credentials::new(
+            Some("trader"),
+            Some(&format!("token-{}", token)),
+        ))
+    }
+}
+
+#[tokio::test]
+async fn fresh_credentials_are_sent_on_each_session_creation() {
+    // The first connection is dropped, and the session can't be recovered.
+    let looped = AtomicBool::new(false);
+    let mut server = MockServer::start(move |request| {
+        if request.contains("LS_op=add") && !looped.swap(true, Ordering::SeqCst) {
+            vec![drop_connection()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let mut client = server.client();
+    client
+        .connection_details
+        .set_password(Some("static".into()));
+    client
+        .connection_details
+        .set_credentials_provider(Some(Arc::new(TokenProvider::default())));
+    client
+        .connection_options
+        .set_session_recovery_timeout(0)
+        .unwrap();
+    client.connection_options.set_retry_delay(10).unwrap();
+    client
+        .connection_options
+        .set_first_retry_max_delay(10)
+        .unwrap();
+    let subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price"]).unwrap();
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    for expected in ["token-1", "token-2"] {
+        let request = server.next_request("create_session").await;
+        assert_eq!(request_param(&request, "LS_user"), Some("trader"));
+        assert_eq!(request_param(&request, "LS_password"), Some(expected));
+        assert_eq!(request.matches("LS_password").count(), 1);
+    }
+
+    client.disconnect().await;
+}
+
+#[test]
+fn password_is_not_printed() {
+    let credentials = Credentials::new(Some("trader"), Some("secret"));
+    let debug = format!("{:?}", credentials);
+    assert!(debug.contains("trader"));
+    assert!(!debug.contains("secret"));
+}
+
+#[test]
+fn passwords_are_redacted_in_connection_settings() {
+    let details = ConnectionDetails::new(
+        Some("http://localhost:8080"),
+        Some("DEMO"),
+        Some("trader"),
+        Some("secret"),
+    )
+    .unwrap();
+    let debug = format!("{:?}", details);
+    assert!(debug.contains("trader"));
+    assert!(!debug.contains("secret"));
+
+    let proxy = Proxy::new(
+        ProxyType::Http,
+        "proxy.local".to_string(),
+        3128,
+        Some("proxy-user".to_string()),
+        Some("proxy-secret".into()),
+    );
+    let debug = format!("{:?}", proxy);
+    assert!(debug.contains("proxy-user"));
+    assert!(!debug.contains("proxy-secret"));
+}
+
+#[test]
+fn secrets_are_only_readable_explicitly() {
+    let secret = SecretString::from("token");
+    assert_eq!(secret.expose_secret(), "token");
+    assert!(!format!("{:?}", secret).contains("token"));
+}
+
+#[cfg(feature = "secrecy")]
+#[test]
+fn secrets_convert_from_and_into_secrecy() {
+    use secrecy::ExposeSecret;
+
+    let secret = SecretString::from(secrecy::SecretString::from("token"));
+    assert_eq!(secret.expose_secret(), "token");
+    let secret: secrecy::SecretString = secret.into();
+    assert_eq!(secret.expose_secret(), "token");
+}
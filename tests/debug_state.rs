@@ -0,0 +1,55 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::ls_client::ConnectionType;
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+
+#[tokio::test]
+async fn debug_state_reports_session_and_subscriptions() {
+    let mut server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec!["SUBOK,1,1,1".to_string(), "U,1,1,10".to_string()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price"]).unwrap();
+    client.subscribe(subscription);
+
+    let state = client.debug_state();
+    assert_eq!(state.session_id, None);
+    assert_eq!(state.transport, None);
+    assert_eq!(state.subscriptions.len(), 1);
+    assert!(state.subscriptions[0].active);
+    assert_eq!(state.subscriptions[0].subscription_id, None);
+
+    client.connect().await.unwrap();
+    server.next_request("control").await;
+    tokio::time::timeout(TIMEOUT, async {
+        while client.debug_state().subscriptions[0].updates_received == 0 {
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("update not received");
+
+    let state = client.debug_state();
+    assert_eq!(state.session_id.as_deref(), Some("S1"));
+    assert_eq!(state.transport, Some(ConnectionType::WsStreaming));
+    let subscription = &state.subscriptions[0];
+    assert_eq!(subscription.items, "item");
+    assert_eq!(subscription.fields, "price");
+    assert_eq!(subscription.subscription_id, Some(1));
+    assert!(subscription.since_last_update.is_some());
+    assert!(state.to_string().contains("session: S1"));
+
+    client.disconnect().await;
+    let state = client.debug_state();
+    assert_eq!(state.session_id, None);
+    assert_eq!(state.subscriptions[0].subscription_id, None);
+}
@@ -0,0 +1,96 @@
+#![cfg(feature = "derive")]
+
+use lightstreamer_client::item_update::ItemUpdate;
+use lightstreamer_client::LightstreamerFields;
+
+#[derive(Debug, PartialEq, LightstreamerFields)]
+struct Quote {
+    stock_name: String,
+    #[lightstreamer(rename = "last_price")]
+    price: f64,
+    volume: u64,
+    bid: Option<f64>,
+    ask: Option<f64>,
+}
+
+fn update<'a>(fields: impl IntoIterator<Item = (&'a str, Option<&'a str>)>) -> ItemUpdate {
+    ItemUpdate::new(Some("item1"), 1, fields, false)
+}
+
+#[test]
+fn fields_are_parsed_into_the_struct() {
+    let update = update([
+        ("stock_name", Some("ACME")),
+        ("last_price", Some("12.5")),
+        ("volume", Some("1200")),
+        ("bid", Some("12.4")),
+        ("ask", None),
+        ("time", Some("10:00:00")),
+    ]);
+    let quote = Quote::from_item_update(&update).unwrap();
+    assert_eq!(
+        quote,
+        Quote {
+            stock_name: "ACME".to_string(),
+            price: 12.5,
+            volume: 1200,
+            bid: Some(12.4),
+            ask: None,
+        }
+    );
+}
+
+#[test]
+fn optional_fields_without_a_value_are_none() {
+    let update = update([
+        ("stock_name", Some("ACME")),
+        ("last_price", Some("12.5")),
+        ("volume", Some("0")),
+    ]);
+    let quote = Quote::from_item_update(&update).unwrap();
+    assert_eq!(quote.bid, None);
+    assert_eq!(quote.ask, None);
+}
+
+#[test]
+fn renamed_fields_are_read_from_the_lightstreamer_name() {
+    let update = update([
+        ("stock_name", Some("ACME")),
+        ("price", Some("12.5")),
+        ("volume", Some("1200")),
+    ]);
+    let error = Quote::from_item_update(&update).unwrap_err();
+    assert_eq!(error.to_string(), "Field 'last_price' has no value");
+}
+
+#[test]
+fn missing_or_invalid_values_are_rejected() {
+    let mut fields = vec![
+        ("stock_name", Some("ACME")),
+        ("last_price", Some("12.5")),
+        ("volume", None),
+    ];
+    let error = Quote::from_item_update(&update(fields.clone())).unwrap_err();
+    assert_eq!(error.to_string(), "Field 'volume' has no value");
+
+    fields[2] = ("volume", Some("many"));
+    let error = Quote::from_item_update(&update(fields.clone())).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("Invalid value for field 'volume'"),
+        "{}",
+        error
+    );
+
+    fields[2] = ("volume", Some("1200"));
+    fields.push(("bid", Some("n/a")));
+    let error = Quote::from_item_update(&update(fields)).unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("Invalid value for field 'bid'"),
+        "{}",
+        error
+    );
+}
@@ -0,0 +1,87 @@
+#![cfg(feature = "serde")]
+
+use lightstreamer_client::item_update::ItemUpdate;
+use serde::Deserialize;
+
+#[derive(Debug, PartialEq, Deserialize)]
+#[serde(rename_all = "UPPERCASE")]
+enum Side {
+    Buy,
+    Sell,
+}
+
+#[derive(Debug, PartialEq, Deserialize)]
+struct Tick<'a> {
+    #[serde(rename = "stock_name")]
+    name: &'a str,
+    last_price: f64,
+    volume: u64,
+    side: Side,
+    halted: bool,
+    bid: Option<f64>,
+    ask: Option<f64>,
+}
+
+fn update<'a>(fields: impl IntoIterator<Item = (&'a str, Option<&'a str>)>) -> ItemUpdate {
+    ItemUpdate::new(Some("item1"), 1, fields, false)
+}
+
+#[test]
+fn fields_are_parsed_into_the_target_type() {
+    let update = update([
+        ("stock_name", Some("ACME")),
+        ("last_price", Some("12.5")),
+        ("volume", Some("1200")),
+        ("side", Some("BUY")),
+        ("halted", Some("false")),
+        ("bid", None),
+        ("time", Some("10:00:00")),
+    ]);
+    let tick: Tick = update.deserialize().unwrap();
+    assert_eq!(
+        tick,
+        Tick {
+            name: "ACME",
+            last_price: 12.5,
+            volume: 1200,
+            side: Side::Buy,
+            halted: false,
+            bid: None,
+            ask: None,
+        }
+    );
+}
+
+#[test]
+fn invalid_or_missing_values_are_rejected() {
+    let mut fields = vec![
+        ("stock_name", Some("ACME")),
+        ("last_price", Some("n/a")),
+        ("volume", Some("1200")),
+        ("side", Some("SELL")),
+        ("halted", Some("true")),
+    ];
+    let error = update(fields.clone()).deserialize::<Tick>().unwrap_err();
+    assert!(error.to_string().contains("last_price"), "{}", error);
+
+    fields[1] = ("last_price", None);
+    let error = update(fields.clone()).deserialize::<Tick>().unwrap_err();
+    assert_eq!(error.to_string(), "Field 'last_price' has no value");
+
+    fields.remove(1);
+    let error = update(fields).deserialize::<Tick>().unwrap_err();
+    assert_eq!(error.to_string(), "missing field `last_price`");
+}
+
+#[test]
+fn unknown_fields_can_be_denied() {
+    #[derive(Debug, Deserialize)]
+    #[serde(deny_unknown_fields)]
+    #[allow(dead_code)]
+    struct Price {
+        last_price: f64,
+    }
+
+    let update = update([("last_price", Some("1")), ("bid", Some("2"))]);
+    assert!(update.deserialize::<Price>().is_err());
+}
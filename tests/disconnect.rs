@@ -0,0 +1,141 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{close_connection, drop_connection, MockServer, TIMEOUT};
+use lightstreamer_client::client_listener::ClientListener;
+use lightstreamer_client::disconnect_info::{DisconnectInfo, DisconnectInitiator};
+use lightstreamer_client::ls_client::{ClientStatus, LightstreamerClient};
+use lightstreamer_client::retry_policy::{DefaultRetryPolicy, RetryPolicy};
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// Listener forwarding the closures of the connections to the test.
+#[derive(Debug)]
+struct DisconnectForwarder(UnboundedSender<DisconnectInfo>);
+
+impl ClientListener for DisconnectForwarder {
+    fn on_disconnect(&self, info: &DisconnectInfo) {
+        let _ = self.0.send(info.clone());
+    }
+}
+
+/// Connects a client subscribed to "item1", so that the mock server receives a subscription
+/// request once the session is created. The retry delay is shortened, as it applies even to the
+/// first attempt after a clean close.
+async fn connect(server: &MockServer) -> (LightstreamerClient, UnboundedReceiver<DisconnectInfo>) {
+    let mut client = server.client();
+    client.connection_options.set_retry_delay(100).unwrap();
+    let (sender, disconnects) = mpsc::unbounded_channel();
+    client.add_listener(Box::new(DisconnectForwarder(sender)));
+    let subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["field1"]).unwrap();
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+    (client, disconnects)
+}
+
+async fn next_disconnect(disconnects: &mut UnboundedReceiver<DisconnectInfo>) -> DisconnectInfo {
+    tokio::time::timeout(TIMEOUT, disconnects.recv())
+        .await
+        .expect("no disconnection notified")
+        .expect("listener dropped")
+}
+
+#[tokio::test]
+async fn server_close_frames_are_reported_as_clean() {
+    let mut server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec![
+                "SUBOK,1,1,1".to_string(),
+                close_connection(1001, "restarting"),
+            ]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let (client, mut disconnects) = connect(&server).await;
+
+    let info = next_disconnect(&mut disconnects).await;
+    assert_eq!(
+        info,
+        DisconnectInfo {
+            initiator: DisconnectInitiator::Server,
+            code: Some(1001),
+            reason: "restarting".to_string(),
+            clean: true,
+        }
+    );
+    assert!(info.is_clean_server_close());
+    assert_eq!(client.get_last_disconnect_info(), Some(info));
+    server.next_request("recover_session").await;
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn dropped_connections_are_reported_as_aborted() {
+    let mut server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec![drop_connection()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let (client, mut disconnects) = connect(&server).await;
+
+    let info = next_disconnect(&mut disconnects).await;
+    assert_eq!(info.initiator, DisconnectInitiator::Server);
+    assert_eq!(info.code, None);
+    assert!(!info.clean);
+    assert!(!info.is_clean_server_close());
+    server.next_request("recover_session").await;
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn disconnect_performs_the_close_handshake() {
+    let server = MockServer::start(|_| Vec::new()).await;
+    let (client, mut disconnects) = connect(&server).await;
+    tokio::time::timeout(TIMEOUT, async {
+        while !matches!(client.get_status(), ClientStatus::Connected(_)) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("client not connected");
+    assert_eq!(client.get_last_disconnect_info(), None);
+
+    client.disconnect().await;
+
+    let info = next_disconnect(&mut disconnects).await;
+    assert_eq!(info.initiator, DisconnectInitiator::Client);
+    assert_eq!(info.code, Some(1000));
+    assert!(info.clean);
+    assert_eq!(client.get_last_disconnect_info(), Some(info));
+}
+
+#[test]
+fn default_policy_waits_longer_after_clean_server_closes() {
+    let retry_delay = Duration::from_secs(4);
+    let policy = DefaultRetryPolicy::new(retry_delay, Duration::from_millis(100));
+    let clean = DisconnectInfo {
+        initiator: DisconnectInitiator::Server,
+        code: Some(1001),
+        reason: String::new(),
+        clean: true,
+    };
+    let aborted = DisconnectInfo {
+        clean: false,
+        code: None,
+        ..clean.clone()
+    };
+
+    assert_eq!(policy.next_delay(0, &clean), Some(retry_delay));
+    assert!(policy.next_delay(0, &aborted).unwrap() <= Duration::from_millis(100));
+    assert_eq!(policy.next_delay(1, &aborted), Some(retry_delay));
+}
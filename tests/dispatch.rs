@@ -0,0 +1,165 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::client_listener::ClientListener;
+use lightstreamer_client::item_update::ItemUpdate;
+use lightstreamer_client::subscription::{DispatchMode, Subscription, SubscriptionMode};
+use lightstreamer_client::subscription_listener::SubscriptionListener;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// Records the events received by all the listeners, detecting callbacks running concurrently.
+#[derive(Debug, Clone)]
+struct Recorder {
+    events: UnboundedSender<String>,
+    running: Arc<AtomicUsize>,
+    overlapped: Arc<AtomicBool>,
+}
+
+impl Recorder {
+    fn record(&self, event: String) {
+        if self.running.fetch_add(1, Ordering::SeqCst) > 0 {
+            self.overlapped.store(true, Ordering::SeqCst);
+        }
+        // Give a concurrent callback the chance to show up.
+        std::thread::sleep(Duration::from_millis(1));
+        let _ = self.events.send(event);
+        self.running.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[derive(Debug)]
+struct StatusListener(Recorder);
+
+impl ClientListener for StatusListener {
+    fn on_status_change(&self, status: &str) {
+        self.0.record(status.to_string());
+    }
+}
+
+struct UpdateListener(Recorder);
+
+impl SubscriptionListener for UpdateListener {
+    fn on_item_update(&self, update: &ItemUpdate) {
+        let price = update.get_value("price").unwrap_or_default();
+        self.0.record(format!("update {}", price));
+    }
+}
+
+async fn next_event(events: &mut UnboundedReceiver<String>) -> String {
+    tokio::time::timeout(TIMEOUT, events.recv())
+        .await
+        .expect("no event received")
+        .expect("listener dropped")
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn events_are_dispatched_in_order_one_at_a_time() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            let mut notifications = vec!["SUBOK,1,1,1".to_string()];
+            notifications.extend((1..=20).map(|price| format!("U,1,1,{}", price)));
+            notifications
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let (sender, mut events) = mpsc::unbounded_channel();
+    let recorder = Recorder {
+        events: sender,
+        running: Arc::default(),
+        overlapped: Arc::default(),
+    };
+    client.add_listener(Box::new(StatusListener(recorder.clone())));
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price"]).unwrap();
+    subscription.add_listener(Box::new(UpdateListener(recorder.clone())));
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let mut expected = vec![
+        "CONNECTING".to_string(),
+        "CONNECTED:WS-STREAMING".to_string(),
+    ];
+    expected.extend((1..=20).map(|price| format!("update {}", price)));
+    for expected in expected {
+        assert_eq!(next_event(&mut events).await, expected);
+    }
+    client.disconnect().await;
+    assert_eq!(next_event(&mut events).await, "DISCONNECTED");
+
+    assert!(!recorder.overlapped.load(Ordering::SeqCst));
+}
+
+struct PooledListener(UnboundedSender<(usize, u32)>);
+
+impl SubscriptionListener for PooledListener {
+    fn on_item_update(&self, update: &ItemUpdate) {
+        let price = update.get_value("price").unwrap_or_default();
+        let _ = self.0.send((update.get_item_pos(), price.parse().unwrap()));
+    }
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+async fn pooled_updates_keep_the_order_of_each_item() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            let mut notifications = vec!["SUBOK,1,4,1".to_string()];
+            for price in 1..=10 {
+                notifications.extend((1..=4).map(|item| format!("U,1,{},{}", item, price)));
+            }
+            notifications
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let (sender, mut updates) = mpsc::unbounded_channel();
+    let mut subscription = Subscription::new(
+        SubscriptionMode::Merge,
+        Some(["item1", "item2", "item3", "item4"]),
+        Some(["price"]),
+    )
+    .unwrap();
+    subscription
+        .set_dispatch_mode(DispatchMode::Pooled(4))
+        .unwrap();
+    subscription.add_pooled_listener(Arc::new(PooledListener(sender)));
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let mut last_prices = HashMap::new();
+    for _ in 0..40 {
+        let (item, price) = tokio::time::timeout(TIMEOUT, updates.recv())
+            .await
+            .expect("no update received")
+            .expect("listener dropped");
+        let last_price = last_prices.insert(item, price).unwrap_or(0);
+        assert_eq!(
+            price,
+            last_price + 1,
+            "update out of order for item {}",
+            item
+        );
+    }
+    assert_eq!(last_prices, (1..=4).map(|item| (item, 10)).collect());
+    client.disconnect().await;
+}
+
+#[test]
+fn pool_without_workers_is_rejected() {
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price"]).unwrap();
+    assert!(subscription
+        .set_dispatch_mode(DispatchMode::Pooled(0))
+        .is_err());
+    assert_eq!(subscription.get_dispatch_mode(), DispatchMode::Ordered);
+}
@@ -0,0 +1,178 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{request_param, MockServer, TIMEOUT};
+use lightstreamer_client::client_metrics::DispatchStall;
+use lightstreamer_client::connection_options::ConnectionOptions;
+use lightstreamer_client::item_update::ItemUpdate;
+use lightstreamer_client::ls_client::LightstreamerClient;
+use lightstreamer_client::subscription::{BackpressurePolicy, Subscription, SubscriptionMode};
+use lightstreamer_client::subscription_listener::SubscriptionListener;
+use std::sync::mpsc::{Receiver, Sender};
+use std::sync::Mutex;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// Listener forwarding the values of the updates to the test, optionally blocked on its first
+/// update until released.
+struct Forwarder {
+    values: UnboundedSender<String>,
+    gate: Mutex<Option<Receiver<()>>>,
+}
+
+impl SubscriptionListener for Forwarder {
+    fn on_item_update(&self, update: &ItemUpdate) {
+        if let Some(gate) = self.gate.lock().unwrap().take() {
+            // The runtime worker hands over its queued tasks while blocked.
+            let _ = tokio::task::block_in_place(|| gate.recv());
+        }
+        let value = update.get_value("field1").unwrap_or_default().to_string();
+        let _ = self.values.send(value);
+    }
+}
+
+/// Starts a mock server answering every subscription request with 3 updates.
+async fn start_server() -> MockServer {
+    MockServer::start(|request| {
+        if !request.contains("LS_op=add") {
+            return Vec::new();
+        }
+        let id = request_param(request, "LS_subId").unwrap_or_default();
+        let mut notifications = vec![format!("SUBOK,{},1,1", id)];
+        notifications.extend((1..=3).map(|value| format!("U,{},1,{}", id, value)));
+        notifications
+    })
+    .await
+}
+
+/// Subscribes to the given item with a listener, blocked if a gate is given. The updates of a
+/// blocked listener go through a backpressure buffer, so that they are delivered one at a time.
+fn subscribe(
+    client: &LightstreamerClient,
+    item: &str,
+    gate: Option<Receiver<()>>,
+) -> UnboundedReceiver<String> {
+    let (values, receiver) = mpsc::unbounded_channel();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, item, ["field1"]).unwrap();
+    if gate.is_some() {
+        subscription
+            .set_backpressure_policy(BackpressurePolicy::Block(10))
+            .unwrap();
+    }
+    subscription.add_listener(Box::new(Forwarder {
+        values,
+        gate: Mutex::new(gate),
+    }));
+    client.subscribe(subscription);
+    receiver
+}
+
+async fn wait_for_stall(client: &LightstreamerClient) -> DispatchStall {
+    tokio::time::timeout(TIMEOUT, async {
+        loop {
+            if let Some(stall) = client.get_dispatch_stall() {
+                return stall;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("no stall reported")
+}
+
+async fn next_value(values: &mut UnboundedReceiver<String>) -> String {
+    tokio::time::timeout(TIMEOUT, values.recv())
+        .await
+        .expect("no update received")
+        .expect("listener dropped")
+}
+
+/// Connects a client with a watchdog, whose first subscription blocks on its first update, and
+/// waits until the stall is reported. Returns the receivers of the values of both subscriptions
+/// and the sender releasing the blocked listener.
+async fn connect_stalled(
+    server: &MockServer,
+    isolation: bool,
+) -> (
+    LightstreamerClient,
+    UnboundedReceiver<String>,
+    UnboundedReceiver<String>,
+    Sender<()>,
+) {
+    let mut client = server.client();
+    client
+        .connection_options
+        .set_dispatch_watchdog_timeout(Some(100))
+        .unwrap();
+    client
+        .connection_options
+        .set_stalled_subscription_isolation_enabled(isolation);
+    let (release, gate) = std::sync::mpsc::channel();
+    let stalled_values = subscribe(&client, "item1", Some(gate));
+    let other_values = subscribe(&client, "item2", None);
+    client.connect().await.unwrap();
+
+    let stall = wait_for_stall(&client).await;
+    assert_eq!(stall.callback, "onItemUpdates");
+    assert_eq!(stall.subscription.as_deref(), Some("item1"));
+    assert!(stall.stalled_for >= Duration::from_millis(100));
+    assert_eq!(stall.isolated, isolation);
+    assert_eq!(client.get_metrics().dispatch_stalls, 1);
+    (client, stalled_values, other_values, release)
+}
+
+#[test]
+fn dispatch_watchdog_is_disabled_by_default() {
+    let mut options = ConnectionOptions::new();
+    assert_eq!(options.get_dispatch_watchdog_timeout(), None);
+    assert!(!options.is_stalled_subscription_isolation_enabled());
+    assert!(options.set_dispatch_watchdog_timeout(Some(0)).is_err());
+    options.set_dispatch_watchdog_timeout(Some(1000)).unwrap();
+    options.set_stalled_subscription_isolation_enabled(true);
+    assert_eq!(options.get_dispatch_watchdog_timeout(), Some(1000));
+    assert!(options.is_stalled_subscription_isolation_enabled());
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn stalled_listeners_are_reported_until_they_return() {
+    let server = start_server().await;
+    let (client, mut stalled_values, mut other_values, release) =
+        connect_stalled(&server, false).await;
+
+    release.send(()).unwrap();
+    for value in ["1", "2", "3"] {
+        assert_eq!(next_value(&mut stalled_values).await, value);
+    }
+    for value in ["1", "2", "3"] {
+        assert_eq!(next_value(&mut other_values).await, value);
+    }
+    assert_eq!(client.get_dispatch_stall(), None);
+    assert!(!client.get_subscriptions()[0].is_isolated());
+
+    client.disconnect().await;
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn stalled_subscriptions_are_isolated() {
+    let server = start_server().await;
+    let (client, mut stalled_values, mut other_values, release) =
+        connect_stalled(&server, true).await;
+
+    release.send(()).unwrap();
+    // The update the listener was blocked on completes, while the following ones are skipped.
+    assert_eq!(next_value(&mut stalled_values).await, "1");
+    for value in ["1", "2", "3"] {
+        assert_eq!(next_value(&mut other_values).await, value);
+    }
+    assert!(stalled_values.try_recv().is_err());
+    assert_eq!(client.get_dispatch_stall(), None);
+    {
+        let subscriptions = client.get_subscriptions();
+        assert!(subscriptions[0].is_isolated());
+        assert!(!subscriptions[1].is_isolated());
+    }
+
+    client.disconnect().await;
+}
@@ -0,0 +1,90 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::connection_options::ConnectionOptions;
+use lightstreamer_client::event_history::{ClientEventKind, DEFAULT_EVENT_HISTORY_SIZE};
+use lightstreamer_client::ls_client::LightstreamerClient;
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use std::time::Duration;
+
+async fn start_server() -> MockServer {
+    MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec!["SUBOK,1,1,1".to_string(), "U,1,1,value".to_string()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await
+}
+
+/// Connects the client, subscribed to an item, and disconnects it once the update is received.
+async fn run_session(client: &LightstreamerClient) {
+    client.subscribe(
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["field1"]).unwrap(),
+    );
+    client.connect().await.unwrap();
+    tokio::time::timeout(TIMEOUT, async {
+        while client.get_metrics().updates_received < 1 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .expect("no update received");
+    client.disconnect().await;
+}
+
+#[test]
+fn event_history_size_defaults_to_the_constant() {
+    let mut options = ConnectionOptions::new();
+    assert_eq!(options.get_event_history_size(), DEFAULT_EVENT_HISTORY_SIZE);
+    options.set_event_history_size(0);
+    assert_eq!(options.get_event_history_size(), 0);
+}
+
+#[tokio::test]
+async fn recent_events_describe_the_session() {
+    let server = start_server().await;
+    let client = server.client();
+    run_session(&client).await;
+
+    let events = client.recent_events();
+    let statuses: Vec<&str> = events
+        .iter()
+        .filter(|event| event.kind == ClientEventKind::StatusChange)
+        .map(|event| event.description.as_str())
+        .collect();
+    assert_eq!(
+        statuses,
+        ["CONNECTING", "CONNECTED:WS-STREAMING", "DISCONNECTED"]
+    );
+    let frames = events
+        .iter()
+        .find(|event| event.kind == ClientEventKind::Frames)
+        .expect("no frame summary");
+    assert!(frames.description.starts_with("Received "), "{}", frames);
+    assert!(events
+        .iter()
+        .any(|event| event.kind == ClientEventKind::Info
+            && event
+                .description
+                .starts_with("Subscription confirmed by server")));
+}
+
+#[tokio::test]
+async fn recent_events_are_bounded() {
+    let server = start_server().await;
+    let mut client = server.client();
+    client.connection_options.set_event_history_size(3);
+    run_session(&client).await;
+
+    let events = client.recent_events();
+    assert_eq!(events.len(), 3, "{:?}", events);
+    assert_eq!(events[2].description, "DISCONNECTED");
+
+    client.connection_options.set_event_history_size(0);
+    run_session(&client).await;
+    assert!(client.recent_events().is_empty());
+}
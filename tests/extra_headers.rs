@@ -0,0 +1,85 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{drop_connection, MockServer};
+use lightstreamer_client::ls_client::LightstreamerClient;
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Starts a mock server dropping the first connection once the subscription is confirmed, so
+/// that the session is recovered on a new connection.
+async fn start_server() -> MockServer {
+    let dropped = AtomicBool::new(false);
+    MockServer::start(move |request| {
+        if request.contains("LS_op=add") && !dropped.swap(true, Ordering::SeqCst) {
+            vec!["SUBOK,1,1,1".to_string(), drop_connection()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await
+}
+
+async fn connect(server: &MockServer, session_creation_only: bool) -> LightstreamerClient {
+    let mut client = server.client();
+    client
+        .connection_options
+        .set_http_extra_headers(Some(HashMap::from([(
+            "X-Gateway-Token".to_string(),
+            "abc".to_string(),
+        )])));
+    client
+        .connection_options
+        .set_http_extra_headers_on_session_creation_only(session_creation_only);
+    client.connection_options.set_retry_delay(10).unwrap();
+    client.subscribe(
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price"]).unwrap(),
+    );
+    client.connect().await.unwrap();
+    client
+}
+
+#[tokio::test]
+async fn extra_headers_are_sent_on_every_handshake() {
+    let mut server = start_server().await;
+    let client = connect(&server, false).await;
+
+    let handshake = server.next_request("handshake").await;
+    assert!(handshake.contains("x-gateway-token: abc"), "{}", handshake);
+    let handshake = server.next_request("handshake").await;
+    assert!(handshake.contains("x-gateway-token: abc"), "{}", handshake);
+    server.next_request("recover_session").await;
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn extra_headers_can_be_restricted_to_session_creation() {
+    let mut server = start_server().await;
+    let client = connect(&server, true).await;
+
+    let handshake = server.next_request("handshake").await;
+    assert!(handshake.contains("x-gateway-token: abc"), "{}", handshake);
+    server.next_request("create_session").await;
+    let handshake = server.next_request("handshake").await;
+    assert!(!handshake.contains("x-gateway-token"), "{}", handshake);
+    server.next_request("recover_session").await;
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn invalid_extra_headers_fail_the_connection() {
+    let server = start_server().await;
+    let mut client = server.client();
+    client
+        .connection_options
+        .set_http_extra_headers(Some(HashMap::from([(
+            "X-Token".to_string(),
+            "line\nbreak".to_string(),
+        )])));
+    let error = client.connect().await.unwrap_err();
+    assert!(error.to_string().contains("X-Token"), "{}", error);
+}
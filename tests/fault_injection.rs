@@ -0,0 +1,130 @@
+#![cfg(all(feature = "runtime-tokio", feature = "test-util"))]
+
+mod common;
+
+use common::{request_param, MockServer, TIMEOUT};
+use lightstreamer_client::fault_injection::FaultInjector;
+use lightstreamer_client::item_update::ItemUpdate;
+use lightstreamer_client::ls_client::LightstreamerClient;
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use lightstreamer_client::subscription_listener::SubscriptionListener;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+struct PriceListener(UnboundedSender<String>);
+
+impl SubscriptionListener for PriceListener {
+    fn on_item_update(&self, update: &ItemUpdate) {
+        let _ = self
+            .0
+            .send(update.get_value("price").unwrap_or_default().to_string());
+    }
+}
+
+/// Starts a server sending two updates for each subscription, and a client connecting to it
+/// through the given fault injector.
+async fn start(injector: &Arc<FaultInjector>) -> (MockServer, LightstreamerClient) {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            let id = request_param(request, "LS_subId").unwrap_or("1");
+            vec![
+                format!("SUBOK,{},1,1", id),
+                format!("U,{},1,10.5", id),
+                format!("U,{},1,11", id),
+            ]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let mut client = server.client();
+    client
+        .connection_options
+        .set_fault_injector(Some(Arc::clone(injector)));
+    client.connection_options.set_retry_delay(10).unwrap();
+    client
+        .connection_options
+        .set_first_retry_max_delay(10)
+        .unwrap();
+    (server, client)
+}
+
+/// Subscribes the client to the prices of an item.
+fn subscribe(client: &mut LightstreamerClient) -> UnboundedReceiver<String> {
+    let (sender, prices) = mpsc::unbounded_channel();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price"]).unwrap();
+    subscription.add_listener(Box::new(PriceListener(sender)));
+    client.subscribe(subscription);
+    prices
+}
+
+async fn next_price(prices: &mut UnboundedReceiver<String>) -> String {
+    tokio::time::timeout(TIMEOUT, prices.recv())
+        .await
+        .expect("no update received")
+        .expect("listener dropped")
+}
+
+#[tokio::test]
+async fn dropped_connection_is_recovered() {
+    let injector = Arc::new(FaultInjector::new());
+    let (mut server, mut client) = start(&injector).await;
+    let mut prices = subscribe(&mut client);
+    client.connect().await.unwrap();
+    assert_eq!(next_price(&mut prices).await, "10.5");
+    assert_eq!(next_price(&mut prices).await, "11");
+
+    injector.drop_connection();
+    server.next_request("recover_session").await;
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn truncated_frame_is_tolerated() {
+    let injector = Arc::new(FaultInjector::new());
+    let (_server, mut client) = start(&injector).await;
+    client.connect().await.unwrap();
+    tokio::time::timeout(TIMEOUT, async {
+        while client.get_status().to_string() != "CONNECTED:WS-STREAMING" {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("not connected");
+
+    // The frame with the subscription outcome loses the last digit of the second update.
+    injector.truncate_next_frame("SUBOK,1,1,1\r\nU,1,1,10.5\r\nU,1,1,1".len());
+    let mut prices = subscribe(&mut client);
+    assert_eq!(next_price(&mut prices).await, "10.5");
+    assert_eq!(next_price(&mut prices).await, "1");
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn corrupted_frame_drops_the_connection() {
+    let injector = Arc::new(FaultInjector::new());
+    let (mut server, client) = start(&injector).await;
+    // The first frame received, i.e. WSOK, is no longer valid UTF-8.
+    injector.corrupt_next_frame(0, 0xff);
+    client.connect().await.unwrap();
+    server.next_request("wsok").await;
+    server.next_request("wsok").await;
+    server.next_request("create_session").await;
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn frames_are_delayed() {
+    let injector = Arc::new(FaultInjector::new());
+    let (_server, mut client) = start(&injector).await;
+    let mut prices = subscribe(&mut client);
+    injector.delay_frames(Some(Duration::from_millis(50)));
+    let started_at = Instant::now();
+    client.connect().await.unwrap();
+    assert_eq!(next_price(&mut prices).await, "10.5");
+    // WSOK, CONOK and the subscription frame were each delayed.
+    assert!(started_at.elapsed() >= Duration::from_millis(150));
+    client.disconnect().await;
+}
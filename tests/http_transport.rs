@@ -0,0 +1,354 @@
+#![cfg(feature = "reqwest")]
+
+mod common;
+
+use common::{request_param, TIMEOUT};
+use lightstreamer_client::connection_options::ReadBufferGrowth;
+use lightstreamer_client::item_update::ItemUpdate;
+use lightstreamer_client::ls_client::{
+    ClientStatus, ConnectionType, LightstreamerClient, Transport,
+};
+use lightstreamer_client::request_interceptor::{InterceptedRequest, RequestInterceptor};
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use lightstreamer_client::subscription_listener::SubscriptionListener;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// Mock server speaking the HTTP binding of TLCP. Session requests are answered with a `CONOK`,
+/// followed by a `LOOP` in polling mode, while the notifications pushed in streaming mode are
+/// written on the last stream opened. Control requests are confirmed with a `REQOK`.
+struct HttpServer {
+    address: String,
+    /// Requests received, as "<name>\r\n<query>\r\n<headers>\r\n<body>".
+    requests: UnboundedReceiver<String>,
+    /// Writer of the notifications on the last stream opened.
+    stream: Arc<Mutex<Option<UnboundedSender<String>>>>,
+}
+
+impl HttpServer {
+    async fn start() -> Self {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = format!("http://{}/", listener.local_addr().unwrap());
+        let (request_sender, requests) = mpsc::unbounded_channel();
+        let stream = Arc::new(Mutex::new(None));
+        let server_stream = Arc::clone(&stream);
+        tokio::spawn(async move {
+            while let Ok((socket, _)) = listener.accept().await {
+                tokio::spawn(serve(
+                    socket,
+                    request_sender.clone(),
+                    Arc::clone(&server_stream),
+                ));
+            }
+        });
+        HttpServer {
+            address,
+            requests,
+            stream,
+        }
+    }
+
+    /// Creates a client connecting to this server over the given HTTP transport.
+    fn client(&self, transport: Transport) -> LightstreamerClient {
+        let mut client = LightstreamerClient::new(Some(&self.address), None, None, None).unwrap();
+        client
+            .connection_options
+            .set_forced_transport(Some(transport));
+        client
+            .connection_options
+            .set_http_client(Some(reqwest::Client::new()));
+        client
+    }
+
+    /// Writes a notification on the last stream opened.
+    fn push(&self, notification: &str) {
+        let stream = self.stream.lock().unwrap();
+        let _ = stream
+            .as_ref()
+            .expect("no stream opened")
+            .send(notification.to_string());
+    }
+
+    /// Waits for the next request with the given name.
+    async fn next_request(&mut self, name: &str) -> String {
+        tokio::time::timeout(TIMEOUT, async {
+            loop {
+                let request = self.requests.recv().await.expect("server stopped");
+                if request.starts_with(&format!("{}\r\n", name)) {
+                    return request;
+                }
+            }
+        })
+        .await
+        .unwrap_or_else(|_| panic!("no {} request received", name))
+    }
+}
+
+/// Answers a single request, with a response delimited by the closure of the connection.
+async fn serve(
+    socket: TcpStream,
+    requests: UnboundedSender<String>,
+    stream: Arc<Mutex<Option<UnboundedSender<String>>>>,
+) {
+    let mut reader = BufReader::new(socket);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line).await.unwrap();
+    let mut content_length = 0;
+    let mut headers = String::new();
+    loop {
+        let mut header = String::new();
+        reader.read_line(&mut header).await.unwrap();
+        if header.trim().is_empty() {
+            break;
+        }
+        headers.push_str(&header);
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap();
+            }
+        }
+    }
+    let mut body = vec![0; content_length];
+    reader.read_exact(&mut body).await.unwrap();
+    let body = String::from_utf8(body).unwrap();
+    let target = request_line.split(' ').nth(1).unwrap_or_default();
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+    let name = path
+        .trim_start_matches("/lightstreamer/")
+        .trim_end_matches(".txt")
+        .to_string();
+    let _ = requests.send(format!("{}\r\n{}\r\n{}\r\n{}", name, query, headers, body));
+
+    let mut socket = reader.into_inner();
+    socket
+        .write_all(b"HTTP/1.1 200 OK\r\nContent-Type: text/plain\r\nConnection: close\r\n\r\n")
+        .await
+        .unwrap();
+    match name.as_str() {
+        "create_session" | "bind_session" | "recover_session" => {
+            let _ = socket.write_all(b"CONOK,S1,50000,5000,*\r\n").await;
+            if request_param(&body, "LS_polling") == Some("true") {
+                let _ = socket.write_all(b"LOOP,0\r\n").await;
+                return;
+            }
+            let (sender, mut notifications) = mpsc::unbounded_channel::<String>();
+            *stream.lock().unwrap() = Some(sender);
+            while let Some(notification) = notifications.recv().await {
+                if socket
+                    .write_all(format!("{}\r\n", notification).as_bytes())
+                    .await
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        }
+        _ => {
+            let request_id = request_param(&body, "LS_reqId").unwrap_or_default();
+            let _ = socket
+                .write_all(format!("REQOK,{}\r\n", request_id).as_bytes())
+                .await;
+        }
+    }
+}
+
+/// Listener forwarding the updates to the test.
+struct UpdateForwarder(UnboundedSender<String>);
+
+impl SubscriptionListener for UpdateForwarder {
+    fn on_item_update(&self, update: &ItemUpdate) {
+        let value = update.get_value("field1").unwrap_or_default().to_string();
+        let _ = self.0.send(value);
+    }
+}
+
+/// Waits until the client is connected with the given connection type.
+async fn wait_connected(client: &LightstreamerClient, connection_type: ConnectionType) {
+    let expected = ClientStatus::Connected(connection_type);
+    tokio::time::timeout(TIMEOUT, async {
+        while client.get_status() != expected {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("client not {}", expected));
+}
+
+#[tokio::test]
+async fn notifications_are_streamed_over_http() {
+    let mut server = HttpServer::start().await;
+    let client = server.client(Transport::HttpStreaming);
+    let (sender, mut updates) = mpsc::unbounded_channel();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["field1"]).unwrap();
+    subscription.add_listener(Box::new(UpdateForwarder(sender)));
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let request = server.next_request("create_session").await;
+    assert!(request.contains("LS_protocol=TLCP-2.4.0"));
+    wait_connected(&client, ConnectionType::HttpStreaming).await;
+    let request = server.next_request("control").await;
+    assert!(request.contains("LS_session=S1"), "{}", request);
+    assert!(request.contains("LS_op=add"), "{}", request);
+    server.push("SUBOK,1,1,1");
+    server.push("U,1,1,value1");
+    let update = tokio::time::timeout(TIMEOUT, updates.recv())
+        .await
+        .expect("no update received");
+    assert_eq!(update.as_deref(), Some("value1"));
+
+    client.disconnect().await;
+    let request = server.next_request("control").await;
+    assert!(request.contains("LS_op=destroy"), "{}", request);
+}
+
+#[tokio::test]
+async fn long_notifications_are_received_with_small_buffers() {
+    for growth in [
+        ReadBufferGrowth::Doubling,
+        ReadBufferGrowth::Exact,
+        ReadBufferGrowth::Shrinking,
+    ] {
+        let mut server = HttpServer::start().await;
+        let mut client = server.client(Transport::HttpStreaming);
+        client
+            .connection_options
+            .set_read_buffer_size(Some(16))
+            .unwrap();
+        client.connection_options.set_read_buffer_growth(growth);
+        let (sender, mut updates) = mpsc::unbounded_channel();
+        let mut subscription =
+            Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["field1"]).unwrap();
+        subscription.add_listener(Box::new(UpdateForwarder(sender)));
+        client.subscribe(subscription);
+        client.connect().await.unwrap();
+        server.next_request("control").await;
+
+        server.push("SUBOK,1,1,1");
+        for length in [100, 10000, 10] {
+            server.push(&format!("U,1,1,{}", "x".repeat(length)));
+            let update = tokio::time::timeout(TIMEOUT, updates.recv())
+                .await
+                .expect("no update received");
+            assert_eq!(
+                update.map(|value| value.len()),
+                Some(length),
+                "{:?}",
+                growth
+            );
+        }
+
+        client.disconnect().await;
+    }
+}
+
+#[tokio::test]
+async fn polls_are_issued_over_http() {
+    let mut server = HttpServer::start().await;
+    let mut client = server.client(Transport::HttpPolling);
+    client.connection_options.set_idle_timeout(50).unwrap();
+    client.connection_options.set_polling_interval(50).unwrap();
+    client.connect().await.unwrap();
+
+    let request = server.next_request("create_session").await;
+    assert!(request.contains("LS_polling=true"), "{}", request);
+    wait_connected(&client, ConnectionType::HttpPolling).await;
+    let request = server.next_request("bind_session").await;
+    assert!(request.contains("LS_session=S1"), "{}", request);
+    assert!(request.contains("LS_polling=true"), "{}", request);
+    assert_eq!(client.get_metrics().reconnections, 0);
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn intercepted_headers_are_sent_with_every_request() {
+    #[derive(Debug)]
+    struct Gateway;
+
+    impl RequestInterceptor for Gateway {
+        fn intercept(&self, request: &mut InterceptedRequest) {
+            request.set_header("x-gateway", &request.get_kind().to_string());
+        }
+    }
+
+    let mut server = HttpServer::start().await;
+    let mut client = server.client(Transport::HttpStreaming);
+    client
+        .connection_options
+        .add_request_interceptor(Arc::new(Gateway));
+    client.subscribe(
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["field1"]).unwrap(),
+    );
+    client.connect().await.unwrap();
+
+    let request = server.next_request("create_session").await;
+    assert!(request.contains("x-gateway: create_session"), "{}", request);
+    let request = server.next_request("control").await;
+    assert!(request.contains("x-gateway: control"), "{}", request);
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn extra_headers_restricted_to_session_creation_are_sent_with_it_only() {
+    let mut server = HttpServer::start().await;
+    let mut client = server.client(Transport::HttpStreaming);
+    client
+        .connection_options
+        .set_http_extra_headers(Some(HashMap::from([(
+            "x-gateway-token".to_string(),
+            "abc".to_string(),
+        )])));
+    client
+        .connection_options
+        .set_http_extra_headers_on_session_creation_only(true);
+    client.subscribe(
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["field1"]).unwrap(),
+    );
+    client.connect().await.unwrap();
+
+    let request = server.next_request("create_session").await;
+    assert!(request.contains("x-gateway-token: abc"), "{}", request);
+    let request = server.next_request("control").await;
+    assert!(!request.contains("x-gateway-token"), "{}", request);
+
+    client.disconnect().await;
+}
+
+#[cfg(feature = "tower")]
+#[tokio::test]
+async fn requests_go_through_the_http_service() {
+    use lightstreamer_client::connection_options::HttpService;
+    use tower::ServiceBuilder;
+
+    let mut server = HttpServer::start().await;
+    let mut client = server.client(Transport::HttpStreaming);
+    let service = ServiceBuilder::new()
+        .map_request(|mut request: reqwest::Request| {
+            let layer = "tower".parse().unwrap();
+            request.headers_mut().insert("x-layer", layer);
+            request
+        })
+        .service(reqwest::Client::new());
+    client
+        .connection_options
+        .set_http_service(Some(HttpService::new(service)));
+    client.subscribe(
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["field1"]).unwrap(),
+    );
+    client.connect().await.unwrap();
+
+    let request = server.next_request("create_session").await;
+    assert!(request.contains("x-layer: tower"), "{}", request);
+    wait_connected(&client, ConnectionType::HttpStreaming).await;
+    let request = server.next_request("control").await;
+    assert!(request.contains("x-layer: tower"), "{}", request);
+
+    client.disconnect().await;
+}
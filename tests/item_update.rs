@@ -0,0 +1,183 @@
+use lightstreamer_client::item_update::ItemUpdate;
+
+fn update<'a>(fields: impl IntoIterator<Item = (&'a str, Option<&'a str>)>) -> ItemUpdate {
+    ItemUpdate::new(Some("item1"), 1, fields, false)
+}
+
+#[test]
+#[allow(deprecated)]
+fn fields_are_iterated_in_field_list_order() {
+    let names = ["time", "stock_name", "last_price", "bid", "ask"];
+    let update = update(names.iter().map(|name| (*name, Some("1"))));
+    let iterated: Vec<&str> = update.fields_iter().map(|(name, _)| name).collect();
+    assert_eq!(iterated, names);
+    assert!(update.iter_fields_ordered().eq(update.fields_iter()));
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json_values_are_parsed() {
+    let update = update([
+        ("payload", Some(r#"{"bid":12.4,"tags":["a","b"]}"#)),
+        ("empty", None),
+    ]);
+    let payload = update.get_value_as_json("payload").unwrap().unwrap();
+    assert_eq!(payload["bid"], 12.4);
+    assert_eq!(payload["tags"][1], "b");
+    assert_eq!(update.get_value_as_json("1").unwrap(), Some(payload));
+    assert_eq!(update.get_value_as_json("empty").unwrap(), None);
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn invalid_json_values_are_rejected() {
+    let update = update([("payload", Some("{\"bid\":")), ("text", Some("ACME"))]);
+    let error = update.get_value_as_json("payload").unwrap_err();
+    assert!(error.is_eof(), "{}", error);
+    let error = update.get_value_as_json("text").unwrap_err();
+    assert!(error.is_syntax(), "{}", error);
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn timestamps_are_parsed_in_every_format() {
+    use lightstreamer_client::item_update::TimestampFormat;
+
+    let update = update([
+        ("seconds", Some("1718000000")),
+        ("millis", Some(" 1718000000123 ")),
+        ("rfc3339", Some("2024-06-10T08:13:20+02:00")),
+        ("offset", Some("10/06/2024 08:13:20 +0200")),
+        ("naive", Some("10/06/2024 06:13:20")),
+        ("empty", None),
+    ]);
+    let parse = |field: &str, format: &TimestampFormat| {
+        update
+            .get_value_as_datetime(field, format)
+            .unwrap()
+            .map(|timestamp| timestamp.to_rfc3339())
+    };
+    let expected = Some("2024-06-10T06:13:20+00:00".to_string());
+    assert_eq!(parse("seconds", &TimestampFormat::UnixSeconds), expected);
+    assert_eq!(
+        parse("millis", &TimestampFormat::UnixMillis).as_deref(),
+        Some("2024-06-10T06:13:20.123+00:00")
+    );
+    assert_eq!(parse("rfc3339", &TimestampFormat::Rfc3339), expected);
+    // Custom formats with an offset are converted to UTC, and those without one are taken as UTC.
+    assert_eq!(
+        parse(
+            "offset",
+            &TimestampFormat::Custom("%d/%m/%Y %H:%M:%S %z".to_string())
+        ),
+        expected
+    );
+    assert_eq!(
+        parse(
+            "naive",
+            &TimestampFormat::Custom("%d/%m/%Y %H:%M:%S".to_string())
+        ),
+        expected
+    );
+    assert_eq!(parse("empty", &TimestampFormat::UnixSeconds), None);
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn invalid_timestamps_are_rejected() {
+    use lightstreamer_client::item_update::TimestampFormat;
+
+    let update = update([
+        ("seconds", Some("yesterday")),
+        ("millis", Some("9223372036854775807")),
+        ("custom", Some("2024-06-10")),
+    ]);
+    let error = update
+        .get_value_as_datetime("seconds", &TimestampFormat::UnixSeconds)
+        .unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("Invalid timestamp for field 'seconds'"),
+        "{}",
+        error
+    );
+    let error = update
+        .get_value_as_datetime("millis", &TimestampFormat::UnixMillis)
+        .unwrap_err();
+    assert!(error.to_string().contains("out of range"), "{}", error);
+    assert!(update
+        .get_value_as_datetime("custom", &TimestampFormat::Rfc3339)
+        .is_err());
+    assert!(update
+        .get_value_as_datetime(
+            "custom",
+            &TimestampFormat::Custom("%d/%m/%Y %H:%M:%S".to_string())
+        )
+        .is_err());
+}
+
+#[cfg(feature = "chrono")]
+#[test]
+fn times_of_day_are_parsed() {
+    use chrono::NaiveTime;
+
+    let update = update([
+        ("time", Some("10:05:30")),
+        ("date", Some("2024-06-10")),
+        ("empty", None),
+    ]);
+    assert_eq!(
+        update.get_value_as_time("time", "%H:%M:%S").unwrap(),
+        NaiveTime::from_hms_opt(10, 5, 30)
+    );
+    assert_eq!(update.get_value_as_time("empty", "%H:%M:%S").unwrap(), None);
+    let error = update.get_value_as_time("date", "%H:%M:%S").unwrap_err();
+    assert!(
+        error
+            .to_string()
+            .starts_with("Invalid time for field 'date'"),
+        "{}",
+        error
+    );
+}
+
+#[cfg(feature = "decimal")]
+#[test]
+fn decimal_values_are_parsed_exactly() {
+    let update = update([
+        ("price", Some("12.10")),
+        ("padded", Some(" 0.3 ")),
+        ("scientific", Some("1.5e3")),
+        ("negative", Some("-2.5E-2")),
+        ("empty", None),
+    ]);
+    let decimal = |field: &str| {
+        update
+            .get_value_as_decimal(field)
+            .unwrap()
+            .map(|value| value.to_string())
+    };
+    assert_eq!(decimal("price").as_deref(), Some("12.10"));
+    assert_eq!(decimal("padded").as_deref(), Some("0.3"));
+    // Values in scientific notation fall back to their exponent form.
+    assert_eq!(decimal("scientific").as_deref(), Some("1500"));
+    assert_eq!(decimal("negative").as_deref(), Some("-0.025"));
+    assert_eq!(decimal("empty"), None);
+}
+
+#[cfg(feature = "decimal")]
+#[test]
+fn invalid_decimal_values_are_rejected() {
+    let update = update([("price", Some("n/a")), ("exponent", Some("1.5e"))]);
+    for field in ["price", "exponent"] {
+        let error = update.get_value_as_decimal(field).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with(&format!("Invalid decimal for field '{}'", field)),
+            "{}",
+            error
+        );
+    }
+}
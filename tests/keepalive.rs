@@ -0,0 +1,155 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, MAX_KEEPALIVE, TIMEOUT};
+use lightstreamer_client::client_listener::ClientListener;
+use lightstreamer_client::ls_client::{ClientStatus, LightstreamerClient};
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+#[derive(Debug, PartialEq)]
+enum Event {
+    Keepalive,
+    Sync(u64),
+    Status(String),
+}
+
+/// Listener forwarding the liveness events to the test.
+#[derive(Debug)]
+struct LivenessListener(UnboundedSender<Event>);
+
+impl ClientListener for LivenessListener {
+    fn on_server_keepalive(&self) {
+        let _ = self.0.send(Event::Keepalive);
+    }
+
+    fn on_server_sync(&self, seconds: u64) {
+        let _ = self.0.send(Event::Sync(seconds));
+    }
+
+    fn on_status_change(&self, status: &str) {
+        let _ = self.0.send(Event::Status(status.to_string()));
+    }
+}
+
+/// Waits until the client is connected.
+async fn wait_connected(client: &LightstreamerClient) {
+    tokio::time::timeout(TIMEOUT, async {
+        while !matches!(client.get_status(), ClientStatus::Connected(_)) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("client not connected");
+}
+
+#[tokio::test]
+async fn probe_and_sync_are_notified_to_listeners() {
+    let mut server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec!["PROBE".to_string(), "SYNC,42".to_string()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let (sender, mut events) = mpsc::unbounded_channel();
+    client.add_listener(Box::new(LivenessListener(sender)));
+    let subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price"]).unwrap();
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+    server.next_request("control").await;
+
+    let mut expected = vec![Event::Keepalive, Event::Sync(42)];
+    while !expected.is_empty() {
+        let event = tokio::time::timeout(TIMEOUT, events.recv())
+            .await
+            .expect("no event received")
+            .unwrap();
+        if !matches!(event, Event::Status(_)) {
+            assert_eq!(event, expected.remove(0));
+        }
+    }
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn keepalive_interval_notified_by_the_server_is_exposed() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let mut client = server.client();
+    client
+        .connection_options
+        .set_keepalive_interval(60000)
+        .unwrap();
+    client.connect().await.unwrap();
+    server.next_request("create_session").await;
+    wait_connected(&client).await;
+    assert_eq!(
+        client.connection_options.get_keepalive_interval(),
+        MAX_KEEPALIVE
+    );
+
+    client
+        .connection_options
+        .set_keepalive_interval(10000)
+        .unwrap();
+    assert_eq!(client.connection_options.get_keepalive_interval(), 10000);
+    server.next_request("bind_session").await;
+    tokio::time::timeout(TIMEOUT, async {
+        while client.connection_options.get_keepalive_interval() != MAX_KEEPALIVE {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("keepalive interval not notified");
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn silent_connections_stall_then_reconnect() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let mut client = server.client();
+    client
+        .connection_options
+        .set_keepalive_interval(300)
+        .unwrap();
+    client.connection_options.set_stalled_timeout(100).unwrap();
+    client
+        .connection_options
+        .set_reconnect_timeout(200)
+        .unwrap();
+    let (sender, mut events) = mpsc::unbounded_channel();
+    client.add_listener(Box::new(LivenessListener(sender)));
+    client.connect().await.unwrap();
+    server.next_request("create_session").await;
+
+    let statuses = tokio::time::timeout(TIMEOUT, async {
+        let mut statuses = Vec::new();
+        while let Some(event) = events.recv().await {
+            if let Event::Status(status) = event {
+                statuses.push(status);
+                if statuses.ends_with(&["STALLED".to_string()]) {
+                    return statuses;
+                }
+            }
+        }
+        statuses
+    })
+    .await
+    .expect("client not stalled");
+    assert_eq!(
+        statuses,
+        ["CONNECTING", "CONNECTED:WS-STREAMING", "STALLED"]
+    );
+    server.next_request("recover_session").await;
+    wait_connected(&client).await;
+    assert_eq!(client.get_metrics().reconnections, 1);
+
+    client.disconnect().await;
+}
@@ -0,0 +1,204 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{
+    next_update, request_param, subscription_server, MockServer, UpdateForwarder, TIMEOUT,
+};
+use lightstreamer_client::item_update::ItemUpdate;
+use lightstreamer_client::ls_client::LightstreamerClient;
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode, SubscriptionToken};
+use lightstreamer_client::subscription_listener::SubscriptionListener;
+use std::sync::mpsc::Receiver;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+/// Pooled listener blocked on its first update until released, to keep the later updates
+/// pending in the dispatcher.
+struct BlockedListener(Mutex<Option<Receiver<()>>>);
+
+impl SubscriptionListener for BlockedListener {
+    fn on_item_update(&self, _update: &ItemUpdate) {
+        if let Some(gate) = self.0.lock().unwrap().take() {
+            // The runtime worker hands over its queued tasks while blocked.
+            let _ = tokio::task::block_in_place(|| gate.recv());
+        }
+    }
+}
+
+/// Subscribes the client and waits until the given number of updates has been received,
+/// returning the token of the subscription.
+async fn subscribe(
+    client: &LightstreamerClient,
+    subscription: Subscription,
+    updates: u64,
+) -> SubscriptionToken {
+    let token = client.subscribe(subscription);
+    client.connect().await.unwrap();
+    tokio::time::timeout(TIMEOUT, async {
+        while client
+            .with_subscription(token, |subscription| {
+                subscription.get_stats().updates_received
+            })
+            .unwrap()
+            < updates
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("updates not received");
+    token
+}
+
+/// Adds a listener replaying the current state and collects the replayed updates.
+fn add_late_listener(client: &LightstreamerClient, token: SubscriptionToken) -> Vec<ItemUpdate> {
+    let (sender, mut updates) = mpsc::unbounded_channel();
+    client
+        .with_subscription(token, |subscription| {
+            subscription.add_listener_with_replay(Box::new(UpdateForwarder(sender)))
+        })
+        .unwrap();
+    let mut replayed = Vec::new();
+    while let Ok(update) = updates.try_recv() {
+        replayed.push(update);
+    }
+    replayed
+}
+
+#[tokio::test]
+async fn current_values_are_replayed_to_late_listeners() {
+    let server = subscription_server(
+        "SUBOK,1,2,2",
+        &["U,1,2,ACME|10", "U,1,1,FOO|5", "U,1,2,|11"],
+    )
+    .await;
+    let client = server.client();
+    let subscription = Subscription::new(
+        SubscriptionMode::Merge,
+        Some(["item1", "item2"]),
+        Some(["name", "price"]),
+    )
+    .unwrap();
+    let token = subscribe(&client, subscription, 3).await;
+
+    let replayed = add_late_listener(&client, token);
+    assert_eq!(replayed.len(), 2);
+    assert_eq!(replayed[0].get_item_name(), Some("item1"));
+    assert_eq!(replayed[0].get_value("price"), Some("5"));
+    assert_eq!(replayed[1].get_item_name(), Some("item2"));
+    assert_eq!(replayed[1].get_value("name"), Some("ACME"));
+    assert_eq!(replayed[1].get_value("price"), Some("11"));
+    assert!(replayed.iter().all(ItemUpdate::is_snapshot));
+    assert!(replayed[1].is_value_changed("name"));
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn command_keys_are_replayed_as_additions() {
+    let server = subscription_server(
+        "SUBOK,1,1,3",
+        &[
+            "U,1,1,k1|ADD|10",
+            "U,1,1,k2||20",
+            "U,1,1,k1|UPDATE|11",
+            "U,1,1,k3|ADD|30",
+            "U,1,1,|DELETE|",
+        ],
+    )
+    .await;
+    let client = server.client();
+    let subscription = Subscription::new_single_item(
+        SubscriptionMode::Command,
+        "item1",
+        ["key", "command", "price"],
+    )
+    .unwrap();
+    let token = subscribe(&client, subscription, 5).await;
+
+    let replayed = add_late_listener(&client, token);
+    let rows: Vec<_> = replayed
+        .iter()
+        .map(|update| {
+            (
+                update.get_value("key").unwrap(),
+                update.get_value("command").unwrap(),
+                update.get_value("price").unwrap(),
+            )
+        })
+        .collect();
+    assert_eq!(rows, [("k1", "ADD", "11"), ("k2", "ADD", "20")]);
+
+    client.disconnect().await;
+}
+
+#[test]
+fn nothing_is_replayed_before_the_first_update() {
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["price"]).unwrap();
+    let (sender, mut updates) = mpsc::unbounded_channel();
+    subscription.add_listener_with_replay(Box::new(UpdateForwarder(sender)));
+    assert!(updates.try_recv().is_err());
+    assert_eq!(subscription.get_listeners().len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn pending_updates_are_not_delivered_again_after_the_replay() {
+    // Each message makes the server send an update carrying the message as price, in a frame
+    // of its own.
+    let mut server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec!["SUBOK,1,1,1".to_string(), "U,1,1,10".to_string()]
+        } else if request.starts_with("msg") {
+            let price = request_param(request, "LS_message").unwrap();
+            vec![format!("U,1,1,{}", price)]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let (release, gate) = std::sync::mpsc::channel();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["price"]).unwrap();
+    subscription.add_pooled_listener(Arc::new(BlockedListener(Mutex::new(Some(gate)))));
+    let token = client.subscribe(subscription);
+    client.connect().await.unwrap();
+    server.next_request("control").await;
+    for price in ["11", "12"] {
+        client.send_message(price, None, None, None, false).unwrap();
+        server.next_request("msg").await;
+    }
+    tokio::time::timeout(TIMEOUT, async {
+        while client
+            .with_subscription(token, |subscription| {
+                subscription.get_stats().updates_received
+            })
+            .unwrap()
+            < 3
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("updates not received");
+
+    // The updates to 11 and 12 are still pending behind the blocked listener.
+    let (sender, mut updates) = mpsc::unbounded_channel();
+    client
+        .with_subscription(token, |subscription| {
+            subscription.add_listener_with_replay(Box::new(UpdateForwarder(sender)))
+        })
+        .unwrap();
+    let replayed = updates.try_recv().unwrap();
+    assert_eq!(replayed.get_value("price"), Some("12"));
+    release.send(()).unwrap();
+    client.send_message("13", None, None, None, false).unwrap();
+
+    let update = next_update(&mut updates).await;
+    assert_eq!(update.get_value("price"), Some("13"));
+
+    client.disconnect().await;
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{next_update, MockServer, UpdateForwarder};
+use lightstreamer_client::item_update::ItemUpdate;
+use lightstreamer_client::ls_client::LightstreamerClient;
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode, SubscriptionToken};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+/// Gets the current time in milliseconds since the Unix epoch, as set by the mock server.
+fn now_millis() -> u128 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_millis()
+}
+
+/// Subscribes to "item1" with the "price" and "timestamp" fields, measuring the latency on the
+/// latter. Returns the token of the subscription and a channel receiving its updates.
+fn subscribe(client: &LightstreamerClient) -> (SubscriptionToken, UnboundedReceiver<ItemUpdate>) {
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["price", "timestamp"])
+            .unwrap();
+    subscription.set_latency_field(Some("timestamp".to_string()));
+    let (sender, updates) = mpsc::unbounded_channel();
+    subscription.add_listener(Box::new(UpdateForwarder(sender)));
+    (client.subscribe(subscription), updates)
+}
+
+#[test]
+fn latency_is_not_measured_by_default() {
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["price"]).unwrap();
+    assert_eq!(subscription.get_latency_field(), None);
+    assert_eq!(subscription.get_latency_stats(), None);
+    subscription.set_latency_field(Some("2".to_string()));
+    assert_eq!(
+        subscription.get_latency_field().map(String::as_str),
+        Some("2")
+    );
+}
+
+#[tokio::test]
+async fn latency_is_measured_on_the_updates_changing_the_timestamp() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            let sent = now_millis();
+            vec![
+                "SUBOK,1,1,2".to_string(),
+                format!("U,1,1,10|{}", sent - 300),
+                // The timestamp is unchanged: no measurement.
+                "U,1,1,11|".to_string(),
+                format!("U,1,1,12|{}", sent - 100),
+            ]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let (token, mut updates) = subscribe(&client);
+    client.connect().await.unwrap();
+    for _ in 0..3 {
+        next_update(&mut updates).await;
+    }
+
+    let stats = client
+        .with_subscription(token, |subscription| subscription.get_latency_stats())
+        .unwrap()
+        .unwrap();
+    assert_eq!(stats.samples, 2);
+    assert!(stats.p50 >= Duration::from_millis(100));
+    assert!(stats.max >= Duration::from_millis(300));
+    assert!(stats.max < Duration::from_secs(3));
+    assert_eq!(stats.p99, stats.max);
+    assert!(stats.p50 <= stats.p90 && stats.p90 <= stats.p99);
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn invalid_timestamps_are_not_measured() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec!["SUBOK,1,1,2".to_string(), "U,1,1,10|yesterday".to_string()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let (token, mut updates) = subscribe(&client);
+    client.connect().await.unwrap();
+    next_update(&mut updates).await;
+
+    assert_eq!(
+        client
+            .with_subscription(token, |subscription| subscription.get_latency_stats())
+            .unwrap(),
+        None
+    );
+
+    client.disconnect().await;
+}
@@ -0,0 +1,90 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::client_listener::ClientListener;
+use lightstreamer_client::item_update::ItemUpdate;
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use lightstreamer_client::subscription_listener::SubscriptionListener;
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+/// Listener panicking on every update.
+struct PanickingListener;
+
+impl SubscriptionListener for PanickingListener {
+    fn on_item_update(&self, _update: &ItemUpdate) {
+        panic!("faulty listener");
+    }
+}
+
+/// Listener forwarding the received prices to the test.
+struct PriceListener(UnboundedSender<String>);
+
+impl SubscriptionListener for PriceListener {
+    fn on_item_update(&self, update: &ItemUpdate) {
+        let _ = self
+            .0
+            .send(update.get_value("price").unwrap_or_default().to_string());
+    }
+}
+
+#[tokio::test]
+async fn panicking_listener_does_not_stop_dispatching() {
+    let mut server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec![
+                "SUBOK,1,1,1".to_string(),
+                "U,1,1,10".to_string(),
+                "U,1,1,11".to_string(),
+            ]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price"]).unwrap();
+    let (sender, mut prices) = mpsc::unbounded_channel();
+    subscription.add_listener(Box::new(PanickingListener));
+    subscription.add_listener(Box::new(PriceListener(sender)));
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+    server.next_request("control").await;
+
+    for expected in ["10", "11"] {
+        let price = tokio::time::timeout(TIMEOUT, prices.recv())
+            .await
+            .expect("no update received");
+        assert_eq!(price.as_deref(), Some(expected));
+    }
+
+    client.disconnect().await;
+}
+
+#[test]
+fn default_event_handlers_do_nothing() {
+    #[derive(Debug)]
+    struct Silent;
+
+    impl SubscriptionListener for Silent {}
+    impl ClientListener for Silent {}
+
+    let update = ItemUpdate::new(Some("item"), 1, [("price", Some("10"))], false);
+    let mut listener = Silent;
+    listener.on_clear_snapshot(Some("item"), 1);
+    listener.on_command_second_level_item_lost_updates(1, "key");
+    listener.on_command_second_level_subscription_error(21, None, "key");
+    listener.on_end_of_snapshot(Some("item"), 1);
+    listener.on_item_lost_updates(Some("item"), 1, 1);
+    listener.on_item_update(&update);
+    listener.on_item_updates(std::slice::from_ref(&update));
+    listener.on_real_max_frequency(Some(1.0));
+    listener.on_subscription();
+    listener.on_subscription_error(21, None);
+    listener.on_unsubscription();
+    ClientListener::on_property_change(&listener, "forcedTransport");
+    ClientListener::on_status_change(&listener, "CONNECTING");
+    ClientListener::on_server_error(&listener, 1, "error");
+}
@@ -0,0 +1,70 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::client_listener::ClientListener;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Listener recording the status changes of the client and the end of its listening.
+#[derive(Debug)]
+struct EventRecorder(Arc<Mutex<Vec<String>>>);
+
+impl ClientListener for EventRecorder {
+    fn on_status_change(&self, status: &str) {
+        self.0.lock().unwrap().push(status.to_string());
+    }
+
+    fn on_listen_end(&self) {
+        self.0.lock().unwrap().push("END".to_string());
+    }
+}
+
+/// Waits until the given condition holds.
+async fn wait_until(mut condition: impl FnMut() -> bool) {
+    tokio::time::timeout(TIMEOUT, async {
+        while !condition() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("condition not met");
+}
+
+#[tokio::test]
+async fn removed_listeners_receive_a_last_listen_end_event() {
+    let server = MockServer::start(|_| Vec::new()).await;
+    let client = server.client();
+    let removed = Arc::new(Mutex::new(Vec::new()));
+    let kept = Arc::new(Mutex::new(Vec::new()));
+    let last = Arc::new(Mutex::new(Vec::new()));
+    let removed_token = client.add_listener(Box::new(EventRecorder(Arc::clone(&removed))));
+    client.add_listener(Box::new(EventRecorder(Arc::clone(&kept))));
+    let last_token = client.add_listener(Box::new(EventRecorder(Arc::clone(&last))));
+    client.connect().await.unwrap();
+    wait_until(|| {
+        removed
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|status| status.starts_with("CONNECTED:"))
+    })
+    .await;
+
+    client.remove_listener(removed_token).unwrap();
+    assert!(client.remove_listener(removed_token).is_err());
+    // Removing a listener doesn't change the tokens of the ones added after it.
+    client.remove_listener(last_token).unwrap();
+    assert_eq!(client.get_listeners().len(), 1);
+    wait_until(|| removed.lock().unwrap().last().map(String::as_str) == Some("END")).await;
+    wait_until(|| last.lock().unwrap().last().map(String::as_str) == Some("END")).await;
+    client.disconnect().await;
+
+    wait_until(|| kept.lock().unwrap().last().map(String::as_str) == Some("DISCONNECTED")).await;
+    assert_eq!(
+        removed.lock().unwrap().last().map(String::as_str),
+        Some("END")
+    );
+    assert!(!kept.lock().unwrap().contains(&"END".to_string()));
+}
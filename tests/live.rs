@@ -0,0 +1,204 @@
+//! Integration tests against a real Lightstreamer Server running the DEMO adapter set, by default
+//! the public demo server at push.lightstreamer.com.
+//!
+//! They need network access, so they are ignored unless explicitly requested:
+//!
+//! ```sh
+//! cargo test --features test-util --test live -- --ignored
+//! ```
+//!
+//! Another server can be targeted through the `LS_LIVE_SERVER` environment variable.
+
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{next_update_within, UpdateForwarder};
+use lightstreamer_client::client_message_listener::ClientMessageListener;
+use lightstreamer_client::item_update::ItemUpdate;
+use lightstreamer_client::ls_client::{LightstreamerClient, Transport};
+use lightstreamer_client::subscription::{Snapshot, Subscription, SubscriptionMode};
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// Maximum time the tests wait for an event from the server.
+const TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Address of the public demo server.
+const DEFAULT_SERVER: &str = "https://push.lightstreamer.com/";
+
+/// Creates a client configured to connect to the live server.
+fn client() -> LightstreamerClient {
+    let server = std::env::var("LS_LIVE_SERVER").unwrap_or_else(|_| DEFAULT_SERVER.to_string());
+    let mut client = LightstreamerClient::new(Some(&server), Some("DEMO"), None, None).unwrap();
+    client
+        .connection_options
+        .set_forced_transport(Some(Transport::WsStreaming));
+    client
+}
+
+/// Subscribes the client to the given items and fields of a demo data adapter.
+fn subscribe(
+    client: &mut LightstreamerClient,
+    mode: SubscriptionMode,
+    data_adapter: &str,
+    items: &[&str],
+    fields: &[&str],
+) -> UnboundedReceiver<ItemUpdate> {
+    let (sender, updates) = mpsc::unbounded_channel();
+    let mut subscription = Subscription::new(mode, Some(items), Some(fields)).unwrap();
+    subscription
+        .set_data_adapter(Some(data_adapter.to_string()))
+        .unwrap();
+    subscription
+        .set_requested_snapshot(Some(Snapshot::Yes))
+        .unwrap();
+    subscription.add_listener(Box::new(UpdateForwarder(sender)));
+    client.subscribe(subscription);
+    updates
+}
+
+/// Waits for the client to report the given status.
+async fn wait_for_status(client: &LightstreamerClient, status: &str) {
+    tokio::time::timeout(TIMEOUT, async {
+        while client.get_status().to_string() != status {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("status {} not reached", status));
+}
+
+/// Subscribes to the stock quotes of the stock-list demo.
+fn subscribe_quotes(client: &mut LightstreamerClient) -> UnboundedReceiver<ItemUpdate> {
+    subscribe(
+        client,
+        SubscriptionMode::Merge,
+        "QUOTE_ADAPTER",
+        &["item1", "item2"],
+        &["stock_name", "last_price", "time"],
+    )
+}
+
+#[tokio::test]
+#[ignore = "needs a live Lightstreamer server"]
+async fn connect_and_disconnect() {
+    let client = client();
+    client.connect().await.unwrap();
+    wait_for_status(&client, "CONNECTED:WS-STREAMING").await;
+    client.disconnect().await;
+    assert_eq!(client.get_status().to_string(), "DISCONNECTED");
+}
+
+#[tokio::test]
+#[ignore = "needs a live Lightstreamer server"]
+async fn merge_subscription_receives_quotes() {
+    let mut client = client();
+    let mut updates = subscribe_quotes(&mut client);
+    client.connect().await.unwrap();
+
+    let update = next_update_within(&mut updates, TIMEOUT).await;
+    assert!(update.is_snapshot());
+    assert!(update.get_value("stock_name").is_some());
+    assert!(update
+        .get_value("last_price")
+        .is_some_and(|price| price.parse::<f64>().is_ok()));
+    client.disconnect().await;
+}
+
+#[tokio::test]
+#[ignore = "needs a live Lightstreamer server"]
+async fn command_subscription_receives_portfolio() {
+    let mut client = client();
+    let mut updates = subscribe(
+        &mut client,
+        SubscriptionMode::Command,
+        "PORTFOLIO_ADAPTER",
+        &["portfolio1"],
+        &["key", "command", "qty"],
+    );
+    client.connect().await.unwrap();
+
+    let update = next_update_within(&mut updates, TIMEOUT).await;
+    assert!(update.get_value("key").is_some());
+    assert!(matches!(
+        update.get_value("command"),
+        Some("ADD" | "UPDATE" | "DELETE")
+    ));
+    client.disconnect().await;
+}
+
+#[derive(Debug)]
+struct OutcomeListener(UnboundedSender<&'static str>);
+
+impl ClientMessageListener for OutcomeListener {
+    fn on_abort(&self, _msg: &str, _sent_on_network: bool) {
+        let _ = self.0.send("abort");
+    }
+
+    fn on_deny(&self, _msg: &str, _code: i32, _error: &str) {
+        let _ = self.0.send("deny");
+    }
+
+    fn on_discarded(&self, _msg: &str) {
+        let _ = self.0.send("discarded");
+    }
+
+    fn on_error(&self, _msg: &str) {
+        let _ = self.0.send("error");
+    }
+
+    fn on_processed(&self, _msg: &str, _response: Option<&str>) {
+        let _ = self.0.send("processed");
+    }
+}
+
+#[tokio::test]
+#[ignore = "needs a live Lightstreamer server"]
+async fn message_is_processed() {
+    let client = client();
+    client.connect().await.unwrap();
+    wait_for_status(&client, "CONNECTED:WS-STREAMING").await;
+
+    // An order of the portfolio demo, handled by the Metadata Adapter of the DEMO adapter set.
+    let (sender, mut outcomes) = mpsc::unbounded_channel();
+    client
+        .send_message(
+            "BUY|portfolio1|item1|1",
+            None,
+            None,
+            Some(Box::new(OutcomeListener(sender))),
+            false,
+        )
+        .unwrap();
+    let outcome = tokio::time::timeout(TIMEOUT, outcomes.recv())
+        .await
+        .expect("no message outcome received");
+    assert_eq!(outcome, Some("processed"));
+    client.disconnect().await;
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+#[ignore = "needs a live Lightstreamer server"]
+async fn dropped_connection_is_recovered() {
+    use lightstreamer_client::fault_injection::FaultInjector;
+    use std::sync::Arc;
+
+    let injector = Arc::new(FaultInjector::new());
+    let mut client = client();
+    client
+        .connection_options
+        .set_fault_injector(Some(Arc::clone(&injector)));
+    let mut updates = subscribe_quotes(&mut client);
+    client.connect().await.unwrap();
+    next_update_within(&mut updates, TIMEOUT).await;
+
+    injector.drop_connection();
+    wait_for_status(&client, "DISCONNECTED:TRYING-RECOVERY").await;
+    wait_for_status(&client, "CONNECTED:WS-STREAMING").await;
+    // Updates keep flowing on the recovered session.
+    while updates.try_recv().is_ok() {}
+    next_update_within(&mut updates, TIMEOUT).await;
+    client.disconnect().await;
+}
@@ -0,0 +1,127 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{request_param, MockServer, TIMEOUT};
+use lightstreamer_client::ls_client::{ClientStatus, LightstreamerClient};
+use std::time::Duration;
+
+/// Waits until the client is connected.
+async fn wait_connected(client: &LightstreamerClient) {
+    tokio::time::timeout(TIMEOUT, async {
+        while !matches!(client.get_status(), ClientStatus::Connected(_)) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("client not connected");
+}
+
+#[tokio::test]
+async fn stream_settings_are_sent_on_session_creation() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let mut client = server.client();
+    client.connection_options.set_retry_delay(100).unwrap();
+    client
+        .connection_options
+        .set_keepalive_interval(5000)
+        .unwrap();
+    client
+        .connection_options
+        .set_reverse_heartbeat_interval(6000)
+        .unwrap();
+    client
+        .connection_options
+        .set_requested_max_bandwidth(Some(40.5))
+        .unwrap();
+    client.connect().await.unwrap();
+
+    let request = server.next_request("create_session").await;
+    assert_eq!(request_param(&request, "LS_keepalive_millis"), Some("5000"));
+    assert_eq!(
+        request_param(&request, "LS_inactivity_millis"),
+        Some("6000")
+    );
+    assert_eq!(
+        request_param(&request, "LS_requested_max_bandwidth"),
+        Some("40.5")
+    );
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn bandwidth_changes_are_applied_on_the_fly() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let mut client = server.client();
+    client.connect().await.unwrap();
+    let request = server.next_request("create_session").await;
+    assert_eq!(request_param(&request, "LS_requested_max_bandwidth"), None);
+    wait_connected(&client).await;
+
+    client
+        .connection_options
+        .set_requested_max_bandwidth(Some(20.0))
+        .unwrap();
+    let request = server.next_request("control").await;
+    assert_eq!(request_param(&request, "LS_op"), Some("constrain"));
+    assert_eq!(
+        request_param(&request, "LS_requested_max_bandwidth"),
+        Some("20")
+    );
+
+    client
+        .connection_options
+        .set_requested_max_bandwidth(None)
+        .unwrap();
+    let request = server.next_request("control").await;
+    assert_eq!(
+        request_param(&request, "LS_requested_max_bandwidth"),
+        Some("unlimited")
+    );
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn reverse_heartbeats_start_on_the_fly() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let mut client = server.client();
+    client.connection_options.set_retry_delay(100).unwrap();
+    client.connect().await.unwrap();
+    server.next_request("create_session").await;
+    wait_connected(&client).await;
+
+    client
+        .connection_options
+        .set_reverse_heartbeat_interval(100)
+        .unwrap();
+    server.next_request("heartbeat").await;
+    server.next_request("heartbeat").await;
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn keepalive_changes_rebind_the_session() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let mut client = server.client();
+    client.connect().await.unwrap();
+    server.next_request("create_session").await;
+    wait_connected(&client).await;
+
+    client
+        .connection_options
+        .set_keepalive_interval(10000)
+        .unwrap();
+    let request = server.next_request("bind_session").await;
+    assert_eq!(request_param(&request, "LS_session"), Some("S1"));
+    assert_eq!(
+        request_param(&request, "LS_keepalive_millis"),
+        Some("10000")
+    );
+    wait_connected(&client).await;
+    assert_eq!(client.get_metrics().reconnections, 0);
+
+    client.disconnect().await;
+}
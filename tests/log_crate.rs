@@ -0,0 +1,77 @@
+#![cfg(all(feature = "runtime-tokio", feature = "log"))]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use log::{Level, LevelFilter, Log, Metadata, Record};
+use std::sync::Mutex;
+use tokio::sync::mpsc;
+
+/// `log` records received by `RecordingLog`, as target, level and message.
+static RECORDS: Mutex<Vec<(String, Level, String)>> = Mutex::new(Vec::new());
+
+/// Logger of the `log` crate recording everything it receives.
+struct RecordingLog;
+
+impl Log for RecordingLog {
+    fn enabled(&self, _metadata: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        RECORDS.lock().unwrap().push((
+            record.target().to_string(),
+            record.level(),
+            record.args().to_string(),
+        ));
+    }
+
+    fn flush(&self) {}
+}
+
+#[tokio::test]
+async fn diagnostics_are_log_records_by_default() {
+    log::set_logger(&RecordingLog).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec!["SUBOK,1,1,1".to_string(), "U,1,1,10".to_string()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["price"]).unwrap();
+    let (sender, mut updates) = mpsc::unbounded_channel();
+    subscription.on_update(move |_| {
+        let _ = sender.send(());
+    });
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+    tokio::time::timeout(TIMEOUT, updates.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    client.disconnect().await;
+
+    // The target of each record is the category of the message.
+    let records = RECORDS.lock().unwrap();
+    let logged = |target: &str, level: Level, prefix: &str| {
+        records
+            .iter()
+            .any(|record| record.0 == target && record.1 == level && record.2.starts_with(prefix))
+    };
+    assert!(logged(
+        "lightstreamer.connections",
+        Level::Debug,
+        "Session created with ID"
+    ));
+    assert!(logged(
+        "lightstreamer.subscriptions",
+        Level::Info,
+        "Sent subscription request"
+    ));
+}
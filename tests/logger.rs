@@ -0,0 +1,121 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::logger::{LogCategory, Logger, LoggerProvider};
+use lightstreamer_client::ls_client::LightstreamerClient;
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+use tracing::Level;
+
+/// A message received by a `RecordingLogger`.
+type Record = (LogCategory, Level, String);
+
+/// Provider of loggers recording the messages up to a threshold level per category.
+#[derive(Default)]
+struct RecordingProvider {
+    records: Arc<Mutex<Vec<Record>>>,
+    requested_loggers: AtomicUsize,
+}
+
+impl LoggerProvider for RecordingProvider {
+    fn get_logger(&self, category: LogCategory) -> Arc<dyn Logger> {
+        self.requested_loggers.fetch_add(1, Ordering::SeqCst);
+        Arc::new(RecordingLogger {
+            category,
+            level: threshold(category),
+            records: Arc::clone(&self.records),
+        })
+    }
+}
+
+struct RecordingLogger {
+    category: LogCategory,
+    level: Level,
+    records: Arc<Mutex<Vec<Record>>>,
+}
+
+impl Logger for RecordingLogger {
+    fn log(&self, level: Level, message: &str) {
+        self.records
+            .lock()
+            .unwrap()
+            .push((self.category, level, message.to_string()));
+    }
+
+    fn is_enabled(&self, level: Level) -> bool {
+        level <= self.level
+    }
+}
+
+/// The level up to which the messages of a category are recorded.
+fn threshold(category: LogCategory) -> Level {
+    match category {
+        LogCategory::Connections => Level::DEBUG,
+        LogCategory::Subscriptions => Level::INFO,
+        LogCategory::Protocol | LogCategory::Messages => Level::ERROR,
+    }
+}
+
+// The provider is process-wide, so its whole lifecycle is tested at once.
+#[tokio::test]
+async fn diagnostics_are_routed_to_the_logger_of_their_category() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec!["SUBOK,1,1,1".to_string(), "U,1,1,10".to_string()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let provider = Arc::new(RecordingProvider::default());
+    let records = Arc::clone(&provider.records);
+    LightstreamerClient::set_logger_provider(Some(provider.clone()));
+    assert_eq!(provider.requested_loggers.load(Ordering::SeqCst), 4);
+
+    let client = server.client();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["price"]).unwrap();
+    let (sender, mut updates) = mpsc::unbounded_channel();
+    subscription.on_update(move |_| {
+        let _ = sender.send(());
+    });
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+    tokio::time::timeout(TIMEOUT, updates.recv())
+        .await
+        .unwrap()
+        .unwrap();
+
+    {
+        let records = records.lock().unwrap();
+        let logged = |category: LogCategory, level: Level, prefix: &str| {
+            records.iter().any(|record| {
+                record.0 == category && record.1 == level && record.2.starts_with(prefix)
+            })
+        };
+        assert!(logged(
+            LogCategory::Connections,
+            Level::DEBUG,
+            "Session created with ID"
+        ));
+        assert!(logged(
+            LogCategory::Subscriptions,
+            Level::INFO,
+            "Sent subscription request"
+        ));
+        // Messages above the threshold of their category are not passed to the logger.
+        for (category, level, message) in records.iter() {
+            assert!(*level <= threshold(*category), "{:?} {}", category, message);
+        }
+    }
+
+    LightstreamerClient::set_logger_provider(None);
+    let count = records.lock().unwrap().len();
+    client.disconnect().await;
+    assert_eq!(records.lock().unwrap().len(), count);
+    assert_eq!(provider.requested_loggers.load(Ordering::SeqCst), 4);
+}
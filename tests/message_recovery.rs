@@ -0,0 +1,170 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{drop_connection, request_param, MockServer, TIMEOUT};
+use lightstreamer_client::client_message_listener::ClientMessageListener;
+use lightstreamer_client::connection_options::MessageRecoveryPolicy;
+use lightstreamer_client::ls_client::{ClientStatus, LightstreamerClient};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// Outcome of a message notified to its listener.
+#[derive(Debug, PartialEq)]
+enum Outcome {
+    Processed(String),
+    Aborted(String, bool),
+}
+
+/// Listener forwarding the outcomes of the messages to the test.
+#[derive(Debug)]
+struct OutcomeForwarder(UnboundedSender<Outcome>);
+
+impl ClientMessageListener for OutcomeForwarder {
+    fn on_abort(&self, message: &str, sent_on_network: bool) {
+        let _ = self
+            .0
+            .send(Outcome::Aborted(message.to_string(), sent_on_network));
+    }
+
+    fn on_processed(&self, message: &str, _response: Option<&str>) {
+        let _ = self.0.send(Outcome::Processed(message.to_string()));
+    }
+}
+
+/// Starts a mock server that drops the connection on the first message request, without
+/// acknowledging it, and processes the following ones.
+async fn start_server() -> MockServer {
+    let dropped = AtomicBool::new(false);
+    MockServer::start(move |request| {
+        if !request.starts_with("msg") {
+            Vec::new()
+        } else if !dropped.swap(true, Ordering::SeqCst) {
+            vec![drop_connection()]
+        } else {
+            let request_id = request_param(request, "LS_reqId").unwrap();
+            let sequence = request_param(request, "LS_sequence").unwrap();
+            let prog = request_param(request, "LS_msg_prog").unwrap();
+            vec![
+                format!("REQOK,{}", request_id),
+                format!("MSGDONE,{},{},", sequence, prog),
+            ]
+        }
+    })
+    .await
+}
+
+/// Connects a client with the given message recovery policy and sends a message once connected.
+async fn send_after_connecting(
+    server: &mut MockServer,
+    policy: MessageRecoveryPolicy,
+    session_recovery_timeout: u64,
+) -> (LightstreamerClient, UnboundedReceiver<Outcome>) {
+    let mut client = server.client();
+    client.connection_options.set_retry_delay(100).unwrap();
+    client
+        .connection_options
+        .set_session_recovery_timeout(session_recovery_timeout)
+        .unwrap();
+    client
+        .connection_options
+        .set_message_recovery_policy(policy);
+    client.connect().await.unwrap();
+    server.next_request("create_session").await;
+    tokio::time::timeout(TIMEOUT, async {
+        while !matches!(client.get_status(), ClientStatus::Connected(_)) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("client not connected");
+
+    let (sender, outcomes) = mpsc::unbounded_channel();
+    client
+        .send_message(
+            "hello",
+            Some("seq"),
+            None,
+            Some(Box::new(OutcomeForwarder(sender))),
+            false,
+        )
+        .unwrap();
+    let request = server.next_request("msg").await;
+    assert_eq!(request_param(&request, "LS_msg_prog"), Some("1"));
+    (client, outcomes)
+}
+
+async fn next_outcome(outcomes: &mut UnboundedReceiver<Outcome>) -> Outcome {
+    tokio::time::timeout(TIMEOUT, outcomes.recv())
+        .await
+        .expect("no outcome notified")
+        .expect("listener dropped")
+}
+
+#[tokio::test]
+async fn unacknowledged_messages_are_resent_on_recovery() {
+    let mut server = start_server().await;
+    let (client, mut outcomes) =
+        send_after_connecting(&mut server, MessageRecoveryPolicy::ResendOnRecovery, 15000).await;
+
+    server.next_request("recover_session").await;
+    let request = server.next_request("msg").await;
+    assert_eq!(request_param(&request, "LS_message"), Some("hello"));
+    assert_eq!(request_param(&request, "LS_sequence"), Some("seq"));
+    assert_eq!(request_param(&request, "LS_msg_prog"), Some("1"));
+    assert_eq!(
+        next_outcome(&mut outcomes).await,
+        Outcome::Processed("hello".to_string())
+    );
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn unacknowledged_messages_are_aborted_on_a_new_session() {
+    let mut server = start_server().await;
+    let (client, mut outcomes) =
+        send_after_connecting(&mut server, MessageRecoveryPolicy::ResendOnRecovery, 0).await;
+
+    server.next_request("create_session").await;
+    assert_eq!(
+        next_outcome(&mut outcomes).await,
+        Outcome::Aborted("hello".to_string(), true)
+    );
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn unacknowledged_messages_can_be_resent_on_a_new_session() {
+    let mut server = start_server().await;
+    let (client, mut outcomes) =
+        send_after_connecting(&mut server, MessageRecoveryPolicy::ResendAlways, 0).await;
+
+    server.next_request("create_session").await;
+    let request = server.next_request("msg").await;
+    assert_eq!(request_param(&request, "LS_message"), Some("hello"));
+    assert_eq!(request_param(&request, "LS_msg_prog"), Some("1"));
+    assert_eq!(
+        next_outcome(&mut outcomes).await,
+        Outcome::Processed("hello".to_string())
+    );
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn messages_can_be_aborted_on_disconnection() {
+    let mut server = start_server().await;
+    let (client, mut outcomes) =
+        send_after_connecting(&mut server, MessageRecoveryPolicy::AbortOnDisconnect, 15000).await;
+
+    assert_eq!(
+        next_outcome(&mut outcomes).await,
+        Outcome::Aborted("hello".to_string(), true)
+    );
+    server.next_request("recover_session").await;
+
+    client.disconnect().await;
+}
@@ -0,0 +1,158 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::client_listener::ClientListener;
+use lightstreamer_client::connection_options::ConnectionOptions;
+use lightstreamer_client::ls_client::{ClientStatus, DisconnectionType};
+use lightstreamer_client::retry_policy::RetryPolicy;
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use std::error::Error;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+/// Listener forwarding the connection errors to the test.
+#[derive(Debug)]
+struct ErrorForwarder(UnboundedSender<String>);
+
+impl ClientListener for ErrorForwarder {
+    fn on_connection_error(&self, message: &str) {
+        let _ = self.0.send(message.to_string());
+    }
+}
+
+#[test]
+fn size_limits_default_to_the_websocket_ones() {
+    let mut options = ConnectionOptions::new();
+    assert_eq!(options.get_max_frame_size(), None);
+    assert_eq!(options.get_max_message_size(), None);
+    assert!(options.set_max_frame_size(Some(0)).is_err());
+    assert!(options.set_max_message_size(Some(0)).is_err());
+    options.set_max_frame_size(Some(512)).unwrap();
+    options.set_max_message_size(Some(4096)).unwrap();
+    assert_eq!(options.get_max_frame_size(), Some(512));
+    assert_eq!(options.get_max_message_size(), Some(4096));
+    options.set_max_message_size(None).unwrap();
+    assert_eq!(options.get_max_message_size(), None);
+}
+
+/// Retry policy recording the attempts it is consulted for, giving up after the given number.
+#[derive(Debug)]
+struct RecordingPolicy {
+    attempts: Mutex<Vec<u32>>,
+    max_attempts: u32,
+}
+
+impl RetryPolicy for RecordingPolicy {
+    fn next_delay(
+        &self,
+        attempt: u32,
+        _error: &(dyn Error + Send + Sync + 'static),
+    ) -> Option<Duration> {
+        self.attempts.lock().unwrap().push(attempt);
+        (attempt < self.max_attempts).then_some(Duration::from_millis(10))
+    }
+}
+
+/// Checks that an update larger than the limits set by `configure` is reported and that the
+/// session is bound to a new connection, without counting as a reconnection.
+async fn assert_oversized_updates_rebind(configure: impl FnOnce(&mut ConnectionOptions)) {
+    let mut server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec![
+                "SUBOK,1,1,1".to_string(),
+                format!("U,1,1,{}", "x".repeat(2048)),
+            ]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let mut client = server.client();
+    configure(&mut client.connection_options);
+    let (sender, mut errors) = mpsc::unbounded_channel();
+    client.add_listener(Box::new(ErrorForwarder(sender)));
+    let subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["field1"]).unwrap();
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+    server.next_request("create_session").await;
+
+    let error = tokio::time::timeout(TIMEOUT, errors.recv())
+        .await
+        .expect("no connection error notified")
+        .unwrap();
+    assert!(error.contains("too large"), "{}", error);
+    server.next_request("bind_session").await;
+    assert_eq!(client.get_metrics().reconnections, 0);
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn oversized_messages_are_reported_and_the_session_rebound() {
+    assert_oversized_updates_rebind(|options| {
+        options.set_max_message_size(Some(1024)).unwrap();
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn oversized_frames_are_reported_and_the_session_rebound() {
+    assert_oversized_updates_rebind(|options| {
+        options.set_max_frame_size(Some(1024)).unwrap();
+    })
+    .await;
+}
+
+#[tokio::test]
+async fn repeated_oversized_messages_back_off_until_the_policy_gives_up() {
+    let mut server = MockServer::start(|request| {
+        if request.contains("LS_op=add") || request.starts_with("bind_session") {
+            vec![format!("U,1,1,{}", "x".repeat(2048))]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let mut client = server.client();
+    client
+        .connection_options
+        .set_max_message_size(Some(1024))
+        .unwrap();
+    let policy = Arc::new(RecordingPolicy {
+        attempts: Mutex::new(Vec::new()),
+        max_attempts: 2,
+    });
+    client
+        .connection_options
+        .set_retry_policy(Some(Arc::clone(&policy) as Arc<dyn RetryPolicy>));
+    let (sender, mut errors) = mpsc::unbounded_channel();
+    client.add_listener(Box::new(ErrorForwarder(sender)));
+    let subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["field1"]).unwrap();
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+    server.next_request("create_session").await;
+
+    // Every connection receives the oversized message again: the rebinds are delayed as growing
+    // attempts, until the policy gives up.
+    for _ in 0..3 {
+        let error = tokio::time::timeout(TIMEOUT, errors.recv())
+            .await
+            .expect("no connection error notified")
+            .unwrap();
+        assert!(error.contains("too large"), "{}", error);
+    }
+    tokio::time::timeout(TIMEOUT, async {
+        while client.get_status() != ClientStatus::Disconnected(DisconnectionType::None) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("the client kept rebinding");
+    assert_eq!(*policy.attempts.lock().unwrap(), [0, 1, 2]);
+    assert_eq!(client.get_metrics().reconnections, 0);
+}
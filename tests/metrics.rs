@@ -0,0 +1,64 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::ls_client::{ClientStatus, ConnectionType};
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+
+#[tokio::test]
+async fn metrics_count_traffic_and_updates() {
+    let mut server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec![
+                "SUBOK,1,1,1".to_string(),
+                "U,1,1,10".to_string(),
+                "U,1,1,11".to_string(),
+            ]
+        } else if request.starts_with("msg") {
+            vec!["REQOK,2".to_string()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let metrics = client.get_metrics();
+    assert_eq!(metrics.updates_received, 0);
+    assert_eq!(metrics.bytes_sent, 0);
+    assert_eq!(metrics.last_rtt, None);
+
+    let subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price"]).unwrap();
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+    server.next_request("control").await;
+    client
+        .send_message("hello", None, None, None, false)
+        .unwrap();
+    server.next_request("msg").await;
+
+    tokio::time::timeout(TIMEOUT, async {
+        loop {
+            let metrics = client.get_metrics();
+            if metrics.updates_received == 2 && metrics.messages_sent == 1 {
+                break;
+            }
+            tokio::time::sleep(std::time::Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("metrics not updated");
+
+    let metrics = client.get_metrics();
+    assert!(metrics.bytes_sent > 0);
+    assert!(metrics.bytes_received > 0);
+    assert!(metrics.last_rtt.is_some());
+    assert_eq!(metrics.reconnections, 0);
+    assert_eq!(
+        metrics.status,
+        ClientStatus::Connected(ConnectionType::WsStreaming)
+    );
+
+    client.disconnect().await;
+}
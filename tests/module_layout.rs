@@ -0,0 +1,29 @@
+use std::any::TypeId;
+
+#[test]
+fn canonical_paths_and_reexports_name_the_same_types() {
+    assert_eq!(
+        TypeId::of::<lightstreamer_client::LightstreamerClient>(),
+        TypeId::of::<lightstreamer_client::ls_client::LightstreamerClient>()
+    );
+    assert_eq!(
+        TypeId::of::<lightstreamer_client::lightstreamer_client::LightstreamerClient>(),
+        TypeId::of::<lightstreamer_client::ls_client::LightstreamerClient>()
+    );
+    assert_eq!(
+        TypeId::of::<lightstreamer_client::prelude::LightstreamerClient>(),
+        TypeId::of::<lightstreamer_client::ls_client::LightstreamerClient>()
+    );
+    assert_eq!(
+        TypeId::of::<lightstreamer_client::IllegalArgumentException>(),
+        TypeId::of::<lightstreamer_client::error::IllegalArgumentException>()
+    );
+    assert_eq!(
+        TypeId::of::<lightstreamer_client::Subscription>(),
+        TypeId::of::<lightstreamer_client::subscription::Subscription>()
+    );
+    assert_eq!(
+        TypeId::of::<lightstreamer_client::ConnectionOptions>(),
+        TypeId::of::<lightstreamer_client::connection_options::ConnectionOptions>()
+    );
+}
@@ -0,0 +1,114 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{next_update, request_param, MockServer, UpdateForwarder};
+use lightstreamer_client::monitor::{self, SessionStatistics, ThroughputStatistics};
+use lightstreamer_client::subscription::{Snapshot, SubscriptionMode};
+use lightstreamer_client::LightstreamerFields;
+use tokio::sync::mpsc;
+
+#[test]
+fn monitor_subscriptions_target_the_statistics_item() {
+    let subscription = monitor::sessions_subscription();
+    assert_eq!(subscription.get_mode(), &SubscriptionMode::Merge);
+    assert_eq!(
+        subscription.get_items(),
+        Some(&vec!["monitor_statistics".to_string()])
+    );
+    assert_eq!(
+        subscription.get_data_adapter().map(String::as_str),
+        Some("MONITOR")
+    );
+    assert!(matches!(
+        subscription.get_requested_snapshot(),
+        Some(Snapshot::Yes)
+    ));
+    assert_eq!(
+        subscription.get_fields(),
+        Some(&monitor::SESSION_FIELDS.map(str::to_string).to_vec())
+    );
+
+    let subscription = monitor::throughput_subscription();
+    assert_eq!(
+        subscription.get_fields(),
+        Some(&monitor::THROUGHPUT_FIELDS.map(str::to_string).to_vec())
+    );
+}
+
+#[tokio::test]
+async fn monitor_updates_are_read_into_statistics() {
+    let mut server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec![
+                "SUBOK,1,1,3".to_string(),
+                "U,1,1,12|40|5".to_string(),
+                "U,1,1,13||7".to_string(),
+            ]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let mut subscription = monitor::sessions_subscription();
+    let (sender, mut updates) = mpsc::unbounded_channel();
+    subscription.add_listener(Box::new(UpdateForwarder(sender)));
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let request = server.next_request("control").await;
+    assert_eq!(request_param(&request, "LS_data_adapter"), Some("MONITOR"));
+    assert_eq!(
+        request_param(&request, "LS_group"),
+        Some("monitor_statistics")
+    );
+    assert_eq!(
+        request_param(&request, "LS_schema"),
+        Some("CURR_SESSIONS%20MAX_SESSIONS%20CURR_ITEMS")
+    );
+
+    let first = SessionStatistics::from_item_update(&next_update(&mut updates).await).unwrap();
+    assert_eq!(
+        first,
+        SessionStatistics {
+            current_sessions: Some(12),
+            max_sessions: Some(40),
+            current_items: Some(5),
+        }
+    );
+    let second = SessionStatistics::from_item_update(&next_update(&mut updates).await).unwrap();
+    assert_eq!(second.current_sessions, Some(13));
+    assert_eq!(second.max_sessions, Some(40));
+    assert_eq!(second.current_items, Some(7));
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn throughput_statistics_reject_invalid_values() {
+    let mut server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec![
+                "SUBOK,1,1,5".to_string(),
+                "U,1,1,10.5|250|300|64.2|n/a".to_string(),
+            ]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let mut subscription = monitor::throughput_subscription();
+    let (sender, mut updates) = mpsc::unbounded_channel();
+    subscription.add_listener(Box::new(UpdateForwarder(sender)));
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+    server.next_request("control").await;
+
+    let update = next_update(&mut updates).await;
+    assert_eq!(update.get_value_as::<f64>("OUT_BANDWIDTH"), Ok(Some(64.2)));
+    assert!(ThroughputStatistics::from_item_update(&update).is_err());
+
+    client.disconnect().await;
+}
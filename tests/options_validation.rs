@@ -0,0 +1,58 @@
+use lightstreamer_client::connection_options::ConnectionOptions;
+
+#[test]
+fn options_can_be_set_in_any_order() {
+    let mut options = ConnectionOptions::new();
+    options.set_stalled_timeout(100).unwrap();
+    options.set_reconnect_timeout(200).unwrap();
+    options.set_keepalive_interval(300).unwrap();
+    options.set_polling_interval(500).unwrap();
+    options.set_idle_timeout(400).unwrap();
+    options.set_reverse_heartbeat_interval(1000).unwrap();
+    options.set_retry_delay(500).unwrap();
+    assert!(options.validate().is_ok());
+}
+
+#[test]
+fn stalled_timeout_is_not_bound_by_a_zero_keepalive() {
+    let mut options = ConnectionOptions::new();
+    options.set_keepalive_interval(0).unwrap();
+    options.set_stalled_timeout(2500).unwrap();
+    assert!(options.validate().is_ok());
+}
+
+#[test]
+fn all_violations_are_reported() {
+    let mut options = ConnectionOptions::new();
+    options.set_keepalive_interval(1000).unwrap();
+    options.set_polling_interval(100).unwrap();
+    options.set_session_recovery_timeout(1000).unwrap();
+
+    let err = options.validate().unwrap_err();
+    assert_eq!(
+        err.get_violations(),
+        [
+            "Stalled timeout (2000 ms) should be less than keepalive interval (1000 ms)",
+            "Keepalive interval (1000 ms) should be greater than or equal to reconnect timeout (3000 ms)",
+            "Polling interval (100 ms) should be greater than or equal to idle timeout (19000 ms)",
+            "Session recovery timeout (1000 ms) should be greater than or equal to retry delay (4000 ms)",
+        ]
+    );
+    assert!(err.to_string().starts_with("Invalid connection options: "));
+}
+
+#[cfg(feature = "runtime-tokio")]
+#[tokio::test]
+async fn connect_refuses_invalid_options() {
+    use lightstreamer_client::ls_client::{ClientStatus, DisconnectionType, LightstreamerClient};
+
+    let mut client =
+        LightstreamerClient::new(Some("http://localhost:8080"), Some("DEMO"), None, None).unwrap();
+    client.connection_options.set_stalled_timeout(5000).unwrap();
+
+    assert!(client.connect().await.is_err());
+    assert_eq!(
+        client.get_status(),
+        ClientStatus::Disconnected(DisconnectionType::None)
+    );
+}
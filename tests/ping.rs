@@ -0,0 +1,105 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::connection_options::ConnectionOptions;
+use lightstreamer_client::ls_client::LightstreamerClient;
+use std::time::Duration;
+
+/// Waits until the given condition holds for the client.
+async fn wait_for(client: &LightstreamerClient, condition: impl Fn(&LightstreamerClient) -> bool) {
+    tokio::time::timeout(TIMEOUT, async {
+        while !condition(client) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("condition not met");
+}
+
+#[test]
+fn pings_are_disabled_by_default() {
+    let mut options = ConnectionOptions::new();
+    assert_eq!(options.get_ping_interval(), 0);
+    assert_eq!(options.get_pong_timeout(), 5000);
+    assert!(options.set_pong_timeout(0).is_err());
+    options.set_ping_interval(1000);
+    options.set_pong_timeout(500).unwrap();
+    assert_eq!(options.get_ping_interval(), 1000);
+    assert_eq!(options.get_pong_timeout(), 500);
+}
+
+#[tokio::test]
+async fn pongs_measure_the_round_trip_time() {
+    let server = MockServer::start(|_| Vec::new()).await;
+    let mut client = server.client();
+    client.connection_options.set_ping_interval(20);
+    assert_eq!(client.get_metrics().ping_rtt, None);
+    client.connect().await.unwrap();
+
+    wait_for(&client, |client| client.get_metrics().ping_rtt.is_some()).await;
+    let metrics = client.get_metrics();
+    assert!(metrics.ping_rtt.unwrap() < TIMEOUT);
+    assert_eq!(metrics.reconnections, 0);
+
+    client.disconnect().await;
+}
+
+#[cfg(feature = "test-util")]
+#[tokio::test]
+async fn missing_pongs_make_the_client_reconnect() {
+    use lightstreamer_client::client_listener::ClientListener;
+    use lightstreamer_client::fault_injection::FaultInjector;
+    use lightstreamer_client::ls_client::ClientStatus;
+    use std::sync::Arc;
+    use tokio::sync::mpsc::{self, UnboundedSender};
+
+    /// Listener forwarding the status changes to the test.
+    #[derive(Debug)]
+    struct StatusForwarder(UnboundedSender<String>);
+
+    impl ClientListener for StatusForwarder {
+        fn on_status_change(&self, status: &str) {
+            let _ = self.0.send(status.to_string());
+        }
+    }
+
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let injector = Arc::new(FaultInjector::new());
+    let mut client = server.client();
+    client.connection_options.set_ping_interval(20);
+    client.connection_options.set_pong_timeout(200).unwrap();
+    client
+        .connection_options
+        .set_fault_injector(Some(Arc::clone(&injector)));
+    client.connect().await.unwrap();
+    server.next_request("create_session").await;
+    wait_for(&client, |client| {
+        matches!(client.get_status(), ClientStatus::Connected(_))
+    })
+    .await;
+
+    injector.drop_pongs(true);
+    let (sender, mut statuses) = mpsc::unbounded_channel();
+    client.add_listener(Box::new(StatusForwarder(sender)));
+    let stalled = tokio::time::timeout(TIMEOUT, async {
+        while let Some(status) = statuses.recv().await {
+            if status == "STALLED" {
+                return true;
+            }
+        }
+        false
+    });
+    assert!(stalled.await.expect("client not stalled"));
+    injector.drop_pongs(false);
+
+    server.next_request("recover_session").await;
+    wait_for(&client, |client| {
+        matches!(client.get_status(), ClientStatus::Connected(_))
+    })
+    .await;
+    assert_eq!(client.get_metrics().reconnections, 1);
+
+    client.disconnect().await;
+}
@@ -0,0 +1,444 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{next_update, MockServer, UpdateForwarder, TIMEOUT};
+use lightstreamer_client::item_update::{FieldNames, FieldValue, ItemUpdate};
+use lightstreamer_client::protocol::decode_update_values;
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use lightstreamer_client::subscription_listener::SubscriptionListener;
+use std::sync::Arc;
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+struct UpdateListener(UnboundedSender<(String, String)>);
+
+impl SubscriptionListener for UpdateListener {
+    fn on_item_update(&self, update: &ItemUpdate) {
+        let _ = self.0.send((
+            update.get_value("name").unwrap_or_default().to_string(),
+            update.get_value("price").unwrap_or_default().to_string(),
+        ));
+    }
+}
+
+#[tokio::test]
+async fn update_values_are_delivered_verbatim() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec![
+                "SUBOK,1,1,2".to_string(),
+                // A blank line and a line terminated by a bare line feed are tolerated.
+                "\r\nU,1,1,ACME Corp|12.5E3\nU,1,1,Foo Bar|".to_string(),
+            ]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let (sender, mut updates) = mpsc::unbounded_channel();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["name", "price"]).unwrap();
+    subscription.add_listener(Box::new(UpdateListener(sender)));
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    for expected in [("ACME Corp", "12.5E3"), ("Foo Bar", "12.5E3")] {
+        let update = tokio::time::timeout(TIMEOUT, updates.recv())
+            .await
+            .expect("no update received")
+            .expect("listener dropped");
+        assert_eq!(update, (expected.0.to_string(), expected.1.to_string()));
+    }
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn percent_encoded_values_are_decoded() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            [
+                "SUBOK,1,1,3",
+                "U,1,1,a%7Cb|100%25|1+1",
+                "U,1,1,caf%C3%A9|%2C%0A|%ZZ",
+            ]
+            .map(str::to_string)
+            .to_vec()
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let updates = receive_updates(
+        &server,
+        SubscriptionMode::Merge,
+        ["name", "price", "volume"],
+        2,
+    )
+    .await;
+
+    assert_eq!(updates[0].get_value("name"), Some("a|b"));
+    assert_eq!(updates[0].get_value("price"), Some("100%"));
+    assert_eq!(updates[0].get_value("volume"), Some("1+1"));
+    assert_eq!(updates[1].get_value("name"), Some("café"));
+    assert_eq!(updates[1].get_value("price"), Some(",\n"));
+    // Malformed escapes are kept as they are.
+    assert_eq!(updates[1].get_value("volume"), Some("%ZZ"));
+}
+
+struct FieldNamesListener(UnboundedSender<Arc<FieldNames>>);
+
+impl SubscriptionListener for FieldNamesListener {
+    fn on_item_update(&self, update: &ItemUpdate) {
+        let _ = self.0.send(Arc::clone(update.get_field_names()));
+    }
+}
+
+#[tokio::test]
+async fn field_names_are_shared_between_updates() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            ["SUBOK,1,2,2", "U,1,1,a|1", "U,1,2,b|2"]
+                .map(str::to_string)
+                .to_vec()
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let (sender, mut updates) = mpsc::unbounded_channel();
+    let mut subscription = Subscription::new(
+        SubscriptionMode::Merge,
+        Some(["item1", "item2"]),
+        Some(["name", "price"]),
+    )
+    .unwrap();
+    subscription.add_listener(Box::new(FieldNamesListener(sender)));
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let mut field_names = Vec::new();
+    for _ in 0..2 {
+        let names = tokio::time::timeout(TIMEOUT, updates.recv())
+            .await
+            .expect("no update received")
+            .expect("listener dropped");
+        field_names.push(names);
+    }
+    assert_eq!(field_names[0].iter().collect::<Vec<_>>(), ["name", "price"]);
+    assert!(Arc::ptr_eq(&field_names[0], &field_names[1]));
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn unchanged_values_are_carried_over_by_position() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            ["SUBOK,1,1,3", "U,1,1,ACME|12|100", "U,1,1,|13|^1"]
+                .map(str::to_string)
+                .to_vec()
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let (sender, mut updates) = mpsc::unbounded_channel();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["name", "price", "volume"])
+            .unwrap();
+    subscription.add_listener(Box::new(UpdateForwarder(sender)));
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let mut received = Vec::new();
+    for _ in 0..2 {
+        received.push(next_update(&mut updates).await);
+    }
+    let update = &received[1];
+    assert_eq!(update.get_value_by_position(1), Some("ACME"));
+    assert_eq!(update.get_value("2"), Some("13"));
+    assert_eq!(update.get_value("volume"), Some("100"));
+    assert_eq!(
+        update.changed_fields_iter().collect::<Vec<_>>(),
+        [("price", Some("13"))]
+    );
+    assert!(!update.is_value_changed("name"));
+    assert!(!update.is_value_changed("4"));
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn schema_fields_are_only_available_by_position() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            ["SUBOK,1,1,2", "U,1,1,ACME|12"]
+                .map(str::to_string)
+                .to_vec()
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let (sender, mut updates) = mpsc::unbounded_channel();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["name", "price"]).unwrap();
+    subscription.set_field_schema("quote".to_string()).unwrap();
+    assert_eq!(subscription.get_fields(), None);
+    subscription.add_listener(Box::new(UpdateForwarder(sender)));
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let update = next_update(&mut updates).await;
+    assert!(update.get_field_names().is_schema());
+    assert!(update.get_fields().is_err());
+    assert!(update.get_changed_fields().is_err());
+    assert_eq!(
+        update.get_fields_by_position(),
+        [(1, Some("ACME".to_string())), (2, Some("12".to_string()))].into()
+    );
+    assert_eq!(update.get_value("2"), Some("12"));
+    assert_eq!(update.get_value("price"), None);
+    client.disconnect().await;
+}
+
+/// Subscribes to "item" with the given mode and fields, and collects the first `count` updates.
+async fn receive_updates(
+    server: &MockServer,
+    mode: SubscriptionMode,
+    fields: [&str; 3],
+    count: usize,
+) -> Vec<ItemUpdate> {
+    let client = server.client();
+    let (sender, mut updates) = mpsc::unbounded_channel();
+    let mut subscription = Subscription::new_single_item(mode, "item", fields).unwrap();
+    subscription.add_listener(Box::new(UpdateForwarder(sender)));
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+    let mut received = Vec::new();
+    for _ in 0..count {
+        received.push(next_update(&mut updates).await);
+    }
+    client.disconnect().await;
+    received
+}
+
+#[tokio::test]
+async fn null_empty_and_unchanged_values_are_told_apart() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            ["SUBOK,1,1,3", "U,1,1,ACME|12|", "U,1,1,#|$|"]
+                .map(str::to_string)
+                .to_vec()
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let updates = receive_updates(
+        &server,
+        SubscriptionMode::Merge,
+        ["name", "price", "volume"],
+        2,
+    )
+    .await;
+
+    let first = &updates[0];
+    assert_eq!(first.get_field_value("volume"), Some(FieldValue::Unchanged));
+    assert!(!first.is_null("volume"));
+    let update = &updates[1];
+    assert_eq!(update.get_field_value("name"), Some(FieldValue::Null));
+    assert_eq!(update.get_value("name"), None);
+    assert!(update.is_null("name"));
+    assert!(update.is_value_changed("name"));
+    assert_eq!(update.get_field_value("price"), Some(FieldValue::Value("")));
+    assert_eq!(update.get_value("price"), Some(""));
+    assert!(!update.is_null("price"));
+    assert_eq!(
+        update.get_field_value("volume"),
+        Some(FieldValue::Unchanged)
+    );
+    assert_eq!(update.get_field_value("bid"), None);
+}
+
+#[tokio::test]
+async fn tlcp_diff_values_are_skipped() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            ["SUBOK,1,1,3", "U,1,1,ACME|12|5", "U,1,1,|^Tbdzz|6"]
+                .map(str::to_string)
+                .to_vec()
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let updates = receive_updates(
+        &server,
+        SubscriptionMode::Merge,
+        ["name", "price", "volume"],
+        2,
+    )
+    .await;
+
+    // The field with the unsupported diff keeps its previous value.
+    let update = &updates[1];
+    assert_eq!(update.get_value("price"), Some("12"));
+    assert!(!update.is_value_changed("price"));
+    assert_eq!(update.get_value("volume"), Some("6"));
+
+    let mut changes = [FieldValue::Unchanged, FieldValue::Unchanged];
+    assert_eq!(decode_update_values("^Tbdzz|^Tc", &[], &mut changes), 2);
+    assert_eq!(changes, [FieldValue::Unchanged, FieldValue::Unchanged]);
+}
+
+#[tokio::test]
+async fn json_patches_are_applied_to_the_previous_values() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            [
+                "SUBOK,1,1,3",
+                r#"U,1,1,ACME|{"bid":10,"ask":11}|5"#,
+                r#"U,1,1,|^P[{"op":"replace","path":"/bid","value":12}]|"#,
+                r#"U,1,1,|^P[{"op":"remove","path":"/missing"}]|6"#,
+            ]
+            .map(str::to_string)
+            .to_vec()
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let updates = receive_updates(
+        &server,
+        SubscriptionMode::Merge,
+        ["name", "quote", "volume"],
+        3,
+    )
+    .await;
+
+    let quote = |update: &ItemUpdate| {
+        serde_json::from_str::<serde_json::Value>(update.get_value("quote").unwrap()).unwrap()
+    };
+    assert_eq!(
+        quote(&updates[1]),
+        serde_json::json!({"bid": 12, "ask": 11})
+    );
+    assert!(updates[1].is_value_changed("quote"));
+    // A patch that can't be applied leaves the previous value.
+    assert_eq!(
+        quote(&updates[2]),
+        serde_json::json!({"bid": 12, "ask": 11})
+    );
+    assert!(!updates[2].is_value_changed("quote"));
+
+    let previous = [FieldValue::Value(r#"{"bid":1}"#.to_string())];
+    let mut changes = [FieldValue::Unchanged];
+    let patch = r#"^P[{"op":"add","path":"/ask","value":2}]"#;
+    assert_eq!(decode_update_values(patch, &previous, &mut changes), 0);
+    let FieldValue::Value(patched) = &changes[0] else {
+        panic!("patch not applied: {:?}", changes);
+    };
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(patched).unwrap(),
+        serde_json::json!({"bid": 1, "ask": 2})
+    );
+    assert_eq!(decode_update_values(patch, &[], &mut changes[..0]), 1);
+}
+
+#[tokio::test]
+async fn delete_commands_null_the_other_fields() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            ["SUBOK,1,1,3", "U,1,1,k1|ADD|10", "U,1,1,|DELETE|"]
+                .map(str::to_string)
+                .to_vec()
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let updates = receive_updates(
+        &server,
+        SubscriptionMode::Command,
+        ["key", "command", "price"],
+        2,
+    )
+    .await;
+
+    let update = &updates[1];
+    assert_eq!(update.get_field_value("key"), Some(FieldValue::Unchanged));
+    assert_eq!(update.get_value("key"), Some("k1"));
+    assert_eq!(
+        update.get_field_value("command"),
+        Some(FieldValue::Value("DELETE"))
+    );
+    assert_eq!(update.get_field_value("price"), Some(FieldValue::Null));
+    assert!(update.is_null("price"));
+}
+
+#[tokio::test]
+async fn schema_updates_are_keyed_by_position() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            [
+                "SUBCMD,1,1,3,2,1",
+                "U,1,1,ADD|k1|10",
+                "U,1,1,UPDATE||11",
+                "U,1,1,DELETE||",
+            ]
+            .map(str::to_string)
+            .to_vec()
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let (sender, mut updates) = mpsc::unbounded_channel();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Command, "item", ["command"]).unwrap();
+    subscription
+        .set_field_schema("portfolio".to_string())
+        .unwrap();
+    subscription.add_listener(Box::new(UpdateForwarder(sender)));
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let mut received = Vec::new();
+    for _ in 0..3 {
+        received.push(next_update(&mut updates).await);
+    }
+    {
+        let subscriptions = client.get_subscriptions();
+        assert_eq!(subscriptions[0].get_key_position(), Some(2));
+        assert_eq!(subscriptions[0].get_command_position(), Some(1));
+    }
+
+    let update = &received[1];
+    assert_eq!(update.get_changed_positions(), [1, 3].into());
+    assert!(update.is_value_changed_by_position(3));
+    assert!(!update.is_value_changed_by_position(2));
+    assert!(!update.is_value_changed_by_position(4));
+    assert_eq!(
+        update.get_field_value_by_position(3),
+        Some(FieldValue::Value("11"))
+    );
+    assert_eq!(update.get_value_by_position(2), Some("k1"));
+
+    // The key is told apart by the position confirmed by the server.
+    let update = &received[2];
+    assert_eq!(update.get_changed_positions(), [1, 3].into());
+    assert_eq!(
+        update.get_field_value_by_position(2),
+        Some(FieldValue::Unchanged)
+    );
+    assert_eq!(
+        update.get_field_value_by_position(3),
+        Some(FieldValue::Null)
+    );
+    assert_eq!(update.get_field_value_by_position(4), None);
+    client.disconnect().await;
+}
@@ -0,0 +1,144 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{request_param, MockServer, TIMEOUT};
+use lightstreamer_client::item_update::ItemUpdate;
+use lightstreamer_client::subscription::{Snapshot, Subscription, SubscriptionMode};
+use lightstreamer_client::subscription_listener::SubscriptionListener;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// Events received by `RecordingListener`.
+#[derive(Debug)]
+enum Event {
+    Update(ItemUpdate),
+    LostUpdates(Option<String>, usize, u32),
+}
+
+/// Listener forwarding the received events to the test.
+struct RecordingListener(UnboundedSender<Event>);
+
+impl SubscriptionListener for RecordingListener {
+    fn on_item_update(&self, update: &ItemUpdate) {
+        let _ = self.0.send(Event::Update(update.clone()));
+    }
+
+    fn on_item_lost_updates(&mut self, item_name: Option<&str>, item_pos: usize, lost: u32) {
+        let _ = self.0.send(Event::LostUpdates(
+            item_name.map(str::to_string),
+            item_pos,
+            lost,
+        ));
+    }
+}
+
+/// Creates a RAW subscription to the "chat" item with the "user" and "text" fields.
+fn raw_subscription() -> (Subscription, UnboundedReceiver<Event>) {
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Raw, "chat", ["user", "text"]).unwrap();
+    let (sender, events) = mpsc::unbounded_channel();
+    subscription.add_listener(Box::new(RecordingListener(sender)));
+    (subscription, events)
+}
+
+async fn next_event(events: &mut UnboundedReceiver<Event>) -> Event {
+    tokio::time::timeout(TIMEOUT, events.recv())
+        .await
+        .expect("no event received")
+        .expect("listener dropped")
+}
+
+#[test]
+fn raw_subscription_rejects_snapshot_requests() {
+    let (mut subscription, _) = raw_subscription();
+    assert!(subscription
+        .set_requested_snapshot(Some(Snapshot::Yes))
+        .is_err());
+    assert!(subscription.set_requested_snapshot(None).is_ok());
+}
+
+#[tokio::test]
+async fn raw_subscription_requests_no_snapshot() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let client = server.client();
+    let (subscription, _) = raw_subscription();
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let request = server.next_request("control").await;
+    assert_eq!(request_param(&request, "LS_op"), Some("add"));
+    assert_eq!(request_param(&request, "LS_mode"), Some("RAW"));
+    assert_eq!(request_param(&request, "LS_group"), Some("chat"));
+    assert_eq!(request_param(&request, "LS_snapshot"), None);
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn raw_updates_are_independent_events() {
+    let mut server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec![
+                "SUBOK,1,1,2".to_string(),
+                "U,1,1,ann|hello".to_string(),
+                // The user is unchanged, so it's not sent again.
+                "U,1,1,|bye".to_string(),
+            ]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let (subscription, mut events) = raw_subscription();
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+    server.next_request("control").await;
+
+    let Event::Update(first) = next_event(&mut events).await else {
+        panic!("update expected");
+    };
+    assert_eq!(first.get_value("user"), Some("ann"));
+    assert_eq!(first.get_value("text"), Some("hello"));
+    assert!(!first.is_snapshot());
+
+    let Event::Update(second) = next_event(&mut events).await else {
+        panic!("update expected");
+    };
+    assert_eq!(second.get_value("user"), Some("ann"));
+    assert_eq!(second.get_value("text"), Some("bye"));
+    assert!(!second.is_snapshot());
+    // Every RAW event carries all its values.
+    assert!(second.is_value_changed("user"));
+    assert!(second.is_value_changed("text"));
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn lost_updates_are_routed_to_listeners() {
+    let mut server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec!["SUBOK,1,1,2".to_string(), "OV,1,1,7".to_string()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let (subscription, mut events) = raw_subscription();
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+    server.next_request("control").await;
+
+    match next_event(&mut events).await {
+        Event::LostUpdates(item_name, item_pos, lost) => {
+            assert_eq!(item_name.as_deref(), Some("chat"));
+            assert_eq!(item_pos, 1);
+            assert_eq!(lost, 7);
+        }
+        event => panic!("lost updates expected, got {:?}", event),
+    }
+
+    client.disconnect().await;
+}
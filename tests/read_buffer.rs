@@ -0,0 +1,69 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::connection_options::{ConnectionOptions, ReadBufferGrowth};
+use lightstreamer_client::item_update::ItemUpdate;
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use lightstreamer_client::subscription_listener::SubscriptionListener;
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+/// Listener forwarding the values of the updates to the test.
+struct ValueForwarder(UnboundedSender<String>);
+
+impl SubscriptionListener for ValueForwarder {
+    fn on_item_update(&self, update: &ItemUpdate) {
+        let value = update.get_value("field1").unwrap_or_default().to_string();
+        let _ = self.0.send(value);
+    }
+}
+
+#[test]
+fn read_buffers_default_to_the_websocket_ones() {
+    let mut options = ConnectionOptions::new();
+    assert_eq!(options.get_read_buffer_size(), None);
+    assert_eq!(options.get_read_buffer_growth(), ReadBufferGrowth::Doubling);
+    assert!(options.set_read_buffer_size(Some(0)).is_err());
+    options.set_read_buffer_size(Some(4096)).unwrap();
+    options.set_read_buffer_growth(ReadBufferGrowth::Shrinking);
+    assert_eq!(options.get_read_buffer_size(), Some(4096));
+    assert_eq!(
+        options.get_read_buffer_growth(),
+        ReadBufferGrowth::Shrinking
+    );
+    options.set_read_buffer_size(None).unwrap();
+    assert_eq!(options.get_read_buffer_size(), None);
+}
+
+#[tokio::test]
+async fn messages_longer_than_the_buffer_are_received() {
+    let value = "x".repeat(4096);
+    let notification = format!("U,1,1,{}", value);
+    let server = MockServer::start(move |request| {
+        if request.contains("LS_op=add") {
+            vec!["SUBOK,1,1,1".to_string(), notification.clone()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let mut client = server.client();
+    client
+        .connection_options
+        .set_read_buffer_size(Some(64))
+        .unwrap();
+    let (sender, mut values) = mpsc::unbounded_channel();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["field1"]).unwrap();
+    subscription.add_listener(Box::new(ValueForwarder(sender)));
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let received = tokio::time::timeout(TIMEOUT, values.recv())
+        .await
+        .expect("no update received");
+    assert_eq!(received, Some(value));
+
+    client.disconnect().await;
+}
@@ -0,0 +1,170 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{drop_connection, request_param, MockServer, TIMEOUT};
+use lightstreamer_client::client_listener::ClientListener;
+use lightstreamer_client::credentials_provider::{
+    Credentials, ReauthenticationFuture, ReauthenticationHandler,
+};
+use lightstreamer_client::ls_client::{
+    ClientStatus, ConnectionType, DisconnectionType, LightstreamerClient,
+};
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+/// Handler simulating a REST login that issues a new token each time it is invoked.
+#[derive(Debug, Default)]
+struct LoginHandler(AtomicUsize);
+
+impl ReauthenticationHandler for LoginHandler {
+    fn reauthenticate(&self) -> ReauthenticationFuture<'_> {
+        Box::pin(async move {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+            let login = self.0.fetch_add(1, Ordering::SeqCst) + 1;
+            Ok(Some(Credentials::new(
+                Some("trader"),
+                Some(&format!("cst-{}", login)),
+            )))
+        })
+    }
+}
+
+#[tokio::test]
+async fn handler_is_invoked_only_when_a_lost_session_is_replaced() {
+    // The first connection is dropped, and the session can't be recovered.
+    let looped = AtomicBool::new(false);
+    let mut server = MockServer::start(move |request| {
+        if request.contains("LS_op=add") && !looped.swap(true, Ordering::SeqCst) {
+            vec![drop_connection()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let handler = Arc::new(LoginHandler::default());
+    let mut client = server.client();
+    client
+        .connection_details
+        .set_password(Some("initial".into()));
+    client
+        .connection_details
+        .set_reauthentication_handler(Some(handler.clone()));
+    client
+        .connection_options
+        .set_session_recovery_timeout(0)
+        .unwrap();
+    client.connection_options.set_retry_delay(10).unwrap();
+    client
+        .connection_options
+        .set_first_retry_max_delay(10)
+        .unwrap();
+    let subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price"]).unwrap();
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let request = server.next_request("create_session").await;
+    assert_eq!(request_param(&request, "LS_password"), Some("initial"));
+    let request = server.next_request("create_session").await;
+    assert_eq!(request_param(&request, "LS_user"), Some("trader"));
+    assert_eq!(request_param(&request, "LS_password"), Some("cst-1"));
+    assert_eq!(handler.0.load(Ordering::SeqCst), 1);
+
+    client.disconnect().await;
+}
+
+/// Listener forwarding the errors notified by the server to the test.
+#[derive(Debug)]
+struct ServerErrorForwarder(UnboundedSender<(i32, String)>);
+
+impl ClientListener for ServerErrorForwarder {
+    fn on_server_error(&self, code: i32, message: &str) {
+        let _ = self.0.send((code, message.to_string()));
+    }
+}
+
+/// Starts a mock server rejecting the session creations whose password is not the given one.
+async fn start_server(accepted_password: &'static str) -> MockServer {
+    MockServer::start(move |request| {
+        if request.starts_with("create_session")
+            && request_param(request, "LS_password") != Some(accepted_password)
+        {
+            vec!["CONERR,1,Invalid credentials".to_string()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await
+}
+
+fn relogin_client(server: &MockServer, handler: Arc<LoginHandler>) -> LightstreamerClient {
+    let mut client = server.client();
+    client
+        .connection_details
+        .set_password(Some("expired".into()));
+    client
+        .connection_details
+        .set_reauthentication_handler(Some(handler));
+    client.connection_options.set_max_relogin_attempts(2);
+    client.connection_options.set_retry_delay(10).unwrap();
+    client
+        .connection_options
+        .set_first_retry_max_delay(10)
+        .unwrap();
+    client
+}
+
+#[tokio::test]
+async fn rejected_credentials_are_refreshed_and_the_session_created_again() {
+    let mut server = start_server("cst-2").await;
+    let handler = Arc::new(LoginHandler::default());
+    let client = relogin_client(&server, handler.clone());
+    client.connect().await.unwrap();
+
+    let request = server.next_request("create_session").await;
+    assert_eq!(request_param(&request, "LS_password"), Some("expired"));
+    let request = server.next_request("create_session").await;
+    assert_eq!(request_param(&request, "LS_password"), Some("cst-1"));
+    let request = server.next_request("create_session").await;
+    assert_eq!(request_param(&request, "LS_password"), Some("cst-2"));
+    tokio::time::timeout(TIMEOUT, async {
+        while client.get_status() != ClientStatus::Connected(ConnectionType::WsStreaming) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("client not connected");
+    assert_eq!(handler.0.load(Ordering::SeqCst), 2);
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn relogin_gives_up_after_the_configured_attempts() {
+    let mut server = start_server("never").await;
+    let handler = Arc::new(LoginHandler::default());
+    let client = relogin_client(&server, handler.clone());
+    let (sender, mut errors) = mpsc::unbounded_channel();
+    client.add_listener(Box::new(ServerErrorForwarder(sender)));
+    client.connect().await.unwrap();
+
+    let error = tokio::time::timeout(TIMEOUT, errors.recv())
+        .await
+        .expect("no server error notified");
+    assert_eq!(error, Some((1, "Invalid credentials".to_string())));
+    assert_eq!(handler.0.load(Ordering::SeqCst), 2);
+    for _ in 0..3 {
+        server.next_request("create_session").await;
+    }
+    tokio::time::timeout(TIMEOUT, async {
+        while client.get_status() != ClientStatus::Disconnected(DisconnectionType::None) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("client still connecting");
+}
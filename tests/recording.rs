@@ -0,0 +1,103 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::item_update::ItemUpdate;
+use lightstreamer_client::ls_client::LightstreamerClient;
+use lightstreamer_client::recording::{FrameDirection, SessionRecorder, SessionRecording};
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use lightstreamer_client::subscription_listener::SubscriptionListener;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// Writer collecting the recorded frames in memory.
+#[derive(Clone, Default)]
+struct SharedBuffer(Arc<Mutex<Vec<u8>>>);
+
+impl Write for SharedBuffer {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0.lock().unwrap().write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+struct PriceListener(UnboundedSender<String>);
+
+impl SubscriptionListener for PriceListener {
+    fn on_item_update(&self, update: &ItemUpdate) {
+        let _ = self
+            .0
+            .send(update.get_value("price").unwrap_or_default().to_string());
+    }
+}
+
+/// Subscribes the client to the prices of an item.
+fn subscribe(client: &mut LightstreamerClient) -> UnboundedReceiver<String> {
+    let (sender, prices) = mpsc::unbounded_channel();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price"]).unwrap();
+    subscription.add_listener(Box::new(PriceListener(sender)));
+    client.subscribe(subscription);
+    prices
+}
+
+async fn receive(prices: &mut UnboundedReceiver<String>, count: usize) -> Vec<String> {
+    let mut received = Vec::new();
+    for _ in 0..count {
+        let price = tokio::time::timeout(TIMEOUT, prices.recv())
+            .await
+            .expect("no update received")
+            .expect("listener dropped");
+        received.push(price);
+    }
+    received
+}
+
+#[tokio::test]
+async fn recorded_session_is_replayed_offline() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            ["SUBOK,1,1,1", "U,1,1,10.5", "U,1,1,11"]
+                .map(str::to_string)
+                .to_vec()
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let buffer = SharedBuffer::default();
+    let mut client = server.client();
+    client
+        .connection_details
+        .set_password(Some("secret".into()));
+    client
+        .connection_options
+        .set_session_recorder(Some(Arc::new(SessionRecorder::new(buffer.clone()))));
+    let mut prices = subscribe(&mut client);
+    client.connect().await.unwrap();
+    let live = receive(&mut prices, 2).await;
+    client.disconnect().await;
+
+    let recorded = buffer.0.lock().unwrap().clone();
+    let recording = SessionRecording::from_reader(recorded.as_slice()).unwrap();
+    let frames = recording.frames();
+    assert_eq!(frames[0].direction, FrameDirection::Connected);
+    assert!(frames
+        .iter()
+        .any(|frame| frame.direction == FrameDirection::Received && frame.text.contains("U,1,1")));
+    assert!(!String::from_utf8(recorded).unwrap().contains("secret"));
+
+    // The replaying client doesn't need the server.
+    drop(server);
+    let mut client =
+        LightstreamerClient::new(Some("http://localhost:1/"), Some("DEMO"), None, None).unwrap();
+    let mut prices = subscribe(&mut client);
+    client.replay(recording).await.unwrap();
+    assert_eq!(receive(&mut prices, 2).await, live);
+    client.disconnect().await;
+}
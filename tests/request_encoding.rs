@@ -0,0 +1,54 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{request_param, MockServer};
+use lightstreamer_client::protocol::encode_params;
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+
+#[test]
+fn parameters_are_percent_encoded() {
+    assert_eq!(
+        encode_params([("LS_user", "a&b=c"), ("LS_password", "50%+|€ x~._-")]),
+        "LS_user=a%26b%3Dc&LS_password=50%25%2B%7C%E2%82%AC%20x~._-"
+    );
+    assert_eq!(encode_params(Vec::<(&str, &str)>::new()), "");
+}
+
+#[tokio::test]
+async fn credentials_and_messages_are_sent_encoded() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let mut client = server.client();
+    client
+        .connection_details
+        .set_user(Some("trader&desk=1".to_string()));
+    client
+        .connection_details
+        .set_password(Some("p%ss|wörd".into()));
+    let subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item", ["price"]).unwrap();
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let request = server.next_request("create_session").await;
+    assert_eq!(
+        request_param(&request, "LS_user"),
+        Some("trader%26desk%3D1")
+    );
+    assert_eq!(
+        request_param(&request, "LS_password"),
+        Some("p%25ss%7Cw%C3%B6rd")
+    );
+    server.next_request("control").await;
+
+    client
+        .send_message("BUY 10 @ 1+1|a=b&c", None, None, None, false)
+        .unwrap();
+    let request = server.next_request("msg").await;
+    assert_eq!(
+        request_param(&request, "LS_message"),
+        Some("BUY%2010%20%40%201%2B1%7Ca%3Db%26c")
+    );
+
+    client.disconnect().await;
+}
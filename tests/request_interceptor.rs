@@ -0,0 +1,79 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{request_param, MockServer};
+use lightstreamer_client::request_interceptor::{
+    InterceptedRequest, RequestInterceptor, RequestKind,
+};
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use std::sync::{Arc, Mutex};
+
+/// Interceptor adding a tracking parameter to every request, and recording their kinds.
+#[derive(Debug, Default)]
+struct Tracker(Mutex<Vec<RequestKind>>);
+
+impl RequestInterceptor for Tracker {
+    fn intercept(&self, request: &mut InterceptedRequest) {
+        self.0.lock().unwrap().push(request.get_kind());
+        if request.get_kind() == RequestKind::Handshake {
+            request.set_header("x-tracking", "abc");
+        } else {
+            request.set_param("LS_tracking", "abc");
+        }
+    }
+}
+
+/// Interceptor signing the requests with the parameters set by the previous interceptors.
+#[derive(Debug)]
+struct Signer;
+
+impl RequestInterceptor for Signer {
+    fn intercept(&self, request: &mut InterceptedRequest) {
+        let signature = request
+            .get_params()
+            .iter()
+            .map(|(name, _)| name.as_str())
+            .collect::<Vec<_>>()
+            .join(".");
+        request.remove_param("LS_cid");
+        request.set_param("LS_signature", &signature);
+    }
+}
+
+#[tokio::test]
+async fn requests_go_through_the_interceptors_in_order() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let mut client = server.client();
+    let tracker = Arc::new(Tracker::default());
+    client
+        .connection_options
+        .add_request_interceptor(Arc::clone(&tracker) as Arc<dyn RequestInterceptor>);
+    client
+        .connection_options
+        .add_request_interceptor(Arc::new(Signer));
+    client.subscribe(
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["field1"]).unwrap(),
+    );
+    client.connect().await.unwrap();
+
+    let request = server.next_request("create_session").await;
+    assert_eq!(request_param(&request, "LS_tracking"), Some("abc"));
+    assert_eq!(request_param(&request, "LS_cid"), None);
+    let signature = request_param(&request, "LS_signature").unwrap();
+    assert!(signature.ends_with(".LS_tracking"), "{}", signature);
+    let request = server.next_request("control").await;
+    assert_eq!(request_param(&request, "LS_op"), Some("add"));
+    assert_eq!(request_param(&request, "LS_tracking"), Some("abc"));
+    assert!(request_param(&request, "LS_signature").is_some());
+    assert_eq!(
+        tracker.0.lock().unwrap()[..3],
+        [
+            RequestKind::Handshake,
+            RequestKind::CreateSession,
+            RequestKind::Control
+        ]
+    );
+
+    client.disconnect().await;
+}
@@ -0,0 +1,55 @@
+use lightstreamer_client::connection_options::ConnectionOptions;
+use lightstreamer_client::retry_policy::{DefaultRetryPolicy, RetryPolicy};
+use std::io;
+use std::time::Duration;
+
+const RETRY_DELAY: Duration = Duration::from_secs(4);
+
+fn failure() -> io::Error {
+    io::Error::new(io::ErrorKind::ConnectionRefused, "refused")
+}
+
+#[test]
+fn delays_grow_while_the_attempts_keep_failing() {
+    let policy = DefaultRetryPolicy::new(RETRY_DELAY, Duration::from_millis(100));
+
+    assert_eq!(policy.next_delay(1, &failure()), Some(RETRY_DELAY));
+    for _ in 0..20 {
+        let delay = policy.next_delay(2, &failure()).unwrap();
+        assert!(delay >= RETRY_DELAY && delay <= RETRY_DELAY * 2);
+        let delay = policy.next_delay(4, &failure()).unwrap();
+        assert!(delay >= RETRY_DELAY * 4 && delay <= RETRY_DELAY * 8);
+    }
+}
+
+#[test]
+fn delays_are_bounded() {
+    let max_retry_delay = Duration::from_secs(30);
+    let policy = DefaultRetryPolicy::new(RETRY_DELAY, Duration::from_millis(100))
+        .with_max_retry_delay(max_retry_delay);
+
+    for attempt in [10, 40, u32::MAX] {
+        let delay = policy.next_delay(attempt, &failure()).unwrap();
+        assert!(delay >= max_retry_delay / 2 && delay <= max_retry_delay);
+    }
+}
+
+#[test]
+fn bounds_below_the_retry_delay_disable_the_growth() {
+    let policy = DefaultRetryPolicy::new(RETRY_DELAY, Duration::from_millis(100))
+        .with_max_retry_delay(Duration::from_secs(1));
+
+    assert_eq!(policy.next_delay(5, &failure()), Some(RETRY_DELAY));
+}
+
+#[test]
+fn max_retry_delay_defaults_to_one_minute() {
+    let mut options = ConnectionOptions::new();
+    assert_eq!(
+        Duration::from_millis(options.get_max_retry_delay()),
+        DefaultRetryPolicy::DEFAULT_MAX_RETRY_DELAY
+    );
+    assert!(options.set_max_retry_delay(0).is_err());
+    options.set_max_retry_delay(10000).unwrap();
+    assert_eq!(options.get_max_retry_delay(), 10000);
+}
@@ -0,0 +1,135 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{drop_connection, MockServer, TIMEOUT};
+use lightstreamer_client::client_listener::ClientListener;
+use lightstreamer_client::ls_client::LightstreamerClient;
+use lightstreamer_client::session_end_cause::SessionEndCause;
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// Session lifecycle event notified to the client listeners.
+#[derive(Debug, PartialEq)]
+enum SessionEvent {
+    Start(String),
+    End(SessionEndCause),
+}
+
+/// Listener forwarding the session lifecycle events to the test.
+#[derive(Debug)]
+struct SessionForwarder(UnboundedSender<SessionEvent>);
+
+impl ClientListener for SessionForwarder {
+    fn on_session_end(&self, cause: &SessionEndCause) {
+        let _ = self.0.send(SessionEvent::End(cause.clone()));
+    }
+
+    fn on_session_start(&self, session_id: &str) {
+        let _ = self.0.send(SessionEvent::Start(session_id.to_string()));
+    }
+}
+
+/// Starts a mock server answering the first subscription request with the given notifications.
+async fn start_server(notifications: Vec<String>) -> MockServer {
+    let answered = AtomicBool::new(false);
+    MockServer::start(move |request| {
+        if request.contains("LS_op=add") && !answered.swap(true, Ordering::SeqCst) {
+            notifications.clone()
+        } else {
+            Vec::new()
+        }
+    })
+    .await
+}
+
+/// Connects a client subscribed to "item1", so that the mock server receives a subscription
+/// request once the session is created.
+async fn connect(
+    server: &MockServer,
+    session_recovery_timeout: u64,
+) -> (LightstreamerClient, UnboundedReceiver<SessionEvent>) {
+    let mut client = server.client();
+    client.connection_options.set_retry_delay(100).unwrap();
+    client
+        .connection_options
+        .set_session_recovery_timeout(session_recovery_timeout)
+        .unwrap();
+    let (sender, events) = mpsc::unbounded_channel();
+    client.add_listener(Box::new(SessionForwarder(sender)));
+    let subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["field1"]).unwrap();
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+    (client, events)
+}
+
+async fn next_event(events: &mut UnboundedReceiver<SessionEvent>) -> SessionEvent {
+    tokio::time::timeout(TIMEOUT, events.recv())
+        .await
+        .expect("no session event notified")
+        .expect("listener dropped")
+}
+
+#[tokio::test]
+async fn recovered_sessions_are_notified_once() {
+    let mut server = start_server(vec![drop_connection()]).await;
+    let (client, mut events) = connect(&server, 15000).await;
+
+    assert_eq!(
+        next_event(&mut events).await,
+        SessionEvent::Start("S1".to_string())
+    );
+    server.next_request("recover_session").await;
+    client.disconnect().await;
+    assert_eq!(
+        next_event(&mut events).await,
+        SessionEvent::End(SessionEndCause::Disconnected)
+    );
+    assert!(events.try_recv().is_err());
+}
+
+#[tokio::test]
+async fn sessions_not_recovered_are_replaced() {
+    let mut server = start_server(vec![drop_connection()]).await;
+    let (client, mut events) = connect(&server, 0).await;
+
+    assert_eq!(
+        next_event(&mut events).await,
+        SessionEvent::Start("S1".to_string())
+    );
+    assert_eq!(
+        next_event(&mut events).await,
+        SessionEvent::End(SessionEndCause::Lost)
+    );
+    server.next_request("create_session").await;
+    server.next_request("create_session").await;
+    assert_eq!(
+        next_event(&mut events).await,
+        SessionEvent::Start("S1".to_string())
+    );
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn sessions_closed_by_the_server_are_notified() {
+    let server = start_server(vec!["END,32,closed by admin".to_string()]).await;
+    let (client, mut events) = connect(&server, 15000).await;
+
+    assert_eq!(
+        next_event(&mut events).await,
+        SessionEvent::Start("S1".to_string())
+    );
+    assert_eq!(
+        next_event(&mut events).await,
+        SessionEvent::End(SessionEndCause::ServerClosed {
+            code: 32,
+            message: "closed by admin".to_string(),
+        })
+    );
+
+    client.disconnect().await;
+    assert!(events.try_recv().is_err());
+}
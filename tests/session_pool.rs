@@ -0,0 +1,101 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{request_param, MockServer};
+use lightstreamer_client::ls_client::{ClientStatus, LightstreamerClient};
+use lightstreamer_client::session_pool::SessionPool;
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+
+/// Creates a subscription to the given Data Adapter.
+fn subscription(data_adapter: Option<&str>) -> Subscription {
+    let mut subscription =
+        Subscription::new(SubscriptionMode::Merge, Some(["item1"]), Some(["price"])).unwrap();
+    subscription
+        .set_data_adapter(data_adapter.map(str::to_string))
+        .unwrap();
+    subscription
+}
+
+#[test]
+fn clients_are_registered_once_and_routes_need_a_client() {
+    let mut pool = SessionPool::new();
+    let client = || LightstreamerClient::new(None, None, None, None).unwrap();
+    pool.add_client("quotes", client()).unwrap();
+    pool.add_client("news", client()).unwrap();
+    assert!(pool.add_client("quotes", client()).is_err());
+    assert_eq!(pool.get_client_names(), ["quotes", "news"]);
+    assert_eq!(pool.get_default_client(), Some("quotes"));
+
+    assert!(pool.route_data_adapter("NEWS", "missing").is_err());
+    pool.route_data_adapter("NEWS", "news").unwrap();
+    assert_eq!(pool.route(&subscription(Some("NEWS"))), Some("news"));
+    assert_eq!(pool.route(&subscription(Some("QUOTES"))), Some("quotes"));
+    assert_eq!(pool.route(&subscription(None)), Some("quotes"));
+
+    pool.set_default_client(None).unwrap();
+    assert_eq!(pool.route(&subscription(None)), None);
+    assert!(pool.subscribe(subscription(None)).is_err());
+    assert!(pool.set_default_client(Some("missing")).is_err());
+}
+
+#[tokio::test]
+async fn subscriptions_are_routed_to_their_session() {
+    let mut quotes = MockServer::start(|_| Vec::new()).await;
+    let mut news = MockServer::start(|_| Vec::new()).await;
+    let mut pool = SessionPool::new();
+    pool.add_client("quotes", quotes.client()).unwrap();
+    pool.add_client("news", news.client()).unwrap();
+    pool.route_data_adapter("NEWS", "news").unwrap();
+    assert!(pool.get_status().is_all_disconnected());
+
+    pool.connect_all().await.unwrap();
+    quotes.next_request("create_session").await;
+    news.next_request("create_session").await;
+
+    assert_eq!(pool.subscribe(subscription(Some("NEWS"))).unwrap(), "news");
+    let request = news.next_request("control").await;
+    assert_eq!(request_param(&request, "LS_data_adapter"), Some("NEWS"));
+    assert_eq!(pool.subscribe(subscription(None)).unwrap(), "quotes");
+    let request = quotes.next_request("control").await;
+    assert_eq!(request_param(&request, "LS_op"), Some("add"));
+    assert_eq!(request_param(&request, "LS_data_adapter"), None);
+    assert_eq!(
+        pool.get_client("news").unwrap().get_subscriptions().len(),
+        1
+    );
+
+    pool.disconnect_all().await;
+    let status = pool.get_status();
+    assert!(status.is_all_disconnected());
+    assert_eq!(status.connected_count(), 0);
+}
+
+#[tokio::test]
+async fn aggregate_status_reports_every_client() {
+    let server = MockServer::start(|_| Vec::new()).await;
+    let mut pool = SessionPool::new();
+    pool.add_client("live", server.client()).unwrap();
+    pool.add_client(
+        "unconfigured",
+        LightstreamerClient::new(None, None, None, None).unwrap(),
+    )
+    .unwrap();
+
+    let error = pool.connect_all().await.unwrap_err();
+    assert!(error.to_string().contains("client 'unconfigured'"));
+    let status = pool.get_status();
+    assert!(!matches!(
+        status.get("live"),
+        Some(ClientStatus::Disconnected(_))
+    ));
+    assert!(matches!(
+        status.get("unconfigured"),
+        Some(ClientStatus::Disconnected(_))
+    ));
+    assert!(!status.is_all_connected());
+    assert!(!status.is_all_disconnected());
+    assert_eq!(status.get("missing"), None);
+
+    pool.disconnect_all().await;
+}
@@ -0,0 +1,111 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{drop_connection, next_update, request_param, MockServer, UpdateForwarder};
+use lightstreamer_client::item_update::ItemUpdate;
+use lightstreamer_client::ls_client::LightstreamerClient;
+use lightstreamer_client::subscription::{Snapshot, Subscription, SubscriptionMode};
+use std::sync::atomic::{AtomicBool, Ordering};
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+/// Connects a client subscribed to "item1" in MERGE mode with snapshot.
+async fn connect(server: &MockServer) -> (LightstreamerClient, UnboundedReceiver<ItemUpdate>) {
+    let mut client = server.client();
+    client.connection_options.set_retry_delay(100).unwrap();
+    let (sender, updates) = mpsc::unbounded_channel();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["field1"]).unwrap();
+    subscription
+        .set_requested_snapshot(Some(Snapshot::Yes))
+        .unwrap();
+    subscription.add_listener(Box::new(UpdateForwarder(sender)));
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+    (client, updates)
+}
+
+#[tokio::test]
+async fn notifications_resent_on_recovery_are_skipped() {
+    let mut server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec![
+                "SUBOK,1,1,1".to_string(),
+                "U,1,1,a".to_string(),
+                "U,1,1,b".to_string(),
+                drop_connection(),
+            ]
+        } else if request.starts_with("recover_session") {
+            // The server resends the last notification the client already received.
+            let recovery_from: u64 = request_param(request, "LS_recovery_from")
+                .unwrap()
+                .parse()
+                .unwrap();
+            vec![
+                format!("PROG,{}", recovery_from - 1),
+                "U,1,1,b".to_string(),
+                "U,1,1,c".to_string(),
+            ]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let (client, mut updates) = connect(&server).await;
+
+    let request = server.next_request("recover_session").await;
+    assert_eq!(request_param(&request, "LS_recovery_from"), Some("3"));
+    let values: Vec<_> = [
+        next_update(&mut updates).await,
+        next_update(&mut updates).await,
+        next_update(&mut updates).await,
+    ]
+    .iter()
+    .map(|update| update.get_value("field1").unwrap().to_string())
+    .collect();
+    assert_eq!(values, ["a", "b", "c"]);
+    assert!(updates.try_recv().is_err());
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn snapshots_are_refreshed_when_notifications_are_lost() {
+    let subscribed = AtomicBool::new(false);
+    let mut server = MockServer::start(move |request| {
+        if request.contains("LS_op=add") {
+            if !subscribed.swap(true, Ordering::SeqCst) {
+                vec![
+                    "SUBOK,1,1,1".to_string(),
+                    "U,1,1,a".to_string(),
+                    drop_connection(),
+                ]
+            } else {
+                vec!["SUBOK,1,1,1".to_string(), "U,1,1,z".to_string()]
+            }
+        } else if request.starts_with("recover_session") {
+            // The server can't resend a notification the client never received.
+            let recovery_from: u64 = request_param(request, "LS_recovery_from")
+                .unwrap()
+                .parse()
+                .unwrap();
+            vec![format!("PROG,{}", recovery_from + 1)]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let (client, mut updates) = connect(&server).await;
+
+    let update = next_update(&mut updates).await;
+    assert!(update.is_snapshot());
+    assert!(!update.is_snapshot_refresh());
+    server.next_request("recover_session").await;
+    server.next_request("create_session").await;
+    let update = next_update(&mut updates).await;
+    assert_eq!(update.get_value("field1"), Some("z"));
+    assert!(update.is_snapshot());
+    assert!(update.is_snapshot_refresh());
+
+    client.disconnect().await;
+}
@@ -0,0 +1,85 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::client_message_listener::ClientMessageListener;
+use tokio::sync::mpsc::{self, UnboundedSender};
+
+/// Listener forwarding the aborted messages to the test.
+#[derive(Debug)]
+struct AbortForwarder(UnboundedSender<String>);
+
+impl ClientMessageListener for AbortForwarder {
+    fn on_abort(&self, message: &str, _sent_on_network: bool) {
+        let _ = self.0.send(message.to_string());
+    }
+}
+
+#[tokio::test]
+async fn connecting_twice_is_refused() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let client = server.client();
+    client.connect().await.unwrap();
+    assert!(client.connect().await.is_err());
+    assert!(client
+        .connect_with_deadline(std::time::Duration::from_secs(1))
+        .await
+        .is_err());
+    server.next_request("create_session").await;
+
+    client.disconnect().await;
+    client.connect().await.unwrap();
+    server.next_request("create_session").await;
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn messages_sent_while_disconnected_must_be_enqueued() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let client = server.client();
+    let (sender, mut aborted) = mpsc::unbounded_channel();
+
+    let result = client.send_message(
+        "hello",
+        None,
+        None,
+        Some(Box::new(AbortForwarder(sender.clone()))),
+        false,
+    );
+    assert!(result.is_err());
+    client
+        .send_message(
+            "queued",
+            None,
+            None,
+            Some(Box::new(AbortForwarder(sender))),
+            true,
+        )
+        .unwrap();
+
+    // The listener events are dispatched once the client is started.
+    client.connect().await.unwrap();
+    let message = tokio::time::timeout(TIMEOUT, aborted.recv())
+        .await
+        .expect("message not aborted")
+        .unwrap();
+    assert_eq!(message, "hello");
+    let request = server.next_request("msg").await;
+    assert!(request.contains("LS_message=queued"));
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn handles_refuse_the_same_misuse() {
+    let server = MockServer::start(|_| Vec::new()).await;
+    let handle = server.client().spawn();
+    assert!(handle
+        .send_message("hello", None, None, None, false)
+        .await
+        .is_err());
+    handle.connect().await.unwrap();
+    assert!(handle.connect().await.is_err());
+    handle.disconnect().await;
+}
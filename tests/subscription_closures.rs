@@ -0,0 +1,110 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{request_param, MockServer, TIMEOUT};
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use tokio::sync::mpsc;
+
+/// Creates a subscription to a single item.
+fn subscription() -> Subscription {
+    Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["price"]).unwrap()
+}
+
+#[tokio::test]
+async fn update_closure_receives_the_updates() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec![
+                "SUBOK,1,1,1".to_string(),
+                "U,1,1,10".to_string(),
+                "U,1,1,11".to_string(),
+            ]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let (sender, mut prices) = mpsc::unbounded_channel();
+    let mut subscription = subscription();
+    let mut count = 0;
+    subscription.on_update(move |update| {
+        count += 1;
+        let price = update.get_value("price").unwrap().to_string();
+        let _ = sender.send((count, price));
+    });
+    subscription.on_error(|code, message| panic!("unexpected error {} {:?}", code, message));
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    for expected in [(1, "10"), (2, "11")] {
+        let received = tokio::time::timeout(TIMEOUT, prices.recv()).await.unwrap();
+        assert_eq!(received, Some((expected.0, expected.1.to_string())));
+    }
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn error_closure_receives_the_refusal_of_the_subscription() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            let request_id = request_param(request, "LS_reqId").unwrap();
+            vec![format!("REQERR,{},21,Bad Group name", request_id)]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let (sender, mut errors) = mpsc::unbounded_channel();
+    let mut subscription = subscription();
+    subscription.on_update(|_| panic!("unexpected update"));
+    subscription.on_error(move |code, message| {
+        let _ = sender.send((code, message.map(str::to_string)));
+    });
+    let token = client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let error = tokio::time::timeout(TIMEOUT, errors.recv()).await.unwrap();
+    assert_eq!(error, Some((21, Some("Bad Group name".to_string()))));
+    assert!(!client
+        .with_subscription(token, |subscription| subscription.is_subscribed())
+        .unwrap());
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn closures_are_removed_through_the_returned_token() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec!["SUBOK,1,1,1".to_string(), "U,1,1,10".to_string()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let (sender, mut prices) = mpsc::unbounded_channel();
+    let mut subscription = subscription();
+    let listener = subscription.on_update(move |update| {
+        let _ = sender.send(update.get_value("price").unwrap().to_string());
+    });
+    subscription.on_error(|code, message| panic!("unexpected error {} {:?}", code, message));
+    let token = client.subscribe(subscription);
+    client.connect().await.unwrap();
+    let price = tokio::time::timeout(TIMEOUT, prices.recv()).await.unwrap();
+    assert_eq!(price.as_deref(), Some("10"));
+
+    client
+        .with_subscription(token, |subscription| {
+            subscription.remove_listener_by_token(listener).unwrap();
+            assert!(subscription.remove_listener_by_token(listener).is_err());
+            assert_eq!(subscription.get_listeners().len(), 1);
+        })
+        .unwrap();
+    // The closure is dropped along with its listener, closing the channel.
+    let price = tokio::time::timeout(TIMEOUT, prices.recv()).await.unwrap();
+    assert_eq!(price, None);
+    client.disconnect().await;
+}
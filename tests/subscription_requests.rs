@@ -0,0 +1,270 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{next_update, request_param, subscription_server, MockServer, TIMEOUT};
+use lightstreamer_client::item_update::ItemUpdate;
+use lightstreamer_client::subscription::{BufferSize, Snapshot, Subscription, SubscriptionMode};
+use lightstreamer_client::subscription_listener::SubscriptionListener;
+use tokio::sync::mpsc::{self, UnboundedReceiver, UnboundedSender};
+
+/// Listener forwarding whether the subscription is subscribed to after each change.
+struct SubscribedForwarder(UnboundedSender<bool>);
+
+impl SubscriptionListener for SubscribedForwarder {
+    fn on_subscription(&mut self) {
+        let _ = self.0.send(true);
+    }
+
+    fn on_unsubscription(&mut self) {
+        let _ = self.0.send(false);
+    }
+}
+
+/// Adds a closure forwarding the updates of the subscription to a channel.
+fn forward_updates(subscription: &mut Subscription) -> UnboundedReceiver<ItemUpdate> {
+    let (sender, updates) = mpsc::unbounded_channel();
+    subscription.on_update(move |update| {
+        let _ = sender.send(update.clone());
+    });
+    updates
+}
+
+async fn next_change(subscribed: &mut UnboundedReceiver<bool>) -> bool {
+    tokio::time::timeout(TIMEOUT, subscribed.recv())
+        .await
+        .unwrap()
+        .unwrap()
+}
+
+#[tokio::test]
+async fn item_groups_and_field_schemas_are_requested_by_name() {
+    let mut server = subscription_server("SUBOK,1,1,2", &["U,1,1,ACME|12.5", "U,1,1,|12.6"]).await;
+    let client = server.client();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["price"]).unwrap();
+    subscription
+        .set_item_group("portfolio".to_string())
+        .unwrap();
+    subscription.set_field_schema("quote".to_string()).unwrap();
+    assert_eq!(subscription.get_items(), None);
+    let mut updates = forward_updates(&mut subscription);
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let request = server.next_request("control").await;
+    assert_eq!(request_param(&request, "LS_group"), Some("portfolio"));
+    assert_eq!(request_param(&request, "LS_schema"), Some("quote"));
+
+    // The items of an item group and the fields of a field schema are only known by position.
+    let first = next_update(&mut updates).await;
+    assert_eq!(first.get_item_pos(), 1);
+    assert_eq!(first.get_item_name(), None);
+    assert_eq!(first.get_value("1"), Some("ACME"));
+    assert_eq!(first.get_value("2"), Some("12.5"));
+    let second = next_update(&mut updates).await;
+    assert_eq!(second.get_value("1"), Some("ACME"));
+    assert_eq!(second.get_value("2"), Some("12.6"));
+    assert!(!second.is_value_changed("1"));
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn snapshot_lengths_are_requested_and_end_with_the_snapshot() {
+    let mut server = subscription_server(
+        "SUBOK,1,1,1",
+        &["U,1,1,first", "U,1,1,second", "EOS,1,1", "U,1,1,live"],
+    )
+    .await;
+    let client = server.client();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Distinct, "news", ["headline"]).unwrap();
+    subscription
+        .set_requested_snapshot(Some(Snapshot::Number(2)))
+        .unwrap();
+    let mut updates = forward_updates(&mut subscription);
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let request = server.next_request("control").await;
+    assert_eq!(request_param(&request, "LS_snapshot"), Some("2"));
+
+    for (headline, is_snapshot) in [("first", true), ("second", true), ("live", false)] {
+        let update = next_update(&mut updates).await;
+        assert_eq!(update.get_value("headline"), Some(headline));
+        assert_eq!(update.is_snapshot(), is_snapshot, "{}", headline);
+    }
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn requested_buffer_sizes_are_sent() {
+    let mut server = subscription_server("SUBOK,1,1,1", &[]).await;
+    let client = server.client();
+    for size in [
+        None,
+        Some(BufferSize::Size(10)),
+        Some(BufferSize::Unlimited),
+    ] {
+        let mut subscription =
+            Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["price"]).unwrap();
+        subscription.set_requested_buffer_size(size).unwrap();
+        client.subscribe(subscription);
+    }
+    client.connect().await.unwrap();
+
+    for expected in [None, Some("10"), Some("unlimited")] {
+        let request = server.next_request("control").await;
+        assert_eq!(
+            request_param(&request, "LS_requested_buffer_size"),
+            expected
+        );
+    }
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn unsubscribing_deletes_and_removes_the_subscription() {
+    let mut server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            let subscription_id = request_param(request, "LS_subId").unwrap();
+            vec![
+                format!("SUBOK,{},1,1", subscription_id),
+                format!("U,{},1,{}", subscription_id, subscription_id),
+            ]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let mut first =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["price"]).unwrap();
+    let (sender, mut subscribed) = mpsc::unbounded_channel();
+    first.add_listener(Box::new(SubscribedForwarder(sender)));
+    let first = client.subscribe(first);
+    let mut second =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item2", ["price"]).unwrap();
+    let mut second_updates = forward_updates(&mut second);
+    client.subscribe(second);
+    client.connect().await.unwrap();
+    server.next_request("control").await;
+    server.next_request("control").await;
+    assert!(next_change(&mut subscribed).await);
+    next_update(&mut second_updates).await;
+
+    client.unsubscribe(first).unwrap();
+    let request = server.next_request("control").await;
+    assert_eq!(request_param(&request, "LS_op"), Some("delete"));
+    assert_eq!(request_param(&request, "LS_subId"), Some("1"));
+    assert!(!next_change(&mut subscribed).await);
+    assert!(client.unsubscribe(first).is_err());
+    assert!(client.with_subscription(first, |_| ()).is_err());
+
+    // The other subscriptions keep receiving their updates, also the ones added afterwards.
+    let mut third =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item3", ["price"]).unwrap();
+    let mut third_updates = forward_updates(&mut third);
+    client.subscribe(third);
+    let update = next_update(&mut third_updates).await;
+    assert_eq!(update.get_value("price"), Some("3"));
+    {
+        let subscriptions = client.get_subscriptions();
+        let items: Vec<_> = subscriptions
+            .iter()
+            .map(|subscription| subscription.get_items().unwrap()[0].as_str())
+            .collect();
+        assert_eq!(items, ["item2", "item3"]);
+    }
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn items_are_updated_through_the_token_after_other_unsubscriptions() {
+    let mut server = subscription_server("SUBOK,1,1,1", &[]).await;
+    let client = server.client();
+    let first = client.subscribe(
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["price"]).unwrap(),
+    );
+    let second = client.subscribe(
+        Subscription::new_single_item(SubscriptionMode::Merge, "item2", ["price"]).unwrap(),
+    );
+    client.connect().await.unwrap();
+    server.next_request("control").await;
+    server.next_request("control").await;
+
+    client.unsubscribe(first).unwrap();
+    server.next_request("control").await;
+    client.update_items(second, ["item3"]).unwrap();
+    let request = server.next_request("control").await;
+    assert_eq!(request_param(&request, "LS_op"), Some("delete"));
+    assert_eq!(request_param(&request, "LS_subId"), Some("2"));
+    let request = server.next_request("control").await;
+    assert_eq!(request_param(&request, "LS_op"), Some("add"));
+    assert_eq!(request_param(&request, "LS_group"), Some("item3"));
+    {
+        let subscriptions = client.get_subscriptions();
+        assert_eq!(subscriptions.len(), 1);
+        assert_eq!(subscriptions[0].get_items().unwrap(), &["item3"]);
+    }
+
+    // A stale token doesn't fall back on whichever subscription took its place.
+    assert!(client.update_items(first, ["item4"]).is_err());
+    assert!(client.update_fields(first, ["bid"]).is_err());
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn max_frequency_changes_reconfigure_the_subscription() {
+    let mut server = subscription_server("SUBOK,1,1,1", &[]).await;
+    let client = server.client();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["price"]).unwrap();
+    let (sender, mut subscribed) = mpsc::unbounded_channel();
+    subscription.add_listener(Box::new(SubscribedForwarder(sender)));
+    let token = client.subscribe(subscription);
+    client.connect().await.unwrap();
+    server.next_request("control").await;
+    assert!(next_change(&mut subscribed).await);
+
+    client
+        .with_subscription(token, |subscription| {
+            subscription.set_requested_max_frequency(Some(2.5))
+        })
+        .unwrap()
+        .unwrap();
+    let request = server.next_request("control").await;
+    assert_eq!(request_param(&request, "LS_op"), Some("reconf"));
+    assert_eq!(request_param(&request, "LS_subId"), Some("1"));
+    assert_eq!(
+        request_param(&request, "LS_requested_max_frequency"),
+        Some("2.5")
+    );
+
+    client
+        .with_subscription(token, |subscription| {
+            subscription.set_requested_max_frequency(Some(f64::INFINITY))
+        })
+        .unwrap()
+        .unwrap();
+    let request = server.next_request("control").await;
+    assert_eq!(request_param(&request, "LS_op"), Some("reconf"));
+    assert_eq!(request_param(&request, "LS_subId"), Some("1"));
+    assert_eq!(
+        request_param(&request, "LS_requested_max_frequency"),
+        Some("unlimited")
+    );
+    client.disconnect().await;
+}
+
+#[test]
+fn max_frequency_is_refused_for_raw_subscriptions() {
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Raw, "item1", ["price"]).unwrap();
+    assert!(subscription.set_requested_max_frequency(Some(1.0)).is_err());
+    assert!(subscription
+        .set_requested_max_frequency(Some(f64::INFINITY))
+        .is_err());
+    subscription.set_requested_max_frequency(None).unwrap();
+    assert_eq!(subscription.get_requested_max_frequency(), None);
+}
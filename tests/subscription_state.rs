@@ -0,0 +1,194 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{subscription_server, TIMEOUT};
+use lightstreamer_client::ls_client::LightstreamerClient;
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode, SubscriptionToken};
+use std::collections::BTreeMap;
+use std::time::Duration;
+use tokio::sync::mpsc::{self, UnboundedReceiver};
+
+/// Subscribes through the client, returning the token of the subscription and a channel
+/// receiving a unit for every update.
+fn subscribe(
+    client: &LightstreamerClient,
+    mut subscription: Subscription,
+) -> (SubscriptionToken, UnboundedReceiver<()>) {
+    let (sender, updates) = mpsc::unbounded_channel();
+    subscription.on_update(move |_| {
+        let _ = sender.send(());
+    });
+    (client.subscribe(subscription), updates)
+}
+
+/// Waits for the given number of updates.
+async fn wait_updates(updates: &mut UnboundedReceiver<()>, count: usize) {
+    for _ in 0..count {
+        tokio::time::timeout(TIMEOUT, updates.recv())
+            .await
+            .unwrap()
+            .unwrap();
+    }
+}
+
+fn values(values: &[(usize, &str)]) -> BTreeMap<usize, String> {
+    values
+        .iter()
+        .map(|(field_pos, value)| (*field_pos, value.to_string()))
+        .collect()
+}
+
+#[tokio::test]
+async fn snapshot_holds_the_latest_values_of_each_item() {
+    let server =
+        subscription_server("SUBOK,1,2,2", &["U,1,1,10|9", "U,1,2,20|19", "U,1,1,11|"]).await;
+    let client = server.client();
+    let subscription = Subscription::new(
+        SubscriptionMode::Merge,
+        Some(["item1", "item2"]),
+        Some(["price", "bid"]),
+    )
+    .unwrap();
+    let (token, mut updates) = subscribe(&client, subscription);
+    client.connect().await.unwrap();
+
+    wait_updates(&mut updates, 3).await;
+    let snapshot = client
+        .with_subscription(token, |subscription| subscription.get_snapshot())
+        .unwrap();
+    assert_eq!(snapshot.fields, ["price", "bid"]);
+    assert_eq!(
+        snapshot.items,
+        BTreeMap::from([
+            (1, values(&[(1, "11"), (2, "9")])),
+            (2, values(&[(1, "20"), (2, "19")])),
+        ])
+    );
+    assert!(snapshot.keys.is_empty());
+    assert_eq!(
+        client
+            .with_subscription(token, |subscription| subscription.get_value(1, 1).cloned())
+            .unwrap(),
+        Some("11".to_string())
+    );
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn snapshot_of_command_subscriptions_follows_the_keys() {
+    let server = subscription_server(
+        "SUBOK,1,1,3",
+        &[
+            "U,1,1,a|ADD|1",
+            "U,1,1,b|ADD|2",
+            "U,1,1,a|UPDATE|3",
+            "U,1,1,b|DELETE|#",
+        ],
+    )
+    .await;
+    let client = server.client();
+    let subscription = Subscription::new_single_item(
+        SubscriptionMode::Command,
+        "item1",
+        ["key", "command", "qty"],
+    )
+    .unwrap();
+    let (token, mut updates) = subscribe(&client, subscription);
+    client.connect().await.unwrap();
+
+    wait_updates(&mut updates, 4).await;
+    let snapshot = client
+        .with_subscription(token, |subscription| subscription.get_snapshot())
+        .unwrap();
+    assert!(snapshot.items.is_empty());
+    assert_eq!(
+        snapshot.keys,
+        BTreeMap::from([(
+            1,
+            BTreeMap::from([(
+                "a".to_string(),
+                values(&[(1, "a"), (2, "UPDATE"), (3, "3")])
+            )])
+        )])
+    );
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn distinct_history_keeps_the_latest_updates_of_each_item() {
+    let server =
+        subscription_server("SUBOK,1,2,1", &["U,1,1,a", "U,1,1,b", "U,1,2,x", "U,1,1,c"]).await;
+    let client = server.client();
+    let mut subscription = Subscription::new(
+        SubscriptionMode::Distinct,
+        Some(["news1", "news2"]),
+        Some(["headline"]),
+    )
+    .unwrap();
+    subscription.set_history_length(2).unwrap();
+    let (token, mut updates) = subscribe(&client, subscription);
+    client.connect().await.unwrap();
+
+    wait_updates(&mut updates, 4).await;
+    let history = |subscription: &Subscription, item_pos| {
+        subscription
+            .get_history(item_pos)
+            .map(|update| update.get_value("headline").unwrap().to_string())
+            .collect::<Vec<_>>()
+    };
+    client
+        .with_subscription(token, |subscription| {
+            assert_eq!(history(subscription, 1), ["b", "c"]);
+            assert_eq!(history(subscription, 2), ["x"]);
+            assert_eq!(history(subscription, 3), Vec::<String>::new());
+
+            // Reducing the length discards the oldest updates.
+            subscription.set_history_length(1).unwrap();
+            assert_eq!(history(subscription, 1), ["c"]);
+        })
+        .unwrap();
+    client.disconnect().await;
+}
+
+#[test]
+fn history_is_only_available_for_distinct_subscriptions() {
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["price"]).unwrap();
+    assert!(subscription.set_history_length(5).is_err());
+    assert!(subscription.set_history_length(0).is_ok());
+    assert_eq!(subscription.get_history_length(), 0);
+}
+
+#[tokio::test]
+async fn first_update_can_be_awaited() {
+    let server = subscription_server("SUBOK,1,1,1", &["U,1,1,10", "U,1,1,11"]).await;
+    let client = server.client();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["price"]).unwrap();
+    let first_update = subscription.wait_first_update(TIMEOUT);
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let update = first_update.await.unwrap();
+    assert_eq!(update.get_value("price"), Some("10"));
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn waiting_for_the_first_update_times_out() {
+    let server = subscription_server("SUBOK,1,1,1", &[]).await;
+    let client = server.client();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["price"]).unwrap();
+    let first_update = subscription.wait_first_update(Duration::from_millis(100));
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let error = tokio::time::timeout(TIMEOUT, first_update)
+        .await
+        .unwrap()
+        .unwrap_err();
+    assert_eq!(error.to_string(), "No update received within 100ms");
+    client.disconnect().await;
+}
@@ -0,0 +1,103 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::client_metrics::SubscriptionStats;
+use lightstreamer_client::ls_client::LightstreamerClient;
+use lightstreamer_client::subscription::{
+    Snapshot, Subscription, SubscriptionMode, SubscriptionToken,
+};
+use std::time::Duration;
+
+/// Waits until the stats of the given subscription of the client satisfy the given condition.
+async fn wait_stats(
+    client: &LightstreamerClient,
+    token: SubscriptionToken,
+    condition: impl Fn(&SubscriptionStats) -> bool,
+) -> SubscriptionStats {
+    tokio::time::timeout(TIMEOUT, async {
+        loop {
+            let stats = client
+                .with_subscription(token, |subscription| subscription.get_stats())
+                .unwrap();
+            if condition(&stats) {
+                return stats;
+            }
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("stats not reached")
+}
+
+#[tokio::test]
+async fn subscription_counters_are_tracked() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            [
+                "SUBOK,1,1,1",
+                "CONF,1,2.5,filtered",
+                "U,1,1,a",
+                "U,1,1,b",
+                "OV,1,1,3",
+            ]
+            .map(str::to_string)
+            .to_vec()
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["price"]).unwrap();
+    subscription
+        .set_requested_snapshot(Some(Snapshot::Yes))
+        .unwrap();
+    assert_eq!(
+        subscription.get_stats(),
+        SubscriptionStats {
+            updates_received: 0,
+            snapshot_updates: 0,
+            lost_updates: 0,
+            last_update_at: None,
+            real_max_frequency: None,
+        }
+    );
+    let token = client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let stats = wait_stats(&client, token, |stats| stats.lost_updates > 0).await;
+    assert_eq!(stats.updates_received, 2);
+    assert_eq!(stats.snapshot_updates, 1);
+    assert_eq!(stats.lost_updates, 3);
+    assert!(stats.last_update_at.is_some());
+    assert_eq!(stats.real_max_frequency, Some(2.5));
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn unlimited_frequency_is_reported_as_infinite() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            ["SUBOK,1,1,1", "CONF,1,unlimited,unfiltered"]
+                .map(str::to_string)
+                .to_vec()
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["price"]).unwrap();
+    let token = client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let stats = wait_stats(&client, token, |stats| stats.real_max_frequency.is_some()).await;
+    assert_eq!(stats.real_max_frequency, Some(f64::INFINITY));
+
+    client.disconnect().await;
+}
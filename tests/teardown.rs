@@ -0,0 +1,134 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::client_listener::ClientListener;
+use lightstreamer_client::item_update::ItemUpdate;
+use lightstreamer_client::ls_client::{ClientStatus, LightstreamerClient};
+use lightstreamer_client::subscription::{DispatchMode, Subscription, SubscriptionMode};
+use lightstreamer_client::subscription_listener::SubscriptionListener;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Slow listener recording the status changes.
+#[derive(Debug)]
+struct StatusRecorder(Arc<Mutex<Vec<String>>>);
+
+impl ClientListener for StatusRecorder {
+    fn on_status_change(&self, status: &str) {
+        std::thread::sleep(Duration::from_millis(20));
+        self.0.lock().unwrap().push(status.to_string());
+    }
+}
+
+/// Slow pooled listener counting the updates.
+struct UpdateCounter(Arc<AtomicUsize>);
+
+impl SubscriptionListener for UpdateCounter {
+    fn on_item_update(&self, _update: &ItemUpdate) {
+        std::thread::sleep(Duration::from_millis(5));
+        self.0.fetch_add(1, Ordering::SeqCst);
+    }
+}
+
+/// Connects a client recording its status changes and waits until it is connected.
+async fn connect(server: &MockServer) -> (LightstreamerClient, Arc<Mutex<Vec<String>>>) {
+    let client = server.client();
+    let statuses = Arc::new(Mutex::new(Vec::new()));
+    client.add_listener(Box::new(StatusRecorder(Arc::clone(&statuses))));
+    client.connect().await.unwrap();
+    wait_connected(&client).await;
+    (client, statuses)
+}
+
+async fn wait_connected(client: &LightstreamerClient) {
+    tokio::time::timeout(TIMEOUT, async {
+        while !matches!(client.get_status(), ClientStatus::Connected(_)) {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("client not connected");
+}
+
+#[tokio::test]
+async fn pending_notifications_are_delivered_before_returning() {
+    let server = MockServer::start(|_| Vec::new()).await;
+    let (client, statuses) = connect(&server).await;
+
+    client.disconnect_and_wait().await;
+    assert_eq!(
+        statuses.lock().unwrap().last().map(String::as_str),
+        Some("DISCONNECTED")
+    );
+}
+
+#[tokio::test]
+async fn pooled_workers_are_joined() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            let mut notifications = vec!["SUBOK,1,1,1".to_string()];
+            notifications.extend((1..=10).map(|price| format!("U,1,1,{}", price)));
+            notifications
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let updates = Arc::new(AtomicUsize::new(0));
+    let mut subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["price"]).unwrap();
+    subscription
+        .set_dispatch_mode(DispatchMode::Pooled(2))
+        .unwrap();
+    subscription.add_pooled_listener(Arc::new(UpdateCounter(Arc::clone(&updates))));
+    let token = client.subscribe(subscription);
+    client.connect().await.unwrap();
+    tokio::time::timeout(TIMEOUT, async {
+        while client
+            .with_subscription(token, |subscription| {
+                subscription.get_stats().updates_received
+            })
+            .unwrap()
+            < 10
+        {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("updates not received");
+
+    client.disconnect_and_wait().await;
+    assert_eq!(updates.load(Ordering::SeqCst), 10);
+}
+
+#[tokio::test]
+async fn notifications_resume_on_the_next_connection() {
+    let server = MockServer::start(|_| Vec::new()).await;
+    let (client, statuses) = connect(&server).await;
+    client.disconnect_and_wait().await;
+    statuses.lock().unwrap().clear();
+
+    client.connect().await.unwrap();
+    wait_connected(&client).await;
+    client.disconnect_and_wait().await;
+    let statuses = statuses.lock().unwrap();
+    assert_eq!(statuses.first().map(String::as_str), Some("CONNECTING"));
+    assert_eq!(statuses.last().map(String::as_str), Some("DISCONNECTED"));
+}
+
+#[tokio::test]
+async fn handles_wait_for_the_client_tasks() {
+    let server = MockServer::start(|_| Vec::new()).await;
+    let (client, statuses) = connect(&server).await;
+    let handle = client.spawn();
+
+    handle.disconnect_and_wait().await;
+    assert_eq!(
+        statuses.lock().unwrap().last().map(String::as_str),
+        Some("DISCONNECTED")
+    );
+}
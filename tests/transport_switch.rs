@@ -0,0 +1,167 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{request_param, MockServer, TIMEOUT};
+use lightstreamer_client::ls_client::{
+    ClientStatus, ConnectionType, LightstreamerClient, Transport,
+};
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Waits until the client is connected with the given connection type.
+async fn wait_connected(client: &LightstreamerClient, connection_type: ConnectionType) {
+    let expected = ClientStatus::Connected(connection_type);
+    tokio::time::timeout(TIMEOUT, async {
+        while client.get_status() != expected {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .unwrap_or_else(|_| panic!("client not {}", expected));
+}
+
+#[tokio::test]
+async fn polls_are_bound_on_the_same_connection() {
+    let mut server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec!["SUBOK,1,1,1".to_string(), "LOOP,0".to_string()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let mut client = server.client();
+    client
+        .connection_options
+        .set_forced_transport(Some(Transport::WsPolling));
+    client.connection_options.set_idle_timeout(50).unwrap();
+    client.connection_options.set_polling_interval(50).unwrap();
+    let subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["field1"]).unwrap();
+    client.subscribe(subscription);
+    client.connect().await.unwrap();
+
+    let request = server.next_request("create_session").await;
+    assert_eq!(request_param(&request, "LS_polling"), Some("true"));
+    assert_eq!(request_param(&request, "LS_polling_millis"), Some("50"));
+    assert_eq!(request_param(&request, "LS_idle_millis"), Some("50"));
+    assert_eq!(request_param(&request, "LS_keepalive_millis"), None);
+    wait_connected(&client, ConnectionType::WsPolling).await;
+    let request = server.next_request("bind_session").await;
+    assert_eq!(request_param(&request, "LS_session"), Some("S1"));
+    assert_eq!(request_param(&request, "LS_polling"), Some("true"));
+    assert_eq!(client.get_metrics().reconnections, 0);
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn streaming_loops_rebind_the_session_at_once() {
+    let looped = AtomicBool::new(false);
+    let mut server = MockServer::start(move |request| {
+        if request.contains("LS_op=add") && !looped.swap(true, Ordering::SeqCst) {
+            vec!["SUBOK,1,1,1".to_string(), "LOOP,0".to_string()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let mut client = server.client();
+    // A rebind must neither wait for the retry delay nor end the session.
+    client
+        .connection_options
+        .set_session_recovery_timeout(0)
+        .unwrap();
+    client
+        .connection_options
+        .set_first_retry_max_delay(3_600_000)
+        .unwrap();
+    let statuses = Arc::new(Mutex::new(Vec::new()));
+    let recorded = Arc::clone(&statuses);
+    client.on_status_change(move |status| recorded.lock().unwrap().push(status.to_string()));
+    let subscription =
+        Subscription::new_single_item(SubscriptionMode::Merge, "item1", ["field1"]).unwrap();
+    let token = client.subscribe(subscription);
+    client.connect().await.unwrap();
+    server.next_request("create_session").await;
+
+    let request = server.next_request("bind_session").await;
+    assert_eq!(request_param(&request, "LS_session"), Some("S1"));
+    assert_eq!(request_param(&request, "LS_recovery_from"), None);
+    assert_eq!(request_param(&request, "LS_polling"), None);
+    wait_connected(&client, ConnectionType::WsStreaming).await;
+    assert_eq!(client.get_metrics().reconnections, 0);
+    assert_eq!(
+        *statuses.lock().unwrap(),
+        ["CONNECTING", "CONNECTED:WS-STREAMING"]
+    );
+    assert!(client
+        .with_subscription(token, |subscription| subscription.is_subscribed())
+        .unwrap());
+
+    client.disconnect().await;
+}
+
+#[tokio::test]
+async fn transport_changes_rebind_the_session() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let mut client = server.client();
+    client.connect().await.unwrap();
+    server.next_request("create_session").await;
+    wait_connected(&client, ConnectionType::WsStreaming).await;
+
+    client
+        .connection_options
+        .set_forced_transport(Some(Transport::WsPolling));
+    let request = server.next_request("bind_session").await;
+    assert_eq!(request_param(&request, "LS_session"), Some("S1"));
+    assert_eq!(request_param(&request, "LS_polling"), Some("true"));
+    wait_connected(&client, ConnectionType::WsPolling).await;
+    assert_eq!(client.get_metrics().reconnections, 0);
+
+    client
+        .connection_options
+        .set_forced_transport(Some(Transport::WsStreaming));
+    let request = server.next_request("bind_session").await;
+    assert_eq!(request_param(&request, "LS_session"), Some("S1"));
+    assert_eq!(request_param(&request, "LS_polling"), None);
+    wait_connected(&client, ConnectionType::WsStreaming).await;
+    assert_eq!(client.get_metrics().reconnections, 0);
+
+    client.disconnect().await;
+}
+
+// HTTP transports are supported with the `reqwest` feature.
+#[cfg(not(feature = "reqwest"))]
+#[tokio::test]
+async fn unsupported_transports_keep_the_current_connection() {
+    let mut server = MockServer::start(|_| Vec::new()).await;
+    let mut client = server.client();
+    client.connect().await.unwrap();
+    server.next_request("create_session").await;
+    wait_connected(&client, ConnectionType::WsStreaming).await;
+
+    client
+        .connection_options
+        .set_forced_transport(Some(Transport::HttpPolling));
+    client
+        .connection_options
+        .set_requested_max_bandwidth(Some(10.0))
+        .unwrap();
+    // The next request is the constrain one, sent on the same connection.
+    let request = server.next_request("").await;
+    assert!(
+        request.starts_with("control"),
+        "unexpected request: {}",
+        request
+    );
+    assert_eq!(
+        client.get_status(),
+        ClientStatus::Connected(ConnectionType::WsStreaming)
+    );
+
+    client.disconnect().await;
+}
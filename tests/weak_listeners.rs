@@ -0,0 +1,114 @@
+#![cfg(feature = "runtime-tokio")]
+
+mod common;
+
+use common::{MockServer, TIMEOUT};
+use lightstreamer_client::client_listener::ClientListener;
+use lightstreamer_client::subscription::{Subscription, SubscriptionMode};
+use lightstreamer_client::subscription_listener::SubscriptionListener;
+use lightstreamer_client::weak_listener::WeakSubscriptionListener;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Listener recording the status changes of the client.
+#[derive(Debug, Default)]
+struct StatusRecorder(Mutex<Vec<String>>);
+
+impl ClientListener for StatusRecorder {
+    fn on_status_change(&self, status: &str) {
+        self.0.lock().unwrap().push(status.to_string());
+    }
+}
+
+/// Listener counting the subscriptions.
+#[derive(Default)]
+struct SubscriptionCounter {
+    subscriptions: u32,
+}
+
+impl SubscriptionListener for SubscriptionCounter {
+    fn on_subscription(&mut self) {
+        self.subscriptions += 1;
+    }
+}
+
+/// Waits until the given condition holds.
+async fn wait_until(mut condition: impl FnMut() -> bool) {
+    tokio::time::timeout(TIMEOUT, async {
+        while !condition() {
+            tokio::time::sleep(Duration::from_millis(10)).await;
+        }
+    })
+    .await
+    .expect("condition not met");
+}
+
+#[test]
+fn weak_subscription_listener_forwards_until_dropped() {
+    let listener = Arc::new(Mutex::new(SubscriptionCounter::default()));
+    let mut weak = WeakSubscriptionListener::new(&listener);
+    assert!(!weak.is_expired());
+    weak.on_subscription();
+    assert_eq!(listener.lock().unwrap().subscriptions, 1);
+
+    drop(listener);
+    assert!(weak.is_expired());
+    weak.on_subscription();
+}
+
+#[tokio::test]
+async fn dropped_client_listeners_are_removed() {
+    let server = MockServer::start(|_| Vec::new()).await;
+    let client = server.client();
+    let kept = Arc::new(StatusRecorder::default());
+    let dropped = Arc::new(StatusRecorder::default());
+    client.add_weak_listener(&kept);
+    client.add_weak_listener(&dropped);
+    assert_eq!(client.get_listeners().len(), 2);
+
+    client.connect().await.unwrap();
+    wait_until(|| {
+        dropped
+            .0
+            .lock()
+            .unwrap()
+            .iter()
+            .any(|status| status.starts_with("CONNECTED:"))
+    })
+    .await;
+    drop(dropped);
+    client.disconnect().await;
+
+    wait_until(|| client.get_listeners().len() == 1).await;
+    wait_until(|| kept.0.lock().unwrap().last().map(String::as_str) == Some("DISCONNECTED")).await;
+}
+
+#[tokio::test]
+async fn dropped_subscription_listeners_are_removed() {
+    let server = MockServer::start(|request| {
+        if request.contains("LS_op=add") {
+            vec!["SUBOK,1,1,1".to_string()]
+        } else {
+            Vec::new()
+        }
+    })
+    .await;
+    let client = server.client();
+    let listener = Arc::new(Mutex::new(SubscriptionCounter::default()));
+    let mut subscription =
+        Subscription::new(SubscriptionMode::Merge, Some(["item1"]), Some(["price"])).unwrap();
+    subscription.add_weak_listener(&listener);
+    let token = client.subscribe(subscription);
+    client.connect().await.unwrap();
+    wait_until(|| listener.lock().unwrap().subscriptions == 1).await;
+
+    drop(listener);
+    let listeners = || {
+        client
+            .with_subscription(token, |subscription| subscription.get_listeners().len())
+            .unwrap()
+    };
+    assert_eq!(listeners(), 1);
+    client.disconnect().await;
+    wait_until(|| listeners() == 0).await;
+}